@@ -0,0 +1,107 @@
+//! `vcf-tools compare`
+
+use crate::io::RecordReader;
+use anyhow::Result;
+use clap::Args;
+use std::process::ExitCode;
+use vcf_parser::{
+    compare_callsets, CompareOptions, CompareResult, VcfHeader, VcfParser, VcfRecord,
+};
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// First callset (A), or `-` for stdin
+    a: String,
+
+    /// Second callset (B)
+    b: String,
+
+    /// Only compare records with FILTER = PASS
+    #[arg(long)]
+    pass_only: bool,
+
+    /// Treat `chr1` and `1` as the same chromosome
+    #[arg(long)]
+    strip_chr: bool,
+
+    /// List field-level differences for up to N shared variants whose
+    /// records differ
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    diffs: usize,
+
+    /// Print JSON instead of a summary
+    #[arg(long)]
+    json: bool,
+}
+
+pub fn run(args: CompareArgs) -> Result<ExitCode> {
+    // INFO is only needed to diff records
+    let parse_info = args.diffs > 0;
+    let (header_a, records_a) = read_callset(&args.a, parse_info)?;
+    let (header_b, records_b) = read_callset(&args.b, parse_info)?;
+
+    let options = CompareOptions {
+        strip_chr_prefix: args.strip_chr,
+        pass_only: args.pass_only,
+        max_diffs: args.diffs,
+        ..CompareOptions::default()
+    };
+    let result = compare_callsets(&header_a, &records_a, &header_b, &records_b, &options);
+
+    if args.json {
+        let mut json = serde_json::to_value(&result)?;
+        json["genotype_concordance"] = result.genotype_concordance().into();
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        print_summary(&args, &result);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Header and records of a callset, with INFO only if `parse_info`
+fn read_callset(path: &str, parse_info: bool) -> Result<(VcfHeader, Vec<VcfRecord>)> {
+    let mut parser = VcfParser::new();
+    parser.parse_info = parse_info;
+    let reader = RecordReader::open(path, parser)?;
+    let header = reader.header().clone();
+    let records = reader
+        .map(|record| record.map(|(_, record)| record))
+        .collect::<Result<_>>()?;
+    Ok((header, records))
+}
+
+fn print_summary(args: &CompareArgs, result: &CompareResult) {
+    println!("A: {}", args.a);
+    println!("B: {}", args.b);
+    println!("{:<24}{:>10}", "Shared", result.shared);
+    println!("{:<24}{:>10}", "Only in A", result.only_a);
+    println!("{:<24}{:>10}", "Only in B", result.only_b);
+
+    let compared = result.concordant_genotypes + result.discordant_genotypes;
+    match result.genotype_concordance() {
+        Some(concordance) => println!(
+            "{:<24}{:>10.4} ({} of {})",
+            "Genotype concordance", concordance, result.concordant_genotypes, compared
+        ),
+        None => println!("{:<24}{:>10}", "Genotype concordance", "-"),
+    }
+
+    if !result.discordant.is_empty() {
+        println!();
+        println!("Genotype-discordant variants:");
+        for key in &result.discordant {
+            println!("  {}", key);
+        }
+    }
+
+    if !result.diffs.is_empty() {
+        println!();
+        println!("Field differences (A -> B):");
+        for diff in &result.diffs {
+            println!("  {}", diff.key);
+            for field in &diff.fields {
+                println!("    {}: {} -> {}", field.field, field.a, field.b);
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! `vcf-tools filter`
+
+use crate::io::{Output, RecordReader, STDIO};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::process::ExitCode;
+use vcf_parser::view::variant_type_from_label;
+use vcf_parser::{
+    filter_expr, CompiledFilter, FilterStatus, Region, VariantType, VcfError, VcfHeader, VcfParser,
+    VcfRecord, VcfWriter,
+};
+
+#[derive(Args)]
+pub struct FilterArgs {
+    /// VCF file, or `-` for stdin
+    file: String,
+
+    /// Only records in this region, e.g. `chr1` or `chr1:1000-2000`
+    #[arg(long, value_parser = parse_region)]
+    region: Option<Region>,
+
+    /// Only records with QUAL at least this; records without QUAL are dropped
+    #[arg(long)]
+    min_qual: Option<f64>,
+
+    /// Only records with FILTER = PASS
+    #[arg(long)]
+    pass_only: bool,
+
+    /// Only variants of this type: snp, ins, del, complex or other
+    #[arg(long = "type", value_parser = parse_variant_type)]
+    variant_type: Option<VariantType>,
+
+    /// Only records matching this expression, e.g.
+    /// `QUAL>30 && INFO/DP>=10 && TYPE="snp" && GT[0]="het"`
+    #[arg(short = 'i', long)]
+    include: Option<String>,
+
+    /// Output file, or `-` for stdout; gzipped if it ends in `.gz`
+    #[arg(short, long, default_value = STDIO)]
+    output: String,
+
+    /// Gzip the output whatever its name (e.g. for stdout)
+    #[arg(short = 'z', long)]
+    gzip: bool,
+}
+
+impl FilterArgs {
+    fn keep(&self, record: &VcfRecord) -> bool {
+        self.region
+            .as_ref()
+            .is_none_or(|region| region.contains_record(record))
+            && self
+                .min_qual
+                .is_none_or(|min| record.qual.is_some_and(|qual| qual >= min))
+            && (!self.pass_only || record.filter == FilterStatus::Pass)
+            && self.variant_type.is_none_or(|t| record.variant_type() == t)
+    }
+}
+
+pub fn run(args: FilterArgs) -> Result<ExitCode> {
+    // Kept records are written with QUAL and INFO floats as they were read
+    let mut parser = VcfParser::new();
+    parser.keep_float_text = true;
+    let records = RecordReader::open(&args.file, parser)?;
+    let header = records.header().clone();
+    let include = match &args.include {
+        Some(expr) => Some(compile_include(expr, &header)?),
+        None => None,
+    };
+
+    let mut writer = VcfWriter::new(Output::create(&args.output, args.gzip)?, &header);
+    writer.write_header()?;
+    for record in records {
+        let (_, record) = record?;
+        if args.keep(&record) && include.as_ref().is_none_or(|f| f.matches(&record)) {
+            writer.write_record(&record)?;
+        }
+    }
+    writer.into_inner()?.finish()?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_region(value: &str) -> Result<Region> {
+    Ok(Region::parse(value)?)
+}
+
+fn parse_variant_type(value: &str) -> Result<VariantType> {
+    variant_type_from_label(value)
+        .ok_or_else(|| anyhow!("expected snp, ins, del, complex or other"))
+}
+
+/// Compile `--include`, pointing at the offending column on error
+fn compile_include(expr: &str, header: &VcfHeader) -> Result<CompiledFilter> {
+    filter_expr::compile(expr, header).map_err(|e| match e {
+        VcfError::InvalidFilterExpression { column, .. } => {
+            anyhow!("{}\n  {}\n  {:>column$}", e, expr, "^")
+        }
+        e => e.into(),
+    })
+}
@@ -0,0 +1,156 @@
+//! Input and Output
+//!
+//! Opening inputs and outputs by path, with `-` for stdin / stdout and
+//! gzip handled on both sides.
+
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Lines, Write};
+use vcf_parser::error::ParseWarning;
+use vcf_parser::{gzip, VcfHeader, VcfParser, VcfPushParser, VcfRecord};
+
+/// Path meaning stdin for inputs and stdout for outputs
+pub const STDIO: &str = "-";
+
+/// Open `path` (or stdin for `-`) for reading, decompressing gzip input
+pub fn open_input(path: &str) -> Result<Box<dyn BufRead + Send>> {
+    let reader = if path == STDIO {
+        gzip::auto_decompress(BufReader::new(io::stdin()))
+    } else {
+        gzip::open_vcf(path)
+    };
+    reader.with_context(|| format!("cannot open {}", path))
+}
+
+/// Destination for VCF output; call [`Output::finish`] when done so a
+/// gzip stream gets its trailer
+pub enum Output {
+    Plain(BufWriter<Box<dyn Write>>),
+    Gzip(GzEncoder<BufWriter<Box<dyn Write>>>),
+}
+
+impl Output {
+    /// Create `path` (or stdout for `-`); output is gzipped when `gzip` is
+    /// set or the path ends in `.gz`
+    pub fn create(path: &str, gzip: bool) -> Result<Self> {
+        let out: Box<dyn Write> = if path == STDIO {
+            Box::new(io::stdout())
+        } else {
+            Box::new(File::create(path).with_context(|| format!("cannot create {}", path))?)
+        };
+        let out = BufWriter::new(out);
+
+        if gzip || path.ends_with(".gz") {
+            Ok(Output::Gzip(GzEncoder::new(out, Compression::default())))
+        } else {
+            Ok(Output::Plain(out))
+        }
+    }
+
+    /// Flush everything written, finishing the gzip stream if any
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Output::Plain(mut out) => out.flush(),
+            Output::Gzip(out) => out.finish()?.flush(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Plain(out) => out.write(buf),
+            Output::Gzip(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Plain(out) => out.flush(),
+            Output::Gzip(out) => out.flush(),
+        }
+    }
+}
+
+/// Streaming record reader over a file or stdin
+///
+/// The header is read when the reader is opened; iterating yields each
+/// record with its 1-based line number.
+pub struct RecordReader {
+    path: String,
+    lines: Lines<Box<dyn BufRead + Send>>,
+    stream: VcfPushParser,
+    line: usize,
+}
+
+impl RecordReader {
+    /// Open `path` and read its header, parsing with `parser`'s options
+    pub fn open(path: &str, parser: VcfParser) -> Result<Self> {
+        let mut reader = Self {
+            path: path.to_string(),
+            lines: open_input(path)?.lines(),
+            stream: VcfPushParser::new(parser),
+            line: 0,
+        };
+
+        while !reader.stream.header_complete() {
+            let Some(line) = reader.next_line()? else {
+                break;
+            };
+            reader
+                .stream
+                .push_line(&line)
+                .with_context(|| reader.context())?;
+        }
+        reader.stream.finish().with_context(|| reader.context())?;
+
+        Ok(reader)
+    }
+
+    pub fn header(&self) -> &VcfHeader {
+        self.stream.header()
+    }
+
+    /// Invalid records skipped so far, when the parser skips them
+    pub fn warnings(&self) -> &[ParseWarning] {
+        self.stream.warnings()
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>> {
+        match self.lines.next() {
+            Some(line) => {
+                self.line += 1;
+                let line = line
+                    .map_err(gzip::gzip_error)
+                    .with_context(|| self.context())?;
+                Ok(Some(line))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn context(&self) -> String {
+        format!("{}: line {}", self.path, self.line)
+    }
+}
+
+impl Iterator for RecordReader {
+    type Item = Result<(usize, VcfRecord)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.next_line() {
+                Ok(Some(line)) => line,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+            match self.stream.push_line(&line) {
+                Ok(Some(record)) => return Some(Ok((self.line, record))),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(anyhow::Error::new(e).context(self.path.clone()))),
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! vcf-tools
+//!
+//! Command-line front end for the VCF parser: statistics, filtering,
+//! validation, callset comparison and runs of homozygosity. Every input
+//! may be plain or gzip/BGZF-compressed, and `-` reads stdin or writes
+//! stdout.
+
+mod compare;
+mod filter;
+mod io;
+mod roh;
+mod stats;
+mod validate;
+
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "vcf-tools",
+    version,
+    about = "Statistics, filtering and validation for VCF files"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Summary statistics, streamed without keeping records in memory
+    Stats(stats::StatsArgs),
+    /// Write the records that pass every given filter
+    Filter(filter::FilterArgs),
+    /// Check a file strictly; exits non-zero if any issue is found
+    Validate(validate::ValidateArgs),
+    /// Concordance between two callsets
+    Compare(compare::CompareArgs),
+    /// Runs of homozygosity in one sample, as BED
+    Roh(roh::RohArgs),
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Stats(args) => stats::run(args),
+        Command::Filter(args) => filter::run(args),
+        Command::Validate(args) => validate::run(args),
+        Command::Compare(args) => compare::run(args),
+        Command::Roh(args) => roh::run(args),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("vcf-tools: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
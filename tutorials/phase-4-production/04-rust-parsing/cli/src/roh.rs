@@ -0,0 +1,73 @@
+//! `vcf-tools roh`
+
+use crate::io::{Output, RecordReader, STDIO};
+use anyhow::{bail, Result};
+use clap::Args;
+use std::io::Write;
+use std::process::ExitCode;
+use vcf_parser::{detect_roh, RohOptions, VcfParser, VcfRecord};
+
+#[derive(Args)]
+pub struct RohArgs {
+    /// VCF file, or `-` for stdin
+    file: String,
+
+    /// Sample to scan
+    #[arg(short, long)]
+    sample: String,
+
+    /// Variants per sliding window
+    #[arg(long, default_value_t = 50)]
+    window: usize,
+
+    /// Het genotypes allowed in a homozygous window
+    #[arg(long, default_value_t = 1)]
+    max_hets: usize,
+
+    /// Missing genotypes allowed in a homozygous window
+    #[arg(long, default_value_t = 5)]
+    max_missing: usize,
+
+    /// Shortest segment reported, in bp
+    #[arg(long, default_value_t = 1_000_000)]
+    min_length: u64,
+
+    /// Fewest variants in a reported segment
+    #[arg(long, default_value_t = 100)]
+    min_variants: usize,
+
+    /// BED output file, or `-` for stdout; gzipped if it ends in `.gz`
+    #[arg(short, long, default_value = STDIO)]
+    output: String,
+
+    /// Gzip the output whatever its name (e.g. for stdout)
+    #[arg(short = 'z', long)]
+    gzip: bool,
+}
+
+pub fn run(args: RohArgs) -> Result<ExitCode> {
+    let mut parser = VcfParser::new();
+    parser.parse_info = false;
+    let reader = RecordReader::open(&args.file, parser)?;
+    if !reader.header().samples.contains(&args.sample) {
+        bail!("{}: no sample named {}", args.file, args.sample);
+    }
+    let records: Vec<VcfRecord> = reader
+        .map(|record| record.map(|(_, record)| record))
+        .collect::<Result<_>>()?;
+
+    let options = RohOptions {
+        window_size: args.window,
+        max_hets: args.max_hets,
+        max_missing: args.max_missing,
+        min_length: args.min_length,
+        min_variants: args.min_variants,
+    };
+    let mut out = Output::create(&args.output, args.gzip)?;
+    for segment in detect_roh(&records, &args.sample, options) {
+        writeln!(out, "{}", segment.to_bed(&args.sample))?;
+    }
+    out.finish()?;
+
+    Ok(ExitCode::SUCCESS)
+}
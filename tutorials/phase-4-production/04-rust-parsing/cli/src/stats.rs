@@ -0,0 +1,84 @@
+//! `vcf-tools stats`
+
+use crate::io::RecordReader;
+use anyhow::Result;
+use clap::Args;
+use std::process::ExitCode;
+use vcf_parser::{detect_assembly, Assembly, AssemblyGuess, VcfParser, VcfStats};
+
+#[derive(Args)]
+pub struct StatsArgs {
+    /// VCF file, or `-` for stdin
+    file: String,
+
+    /// Print JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+pub fn run(args: StatsArgs) -> Result<ExitCode> {
+    let reader = RecordReader::open(&args.file, VcfParser::fast())?;
+    let assembly = detect_assembly(reader.header());
+    let mut stats = VcfStats::new();
+    for record in reader {
+        stats.update(&record?.1);
+    }
+
+    if args.json {
+        let mut json = serde_json::to_value(&stats)?;
+        json["ts_tv_ratio"] = stats.ts_tv_ratio().into();
+        json["assembly"] = serde_json::to_value(&assembly)?;
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        print_table(&stats, &assembly);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_table(stats: &VcfStats, assembly: &AssemblyGuess) {
+    let ts_tv = stats
+        .ts_tv_ratio()
+        .map_or_else(|| "-".to_string(), |r| format!("{:.2}", r));
+    let assembly = match assembly.assembly {
+        Assembly::Unknown => "-".to_string(),
+        name => format!("{} ({:.0}%)", name, assembly.confidence * 100.0),
+    };
+    let rows = [
+        ("Records", stats.total_records.to_string()),
+        ("SNPs", stats.snps.to_string()),
+        ("Insertions", stats.insertions.to_string()),
+        ("Deletions", stats.deletions.to_string()),
+        ("Complex", stats.complex.to_string()),
+        ("PASS", stats.passed_filter.to_string()),
+        ("Failed filter", stats.failed_filter.to_string()),
+        ("Transitions", stats.transitions.to_string()),
+        ("Transversions", stats.transversions.to_string()),
+        ("Ts/Tv", ts_tv),
+        ("Assembly", assembly),
+    ];
+    for (label, value) in rows {
+        println!("{:<16}{:>12}", label, value);
+    }
+
+    if stats.per_chromosome.is_empty() {
+        return;
+    }
+    let width = stats
+        .per_chromosome
+        .iter()
+        .map(|c| c.chrom.len())
+        .max()
+        .unwrap_or(0)
+        .max("Chromosome".len());
+    println!();
+    println!(
+        "{:<width$}  {:>10}  {:>10}  {:>10}",
+        "Chromosome", "Records", "SNPs", "Indels"
+    );
+    for chrom in &stats.per_chromosome {
+        println!(
+            "{:<width$}  {:>10}  {:>10}  {:>10}",
+            chrom.chrom, chrom.records, chrom.snps, chrom.indels
+        );
+    }
+}
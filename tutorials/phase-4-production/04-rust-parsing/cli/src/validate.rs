@@ -0,0 +1,385 @@
+//! `vcf-tools validate`
+//!
+//! On top of what the parser rejects, strict mode checks that INFO keys
+//! and FILTER ids are declared in the header, that declared INFO and
+//! FORMAT fields hold as many values as their `Number` calls for, and
+//! that records are sorted by position within each contiguous block of a
+//! contig. When the header
+//! has `##contig` lines, records must also lie on a declared contig and
+//! within its length; headers without any are common enough that their
+//! absence is not an issue, and `--infer-contigs` prints lines to add.
+//!
+//! With `--reference`, REF alleles are also checked against a FASTA.
+//! Mismatches are warnings, since a few are common in real callsets, but
+//! a mismatch rate high enough to suggest the wrong genome build is an
+//! issue in strict mode.
+
+use crate::io::RecordReader;
+use anyhow::{Context, Result};
+use clap::Args;
+use std::collections::{HashMap, HashSet};
+use std::process::ExitCode;
+use vcf_parser::ref_check::{RefValidationReport, DEFAULT_WRONG_BUILD_RATE};
+use vcf_parser::writer::contig_line;
+use vcf_parser::{
+    expected_value_count, FastaIndex, FilterStatus, InfoValue, RefValidator, ReservoirSampler,
+    VcfHeader, VcfParser, VcfRecord,
+};
+
+/// Seed for `--ref-sample`, so reruns check the same records
+const REF_SAMPLE_SEED: u64 = 0x5EED;
+
+#[derive(Args)]
+pub struct ValidateArgs {
+    /// VCF file, or `-` for stdin
+    file: String,
+
+    /// Only report issues the parser itself finds
+    #[arg(long)]
+    lenient: bool,
+
+    /// Print `##contig` lines for every contig seen, with lengths estimated
+    /// from the records where the header declares none
+    #[arg(long)]
+    infer_contigs: bool,
+
+    /// Check REF alleles against this FASTA, using its `.fai` index when
+    /// there is one
+    #[arg(long, value_name = "FASTA")]
+    reference: Option<String>,
+
+    /// Check the REF of this many randomly chosen records instead of all
+    #[arg(long, value_name = "N", requires = "reference")]
+    ref_sample: Option<usize>,
+}
+
+/// Problem found at a line of the input
+struct Issue {
+    line: usize,
+    message: String,
+    /// Printed but not counted against the file
+    warning: bool,
+}
+
+/// Header-consistency and ordering checks run on each record
+struct StrictChecks {
+    info_ids: HashSet<String>,
+    filter_ids: HashSet<String>,
+    /// `Number` of each declared INFO and FORMAT field
+    info_numbers: HashMap<String, String>,
+    format_numbers: HashMap<String, String>,
+    /// Declared contig lengths; empty when the header declares no contigs
+    contig_lengths: HashMap<String, Option<u64>>,
+    /// Undeclared contigs already reported
+    undeclared_contigs: HashSet<String>,
+    /// Contigs whose block of records has ended
+    finished_contigs: HashSet<String>,
+    previous: Option<(String, u64)>,
+}
+
+impl StrictChecks {
+    fn new(header: &VcfHeader) -> Self {
+        Self {
+            info_ids: header.info_fields.iter().map(|f| f.id.clone()).collect(),
+            filter_ids: header.filters.iter().map(|f| f.id.clone()).collect(),
+            info_numbers: header
+                .info_fields
+                .iter()
+                .map(|f| (f.id.clone(), f.number.clone()))
+                .collect(),
+            format_numbers: header
+                .format_fields
+                .iter()
+                .map(|f| (f.id.clone(), f.number.clone()))
+                .collect(),
+            contig_lengths: header
+                .contigs
+                .iter()
+                .map(|c| (c.id.clone(), c.length))
+                .collect(),
+            undeclared_contigs: HashSet::new(),
+            finished_contigs: HashSet::new(),
+            previous: None,
+        }
+    }
+
+    fn check(&mut self, line: usize, record: &VcfRecord, issues: &mut Vec<Issue>) {
+        let mut issue = |message: String| {
+            issues.push(Issue {
+                line,
+                message,
+                warning: false,
+            })
+        };
+
+        let mut keys: Vec<&str> = record
+            .info
+            .keys()
+            .map(|key| &**key)
+            .filter(|key| !self.info_ids.contains(*key))
+            .collect();
+        keys.sort();
+        for key in keys {
+            issue(format!("INFO key '{}' is not declared in the header", key));
+        }
+
+        if let FilterStatus::Failed(filters) = &record.filter {
+            for filter in filters.iter().filter(|f| !self.filter_ids.contains(*f)) {
+                issue(format!("FILTER '{}' is not declared in the header", filter));
+            }
+        }
+
+        for message in self.value_count_issues(record) {
+            issue(message);
+        }
+
+        if !self.contig_lengths.is_empty() {
+            match self.contig_lengths.get(&record.chrom) {
+                Some(Some(length)) if record.pos > *length => issue(format!(
+                    "position {} is beyond the end of {} (length {})",
+                    record.pos, record.chrom, length
+                )),
+                Some(_) => {}
+                None => {
+                    if self.undeclared_contigs.insert(record.chrom.clone()) {
+                        issue(format!(
+                            "contig {} is not declared in the header",
+                            record.chrom
+                        ));
+                    }
+                }
+            }
+        }
+
+        match &self.previous {
+            Some((chrom, pos)) if *chrom == record.chrom => {
+                if record.pos < *pos {
+                    issue(format!(
+                        "position {} comes after {} on {}; records are not sorted",
+                        record.pos, pos, chrom
+                    ));
+                }
+            }
+            previous => {
+                if let Some((chrom, _)) = previous {
+                    self.finished_contigs.insert(chrom.clone());
+                }
+                if self.finished_contigs.contains(&record.chrom) {
+                    issue(format!(
+                        "records for {} are not contiguous; records are not sorted",
+                        record.chrom
+                    ));
+                }
+            }
+        }
+        self.previous = Some((record.chrom.clone(), record.pos));
+    }
+
+    /// INFO and FORMAT fields whose number of values does not match their
+    /// declared `Number`; a lone `.` is a valid missing value for any
+    /// `Number`. INFO `G` fields are taken as diploid, and each FORMAT
+    /// field is reported once per record, for its first offending sample.
+    fn value_count_issues(&self, record: &VcfRecord) -> Vec<String> {
+        let n_alts = record.alternate.len();
+        let mut messages = Vec::new();
+
+        let mut keys: Vec<&str> = record.info.keys().map(|key| &**key).collect();
+        keys.sort();
+        for key in keys {
+            let Some(number) = self.info_numbers.get(key) else {
+                continue;
+            };
+            let count = match &record.info[key] {
+                InfoValue::Flag => 0,
+                InfoValue::String(s) if s == "." => continue,
+                InfoValue::IntegerArray(values) => values.len(),
+                InfoValue::FloatArray(values) => values.len(),
+                InfoValue::StringArray(values) => values.len(),
+                _ => 1,
+            };
+            if let Some(expected) = expected_value_count(number, n_alts, 2) {
+                if count != expected {
+                    messages.push(format!(
+                        "INFO '{}' has {} value{}, expected {} (Number={})",
+                        key,
+                        count,
+                        plural(count),
+                        expected,
+                        number
+                    ));
+                }
+            }
+        }
+
+        let mut keys: Vec<&str> = record
+            .samples
+            .iter()
+            .flat_map(|s| s.fields.keys().map(String::as_str))
+            .collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            let Some(number) = self.format_numbers.get(key) else {
+                continue;
+            };
+            let mismatch = record.samples.iter().find_map(|sample| {
+                let value = sample.fields.get(key).filter(|v| *v != ".")?;
+                let ploidy = sample.genotype.as_ref().map_or(2, |gt| gt.alleles.len());
+                let expected = expected_value_count(number, n_alts, ploidy)?;
+                let count = value.split(',').count();
+                (count != expected).then_some((&sample.name, count, expected))
+            });
+            if let Some((sample, count, expected)) = mismatch {
+                messages.push(format!(
+                    "FORMAT '{}' of sample {} has {} value{}, expected {} (Number={})",
+                    key,
+                    sample,
+                    count,
+                    plural(count),
+                    expected,
+                    number
+                ));
+            }
+        }
+
+        messages
+    }
+}
+
+fn plural(count: usize) -> &'static str {
+    if count == 1 {
+        ""
+    } else {
+        "s"
+    }
+}
+
+pub fn run(args: ValidateArgs) -> Result<ExitCode> {
+    let mut parser = VcfParser::new();
+    parser.skip_invalid = true;
+    let mut reader = RecordReader::open(&args.file, parser)?;
+
+    let fasta = match &args.reference {
+        Some(path) => {
+            Some(FastaIndex::open(path).with_context(|| format!("cannot read {}", path))?)
+        }
+        None => None,
+    };
+    let mut ref_validator = fasta
+        .as_ref()
+        .map(|fasta| RefValidator::new(fasta, DEFAULT_WRONG_BUILD_RATE));
+    let mut ref_sample = args
+        .ref_sample
+        .map(|n| ReservoirSampler::new(n, REF_SAMPLE_SEED));
+
+    let mut checks = StrictChecks::new(reader.header());
+    let mut issues = Vec::new();
+    let mut records = 0;
+    // Last record seen on each contig, in first-seen order, with the
+    // largest position
+    let mut contig_ends: Vec<VcfRecord> = Vec::new();
+    let mut contig_index: HashMap<String, usize> = HashMap::new();
+    for record in &mut reader {
+        let (line, record) = record?;
+        records += 1;
+        if !args.lenient {
+            checks.check(line, &record, &mut issues);
+        }
+        match (&mut ref_sample, &mut ref_validator) {
+            (Some(sample), _) => sample.offer((line, record.clone())),
+            (None, Some(validator)) => validator.check(line, &record)?,
+            (None, None) => {}
+        }
+        if args.infer_contigs {
+            match contig_index.get(&record.chrom) {
+                Some(&i) => contig_ends[i].pos = contig_ends[i].pos.max(record.pos),
+                None => {
+                    contig_index.insert(record.chrom.clone(), contig_ends.len());
+                    contig_ends.push(VcfRecord::new(&record.chrom, record.pos, "N", vec![]));
+                }
+            }
+        }
+    }
+
+    issues.extend(reader.warnings().iter().map(|w| Issue {
+        line: w.line,
+        message: w.message.clone(),
+        warning: false,
+    }));
+
+    let ref_report = match ref_validator {
+        Some(mut validator) => {
+            for (line, record) in ref_sample
+                .map(ReservoirSampler::into_vec)
+                .unwrap_or_default()
+            {
+                validator.check(line, &record)?;
+            }
+            let report = validator.finish();
+            issues.extend(ref_warnings(&report));
+            Some(report)
+        }
+        None => None,
+    };
+    issues.sort_by_key(|issue| issue.line);
+
+    for issue in &issues {
+        let kind = if issue.warning { "warning: " } else { "" };
+        println!("{}:{}: {}{}", args.file, issue.line, kind, issue.message);
+    }
+    if args.infer_contigs {
+        let mut header = reader.header().clone();
+        header.build_contigs_from_records(&contig_ends);
+        for contig in &header.contigs {
+            println!("{}", contig_line(contig));
+        }
+    }
+    let mut n_issues = issues.iter().filter(|issue| !issue.warning).count();
+    if let Some(report) = &ref_report {
+        let rate = report.mismatch_rate().unwrap_or(0.0);
+        println!(
+            "{}: REF checked for {} records, {} mismatched ({:.1}%), {} on sequences \
+             missing from the reference, {} symbolic",
+            args.file,
+            report.checked,
+            report.mismatched.len(),
+            rate * 100.0,
+            report.skipped_no_contig,
+            report.skipped_symbolic
+        );
+        if report.likely_wrong_build {
+            println!(
+                "{}: {:.1}% of REF alleles differ from the reference; the file is \
+                 likely on another genome build",
+                args.file,
+                rate * 100.0
+            );
+            n_issues += usize::from(!args.lenient);
+        }
+    }
+    println!(
+        "{}: {} records, {} issue{}",
+        args.file,
+        records,
+        n_issues,
+        plural(n_issues)
+    );
+
+    Ok(if n_issues == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// A warning for each REF that differs from the reference
+fn ref_warnings(report: &RefValidationReport) -> impl Iterator<Item = Issue> + '_ {
+    report.mismatched.iter().map(|m| Issue {
+        line: m.index,
+        message: format!(
+            "REF {} at {}:{} does not match the reference ({})",
+            m.vcf_ref, m.chrom, m.pos, m.genome_ref
+        ),
+        warning: true,
+    })
+}
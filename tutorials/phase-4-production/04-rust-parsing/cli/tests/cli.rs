@@ -0,0 +1,535 @@
+//! Integration tests driving the `vcf-tools` binary
+
+use assert_cmd::Command;
+use flate2::read::MultiGzDecoder;
+use predicates::prelude::*;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../tests/data")
+        .join(name)
+}
+
+fn vcf_tools() -> Command {
+    Command::cargo_bin("vcf-tools").unwrap()
+}
+
+fn stdout_of(command: &mut Command) -> String {
+    let output = command.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+/// Data lines of VCF text as (CHROM, POS)
+fn positions(vcf: &str) -> Vec<(String, u64)> {
+    vcf.lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| {
+            let mut fields = line.split('\t');
+            let chrom = fields.next().unwrap().to_string();
+            (chrom, fields.next().unwrap().parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn test_stats_table() {
+    vcf_tools()
+        .arg("stats")
+        .arg(fixture("shared-records.vcf"))
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Records\s+5\n").unwrap())
+        .stdout(predicate::str::is_match(r"Ts/Tv\s+2\.00\n").unwrap())
+        .stdout(predicate::str::is_match(r"chrX\s+1\s+0\s+1\n").unwrap());
+}
+
+#[test]
+fn test_stats_json_from_gzip_stdin() {
+    let gz = std::fs::read(fixture("indexed.vcf.gz")).unwrap();
+    let json = stdout_of(vcf_tools().args(["stats", "--json", "-"]).write_stdin(gz));
+    let stats: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(
+        stats["chromosomes"],
+        serde_json::json!(["chr1", "chr2", "chrX"])
+    );
+    assert!(stats["total_records"].as_u64().unwrap() > 0);
+    assert!(stats["ts_tv_ratio"].is_f64());
+}
+
+#[test]
+fn test_stats_reports_assembly() {
+    let vcf = "##fileformat=VCFv4.2\n\
+               ##reference=GRCh38\n\
+               ##contig=<ID=chr1,length=248956422>\n\
+               #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+               chr1\t100\t.\tA\tG\t30\tPASS\t.\n";
+
+    vcf_tools()
+        .args(["stats", "-"])
+        .write_stdin(vcf)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Assembly\s+GRCh38 \(67%\)\n").unwrap());
+
+    let json = stdout_of(vcf_tools().args(["stats", "--json", "-"]).write_stdin(vcf));
+    let stats: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(stats["assembly"]["assembly"], "GRCh38");
+    assert_eq!(stats["assembly"]["evidence"].as_array().unwrap().len(), 2);
+
+    // No hints at all
+    vcf_tools()
+        .arg("stats")
+        .arg(fixture("shared-records.vcf"))
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Assembly\s+-\n").unwrap());
+}
+
+#[test]
+fn test_filter_to_stdout() {
+    let vcf = stdout_of(
+        vcf_tools()
+            .arg("filter")
+            .arg(fixture("shared-records.vcf"))
+            .args(["--region", "chr1:1-1000", "--min-qual", "30", "--pass-only"])
+            .args(["--type", "snp"]),
+    );
+
+    assert!(vcf.starts_with("##fileformat=VCFv4.2\n"));
+    assert!(
+        vcf.contains("#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tSAMPLE1\tSAMPLE2\n")
+    );
+    assert_eq!(positions(&vcf), [("chr1".to_string(), 100)]);
+}
+
+#[test]
+fn test_filter_keeps_float_text() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("floats.vcf");
+    let line = "chr1\t100\t.\tA\tG\t30.10\tPASS\tAF=0.250;MQ=60.0\n";
+    std::fs::write(
+        &input,
+        format!(
+            "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n{}",
+            line
+        ),
+    )
+    .unwrap();
+
+    let vcf = stdout_of(vcf_tools().arg("filter").arg(&input));
+    assert!(vcf.ends_with(line), "{}", vcf);
+}
+
+#[test]
+fn test_filter_gzip_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("deletions.vcf.gz");
+
+    vcf_tools()
+        .arg("filter")
+        .arg(fixture("shared-records.vcf"))
+        .args(["--type", "del", "-o"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("");
+
+    let mut vcf = String::new();
+    MultiGzDecoder::new(std::fs::File::open(&out).unwrap())
+        .read_to_string(&mut vcf)
+        .unwrap();
+    assert_eq!(
+        positions(&vcf),
+        [("chr1".to_string(), 200), ("chrX".to_string(), 500)]
+    );
+}
+
+#[test]
+fn test_filter_include_expression() {
+    let vcf = stdout_of(
+        vcf_tools()
+            .arg("filter")
+            .arg(fixture("shared-records.vcf"))
+            .args(["-i", "INFO/DP>=10 || GT[0]=\"het\" && TYPE=\"indel\""])
+            .args(["--pass-only"]),
+    );
+
+    assert_eq!(
+        positions(&vcf),
+        [("chr1".to_string(), 100), ("chr2".to_string(), 400)]
+    );
+}
+
+#[test]
+fn test_filter_include_reports_error_column() {
+    vcf_tools()
+        .arg("filter")
+        .arg(fixture("shared-records.vcf"))
+        .args(["--include", "QUAL>30 && INFO/XX>1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid filter expression at column 12: INFO/XX is not defined in the header\n  \
+             QUAL>30 && INFO/XX>1\n             ^\n",
+        ));
+}
+
+#[test]
+fn test_filter_rejects_unknown_type() {
+    vcf_tools()
+        .arg("filter")
+        .arg(fixture("shared-records.vcf"))
+        .args(["--type", "mnp"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "expected snp, ins, del, complex or other",
+        ));
+}
+
+#[test]
+fn test_validate_clean_file() {
+    vcf_tools()
+        .arg("validate")
+        .arg(fixture("shared-records.vcf"))
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("5 records, 0 issues\n"));
+}
+
+#[test]
+fn test_validate_reports_issues_with_line_numbers() {
+    let vcf = "##fileformat=VCFv4.2\n\
+               ##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">\n\
+               #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+               chr1\t200\t.\tA\tG\t30\tPASS\tDP=5\n\
+               chr1\t100\t.\tA\tG\t30\tlowQ\tXX=1\n\
+               chr1\tabc\t.\tA\tG\t30\tPASS\t.\n\
+               chr2\t10\t.\tA\tG\t30\tPASS\t.\n\
+               chr1\t300\t.\tA\tG\t30\tPASS\t.\n";
+
+    let output = vcf_tools()
+        .args(["validate", "-"])
+        .write_stdin(vcf)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(
+        lines,
+        [
+            "-:5: INFO key 'XX' is not declared in the header",
+            "-:5: FILTER 'lowQ' is not declared in the header",
+            "-:5: position 100 comes after 200 on chr1; records are not sorted",
+            "-:6: Invalid position 'abc' at line 6",
+            "-:8: records for chr1 are not contiguous; records are not sorted",
+            "-: 4 records, 5 issues",
+        ]
+    );
+}
+
+#[test]
+fn test_validate_checks_value_counts() {
+    let vcf = "##fileformat=VCFv4.2\n\
+               ##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele frequency\">\n\
+               ##INFO=<ID=DB,Number=0,Type=Flag,Description=\"dbSNP\">\n\
+               ##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">\n\
+               ##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">\n\
+               ##FORMAT=<ID=PL,Number=G,Type=Integer,Description=\"Likelihoods\">\n\
+               #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\n\
+               chr1\t100\t.\tA\tC,G\t30\tPASS\tAF=0.1,0.2;DB\tGT:AD:PL\t1/2:1,2,3:0,1,2,3,4,5\t1:.:0,1,2\n\
+               chr1\t200\t.\tA\tC,G\t30\tPASS\tAF=0.1\tGT:AD:PL\t0/1:1,2:0,1,2\t./.:1,2,3:.\n";
+
+    let output = vcf_tools()
+        .args(["validate", "-"])
+        .write_stdin(vcf)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    // Haploid S1 PL at 100 is right with 3 values, as is the missing AD
+    assert_eq!(
+        lines,
+        [
+            "-:9: INFO 'AF' has 1 value, expected 2 (Number=A)",
+            "-:9: FORMAT 'AD' of sample S1 has 2 values, expected 3 (Number=R)",
+            "-:9: FORMAT 'PL' of sample S1 has 3 values, expected 6 (Number=G)",
+            "-: 2 records, 3 issues",
+        ]
+    );
+}
+
+#[test]
+fn test_validate_checks_declared_contigs() {
+    let vcf = "##fileformat=VCFv4.2\n\
+               ##contig=<ID=chr1,length=1000>\n\
+               #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+               chr1\t999\t.\tA\tG\t30\tPASS\t.\n\
+               chr1\t1500\t.\tA\tG\t30\tPASS\t.\n\
+               chr2\t10\t.\tA\tG\t30\tPASS\t.\n\
+               chr2\t20\t.\tA\tG\t30\tPASS\t.\n";
+
+    let output = vcf_tools()
+        .args(["validate", "--infer-contigs", "-"])
+        .write_stdin(vcf)
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(
+        lines,
+        [
+            "-:5: position 1500 is beyond the end of chr1 (length 1000)",
+            "-:6: contig chr2 is not declared in the header",
+            "##contig=<ID=chr1,length=1000>",
+            "##contig=<ID=chr2,length=20>",
+            "-: 4 records, 2 issues",
+        ]
+    );
+
+    // Lenient mode skips the contig checks
+    vcf_tools()
+        .args(["validate", "--lenient", "-"])
+        .write_stdin(vcf)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_validate_infers_contigs_when_header_has_none() {
+    vcf_tools()
+        .args(["validate", "--infer-contigs"])
+        .arg(fixture("shared-records.vcf"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "##contig=<ID=chr1,length=200>\n\
+             ##contig=<ID=chr2,length=400>\n\
+             ##contig=<ID=chrX,length=500>\n",
+        ));
+}
+
+/// Tiny reference for `--reference`, and VCF text with a record per
+/// listed (POS, REF) on its chr1
+fn reference_fixture(dir: &Path) -> PathBuf {
+    let fasta = dir.join("ref.fa");
+    std::fs::write(&fasta, ">chr1 test\nACGTACGTAC\nGGCCTTAAGG\n").unwrap();
+    fasta
+}
+
+fn ref_vcf(records: &[(u64, &str)]) -> String {
+    let mut vcf =
+        String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+    for (pos, reference) in records {
+        vcf += &format!("chr1\t{}\t.\t{}\tN\t30\tPASS\t.\n", pos, reference);
+    }
+    vcf
+}
+
+#[test]
+fn test_validate_reference_warns_on_mismatch() {
+    let dir = tempfile::tempdir().unwrap();
+    let fasta = reference_fixture(dir.path());
+    let bases = "ACGTACGTACGGCCTTAAGG";
+    let mut records: Vec<(u64, &str)> = (1..=10)
+        .map(|pos| (pos, &bases[pos as usize - 1..pos as usize]))
+        .collect();
+    // The reference has G at 12, and CCTT at 13
+    records.extend([(12, "T"), (13, "cctt")]);
+    let mut vcf = ref_vcf(&records);
+    vcf += "chr2\t5\t.\tA\tG\t30\tPASS\t.\nchr1\t18\t.\tA\t<DEL>\t30\tPASS\t.\n";
+
+    let output = stdout_of(
+        vcf_tools()
+            .args(["validate", "--lenient", "--reference"])
+            .arg(&fasta)
+            .arg("-")
+            .write_stdin(vcf),
+    );
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "-:13: warning: REF T at chr1:12 does not match the reference (G)",
+            "-: REF checked for 12 records, 1 mismatched (8.3%), 1 on sequences \
+             missing from the reference, 1 symbolic",
+            "-: 14 records, 0 issues",
+        ]
+    );
+}
+
+#[test]
+fn test_validate_reference_flags_wrong_build() {
+    let dir = tempfile::tempdir().unwrap();
+    let fasta = reference_fixture(dir.path());
+    // Every REF a base early, as on another build
+    let bases = "ACGTACGTACGGCCTTAAGG";
+    let records: Vec<(u64, &str)> = (2..=20)
+        .map(|pos| (pos, &bases[pos as usize - 2..pos as usize - 1]))
+        .collect();
+
+    vcf_tools()
+        .args(["validate", "--ref-sample", "8", "--reference"])
+        .arg(&fasta)
+        .arg("-")
+        .write_stdin(ref_vcf(&records))
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("REF checked for 8 records"))
+        .stdout(predicate::str::contains("likely on another genome build"))
+        .stdout(predicate::str::ends_with("19 records, 1 issue\n"));
+
+    // Lenient mode still reports it, but does not fail
+    vcf_tools()
+        .args(["validate", "--lenient", "--reference"])
+        .arg(&fasta)
+        .arg("-")
+        .write_stdin(ref_vcf(&records))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("likely on another genome build"));
+}
+
+#[test]
+fn test_validate_missing_header_fails() {
+    vcf_tools()
+        .args(["validate", "-"])
+        .write_stdin("chr1\t100\t.\tA\tG\t30\tPASS\t.\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Missing required header"));
+}
+
+#[test]
+fn test_compare_summary() {
+    let dir = tempfile::tempdir().unwrap();
+    let b = dir.path().join("b.vcf");
+    // Drop the chr2 record and flip a genotype at chr1:100
+    let fixture_text = std::fs::read_to_string(fixture("shared-records.vcf")).unwrap();
+    let edited: String = fixture_text
+        .lines()
+        .filter(|line| !line.starts_with("chr2"))
+        .map(|line| line.replace("0/1:20:10,10", "1/1:20:10,10") + "\n")
+        .collect();
+    std::fs::write(&b, edited).unwrap();
+
+    vcf_tools()
+        .arg("compare")
+        .arg(fixture("shared-records.vcf"))
+        .arg(&b)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Shared\s+3\n").unwrap())
+        .stdout(predicate::str::is_match(r"Only in A\s+3\n").unwrap())
+        .stdout(predicate::str::is_match(r"Only in B\s+0\n").unwrap())
+        .stdout(predicate::str::contains("chr1:100:A:G"));
+
+    let json = stdout_of(
+        vcf_tools()
+            .args(["compare", "--json"])
+            .arg(fixture("shared-records.vcf"))
+            .arg(&b),
+    );
+    let result: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(result["discordant_genotypes"], 1);
+    assert_eq!(result["discordant"], serde_json::json!(["chr1:100:A:G"]));
+}
+
+#[test]
+fn test_compare_diffs() {
+    let dir = tempfile::tempdir().unwrap();
+    let b = dir.path().join("b.vcf");
+    let fixture_text = std::fs::read_to_string(fixture("shared-records.vcf")).unwrap();
+    std::fs::write(&b, fixture_text.replace("0/1:20:10,10", "1/1:20:10,10")).unwrap();
+
+    vcf_tools()
+        .args(["compare", "--diffs", "5"])
+        .arg(fixture("shared-records.vcf"))
+        .arg(&b)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Field differences (A -> B):"))
+        .stdout(predicate::str::contains("GT: 0/1 -> 1/1"));
+
+    let json = stdout_of(
+        vcf_tools()
+            .args(["compare", "--json", "--diffs", "1"])
+            .arg(fixture("shared-records.vcf"))
+            .arg(&b),
+    );
+    let result: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let diffs = result["diffs"].as_array().unwrap();
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0]["key"], "chr1:100:A:G");
+    assert_eq!(diffs[0]["fields"][0]["a"], "0/1");
+
+    // Without --diffs nothing is diffed
+    let json = stdout_of(
+        vcf_tools()
+            .args(["compare", "--json"])
+            .arg(fixture("shared-records.vcf"))
+            .arg(&b),
+    );
+    let result: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(result["diffs"], serde_json::json!([]));
+}
+
+#[test]
+fn test_roh_bed() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("roh.vcf");
+    // A het-rich background with 30 homozygous variants from 21 kb to 50 kb
+    let mut vcf = String::from("##fileformat=VCFv4.2\n");
+    vcf += "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\n";
+    for i in 0..80 {
+        let gt = if (20..50).contains(&i) { "1/1" } else { "0/1" };
+        vcf += &format!(
+            "chr1\t{}\t.\tA\tG\t50\tPASS\t.\tGT\t{}\n",
+            (i + 1) * 1000,
+            gt
+        );
+    }
+    std::fs::write(&input, vcf).unwrap();
+
+    let roh = |extra: &[&str]| {
+        stdout_of(
+            vcf_tools()
+                .args(["roh", "-s", "S1", "--window", "10", "--min-length", "5000"])
+                .args(extra)
+                .arg(&input),
+        )
+    };
+    assert_eq!(roh(&["--min-variants", "30"]), "chr1\t20999\t50000\tS1\n");
+    assert_eq!(roh(&["--min-variants", "31"]), "");
+
+    vcf_tools()
+        .args(["roh", "-s", "NOBODY"])
+        .arg(&input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no sample named NOBODY"));
+}
+
+#[test]
+fn test_missing_input_file() {
+    vcf_tools()
+        .args(["stats", "does-not-exist.vcf"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot open does-not-exist.vcf"));
+}
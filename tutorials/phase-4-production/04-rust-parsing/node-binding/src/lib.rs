@@ -4,13 +4,40 @@
 //! enabling high-performance VCF parsing from Node.js.
 
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{
+    ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::JsFunction;
 use napi_derive::napi;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io::BufRead;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
 use vcf_parser::{
-    types::{VcfStats as RustVcfStats, VariantType as RustVariantType},
-    VcfParser as RustParser,
+    compare_callsets,
+    error::{ParseWarning as RustParseWarning, VcfResult, WarningCategory},
+    filter_expr::{self, Expr},
+    gzip,
+    types::{
+        FilterStatus, InfoValue, VariantType as RustVariantType, VcfHeader as RustVcfHeader,
+        VcfRecord as RustVcfRecord, VcfStats as RustVcfStats,
+    },
+    view::{variant_type_code, variant_type_from_label, InfoValueView, VARIANT_TYPE_LABELS},
+    CompareOptions as RustCompareOptions, CompareResult as RustCompareResult, CompiledFilter,
+    IndexedVcfReader, KinshipAccumulator, KinshipMatrix, Region, ReservoirSampler, SampleView,
+    VcfError, VcfParser as RustParser, VcfPushParser, VcfRecordView,
 };
 
+/// Records handed to a `forEachRecord` callback per call
+const RECORD_BATCH_SIZE: usize = 1000;
+
+/// Warnings kept per parse; the summary still counts every warning
+const MAX_WARNINGS: usize = 100;
+
 /// VCF Header information exposed to JavaScript
 #[napi(object)]
 pub struct VcfHeader {
@@ -22,33 +49,120 @@ pub struct VcfHeader {
     pub format_field_count: u32,
 }
 
+/// Complete VCF header, as returned by `parseHeaderFile`
+#[napi(object)]
+pub struct VcfHeaderFull {
+    pub file_format: String,
+    pub reference: Option<String>,
+    pub samples: Vec<String>,
+    pub contigs: Vec<ContigDefinition>,
+    pub info_fields: Vec<FieldDefinition>,
+    pub format_fields: Vec<FieldDefinition>,
+    pub filters: Vec<FilterDefinition>,
+}
+
+/// `##contig` header line
+#[napi(object)]
+pub struct ContigDefinition {
+    pub id: String,
+    pub length: Option<f64>,
+}
+
+/// `##INFO` or `##FORMAT` header line
+#[napi(object)]
+pub struct FieldDefinition {
+    pub id: String,
+    /// e.g. "1", "A", "R", "G" or "."
+    pub number: String,
+    /// e.g. "Integer", "Float", "Flag", "Character" or "String"
+    pub field_type: String,
+    pub description: String,
+}
+
+/// `##FILTER` header line
+#[napi(object)]
+pub struct FilterDefinition {
+    pub id: String,
+    pub description: String,
+}
+
 /// VCF Record exposed to JavaScript
 #[napi(object)]
 pub struct VcfRecord {
     pub chrom: String,
-    pub pos: u32,
+    /// 1-based position; a JS number is exact up to 2^53
+    pub pos: f64,
     pub id: Option<String>,
     pub reference: String,
     pub alternate: Vec<String>,
     pub qual: Option<f64>,
     pub filter: String,
     pub variant_type: String,
+    /// `chrom:g.` HGVS name of each ALT allele
+    pub hgvs_g: Vec<String>,
     pub is_snp: bool,
     pub is_insertion: bool,
     pub is_deletion: bool,
+    /// INFO values as numbers, strings, arrays or `true` for flags;
+    /// only present when INFO parsing is enabled
+    pub info: Option<BTreeMap<String, serde_json::Value>>,
+    /// Only present when sample parsing is enabled
+    pub samples: Option<Vec<VcfSample>>,
+}
+
+/// Sample genotype and FORMAT fields exposed to JavaScript
+#[napi(object)]
+pub struct VcfSample {
+    pub name: String,
+    /// Genotype as written, e.g. "0/1" or "1|0"; null when missing
+    pub gt: Option<String>,
+    pub phased: bool,
+    pub fields: BTreeMap<String, String>,
 }
 
 /// VCF Statistics exposed to JavaScript
 #[napi(object)]
 pub struct VcfStats {
-    pub total_records: u32,
-    pub snps: u32,
-    pub insertions: u32,
-    pub deletions: u32,
-    pub complex: u32,
-    pub passed_filter: u32,
-    pub failed_filter: u32,
+    pub total_records: i64,
+    pub snps: i64,
+    pub insertions: i64,
+    pub deletions: i64,
+    pub complex: i64,
+    pub passed_filter: i64,
+    pub failed_filter: i64,
     pub chromosomes: Vec<String>,
+    pub transitions: i64,
+    pub transversions: i64,
+    /// Transitions / transversions; null when there are no transversions
+    pub ts_tv_ratio: Option<f64>,
+    /// Counts per chromosome, in the same order as `chromosomes`
+    pub per_chromosome: Vec<ChromosomeStats>,
+}
+
+/// Record counts for one chromosome
+#[napi(object)]
+pub struct ChromosomeStats {
+    pub chrom: String,
+    pub records: i64,
+    pub snps: i64,
+    /// Insertions, deletions and complex indels
+    pub indels: i64,
+}
+
+/// Non-fatal problem found while parsing
+#[napi(object)]
+pub struct VcfWarning {
+    pub line: u32,
+    /// e.g. "malformed_genotype" or "other" (skipped invalid records)
+    pub category: String,
+    pub message: String,
+}
+
+/// Warning totals, including warnings beyond the kept ones
+#[napi(object)]
+pub struct WarningSummary {
+    pub total: i64,
+    pub by_category: HashMap<String, i64>,
 }
 
 /// Parse result containing header and records
@@ -57,14 +171,169 @@ pub struct ParseResult {
     pub header: VcfHeader,
     pub records: Vec<VcfRecord>,
     pub stats: VcfStats,
+    /// The first 100 warnings
+    pub warnings: Vec<VcfWarning>,
+    pub warning_summary: WarningSummary,
     pub parse_time_ms: f64,
 }
 
+/// Record filters for `query`; every condition given must hold
+#[napi(object)]
+pub struct QueryOptions {
+    /// Locus string such as "chr17:41196312-41277500", instead of
+    /// `chrom` / `start` / `end`
+    pub region: Option<String>,
+    pub chrom: Option<String>,
+    /// 1-based inclusive bounds on POS
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+    /// Records without a QUAL never pass this
+    pub min_qual: Option<f64>,
+    pub pass_only: Option<bool>,
+    /// Any of "SNP", "INS", "DEL", "COMPLEX" and "OTHER"
+    pub variant_types: Option<Vec<String>>,
+    /// Filter expression such as `QUAL>30 && INFO/DP>=10 && GT[0]="het"`
+    pub filter_expr: Option<String>,
+    /// Number of matching records to skip
+    pub offset: Option<u32>,
+    /// Maximum number of records to return
+    pub limit: Option<u32>,
+}
+
+/// Options for `parseColumnar`
+#[napi(object)]
+pub struct ColumnarOptions {
+    /// INFO keys to extract as typed arrays
+    pub info_columns: Option<Vec<String>>,
+}
+
+/// Records as parallel columns, one entry per record
+#[napi(object)]
+pub struct ColumnarResult {
+    pub positions: Float64Array,
+    /// NaN where QUAL is missing
+    pub quals: Float64Array,
+    /// Index into `chromNames`, numbered by first appearance
+    pub chrom_ids: Uint16Array,
+    pub chrom_names: Vec<String>,
+    pub refs: Vec<String>,
+    /// ALT alleles joined with ","
+    pub alts: Vec<String>,
+    /// Index into `typeLabels`
+    pub type_codes: Uint8Array,
+    pub type_labels: Vec<String>,
+    /// Requested INFO columns: Int32Array for Integer and Flag fields
+    /// (missing values are -2^31, absent flags 0), Float64Array with NaN
+    /// for missing values otherwise. Only the first value of list fields
+    /// is kept.
+    pub info: Option<HashMap<String, Either<Float64Array, Int32Array>>>,
+}
+
+/// Options for `getStatsMulti`
+#[napi(object)]
+pub struct StatsMultiOptions {
+    /// Reject on the first failing file instead of listing it in `errors`
+    pub fail_fast: Option<bool>,
+}
+
+/// Statistics merged across the files given to `getStatsMulti`
+#[napi(object)]
+pub struct MultiFileStats {
+    /// Totals over every file that was read successfully
+    pub stats: VcfStats,
+    /// Files that could not be read or parsed, in input order
+    pub errors: Vec<FileError>,
+}
+
+/// A file skipped by `getStatsMulti`
+#[napi(object)]
+pub struct FileError {
+    pub path: String,
+    pub message: String,
+}
+
+/// KING-robust kinship of every pair of samples, from `kinshipMatrix`
+#[napi(object)]
+pub struct KinshipResult {
+    /// Row and column order
+    pub samples: Vec<String>,
+    /// Row-major `rows` × `cols`, 0.5 on the diagonal and NaN for pairs
+    /// without a heterozygous site
+    pub data: Vec<f64>,
+    pub rows: u32,
+    pub cols: u32,
+    /// Sites used after thinning
+    pub sites: u32,
+}
+
+/// Records matched by `query`
+#[napi(object)]
+pub struct QueryResult {
+    pub records: Vec<VcfRecord>,
+    /// Every matching record, including those outside `offset` / `limit`
+    pub total_matched: i64,
+}
+
+/// Result of `sampleRecords`
+#[napi(object)]
+pub struct SampleResult {
+    /// The sample, in file order
+    pub records: Vec<VcfRecord>,
+    /// Records in the whole file
+    pub total_records: i64,
+}
+
+/// Options for `compareFiles`
+#[napi(object)]
+pub struct CompareOptions {
+    /// Treat `chr1` and `1` as the same chromosome
+    pub strip_chr_prefix: Option<bool>,
+    pub pass_only: Option<bool>,
+    /// Genotype-discordant keys to list (default 100)
+    pub max_discordant: Option<u32>,
+    /// Shared variants to list field-level diffs for (default 0)
+    pub max_diffs: Option<u32>,
+}
+
+/// Outcome of `compareFiles`
+#[napi(object)]
+pub struct CompareResult {
+    pub shared: i64,
+    pub only_a: i64,
+    pub only_b: i64,
+    pub concordant_genotypes: i64,
+    pub discordant_genotypes: i64,
+    /// Null if no genotypes could be compared
+    pub genotype_concordance: Option<f64>,
+    /// `chrom:pos:ref:alt` keys with mismatched genotypes
+    pub discordant: Vec<String>,
+    pub diffs: Vec<VariantDiff>,
+}
+
+/// Fields that differ between the two records of a shared variant
+#[napi(object)]
+pub struct VariantDiff {
+    pub key: String,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// One differing field, e.g. `info.DP` or `samples[NA12878].GT`, with
+/// both values as VCF text (`.` when absent)
+#[napi(object)]
+pub struct FieldDiff {
+    pub field: String,
+    pub a: String,
+    pub b: String,
+}
+
 /// High-performance VCF Parser
 #[napi]
 pub struct VcfParserNode {
     parse_info: bool,
     parse_samples: bool,
+    skip_invalid: bool,
+    collect_warnings: bool,
+    max_invalid_records: Option<u32>,
 }
 
 #[napi]
@@ -75,6 +344,9 @@ impl VcfParserNode {
         Self {
             parse_info: true,
             parse_samples: true,
+            skip_invalid: true,
+            collect_warnings: true,
+            max_invalid_records: None,
         }
     }
 
@@ -84,6 +356,9 @@ impl VcfParserNode {
         Self {
             parse_info: false,
             parse_samples: false,
+            skip_invalid: true,
+            collect_warnings: false,
+            max_invalid_records: None,
         }
     }
 
@@ -99,54 +374,1295 @@ impl VcfParserNode {
         self.parse_samples = value;
     }
 
+    /// Set whether invalid records are skipped with a warning (the
+    /// default) instead of failing the parse
+    #[napi]
+    pub fn set_skip_invalid(&mut self, value: bool) {
+        self.skip_invalid = value;
+    }
+
+    /// Set whether warnings are collected into the result
+    #[napi]
+    pub fn set_collect_warnings(&mut self, value: bool) {
+        self.collect_warnings = value;
+    }
+
+    /// Fail once more than `value` invalid records have been skipped;
+    /// `null` removes the limit
+    #[napi]
+    pub fn set_max_invalid_records(&mut self, value: Option<u32>) {
+        self.max_invalid_records = value;
+    }
+
     /// Parse VCF file from path
+    ///
+    /// Gzip and BGZF (`.vcf.gz`) files are decompressed transparently.
+    /// Parse errors are thrown as JS errors with `code` and, where known,
+    /// `line` and `field` properties.
     #[napi]
-    pub fn parse_file(&self, path: String) -> Result<ParseResult> {
-        let start = std::time::Instant::now();
-        
-        let content = fs::read_to_string(&path)
-            .map_err(|e| Error::from_reason(format!("Failed to read file: {}", e)))?;
-        
-        self.parse_internal(&content, start)
+    pub fn parse_file(&self, env: Env, path: String) -> Result<ParseResult> {
+        let start = Instant::now();
+        let content = read_file(&path)?;
+        self.parse_text(&env, &content, start)
     }
 
     /// Parse VCF from string content
     #[napi]
-    pub fn parse_string(&self, content: String) -> Result<ParseResult> {
-        let start = std::time::Instant::now();
-        self.parse_internal(&content, start)
+    pub fn parse_string(&self, env: Env, content: String) -> Result<ParseResult> {
+        let start = Instant::now();
+        self.parse_text(&env, &content, start)
     }
 
     /// Parse VCF from Buffer
     #[napi]
-    pub fn parse_buffer(&self, buffer: Buffer) -> Result<ParseResult> {
-        let start = std::time::Instant::now();
-        
+    pub fn parse_buffer(&self, env: Env, buffer: Buffer) -> Result<ParseResult> {
+        let start = Instant::now();
+
         let content = std::str::from_utf8(&buffer)
             .map_err(|e| Error::from_reason(format!("Invalid UTF-8: {}", e)))?;
-        
-        self.parse_internal(content, start)
+
+        self.parse_text(&env, content, start)
+    }
+
+    /// Parse VCF from a Buffer of gzip or BGZF compressed bytes
+    #[napi]
+    pub fn parse_buffer_gzip(&self, env: Env, buffer: Buffer) -> Result<ParseResult> {
+        let start = Instant::now();
+
+        let data = gzip::decompress(&buffer).map_err(|e| Error::from_reason(e.to_string()))?;
+        let content = String::from_utf8(data)
+            .map_err(|e| Error::from_reason(format!("Invalid UTF-8: {}", e)))?;
+
+        self.parse_text(&env, &content, start)
+    }
+
+    /// Get only statistics without full record parsing (faster for large files)
+    ///
+    /// Records are counted as they stream past, so memory use does not
+    /// grow with the file.
+    #[napi]
+    pub fn get_stats(&self, env: Env, path: String) -> Result<VcfStats> {
+        file_stats(&path)
+            .map(VcfStats::from)
+            .map_err(|f| f.into_error(&env))
+    }
+
+    /// Compare the callsets in two files (A and B)
+    ///
+    /// Variants are matched per ALT allele after trimming bases shared by
+    /// REF and ALT. With `maxDiffs`, `diffs` lists the fields that differ
+    /// for up to that many shared variants.
+    #[napi]
+    pub fn compare_files(
+        &self,
+        env: Env,
+        path_a: String,
+        path_b: String,
+        options: Option<CompareOptions>,
+    ) -> Result<CompareResult> {
+        let (header_a, records_a) = self
+            .rust_parser()
+            .parse_str(&read_file(&path_a)?)
+            .map_err(|e| parse_error(&env, e))?;
+        let (header_b, records_b) = self
+            .rust_parser()
+            .parse_str(&read_file(&path_b)?)
+            .map_err(|e| parse_error(&env, e))?;
+
+        let defaults = RustCompareOptions::default();
+        let options = options.map_or(defaults.clone(), |o| RustCompareOptions {
+            strip_chr_prefix: o.strip_chr_prefix.unwrap_or(defaults.strip_chr_prefix),
+            pass_only: o.pass_only.unwrap_or(defaults.pass_only),
+            max_discordant: o
+                .max_discordant
+                .map_or(defaults.max_discordant, |n| n as usize),
+            max_diffs: o.max_diffs.map_or(defaults.max_diffs, |n| n as usize),
+        });
+        let result = compare_callsets(&header_a, &records_a, &header_b, &records_b, &options);
+        Ok(result.into())
+    }
+
+    /// Read only the header of a VCF file (plain or gzipped)
+    ///
+    /// Stops at the `#CHROM` line, so the cost does not depend on the
+    /// number of records.
+    #[napi]
+    pub fn parse_header_file(&self, env: Env, path: String) -> Result<VcfHeaderFull> {
+        let reader = gzip::open_vcf(&path).map_err(read_error)?;
+
+        match self.rust_parser().read_header(reader) {
+            Ok(header) => Ok(header.into()),
+            Err(VcfError::Io(e)) => Err(read_error(gzip::gzip_error(e))),
+            Err(e) => Err(parse_error(&env, e)),
+        }
+    }
+
+    /// Parse a VCF file on the libuv thread pool
+    ///
+    /// Resolves with the same result as `parseFile` and rejects with the
+    /// same errors. Aborting `signal` cancels a parse that has not started.
+    #[napi]
+    pub fn parse_file_async(
+        &self,
+        path: String,
+        signal: Option<AbortSignal>,
+    ) -> AsyncTask<ParseTask> {
+        AsyncTask::with_optional_signal(
+            ParseTask {
+                parser: self.rust_parser(),
+                source: ParseSource::File(path),
+                failure: None,
+            },
+            signal,
+        )
+    }
+
+    /// Parse VCF string content on the libuv thread pool
+    #[napi]
+    pub fn parse_string_async(
+        &self,
+        content: String,
+        signal: Option<AbortSignal>,
+    ) -> AsyncTask<ParseTask> {
+        AsyncTask::with_optional_signal(
+            ParseTask {
+                parser: self.rust_parser(),
+                source: ParseSource::Text(content),
+                failure: None,
+            },
+            signal,
+        )
     }
 
-    /// Internal parsing logic
-    fn parse_internal(&self, content: &str, start: std::time::Instant) -> Result<ParseResult> {
+    /// Compute file statistics on the libuv thread pool
+    #[napi]
+    pub fn get_stats_async(
+        &self,
+        path: String,
+        signal: Option<AbortSignal>,
+    ) -> AsyncTask<StatsTask> {
+        AsyncTask::with_optional_signal(
+            StatsTask {
+                path,
+                failure: None,
+            },
+            signal,
+        )
+    }
+
+    /// Compute statistics for several files and merge them
+    ///
+    /// Files are streamed on up to `concurrency` threads; chromosome order
+    /// follows the order of `paths`. Failing files are listed in `errors`
+    /// unless `failFast` is set.
+    #[napi]
+    pub fn get_stats_multi(
+        &self,
+        paths: Vec<String>,
+        concurrency: u32,
+        options: Option<StatsMultiOptions>,
+        signal: Option<AbortSignal>,
+    ) -> Result<AsyncTask<StatsMultiTask>> {
+        if concurrency == 0 {
+            return Err(Error::new(
+                Status::InvalidArg,
+                "concurrency must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(AsyncTask::with_optional_signal(
+            StatsMultiTask {
+                paths,
+                concurrency: concurrency as usize,
+                fail_fast: options.and_then(|o| o.fail_fast).unwrap_or(false),
+            },
+            signal,
+        ))
+    }
+
+    /// KING-robust kinship matrix of a file's samples, for relatedness QC
+    ///
+    /// Records are streamed and only per-pair counts are kept. With
+    /// `maxSites`, the file is first scanned for its record count and then
+    /// thinned to an even stride that uses at most that many records.
+    #[napi]
+    pub fn kinship_matrix(
+        &self,
+        path: String,
+        max_sites: Option<u32>,
+        signal: Option<AbortSignal>,
+    ) -> AsyncTask<KinshipTask> {
+        AsyncTask::with_optional_signal(
+            KinshipTask {
+                path,
+                max_sites: max_sites.map(|m| m as usize),
+                failure: None,
+            },
+            signal,
+        )
+    }
+
+    /// Uniform random sample of `n` records from a VCF file, for previews
+    ///
+    /// The file is streamed through a reservoir on the libuv thread pool,
+    /// so memory stays at `n` records however large the file is. The same
+    /// `seed` gives the same sample.
+    #[napi]
+    pub fn sample_records(
+        &self,
+        path: String,
+        n: u32,
+        seed: u32,
+        signal: Option<AbortSignal>,
+    ) -> AsyncTask<SampleTask> {
+        AsyncTask::with_optional_signal(
+            SampleTask {
+                path,
+                n: n as usize,
+                seed: u64::from(seed),
+                parser: self.rust_parser(),
+                parse_info: self.parse_info,
+                parse_samples: self.parse_samples,
+                failure: None,
+            },
+            signal,
+        )
+    }
+
+    /// Select records from a VCF file path or Buffer
+    ///
+    /// Records are filtered while streaming, so only the matching records
+    /// inside `offset` / `limit` are ever converted. Gzip input is
+    /// decompressed transparently.
+    #[napi]
+    pub fn query(
+        &self,
+        env: Env,
+        input: Either<String, Buffer>,
+        options: Option<QueryOptions>,
+    ) -> Result<QueryResult> {
+        let mut query = match options {
+            Some(options) => RecordQuery::new(options)?,
+            None => RecordQuery::default(),
+        };
+        let reader = match &input {
+            Either::A(path) => gzip::open_vcf(path),
+            Either::B(buffer) => gzip::auto_decompress(&buffer[..]),
+        }
+        .map_err(read_error)?;
+
+        let mut parser = self.rust_parser();
+        parser.parse_info |= query.needs_info();
+        parser.parse_samples |= query.needs_samples();
+
+        let mut records = Vec::new();
+        let mut total_matched = 0;
+        let header = stream_records(reader, parser, |header, record| {
+            query.bind(header)?;
+            if query.matches(&record) {
+                if total_matched >= query.offset && records.len() < query.limit {
+                    records.push(record_to_js(record, self.parse_info, self.parse_samples));
+                }
+                total_matched += 1;
+            }
+            Ok(())
+        })
+        .map_err(|f| f.into_error(&env))?;
+        // Checks the expression even when there were no records
+        query.bind(&header)?;
+
+        Ok(QueryResult {
+            records,
+            total_matched: total_matched as i64,
+        })
+    }
+
+    /// Parse a VCF file into typed-array columns for DataFrame libraries
+    ///
+    /// Records are never materialized as JS objects, and sample data is
+    /// not parsed.
+    #[napi]
+    pub fn parse_columnar(
+        &self,
+        env: Env,
+        path: String,
+        options: Option<ColumnarOptions>,
+    ) -> Result<ColumnarResult> {
+        let info_keys = options.and_then(|o| o.info_columns).unwrap_or_default();
+        let mut parser = self.rust_parser();
+        parser.parse_info = !info_keys.is_empty();
+        parser.parse_samples = false;
+
+        let reader = gzip::open_vcf(&path).map_err(read_error)?;
+        let mut columns = Columns::new(&info_keys);
+        let header = stream_records(reader, parser, |_, record| columns.push(record))
+            .map_err(|f| f.into_error(&env))?;
+
+        columns.finish(&header)
+    }
+
+    /// Stream the records of a VCF file to `callback` without building them all
+    ///
+    /// The file is parsed on the libuv thread pool and `callback` receives
+    /// arrays of up to 1,000 records, in file order. The next batch is not
+    /// parsed until the previous one has been handed to JavaScript, so a
+    /// slow callback throttles the parser instead of queueing records.
+    /// Resolves with the statistics of the streamed records.
+    #[napi(
+        ts_args_type = "path: string, callback: (records: Array<VcfRecord>) => void, signal?: AbortSignal"
+    )]
+    pub fn for_each_record(
+        &self,
+        path: String,
+        callback: JsFunction,
+        signal: Option<AbortSignal>,
+    ) -> Result<AsyncTask<RecordStreamTask>> {
+        let (ack, acks) = mpsc::channel();
+        let (parse_info, parse_samples) = (self.parse_info, self.parse_samples);
+        let callback = callback.create_threadsafe_function(
+            1,
+            move |ctx: ThreadSafeCallContext<Vec<RustVcfRecord>>| {
+                // Runs on the JS thread right before the callback is called
+                let _ = ack.send(());
+                let records: Vec<VcfRecord> = ctx
+                    .value
+                    .into_iter()
+                    .map(|r| record_to_js(r, parse_info, parse_samples))
+                    .collect();
+                Ok(vec![records])
+            },
+        )?;
+
+        Ok(AsyncTask::with_optional_signal(
+            RecordStreamTask {
+                parser: Some(self.rust_parser()),
+                path,
+                callback: Some(callback),
+                acks,
+                failure: None,
+            },
+            signal,
+        ))
+    }
+}
+
+impl Default for VcfParserNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VcfParserNode {
+    /// Core parser configured with this parser's options
+    fn rust_parser(&self) -> RustParser {
         let mut parser = RustParser::new();
         parser.parse_info = self.parse_info;
         parser.parse_samples = self.parse_samples;
-        parser.skip_invalid = true;
+        parser.skip_invalid = self.skip_invalid;
+        parser.collect_warnings = self.collect_warnings;
+        parser.max_warnings = Some(MAX_WARNINGS);
+        parser.max_invalid_records = self.max_invalid_records.map(|n| n as usize);
+        parser
+    }
+
+    fn parse_text(&self, env: &Env, content: &str, start: Instant) -> Result<ParseResult> {
+        parse_content(&mut self.rust_parser(), content, start)
+            .map(ParseResult::from)
+            .map_err(|e| parse_error(env, e))
+    }
+}
+
+/// Region queries on a bgzipped VCF with a tabix index
+///
+/// The index is parsed once and the file kept open; concurrent queries,
+/// including `queryAsync` ones, take turns on the shared handle.
+#[napi]
+pub struct IndexedVcf {
+    reader: Arc<Mutex<IndexedVcfReader>>,
+}
+
+#[napi]
+impl IndexedVcf {
+    /// Open `vcfPath` with the index at `tbiPath`, or `<vcfPath>.tbi`
+    #[napi(factory)]
+    pub fn open(env: Env, vcf_path: String, tbi_path: Option<String>) -> Result<Self> {
+        let mut parser = RustParser::new();
+        // Warnings would pile up across queries with nowhere to report them
+        parser.collect_warnings = false;
+
+        let reader = IndexedVcfReader::open(&vcf_path, tbi_path.as_deref().map(Path::new))
+            .map_err(|e| indexed_error(&env, e))?
+            .with_parser(parser);
+
+        Ok(Self {
+            reader: Arc::new(Mutex::new(reader)),
+        })
+    }
+
+    /// Contigs present in the index
+    #[napi]
+    pub fn chromosomes(&self) -> Vec<String> {
+        lock_reader(&self.reader).chromosomes().to_vec()
+    }
+
+    /// Records in a locus such as "chr17:41196312-41277500"
+    ///
+    /// `options` filters further, as in `VcfParserNode.query`.
+    #[napi]
+    pub fn query(
+        &self,
+        env: Env,
+        region: String,
+        options: Option<QueryOptions>,
+    ) -> Result<Vec<VcfRecord>> {
+        let (region, query) = indexed_query(&self.reader, &region, options)?;
+
+        indexed_records(&self.reader, &region, &query)
+            .map(|records| records.into_iter().map(VcfRecord::from).collect())
+            .map_err(|e| indexed_error(&env, e))
+    }
+
+    /// `query` on the libuv thread pool
+    #[napi]
+    pub fn query_async(
+        &self,
+        region: String,
+        options: Option<QueryOptions>,
+        signal: Option<AbortSignal>,
+    ) -> Result<AsyncTask<IndexedQueryTask>> {
+        let (region, query) = indexed_query(&self.reader, &region, options)?;
+
+        Ok(AsyncTask::with_optional_signal(
+            IndexedQueryTask {
+                reader: Arc::clone(&self.reader),
+                region,
+                query,
+                failure: None,
+            },
+            signal,
+        ))
+    }
+
+    /// Number of records in a locus
+    #[napi]
+    pub fn query_count(&self, env: Env, region: String) -> Result<i64> {
+        let (region, _) = indexed_query(&self.reader, &region, None)?;
+
+        lock_reader(&self.reader)
+            .query_count(&region)
+            .map(|n| n as i64)
+            .map_err(|e| indexed_error(&env, e))
+    }
+}
+
+/// Background region query for `IndexedVcf.queryAsync`
+pub struct IndexedQueryTask {
+    reader: Arc<Mutex<IndexedVcfReader>>,
+    region: Region,
+    query: RecordQuery,
+    /// Error kept for `reject`, which builds the JS error
+    failure: Option<VcfError>,
+}
+
+impl Task for IndexedQueryTask {
+    type Output = Vec<RustVcfRecord>;
+    type JsValue = Vec<VcfRecord>;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        indexed_records(&self.reader, &self.region, &self.query).map_err(|e| {
+            let error = Error::from_reason(e.to_string());
+            self.failure = Some(e);
+            error
+        })
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into_iter().map(VcfRecord::from).collect())
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(self.failure.take().map_or(err, |e| indexed_error(&env, e)))
+    }
+}
+
+/// Region and record filters for an `IndexedVcf` query
+fn indexed_query(
+    reader: &Mutex<IndexedVcfReader>,
+    region: &str,
+    options: Option<QueryOptions>,
+) -> Result<(Region, RecordQuery)> {
+    let region =
+        Region::parse(region).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+    let mut query = match options {
+        Some(options) => RecordQuery::new(options)?,
+        None => RecordQuery::default(),
+    };
+    query.bind(lock_reader(reader).header())?;
+    Ok((region, query))
+}
+
+/// Records in `region` that pass `query`, within its offset / limit
+fn indexed_records(
+    reader: &Mutex<IndexedVcfReader>,
+    region: &Region,
+    query: &RecordQuery,
+) -> VcfResult<Vec<RustVcfRecord>> {
+    let mut records = Vec::new();
+    let mut matched = 0;
+    lock_reader(reader).for_each(region, |record| {
+        if query.matches(&record) {
+            if matched >= query.offset && records.len() < query.limit {
+                records.push(record);
+            }
+            matched += 1;
+        }
+        Ok(())
+    })?;
+    Ok(records)
+}
+
+/// Lock the shared reader; a query that panicked leaves it usable, as
+/// every query seeks before reading
+fn lock_reader(reader: &Mutex<IndexedVcfReader>) -> MutexGuard<'_, IndexedVcfReader> {
+    reader
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// JS error for an indexed read: file problems read as such, anything
+/// else as a parse error
+fn indexed_error(env: &Env, error: VcfError) -> Error {
+    match error {
+        VcfError::Io(_) | VcfError::TruncatedGzip | VcfError::InvalidIndex(_) => read_error(error),
+        e => parse_error(env, e),
+    }
+}
+
+/// Parsed VCF still in Rust types, so it can cross threads
+pub struct ParsedVcf {
+    header: RustVcfHeader,
+    records: Vec<RustVcfRecord>,
+    stats: RustVcfStats,
+    parse_time_ms: f64,
+    parse_info: bool,
+    parse_samples: bool,
+    warnings: Vec<RustParseWarning>,
+    warning_counts: HashMap<WarningCategory, usize>,
+}
+
+/// Where a `ParseTask` reads its VCF text from
+enum ParseSource {
+    File(String),
+    Text(String),
+}
+
+/// Background parse for the `*Async` methods
+///
+/// Reading and parsing run in `compute` off the JS thread; records are
+/// converted to JS objects in `resolve`, back on the JS thread.
+pub struct ParseTask {
+    parser: RustParser,
+    source: ParseSource,
+    /// Parse error kept for `reject`, which builds the JS error
+    failure: Option<VcfError>,
+}
+
+impl Task for ParseTask {
+    type Output = ParsedVcf;
+    type JsValue = ParseResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let start = Instant::now();
+        let parsed = match &mut self.source {
+            ParseSource::File(path) => {
+                let content = read_file(path)?;
+                parse_content(&mut self.parser, &content, start)
+            }
+            ParseSource::Text(content) => {
+                let content = std::mem::take(content);
+                parse_content(&mut self.parser, &content, start)
+            }
+        };
+        parsed.map_err(|e| stash_failure(&mut self.failure, Failure::Parse(e)))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(self.failure.take().map_or(err, |e| parse_error(&env, e)))
+    }
+}
+
+/// Background statistics for `getStatsAsync`
+pub struct StatsTask {
+    path: String,
+    /// Parse error kept for `reject`, which builds the JS error
+    failure: Option<VcfError>,
+}
+
+impl Task for StatsTask {
+    type Output = RustVcfStats;
+    type JsValue = VcfStats;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        file_stats(&self.path).map_err(|f| stash_failure(&mut self.failure, f))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(self.failure.take().map_or(err, |e| parse_error(&env, e)))
+    }
+}
+
+/// Background kinship for `kinshipMatrix`
+pub struct KinshipTask {
+    path: String,
+    max_sites: Option<usize>,
+    /// Parse error kept for `reject`, which builds the JS error
+    failure: Option<VcfError>,
+}
+
+impl Task for KinshipTask {
+    type Output = KinshipMatrix;
+    type JsValue = KinshipResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        file_kinship(&self.path, self.max_sites).map_err(|f| stash_failure(&mut self.failure, f))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(KinshipResult {
+            rows: output.rows() as u32,
+            cols: output.cols() as u32,
+            sites: output.sites as u32,
+            samples: output.samples,
+            data: output.data,
+        })
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(self.failure.take().map_or(err, |e| parse_error(&env, e)))
+    }
+}
+
+/// Background reservoir sampling for `sampleRecords`
+pub struct SampleTask {
+    path: String,
+    n: usize,
+    seed: u64,
+    parser: RustParser,
+    /// Which parts of each record to hand to JavaScript
+    parse_info: bool,
+    parse_samples: bool,
+    /// Parse error kept for `reject`, which builds the JS error
+    failure: Option<VcfError>,
+}
+
+impl Task for SampleTask {
+    type Output = ReservoirSampler<RustVcfRecord>;
+    type JsValue = SampleResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let reader = gzip::open_vcf(&self.path).map_err(read_error)?;
+        let mut sampler = ReservoirSampler::new(self.n, self.seed);
+        stream_records(reader, std::mem::take(&mut self.parser), |_, record| {
+            sampler.offer(record);
+            Ok(())
+        })
+        .map_err(|f| stash_failure(&mut self.failure, f))?;
+        Ok(sampler)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        let (parse_info, parse_samples) = (self.parse_info, self.parse_samples);
+        Ok(SampleResult {
+            total_records: output.seen() as i64,
+            records: output
+                .into_vec()
+                .into_iter()
+                .map(|r| record_to_js(r, parse_info, parse_samples))
+                .collect(),
+        })
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(self.failure.take().map_or(err, |e| parse_error(&env, e)))
+    }
+}
+
+/// Background multi-file statistics for `getStatsMulti`
+pub struct StatsMultiTask {
+    paths: Vec<String>,
+    concurrency: usize,
+    fail_fast: bool,
+}
+
+impl Task for StatsMultiTask {
+    type Output = (RustVcfStats, Vec<FileError>);
+    type JsValue = MultiFileStats;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let next = AtomicUsize::new(0);
+        let stop = AtomicBool::new(false);
+        let paths = &self.paths;
+        let fail_fast = self.fail_fast;
+
+        let worker = || {
+            let mut done = Vec::new();
+            while !stop.load(Ordering::Relaxed) {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = paths.get(i) else {
+                    break;
+                };
+                let result = file_stats(path).map_err(failure_message);
+                if result.is_err() && fail_fast {
+                    stop.store(true, Ordering::Relaxed);
+                }
+                done.push((i, result));
+            }
+            done
+        };
+
+        let mut results: Vec<_> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..self.concurrency.min(paths.len()))
+                .map(|_| scope.spawn(worker))
+                .collect();
+            workers
+                .into_iter()
+                .flat_map(|w| w.join().expect("stats worker panicked"))
+                .collect()
+        });
+        results.sort_by_key(|(i, _)| *i);
+
+        let mut stats = RustVcfStats::new();
+        let mut errors = Vec::new();
+        for (i, result) in results {
+            match result {
+                Ok(file) => stats.merge(file),
+                Err(message) if fail_fast => {
+                    return Err(Error::from_reason(format!("{}: {}", paths[i], message)));
+                }
+                Err(message) => errors.push(FileError {
+                    path: paths[i].clone(),
+                    message,
+                }),
+            }
+        }
+
+        Ok((stats, errors))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        let (stats, errors) = output;
+        Ok(MultiFileStats {
+            stats: stats.into(),
+            errors,
+        })
+    }
+}
+
+/// Background streaming parse for `forEachRecord`
+pub struct RecordStreamTask {
+    parser: Option<RustParser>,
+    path: String,
+    callback: Option<ThreadsafeFunction<Vec<RustVcfRecord>, ErrorStrategy::Fatal>>,
+    acks: Receiver<()>,
+    /// Parse error kept for `reject`, which builds the JS error
+    failure: Option<VcfError>,
+}
+
+impl RecordStreamTask {
+    /// Hand a batch to JavaScript and wait until it has been taken
+    fn send(
+        &self,
+        callback: &ThreadsafeFunction<Vec<RustVcfRecord>, ErrorStrategy::Fatal>,
+        batch: Vec<RustVcfRecord>,
+    ) -> Result<()> {
+        let status = callback.call(batch, ThreadsafeFunctionCallMode::Blocking);
+        if status != Status::Ok {
+            return Err(Error::new(status, "Record callback is closed".to_string()));
+        }
+        self.acks
+            .recv()
+            .map_err(|_| Error::from_reason("Record callback is closed"))
+    }
+}
 
-        let (header, records) = parser.parse_str(content)
-            .map_err(|e| Error::from_reason(format!("Parse error: {}", e)))?;
+impl Task for RecordStreamTask {
+    type Output = RustVcfStats;
+    type JsValue = VcfStats;
 
-        // Calculate statistics
+    fn compute(&mut self) -> Result<Self::Output> {
+        // Taking the callback releases it when streaming ends, even on error
+        let callback = self
+            .callback
+            .take()
+            .ok_or_else(|| Error::from_reason("Records were already streamed"))?;
+        let parser = self.parser.take().unwrap_or_default();
+
+        let reader = gzip::open_vcf(&self.path).map_err(read_error)?;
         let mut stats = RustVcfStats::new();
-        for record in &records {
-            stats.update(record);
+        let mut batch = Vec::with_capacity(RECORD_BATCH_SIZE);
+
+        stream_records(reader, parser, |_, record| {
+            stats.update(&record);
+            batch.push(record);
+            if batch.len() == RECORD_BATCH_SIZE {
+                let full = std::mem::replace(&mut batch, Vec::with_capacity(RECORD_BATCH_SIZE));
+                self.send(&callback, full)?;
+            }
+            Ok(())
+        })
+        .map_err(|f| stash_failure(&mut self.failure, f))?;
+
+        if !batch.is_empty() {
+            self.send(&callback, batch)?;
+        }
+        Ok(stats)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        Err(self.failure.take().map_or(err, |e| parse_error(&env, e)))
+    }
+}
+
+/// Filters from `QueryOptions`, resolved once before streaming
+struct RecordQuery {
+    region: Option<Region>,
+    start: u64,
+    end: u64,
+    min_qual: Option<f64>,
+    pass_only: bool,
+    variant_types: Option<Vec<RustVariantType>>,
+    /// `filter_expr` and its parse, checked against the header by `bind`
+    expr: Option<(String, Expr)>,
+    filter: Option<CompiledFilter>,
+    offset: usize,
+    limit: usize,
+}
+
+impl Default for RecordQuery {
+    fn default() -> Self {
+        Self {
+            region: None,
+            start: 0,
+            end: u64::MAX,
+            min_qual: None,
+            pass_only: false,
+            variant_types: None,
+            expr: None,
+            filter: None,
+            offset: 0,
+            limit: usize::MAX,
+        }
+    }
+}
+
+impl RecordQuery {
+    fn new(options: QueryOptions) -> Result<Self> {
+        let invalid = |message: String| Error::new(Status::InvalidArg, message);
+        let mut query = Self::default();
+
+        if let Some(region) = options.region {
+            if options.chrom.is_some() || options.start.is_some() || options.end.is_some() {
+                return Err(invalid(
+                    "Pass either region or chrom/start/end, not both".to_string(),
+                ));
+            }
+            query.region = Some(Region::parse(&region).map_err(|e| invalid(e.to_string()))?);
+        } else {
+            query.region = options.chrom.map(Region::contig);
+            query.start = options.start.map_or(0, |p| p.max(0.0) as u64);
+            query.end = options.end.map_or(u64::MAX, |p| p.max(0.0) as u64);
+        }
+
+        if let Some(names) = options.variant_types {
+            let types = names
+                .iter()
+                .map(|name| {
+                    variant_type_from_label(name).ok_or_else(|| {
+                        invalid(format!(
+                            "Unknown variant type '{}' (expected SNP, INS, DEL, COMPLEX or OTHER)",
+                            name
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            query.variant_types = Some(types);
+        }
+
+        if let Some(source) = options.filter_expr {
+            let expr = filter_expr::parse(&source).map_err(|e| invalid(e.to_string()))?;
+            query.expr = Some((source, expr));
+        }
+
+        query.min_qual = options.min_qual;
+        query.pass_only = options.pass_only.unwrap_or(false);
+        query.offset = options.offset.unwrap_or(0) as usize;
+        query.limit = options.limit.map_or(usize::MAX, |l| l as usize);
+        Ok(query)
+    }
+
+    /// Compile the filter expression against the file's header; does
+    /// nothing once compiled
+    fn bind(&mut self, header: &RustVcfHeader) -> Result<()> {
+        if let (Some((source, _)), None) = (&self.expr, &self.filter) {
+            let filter = filter_expr::compile(source, header)
+                .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?;
+            self.filter = Some(filter);
+        }
+        Ok(())
+    }
+
+    fn needs_info(&self) -> bool {
+        self.expr
+            .as_ref()
+            .is_some_and(|(_, expr)| expr.needs_info())
+    }
+
+    fn needs_samples(&self) -> bool {
+        self.expr
+            .as_ref()
+            .is_some_and(|(_, expr)| expr.needs_samples())
+    }
+
+    fn matches(&self, record: &RustVcfRecord) -> bool {
+        if self
+            .region
+            .as_ref()
+            .is_some_and(|r| !r.contains_record(record))
+        {
+            return false;
+        }
+        if record.pos < self.start || record.pos > self.end {
+            return false;
+        }
+        if let Some(min) = self.min_qual {
+            if !record.qual.is_some_and(|q| q >= min) {
+                return false;
+            }
+        }
+        if self.pass_only && record.filter != FilterStatus::Pass {
+            return false;
+        }
+        if self.filter.as_ref().is_some_and(|f| !f.matches(record)) {
+            return false;
+        }
+        match &self.variant_types {
+            Some(types) => types.contains(&record.variant_type()),
+            None => true,
+        }
+    }
+}
+
+/// Error from streaming input
+///
+/// Parse errors stay core errors until they reach the JS thread, where
+/// `parse_error` can attach their line and field.
+enum Failure {
+    Parse(VcfError),
+    Other(Error),
+}
+
+impl From<Error> for Failure {
+    fn from(error: Error) -> Self {
+        Failure::Other(error)
+    }
+}
+
+impl Failure {
+    fn into_error(self, env: &Env) -> Error {
+        match self {
+            Failure::Parse(e) => parse_error(env, e),
+            Failure::Other(e) => e,
+        }
+    }
+}
+
+/// Message for a failure that is reported rather than thrown
+fn failure_message(failure: Failure) -> String {
+    match failure {
+        Failure::Parse(e) => format!("Parse error: {}", e),
+        Failure::Other(e) => e.reason,
+    }
+}
+
+/// Keep a parse error for a task's `reject`, returning the plain error
+/// `compute` has to report
+fn stash_failure(slot: &mut Option<VcfError>, failure: Failure) -> Error {
+    match failure {
+        Failure::Parse(e) => {
+            let error = Error::from_reason(format!("Parse error: {}", e));
+            *slot = Some(e);
+            error
+        }
+        Failure::Other(e) => e,
+    }
+}
+
+/// JS error for a core parse error, carrying `code` and, where known, the
+/// `line` and VCF `field`
+fn parse_error(env: &Env, error: VcfError) -> Error {
+    let message = format!("Parse error: {}", error);
+    let build = || -> Result<Error> {
+        let mut js_error = env.create_error(Error::from_reason(message.clone()))?;
+        js_error.set_named_property("code", env.create_string(error.code())?)?;
+        if let Some(line) = error.line() {
+            js_error.set_named_property("line", env.create_double(line as f64)?)?;
+        }
+        if let Some(field) = error.field() {
+            js_error.set_named_property("field", env.create_string(field)?)?;
+        }
+        Ok(Error::from(js_error.into_unknown()))
+    };
+    build().unwrap_or_else(|_| Error::from_reason(message))
+}
+
+/// Feed `reader` line by line through a push parser, handing each record
+/// and the header to `on_record` as soon as it is parsed; returns the header
+fn stream_records(
+    reader: impl BufRead,
+    parser: RustParser,
+    mut on_record: impl FnMut(&RustVcfHeader, RustVcfRecord) -> Result<()>,
+) -> std::result::Result<RustVcfHeader, Failure> {
+    let mut stream = VcfPushParser::new(parser);
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| read_error(gzip::gzip_error(e)))?;
+        let record = stream.push_line(&line).map_err(Failure::Parse)?;
+
+        if let Some(record) = record {
+            on_record(stream.header(), record)?;
+        }
+    }
+
+    stream.finish().map_err(Failure::Parse)?;
+    Ok(stream.into_header())
+}
+
+/// Column builder for `parseColumnar`
+struct Columns<'a> {
+    positions: Vec<f64>,
+    quals: Vec<f64>,
+    chrom_ids: Vec<u16>,
+    chrom_names: Vec<String>,
+    chrom_index: HashMap<String, u16>,
+    refs: Vec<String>,
+    alts: Vec<String>,
+    type_codes: Vec<u8>,
+    /// INFO values as f64 until the header says which keys are integers
+    info: Vec<(&'a str, Vec<f64>)>,
+}
+
+impl<'a> Columns<'a> {
+    fn new(info_keys: &'a [String]) -> Self {
+        Self {
+            positions: Vec::new(),
+            quals: Vec::new(),
+            chrom_ids: Vec::new(),
+            chrom_names: Vec::new(),
+            chrom_index: HashMap::new(),
+            refs: Vec::new(),
+            alts: Vec::new(),
+            type_codes: Vec::new(),
+            info: info_keys.iter().map(|k| (k.as_str(), Vec::new())).collect(),
+        }
+    }
+
+    fn push(&mut self, record: RustVcfRecord) -> Result<()> {
+        let chrom_id = match self.chrom_index.get(&record.chrom) {
+            Some(&id) => id,
+            None => {
+                let id = u16::try_from(self.chrom_names.len()).map_err(|_| {
+                    Error::from_reason("Too many chromosomes for 16-bit chromosome ids")
+                })?;
+                self.chrom_index.insert(record.chrom.clone(), id);
+                self.chrom_names.push(record.chrom.clone());
+                id
+            }
+        };
+
+        self.positions.push(record.pos as f64);
+        self.quals.push(record.qual.unwrap_or(f64::NAN));
+        self.chrom_ids.push(chrom_id);
+        self.type_codes
+            .push(variant_type_code(record.variant_type()));
+        for (key, values) in &mut self.info {
+            values.push(record.info.get(*key).map_or(f64::NAN, info_number));
+        }
+        self.alts.push(record.alternate.join(","));
+        self.refs.push(record.reference);
+        Ok(())
+    }
+
+    fn finish(self, header: &RustVcfHeader) -> Result<ColumnarResult> {
+        let info = if self.info.is_empty() {
+            None
+        } else {
+            let mut columns = HashMap::new();
+            for (key, values) in self.info {
+                let definition =
+                    header
+                        .info_fields
+                        .iter()
+                        .find(|f| f.id == key)
+                        .ok_or_else(|| {
+                            Error::new(
+                                Status::InvalidArg,
+                                format!("INFO field '{}' is not defined in the header", key),
+                            )
+                        })?;
+                let column = match definition.field_type.as_str() {
+                    "Integer" => Either::B(Int32Array::new(
+                        values
+                            .iter()
+                            .map(|&v| if v.is_nan() { i32::MIN } else { v as i32 })
+                            .collect(),
+                    )),
+                    "Flag" => Either::B(Int32Array::new(
+                        values.iter().map(|&v| i32::from(v == 1.0)).collect(),
+                    )),
+                    "Float" => Either::A(Float64Array::new(values)),
+                    other => {
+                        return Err(Error::new(
+                            Status::InvalidArg,
+                            format!("INFO field '{}' has non-numeric type {}", key, other),
+                        ))
+                    }
+                };
+                columns.insert(key.to_string(), column);
+            }
+            Some(columns)
+        };
+
+        Ok(ColumnarResult {
+            positions: Float64Array::new(self.positions),
+            quals: Float64Array::new(self.quals),
+            chrom_ids: Uint16Array::new(self.chrom_ids),
+            chrom_names: self.chrom_names,
+            refs: self.refs,
+            alts: self.alts,
+            type_codes: Uint8Array::new(self.type_codes),
+            type_labels: VARIANT_TYPE_LABELS.iter().map(|l| l.to_string()).collect(),
+            info,
+        })
+    }
+}
+
+/// Numeric value of an INFO entry: the first value of lists, 1 for flags
+fn info_number(value: &InfoValue) -> f64 {
+    match value {
+        InfoValue::Flag => 1.0,
+        InfoValue::Integer(i) => *i as f64,
+        InfoValue::Float(f) => *f,
+        InfoValue::IntegerArray(v) => v.first().map_or(f64::NAN, |&i| i as f64),
+        InfoValue::FloatArray(v) => v.first().copied().unwrap_or(f64::NAN),
+        InfoValue::String(_) | InfoValue::StringArray(_) => f64::NAN,
+    }
+}
+
+/// Read a VCF file to a string, decompressing gzip and BGZF input
+fn read_file(path: &str) -> Result<String> {
+    let mut data = fs::read(path).map_err(|e| read_error(e.into()))?;
+    if gzip::is_gzip(&data) {
+        data = gzip::decompress(&data).map_err(read_error)?;
+    }
+    String::from_utf8(data).map_err(|e| Error::from_reason(format!("Invalid UTF-8: {}", e)))
+}
+
+fn read_error(e: VcfError) -> Error {
+    match e {
+        VcfError::Io(e) => Error::from_reason(format!("Failed to read file: {}", e)),
+        e => Error::from_reason(format!("Failed to read file: {}", e)),
+    }
+}
+
+/// Parse `content` and collect statistics; `start` is when the caller began
+fn parse_content(parser: &mut RustParser, content: &str, start: Instant) -> VcfResult<ParsedVcf> {
+    let (header, records) = parser.parse_str(content)?;
+
+    // Calculate statistics
+    let mut stats = RustVcfStats::new();
+    for record in &records {
+        stats.update(record);
+    }
+
+    Ok(ParsedVcf {
+        header,
+        records,
+        stats,
+        parse_time_ms: start.elapsed().as_secs_f64() * 1000.0,
+        parse_info: parser.parse_info,
+        parse_samples: parser.parse_samples,
+        warnings: parser.warnings().to_vec(),
+        warning_counts: parser.warning_counts().clone(),
+    })
+}
+
+/// Statistics for a file, streamed through the fast parser so no records
+/// are kept
+fn file_stats(path: &str) -> std::result::Result<RustVcfStats, Failure> {
+    let reader = gzip::open_vcf(path).map_err(read_error)?;
+
+    let mut stats = RustVcfStats::new();
+    stream_records(reader, RustParser::fast(), |_, record| {
+        stats.update(&record);
+        Ok(())
+    })?;
+
+    Ok(stats)
+}
+
+/// Stream a file into a kinship accumulator, thinning to at most
+/// `max_sites` records
+fn file_kinship(
+    path: &str,
+    max_sites: Option<usize>,
+) -> std::result::Result<KinshipMatrix, Failure> {
+    let stride = match max_sites {
+        Some(max) => {
+            let mut count = 0;
+            for line in gzip::open_vcf(path).map_err(read_error)?.lines() {
+                let line = line.map_err(|e| read_error(gzip::gzip_error(e)))?;
+                count += usize::from(!line.is_empty() && !line.starts_with('#'));
+            }
+            count.div_ceil(max.max(1))
         }
+        None => 1,
+    };
+
+    let reader = gzip::open_vcf(path).map_err(read_error)?;
+    let mut parser = RustParser::fast();
+    parser.parse_samples = true;
+
+    let mut accumulator = None;
+    let header = stream_records(reader, parser, |header, record| {
+        accumulator
+            .get_or_insert_with(|| {
+                KinshipAccumulator::new(header.samples.len()).with_stride(stride)
+            })
+            .add(&record);
+        Ok(())
+    })?;
+
+    let accumulator = accumulator.unwrap_or_else(|| KinshipAccumulator::new(header.samples.len()));
+    Ok(accumulator.finish(header.samples))
+}
 
-        let parse_time = start.elapsed().as_secs_f64() * 1000.0;
+impl From<ParsedVcf> for ParseResult {
+    fn from(parsed: ParsedVcf) -> Self {
+        let header = parsed.header;
 
-        Ok(ParseResult {
+        ParseResult {
             header: VcfHeader {
                 file_format: header.file_format,
                 reference: header.reference,
@@ -155,95 +1671,224 @@ impl VcfParserNode {
                 info_field_count: header.info_fields.len() as u32,
                 format_field_count: header.format_fields.len() as u32,
             },
-            records: records
+            records: parsed
+                .records
                 .into_iter()
-                .map(|r| {
-                    let variant_type = match r.variant_type() {
-                        RustVariantType::Snp => "SNP",
-                        RustVariantType::Insertion => "INS",
-                        RustVariantType::Deletion => "DEL",
-                        RustVariantType::Complex => "COMPLEX",
-                        RustVariantType::Other => "OTHER",
-                    };
-                    
-                    let filter = match &r.filter {
-                        vcf_parser::types::FilterStatus::Pass => "PASS".to_string(),
-                        vcf_parser::types::FilterStatus::Missing => ".".to_string(),
-                        vcf_parser::types::FilterStatus::Failed(filters) => filters.join(";"),
-                    };
-
-                    VcfRecord {
-                        chrom: r.chrom,
-                        pos: r.pos as u32,
-                        id: r.id,
-                        reference: r.reference.clone(),
-                        alternate: r.alternate.clone(),
-                        qual: r.qual,
-                        filter,
-                        variant_type: variant_type.to_string(),
-                        is_snp: r.is_snp(),
-                        is_insertion: r.is_insertion(),
-                        is_deletion: r.is_deletion(),
-                    }
+                .map(|r| record_to_js(r, parsed.parse_info, parsed.parse_samples))
+                .collect(),
+            stats: parsed.stats.into(),
+            warnings: parsed
+                .warnings
+                .into_iter()
+                .map(|w| VcfWarning {
+                    line: w.line as u32,
+                    category: w.category.as_str().to_string(),
+                    message: w.message,
                 })
                 .collect(),
-            stats: VcfStats {
-                total_records: stats.total_records as u32,
-                snps: stats.snps as u32,
-                insertions: stats.insertions as u32,
-                deletions: stats.deletions as u32,
-                complex: stats.complex as u32,
-                passed_filter: stats.passed_filter as u32,
-                failed_filter: stats.failed_filter as u32,
-                chromosomes: stats.chromosomes,
+            warning_summary: WarningSummary {
+                total: parsed.warning_counts.values().sum::<usize>() as i64,
+                by_category: parsed
+                    .warning_counts
+                    .iter()
+                    .map(|(c, &n)| (c.as_str().to_string(), n as i64))
+                    .collect(),
             },
-            parse_time_ms: parse_time,
-        })
+            parse_time_ms: parsed.parse_time_ms,
+        }
     }
+}
 
-    /// Get only statistics without full record parsing (faster for large files)
-    #[napi]
-    pub fn get_stats(&self, path: String) -> Result<VcfStats> {
-        let content = fs::read_to_string(&path)
-            .map_err(|e| Error::from_reason(format!("Failed to read file: {}", e)))?;
-
-        // Use fast parser for stats only
-        let mut parser = RustParser::fast();
-        let (_, records) = parser.parse_str(&content)
-            .map_err(|e| Error::from_reason(format!("Parse error: {}", e)))?;
-
-        let stats = vcf_parser::calculate_stats(&records);
-
-        Ok(VcfStats {
-            total_records: stats.total_records as u32,
-            snps: stats.snps as u32,
-            insertions: stats.insertions as u32,
-            deletions: stats.deletions as u32,
-            complex: stats.complex as u32,
-            passed_filter: stats.passed_filter as u32,
-            failed_filter: stats.failed_filter as u32,
+impl From<RustVcfRecord> for VcfRecord {
+    fn from(record: RustVcfRecord) -> Self {
+        VcfRecordView::from(record).into()
+    }
+}
+
+/// Convert a record, leaving out INFO / samples when not requested
+fn record_to_js(record: RustVcfRecord, parse_info: bool, parse_samples: bool) -> VcfRecord {
+    VcfRecordView::new(record, parse_info, parse_samples).into()
+}
+
+impl From<VcfRecordView> for VcfRecord {
+    fn from(view: VcfRecordView) -> Self {
+        VcfRecord {
+            chrom: view.chrom,
+            pos: view.pos as f64,
+            id: view.id,
+            reference: view.reference,
+            alternate: view.alternate,
+            qual: view.qual,
+            filter: view.filter,
+            variant_type: view.variant_type,
+            hgvs_g: view.hgvs_g,
+            is_snp: view.is_snp,
+            is_insertion: view.is_insertion,
+            is_deletion: view.is_deletion,
+            info: view.info.map(|info| {
+                info.into_iter()
+                    .map(|(k, v)| {
+                        let value = serde_json::to_value(InfoValueView(&v))
+                            .unwrap_or(serde_json::Value::Null);
+                        (k, value)
+                    })
+                    .collect()
+            }),
+            samples: view
+                .samples
+                .map(|samples| samples.into_iter().map(VcfSample::from).collect()),
+        }
+    }
+}
+
+impl From<SampleView> for VcfSample {
+    fn from(sample: SampleView) -> Self {
+        VcfSample {
+            name: sample.name,
+            gt: sample.gt,
+            phased: sample.phased,
+            fields: sample.fields,
+        }
+    }
+}
+
+impl From<RustVcfHeader> for VcfHeaderFull {
+    fn from(header: RustVcfHeader) -> Self {
+        let field = |id, number, field_type, description| FieldDefinition {
+            id,
+            number,
+            field_type,
+            description,
+        };
+
+        VcfHeaderFull {
+            file_format: header.file_format,
+            reference: header.reference,
+            samples: header.samples,
+            contigs: header
+                .contigs
+                .into_iter()
+                .map(|c| ContigDefinition {
+                    id: c.id,
+                    length: c.length.map(|l| l as f64),
+                })
+                .collect(),
+            info_fields: header
+                .info_fields
+                .into_iter()
+                .map(|f| field(f.id, f.number, f.field_type, f.description))
+                .collect(),
+            format_fields: header
+                .format_fields
+                .into_iter()
+                .map(|f| field(f.id, f.number, f.field_type, f.description))
+                .collect(),
+            filters: header
+                .filters
+                .into_iter()
+                .map(|f| FilterDefinition {
+                    id: f.id,
+                    description: f.description,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RustVcfStats> for VcfStats {
+    fn from(stats: RustVcfStats) -> Self {
+        VcfStats {
+            total_records: stats.total_records as i64,
+            snps: stats.snps as i64,
+            insertions: stats.insertions as i64,
+            deletions: stats.deletions as i64,
+            complex: stats.complex as i64,
+            passed_filter: stats.passed_filter as i64,
+            failed_filter: stats.failed_filter as i64,
+            ts_tv_ratio: stats.ts_tv_ratio(),
             chromosomes: stats.chromosomes,
-        })
+            transitions: stats.transitions as i64,
+            transversions: stats.transversions as i64,
+            per_chromosome: stats
+                .per_chromosome
+                .into_iter()
+                .map(|c| ChromosomeStats {
+                    chrom: c.chrom,
+                    records: c.records as i64,
+                    snps: c.snps as i64,
+                    indels: c.indels as i64,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<RustCompareResult> for CompareResult {
+    fn from(result: RustCompareResult) -> Self {
+        Self {
+            genotype_concordance: result.genotype_concordance(),
+            shared: result.shared as i64,
+            only_a: result.only_a as i64,
+            only_b: result.only_b as i64,
+            concordant_genotypes: result.concordant_genotypes as i64,
+            discordant_genotypes: result.discordant_genotypes as i64,
+            discordant: result.discordant,
+            diffs: result
+                .diffs
+                .into_iter()
+                .map(|diff| VariantDiff {
+                    key: diff.key,
+                    fields: diff
+                        .fields
+                        .into_iter()
+                        .map(|f| FieldDiff {
+                            field: f.field,
+                            a: f.a,
+                            b: f.b,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
     }
 }
 
 /// Parse VCF file (convenience function)
 #[napi]
-pub fn parse_vcf_file(path: String) -> Result<ParseResult> {
+pub fn parse_vcf_file(env: Env, path: String) -> Result<ParseResult> {
     let parser = VcfParserNode::new();
-    parser.parse_file(path)
+    parser.parse_file(env, path)
 }
 
 /// Parse VCF string (convenience function)
 #[napi]
-pub fn parse_vcf_string(content: String) -> Result<ParseResult> {
+pub fn parse_vcf_string(env: Env, content: String) -> Result<ParseResult> {
     let parser = VcfParserNode::new();
-    parser.parse_string(content)
+    parser.parse_string(env, content)
 }
 
 /// Fast parse for statistics only
 #[napi]
-pub fn get_vcf_stats(path: String) -> Result<VcfStats> {
+pub fn get_vcf_stats(env: Env, path: String) -> Result<VcfStats> {
     let parser = VcfParserNode::new();
-    parser.get_stats(path)
+    parser.get_stats(env, path)
+}
+
+/// Compare two VCF files (convenience function)
+#[napi]
+pub fn compare_vcf_files(
+    env: Env,
+    path_a: String,
+    path_b: String,
+    options: Option<CompareOptions>,
+) -> Result<CompareResult> {
+    let parser = VcfParserNode::new();
+    parser.compare_files(env, path_a, path_b, options)
+}
+
+/// Exact two-sided binomial p-value for a het's REF and ALT read counts
+/// against the expected ALT share (0.5 for a balanced het)
+#[napi]
+pub fn allelic_imbalance_test(ref_count: u32, alt_count: u32, expected_ratio: f64) -> f64 {
+    vcf_parser::allelic_imbalance_test(ref_count, alt_count, expected_ratio)
 }
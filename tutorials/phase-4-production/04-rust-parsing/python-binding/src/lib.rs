@@ -0,0 +1,740 @@
+//! Python Bindings for VCF Parser
+//!
+//! This module provides PyO3 bindings for the Rust VCF parser,
+//! enabling high-performance VCF parsing from Python. Parsing runs with
+//! the GIL released; Python objects are only built afterwards.
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyIOError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyIterator, PyList};
+use pyo3::IntoPyObjectExt;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Lines};
+use std::path::PathBuf;
+use std::sync::{Mutex, PoisonError};
+use vcf_parser::{
+    filter_expr, gzip,
+    types::{
+        ChromosomeStats, InfoValue, VcfHeader as RustVcfHeader, VcfRecord as RustVcfRecord,
+        VcfStats as RustVcfStats,
+    },
+    view::{filter_string, variant_type_label},
+    CompiledFilter, SampleView, VcfError as RustVcfError, VcfParser as RustParser, VcfPushParser,
+    VcfRecordView,
+};
+
+/// Records parsed per GIL release while iterating
+const RECORD_BATCH_SIZE: usize = 1000;
+
+create_exception!(
+    vcf_parser_py,
+    VcfError,
+    PyException,
+    "Invalid VCF input; `code`, `line` and `field` say what and where."
+);
+
+/// VCF record with properties mirroring the Rust type
+#[pyclass(name = "VcfRecord", module = "vcf_parser_py")]
+pub struct PyVcfRecord {
+    view: VcfRecordView,
+}
+
+#[pymethods]
+impl PyVcfRecord {
+    #[getter]
+    fn chrom(&self) -> &str {
+        &self.view.chrom
+    }
+
+    /// 1-based position
+    #[getter]
+    fn pos(&self) -> u64 {
+        self.view.pos
+    }
+
+    #[getter]
+    fn id(&self) -> Option<&str> {
+        self.view.id.as_deref()
+    }
+
+    #[getter]
+    fn reference(&self) -> &str {
+        &self.view.reference
+    }
+
+    #[getter]
+    fn alternate(&self) -> Vec<String> {
+        self.view.alternate.clone()
+    }
+
+    #[getter]
+    fn qual(&self) -> Option<f64> {
+        self.view.qual
+    }
+
+    /// "PASS", "." or the failed filters joined with ";"
+    #[getter]
+    fn filter(&self) -> &str {
+        &self.view.filter
+    }
+
+    /// One of "SNP", "INS", "DEL", "COMPLEX" and "OTHER"
+    #[getter]
+    fn variant_type(&self) -> &str {
+        &self.view.variant_type
+    }
+
+    /// `chrom:g.` HGVS name of each ALT allele, e.g. `chr1:g.201del`
+    #[getter]
+    fn hgvs_g(&self) -> Vec<String> {
+        self.view.hgvs_g.clone()
+    }
+
+    #[getter]
+    fn is_snp(&self) -> bool {
+        self.view.is_snp
+    }
+
+    #[getter]
+    fn is_insertion(&self) -> bool {
+        self.view.is_insertion
+    }
+
+    #[getter]
+    fn is_deletion(&self) -> bool {
+        self.view.is_deletion
+    }
+
+    /// INFO values as ints, floats, strings, lists or `True` for flags;
+    /// `None` when INFO parsing is disabled
+    #[getter]
+    fn info(&self, py: Python<'_>) -> PyResult<Option<Py<PyDict>>> {
+        let Some(info) = &self.view.info else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        for (key, value) in info {
+            dict.set_item(key, info_value(py, value)?)?;
+        }
+        Ok(Some(dict.unbind()))
+    }
+
+    /// Samples as dicts with `name`, `gt`, `phased` and `fields`; `None`
+    /// when sample parsing is disabled
+    #[getter]
+    fn samples(&self, py: Python<'_>) -> PyResult<Option<Py<PyList>>> {
+        let Some(samples) = &self.view.samples else {
+            return Ok(None);
+        };
+        let samples = samples
+            .iter()
+            .map(|sample| sample_dict(py, sample))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Some(PyList::new(py, samples)?.unbind()))
+    }
+
+    /// Every property as a plain dict, e.g. for `pandas.DataFrame`
+    fn to_dict(&self, py: Python<'_>) -> PyResult<Py<PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("chrom", self.chrom())?;
+        dict.set_item("pos", self.pos())?;
+        dict.set_item("id", self.id())?;
+        dict.set_item("reference", self.reference())?;
+        dict.set_item("alternate", self.alternate())?;
+        dict.set_item("qual", self.qual())?;
+        dict.set_item("filter", self.filter())?;
+        dict.set_item("variant_type", self.variant_type())?;
+        dict.set_item("hgvs_g", self.hgvs_g())?;
+        dict.set_item("is_snp", self.is_snp())?;
+        dict.set_item("is_insertion", self.is_insertion())?;
+        dict.set_item("is_deletion", self.is_deletion())?;
+        dict.set_item("info", self.info(py)?)?;
+        dict.set_item("samples", self.samples(py)?)?;
+        Ok(dict.unbind())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "VcfRecord({}:{} {}>{})",
+            self.view.chrom,
+            self.view.pos,
+            self.view.reference,
+            self.view.alternate.join(",")
+        )
+    }
+}
+
+/// VCF header
+#[pyclass(name = "VcfHeader", module = "vcf_parser_py")]
+pub struct PyVcfHeader {
+    header: RustVcfHeader,
+}
+
+#[pymethods]
+impl PyVcfHeader {
+    #[getter]
+    fn file_format(&self) -> &str {
+        &self.header.file_format
+    }
+
+    #[getter]
+    fn reference(&self) -> Option<&str> {
+        self.header.reference.as_deref()
+    }
+
+    #[getter]
+    fn samples(&self) -> Vec<String> {
+        self.header.samples.clone()
+    }
+
+    /// `##contig` lines as dicts with `id` and `length`
+    #[getter]
+    fn contigs(&self, py: Python<'_>) -> PyResult<Vec<Py<PyDict>>> {
+        self.header
+            .contigs
+            .iter()
+            .map(|c| {
+                let dict = PyDict::new(py);
+                dict.set_item("id", &c.id)?;
+                dict.set_item("length", c.length)?;
+                Ok(dict.unbind())
+            })
+            .collect()
+    }
+
+    /// `##INFO` lines as dicts with `id`, `number`, `type` and `description`
+    #[getter]
+    fn info_fields(&self, py: Python<'_>) -> PyResult<Vec<Py<PyDict>>> {
+        self.header
+            .info_fields
+            .iter()
+            .map(|f| field_dict(py, &f.id, &f.number, &f.field_type, &f.description))
+            .collect()
+    }
+
+    /// `##FORMAT` lines as dicts with `id`, `number`, `type` and `description`
+    #[getter]
+    fn format_fields(&self, py: Python<'_>) -> PyResult<Vec<Py<PyDict>>> {
+        self.header
+            .format_fields
+            .iter()
+            .map(|f| field_dict(py, &f.id, &f.number, &f.field_type, &f.description))
+            .collect()
+    }
+
+    /// `##FILTER` lines as dicts with `id` and `description`
+    #[getter]
+    fn filters(&self, py: Python<'_>) -> PyResult<Vec<Py<PyDict>>> {
+        self.header
+            .filters
+            .iter()
+            .map(|f| {
+                let dict = PyDict::new(py);
+                dict.set_item("id", &f.id)?;
+                dict.set_item("description", &f.description)?;
+                Ok(dict.unbind())
+            })
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "VcfHeader({}, {} samples)",
+            self.header.file_format,
+            self.header.samples.len()
+        )
+    }
+}
+
+/// Summary counts for a VCF
+#[pyclass(name = "VcfStats", module = "vcf_parser_py")]
+pub struct PyVcfStats {
+    #[pyo3(get)]
+    total_records: usize,
+    #[pyo3(get)]
+    snps: usize,
+    #[pyo3(get)]
+    insertions: usize,
+    #[pyo3(get)]
+    deletions: usize,
+    #[pyo3(get)]
+    complex: usize,
+    #[pyo3(get)]
+    passed_filter: usize,
+    #[pyo3(get)]
+    failed_filter: usize,
+    #[pyo3(get)]
+    chromosomes: Vec<String>,
+    #[pyo3(get)]
+    transitions: usize,
+    #[pyo3(get)]
+    transversions: usize,
+    /// Transitions / transversions; `None` without transversions
+    #[pyo3(get)]
+    ts_tv_ratio: Option<f64>,
+    per_chromosome: Vec<ChromosomeStats>,
+}
+
+#[pymethods]
+impl PyVcfStats {
+    /// Counts per chromosome as dicts with `chrom`, `records`, `snps`
+    /// and `indels`
+    #[getter]
+    fn per_chromosome(&self, py: Python<'_>) -> PyResult<Vec<Py<PyDict>>> {
+        self.per_chromosome
+            .iter()
+            .map(|c| {
+                let dict = PyDict::new(py);
+                dict.set_item("chrom", &c.chrom)?;
+                dict.set_item("records", c.records)?;
+                dict.set_item("snps", c.snps)?;
+                dict.set_item("indels", c.indels)?;
+                Ok(dict.unbind())
+            })
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "VcfStats(total_records={}, snps={}, insertions={}, deletions={})",
+            self.total_records, self.snps, self.insertions, self.deletions
+        )
+    }
+}
+
+impl From<RustVcfStats> for PyVcfStats {
+    fn from(stats: RustVcfStats) -> Self {
+        PyVcfStats {
+            ts_tv_ratio: stats.ts_tv_ratio(),
+            total_records: stats.total_records,
+            snps: stats.snps,
+            insertions: stats.insertions,
+            deletions: stats.deletions,
+            complex: stats.complex,
+            passed_filter: stats.passed_filter,
+            failed_filter: stats.failed_filter,
+            chromosomes: stats.chromosomes,
+            transitions: stats.transitions,
+            transversions: stats.transversions,
+            per_chromosome: stats.per_chromosome,
+        }
+    }
+}
+
+/// Result of `VcfParser.parse_file` / `parse_string`; iterating it yields
+/// the records
+#[pyclass(name = "ParseResult", module = "vcf_parser_py", get_all)]
+pub struct PyParseResult {
+    header: Py<PyVcfHeader>,
+    records: Py<PyList>,
+    stats: Py<PyVcfStats>,
+}
+
+#[pymethods]
+impl PyParseResult {
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.records.bind(py).len()
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        self.records.bind(py).as_any().try_iter()
+    }
+}
+
+/// Streaming record iterator returned by `VcfParser.iter_file`
+///
+/// Records are parsed in batches with the GIL released, so only a batch
+/// is held in memory at a time.
+#[pyclass(name = "RecordIterator", module = "vcf_parser_py")]
+pub struct PyRecordIterator {
+    /// Only ever borrowed mutably; the mutex just makes the class `Sync`
+    lines: Mutex<Lines<Box<dyn BufRead + Send>>>,
+    stream: VcfPushParser,
+    buffer: VecDeque<RustVcfRecord>,
+    filter: Option<CompiledFilter>,
+    parse_info: bool,
+    parse_samples: bool,
+}
+
+impl PyRecordIterator {
+    fn next_line(&mut self) -> Option<io::Result<String>> {
+        self.lines
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .next()
+    }
+
+    /// Push lines until `buffer` holds a batch or the input ends
+    fn fill(&mut self) -> Result<(), RustVcfError> {
+        while self.buffer.len() < RECORD_BATCH_SIZE {
+            let Some(line) = self.next_line() else {
+                return self.stream.finish();
+            };
+            let line = line.map_err(gzip::gzip_error)?;
+            if let Some(record) = self.stream.push_line(&line)? {
+                if self.filter.as_ref().is_none_or(|f| f.matches(&record)) {
+                    self.buffer.push_back(record);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl PyRecordIterator {
+    #[getter]
+    fn header(&self) -> PyVcfHeader {
+        PyVcfHeader {
+            header: self.stream.header().clone(),
+        }
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<'_, Self>, py: Python<'_>) -> PyResult<Option<PyVcfRecord>> {
+        if slf.buffer.is_empty() {
+            let this = &mut *slf;
+            py.allow_threads(|| this.fill()).map_err(vcf_error)?;
+        }
+
+        let (parse_info, parse_samples) = (slf.parse_info, slf.parse_samples);
+        Ok(slf.buffer.pop_front().map(|record| PyVcfRecord {
+            view: VcfRecordView::new(record, parse_info, parse_samples),
+        }))
+    }
+}
+
+/// High-performance VCF Parser
+///
+/// Plain and gzip/BGZF-compressed files are both accepted.
+#[pyclass(name = "VcfParser", module = "vcf_parser_py")]
+#[derive(Clone)]
+pub struct PyVcfParser {
+    #[pyo3(get, set)]
+    parse_info: bool,
+    #[pyo3(get, set)]
+    parse_samples: bool,
+    #[pyo3(get, set)]
+    skip_invalid: bool,
+    /// Fail once more than this many invalid records have been skipped
+    #[pyo3(get, set)]
+    max_invalid_records: Option<usize>,
+    /// Keep only records matching this expression, e.g.
+    /// `QUAL>30 && INFO/DP>=10`; applies to `parse_file`, `parse_string`
+    /// and `iter_file`
+    #[pyo3(get, set)]
+    filter_expr: Option<String>,
+}
+
+#[pymethods]
+impl PyVcfParser {
+    #[new]
+    #[pyo3(signature = (parse_info=true, parse_samples=true, skip_invalid=true, max_invalid_records=None, filter_expr=None))]
+    fn new(
+        parse_info: bool,
+        parse_samples: bool,
+        skip_invalid: bool,
+        max_invalid_records: Option<usize>,
+        filter_expr: Option<String>,
+    ) -> Self {
+        Self {
+            parse_info,
+            parse_samples,
+            skip_invalid,
+            max_invalid_records,
+            filter_expr,
+        }
+    }
+
+    /// Parser that skips INFO and sample parsing
+    #[staticmethod]
+    fn fast() -> Self {
+        Self::new(false, false, true, None, None)
+    }
+
+    /// Parse a VCF file
+    fn parse_file(&self, py: Python<'_>, path: PathBuf) -> PyResult<PyParseResult> {
+        let mut parser = self.rust_parser();
+        let parsed = py
+            .allow_threads(move || parser.parse(gzip::open_vcf(&path)?))
+            .map_err(vcf_error)?;
+        self.parse_result(py, parsed)
+    }
+
+    /// Parse VCF text
+    fn parse_string(&self, py: Python<'_>, content: &str) -> PyResult<PyParseResult> {
+        let mut parser = self.rust_parser();
+        let parsed = py
+            .allow_threads(|| parser.parse_str(content))
+            .map_err(vcf_error)?;
+        self.parse_result(py, parsed)
+    }
+
+    /// Iterate over the records of a file without loading it all
+    fn iter_file(&self, py: Python<'_>, path: PathBuf) -> PyResult<PyRecordIterator> {
+        let parser = self.rust_parser();
+        let mut iterator = py
+            .allow_threads(move || -> Result<_, RustVcfError> {
+                Ok(PyRecordIterator {
+                    lines: Mutex::new(gzip::open_vcf(&path)?.lines()),
+                    stream: VcfPushParser::new(parser),
+                    buffer: VecDeque::new(),
+                    filter: None,
+                    parse_info: self.parse_info,
+                    parse_samples: self.parse_samples,
+                })
+            })
+            .map_err(vcf_error)?;
+
+        // Read the header now so `iterator.header` is complete
+        while !iterator.stream.header_complete() {
+            let Some(line) = iterator.next_line() else {
+                iterator.stream.finish().map_err(vcf_error)?;
+                break;
+            };
+            let line = line.map_err(|e| vcf_error(gzip::gzip_error(e)))?;
+            iterator.stream.push_line(&line).map_err(vcf_error)?;
+        }
+        iterator.filter = self.compile_filter(iterator.stream.header())?;
+        Ok(iterator)
+    }
+
+    /// Statistics for a file, streamed without keeping any records
+    fn stats_file(&self, py: Python<'_>, path: PathBuf) -> PyResult<PyVcfStats> {
+        let stats = py
+            .allow_threads(move || -> Result<_, RustVcfError> {
+                let mut stream = VcfPushParser::new(RustParser::fast());
+                let mut stats = RustVcfStats::new();
+                for line in gzip::open_vcf(&path)?.lines() {
+                    let line = line.map_err(gzip::gzip_error)?;
+                    if let Some(record) = stream.push_line(&line)? {
+                        stats.update(&record);
+                    }
+                }
+                stream.finish()?;
+                Ok(stats)
+            })
+            .map_err(vcf_error)?;
+        Ok(stats.into())
+    }
+
+    /// Parse a file into a dict of equal-length column lists, ready for
+    /// `pandas.DataFrame(...)`
+    ///
+    /// Columns are `chrom`, `pos`, `id`, `reference`, `alternate` (joined
+    /// with ","), `qual` (NaN when missing), `filter`, `variant_type` and
+    /// `hgvs_g` (the HGVS name of each ALT, joined with ","), plus one per
+    /// requested INFO key holding its first value or `None`.
+    #[pyo3(signature = (path, info_columns=None))]
+    fn parse_columnar(
+        &self,
+        py: Python<'_>,
+        path: PathBuf,
+        info_columns: Option<Vec<String>>,
+    ) -> PyResult<Py<PyDict>> {
+        let info_columns = info_columns.unwrap_or_default();
+        let mut parser = self.rust_parser();
+        parser.parse_info = !info_columns.is_empty();
+        parser.parse_samples = false;
+
+        let (header, records) = py
+            .allow_threads(move || parser.parse(gzip::open_vcf(&path)?))
+            .map_err(vcf_error)?;
+
+        if let Some(missing) = info_columns
+            .iter()
+            .find(|key| !header.info_fields.iter().any(|f| &f.id == *key))
+        {
+            return Err(PyValueError::new_err(format!(
+                "INFO field '{}' is not defined in the header",
+                missing
+            )));
+        }
+
+        let column = |f: fn(&RustVcfRecord) -> String| records.iter().map(f).collect::<Vec<_>>();
+        let columns = PyDict::new(py);
+        columns.set_item("chrom", column(|r| r.chrom.clone()))?;
+        columns.set_item("pos", records.iter().map(|r| r.pos).collect::<Vec<_>>())?;
+        columns.set_item(
+            "id",
+            records.iter().map(|r| r.id.as_deref()).collect::<Vec<_>>(),
+        )?;
+        columns.set_item("reference", column(|r| r.reference.clone()))?;
+        columns.set_item("alternate", column(|r| r.alternate.join(",")))?;
+        columns.set_item(
+            "qual",
+            records
+                .iter()
+                .map(|r| r.qual.unwrap_or(f64::NAN))
+                .collect::<Vec<_>>(),
+        )?;
+        columns.set_item("filter", column(|r| filter_string(&r.filter)))?;
+        columns.set_item(
+            "variant_type",
+            column(|r| variant_type_label(r.variant_type()).to_string()),
+        )?;
+        columns.set_item("hgvs_g", column(|r| r.hgvs_g().join(",")))?;
+        for key in &info_columns {
+            let values = records
+                .iter()
+                .map(|r| match r.info.get(key.as_str()) {
+                    Some(value) => first_info_value(py, value),
+                    None => Ok(py.None()),
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            columns.set_item(key, values)?;
+        }
+
+        Ok(columns.unbind())
+    }
+}
+
+impl PyVcfParser {
+    /// Core parser configured with this parser's options
+    fn rust_parser(&self) -> RustParser {
+        let mut parser = RustParser::new();
+        parser.parse_info = self.parse_info;
+        parser.parse_samples = self.parse_samples;
+        parser.skip_invalid = self.skip_invalid;
+        parser.collect_warnings = false;
+        parser.max_invalid_records = self.max_invalid_records;
+
+        // The filter may need columns the caller does not want back; an
+        // invalid expression is reported by `compile_filter`
+        if let Some(expr) = self
+            .filter_expr
+            .as_deref()
+            .and_then(|expr| filter_expr::parse(expr).ok())
+        {
+            parser.parse_info |= expr.needs_info();
+            parser.parse_samples |= expr.needs_samples();
+        }
+        parser
+    }
+
+    /// `filter_expr` checked against `header`; `ValueError` if invalid
+    fn compile_filter(&self, header: &RustVcfHeader) -> PyResult<Option<CompiledFilter>> {
+        self.filter_expr
+            .as_deref()
+            .map(|expr| {
+                filter_expr::compile(expr, header).map_err(|e| PyValueError::new_err(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn parse_result(
+        &self,
+        py: Python<'_>,
+        (header, mut records): (RustVcfHeader, Vec<RustVcfRecord>),
+    ) -> PyResult<PyParseResult> {
+        if let Some(filter) = self.compile_filter(&header)? {
+            records.retain(|record| filter.matches(record));
+        }
+        let stats = vcf_parser::calculate_stats(&records);
+        let records = records
+            .into_iter()
+            .map(|record| {
+                Py::new(
+                    py,
+                    PyVcfRecord {
+                        view: VcfRecordView::new(record, self.parse_info, self.parse_samples),
+                    },
+                )
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(PyParseResult {
+            header: Py::new(py, PyVcfHeader { header })?,
+            records: PyList::new(py, records)?.unbind(),
+            stats: Py::new(py, PyVcfStats::from(stats))?,
+        })
+    }
+}
+
+/// Python exception for a core error: `OSError` for unreadable input,
+/// `VcfError` with `code`, `line` and `field` attributes otherwise
+fn vcf_error(error: RustVcfError) -> PyErr {
+    let error = match error {
+        RustVcfError::Io(e) => gzip::gzip_error(e),
+        e => e,
+    };
+    if matches!(error, RustVcfError::Io(_) | RustVcfError::TruncatedGzip) {
+        return PyIOError::new_err(format!("Failed to read file: {}", error));
+    }
+
+    Python::with_gil(|py| {
+        let err = VcfError::new_err(format!("Parse error: {}", error));
+        let value = err.value(py);
+        let attributes = value
+            .setattr("code", error.code())
+            .and_then(|_| value.setattr("line", error.line()))
+            .and_then(|_| value.setattr("field", error.field()));
+        match attributes {
+            Ok(()) => err,
+            Err(e) => e,
+        }
+    })
+}
+
+/// INFO value with natural Python types
+fn info_value(py: Python<'_>, value: &InfoValue) -> PyResult<PyObject> {
+    match value {
+        InfoValue::Flag => true.into_py_any(py),
+        InfoValue::Integer(i) => i.into_py_any(py),
+        InfoValue::Float(f) => f.into_py_any(py),
+        InfoValue::String(s) => s.into_py_any(py),
+        InfoValue::IntegerArray(v) => v.into_py_any(py),
+        InfoValue::FloatArray(v) => v.into_py_any(py),
+        InfoValue::StringArray(v) => v.into_py_any(py),
+    }
+}
+
+/// First value of a list INFO value, for one-value-per-row columns
+fn first_info_value(py: Python<'_>, value: &InfoValue) -> PyResult<PyObject> {
+    match value {
+        InfoValue::IntegerArray(v) => v.first().into_py_any(py),
+        InfoValue::FloatArray(v) => v.first().into_py_any(py),
+        InfoValue::StringArray(v) => v.first().into_py_any(py),
+        value => info_value(py, value),
+    }
+}
+
+fn sample_dict(py: Python<'_>, sample: &SampleView) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("name", &sample.name)?;
+    dict.set_item("gt", &sample.gt)?;
+    dict.set_item("phased", sample.phased)?;
+    dict.set_item("fields", &sample.fields)?;
+    Ok(dict.unbind())
+}
+
+fn field_dict(
+    py: Python<'_>,
+    id: &str,
+    number: &str,
+    field_type: &str,
+    description: &str,
+) -> PyResult<Py<PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("id", id)?;
+    dict.set_item("number", number)?;
+    dict.set_item("type", field_type)?;
+    dict.set_item("description", description)?;
+    Ok(dict.unbind())
+}
+
+#[pymodule]
+fn vcf_parser_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVcfParser>()?;
+    m.add_class::<PyVcfRecord>()?;
+    m.add_class::<PyVcfHeader>()?;
+    m.add_class::<PyVcfStats>()?;
+    m.add_class::<PyParseResult>()?;
+    m.add_class::<PyRecordIterator>()?;
+    m.add("VcfError", m.py().get_type::<VcfError>())?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+"""
+Tests for the Python binding
+
+Needs the built module: run `maturin develop` in python-binding/, then
+`pytest python-binding/tests`.
+"""
+
+import gzip
+import json
+import math
+from pathlib import Path
+
+import pytest
+
+import vcf_parser_py
+from vcf_parser_py import VcfError, VcfParser
+
+DATA_DIR = Path(__file__).resolve().parents[2] / "tests" / "data"
+
+SAMPLE_VCF = """##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description="Total Depth">
+##FORMAT=<ID=GT,Number=1,Type=String,Description="Genotype">
+#CHROM	POS	ID	REF	ALT	QUAL	FILTER	INFO	FORMAT	SAMPLE1	SAMPLE2
+chr1	100	rs123	A	G	30	PASS	DP=50	GT	0/1	1/1
+chr1	200	.	AT	A	40	PASS	DP=60	GT	0/0	0/1
+chr2	300	rs456	C	T,G	50	q10	DP=70	GT	1/2	0/1
+chr2	400	rs789	G	GATC	60	PASS	DP=80	GT	0/1	1/1
+chrX	500	.	AGTC	A	.	PASS	DP=90	GT	0/0	0/1
+"""
+
+
+@pytest.fixture
+def vcf_path(tmp_path):
+    path = tmp_path / "sample.vcf"
+    path.write_text(SAMPLE_VCF)
+    return path
+
+
+def test_parse_file(vcf_path):
+    result = VcfParser().parse_file(str(vcf_path))
+
+    assert len(result) == 5
+    assert result.header.samples == ["SAMPLE1", "SAMPLE2"]
+    assert result.header.info_fields[0]["id"] == "DP"
+    assert result.stats.total_records == 5
+    assert result.stats.snps == 2
+
+    record = result.records[0]
+    assert (record.chrom, record.pos, record.id) == ("chr1", 100, "rs123")
+    assert record.variant_type == "SNP"
+    assert record.hgvs_g == ["chr1:g.100A>G"]
+    assert record.info == {"DP": 50}
+    assert record.samples[1] == {"name": "SAMPLE2", "gt": "1/1", "phased": False, "fields": {}}
+    assert result.records[2].filter == "q10"
+    assert result.records[4].qual is None
+
+
+def test_parse_gzip_file(tmp_path):
+    path = tmp_path / "sample.vcf.gz"
+    path.write_bytes(gzip.compress(SAMPLE_VCF.encode()))
+
+    assert len(VcfParser().parse_file(str(path))) == 5
+
+
+def test_records_match_shared_snapshot():
+    result = VcfParser().parse_string((DATA_DIR / "shared-records.vcf").read_text())
+    expected = json.loads((DATA_DIR / "shared-records.json").read_text())
+
+    def without_none(value):
+        if isinstance(value, dict):
+            return {k: without_none(v) for k, v in value.items() if v is not None}
+        if isinstance(value, list):
+            return [without_none(v) for v in value]
+        return value
+
+    assert [without_none(r.to_dict()) for r in result] == expected
+
+
+def test_fast_parser_skips_info_and_samples(vcf_path):
+    record = VcfParser.fast().parse_file(str(vcf_path)).records[0]
+
+    assert record.info is None
+    assert record.samples is None
+
+
+def test_iter_file_streams_records(vcf_path):
+    records = VcfParser().iter_file(str(vcf_path))
+
+    assert records.header.samples == ["SAMPLE1", "SAMPLE2"]
+    assert [r.pos for r in records] == [100, 200, 300, 400, 500]
+
+
+def test_stats_file(vcf_path):
+    stats = VcfParser().stats_file(str(vcf_path))
+
+    assert stats.total_records == 5
+    assert stats.chromosomes == ["chr1", "chr2", "chrX"]
+    assert stats.failed_filter == 1
+    assert [c["records"] for c in stats.per_chromosome] == [2, 2, 1]
+
+
+def test_parse_columnar(vcf_path):
+    columns = VcfParser().parse_columnar(str(vcf_path), info_columns=["DP"])
+
+    assert columns["pos"] == [100, 200, 300, 400, 500]
+    assert columns["alternate"][2] == "T,G"
+    assert columns["hgvs_g"][1:4] == [
+        "chr1:g.201del",
+        "chr2:g.300C>T,chr2:g.300C>G",
+        "chr2:g.400_401insATC",
+    ]
+    assert columns["DP"] == [50, 60, 70, 80, 90]
+    assert math.isnan(columns["qual"][4])
+
+    with pytest.raises(ValueError):
+        VcfParser().parse_columnar(str(vcf_path), info_columns=["AF"])
+
+
+def test_filter_expr(vcf_path):
+    parser = VcfParser.fast()
+    parser.filter_expr = 'INFO/DP>=60 && GT[1]="het" && FILTER="PASS"'
+
+    result = parser.parse_file(str(vcf_path))
+    assert [r.pos for r in result] == [200, 500]
+    assert result.stats.total_records == 2
+    assert result.records[0].info is None
+    assert [r.pos for r in parser.iter_file(str(vcf_path))] == [200, 500]
+
+    parser.filter_expr = "QUAL>30 && INFO/AF>0.1"
+    with pytest.raises(ValueError, match="column 12: INFO/AF is not defined"):
+        parser.parse_file(str(vcf_path))
+
+
+def test_invalid_record_raises_vcf_error():
+    content = SAMPLE_VCF + "chr3\tnot_a_number\t.\tA\tG\t10\tPASS\t.\n"
+
+    with pytest.raises(VcfError) as excinfo:
+        VcfParser(skip_invalid=False).parse_string(content)
+
+    assert excinfo.value.line == 10
+    assert excinfo.value.code == "INVALID_POSITION"
+    assert len(VcfParser().parse_string(content)) == 5
+
+
+def test_missing_file_raises_os_error(tmp_path):
+    with pytest.raises(OSError):
+        VcfParser().parse_file(str(tmp_path / "missing.vcf"))
+
+
+def test_module_exports():
+    assert issubclass(vcf_parser_py.VcfError, Exception)
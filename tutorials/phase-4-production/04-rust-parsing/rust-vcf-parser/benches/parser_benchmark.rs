@@ -0,0 +1,111 @@
+//! Parser throughput on synthetic VCF text
+//!
+//! Run with `cargo bench -p vcf-parser`; compare against a saved baseline
+//! with `cargo bench -p vcf-parser -- --save-baseline before` on the old
+//! code and `-- --baseline before` on the new. Inputs come from
+//! `test_support::gen_vcf`, so every run parses the same text.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use vcf_parser::parser::VcfIterator;
+use vcf_parser::test_support::gen_vcf;
+use vcf_parser::{calculate_stats, calculate_stats_parallel, VcfParser};
+
+const RECORDS: usize = 10_000;
+const SEED: u64 = 0xBE7C;
+
+/// Full and fast parses over a sites-only file and one with 10 samples
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_str");
+    group.sample_size(20);
+
+    for samples in [0, 10] {
+        let vcf = gen_vcf(RECORDS, samples, 8, SEED);
+        group.throughput(Throughput::Bytes(vcf.len() as u64));
+        group.bench_function(format!("{}_samples", samples), |b| {
+            b.iter(|| VcfParser::new().parse_str(black_box(&vcf)).unwrap())
+        });
+        group.bench_function(format!("{}_samples_fast", samples), |b| {
+            b.iter(|| VcfParser::fast().parse_str(black_box(&vcf)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+/// Record-at-a-time iteration, without collecting the records
+fn bench_streaming(c: &mut Criterion) {
+    let mut group = c.benchmark_group("streaming");
+    group.sample_size(20);
+
+    let vcf = gen_vcf(RECORDS, 10, 8, SEED);
+    group.throughput(Throughput::Bytes(vcf.len() as u64));
+    group.bench_function("iterator_10_samples", |b| {
+        b.iter(|| {
+            for record in VcfIterator::new(black_box(vcf.as_bytes())).unwrap() {
+                black_box(record.unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+/// INFO-heavy lines, where per-key allocation and hashing dominate
+fn bench_info_heavy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("info_keys");
+    group.sample_size(20);
+
+    for info_keys in [8, 20, 50] {
+        let vcf = gen_vcf(RECORDS, 0, info_keys, SEED);
+        group.throughput(Throughput::Bytes(vcf.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(info_keys), &vcf, |b, vcf| {
+            b.iter(|| VcfParser::new().parse_str(black_box(vcf)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+/// Many-sample lines, where genotype columns dominate
+fn bench_many_samples(c: &mut Criterion) {
+    let mut group = c.benchmark_group("samples");
+    group.sample_size(10);
+
+    for samples in [100, 1000] {
+        let vcf = gen_vcf(RECORDS / 10, samples, 8, SEED);
+        group.throughput(Throughput::Bytes(vcf.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(samples), &vcf, |b, vcf| {
+            b.iter(|| VcfParser::new().parse_str(black_box(vcf)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+/// Stats over parsed records, serially and on the rayon pool
+fn bench_stats(c: &mut Criterion) {
+    let mut group = c.benchmark_group("stats");
+
+    let (_, records) = VcfParser::new()
+        .parse_str(&gen_vcf(RECORDS * 10, 0, 0, SEED))
+        .unwrap();
+    group.throughput(Throughput::Elements(records.len() as u64));
+    group.bench_function("serial", |b| {
+        b.iter(|| calculate_stats(black_box(&records)))
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| calculate_stats_parallel(black_box(&records)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_parse,
+    bench_streaming,
+    bench_info_heavy,
+    bench_many_samples,
+    bench_stats
+);
+criterion_main!(benches);
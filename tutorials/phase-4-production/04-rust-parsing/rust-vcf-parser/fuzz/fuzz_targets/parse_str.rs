@@ -0,0 +1,29 @@
+//! Whole-file parsing of arbitrary text, strict and lenient, plus the
+//! record accessors and statistics a caller would run afterwards
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vcf_parser::{calculate_stats, VcfParser};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let _ = VcfParser::new().parse_str(text);
+
+    let mut lenient = VcfParser::new();
+    lenient.skip_invalid = true;
+    if let Ok((_, records)) = lenient.parse_str(text) {
+        for record in &records {
+            let _ = record.variant_type();
+            for sample in &record.samples {
+                if let Some(gt) = &sample.genotype {
+                    let _ = (gt.is_hom_ref(), gt.is_het(), gt.is_hom_alt());
+                }
+            }
+        }
+        let _ = calculate_stats(&records).ts_tv_ratio();
+    }
+});
@@ -0,0 +1,26 @@
+//! Streaming input: raw bytes through `VcfIterator` (including invalid
+//! UTF-8 and gzip detection), and the same text fed to `VcfPushParser`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use vcf_parser::parser::VcfIterator;
+use vcf_parser::{gzip, VcfParser, VcfPushParser};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(reader) = gzip::auto_decompress(data) {
+        if let Ok(records) = VcfIterator::new(reader) {
+            records.take(10_000).for_each(drop);
+        }
+    }
+
+    let mut parser = VcfParser::new();
+    parser.skip_invalid = true;
+    let mut push = VcfPushParser::new(parser);
+    for line in String::from_utf8_lossy(data).split('\n') {
+        if push.push_line(line).is_err() {
+            break;
+        }
+    }
+    let _ = push.finish();
+});
@@ -0,0 +1,143 @@
+/* Generated by cbindgen from rust-vcf-parser/src/ffi.rs. Do not edit. */
+
+#ifndef VCF_PARSER_H
+#define VCF_PARSER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status returned by fallible C API calls. Values are stable.
+enum VcfErrorCode
+#ifdef __cplusplus
+  : int32_t
+#endif // __cplusplus
+ {
+  VCF_ERROR_CODE_OK = 0,
+  // A required pointer argument was NULL
+  VCF_ERROR_CODE_NULL_POINTER = 1,
+  // An argument was invalid, e.g. a path that is not UTF-8
+  VCF_ERROR_CODE_INVALID_ARGUMENT = 2,
+  // The file could not be read
+  VCF_ERROR_CODE_IO = 3,
+  // The file is not valid VCF
+  VCF_ERROR_CODE_PARSE = 4,
+  // A record index was past the end
+  VCF_ERROR_CODE_OUT_OF_RANGE = 5,
+  // The parser panicked; this is a bug
+  VCF_ERROR_CODE_PANIC = 6,
+};
+#ifndef __cplusplus
+typedef int32_t VcfErrorCode;
+#endif // __cplusplus
+
+// Parsed VCF file
+typedef struct VcfHandle VcfHandle;
+
+// Summary counts filled in by `vcf_stats`
+typedef struct VcfStats {
+  uint64_t total_records;
+  uint64_t snps;
+  uint64_t insertions;
+  uint64_t deletions;
+  uint64_t complex;
+  uint64_t passed_filter;
+  uint64_t failed_filter;
+  // Number of distinct chromosomes
+  uint64_t chromosomes;
+  uint64_t transitions;
+  uint64_t transversions;
+} VcfStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parse a plain or gzipped VCF file
+//
+// On success `*out_handle` receives a handle to free with `vcf_free`;
+// on failure it is set to NULL.
+//
+// # Safety
+// `path` must be NULL or a NUL-terminated string, and `out_handle` NULL
+// or valid for a pointer write.
+VcfErrorCode vcf_parse_file(const char *path, struct VcfHandle **out_handle);
+
+// Number of records in `handle`, or 0 if it is NULL
+//
+// # Safety
+// `handle` must be NULL or a live handle.
+size_t vcf_record_count(const struct VcfHandle *handle);
+
+// CHROM of record `index`
+//
+// # Safety
+// `handle` must be NULL or a live handle; `buf` NULL or valid for
+// `buflen` bytes.
+size_t vcf_record_chrom(const struct VcfHandle *handle, size_t index, char *buf, size_t buflen);
+
+// ID of record `index`, "." when missing
+//
+// # Safety
+// As for `vcf_record_chrom`.
+size_t vcf_record_id(const struct VcfHandle *handle, size_t index, char *buf, size_t buflen);
+
+// REF of record `index`
+//
+// # Safety
+// As for `vcf_record_chrom`.
+size_t vcf_record_ref(const struct VcfHandle *handle, size_t index, char *buf, size_t buflen);
+
+// ALT alleles of record `index`, joined with ","
+//
+// # Safety
+// As for `vcf_record_chrom`.
+size_t vcf_record_alt(const struct VcfHandle *handle, size_t index, char *buf, size_t buflen);
+
+// FILTER of record `index`: "PASS", "." or the failed filters joined
+// with ";"
+//
+// # Safety
+// As for `vcf_record_chrom`.
+size_t vcf_record_filter(const struct VcfHandle *handle, size_t index, char *buf, size_t buflen);
+
+// 1-based POS of record `index`, or 0 on error
+//
+// # Safety
+// `handle` must be NULL or a live handle.
+uint64_t vcf_record_pos(const struct VcfHandle *handle, size_t index);
+
+// QUAL of record `index`; NaN when missing or on error
+//
+// # Safety
+// `handle` must be NULL or a live handle.
+double vcf_record_qual(const struct VcfHandle *handle, size_t index);
+
+// Fill `*out_stats` with summary counts for `handle`
+//
+// # Safety
+// `handle` must be NULL or a live handle, and `out_stats` NULL or valid
+// for a write.
+VcfErrorCode vcf_stats(const struct VcfHandle *handle, struct VcfStats *out_stats);
+
+// Release a handle from `vcf_parse_file`; NULL is ignored
+//
+// # Safety
+// `handle` must be NULL or a live handle, and is dangling afterwards.
+void vcf_free(struct VcfHandle *handle);
+
+// Message for the last failed call on this thread, "" if it succeeded
+//
+// Returns the buffer size needed, like the string accessors.
+//
+// # Safety
+// `buf` must be NULL or valid for `buflen` bytes.
+size_t vcf_last_error_message(char *buf, size_t buflen);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* VCF_PARSER_H */
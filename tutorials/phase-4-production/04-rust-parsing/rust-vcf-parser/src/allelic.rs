@@ -0,0 +1,207 @@
+//! Allelic Imbalance
+//!
+//! Exact binomial tests of a het call's read support against the
+//! expected allele ratio, 0.5 for a balanced germline het. Skewed
+//! support points at allele-specific expression, copy-number changes or
+//! mapping bias. The log factorials here are also what the Fisher's exact
+//! test in the WASM statistics crate uses.
+
+use crate::types::VcfRecord;
+use std::sync::OnceLock;
+
+/// Hets with fewer reads for their two alleles than this are not tested
+pub const DEFAULT_MIN_ALLELIC_DEPTH: u32 = 10;
+
+/// `ln n!` is tabulated below this and taken from Stirling's series above
+const FACTORIAL_TABLE_SIZE: usize = 256;
+
+/// Relative tolerance for outcomes as likely as the observed one, as in
+/// `scipy.stats.binomtest`
+const RELATIVE_TOLERANCE: f64 = 1.0 + 1e-7;
+
+/// `ln n!`, to full double precision
+pub fn ln_factorial(n: u64) -> f64 {
+    static TABLE: OnceLock<[f64; FACTORIAL_TABLE_SIZE]> = OnceLock::new();
+    if n < FACTORIAL_TABLE_SIZE as u64 {
+        let table = TABLE.get_or_init(|| {
+            let mut table = [0.0; FACTORIAL_TABLE_SIZE];
+            for k in 2..FACTORIAL_TABLE_SIZE {
+                table[k] = table[k - 1] + (k as f64).ln();
+            }
+            table
+        });
+        return table[n as usize];
+    }
+
+    // The first omitted term is below 1e-16 from n = 256
+    let n = n as f64;
+    let series = 1.0 / (12.0 * n) - 1.0 / (360.0 * n.powi(3)) + 1.0 / (1260.0 * n.powi(5));
+    (n + 0.5) * n.ln() - n + 0.5 * (2.0 * std::f64::consts::PI).ln() + series
+}
+
+/// Exact two-sided binomial p-value for `alt_count` ALT reads out of
+/// `ref_count + alt_count` when a read is ALT with probability
+/// `expected_ratio`
+///
+/// Sums every outcome no more likely than the observed one, as
+/// `scipy.stats.binomtest` does. No reads give 1; a ratio outside 0..=1
+/// gives NaN.
+pub fn allelic_imbalance_test(ref_count: u32, alt_count: u32, expected_ratio: f64) -> f64 {
+    if !(0.0..=1.0).contains(&expected_ratio) {
+        return f64::NAN;
+    }
+    let n = u64::from(ref_count) + u64::from(alt_count);
+    let k = u64::from(alt_count);
+    if expected_ratio == 0.0 || expected_ratio == 1.0 {
+        // Only one outcome is possible
+        let expected = if expected_ratio == 0.0 { 0 } else { n };
+        return if k == expected { 1.0 } else { 0.0 };
+    }
+    if k as f64 == n as f64 * expected_ratio {
+        // Every outcome is at most as likely as the mean
+        return 1.0;
+    }
+
+    let ln_p = expected_ratio.ln();
+    let ln_q = (-expected_ratio).ln_1p();
+    let ln_n = ln_factorial(n);
+    let ln_pmf = |i: u64| {
+        ln_n - ln_factorial(i) - ln_factorial(n - i) + i as f64 * ln_p + (n - i) as f64 * ln_q
+    };
+
+    let cutoff = ln_pmf(k) + RELATIVE_TOLERANCE.ln();
+    let p: f64 = (0..=n)
+        .map(ln_pmf)
+        .filter(|&ln| ln <= cutoff)
+        .map(f64::exp)
+        .sum();
+    p.min(1.0)
+}
+
+/// [`allelic_imbalance_test`] for each sample of `record`, skipping hets
+/// with fewer than [`DEFAULT_MIN_ALLELIC_DEPTH`] reads
+pub fn record_allelic_imbalance(
+    record: &VcfRecord,
+    expected_ratio: f64,
+) -> Vec<(String, Option<f64>)> {
+    record_allelic_imbalance_with_min_depth(record, expected_ratio, DEFAULT_MIN_ALLELIC_DEPTH)
+}
+
+/// [`allelic_imbalance_test`] for each sample of `record`, in column order
+///
+/// A diploid het is tested on the AD of its two called alleles, the
+/// higher-numbered one counting as ALT. Samples that are not hets, lack
+/// AD for either allele or have fewer than `min_depth` reads for the two
+/// get `None`, which keeps them out of a multiple-testing correction.
+pub fn record_allelic_imbalance_with_min_depth(
+    record: &VcfRecord,
+    expected_ratio: f64,
+    min_depth: u32,
+) -> Vec<(String, Option<f64>)> {
+    record
+        .samples
+        .iter()
+        .map(|sample| {
+            let p = (|| {
+                let [Some(a), Some(b)] = sample.genotype.as_ref()?.alleles[..] else {
+                    return None;
+                };
+                if a == b {
+                    return None;
+                }
+                let depths = sample.int_array_field("AD")?;
+                let count = |allele: u8| -> Option<u32> {
+                    u32::try_from((*depths.get(allele as usize)?)?).ok()
+                };
+                let (ref_count, alt_count) = (count(a.min(b))?, count(a.max(b))?);
+                (u64::from(ref_count) + u64::from(alt_count) >= u64::from(min_depth))
+                    .then(|| allelic_imbalance_test(ref_count, alt_count, expected_ratio))
+            })();
+            (sample.name.clone(), p)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    #[test]
+    fn test_ln_factorial() {
+        assert_eq!(ln_factorial(0), 0.0);
+        assert_eq!(ln_factorial(1), 0.0);
+        assert!((ln_factorial(10) - 3_628_800f64.ln()).abs() < 1e-12);
+        // Either side of the switch to Stirling's series
+        for n in [255, 256, 257, 1000] {
+            let sum: f64 = (2..=n).map(|k| (k as f64).ln()).sum();
+            assert!((ln_factorial(n) - sum).abs() < 1e-9 * sum, "{}", n);
+        }
+    }
+
+    #[test]
+    fn test_binomial_matches_scipy() {
+        // scipy.stats.binomtest(alt, ref + alt, ratio).pvalue
+        let cases = [
+            (5, 5, 0.5, 1.0),
+            (7, 3, 0.5, 0.34375),
+            (10, 0, 0.5, 0.001_953_125),
+            (13, 7, 0.5, 0.263_175_964_355_468_75),
+            (7, 13, 0.5, 0.263_175_964_355_468_75),
+            (28, 12, 0.5, 0.016_589_003_374_974_71),
+            (12, 3, 0.3, 0.575_246_487_682_684),
+            (150, 50, 0.5, 8.393_020_875_604_761e-13),
+            (28, 2, 0.1, 0.763_912_067_767_657_4),
+            (21, 9, 0.1, 0.002_019_829_005_258_299_8),
+            (260, 240, 0.5, 0.395_510_771_434_857_8),
+            (499, 1, 0.5, 3.061_046_236_226_604e-148),
+        ];
+        for (ref_count, alt_count, ratio, expected) in cases {
+            let p = allelic_imbalance_test(ref_count, alt_count, ratio);
+            assert!(
+                (p - expected).abs() <= 1e-9 * expected,
+                "{} {} {}: {} != {}",
+                ref_count,
+                alt_count,
+                ratio,
+                p,
+                expected
+            );
+        }
+
+        // 50/50 is symmetric in the two counts
+        for (r, a) in [(3, 9), (0, 4), (17, 30)] {
+            assert_eq!(
+                allelic_imbalance_test(r, a, 0.5),
+                allelic_imbalance_test(a, r, 0.5)
+            );
+        }
+        assert_eq!(allelic_imbalance_test(0, 0, 0.5), 1.0);
+        assert_eq!(allelic_imbalance_test(4, 0, 0.0), 1.0);
+        assert_eq!(allelic_imbalance_test(4, 1, 0.0), 0.0);
+        assert!(allelic_imbalance_test(4, 1, 1.5).is_nan());
+    }
+
+    #[test]
+    fn test_record_allelic_imbalance() {
+        let vcf = "##fileformat=VCFv4.2\n\
+                   #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3\tS4\tS5\tS6\n\
+                   chr1\t100\t.\tA\tG,T\t50\tPASS\t.\tGT:AD\t\
+                   0/1:28,12,0\t1/1:0,30,0\t0/1:4,3,0\t1/2:0,7,13\t0/1:.\t0|2:10,0,10\n";
+        let (_, records) = VcfParser::new().parse_str(vcf).unwrap();
+        let results = record_allelic_imbalance(&records[0], 0.5);
+
+        let names: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["S1", "S2", "S3", "S4", "S5", "S6"]);
+        let p: Vec<Option<f64>> = results.iter().map(|(_, p)| *p).collect();
+        assert_eq!(p[0], Some(allelic_imbalance_test(28, 12, 0.5)));
+        // Hom-alt, too few reads and no AD are skipped
+        assert_eq!((p[1], p[2], p[4]), (None, None, None));
+        // Alleles 1 and 2, with allele 2 as ALT
+        assert_eq!(p[3], Some(allelic_imbalance_test(7, 13, 0.5)));
+        assert_eq!(p[5], Some(1.0));
+
+        let low = record_allelic_imbalance_with_min_depth(&records[0], 0.5, 5);
+        assert!((low[2].1.unwrap() - 1.0).abs() < 1e-12);
+    }
+}
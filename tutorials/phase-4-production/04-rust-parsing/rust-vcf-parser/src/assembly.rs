@@ -0,0 +1,349 @@
+//! Genome Assemblies
+//!
+//! A heuristic check of which human assembly (GRCh37 or GRCh38) a file's
+//! coordinates refer to, and a hook for remapping records between
+//! assemblies with a liftover implementation supplied by the caller.
+
+use crate::types::{VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Human reference assemblies told apart by [`detect_assembly`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Assembly {
+    GRCh37,
+    GRCh38,
+    Unknown,
+}
+
+impl fmt::Display for Assembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Assembly::GRCh37 => "GRCh37",
+            Assembly::GRCh38 => "GRCh38",
+            Assembly::Unknown => "unknown",
+        })
+    }
+}
+
+/// Result of [`detect_assembly`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AssemblyGuess {
+    pub assembly: Assembly,
+    /// 0 (no evidence) to 1 (plenty of evidence, none conflicting)
+    pub confidence: f64,
+    /// What the guess was based on, one line per piece of evidence
+    pub evidence: Vec<String>,
+}
+
+/// Primary-chromosome lengths: (name without `chr`, GRCh37, GRCh38)
+///
+/// chrM is left out; it is 16 569 bp in both.
+const CONTIG_LENGTHS: [(&str, u64, u64); 24] = [
+    ("1", 249_250_621, 248_956_422),
+    ("2", 243_199_373, 242_193_529),
+    ("3", 198_022_430, 198_295_559),
+    ("4", 191_154_276, 190_214_555),
+    ("5", 180_915_260, 181_538_259),
+    ("6", 171_115_067, 170_805_979),
+    ("7", 159_138_663, 159_345_973),
+    ("8", 146_364_022, 145_138_636),
+    ("9", 141_213_431, 138_394_717),
+    ("10", 135_534_747, 133_797_422),
+    ("11", 135_006_516, 135_086_622),
+    ("12", 133_851_895, 133_275_309),
+    ("13", 115_169_878, 114_364_328),
+    ("14", 107_349_540, 107_043_718),
+    ("15", 102_531_392, 101_991_189),
+    ("16", 90_354_753, 90_338_345),
+    ("17", 81_195_210, 83_257_441),
+    ("18", 78_077_248, 80_373_285),
+    ("19", 59_128_983, 58_617_616),
+    ("20", 63_025_520, 64_444_167),
+    ("21", 48_129_895, 46_709_983),
+    ("22", 51_304_566, 50_818_468),
+    ("X", 155_270_560, 156_040_895),
+    ("Y", 59_373_566, 57_227_415),
+];
+
+/// Names in `##reference` or contig `assembly=` values, lowercase
+const GRCH37_NAMES: [&str; 6] = ["grch37", "hg19", "b37", "hs37d5", "g1k_v37", "37.p13"];
+const GRCH38_NAMES: [&str; 5] = ["grch38", "hg38", "b38", "gca_000001405.15", "38.p"];
+
+/// Votes a `##reference` or `assembly=` name counts for; a contig length
+/// counts for one
+const NAME_WEIGHT: usize = 3;
+
+/// Votes needed for full confidence
+const CONFIDENT_VOTES: usize = 6;
+
+/// Guess the assembly from the `##reference` line, `assembly=` tags on
+/// contig lines and the lengths of the primary chromosomes
+///
+/// Each contig whose length matches one assembly is a vote for it, and a
+/// recognised assembly name is worth three. Confidence is the winner's
+/// share of the votes, scaled down when there are fewer than six; a tie or
+/// no evidence gives [`Assembly::Unknown`].
+pub fn detect_assembly(header: &VcfHeader) -> AssemblyGuess {
+    let mut votes = [0usize; 2];
+    let mut evidence = Vec::new();
+
+    if let Some(reference) = &header.reference {
+        if let Some(assembly) = assembly_named(reference) {
+            votes[index(assembly)] += NAME_WEIGHT;
+            evidence.push(format!("##reference={} names {}", reference, assembly));
+        }
+    }
+
+    // ContigInfo keeps only ID and length, so `assembly=` comes from the
+    // raw lines; one vote per distinct name
+    let mut tagged = Vec::new();
+    for line in header
+        .meta_lines
+        .iter()
+        .filter(|l| l.starts_with("##contig="))
+    {
+        let Some(tag) = line
+            .split(['<', ',', '>'])
+            .find_map(|f| f.strip_prefix("assembly="))
+        else {
+            continue;
+        };
+        if let Some(assembly) = assembly_named(tag) {
+            if !tagged.contains(&tag) {
+                tagged.push(tag);
+                votes[index(assembly)] += NAME_WEIGHT;
+                evidence.push(format!("contig assembly={} names {}", tag, assembly));
+            }
+        }
+    }
+
+    for contig in &header.contigs {
+        let (Some(name), Some(length)) = (primary_name(&contig.id), contig.length) else {
+            continue;
+        };
+        let Some(&(_, grch37, grch38)) = CONTIG_LENGTHS.iter().find(|(n, _, _)| *n == name) else {
+            continue;
+        };
+        let assembly = if length == grch37 {
+            Assembly::GRCh37
+        } else if length == grch38 {
+            Assembly::GRCh38
+        } else {
+            continue;
+        };
+        votes[index(assembly)] += 1;
+        evidence.push(format!(
+            "{} length {} matches {}",
+            contig.id, length, assembly
+        ));
+    }
+
+    let total = votes[0] + votes[1];
+    let (assembly, winner) = match votes[0].cmp(&votes[1]) {
+        _ if total == 0 => (Assembly::Unknown, 0),
+        std::cmp::Ordering::Greater => (Assembly::GRCh37, votes[0]),
+        std::cmp::Ordering::Less => (Assembly::GRCh38, votes[1]),
+        std::cmp::Ordering::Equal => (Assembly::Unknown, 0),
+    };
+    let confidence = if winner == 0 {
+        0.0
+    } else {
+        let support = total.min(CONFIDENT_VOTES) as f64 / CONFIDENT_VOTES as f64;
+        winner as f64 / total as f64 * support
+    };
+
+    AssemblyGuess {
+        assembly,
+        confidence,
+        evidence,
+    }
+}
+
+fn index(assembly: Assembly) -> usize {
+    match assembly {
+        Assembly::GRCh38 => 1,
+        _ => 0,
+    }
+}
+
+/// Assembly named anywhere in `text`, if exactly one is
+fn assembly_named(text: &str) -> Option<Assembly> {
+    let text = text.to_ascii_lowercase();
+    let is_37 = GRCH37_NAMES.iter().any(|name| text.contains(name));
+    let is_38 = GRCH38_NAMES.iter().any(|name| text.contains(name));
+    match (is_37, is_38) {
+        (true, false) => Some(Assembly::GRCh37),
+        (false, true) => Some(Assembly::GRCh38),
+        _ => None,
+    }
+}
+
+/// Chromosome name without a `chr` prefix, for primary chromosomes
+fn primary_name(id: &str) -> Option<&str> {
+    let name = id.strip_prefix("chr").unwrap_or(id);
+    CONTIG_LENGTHS
+        .iter()
+        .any(|(n, _, _)| *n == name)
+        .then_some(name)
+}
+
+/// Maps positions from one assembly to another
+///
+/// Implement this over a chain file (or anything else) to lift records
+/// over with [`remap_records`]; this crate ships no liftover data.
+pub trait CoordinateMapper {
+    /// New chromosome and 1-based position, or `None` if `pos` on `chrom`
+    /// has no counterpart
+    fn map(&self, chrom: &str, pos: u64) -> Option<(String, u64)>;
+}
+
+/// Outcome of [`remap_records`]
+#[derive(Debug, Clone, Default)]
+pub struct RemapSummary {
+    /// Records given a new position
+    pub mapped: usize,
+    /// Mapped records that also moved to a different chromosome
+    pub changed_chrom: usize,
+    /// Records the mapper could not place, removed from the input
+    pub rejected: Vec<VcfRecord>,
+}
+
+/// Rewrite CHROM and POS of every record through `mapper`
+///
+/// Records that cannot be mapped are moved to [`RemapSummary::rejected`];
+/// the rest keep their order. REF and ALT are left untouched, so records
+/// in regions where the two assemblies differ in sequence (or where the
+/// mapping reverses strand) will not match the new reference.
+pub fn remap_records(records: &mut Vec<VcfRecord>, mapper: &dyn CoordinateMapper) -> RemapSummary {
+    let mut summary = RemapSummary::default();
+
+    let mut kept = Vec::with_capacity(records.len());
+    for mut record in records.drain(..) {
+        match mapper.map(&record.chrom, record.pos) {
+            Some((chrom, pos)) => {
+                if chrom != record.chrom {
+                    summary.changed_chrom += 1;
+                    record.chrom = chrom;
+                }
+                record.pos = pos;
+                summary.mapped += 1;
+                kept.push(record);
+            }
+            None => summary.rejected.push(record),
+        }
+    }
+    *records = kept;
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    fn header(meta: &str) -> VcfHeader {
+        let content = format!(
+            "##fileformat=VCFv4.2\n{}#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n",
+            meta
+        );
+        VcfParser::new().parse_str(&content).unwrap().0
+    }
+
+    #[test]
+    fn test_detect_grch38_from_contig_lengths() {
+        let guess = detect_assembly(&header(
+            "##contig=<ID=chr1,length=248956422>\n\
+             ##contig=<ID=chr2,length=242193529>\n\
+             ##contig=<ID=chrM,length=16569>\n\
+             ##contig=<ID=chrUn_KI270302v1,length=2274>\n",
+        ));
+
+        assert_eq!(guess.assembly, Assembly::GRCh38);
+        assert!((guess.confidence - 2.0 / 6.0).abs() < 1e-9);
+        assert_eq!(
+            guess.evidence,
+            [
+                "chr1 length 248956422 matches GRCh38",
+                "chr2 length 242193529 matches GRCh38"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_grch37_from_reference_and_contigs() {
+        let guess = detect_assembly(&header(
+            "##reference=file:///data/hs37d5.fa\n\
+             ##contig=<ID=1,length=249250621,assembly=b37>\n\
+             ##contig=<ID=X,length=155270560,assembly=b37>\n",
+        ));
+
+        assert_eq!(guess.assembly, Assembly::GRCh37);
+        assert_eq!(guess.confidence, 1.0);
+        assert_eq!(guess.evidence.len(), 4);
+    }
+
+    #[test]
+    fn test_detect_conflicting_or_missing_evidence() {
+        let mixed = detect_assembly(&header(
+            "##contig=<ID=chr1,length=249250621>\n\
+             ##contig=<ID=chr2,length=242193529>\n",
+        ));
+        assert_eq!(mixed.assembly, Assembly::Unknown);
+        assert_eq!(mixed.confidence, 0.0);
+        assert_eq!(mixed.evidence.len(), 2);
+
+        // A hg19 reference outvoted by GRCh38 lengths loses some confidence
+        let outvoted = detect_assembly(&header(
+            "##reference=hg19\n\
+             ##contig=<ID=chr1,length=248956422>\n\
+             ##contig=<ID=chr2,length=242193529>\n\
+             ##contig=<ID=chr3,length=198295559>\n\
+             ##contig=<ID=chr4,length=190214555>\n",
+        ));
+        assert_eq!(outvoted.assembly, Assembly::GRCh38);
+        assert!((outvoted.confidence - 4.0 / 7.0).abs() < 1e-9);
+
+        let none = detect_assembly(&header(""));
+        assert_eq!(none.assembly, Assembly::Unknown);
+        assert!(none.evidence.is_empty());
+    }
+
+    /// Shifts everything by 1 000 bp and renames `chr` contigs to bare
+    /// names; chrY has no counterpart
+    struct OffsetMapper;
+
+    impl CoordinateMapper for OffsetMapper {
+        fn map(&self, chrom: &str, pos: u64) -> Option<(String, u64)> {
+            if chrom == "chrY" {
+                return None;
+            }
+            let chrom = chrom.strip_prefix("chr").unwrap_or(chrom);
+            Some((chrom.to_string(), pos + 1000))
+        }
+    }
+
+    #[test]
+    fn test_remap_records() {
+        let mut records = vec![
+            VcfRecord::new("chr1", 100, "A", vec!["G"]),
+            VcfRecord::new("chrY", 200, "C", vec!["T"]),
+            VcfRecord::new("2", 300, "G", vec!["A"]),
+            VcfRecord::new("chrY", 400, "T", vec!["C"]),
+        ];
+
+        let summary = remap_records(&mut records, &OffsetMapper);
+
+        assert_eq!(summary.mapped, 2);
+        assert_eq!(summary.changed_chrom, 1);
+        assert_eq!(summary.rejected.len(), 2);
+        assert!(summary.rejected.iter().all(|r| r.chrom == "chrY"));
+
+        let placed: Vec<_> = records
+            .iter()
+            .map(|r| (r.chrom.as_str(), r.pos, r.reference.as_str()))
+            .collect();
+        assert_eq!(placed, [("1", 1100, "A"), ("2", 1300, "G")]);
+    }
+}
@@ -0,0 +1,736 @@
+//! Callset Comparison
+//!
+//! Matches variants between two VCFs by normalized key and compares
+//! genotypes of the shared variants, or scores a callset against a truth
+//! set with precision and recall.
+
+use crate::diff::{diff_records, FieldDiff};
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Normalized identity of a single ALT allele
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct VariantKey {
+    pub chrom: String,
+    pub pos: u64,
+    pub reference: String,
+    pub alternate: String,
+}
+
+impl VariantKey {
+    /// Key for `alternate` at `pos`, with shared bases trimmed
+    pub fn normalized(chrom: &str, pos: u64, reference: &str, alternate: &str) -> Self {
+        let (pos, reference, alternate) = trim_alleles(pos, reference, alternate);
+        Self {
+            chrom: chrom.to_string(),
+            pos,
+            reference: reference.to_string(),
+            alternate: alternate.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VariantKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}",
+            self.chrom, self.pos, self.reference, self.alternate
+        )
+    }
+}
+
+/// Trim bases shared by REF and ALT, keeping at least one base of each
+///
+/// Shared trailing bases are removed first, then shared leading bases
+/// (advancing the position), so `CAT>CT` at 10 becomes `CA>C` at 10 and
+/// `GAC>GTC` becomes `A>T` at 11.
+pub fn trim_alleles<'a>(
+    pos: u64,
+    reference: &'a str,
+    alternate: &'a str,
+) -> (u64, &'a str, &'a str) {
+    let (mut r, mut a) = (reference.as_bytes(), alternate.as_bytes());
+
+    while r.len() > 1 && a.len() > 1 && r[r.len() - 1].eq_ignore_ascii_case(&a[a.len() - 1]) {
+        r = &r[..r.len() - 1];
+        a = &a[..a.len() - 1];
+    }
+    let mut start = 0;
+    while r.len() - start > 1 && a.len() - start > 1 && r[start].eq_ignore_ascii_case(&a[start]) {
+        start += 1;
+    }
+
+    (
+        pos + start as u64,
+        &reference[start..r.len()],
+        &alternate[start..a.len()],
+    )
+}
+
+/// Options for [`compare_callsets`]
+#[derive(Debug, Clone)]
+pub struct CompareOptions {
+    /// Treat `chr1` and `1` as the same chromosome
+    pub strip_chr_prefix: bool,
+    /// Only consider records with FILTER = PASS
+    pub pass_only: bool,
+    /// Maximum number of genotype-discordant keys to list
+    pub max_discordant: usize,
+    /// Maximum number of shared variants to list field-level diffs for;
+    /// 0 skips diffing
+    pub max_diffs: usize,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            strip_chr_prefix: false,
+            pass_only: false,
+            max_discordant: 100,
+            max_diffs: 0,
+        }
+    }
+}
+
+/// Fields that differ between the A and B records of a shared variant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariantDiff {
+    /// `chrom:pos:ref:alt` key the records were matched on
+    pub key: String,
+    pub fields: Vec<FieldDiff>,
+}
+
+/// Outcome of comparing callset A with callset B
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompareResult {
+    pub shared: usize,
+    pub only_a: usize,
+    pub only_b: usize,
+    /// Shared variants whose genotypes agree in every compared sample
+    pub concordant_genotypes: usize,
+    /// Shared variants with a genotype mismatch in at least one sample
+    pub discordant_genotypes: usize,
+    /// Keys of genotype-discordant variants, capped at `max_discordant`
+    pub discordant: Vec<String>,
+    /// Shared variants whose records differ in any field, by key and
+    /// capped at `max_diffs`
+    #[serde(default)]
+    pub diffs: Vec<VariantDiff>,
+}
+
+impl CompareResult {
+    /// Fraction of genotype-compared shared variants that agree, or `None`
+    /// if no genotypes could be compared
+    pub fn genotype_concordance(&self) -> Option<f64> {
+        let compared = self.concordant_genotypes + self.discordant_genotypes;
+        (compared > 0).then(|| self.concordant_genotypes as f64 / compared as f64)
+    }
+}
+
+/// Compare two callsets by normalized variant key
+///
+/// Multi-allelic records contribute one key per ALT allele. Genotypes are
+/// compared as the number of copies of each key's ALT allele, for samples
+/// matched by name (or by column when the two files share no sample
+/// names); samples with a missing genotype in either file are skipped.
+/// With `max_diffs` set, the first shared variants by key whose records
+/// differ get a [`diff_records`] listing; a record pair is listed once
+/// even when several of its ALT alleles match.
+pub fn compare_callsets(
+    header_a: &VcfHeader,
+    records_a: &[VcfRecord],
+    header_b: &VcfHeader,
+    records_b: &[VcfRecord],
+    options: &CompareOptions,
+) -> CompareResult {
+    let pairs = sample_pairs(header_a, header_b);
+    let a = index_callset(records_a, options, pairs.iter().map(|p| p.0));
+    let b = index_callset(records_b, options, pairs.iter().map(|p| p.1));
+
+    let mut result = CompareResult::default();
+    let mut discordant_keys = Vec::new();
+
+    for (key, (_, dosages_a)) in &a {
+        let Some((_, dosages_b)) = b.get(key) else {
+            result.only_a += 1;
+            continue;
+        };
+        result.shared += 1;
+
+        let compared: Vec<bool> = dosages_a
+            .iter()
+            .zip(dosages_b)
+            .filter_map(|(x, y)| Some(x.as_ref()? == y.as_ref()?))
+            .collect();
+        if compared.is_empty() {
+            continue;
+        }
+        if compared.iter().all(|&same| same) {
+            result.concordant_genotypes += 1;
+        } else {
+            result.discordant_genotypes += 1;
+            discordant_keys.push(key);
+        }
+    }
+    result.only_b = b.keys().filter(|key| !a.contains_key(*key)).count();
+
+    discordant_keys.sort();
+    result.discordant = discordant_keys
+        .into_iter()
+        .take(options.max_discordant)
+        .map(VariantKey::to_string)
+        .collect();
+
+    if options.max_diffs > 0 {
+        result.diffs = variant_diffs(&a, records_a, &b, records_b, options.max_diffs);
+    }
+
+    result
+}
+
+/// Record diffs of shared variants, in key order
+fn variant_diffs(
+    a: &CallsetIndex,
+    records_a: &[VcfRecord],
+    b: &CallsetIndex,
+    records_b: &[VcfRecord],
+    max_diffs: usize,
+) -> Vec<VariantDiff> {
+    let mut shared: Vec<(&VariantKey, usize, usize)> = a
+        .iter()
+        .filter_map(|(key, (i, _))| Some((key, *i, b.get(key)?.0)))
+        .collect();
+    shared.sort();
+
+    let mut seen = HashSet::new();
+    let mut diffs = Vec::new();
+    for (key, i, j) in shared {
+        if diffs.len() == max_diffs {
+            break;
+        }
+        if !seen.insert((i, j)) {
+            continue;
+        }
+        let fields = diff_records(&records_a[i], &records_b[j]);
+        if !fields.is_empty() {
+            diffs.push(VariantDiff {
+                key: key.to_string(),
+                fields,
+            });
+        }
+    }
+    diffs
+}
+
+/// Options for [`benchmark`]
+#[derive(Debug, Clone)]
+pub struct BenchmarkOptions {
+    /// A match is only a true positive if the first sample's ALT dosage
+    /// agrees; a mismatch counts as both a false positive and a false
+    /// negative, and a missing genotype as a mismatch
+    pub match_genotypes: bool,
+    /// Treat `chr1` and `1` as the same chromosome
+    pub strip_chr_prefix: bool,
+    /// Only consider query records with FILTER = PASS
+    pub pass_only: bool,
+    /// Maximum number of false-positive and of false-negative keys to list
+    pub max_listed: usize,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            match_genotypes: false,
+            strip_chr_prefix: false,
+            pass_only: false,
+            max_listed: 100,
+        }
+    }
+}
+
+/// Match counts for one variant class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkCounts {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub false_negatives: usize,
+    /// Matches rejected for disagreeing genotypes; already included in
+    /// the false positives and false negatives
+    pub genotype_mismatches: usize,
+}
+
+impl BenchmarkCounts {
+    /// TP / (TP + FP), or `None` without any query calls
+    pub fn precision(&self) -> Option<f64> {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    /// TP / (TP + FN), or `None` without any truth variants
+    pub fn recall(&self) -> Option<f64> {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+
+    /// Harmonic mean of precision and recall
+    pub fn f1(&self) -> Option<f64> {
+        let (precision, recall) = (self.precision()?, self.recall()?);
+        if precision + recall == 0.0 {
+            return Some(0.0);
+        }
+        Some(2.0 * precision * recall / (precision + recall))
+    }
+}
+
+fn ratio(numerator: usize, denominator: usize) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+/// Outcome of scoring a query callset against a truth set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    /// Single-base substitutions
+    pub snps: BenchmarkCounts,
+    /// Alleles whose REF and ALT differ in length
+    pub indels: BenchmarkCounts,
+    /// Multi-base substitutions
+    pub other: BenchmarkCounts,
+    /// Query-only keys, sorted and capped at `max_listed`
+    pub false_positive_keys: Vec<String>,
+    /// Truth-only keys, sorted and capped at `max_listed`
+    pub false_negative_keys: Vec<String>,
+}
+
+/// Score `query` against `truth` by normalized variant key
+///
+/// Multi-allelic records contribute one key per ALT allele; `*` and `.`
+/// alleles are skipped, as are alleles the first sample's genotype
+/// carries no copy of (hom-ref calls). Only records whose POS lies in one
+/// of `confident_regions` (chrom, start, end; 1-based, inclusive) are
+/// considered when regions are given. Keys are trimmed, not left-aligned,
+/// so indels placed differently in a repeat do not match.
+pub fn benchmark(
+    truth: &[VcfRecord],
+    query: &[VcfRecord],
+    confident_regions: Option<&[(String, u64, u64)]>,
+    options: &BenchmarkOptions,
+) -> BenchmarkResult {
+    let regions = confident_regions.map(|regions| RegionSet::new(regions, options));
+    let truth_calls = index_calls(truth, regions.as_ref(), options, false);
+    let query_calls = index_calls(query, regions.as_ref(), options, options.pass_only);
+
+    let mut result = BenchmarkResult::default();
+    let mut false_positives = Vec::new();
+    let mut false_negatives = Vec::new();
+
+    for (key, truth_dosage) in &truth_calls {
+        let counts = result.counts_mut(key);
+        match query_calls.get(key) {
+            None => {
+                counts.false_negatives += 1;
+                false_negatives.push(key);
+            }
+            Some(query_dosage)
+                if options.match_genotypes
+                    && (truth_dosage.is_none() || truth_dosage != query_dosage) =>
+            {
+                counts.genotype_mismatches += 1;
+                counts.false_negatives += 1;
+                counts.false_positives += 1;
+                false_negatives.push(key);
+                false_positives.push(key);
+            }
+            Some(_) => counts.true_positives += 1,
+        }
+    }
+    for key in query_calls
+        .keys()
+        .filter(|key| !truth_calls.contains_key(*key))
+    {
+        result.counts_mut(key).false_positives += 1;
+        false_positives.push(key);
+    }
+
+    let listed = |mut keys: Vec<&VariantKey>| {
+        keys.sort();
+        keys.into_iter()
+            .take(options.max_listed)
+            .map(VariantKey::to_string)
+            .collect()
+    };
+    result.false_positive_keys = listed(false_positives);
+    result.false_negative_keys = listed(false_negatives);
+
+    result
+}
+
+impl BenchmarkResult {
+    fn counts_mut(&mut self, key: &VariantKey) -> &mut BenchmarkCounts {
+        if key.reference.len() != key.alternate.len() {
+            &mut self.indels
+        } else if key.reference.len() == 1 {
+            &mut self.snps
+        } else {
+            &mut self.other
+        }
+    }
+}
+
+/// Merged confident intervals per chromosome, for binary search
+struct RegionSet {
+    by_chrom: HashMap<String, Vec<(u64, u64)>>,
+}
+
+impl RegionSet {
+    fn new(regions: &[(String, u64, u64)], options: &BenchmarkOptions) -> Self {
+        let mut by_chrom: HashMap<String, Vec<(u64, u64)>> = HashMap::new();
+        for (chrom, start, end) in regions {
+            let chrom = if options.strip_chr_prefix {
+                strip_chr(chrom)
+            } else {
+                chrom
+            };
+            by_chrom
+                .entry(chrom.to_string())
+                .or_default()
+                .push((*start, *end));
+        }
+
+        for intervals in by_chrom.values_mut() {
+            intervals.sort_unstable();
+            let mut merged: Vec<(u64, u64)> = Vec::with_capacity(intervals.len());
+            for &(start, end) in intervals.iter() {
+                match merged.last_mut() {
+                    Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+                    _ => merged.push((start, end)),
+                }
+            }
+            *intervals = merged;
+        }
+
+        Self { by_chrom }
+    }
+
+    fn contains(&self, chrom: &str, pos: u64) -> bool {
+        let Some(intervals) = self.by_chrom.get(chrom) else {
+            return false;
+        };
+        let after = intervals.partition_point(|&(start, _)| start <= pos);
+        after > 0 && intervals[after - 1].1 >= pos
+    }
+}
+
+/// Map each called key to the first sample's ALT dosage; first record wins
+fn index_calls(
+    records: &[VcfRecord],
+    regions: Option<&RegionSet>,
+    options: &BenchmarkOptions,
+    pass_only: bool,
+) -> HashMap<VariantKey, Option<(usize, usize)>> {
+    let mut index = HashMap::new();
+
+    for record in records {
+        if pass_only && record.filter != FilterStatus::Pass {
+            continue;
+        }
+        let chrom = if options.strip_chr_prefix {
+            strip_chr(&record.chrom)
+        } else {
+            &record.chrom
+        };
+        if regions.is_some_and(|regions| !regions.contains(chrom, record.pos)) {
+            continue;
+        }
+        let genotype = record.samples.first().and_then(|s| s.genotype.as_ref());
+
+        for (allele, alt) in record.alternate.iter().enumerate() {
+            if alt == "*" || alt == "." {
+                continue;
+            }
+            let dosage = genotype.and_then(|gt| dosage(gt, allele as u8 + 1));
+            if dosage.is_some_and(|(copies, _)| copies == 0) {
+                continue;
+            }
+            let key = VariantKey::normalized(chrom, record.pos, &record.reference, alt);
+            index.entry(key).or_insert(dosage);
+        }
+    }
+
+    index
+}
+
+/// Sample column pairs (index in A, index in B) to compare
+pub(crate) fn sample_pairs(header_a: &VcfHeader, header_b: &VcfHeader) -> Vec<(usize, usize)> {
+    let by_name: Vec<(usize, usize)> = header_a
+        .samples
+        .iter()
+        .enumerate()
+        .filter_map(|(i, name)| Some((i, header_b.samples.iter().position(|n| n == name)?)))
+        .collect();
+
+    if by_name.is_empty() {
+        (0..header_a.samples.len().min(header_b.samples.len()))
+            .map(|i| (i, i))
+            .collect()
+    } else {
+        by_name
+    }
+}
+
+/// Record index and ALT dosages of the selected samples for each key
+type CallsetIndex = HashMap<VariantKey, (usize, Vec<Option<(usize, usize)>>)>;
+
+/// Map each key to its record and the ALT dosage of the selected samples;
+/// first record wins
+fn index_callset(
+    records: &[VcfRecord],
+    options: &CompareOptions,
+    samples: impl Iterator<Item = usize> + Clone,
+) -> CallsetIndex {
+    let mut index = HashMap::new();
+
+    for (i, record) in records.iter().enumerate() {
+        if options.pass_only && record.filter != FilterStatus::Pass {
+            continue;
+        }
+        let chrom = if options.strip_chr_prefix {
+            strip_chr(&record.chrom)
+        } else {
+            &record.chrom
+        };
+
+        for (allele, alt) in record.alternate.iter().enumerate() {
+            let key = VariantKey::normalized(chrom, record.pos, &record.reference, alt);
+            if index.contains_key(&key) {
+                continue;
+            }
+            let dosages = samples
+                .clone()
+                .map(|i| dosage(record.samples.get(i)?.genotype.as_ref()?, allele as u8 + 1))
+                .collect();
+            index.insert(key, (i, dosages));
+        }
+    }
+
+    index
+}
+
+/// Copies of `allele` in a genotype and its ploidy, `None` if any allele
+/// is missing
+pub(crate) fn dosage(genotype: &Genotype, allele: u8) -> Option<(usize, usize)> {
+    let mut copies = 0;
+    for a in &genotype.alleles {
+        if (*a)? == allele {
+            copies += 1;
+        }
+    }
+    Some((copies, genotype.alleles.len()))
+}
+
+pub(crate) fn strip_chr(chrom: &str) -> &str {
+    match chrom.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("chr") && chrom.len() > 3 => &chrom[3..],
+        _ => chrom,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const CALLSET_A: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2
+chr1\t100\t.\tA\tG\t30\tPASS\t.\tGT\t0/1\t1/1
+chr1\t200\t.\tCAT\tCT\t30\tPASS\t.\tGT\t0/1\t0/0
+chr1\t300\t.\tG\tT,C\t30\tPASS\t.\tGT\t1/2\t0/2
+chr2\t400\t.\tT\tA\t30\tlowq\t.\tGT\t0/1\t0/1
+";
+
+    const CALLSET_B: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS2\tS1
+1\t100\t.\tA\tG\t30\tPASS\t.\tGT\t1/1\t0/1
+1\t200\t.\tCA\tC\t30\tPASS\t.\tGT\t0/0\t1/1
+1\t300\t.\tG\tC\t30\tPASS\t.\tGT\t0/1\t0/1
+1\t500\t.\tG\tA\t30\tPASS\t.\tGT\t./.\t0/1
+";
+
+    fn parse(content: &str) -> (VcfHeader, Vec<VcfRecord>) {
+        VcfParser::new().parse_str(content).unwrap()
+    }
+
+    #[test]
+    fn test_trim_alleles() {
+        assert_eq!(trim_alleles(10, "CAT", "CT"), (10, "CA", "C"));
+        assert_eq!(trim_alleles(10, "GAC", "GTC"), (11, "A", "T"));
+        assert_eq!(trim_alleles(10, "A", "G"), (10, "A", "G"));
+        assert_eq!(trim_alleles(10, "ACGT", "ACGT"), (10, "A", "A"));
+    }
+
+    #[test]
+    fn test_compare_callsets() {
+        let (header_a, records_a) = parse(CALLSET_A);
+        let (header_b, records_b) = parse(CALLSET_B);
+        let options = CompareOptions {
+            strip_chr_prefix: true,
+            ..Default::default()
+        };
+
+        let result = compare_callsets(&header_a, &records_a, &header_b, &records_b, &options);
+        // Shared: 1:100 A>G, 1:200 CA>C (after trimming), 1:300 G>C
+        assert_eq!(result.shared, 3);
+        assert_eq!(result.only_a, 2);
+        assert_eq!(result.only_b, 1);
+        // Samples are matched by name despite the swapped columns
+        assert_eq!(result.concordant_genotypes, 2);
+        assert_eq!(result.discordant_genotypes, 1);
+        assert_eq!(result.discordant, vec!["1:200:CA:C"]);
+        assert_eq!(result.genotype_concordance(), Some(2.0 / 3.0));
+
+        let without_prefix = compare_callsets(
+            &header_a,
+            &records_a,
+            &header_b,
+            &records_b,
+            &CompareOptions::default(),
+        );
+        assert_eq!(without_prefix.shared, 0);
+
+        let pass_only = CompareOptions {
+            strip_chr_prefix: true,
+            pass_only: true,
+            max_discordant: 0,
+            ..Default::default()
+        };
+        let result = compare_callsets(&header_a, &records_a, &header_b, &records_b, &pass_only);
+        assert_eq!(result.only_a, 1);
+        assert!(result.discordant.is_empty());
+        assert!(result.diffs.is_empty());
+    }
+
+    #[test]
+    fn test_compare_callsets_diffs() {
+        let (header_a, records_a) = parse(CALLSET_A);
+        let (header_b, records_b) = parse(CALLSET_B);
+        let options = CompareOptions {
+            strip_chr_prefix: true,
+            max_diffs: 2,
+            ..Default::default()
+        };
+
+        let result = compare_callsets(&header_a, &records_a, &header_b, &records_b, &options);
+        let keys: Vec<&str> = result.diffs.iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(keys, ["1:100:A:G", "1:200:CA:C"]);
+        // Only the chromosome name differs at 100; samples match by name
+        assert_eq!(
+            result.diffs[0].fields,
+            [FieldDiff {
+                field: "chrom".into(),
+                a: "chr1".into(),
+                b: "1".into(),
+            }]
+        );
+        let fields: Vec<&str> = result.diffs[1]
+            .fields
+            .iter()
+            .map(|d| d.field.as_str())
+            .collect();
+        assert_eq!(fields, ["chrom", "ref", "alt", "samples[S1].GT"]);
+    }
+
+    const TRUTH: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tTRUTH
+chr1\t100\t.\tA\tG\t.\tPASS\t.\tGT\t0/1
+chr1\t200\t.\tCAT\tCT\t.\tPASS\t.\tGT\t0/1
+chr1\t300\t.\tG\tT\t.\tPASS\t.\tGT\t0/1
+chr1\t400\t.\tTA\tT\t.\tPASS\t.\tGT\t1/1
+chr2\t900\t.\tC\tA\t.\tPASS\t.\tGT\t0/1
+";
+
+    // 200 is the same deletion written with less context, 300 has the
+    // wrong genotype, 400 is missed, 500 is a false call, 600 is a
+    // filtered false call, 700 is a hom-ref site and chr2 is not confident
+    const QUERY: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tQUERY
+chr1\t100\t.\tA\tG,C\t50\tPASS\t.\tGT\t0/1
+chr1\t200\t.\tCA\tC\t50\tPASS\t.\tGT\t0/1
+chr1\t300\t.\tG\tT\t50\tPASS\t.\tGT\t1/1
+chr1\t500\t.\tGAC\tTAG\t50\tPASS\t.\tGT\t0/1
+chr1\t600\t.\tC\tT\t5\tLowQual\t.\tGT\t0/1
+chr1\t700\t.\tA\tT\t50\tPASS\t.\tGT\t0/0
+chr2\t950\t.\tC\tCA\t50\tPASS\t.\tGT\t0/1
+";
+
+    #[test]
+    fn test_benchmark() {
+        let (_, truth) = parse(TRUTH);
+        let (_, query) = parse(QUERY);
+        let regions = [
+            ("chr1".to_string(), 1, 350),
+            ("chr1".to_string(), 300, 1000),
+            ("chr2".to_string(), 1, 100),
+        ];
+        let options = BenchmarkOptions {
+            pass_only: true,
+            ..Default::default()
+        };
+
+        let result = benchmark(&truth, &query, Some(&regions), &options);
+        let counts = |tp, fp, fn_| BenchmarkCounts {
+            true_positives: tp,
+            false_positives: fp,
+            false_negatives: fn_,
+            genotype_mismatches: 0,
+        };
+        assert_eq!(result.snps, counts(2, 0, 0));
+        assert_eq!(result.indels, counts(1, 0, 1));
+        assert_eq!(result.other, counts(0, 1, 0));
+        assert_eq!(result.false_positive_keys, ["chr1:500:GAC:TAG"]);
+        assert_eq!(result.false_negative_keys, ["chr1:400:TA:T"]);
+        assert_eq!(result.indels.precision(), Some(1.0));
+        assert_eq!(result.indels.recall(), Some(0.5));
+        assert_eq!(result.indels.f1(), Some(2.0 / 3.0));
+        assert_eq!(result.other.recall(), None);
+
+        // The genotype at 300 now disagrees
+        let options = BenchmarkOptions {
+            match_genotypes: true,
+            ..options
+        };
+        let result = benchmark(&truth, &query, Some(&regions), &options);
+        assert_eq!(
+            result.snps,
+            BenchmarkCounts {
+                true_positives: 1,
+                false_positives: 1,
+                false_negatives: 1,
+                genotype_mismatches: 1,
+            }
+        );
+        assert_eq!(result.indels.true_positives, 1);
+        assert_eq!(
+            result.false_negative_keys,
+            ["chr1:300:G:T", "chr1:400:TA:T"]
+        );
+
+        // Without regions or the PASS requirement every call counts
+        let options = BenchmarkOptions {
+            max_listed: 2,
+            ..Default::default()
+        };
+        let result = benchmark(&truth, &query, None, &options);
+        assert_eq!(result.snps, counts(2, 1, 1));
+        assert_eq!(result.indels, counts(1, 1, 1));
+        assert_eq!(
+            result.false_positive_keys,
+            ["chr1:500:GAC:TAG", "chr1:600:C:T"]
+        );
+    }
+}
@@ -0,0 +1,305 @@
+//! Sample Genotype Concordance
+//!
+//! Genotype agreement between samples of one file, as used to spot sample
+//! swaps and duplicates.
+
+use crate::types::{Genotype, HalfCallPolicy, SampleData, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+
+/// Coarse genotype class; rows and columns of a [`ConcordanceMatrix`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GenotypeClass {
+    HomRef,
+    Het,
+    HomAlt,
+    Missing,
+}
+
+impl GenotypeClass {
+    /// All classes, in matrix order
+    pub const ALL: [GenotypeClass; 4] = [
+        GenotypeClass::HomRef,
+        GenotypeClass::Het,
+        GenotypeClass::HomAlt,
+        GenotypeClass::Missing,
+    ];
+
+    /// Classify by which alleles are present: only REF, REF and any ALT,
+    /// or only ALTs (so `1/2` is hom-alt); any missing allele is missing
+    pub fn of(genotype: Option<&Genotype>) -> Self {
+        Self::classify(genotype, HalfCallPolicy::Missing)
+    }
+
+    /// Classify a sample's GT as [`GenotypeClass::of`] does, with half
+    /// calls read under `policy`; no sample or no GT is missing
+    pub fn of_sample(sample: Option<&SampleData>, policy: HalfCallPolicy) -> Self {
+        Self::classify(sample.and_then(|s| s.genotype.as_ref()), policy)
+    }
+
+    fn classify(genotype: Option<&Genotype>, policy: HalfCallPolicy) -> Self {
+        let Some(genotype) = genotype else {
+            return GenotypeClass::Missing;
+        };
+        if genotype.alleles.iter().all(Option::is_none) {
+            return GenotypeClass::Missing;
+        }
+        let (mut has_ref, mut has_alt) = (false, false);
+        for allele in &genotype.alleles {
+            match (allele, policy) {
+                (None, HalfCallPolicy::Missing) => return GenotypeClass::Missing,
+                (None, HalfCallPolicy::Haploid) => {}
+                (None, HalfCallPolicy::Reference) | (Some(0), _) => has_ref = true,
+                (Some(_), _) => has_alt = true,
+            }
+        }
+        match (has_ref, has_alt) {
+            (true, false) => GenotypeClass::HomRef,
+            (true, true) => GenotypeClass::Het,
+            (false, true) => GenotypeClass::HomAlt,
+            (false, false) => GenotypeClass::Missing,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            GenotypeClass::HomRef => 0,
+            GenotypeClass::Het => 1,
+            GenotypeClass::HomAlt => 2,
+            GenotypeClass::Missing => 3,
+        }
+    }
+}
+
+/// Site counts by genotype class of sample A (row) and sample B (column),
+/// both in [`GenotypeClass::ALL`] order
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConcordanceMatrix {
+    pub counts: [[usize; 4]; 4],
+}
+
+impl ConcordanceMatrix {
+    /// Sites where A has class `a` and B has class `b`
+    pub fn get(&self, a: GenotypeClass, b: GenotypeClass) -> usize {
+        self.counts[a.index()][b.index()]
+    }
+
+    fn add(&mut self, a: GenotypeClass, b: GenotypeClass) {
+        self.counts[a.index()][b.index()] += 1;
+    }
+
+    /// Sites where neither genotype is missing
+    pub fn both_called(&self) -> usize {
+        self.counts[..3]
+            .iter()
+            .map(|row| row[..3].iter().sum::<usize>())
+            .sum()
+    }
+
+    /// Fraction of both-called sites with the same class
+    pub fn concordance(&self) -> Option<f64> {
+        let called = self.both_called();
+        let same: usize = (0..3).map(|i| self.counts[i][i]).sum();
+        (called > 0).then(|| same as f64 / called as f64)
+    }
+
+    /// Concordance over both-called sites where either sample carries an
+    /// ALT, so shared hom-ref sites cannot inflate it
+    pub fn non_reference_concordance(&self) -> Option<f64> {
+        let compared = self.both_called() - self.counts[0][0];
+        let same = self.counts[1][1] + self.counts[2][2];
+        (compared > 0).then(|| same as f64 / compared as f64)
+    }
+}
+
+/// Concordance matrix of two samples, found by name in each record; a
+/// sample absent from a record counts as missing there
+pub fn genotype_concordance(
+    records: &[VcfRecord],
+    sample_a: &str,
+    sample_b: &str,
+) -> ConcordanceMatrix {
+    genotype_concordance_with_policy(records, sample_a, sample_b, HalfCallPolicy::Missing)
+}
+
+/// [`genotype_concordance`] with half calls read under `policy`
+pub fn genotype_concordance_with_policy(
+    records: &[VcfRecord],
+    sample_a: &str,
+    sample_b: &str,
+    policy: HalfCallPolicy,
+) -> ConcordanceMatrix {
+    let class = |record: &VcfRecord, name: &str| {
+        GenotypeClass::of_sample(record.samples.iter().find(|s| s.name == name), policy)
+    };
+
+    let mut matrix = ConcordanceMatrix::default();
+    for record in records {
+        matrix.add(class(record, sample_a), class(record, sample_b));
+    }
+    matrix
+}
+
+/// Non-reference concordance of every pair of samples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairwiseConcordance {
+    pub samples: Vec<String>,
+    /// Symmetric; `None` where a pair shares no non-reference site
+    pub non_reference_concordance: Vec<Vec<Option<f64>>>,
+}
+
+/// [`ConcordanceMatrix::non_reference_concordance`] for all sample pairs
+///
+/// Each record's genotypes are classified once and reused for every pair,
+/// so the cost is one pass over the records with a quadratic inner loop.
+pub fn all_pairs_concordance(records: &[VcfRecord], header: &VcfHeader) -> PairwiseConcordance {
+    all_pairs_concordance_with_policy(records, header, HalfCallPolicy::Missing)
+}
+
+/// [`all_pairs_concordance`] with half calls read under `policy`
+pub fn all_pairs_concordance_with_policy(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    policy: HalfCallPolicy,
+) -> PairwiseConcordance {
+    let n = header.samples.len();
+    // Upper triangle including the diagonal, row by row
+    let mut matrices = vec![ConcordanceMatrix::default(); n * (n + 1) / 2];
+    let mut classes = Vec::with_capacity(n);
+
+    for record in records {
+        classes.clear();
+        classes.extend((0..n).map(|i| GenotypeClass::of_sample(record.samples.get(i), policy)));
+
+        let mut pair = 0;
+        for (i, &a) in classes.iter().enumerate() {
+            for &b in &classes[i..] {
+                matrices[pair].add(a, b);
+                pair += 1;
+            }
+        }
+    }
+
+    // Offset of row `i` in the triangle plus the column within it
+    let pair = |i: usize, j: usize| i * n - i * i.saturating_sub(1) / 2 + (j - i);
+    let concordance = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| matrices[pair(i.min(j), i.max(j))].non_reference_concordance())
+                .collect()
+        })
+        .collect();
+
+    PairwiseConcordance {
+        samples: header.samples.clone(),
+        non_reference_concordance: concordance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+    use GenotypeClass::*;
+
+    // S1 and S2 agree everywhere; S3 differs at 200 (het vs hom-alt), 300
+    // (hom-alt vs het) and 500 (missing)
+    const THREE_SAMPLES: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3
+chr1\t100\t.\tA\tG\t50\tPASS\t.\tGT\t0/1\t0/1\t0/1
+chr1\t200\t.\tC\tT\t50\tPASS\t.\tGT\t0/1\t0|1\t1/1
+chr1\t300\t.\tG\tA,C\t50\tPASS\t.\tGT\t1/2\t1/2\t0/2
+chr1\t400\t.\tT\tC\t50\tPASS\t.\tGT\t0/0\t0/0\t0/0
+chr1\t500\t.\tA\tT\t50\tPASS\t.\tGT\t1/1\t1/1\t./.
+chr1\t600\t.\tG\tC\t50\tPASS\t.\tGT\t0/0\t0/0\t0/0
+";
+
+    #[test]
+    fn test_genotype_class() {
+        let class = |gt: &str| GenotypeClass::of(Genotype::parse(gt).as_ref());
+        assert_eq!(class("0/0"), HomRef);
+        assert_eq!(class("0|2"), Het);
+        assert_eq!(class("1/2"), HomAlt);
+        assert_eq!(class("1"), HomAlt);
+        assert_eq!(class("0/."), Missing);
+        assert_eq!(class("./."), Missing);
+    }
+
+    #[test]
+    fn test_genotype_concordance() {
+        let (_, records) = VcfParser::new().parse_str(THREE_SAMPLES).unwrap();
+
+        let same = genotype_concordance(&records, "S1", "S2");
+        assert_eq!(same.both_called(), 6);
+        assert_eq!(same.concordance(), Some(1.0));
+        assert_eq!(same.non_reference_concordance(), Some(1.0));
+
+        let swapped = genotype_concordance(&records, "S1", "S3");
+        assert_eq!(swapped.get(Het, Het), 1);
+        assert_eq!(swapped.get(Het, HomAlt), 1);
+        assert_eq!(swapped.get(HomAlt, Het), 1);
+        assert_eq!(swapped.get(HomRef, HomRef), 2);
+        assert_eq!(swapped.get(HomAlt, Missing), 1);
+        assert_eq!(swapped.both_called(), 5);
+        assert_eq!(swapped.concordance(), Some(3.0 / 5.0));
+        assert_eq!(swapped.non_reference_concordance(), Some(1.0 / 3.0));
+
+        let unknown = genotype_concordance(&records, "S1", "S9");
+        assert_eq!(unknown.both_called(), 0);
+        assert_eq!(unknown.concordance(), None);
+    }
+
+    #[test]
+    fn test_all_pairs_concordance() {
+        let (header, records) = VcfParser::new().parse_str(THREE_SAMPLES).unwrap();
+
+        let pairs = all_pairs_concordance(&records, &header);
+        assert_eq!(pairs.samples, ["S1", "S2", "S3"]);
+        let third = Some(1.0 / 3.0);
+        assert_eq!(
+            pairs.non_reference_concordance,
+            [
+                [Some(1.0), Some(1.0), third],
+                [Some(1.0), Some(1.0), third],
+                [third, third, Some(1.0)],
+            ]
+        );
+
+        // The pairwise values match the two-sample computation
+        let s2_s3 = genotype_concordance(&records, "S2", "S3");
+        assert_eq!(s2_s3.non_reference_concordance(), third);
+    }
+
+    #[test]
+    fn test_concordance_half_call_policy() {
+        // S2 writes the 0/1 at 200 as ./1 and the 1/1 at 300 as 1/.
+        let vcf = THREE_SAMPLES
+            .replace("0/1\t0|1\t1/1", "0/1\t./1\t1/1")
+            .replace("1/2\t1/2\t0/2", "1/1\t1/.\t0/2");
+        let (header, records) = VcfParser::new().parse_str(&vcf).unwrap();
+        let s1_s2 = |policy| genotype_concordance_with_policy(&records, "S1", "S2", policy);
+
+        let missing = s1_s2(HalfCallPolicy::Missing);
+        assert_eq!(missing, genotype_concordance(&records, "S1", "S2"));
+        assert_eq!(missing.get(Het, Missing) + missing.get(HomAlt, Missing), 2);
+        assert_eq!(missing.both_called(), 4);
+
+        // Haploid `1` is hom-alt; REF-filled `0/1` is het
+        let haploid = s1_s2(HalfCallPolicy::Haploid);
+        assert_eq!(
+            (haploid.get(Het, HomAlt), haploid.get(HomAlt, HomAlt)),
+            (1, 2)
+        );
+        let reference = s1_s2(HalfCallPolicy::Reference);
+        assert_eq!(
+            (reference.get(Het, Het), reference.get(HomAlt, Het)),
+            (2, 1)
+        );
+        assert_eq!(reference.both_called(), 6);
+
+        let pairs = all_pairs_concordance_with_policy(&records, &header, HalfCallPolicy::Reference);
+        assert_eq!(
+            pairs.non_reference_concordance[0][1],
+            reference.non_reference_concordance()
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Coding Consequences
+//!
+//! A quick check of what a variant does to one transcript, given its
+//! exons and coding sequence: whether a SNP changes the amino acid, and
+//! whether an indel keeps the reading frame. Meant for triage, not as a
+//! replacement for VEP or SnpEff.
+
+use crate::gene_model::Strand;
+use crate::types::VcfRecord;
+use serde::{Deserialize, Serialize};
+
+/// Intronic bases next to an exon that count as the splice region
+pub const SPLICE_REGION: u64 = 8;
+
+/// Standard genetic code for the 64 codons in TCAG order
+/// (index = 16 * first + 4 * second + third, with T=0, C=1, A=2, G=3)
+const STANDARD_CODE: &[u8; 64] =
+    b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// One transcript, in 1-based inclusive genomic coordinates as in VCF
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TranscriptModel {
+    pub chrom: String,
+    /// `Unknown` is read as `Forward`
+    pub strand: Strand,
+    /// Exon spans, in any order
+    pub exons: Vec<(u64, u64)>,
+    /// Lowest coding base; the first base of the start codon on the
+    /// forward strand, the last base of the stop codon on the reverse
+    pub cds_start: u64,
+    /// Highest coding base
+    pub cds_end: u64,
+    /// Spliced coding sequence from the start codon through the stop
+    /// codon, 5' to 3' on the transcript's strand
+    pub sequence: String,
+}
+
+/// A codon before and after a substitution
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodonChange {
+    /// Codon number in the CDS, 1-based
+    pub codon: usize,
+    /// Reference codon from the model, on the transcript's strand
+    pub ref_codon: String,
+    pub alt_codon: String,
+    /// One-letter amino acids, `*` for a stop
+    pub ref_aa: char,
+    pub alt_aa: char,
+}
+
+/// What a variant does to a transcript
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Consequence {
+    Synonymous(CodonChange),
+    Missense(CodonChange),
+    /// A stop codon gained
+    Nonsense(CodonChange),
+    StopLost(CodonChange),
+    /// The start codon's methionine lost
+    StartLost(CodonChange),
+    /// An insertion or deletion in the CDS whose length is not a multiple
+    /// of three
+    Frameshift,
+    InframeIndel,
+    /// Another change to coding bases, such as an MNP, a symbolic allele
+    /// or a base the model's sequence does not cover
+    Coding,
+    /// Within [`SPLICE_REGION`] bases of an exon, inside an intron
+    SpliceRegion,
+    Intronic,
+    /// An exonic base outside the CDS
+    Utr,
+    /// Not within the transcript
+    Outside,
+}
+
+/// Where a base falls in a transcript
+enum Location {
+    /// Index into the CDS sequence
+    Coding(usize),
+    Utr,
+    SpliceRegion,
+    Intronic,
+    Outside,
+}
+
+impl TranscriptModel {
+    fn reverse(&self) -> bool {
+        self.strand == Strand::Reverse
+    }
+
+    /// Exons sorted by start
+    fn sorted_exons(&self) -> Vec<(u64, u64)> {
+        let mut exons = self.exons.clone();
+        exons.sort_unstable();
+        exons
+    }
+
+    fn locate(&self, pos: u64) -> Location {
+        let exons = self.sorted_exons();
+        let (Some(first), Some(last)) = (exons.first(), exons.last()) else {
+            return Location::Outside;
+        };
+        if pos < first.0 || pos > last.1 {
+            return Location::Outside;
+        }
+
+        if !exons
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&pos))
+        {
+            let near_exon = exons.iter().any(|&(start, end)| {
+                start.abs_diff(pos) <= SPLICE_REGION || end.abs_diff(pos) <= SPLICE_REGION
+            });
+            return if near_exon {
+                Location::SpliceRegion
+            } else {
+                Location::Intronic
+            };
+        }
+        if pos < self.cds_start || pos > self.cds_end {
+            return Location::Utr;
+        }
+
+        // Coding bases before `pos` in genomic order, then flipped for the
+        // reverse strand
+        let coding = |start: u64, end: u64| (start.max(self.cds_start), end.min(self.cds_end));
+        let mut before = 0;
+        let mut total = 0;
+        for &(start, end) in &exons {
+            let (start, end) = coding(start, end);
+            if start > end {
+                continue;
+            }
+            if end < pos {
+                before += end - start + 1;
+            } else if start <= pos {
+                before += pos - start;
+            }
+            total += end - start + 1;
+        }
+        let index = if self.reverse() {
+            total - 1 - before
+        } else {
+            before
+        };
+        Location::Coding(index as usize)
+    }
+
+    /// Base at `pos` on the forward strand, turned to the transcript's
+    fn transcript_base(&self, base: u8) -> u8 {
+        if self.reverse() {
+            complement(base)
+        } else {
+            base.to_ascii_uppercase()
+        }
+    }
+}
+
+/// Consequence of `record`'s first ALT allele for `model`
+///
+/// SNPs are translated with the standard genetic code; the reference
+/// codon comes from the model's sequence, so REF is not checked against
+/// it. Insertions and deletions that touch coding bases are classed by
+/// whether they keep the frame. Codon changes take precedence over a
+/// splice region: a coding base next to an exon edge is classed by its
+/// codon.
+pub fn predict_snp_consequence(record: &VcfRecord, model: &TranscriptModel) -> Consequence {
+    let Some(alt) = record.alternate.first() else {
+        return Consequence::Outside;
+    };
+    if record.chrom != model.chrom {
+        return Consequence::Outside;
+    }
+
+    let (ref_len, alt_len) = (record.reference.len() as u64, alt.len() as u64);
+    let is_base = |allele: &str| allele.len() == 1 && allele != "*" && allele != ".";
+    if ref_len != alt_len && !alt.starts_with('<') && alt != "*" && alt != "." {
+        let mut span = record.pos..record.pos + ref_len.max(1);
+        if span.any(|pos| matches!(model.locate(pos), Location::Coding(_))) {
+            return if ref_len.abs_diff(alt_len) % 3 == 0 {
+                Consequence::InframeIndel
+            } else {
+                Consequence::Frameshift
+            };
+        }
+    }
+
+    match model.locate(record.pos) {
+        Location::Coding(index) if is_base(&record.reference) && is_base(alt) => {
+            codon_change(model, index, alt.as_bytes()[0]).map_or(Consequence::Coding, classify)
+        }
+        Location::Coding(_) => Consequence::Coding,
+        Location::Utr => Consequence::Utr,
+        Location::SpliceRegion => Consequence::SpliceRegion,
+        Location::Intronic => Consequence::Intronic,
+        Location::Outside => Consequence::Outside,
+    }
+}
+
+/// The codon holding CDS base `index` with that base replaced by the
+/// forward-strand base `alt`; `None` if the sequence does not cover it
+fn codon_change(model: &TranscriptModel, index: usize, alt: u8) -> Option<CodonChange> {
+    let start = index - index % 3;
+    let ref_codon = model.sequence.get(start..start + 3)?.to_ascii_uppercase();
+    let mut alt_codon = ref_codon.clone().into_bytes();
+    alt_codon[index % 3] = model.transcript_base(alt);
+    let alt_codon = String::from_utf8(alt_codon).ok()?;
+
+    Some(CodonChange {
+        codon: start / 3 + 1,
+        ref_aa: translate(ref_codon.as_bytes()),
+        alt_aa: translate(alt_codon.as_bytes()),
+        ref_codon,
+        alt_codon,
+    })
+}
+
+fn classify(change: CodonChange) -> Consequence {
+    match (change.ref_aa, change.alt_aa) {
+        (a, b) if a == b => Consequence::Synonymous(change),
+        ('M', _) if change.codon == 1 => Consequence::StartLost(change),
+        (_, '*') => Consequence::Nonsense(change),
+        ('*', _) => Consequence::StopLost(change),
+        _ => Consequence::Missense(change),
+    }
+}
+
+/// Amino acid of an uppercase DNA codon; `X` if it has other bases
+fn translate(codon: &[u8]) -> char {
+    let base = |b: u8| match b {
+        b'T' => Some(0),
+        b'C' => Some(1),
+        b'A' => Some(2),
+        b'G' => Some(3),
+        _ => None,
+    };
+    match (base(codon[0]), base(codon[1]), base(codon[2])) {
+        (Some(a), Some(b), Some(c)) => STANDARD_CODE[16 * a + 4 * b + c] as char,
+        _ => 'X',
+    }
+}
+
+fn complement(base: u8) -> u8 {
+    match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ATG AAA GAA TGG CT|G TTC GGA CAT TAC GCC AGC TAA, with codon 5
+    // split across the exon junction
+    const CDS: &str = "ATGAAAGAATGGCTGTTCGGACATTACGCCAGCTAA";
+
+    fn model(strand: Strand) -> TranscriptModel {
+        TranscriptModel {
+            chrom: "chr1".to_string(),
+            strand,
+            exons: vec![(201, 230), (101, 120)],
+            cds_start: 107,
+            cds_end: 222,
+            sequence: CDS.to_string(),
+        }
+    }
+
+    fn predict(model: &TranscriptModel, pos: u64, reference: &str, alt: &str) -> Consequence {
+        let record = VcfRecord::new("chr1", pos, reference, vec![alt]);
+        predict_snp_consequence(&record, model)
+    }
+
+    fn change(consequence: &Consequence) -> (usize, &str, &str, char, char) {
+        match consequence {
+            Consequence::Synonymous(c)
+            | Consequence::Missense(c)
+            | Consequence::Nonsense(c)
+            | Consequence::StopLost(c)
+            | Consequence::StartLost(c) => {
+                (c.codon, &c.ref_codon, &c.alt_codon, c.ref_aa, c.alt_aa)
+            }
+            other => panic!("no codon change: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_forward_strand_classes() {
+        let model = model(Strand::Forward);
+
+        let missense = predict(&model, 110, "A", "G");
+        assert!(matches!(missense, Consequence::Missense(_)));
+        assert_eq!(change(&missense), (2, "AAA", "GAA", 'K', 'E'));
+
+        let nonsense = predict(&model, 117, "G", "A");
+        assert!(matches!(nonsense, Consequence::Nonsense(_)));
+        assert_eq!(change(&nonsense), (4, "TGG", "TAG", 'W', '*'));
+
+        // Third base of codon 5 is the first base of the second exon
+        let synonymous = predict(&model, 201, "G", "A");
+        assert!(matches!(synonymous, Consequence::Synonymous(_)));
+        assert_eq!(change(&synonymous), (5, "CTG", "CTA", 'L', 'L'));
+
+        let stop_lost = predict(&model, 220, "T", "C");
+        assert!(matches!(stop_lost, Consequence::StopLost(_)));
+        assert_eq!(change(&stop_lost), (12, "TAA", "CAA", '*', 'Q'));
+
+        let start_lost = predict(&model, 108, "T", "C");
+        assert!(matches!(start_lost, Consequence::StartLost(_)));
+        assert_eq!(change(&start_lost), (1, "ATG", "ACG", 'M', 'T'));
+
+        assert_eq!(predict(&model, 123, "C", "T"), Consequence::SpliceRegion);
+        assert_eq!(predict(&model, 195, "C", "T"), Consequence::SpliceRegion);
+        assert_eq!(predict(&model, 150, "C", "T"), Consequence::Intronic);
+        assert_eq!(predict(&model, 103, "C", "T"), Consequence::Utr);
+        assert_eq!(predict(&model, 226, "C", "T"), Consequence::Utr);
+        assert_eq!(predict(&model, 50, "C", "T"), Consequence::Outside);
+        assert_eq!(predict(&model, 110, "AA", "A"), Consequence::Frameshift);
+        assert_eq!(predict(&model, 110, "AAAG", "A"), Consequence::InframeIndel);
+        assert_eq!(predict(&model, 110, "A", "AGC"), Consequence::Frameshift);
+        assert_eq!(predict(&model, 150, "CAG", "C"), Consequence::Intronic);
+        assert_eq!(predict(&model, 110, "AA", "GG"), Consequence::Coding);
+
+        let other_chrom = VcfRecord::new("chr2", 110, "A", vec!["G"]);
+        assert_eq!(
+            predict_snp_consequence(&other_chrom, &model),
+            Consequence::Outside
+        );
+    }
+
+    #[test]
+    fn test_reverse_strand_classes() {
+        // Same CDS read from 222 down to 107, so genomic bases are the
+        // complement of the transcript's
+        let model = model(Strand::Reverse);
+
+        let missense = predict(&model, 219, "T", "C");
+        assert_eq!(change(&missense), (2, "AAA", "GAA", 'K', 'E'));
+        assert!(matches!(missense, Consequence::Missense(_)));
+
+        let nonsense = predict(&model, 212, "C", "T");
+        assert!(matches!(nonsense, Consequence::Nonsense(_)));
+        assert_eq!(change(&nonsense), (4, "TGG", "TAG", 'W', '*'));
+
+        // Codon 8 sits across the junction on this strand: 201, 120, 119
+        let junction = predict(&model, 120, "T", "G");
+        assert!(matches!(junction, Consequence::Missense(_)));
+        assert_eq!(change(&junction), (8, "CAT", "CCT", 'H', 'P'));
+
+        let stop_lost = predict(&model, 109, "A", "G");
+        assert!(matches!(stop_lost, Consequence::StopLost(_)));
+        assert_eq!(change(&stop_lost), (12, "TAA", "CAA", '*', 'Q'));
+
+        assert_eq!(predict(&model, 198, "C", "T"), Consequence::SpliceRegion);
+        assert_eq!(predict(&model, 225, "C", "T"), Consequence::Utr);
+        assert_eq!(predict(&model, 215, "GA", "G"), Consequence::Frameshift);
+    }
+}
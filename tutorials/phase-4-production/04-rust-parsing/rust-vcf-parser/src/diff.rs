@@ -0,0 +1,395 @@
+//! Record and Header Diffs
+//!
+//! Field-by-field differences between two records or two headers, for
+//! finding out why two pipelines disagree about the same variant without
+//! reading both lines side by side.
+
+use crate::types::*;
+use crate::writer::{genotype, info_value};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Numbers closer than this are equal in [`diff_records`]
+pub const DEFAULT_EPSILON: f64 = 1e-6;
+
+/// One field whose value differs, with both values as VCF text
+///
+/// An absent field is `.`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDiff {
+    /// Path of the field, e.g. `qual`, `info.DP` or `samples[NA12878].GT`
+    pub field: String,
+    pub a: String,
+    pub b: String,
+}
+
+impl FieldDiff {
+    fn new(field: impl Into<String>, a: impl Into<String>, b: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            a: a.into(),
+            b: b.into(),
+        }
+    }
+}
+
+/// Fields that differ between two records, with [`DEFAULT_EPSILON`]
+pub fn diff_records(a: &VcfRecord, b: &VcfRecord) -> Vec<FieldDiff> {
+    diff_records_with_epsilon(a, b, DEFAULT_EPSILON)
+}
+
+/// Fields that differ between two records
+///
+/// Diffs come in column order: `chrom`, `pos`, `id`, `ref`, `alt`,
+/// `qual`, `filter`, then `info.KEY` by key and `samples[NAME].KEY` with
+/// `GT` first. Samples are matched by name. QUAL, INFO and sample values
+/// compare element by element, and elements that both parse as numbers
+/// are equal when they differ by at most `epsilon`, so `30` and `30.0`
+/// match. FILTER, INFO and FORMAT key order is ignored.
+pub fn diff_records_with_epsilon(a: &VcfRecord, b: &VcfRecord, epsilon: f64) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+    let mut exact = |field: &str, x: String, y: String| {
+        if x != y {
+            diffs.push(FieldDiff::new(field, x, y));
+        }
+    };
+
+    exact("chrom", a.chrom.clone(), b.chrom.clone());
+    exact("pos", a.pos.to_string(), b.pos.to_string());
+    exact("id", id(a), id(b));
+    exact("ref", a.reference.clone(), b.reference.clone());
+    exact("alt", alt(a), alt(b));
+
+    let qual = |record: &VcfRecord| record.qual.map_or_else(|| ".".into(), |q| q.to_string());
+    push_if_different(&mut diffs, "qual", qual(a), qual(b), epsilon);
+
+    let (filter_a, filter_b) = (filter(a), filter(b));
+    if filter_a != filter_b {
+        diffs.push(FieldDiff::new("filter", filter_a, filter_b));
+    }
+
+    let keys: BTreeSet<&str> = a.info.keys().chain(b.info.keys()).map(|k| &**k).collect();
+    for key in keys {
+        let value = |record: &VcfRecord| record.info.get(key).map_or_else(|| ".".into(), info_text);
+        push_if_different(
+            &mut diffs,
+            &format!("info.{}", key),
+            value(a),
+            value(b),
+            epsilon,
+        );
+    }
+
+    for (i, name) in sample_names(a, b).into_iter().enumerate() {
+        let sample_a = find_sample(a, &name, i);
+        let sample_b = find_sample(b, &name, i);
+        let label = if name.is_empty() { i.to_string() } else { name };
+
+        let gt = |sample: Option<&SampleData>| {
+            sample
+                .and_then(|s| s.genotype.as_ref())
+                .map_or_else(|| ".".into(), genotype)
+        };
+        let (gt_a, gt_b) = (gt(sample_a), gt(sample_b));
+        if gt_a != gt_b {
+            diffs.push(FieldDiff::new(format!("samples[{}].GT", label), gt_a, gt_b));
+        }
+
+        let keys: BTreeSet<&str> = sample_a
+            .into_iter()
+            .chain(sample_b)
+            .flat_map(|s| s.fields.keys().map(String::as_str))
+            .collect();
+        for key in keys {
+            let value = |sample: Option<&SampleData>| {
+                sample
+                    .and_then(|s| s.fields.get(key))
+                    .map_or_else(|| ".".into(), String::clone)
+            };
+            let field = format!("samples[{}].{}", label, key);
+            push_if_different(
+                &mut diffs,
+                &field,
+                value(sample_a),
+                value(sample_b),
+                epsilon,
+            );
+        }
+    }
+
+    diffs
+}
+
+/// Definitions, samples and metadata that differ between two headers
+///
+/// Paths are `fileformat`, `reference`, `info.ID`, `format.ID`,
+/// `filter.ID`, `contig.ID` and `samples`; a definition is rendered as
+/// its `##` line fields, e.g. `Number=1,Type=Integer,Description=Depth`.
+/// Definition order is ignored, sample order is not.
+pub fn diff_headers(a: &VcfHeader, b: &VcfHeader) -> Vec<FieldDiff> {
+    let mut diffs = Vec::new();
+
+    if a.file_format != b.file_format {
+        diffs.push(FieldDiff::new("fileformat", &a.file_format, &b.file_format));
+    }
+    let reference = |header: &VcfHeader| header.reference.clone().unwrap_or_else(|| ".".into());
+    if a.reference != b.reference {
+        diffs.push(FieldDiff::new("reference", reference(a), reference(b)));
+    }
+
+    let info = |header: &VcfHeader| {
+        header
+            .info_fields
+            .iter()
+            .map(|d| {
+                (
+                    d.id.clone(),
+                    definition(&d.number, &d.field_type, &d.description),
+                )
+            })
+            .collect()
+    };
+    diff_definitions(&mut diffs, "info", info(a), info(b));
+
+    let format = |header: &VcfHeader| {
+        header
+            .format_fields
+            .iter()
+            .map(|d| {
+                (
+                    d.id.clone(),
+                    definition(&d.number, &d.field_type, &d.description),
+                )
+            })
+            .collect()
+    };
+    diff_definitions(&mut diffs, "format", format(a), format(b));
+
+    let filters = |header: &VcfHeader| {
+        header
+            .filters
+            .iter()
+            .map(|d| (d.id.clone(), format!("Description={}", d.description)))
+            .collect()
+    };
+    diff_definitions(&mut diffs, "filter", filters(a), filters(b));
+
+    let contigs = |header: &VcfHeader| {
+        header
+            .contigs
+            .iter()
+            .map(|c| {
+                let length = c.length.map_or_else(|| ".".into(), |l| l.to_string());
+                (c.id.clone(), format!("length={}", length))
+            })
+            .collect()
+    };
+    diff_definitions(&mut diffs, "contig", contigs(a), contigs(b));
+
+    if a.samples != b.samples {
+        diffs.push(FieldDiff::new(
+            "samples",
+            a.samples.join(","),
+            b.samples.join(","),
+        ));
+    }
+
+    diffs
+}
+
+fn definition(number: &str, field_type: &str, description: &str) -> String {
+    format!(
+        "Number={},Type={},Description={}",
+        number, field_type, description
+    )
+}
+
+/// Diff `(ID, rendering)` lists by ID, in ID order
+fn diff_definitions(
+    diffs: &mut Vec<FieldDiff>,
+    kind: &str,
+    a: Vec<(String, String)>,
+    b: Vec<(String, String)>,
+) {
+    let ids: BTreeSet<&str> = a.iter().chain(&b).map(|(id, _)| id.as_str()).collect();
+    let find = |list: &[(String, String)], id: &str| {
+        list.iter()
+            .find(|(i, _)| i == id)
+            .map_or_else(|| ".".to_string(), |(_, rendered)| rendered.clone())
+    };
+
+    for id in ids {
+        let (x, y) = (find(&a, id), find(&b, id));
+        if x != y {
+            diffs.push(FieldDiff::new(format!("{}.{}", kind, id), x, y));
+        }
+    }
+}
+
+fn push_if_different(diffs: &mut Vec<FieldDiff>, field: &str, a: String, b: String, epsilon: f64) {
+    if !values_match(&a, &b, epsilon) {
+        diffs.push(FieldDiff::new(field, a, b));
+    }
+}
+
+/// Whether two comma-separated values agree element by element, with
+/// numbers compared to within `epsilon`
+fn values_match(a: &str, b: &str, epsilon: f64) -> bool {
+    if a == b {
+        return true;
+    }
+    let (xs, ys): (Vec<&str>, Vec<&str>) = (a.split(',').collect(), b.split(',').collect());
+    xs.len() == ys.len()
+        && xs
+            .iter()
+            .zip(&ys)
+            .all(|(x, y)| match (x.parse::<f64>(), y.parse::<f64>()) {
+                (Ok(x), Ok(y)) => x == y || (x - y).abs() <= epsilon || (x.is_nan() && y.is_nan()),
+                _ => x == y,
+            })
+}
+
+fn id(record: &VcfRecord) -> String {
+    record.id.clone().unwrap_or_else(|| ".".into())
+}
+
+fn alt(record: &VcfRecord) -> String {
+    if record.alternate.is_empty() {
+        ".".into()
+    } else {
+        record.alternate.join(",")
+    }
+}
+
+/// FILTER with failed filters sorted, so `q10;s50` matches `s50;q10`
+fn filter(record: &VcfRecord) -> String {
+    match &record.filter {
+        FilterStatus::Pass => "PASS".into(),
+        FilterStatus::Missing => ".".into(),
+        FilterStatus::Failed(filters) => {
+            let mut filters = filters.clone();
+            filters.sort();
+            filters.join(";")
+        }
+    }
+}
+
+/// INFO value as written, with a flag shown as `true` so it differs from
+/// an absent key
+fn info_text(value: &InfoValue) -> String {
+    match value {
+        InfoValue::Flag => "true".into(),
+        value => info_value(value),
+    }
+}
+
+/// Sample names of `a` in column order, then those only `b` has
+fn sample_names(a: &VcfRecord, b: &VcfRecord) -> Vec<String> {
+    let mut names: Vec<String> = a.samples.iter().map(|s| s.name.clone()).collect();
+    for sample in &b.samples {
+        if sample.name.is_empty() || !names.contains(&sample.name) {
+            names.push(sample.name.clone());
+        }
+    }
+    names
+}
+
+/// The sample called `name`, or the `column`th one if samples are unnamed
+fn find_sample<'a>(record: &'a VcfRecord, name: &str, column: usize) -> Option<&'a SampleData> {
+    if name.is_empty() {
+        record.samples.get(column).filter(|s| s.name.is_empty())
+    } else {
+        record.samples.iter().find(|s| s.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const HEADER: &str = "##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total Depth\">
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read Depth\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tSAMPLE1\tSAMPLE2
+";
+
+    fn parse(content: &str) -> (VcfHeader, Vec<VcfRecord>) {
+        VcfParser::new().parse_str(content).unwrap()
+    }
+
+    fn record(line: &str) -> VcfRecord {
+        parse(&format!("{}{}\n", HEADER, line)).1.remove(0)
+    }
+
+    #[test]
+    fn test_diff_records_reports_changed_fields() {
+        let a = record(
+            "chr1\t100\trs1\tA\tG,T\t30\tq10;s50\tDP=50;AF=0.25,0.5;DB\tGT:DP\t0/1:25\t1|1:30",
+        );
+        // INFO and FORMAT keys reordered, FILTER reordered, QUAL as a
+        // float, one AF element and SAMPLE2's DP changed
+        let b = record(
+            "chr1\t100\trs1\tA\tG,T\t30.0\ts50;q10\tDB;AF=0.25,0.75;DP=50\tDP:GT\t25:0/1\t31:1|1",
+        );
+
+        assert_eq!(
+            diff_records(&a, &b),
+            [
+                FieldDiff::new("info.AF", "0.25,0.5", "0.25,0.75"),
+                FieldDiff::new("samples[SAMPLE2].DP", "30", "31"),
+            ]
+        );
+        assert!(diff_records(&a, &a).is_empty());
+    }
+
+    #[test]
+    fn test_diff_records_epsilon_and_absent_values() {
+        let a = record("chr1\t100\t.\tA\tG\t30\tPASS\tDP=50;AF=0.5;DB\tGT:DP\t0/1:25\t1|1:30");
+        let b = record("chr1\t100\t.\tA\tG\t30.001\tPASS\tDP=50;AF=0.5000001\tGT\t0|1\t1|1");
+
+        assert_eq!(
+            diff_records(&a, &b),
+            [
+                FieldDiff::new("qual", "30", "30.001"),
+                FieldDiff::new("info.DB", "true", "."),
+                FieldDiff::new("samples[SAMPLE1].GT", "0/1", "0|1"),
+                FieldDiff::new("samples[SAMPLE1].DP", "25", "."),
+                FieldDiff::new("samples[SAMPLE2].DP", "30", "."),
+            ]
+        );
+        assert_eq!(diff_records_with_epsilon(&a, &b, 0.01).len(), 4);
+        assert_eq!(diff_records_with_epsilon(&a, &b, 0.0).len(), 6);
+    }
+
+    #[test]
+    fn test_diff_headers() {
+        let (a, _) = parse(HEADER);
+        let other = HEADER
+            .replace("VCFv4.2", "VCFv4.3")
+            .replace("Allele Frequency", "Alt allele frequency")
+            .replace(
+                "##FORMAT=<ID=DP",
+                "##FILTER=<ID=q10,Description=\"Quality below 10\">\n##FORMAT=<ID=DP",
+            )
+            .replace("SAMPLE1\tSAMPLE2", "SAMPLE2\tSAMPLE1");
+        let (b, _) = parse(&other);
+
+        assert_eq!(
+            diff_headers(&a, &b),
+            [
+                FieldDiff::new("fileformat", "VCFv4.2", "VCFv4.3"),
+                FieldDiff::new(
+                    "info.AF",
+                    "Number=A,Type=Float,Description=Allele Frequency",
+                    "Number=A,Type=Float,Description=Alt allele frequency"
+                ),
+                FieldDiff::new("filter.q10", ".", "Description=Quality below 10"),
+                FieldDiff::new("samples", "SAMPLE1,SAMPLE2", "SAMPLE2,SAMPLE1"),
+            ]
+        );
+        assert!(diff_headers(&a, &a).is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+//! Dosage Matrices
+//!
+//! Samples × variants matrices of ALT allele dosages for PCA, GWAS and
+//! clustering tools: 0, 1 or 2 from called genotypes, the imputed `DS`
+//! value where a record has one, and NaN for missing calls.
+
+use crate::compare::VariantKey;
+use crate::types::{SampleData, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
+
+/// Order of [`DosageMatrix::data`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatrixLayout {
+    /// One sample's dosages after another: `data[sample * n_variants + variant]`
+    #[default]
+    RowMajor,
+    /// One variant's dosages after another: `data[variant * n_samples + sample]`
+    ColumnMajor,
+}
+
+/// Samples × variants dosage matrix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DosageMatrix {
+    pub data: Vec<f32>,
+    pub layout: MatrixLayout,
+    /// Row labels, from the header
+    pub samples: Vec<String>,
+    /// Column labels; a multi-allelic record has one column per ALT allele
+    pub variant_keys: Vec<VariantKey>,
+    /// Fraction of samples with a NaN dosage, per column
+    pub missing_rate_per_variant: Vec<f32>,
+}
+
+impl DosageMatrix {
+    pub fn n_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn n_variants(&self) -> usize {
+        self.variant_keys.len()
+    }
+
+    /// Dosage of `sample` for column `variant`
+    pub fn get(&self, sample: usize, variant: usize) -> f32 {
+        match self.layout {
+            MatrixLayout::RowMajor => self.data[sample * self.n_variants() + variant],
+            MatrixLayout::ColumnMajor => self.data[variant * self.n_samples() + sample],
+        }
+    }
+}
+
+/// Row-major dosage matrix of `records` for the header's samples
+///
+/// Each ALT allele is a column whose values are the sample's `DS` for that
+/// allele when the record has a numeric one, and otherwise the copies of
+/// the allele in GT; a genotype with any missing allele, or no genotype,
+/// is NaN. With `biallelic_only`, records with more than one ALT allele
+/// are left out. Records without ALT alleles have no columns.
+pub fn dosage_matrix(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    biallelic_only: bool,
+) -> DosageMatrix {
+    dosage_matrix_with_layout(records, header, biallelic_only, MatrixLayout::RowMajor)
+}
+
+/// [`dosage_matrix`] in the given layout
+pub fn dosage_matrix_with_layout(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    biallelic_only: bool,
+    layout: MatrixLayout,
+) -> DosageMatrix {
+    let mut builder = Columns::new(header, biallelic_only);
+    for record in records {
+        builder.push(record);
+    }
+    builder.finish(layout)
+}
+
+/// Dosage matrices of `chunk_size` columns at a time, for inputs whose
+/// full matrix would not fit in memory
+///
+/// `on_chunk` gets each chunk as it fills and a last, shorter one for the
+/// remainder; a multi-allelic record is never split between chunks. In
+/// `ColumnMajor` layout the chunks' data concatenated is the data of the
+/// full matrix. Records may be owned, so a streaming reader's output need
+/// not be collected first. A `chunk_size` of 0 is taken as 1.
+pub fn dosage_chunks<I>(
+    records: I,
+    header: &VcfHeader,
+    biallelic_only: bool,
+    layout: MatrixLayout,
+    chunk_size: usize,
+    mut on_chunk: impl FnMut(DosageMatrix),
+) where
+    I: IntoIterator,
+    I::Item: Borrow<VcfRecord>,
+{
+    let chunk_size = chunk_size.max(1);
+    let mut builder = Columns::new(header, biallelic_only);
+    for record in records {
+        builder.push(record.borrow());
+        // A multi-allelic record can overshoot by a few columns
+        if builder.keys.len() >= chunk_size {
+            let full = std::mem::replace(&mut builder, Columns::new(header, biallelic_only));
+            on_chunk(full.finish(layout));
+        }
+    }
+    if !builder.keys.is_empty() {
+        on_chunk(builder.finish(layout));
+    }
+}
+
+/// Column-major dosages as they are read
+struct Columns<'h> {
+    header: &'h VcfHeader,
+    biallelic_only: bool,
+    data: Vec<f32>,
+    keys: Vec<VariantKey>,
+    missing: Vec<f32>,
+}
+
+impl<'h> Columns<'h> {
+    fn new(header: &'h VcfHeader, biallelic_only: bool) -> Self {
+        Self {
+            header,
+            biallelic_only,
+            data: Vec::new(),
+            keys: Vec::new(),
+            missing: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, record: &VcfRecord) {
+        if self.biallelic_only && record.alternate.len() > 1 {
+            return;
+        }
+        let n_samples = self.header.samples.len();
+        for (i, alt) in record.alternate.iter().enumerate() {
+            let mut missing = 0;
+            for sample in 0..n_samples {
+                let value = record
+                    .samples
+                    .get(sample)
+                    .and_then(|s| dosage(s, i + 1))
+                    .unwrap_or(f32::NAN);
+                missing += usize::from(value.is_nan());
+                self.data.push(value);
+            }
+            self.keys.push(VariantKey {
+                chrom: record.chrom.clone(),
+                pos: record.pos,
+                reference: record.reference.clone(),
+                alternate: alt.clone(),
+            });
+            self.missing.push(if n_samples == 0 {
+                0.0
+            } else {
+                missing as f32 / n_samples as f32
+            });
+        }
+    }
+
+    fn finish(self, layout: MatrixLayout) -> DosageMatrix {
+        let n_samples = self.header.samples.len();
+        let data = match layout {
+            MatrixLayout::ColumnMajor => self.data,
+            MatrixLayout::RowMajor => {
+                let n_variants = self.keys.len();
+                let mut rows = vec![0.0; self.data.len()];
+                for (i, value) in self.data.into_iter().enumerate() {
+                    let (variant, sample) = (i / n_samples, i % n_samples);
+                    rows[sample * n_variants + variant] = value;
+                }
+                rows
+            }
+        };
+        DosageMatrix {
+            data,
+            layout,
+            samples: self.header.samples.clone(),
+            variant_keys: self.keys,
+            missing_rate_per_variant: self.missing,
+        }
+    }
+}
+
+/// `DS` for `allele` (1-based), else its copies in GT
+fn dosage(sample: &SampleData, allele: usize) -> Option<f32> {
+    let imputed = sample
+        .fields
+        .get("DS")
+        .and_then(|ds| ds.split(',').nth(allele - 1)?.parse::<f32>().ok())
+        .filter(|ds| ds.is_finite());
+    if imputed.is_some() {
+        return imputed;
+    }
+
+    let genotype = sample.genotype.as_ref()?;
+    let mut copies = 0;
+    for a in &genotype.alleles {
+        copies += u8::from(usize::from((*a)?) == allele);
+    }
+    Some(f32::from(copies))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const GENOTYPES: &str = "##fileformat=VCFv4.2
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DS,Number=A,Type=Float,Description=\"Alternate allele dosage\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3\tS4
+chr1\t100\t.\tA\tG\t.\tPASS\t.\tGT\t0/0\t0/1\t1|1\t./.
+chr1\t200\t.\tC\tT\t.\tPASS\t.\tGT:DS\t0/0:0.2\t0/1:.\t1/1:1.7\t./.:0.9
+chr1\t300\t.\tG\tA,C\t.\tPASS\t.\tGT\t1/2\t2/2\t0/.\t0/1
+chr1\t400\t.\tT\t.\t.\tPASS\t.\tGT\t0/0\t0/0\t0/0\t0/0
+";
+
+    fn parse() -> (VcfHeader, Vec<VcfRecord>) {
+        VcfParser::new().parse_str(GENOTYPES).unwrap()
+    }
+
+    fn column(matrix: &DosageMatrix, variant: usize) -> Vec<f32> {
+        (0..matrix.n_samples())
+            .map(|s| matrix.get(s, variant))
+            .collect()
+    }
+
+    /// Dosages with NaN shown as -1 so they compare
+    fn shown(values: Vec<f32>) -> Vec<f32> {
+        values
+            .into_iter()
+            .map(|v| if v.is_nan() { -1.0 } else { v })
+            .collect()
+    }
+
+    #[test]
+    fn test_genotype_and_ds_dosages() {
+        let (header, records) = parse();
+        let matrix = dosage_matrix(&records, &header, false);
+        assert_eq!(matrix.samples, ["S1", "S2", "S3", "S4"]);
+        assert_eq!(matrix.n_variants(), 4);
+        assert_eq!(matrix.data.len(), 16);
+
+        assert_eq!(shown(column(&matrix, 0)), [0.0, 1.0, 2.0, -1.0]);
+        // DS wins where present; S2's missing DS falls back to GT
+        assert_eq!(shown(column(&matrix, 1)), [0.2, 1.0, 1.7, 0.9]);
+        // One column per ALT allele of the multi-allelic record
+        assert_eq!(shown(column(&matrix, 2)), [1.0, 0.0, -1.0, 1.0]);
+        assert_eq!(shown(column(&matrix, 3)), [1.0, 2.0, -1.0, 0.0]);
+        assert_eq!(matrix.variant_keys[3].to_string(), "chr1:300:G:C");
+        assert_eq!(matrix.missing_rate_per_variant, [0.25, 0.0, 0.25, 0.25]);
+
+        // Row-major: S2's row is the second block of four
+        assert_eq!(shown(matrix.data[4..8].to_vec()), [1.0, 1.0, 0.0, 2.0]);
+
+        let biallelic = dosage_matrix(&records, &header, true);
+        assert_eq!(biallelic.n_variants(), 2);
+        assert_eq!(biallelic.missing_rate_per_variant, [0.25, 0.0]);
+    }
+
+    #[test]
+    fn test_layouts_and_chunks_agree() {
+        let (header, records) = parse();
+        let rows = dosage_matrix(&records, &header, false);
+        let columns =
+            dosage_matrix_with_layout(&records, &header, false, MatrixLayout::ColumnMajor);
+        assert_eq!(shown(columns.data[4..8].to_vec()), [0.2, 1.0, 1.7, 0.9]);
+        for sample in 0..4 {
+            for variant in 0..4 {
+                assert_eq!(
+                    shown(vec![rows.get(sample, variant)]),
+                    shown(vec![columns.get(sample, variant)])
+                );
+            }
+        }
+
+        let mut chunks = Vec::new();
+        dosage_chunks(
+            records.iter(),
+            &header,
+            false,
+            MatrixLayout::ColumnMajor,
+            2,
+            |chunk| chunks.push(chunk),
+        );
+        // The multi-allelic record fills the second chunk on its own
+        let sizes: Vec<usize> = chunks.iter().map(DosageMatrix::n_variants).collect();
+        assert_eq!(sizes, [2, 2]);
+        let joined: Vec<f32> = chunks.iter().flat_map(|c| c.data.clone()).collect();
+        assert_eq!(shown(joined), shown(columns.data.clone()));
+
+        // Owned records from a parser work as well
+        let mut total = 0;
+        dosage_chunks(records, &header, true, MatrixLayout::RowMajor, 1, |chunk| {
+            total += chunk.n_variants()
+        });
+        assert_eq!(total, 2);
+    }
+}
@@ -37,6 +37,30 @@ pub enum VcfError {
     #[error("Parse error: {0}")]
     Parse(String),
 
+    #[error("Too many invalid records: more than {limit} skipped (line {line})")]
+    TooManyInvalidRecords { line: usize, limit: usize },
+
+    #[error("Invalid region: {0}")]
+    InvalidRegion(String),
+
+    #[error("Invalid filter expression at column {column}: {message}")]
+    InvalidFilterExpression { column: usize, message: String },
+
+    #[error("Invalid tabix index: {0}")]
+    InvalidIndex(String),
+
+    #[error("Invalid gene model at line {line}: {message}")]
+    InvalidGeneModel { line: usize, message: String },
+
+    #[error("Invalid FASTA: {0}")]
+    InvalidFasta(String),
+
+    #[error("Record index {index} out of range ({len} records)")]
+    RecordOutOfRange { index: usize, len: usize },
+
+    #[error("Truncated gzip stream: input ends in the middle of a compressed block")]
+    TruncatedGzip,
+
     #[error("UTF-8 encoding error: {0}")]
     Utf8(#[from] std::str::Utf8Error),
 
@@ -80,6 +104,55 @@ impl VcfError {
         }
     }
 
+    /// Stable SCREAMING_SNAKE_CASE code for the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "IO",
+            Self::InvalidFormat(_) => "INVALID_FORMAT",
+            Self::MissingHeader => "MISSING_HEADER",
+            Self::InvalidHeader(_) => "INVALID_HEADER",
+            Self::InvalidRecord { .. } => "INVALID_RECORD",
+            Self::MissingField { .. } => "MISSING_FIELD",
+            Self::InvalidPosition { .. } => "INVALID_POSITION",
+            Self::InvalidQuality { .. } => "INVALID_QUALITY",
+            Self::UnknownChromosome(_) => "UNKNOWN_CHROMOSOME",
+            Self::Parse(_) => "PARSE",
+            Self::TooManyInvalidRecords { .. } => "TOO_MANY_INVALID_RECORDS",
+            Self::InvalidRegion(_) => "INVALID_REGION",
+            Self::InvalidFilterExpression { .. } => "INVALID_FILTER_EXPRESSION",
+            Self::InvalidIndex(_) => "INVALID_INDEX",
+            Self::InvalidGeneModel { .. } => "INVALID_GENE_MODEL",
+            Self::InvalidFasta(_) => "INVALID_FASTA",
+            Self::RecordOutOfRange { .. } => "RECORD_OUT_OF_RANGE",
+            Self::TruncatedGzip => "TRUNCATED_GZIP",
+            Self::Utf8(_) => "UTF8",
+            Self::Serialization(_) => "SERIALIZATION",
+        }
+    }
+
+    /// Line number the error refers to, if any
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::InvalidRecord { line, .. }
+            | Self::MissingField { line, .. }
+            | Self::InvalidPosition { line, .. }
+            | Self::InvalidQuality { line, .. }
+            | Self::TooManyInvalidRecords { line, .. }
+            | Self::InvalidGeneModel { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    /// VCF column the error refers to, if any
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            Self::MissingField { field, .. } => Some(field),
+            Self::InvalidPosition { .. } => Some("POS"),
+            Self::InvalidQuality { .. } => Some("QUAL"),
+            _ => None,
+        }
+    }
+
     /// Check if error is recoverable (can continue parsing)
     pub fn is_recoverable(&self) -> bool {
         matches!(
@@ -100,7 +173,7 @@ pub struct ParseWarning {
     pub category: WarningCategory,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum WarningCategory {
     MissingInfo,
     UnknownFilter,
@@ -109,6 +182,19 @@ pub enum WarningCategory {
     Other,
 }
 
+impl WarningCategory {
+    /// Stable snake_case name, e.g. for serialization
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::MissingInfo => "missing_info",
+            Self::UnknownFilter => "unknown_filter",
+            Self::MalformedGenotype => "malformed_genotype",
+            Self::DeprecatedFormat => "deprecated_format",
+            Self::Other => "other",
+        }
+    }
+}
+
 impl ParseWarning {
     pub fn new(line: usize, message: impl Into<String>, category: WarningCategory) -> Self {
         Self {
@@ -146,6 +232,18 @@ mod tests {
         assert!(!VcfError::InvalidFormat("test".into()).is_recoverable());
     }
 
+    #[test]
+    fn test_error_code_line_and_field() {
+        let error = VcfError::invalid_position(12, "abc");
+        assert_eq!(error.code(), "INVALID_POSITION");
+        assert_eq!(error.line(), Some(12));
+        assert_eq!(error.field(), Some("POS"));
+
+        assert_eq!(VcfError::MissingHeader.code(), "MISSING_HEADER");
+        assert_eq!(VcfError::MissingHeader.line(), None);
+        assert_eq!(VcfError::missing_field(3, "REF").field(), Some("REF"));
+    }
+
     #[test]
     fn test_warning() {
         let warning = ParseWarning::new(15, "Unknown INFO field", WarningCategory::MissingInfo);
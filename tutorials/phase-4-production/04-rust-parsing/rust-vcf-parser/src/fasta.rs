@@ -0,0 +1,291 @@
+//! Indexed FASTA
+//!
+//! Random access to reference sequence in a plain-text FASTA file
+//! through a samtools-style `.fai` index, so looking up a few bases costs
+//! one seek rather than loading the genome. Every line of a sequence but
+//! its last must hold the same number of bases, as `samtools faidx`
+//! requires.
+
+use crate::error::{VcfError, VcfResult};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// One line of a `.fai` index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FaiRecord {
+    pub name: String,
+    /// Bases in the sequence
+    pub length: u64,
+    /// Byte offset of the sequence's first base
+    pub offset: u64,
+    /// Bases per full line
+    pub line_bases: u64,
+    /// Bytes per full line, with the line terminator
+    pub line_width: u64,
+}
+
+impl FaiRecord {
+    /// Byte offset of 0-based position `pos`
+    fn byte_offset(&self, pos: u64) -> u64 {
+        self.offset + pos / self.line_bases * self.line_width + pos % self.line_bases
+    }
+}
+
+/// Anything a FASTA can be read from
+trait Source: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> Source for T {}
+
+/// FASTA file with its `.fai` index
+///
+/// Lookups take `&self`; reads go through a lock, so one index can be
+/// shared between threads.
+pub struct FastaIndex {
+    records: Vec<FaiRecord>,
+    by_name: HashMap<String, usize>,
+    source: Mutex<Box<dyn Source>>,
+}
+
+impl FastaIndex {
+    /// Open a FASTA file, reading `<path>.fai` if there is one and
+    /// indexing the file as it stands otherwise
+    pub fn open(path: impl AsRef<Path>) -> VcfResult<Self> {
+        let path = path.as_ref();
+        let mut fai_path = PathBuf::from(path);
+        fai_path.as_mut_os_string().push(".fai");
+
+        let mut file = File::open(path)?;
+        let records = if fai_path.exists() {
+            parse_fai(&std::fs::read_to_string(&fai_path)?)?
+        } else {
+            let records = build_fai(BufReader::new(&mut file))?;
+            file.seek(SeekFrom::Start(0))?;
+            records
+        };
+        Self::new(records, Box::new(file))
+    }
+
+    /// Index FASTA text held in memory
+    pub fn from_bytes(data: Vec<u8>) -> VcfResult<Self> {
+        let records = build_fai(&data[..])?;
+        Self::new(records, Box::new(Cursor::new(data)))
+    }
+
+    /// FASTA text held in memory with its `.fai` text
+    pub fn from_bytes_with_fai(data: Vec<u8>, fai: &str) -> VcfResult<Self> {
+        Self::new(parse_fai(fai)?, Box::new(Cursor::new(data)))
+    }
+
+    fn new(records: Vec<FaiRecord>, source: Box<dyn Source>) -> VcfResult<Self> {
+        let mut by_name = HashMap::with_capacity(records.len());
+        for (i, record) in records.iter().enumerate() {
+            if by_name.insert(record.name.clone(), i).is_some() {
+                return Err(VcfError::InvalidFasta(format!(
+                    "sequence {} appears more than once",
+                    record.name
+                )));
+            }
+        }
+        Ok(Self {
+            records,
+            by_name,
+            source: Mutex::new(source),
+        })
+    }
+
+    /// The index entries, in file order
+    pub fn records(&self) -> &[FaiRecord] {
+        &self.records
+    }
+
+    /// Length of sequence `name`, if the FASTA has it
+    pub fn sequence_length(&self, name: &str) -> Option<u64> {
+        self.record(name).map(|r| r.length)
+    }
+
+    /// The index as `.fai` text
+    pub fn to_fai(&self) -> String {
+        let mut fai = String::new();
+        for r in &self.records {
+            let _ = writeln!(
+                fai,
+                "{}\t{}\t{}\t{}\t{}",
+                r.name, r.length, r.offset, r.line_bases, r.line_width
+            );
+        }
+        fai
+    }
+
+    /// Up to `len` bases of `name` from 0-based `start`, as written in
+    /// the file; shorter where the sequence ends, and `None` if the FASTA
+    /// has no sequence `name`
+    pub fn fetch(&self, name: &str, start: u64, len: u64) -> VcfResult<Option<String>> {
+        let Some(record) = self.record(name) else {
+            return Ok(None);
+        };
+        let start = start.min(record.length);
+        let end = start.saturating_add(len).min(record.length);
+        if start == end {
+            return Ok(Some(String::new()));
+        }
+
+        let first = record.byte_offset(start);
+        let mut bytes = vec![0; (record.byte_offset(end - 1) - first + 1) as usize];
+        {
+            // A read that panicked leaves nothing to clean up, as every
+            // read seeks first
+            let mut source = self
+                .source
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            source.seek(SeekFrom::Start(first))?;
+            source.read_exact(&mut bytes)?;
+        }
+        bytes.retain(|b| !b.is_ascii_whitespace());
+        String::from_utf8(bytes)
+            .map(Some)
+            .map_err(|_| VcfError::InvalidFasta(format!("{} has non-UTF-8 bases", name)))
+    }
+
+    fn record(&self, name: &str) -> Option<&FaiRecord> {
+        self.by_name.get(name).map(|&i| &self.records[i])
+    }
+}
+
+/// Parse `.fai` text; extra columns, as in FASTQ indexes, are ignored
+pub fn parse_fai(text: &str) -> VcfResult<Vec<FaiRecord>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            let bad = || VcfError::InvalidFasta(format!("bad .fai line {}: {}", i + 1, line));
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [name, length, offset, line_bases, line_width, ..] = fields[..] else {
+                return Err(bad());
+            };
+            let number = |s: &str| s.parse::<u64>().map_err(|_| bad());
+            let record = FaiRecord {
+                name: name.to_string(),
+                length: number(length)?,
+                offset: number(offset)?,
+                line_bases: number(line_bases)?,
+                line_width: number(line_width)?,
+            };
+            if record.length > 0
+                && (record.line_bases == 0 || record.line_width < record.line_bases)
+            {
+                return Err(bad());
+            }
+            Ok(record)
+        })
+        .collect()
+}
+
+/// Index FASTA text by reading it through once
+pub fn build_fai(mut reader: impl BufRead) -> VcfResult<Vec<FaiRecord>> {
+    let mut records: Vec<FaiRecord> = Vec::new();
+    // Whether the current sequence has had a line shorter than a full one,
+    // which must be its last
+    let mut short_line = false;
+    let mut offset = 0u64;
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        let width = reader.read_until(b'\n', &mut line)? as u64;
+        if width == 0 {
+            break;
+        }
+        offset += width;
+        let text = line.trim_ascii_end();
+
+        if let Some(name) = text.strip_prefix(b">") {
+            let name = String::from_utf8_lossy(name);
+            let name = name.split_whitespace().next().unwrap_or_default();
+            records.push(FaiRecord {
+                name: name.to_string(),
+                length: 0,
+                offset,
+                line_bases: 0,
+                line_width: 0,
+            });
+            short_line = false;
+            continue;
+        }
+
+        let bases = text.len() as u64;
+        let Some(record) = records.last_mut() else {
+            if bases == 0 {
+                continue;
+            }
+            return Err(VcfError::InvalidFasta(
+                "sequence before the first '>' line".to_string(),
+            ));
+        };
+        if record.line_bases == 0 {
+            record.line_bases = bases;
+            record.line_width = width;
+        } else if bases > 0 && (short_line || bases > record.line_bases) {
+            return Err(VcfError::InvalidFasta(format!(
+                "{} has lines of uneven length",
+                record.name
+            )));
+        }
+        short_line |= bases < record.line_bases;
+        record.length += bases;
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FASTA: &str = ">chr1 first\nACGTACGTAC\nGTACGTACGT\nacgt\n>chr2\nTTTTGGGG\nCC\n>empty\n";
+
+    #[test]
+    fn test_build_fai_matches_samtools() {
+        let records = build_fai(FASTA.as_bytes()).unwrap();
+        let fasta = FastaIndex::from_bytes(FASTA.as_bytes().to_vec()).unwrap();
+        // As `samtools faidx` writes it
+        assert_eq!(
+            fasta.to_fai(),
+            "chr1\t24\t12\t10\t11\nchr2\t10\t45\t8\t9\nempty\t0\t64\t0\t0\n"
+        );
+        assert_eq!(parse_fai(&fasta.to_fai()).unwrap(), records);
+        assert_eq!(fasta.sequence_length("chr2"), Some(10));
+    }
+
+    #[test]
+    fn test_fetch_across_lines() {
+        let fasta = FastaIndex::from_bytes(FASTA.as_bytes().to_vec()).unwrap();
+        let fetch = |name, start, len| fasta.fetch(name, start, len).unwrap();
+        assert_eq!(fetch("chr1", 0, 3).as_deref(), Some("ACG"));
+        assert_eq!(fetch("chr1", 8, 4).as_deref(), Some("ACGT"));
+        assert_eq!(fetch("chr1", 18, 6).as_deref(), Some("GTacgt"));
+        // Clipped at the end of the sequence
+        assert_eq!(fetch("chr1", 22, 5).as_deref(), Some("gt"));
+        assert_eq!(fetch("chr1", 30, 5).as_deref(), Some(""));
+        assert_eq!(fetch("chr2", 7, 3).as_deref(), Some("GCC"));
+        assert_eq!(fetch("chr3", 0, 1), None);
+
+        let crlf = FastaIndex::from_bytes(FASTA.replace('\n', "\r\n").into_bytes()).unwrap();
+        assert_eq!(crlf.fetch("chr1", 8, 4).unwrap().as_deref(), Some("ACGT"));
+    }
+
+    #[test]
+    fn test_rejects_uneven_lines() {
+        for fasta in [">a\nACGT\nAC\nACGT\n", ">a\nACGT\nACGTA\n", "ACGT\n>a\nA\n"] {
+            assert!(matches!(
+                build_fai(fasta.as_bytes()),
+                Err(VcfError::InvalidFasta(_))
+            ));
+        }
+        assert!(FastaIndex::from_bytes(b">a\nA\n>a\nC\n".to_vec()).is_err());
+        assert!(parse_fai("chr1\t10\tx\t10\t11\n").is_err());
+    }
+}
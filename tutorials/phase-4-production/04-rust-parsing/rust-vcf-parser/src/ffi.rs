@@ -0,0 +1,445 @@
+//! C API
+//!
+//! `extern "C"` functions for embedding the parser in languages with a C
+//! FFI (Julia, R, C itself). Enabled by the `capi` feature; the header is
+//! generated with cbindgen into `include/vcf_parser.h`.
+//!
+//! Conventions:
+//! - A parsed file is an opaque `VcfHandle`, released with `vcf_free`.
+//! - Fallible calls return a [`VcfErrorCode`]; the message of the last
+//!   error on the calling thread is available from `vcf_last_error_message`.
+//! - Strings are UTF-8 and copied into caller-allocated buffers. String
+//!   functions return the buffer size needed, including the trailing NUL,
+//!   and only write when `buflen` is at least that large, so passing
+//!   `NULL, 0` asks for the size. They return 0 on error.
+//! - Panics are caught and reported as `VCF_ERROR_CODE_PANIC`; they never
+//!   unwind into the caller.
+
+use crate::error::VcfError;
+use crate::gzip;
+use crate::parser::{calculate_stats, VcfParser};
+use crate::types::{VcfRecord, VcfStats};
+use crate::view::filter_string;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+/// Status returned by fallible C API calls. Values are stable.
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VcfErrorCode {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// An argument was invalid, e.g. a path that is not UTF-8
+    InvalidArgument = 2,
+    /// The file could not be read
+    Io = 3,
+    /// The file is not valid VCF
+    Parse = 4,
+    /// A record index was past the end
+    OutOfRange = 5,
+    /// The parser panicked; this is a bug
+    Panic = 6,
+}
+
+/// Parsed VCF file
+pub struct VcfHandle {
+    records: Vec<VcfRecord>,
+    stats: VcfStats,
+}
+
+/// Summary counts filled in by `vcf_stats`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FfiStats {
+    pub total_records: u64,
+    pub snps: u64,
+    pub insertions: u64,
+    pub deletions: u64,
+    pub complex: u64,
+    pub passed_filter: u64,
+    pub failed_filter: u64,
+    /// Number of distinct chromosomes
+    pub chromosomes: u64,
+    pub transitions: u64,
+    pub transversions: u64,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Failure inside a C API call, recorded as the thread's last error
+struct FfiError {
+    code: VcfErrorCode,
+    message: String,
+}
+
+impl FfiError {
+    fn new(code: VcfErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn null(argument: &str) -> Self {
+        Self::new(VcfErrorCode::NullPointer, format!("{} is NULL", argument))
+    }
+}
+
+impl From<VcfError> for FfiError {
+    fn from(error: VcfError) -> Self {
+        let error = match error {
+            VcfError::Io(e) => gzip::gzip_error(e),
+            e => e,
+        };
+        let code = match error {
+            VcfError::Io(_) | VcfError::TruncatedGzip => VcfErrorCode::Io,
+            _ => VcfErrorCode::Parse,
+        };
+        Self::new(code, error.to_string())
+    }
+}
+
+/// Run `f`, recording any error or panic as the last error and turning it
+/// into `on_error(code)`
+fn ffi_call<T>(
+    on_error: impl FnOnce(VcfErrorCode) -> T,
+    f: impl FnOnce() -> Result<T, FfiError>,
+) -> T {
+    let error = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => {
+            LAST_ERROR.with(|last| last.borrow_mut().clear());
+            return value;
+        }
+        Ok(Err(error)) => error,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            FfiError::new(VcfErrorCode::Panic, format!("panic: {}", message))
+        }
+    };
+
+    LAST_ERROR.with(|last| *last.borrow_mut() = error.message);
+    on_error(error.code)
+}
+
+/// Borrow the handle, failing on NULL
+///
+/// # Safety
+/// `handle` must be NULL or a live pointer from `vcf_parse_file`.
+unsafe fn handle_ref<'a>(handle: *const VcfHandle) -> Result<&'a VcfHandle, FfiError> {
+    handle.as_ref().ok_or_else(|| FfiError::null("handle"))
+}
+
+/// Record `index` of `handle`
+///
+/// # Safety
+/// As for [`handle_ref`].
+unsafe fn record<'a>(handle: *const VcfHandle, index: usize) -> Result<&'a VcfRecord, FfiError> {
+    let handle = handle_ref(handle)?;
+    handle.records.get(index).ok_or_else(|| {
+        FfiError::new(
+            VcfErrorCode::OutOfRange,
+            format!("record {} of {}", index, handle.records.len()),
+        )
+    })
+}
+
+/// Copy `value` and a NUL into `buf` if it fits; returns the size needed
+///
+/// # Safety
+/// `buf` must be NULL or valid for `buflen` bytes.
+unsafe fn write_string(value: &str, buf: *mut c_char, buflen: usize) -> usize {
+    let needed = value.len() + 1;
+    if !buf.is_null() && buflen >= needed {
+        ptr::copy_nonoverlapping(value.as_ptr(), buf.cast::<u8>(), value.len());
+        *buf.add(value.len()) = 0;
+    }
+    needed
+}
+
+/// Copy a string field of record `index` into `buf`
+///
+/// # Safety
+/// As for [`handle_ref`] and [`write_string`].
+unsafe fn record_string(
+    handle: *const VcfHandle,
+    index: usize,
+    buf: *mut c_char,
+    buflen: usize,
+    field: impl FnOnce(&VcfRecord) -> String,
+) -> usize {
+    ffi_call(
+        |_| 0,
+        || Ok(write_string(&field(record(handle, index)?), buf, buflen)),
+    )
+}
+
+/// Parse a plain or gzipped VCF file
+///
+/// On success `*out_handle` receives a handle to free with `vcf_free`;
+/// on failure it is set to NULL.
+///
+/// # Safety
+/// `path` must be NULL or a NUL-terminated string, and `out_handle` NULL
+/// or valid for a pointer write.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_parse_file(
+    path: *const c_char,
+    out_handle: *mut *mut VcfHandle,
+) -> VcfErrorCode {
+    ffi_call(
+        |code| code,
+        || {
+            let out_handle = out_handle.as_mut().ok_or_else(|| FfiError::null("out_handle"))?;
+            *out_handle = ptr::null_mut();
+            if path.is_null() {
+                return Err(FfiError::null("path"));
+            }
+            let path = CStr::from_ptr(path).to_str().map_err(|_| {
+                FfiError::new(VcfErrorCode::InvalidArgument, "path is not valid UTF-8")
+            })?;
+
+            let (_, records) = VcfParser::new().parse(gzip::open_vcf(path)?)?;
+            let stats = calculate_stats(&records);
+            *out_handle = Box::into_raw(Box::new(VcfHandle { records, stats }));
+            Ok(VcfErrorCode::Ok)
+        },
+    )
+}
+
+/// Number of records in `handle`, or 0 if it is NULL
+///
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_count(handle: *const VcfHandle) -> usize {
+    ffi_call(|_| 0, || Ok(handle_ref(handle)?.records.len()))
+}
+
+/// CHROM of record `index`
+///
+/// # Safety
+/// `handle` must be NULL or a live handle; `buf` NULL or valid for
+/// `buflen` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_chrom(
+    handle: *const VcfHandle,
+    index: usize,
+    buf: *mut c_char,
+    buflen: usize,
+) -> usize {
+    record_string(handle, index, buf, buflen, |r| r.chrom.clone())
+}
+
+/// ID of record `index`, "." when missing
+///
+/// # Safety
+/// As for `vcf_record_chrom`.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_id(
+    handle: *const VcfHandle,
+    index: usize,
+    buf: *mut c_char,
+    buflen: usize,
+) -> usize {
+    record_string(handle, index, buf, buflen, |r| {
+        r.id.clone().unwrap_or_else(|| ".".to_string())
+    })
+}
+
+/// REF of record `index`
+///
+/// # Safety
+/// As for `vcf_record_chrom`.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_ref(
+    handle: *const VcfHandle,
+    index: usize,
+    buf: *mut c_char,
+    buflen: usize,
+) -> usize {
+    record_string(handle, index, buf, buflen, |r| r.reference.clone())
+}
+
+/// ALT alleles of record `index`, joined with ","
+///
+/// # Safety
+/// As for `vcf_record_chrom`.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_alt(
+    handle: *const VcfHandle,
+    index: usize,
+    buf: *mut c_char,
+    buflen: usize,
+) -> usize {
+    record_string(handle, index, buf, buflen, |r| r.alternate.join(","))
+}
+
+/// FILTER of record `index`: "PASS", "." or the failed filters joined
+/// with ";"
+///
+/// # Safety
+/// As for `vcf_record_chrom`.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_filter(
+    handle: *const VcfHandle,
+    index: usize,
+    buf: *mut c_char,
+    buflen: usize,
+) -> usize {
+    record_string(handle, index, buf, buflen, |r| filter_string(&r.filter))
+}
+
+/// 1-based POS of record `index`, or 0 on error
+///
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_pos(handle: *const VcfHandle, index: usize) -> u64 {
+    ffi_call(|_| 0, || Ok(record(handle, index)?.pos))
+}
+
+/// QUAL of record `index`; NaN when missing or on error
+///
+/// # Safety
+/// `handle` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_record_qual(handle: *const VcfHandle, index: usize) -> f64 {
+    ffi_call(|_| f64::NAN, || Ok(record(handle, index)?.qual.unwrap_or(f64::NAN)))
+}
+
+/// Fill `*out_stats` with summary counts for `handle`
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, and `out_stats` NULL or valid
+/// for a write.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_stats(
+    handle: *const VcfHandle,
+    out_stats: *mut FfiStats,
+) -> VcfErrorCode {
+    ffi_call(
+        |code| code,
+        || {
+            let stats = &handle_ref(handle)?.stats;
+            let out_stats = out_stats.as_mut().ok_or_else(|| FfiError::null("out_stats"))?;
+            *out_stats = FfiStats {
+                total_records: stats.total_records as u64,
+                snps: stats.snps as u64,
+                insertions: stats.insertions as u64,
+                deletions: stats.deletions as u64,
+                complex: stats.complex as u64,
+                passed_filter: stats.passed_filter as u64,
+                failed_filter: stats.failed_filter as u64,
+                chromosomes: stats.chromosomes.len() as u64,
+                transitions: stats.transitions as u64,
+                transversions: stats.transversions as u64,
+            };
+            Ok(VcfErrorCode::Ok)
+        },
+    )
+}
+
+/// Release a handle from `vcf_parse_file`; NULL is ignored
+///
+/// # Safety
+/// `handle` must be NULL or a live handle, and is dangling afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_free(handle: *mut VcfHandle) {
+    if !handle.is_null() {
+        ffi_call(
+            |_| (),
+            || {
+                drop(Box::from_raw(handle));
+                Ok(())
+            },
+        );
+    }
+}
+
+/// Message for the last failed call on this thread, "" if it succeeded
+///
+/// Returns the buffer size needed, like the string accessors.
+///
+/// # Safety
+/// `buf` must be NULL or valid for `buflen` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn vcf_last_error_message(buf: *mut c_char, buflen: usize) -> usize {
+    LAST_ERROR.with(|last| write_string(&last.borrow(), buf, buflen))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::path::Path;
+
+    fn fixture(name: &str) -> CString {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/data")
+            .join(name);
+        CString::new(path.to_str().unwrap()).unwrap()
+    }
+
+    fn last_error() -> String {
+        let mut buf = vec![0u8; unsafe { vcf_last_error_message(ptr::null_mut(), 0) }];
+        unsafe { vcf_last_error_message(buf.as_mut_ptr().cast(), buf.len()) };
+        CStr::from_bytes_with_nul(&buf).unwrap().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_and_read_records() {
+        let mut handle = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                vcf_parse_file(fixture("shared-records.vcf").as_ptr(), &mut handle),
+                VcfErrorCode::Ok
+            );
+            assert_eq!(vcf_record_count(handle), 5);
+            assert_eq!(vcf_record_pos(handle, 2), 300);
+            assert!(vcf_record_qual(handle, 1).is_nan());
+
+            // Size negotiation: too small a buffer is left untouched
+            let mut buf = [b'x' as c_char; 4];
+            assert_eq!(vcf_record_alt(handle, 2, buf.as_mut_ptr(), 3), 4);
+            assert_eq!(buf[0], b'x' as c_char);
+            assert_eq!(vcf_record_alt(handle, 2, buf.as_mut_ptr(), 4), 4);
+            assert_eq!(CStr::from_ptr(buf.as_ptr()).to_str().unwrap(), "T,G");
+
+            assert_eq!(vcf_record_chrom(handle, 5, buf.as_mut_ptr(), 4), 0);
+            assert_eq!(last_error(), "record 5 of 5");
+
+            let mut stats = FfiStats::default();
+            assert_eq!(vcf_stats(handle, &mut stats), VcfErrorCode::Ok);
+            assert_eq!(stats.total_records, 5);
+            assert_eq!(stats.chromosomes, 3);
+
+            vcf_free(handle);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut handle = ptr::null_mut();
+        unsafe {
+            assert_eq!(
+                vcf_parse_file(fixture("missing.vcf").as_ptr(), &mut handle),
+                VcfErrorCode::Io
+            );
+            assert!(handle.is_null());
+            assert!(last_error().starts_with("IO error"));
+
+            assert_eq!(vcf_parse_file(ptr::null(), &mut handle), VcfErrorCode::NullPointer);
+            assert_eq!(vcf_record_count(ptr::null()), 0);
+            vcf_free(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,911 @@
+//! Filter Expressions
+//!
+//! A small bcftools-style language for selecting records, e.g.
+//! `QUAL>30 && INFO/DP>=10 && TYPE="snp" && FILTER="PASS"`.
+//!
+//! - `QUAL`, `POS` and INFO keys compare with numbers using `=`, `!=`,
+//!   `<`, `<=`, `>` and `>=`
+//! - `CHROM`, `ID`, `FILTER`, `TYPE` and string INFO keys compare with
+//!   quoted strings using `=` and `!=`
+//! - `INFO/KEY` on its own is true when the key is present, which is how
+//!   flags are tested; the `INFO/` prefix may be left off
+//! - `GT[i]` compares sample `i` (0-based) with `"het"`, `"hom"`,
+//!   `"hom_ref"`, `"hom_alt"` or `"missing"`
+//! - `&&` (or `&`), `||` (or `|`), `!` and parentheses combine tests;
+//!   `&&` binds tighter than `||`
+//!
+//! A comparison with a list-valued INFO key (Number=A, R, ...) holds if
+//! any element matches; wrap the key as `all(INFO/AF)>0.1` to require
+//! every element to, or `any(...)` to be explicit. A comparison with a
+//! missing value (no QUAL, absent INFO key, `.` element) is false.
+
+use crate::error::{VcfError, VcfResult};
+use crate::types::{
+    FilterStatus, Genotype, InfoDefinition, InfoValue, VariantType, VcfHeader, VcfRecord,
+};
+use crate::view::variant_type_from_label;
+use std::str::FromStr;
+
+/// Genotype classes accepted by `GT[i]`
+const GENOTYPE_CLASSES: [&str; 5] = ["het", "hom", "hom_ref", "hom_alt", "missing"];
+
+/// Parsed expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: Field,
+        op: CompareOp,
+        value: Literal,
+        /// How list values are matched; only INFO values can be lists
+        quantifier: Quantifier,
+    },
+    /// INFO key present
+    HasInfo(String),
+}
+
+/// Left-hand side of a comparison
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    Qual,
+    Pos,
+    Chrom,
+    Id,
+    Filter,
+    Type,
+    Info(String),
+    /// Genotype of the sample at this index
+    Genotype(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn holds<T: PartialOrd + ?Sized>(self, left: &T, right: &T) -> bool {
+        match self {
+            CompareOp::Eq => left == right,
+            CompareOp::Ne => left != right,
+            CompareOp::Lt => left < right,
+            CompareOp::Le => left <= right,
+            CompareOp::Gt => left > right,
+            CompareOp::Ge => left >= right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    String(String),
+}
+
+/// Whether any or every element of a list value must match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantifier {
+    #[default]
+    Any,
+    All,
+}
+
+impl Expr {
+    /// Evaluate against a record
+    pub fn matches(&self, record: &VcfRecord) -> bool {
+        match self {
+            Expr::And(left, right) => left.matches(record) && right.matches(record),
+            Expr::Or(left, right) => left.matches(record) || right.matches(record),
+            Expr::Not(inner) => !inner.matches(record),
+            Expr::HasInfo(key) => record.info.contains_key(key.as_str()),
+            Expr::Compare {
+                field,
+                op,
+                value,
+                quantifier,
+            } => compare(record, field, *op, value, *quantifier),
+        }
+    }
+
+    /// Whether evaluating needs the record's INFO column parsed
+    pub fn needs_info(&self) -> bool {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.needs_info() || right.needs_info()
+            }
+            Expr::Not(inner) => inner.needs_info(),
+            Expr::HasInfo(_) => true,
+            Expr::Compare { field, .. } => matches!(field, Field::Info(_)),
+        }
+    }
+
+    /// Whether evaluating needs the record's sample columns parsed
+    pub fn needs_samples(&self) -> bool {
+        match self {
+            Expr::And(left, right) | Expr::Or(left, right) => {
+                left.needs_samples() || right.needs_samples()
+            }
+            Expr::Not(inner) => inner.needs_samples(),
+            Expr::HasInfo(_) => false,
+            Expr::Compare { field, .. } => matches!(field, Field::Genotype(_)),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = VcfError;
+
+    fn from_str(s: &str) -> VcfResult<Self> {
+        parse(s)
+    }
+}
+
+/// Expression checked against a header, ready to evaluate
+#[derive(Debug, Clone)]
+pub struct CompiledFilter {
+    expr: Expr,
+}
+
+impl CompiledFilter {
+    /// Check whether a record passes
+    pub fn matches(&self, record: &VcfRecord) -> bool {
+        self.expr.matches(record)
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// Whether records must be parsed with INFO for this filter
+    pub fn needs_info(&self) -> bool {
+        self.expr.needs_info()
+    }
+
+    /// Whether records must be parsed with samples for this filter
+    pub fn needs_samples(&self) -> bool {
+        self.expr.needs_samples()
+    }
+}
+
+/// Parse an expression without checking it against a header
+pub fn parse(expr: &str) -> VcfResult<Expr> {
+    Parser::new(expr, None)?.parse()
+}
+
+/// Parse an expression and check it against `header`
+///
+/// INFO keys must be declared, comparisons must suit their declared Type,
+/// and `GT[i]` must name an existing sample.
+pub fn compile(expr: &str, header: &VcfHeader) -> VcfResult<CompiledFilter> {
+    let expr = Parser::new(expr, Some(header))?.parse()?;
+    Ok(CompiledFilter { expr })
+}
+
+fn compare(
+    record: &VcfRecord,
+    field: &Field,
+    op: CompareOp,
+    value: &Literal,
+    quantifier: Quantifier,
+) -> bool {
+    match (field, value) {
+        (Field::Qual, Literal::Number(n)) => record.qual.is_some_and(|q| op.holds(&q, n)),
+        (Field::Pos, Literal::Number(n)) => op.holds(&(record.pos as f64), n),
+        (Field::Info(key), value) => {
+            let Some(info) = record.info.get(key.as_str()) else {
+                return false;
+            };
+            let elements = info_elements(info);
+            if elements.is_empty() {
+                return false;
+            }
+            let element_matches = |element: &String| match value {
+                Literal::Number(n) => element.parse::<f64>().is_ok_and(|e| op.holds(&e, n)),
+                Literal::String(s) => element != "." && op.holds(element.as_str(), s.as_str()),
+            };
+            match quantifier {
+                Quantifier::Any => elements.iter().any(element_matches),
+                Quantifier::All => elements.iter().all(element_matches),
+            }
+        }
+        (field, Literal::String(s)) => {
+            let equal = match field {
+                Field::Chrom => record.chrom == *s,
+                Field::Id => record.id.as_deref().unwrap_or(".") == s,
+                Field::Filter => match &record.filter {
+                    FilterStatus::Pass => s == "PASS",
+                    FilterStatus::Missing => s == ".",
+                    FilterStatus::Failed(filters) => filters.contains(s),
+                },
+                Field::Type => type_matches(record.variant_type(), s),
+                Field::Genotype(index) => match record.samples.get(*index) {
+                    Some(sample) => genotype_matches(sample.genotype.as_ref(), s),
+                    None => return false,
+                },
+                _ => return false,
+            };
+            match op {
+                CompareOp::Eq => equal,
+                CompareOp::Ne => !equal,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// INFO value elements as written
+fn info_elements(value: &InfoValue) -> Vec<String> {
+    match value {
+        InfoValue::Flag => Vec::new(),
+        InfoValue::Integer(i) => vec![i.to_string()],
+        InfoValue::Float(f) => vec![f.to_string()],
+        InfoValue::String(s) => vec![s.clone()],
+        InfoValue::IntegerArray(v) => v.iter().map(i64::to_string).collect(),
+        InfoValue::FloatArray(v) => v.iter().map(f64::to_string).collect(),
+        InfoValue::StringArray(v) => v.clone(),
+    }
+}
+
+/// `TYPE` label check; "indel" covers insertions and deletions
+fn type_matches(variant_type: VariantType, label: &str) -> bool {
+    if label.eq_ignore_ascii_case("indel") {
+        return matches!(variant_type, VariantType::Insertion | VariantType::Deletion);
+    }
+    variant_type_from_label(label) == Some(variant_type)
+}
+
+fn genotype_matches(genotype: Option<&Genotype>, class: &str) -> bool {
+    let called = genotype.filter(|gt| gt.alleles.iter().any(Option::is_some));
+    match (class, called) {
+        ("missing", called) => called.is_none(),
+        (_, None) => false,
+        ("het", Some(gt)) => gt.is_het(),
+        ("hom", Some(gt)) => gt.is_hom_ref() || gt.is_hom_alt(),
+        ("hom_ref", Some(gt)) => gt.is_hom_ref(),
+        ("hom_alt", Some(gt)) => gt.is_hom_alt(),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Not,
+    And,
+    Or,
+    Op(CompareOp),
+    End,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Number(n) => format!("number {}", n),
+            Token::Str(s) => format!("string \"{}\"", s),
+            Token::LParen => "'('".to_string(),
+            Token::RParen => "')'".to_string(),
+            Token::LBracket => "'['".to_string(),
+            Token::RBracket => "']'".to_string(),
+            Token::Not => "'!'".to_string(),
+            Token::And => "'&&'".to_string(),
+            Token::Or => "'||'".to_string(),
+            Token::Op(_) => "comparison operator".to_string(),
+            Token::End => "end of expression".to_string(),
+        }
+    }
+}
+
+/// Recursive-descent parser over pre-lexed tokens, each with its byte
+/// offset in the source
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<(Token, usize)>,
+    next: usize,
+    header: Option<&'a VcfHeader>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str, header: Option<&'a VcfHeader>) -> VcfResult<Self> {
+        Ok(Self {
+            source,
+            tokens: lex(source)?,
+            next: 0,
+            header,
+        })
+    }
+
+    fn parse(mut self) -> VcfResult<Expr> {
+        if matches!(self.peek(), Token::End) {
+            return Err(self.error_here("empty expression"));
+        }
+        let expr = self.parse_or()?;
+        match self.peek() {
+            Token::End => Ok(expr),
+            token => {
+                Err(self.error_here(format!("expected '&&' or '||', found {}", token.describe())))
+            }
+        }
+    }
+
+    fn parse_or(&mut self) -> VcfResult<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> VcfResult<Expr> {
+        let mut left = self.parse_unary()?;
+        while self.eat(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> VcfResult<Expr> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> VcfResult<Expr> {
+        let (token, offset) = self.advance();
+        match token {
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                self.expect(&Token::RParen, "')'")?;
+                Ok(expr)
+            }
+            Token::Ident(name)
+                if (name == "any" || name == "all") && *self.peek() == Token::LParen =>
+            {
+                self.advance();
+                let quantifier = if name == "all" {
+                    Quantifier::All
+                } else {
+                    Quantifier::Any
+                };
+                let (key, definition) = match self.advance() {
+                    (Token::Ident(key), offset) => match self.parse_field(&key, offset)? {
+                        (Field::Info(key), definition) => (key, definition),
+                        _ => {
+                            return Err(self.error(offset, format!("{}() takes an INFO key", name)))
+                        }
+                    },
+                    (token, offset) => {
+                        return Err(self.error(
+                            offset,
+                            format!("expected an INFO key, found {}", token.describe()),
+                        ))
+                    }
+                };
+                self.expect(&Token::RParen, "')'")?;
+                if !matches!(self.peek(), Token::Op(_)) {
+                    return Err(
+                        self.error_here(format!("expected a comparison after {}(...)", name))
+                    );
+                }
+                self.parse_comparison(Field::Info(key), definition, quantifier)
+            }
+            Token::Ident(name) => {
+                let (field, definition) = self.parse_field(&name, offset)?;
+                if matches!(self.peek(), Token::Op(_)) {
+                    return self.parse_comparison(field, definition, Quantifier::Any);
+                }
+                match field {
+                    Field::Info(key) => Ok(Expr::HasInfo(key)),
+                    _ => Err(self.error_here(format!("expected a comparison after '{}'", name))),
+                }
+            }
+            Token::End => Err(self.error(offset, "unexpected end of expression")),
+            token => Err(self.error(
+                offset,
+                format!("expected a field, '(' or '!', found {}", token.describe()),
+            )),
+        }
+    }
+
+    /// Field named `name`, with the header's definition for INFO keys
+    fn parse_field(
+        &mut self,
+        name: &str,
+        offset: usize,
+    ) -> VcfResult<(Field, Option<&'a InfoDefinition>)> {
+        let field = match name {
+            "QUAL" => Field::Qual,
+            "POS" => Field::Pos,
+            "CHROM" => Field::Chrom,
+            "ID" => Field::Id,
+            "FILTER" => Field::Filter,
+            "TYPE" => Field::Type,
+            "GT" => {
+                self.expect(&Token::LBracket, "'[' after GT")?;
+                let (index, index_offset) = match self.advance() {
+                    (Token::Number(n), offset) if n >= 0.0 && n.fract() == 0.0 => {
+                        (n as usize, offset)
+                    }
+                    (token, offset) => {
+                        return Err(self.error(
+                            offset,
+                            format!("expected a sample index, found {}", token.describe()),
+                        ))
+                    }
+                };
+                self.expect(&Token::RBracket, "']'")?;
+                if let Some(header) = self.header {
+                    if index >= header.samples.len() {
+                        return Err(self.error(
+                            index_offset,
+                            format!(
+                                "sample index {} is out of range; the file has {} samples",
+                                index,
+                                header.samples.len()
+                            ),
+                        ));
+                    }
+                }
+                Field::Genotype(index)
+            }
+            _ => {
+                let key = name.strip_prefix("INFO/").unwrap_or(name);
+                if key.is_empty() {
+                    return Err(self.error(offset, "missing INFO key after 'INFO/'"));
+                }
+                let definition = match self.header {
+                    Some(header) => match header.info_fields.iter().find(|f| f.id == key) {
+                        Some(definition) => Some(definition),
+                        None => {
+                            return Err(self.error(
+                                offset,
+                                format!("INFO/{} is not defined in the header", key),
+                            ))
+                        }
+                    },
+                    None => None,
+                };
+                return Ok((Field::Info(key.to_string()), definition));
+            }
+        };
+        Ok((field, None))
+    }
+
+    fn parse_comparison(
+        &mut self,
+        field: Field,
+        definition: Option<&InfoDefinition>,
+        quantifier: Quantifier,
+    ) -> VcfResult<Expr> {
+        let (op, op_offset) = match self.advance() {
+            (Token::Op(op), offset) => (op, offset),
+            (token, offset) => {
+                return Err(self.error(
+                    offset,
+                    format!("expected a comparison operator, found {}", token.describe()),
+                ))
+            }
+        };
+        let (value, value_offset) = match self.advance() {
+            (Token::Number(n), offset) => (Literal::Number(n), offset),
+            (Token::Str(s), offset) => (Literal::String(s), offset),
+            (token, offset) => {
+                return Err(self.error(
+                    offset,
+                    format!(
+                        "expected a number or a quoted string, found {}",
+                        token.describe()
+                    ),
+                ))
+            }
+        };
+
+        let numeric = match &field {
+            Field::Qual | Field::Pos => Some(true),
+            Field::Info(_) => {
+                definition.map(|d| matches!(d.field_type.as_str(), "Integer" | "Float"))
+            }
+            _ => Some(false),
+        };
+        if let Some(flag) = definition.filter(|d| d.field_type == "Flag") {
+            return Err(self.error(
+                op_offset,
+                format!("INFO/{} is a Flag; test it without a comparison", flag.id),
+            ));
+        }
+        match (&value, numeric) {
+            (Literal::String(_), Some(true)) => {
+                return Err(self.error(
+                    value_offset,
+                    format!("{} is compared with numbers", field_name(&field)),
+                ))
+            }
+            (Literal::Number(_), Some(false)) => {
+                return Err(self.error(
+                    value_offset,
+                    format!("{} is compared with quoted strings", field_name(&field)),
+                ))
+            }
+            (Literal::String(_), _) if !matches!(op, CompareOp::Eq | CompareOp::Ne) => {
+                return Err(self.error(op_offset, "strings can only be compared with '=' or '!='"))
+            }
+            _ => {}
+        }
+
+        if let Literal::String(s) = &value {
+            let known = match field {
+                Field::Type => {
+                    s.eq_ignore_ascii_case("indel") || variant_type_from_label(s).is_some()
+                }
+                Field::Genotype(_) => GENOTYPE_CLASSES.contains(&s.as_str()),
+                _ => true,
+            };
+            if !known {
+                let expected = match field {
+                    Field::Type => "snp, ins, del, indel, complex or other".to_string(),
+                    _ => GENOTYPE_CLASSES.join(", "),
+                };
+                return Err(self.error(
+                    value_offset,
+                    format!(
+                        "unknown {} \"{}\"; expected {}",
+                        field_name(&field),
+                        s,
+                        expected
+                    ),
+                ));
+            }
+        }
+
+        Ok(Expr::Compare {
+            field,
+            op,
+            value,
+            quantifier,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        self.tokens
+            .get(self.next)
+            .map_or(&Token::End, |(token, _)| token)
+    }
+
+    fn advance(&mut self) -> (Token, usize) {
+        match self.tokens.get(self.next) {
+            Some(entry) => {
+                self.next += 1;
+                entry.clone()
+            }
+            None => (Token::End, self.source.len()),
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == token {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token, what: &str) -> VcfResult<()> {
+        if self.eat(token) {
+            return Ok(());
+        }
+        Err(self.error_here(format!(
+            "expected {}, found {}",
+            what,
+            self.peek().describe()
+        )))
+    }
+
+    fn error_here(&self, message: impl Into<String>) -> VcfError {
+        let offset = self
+            .tokens
+            .get(self.next)
+            .map_or(self.source.len(), |(_, offset)| *offset);
+        self.error(offset, message)
+    }
+
+    fn error(&self, offset: usize, message: impl Into<String>) -> VcfError {
+        syntax_error(self.source, offset, message)
+    }
+}
+
+fn field_name(field: &Field) -> String {
+    match field {
+        Field::Qual => "QUAL".to_string(),
+        Field::Pos => "POS".to_string(),
+        Field::Chrom => "CHROM".to_string(),
+        Field::Id => "ID".to_string(),
+        Field::Filter => "FILTER".to_string(),
+        Field::Type => "TYPE".to_string(),
+        Field::Info(key) => format!("INFO/{}", key),
+        Field::Genotype(index) => format!("GT[{}]", index),
+    }
+}
+
+/// Error at a byte offset, reported as a 1-based character column
+fn syntax_error(source: &str, offset: usize, message: impl Into<String>) -> VcfError {
+    VcfError::InvalidFilterExpression {
+        column: source.get(..offset).map_or(0, |s| s.chars().count()) + 1,
+        message: message.into(),
+    }
+}
+
+fn lex(source: &str) -> VcfResult<Vec<(Token, usize)>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        let mut followed_by = |next: char| chars.next_if(|(_, c)| *c == next).is_some();
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '&' => {
+                followed_by('&');
+                Token::And
+            }
+            '|' => {
+                followed_by('|');
+                Token::Or
+            }
+            '=' => {
+                followed_by('=');
+                Token::Op(CompareOp::Eq)
+            }
+            '!' if followed_by('=') => Token::Op(CompareOp::Ne),
+            '!' => Token::Not,
+            '<' if followed_by('=') => Token::Op(CompareOp::Le),
+            '<' => Token::Op(CompareOp::Lt),
+            '>' if followed_by('=') => Token::Op(CompareOp::Ge),
+            '>' => Token::Op(CompareOp::Gt),
+            '"' | '\'' => {
+                let start = offset + 1;
+                let end = loop {
+                    match chars.next() {
+                        Some((end, q)) if q == c => break end,
+                        Some(_) => {}
+                        None => return Err(syntax_error(source, offset, "unterminated string")),
+                    }
+                };
+                Token::Str(source.get(start..end).unwrap_or_default().to_string())
+            }
+            c if c.is_ascii_digit() || c == '.' || c == '-' => {
+                let mut end = offset + c.len_utf8();
+                let mut previous = c;
+                while let Some((i, next)) = chars.next_if(|&(_, next)| {
+                    next.is_ascii_alphanumeric()
+                        || next == '.'
+                        || (matches!(next, '+' | '-') && matches!(previous, 'e' | 'E'))
+                }) {
+                    end = i + next.len_utf8();
+                    previous = next;
+                }
+                let text = source.get(offset..end).unwrap_or_default();
+                match text.parse::<f64>() {
+                    Ok(n) if n.is_finite() => Token::Number(n),
+                    _ => {
+                        return Err(syntax_error(
+                            source,
+                            offset,
+                            format!("invalid number '{}'", text),
+                        ))
+                    }
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = offset + c.len_utf8();
+                while let Some((i, next)) = chars
+                    .next_if(|&(_, next)| next.is_alphanumeric() || matches!(next, '_' | '.' | '/'))
+                {
+                    end = i + next.len_utf8();
+                }
+                Token::Ident(source.get(offset..end).unwrap_or_default().to_string())
+            }
+            c => {
+                return Err(syntax_error(
+                    source,
+                    offset,
+                    format!("unexpected character '{}'", c),
+                ))
+            }
+        };
+        tokens.push((token, offset));
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const VCF: &str = include_str!("../../tests/data/shared-records.vcf");
+
+    /// (expression, POS of the record, expected)
+    ///
+    /// Records: 100 SNP PASS DP=50 AF=0.25 DB GENE=BRCA1, GTs 0/1 1|1;
+    /// 200 deletion, no QUAL, q10;lowDP, DP=12, GTs ./. 0/1;
+    /// 300 multi-allelic SNP, QUAL 50.5, FILTER ., AF=0.1,0.2, GTs 1/2 0|1;
+    /// 400 insertion, PASS, no INFO, GTs 0/1 .;
+    /// 500 chrX deletion, PASS, DP=9, haploid GTs 1 0.
+    const CASES: &[(&str, u64, bool)] = &[
+        ("QUAL>30", 100, false),
+        ("QUAL>=30", 100, true),
+        ("QUAL>30", 200, false),
+        ("!(QUAL>30)", 200, true),
+        ("QUAL!=30", 200, false),
+        ("QUAL=50.5", 300, true),
+        ("POS<=200", 200, true),
+        ("POS>1e2", 100, false),
+        ("INFO/DP>=10", 100, true),
+        ("INFO/DP>=10", 500, false),
+        ("DP>=10", 200, true),
+        ("INFO/DP>=10", 400, false),
+        ("INFO/DB", 100, true),
+        ("INFO/DB", 200, false),
+        ("!INFO/DB", 200, true),
+        ("INFO/GENE=\"BRCA1\"", 100, true),
+        ("INFO/GENE!=\"BRCA1\"", 100, false),
+        ("INFO/GENE!=\"BRCA1\"", 200, false),
+        ("INFO/AF>0.15", 300, true),
+        ("any(INFO/AF)>0.15", 300, true),
+        ("all(INFO/AF)>0.15", 300, false),
+        ("all(INFO/AF)>0.05", 300, true),
+        ("all(INFO/AF)<0.3", 100, true),
+        ("CHROM=\"chrX\"", 500, true),
+        ("CHROM!=\"chrX\"", 100, true),
+        ("ID=\"rs3\"", 300, true),
+        ("ID=\".\"", 200, true),
+        ("ID!=\".\"", 100, true),
+        ("FILTER=\"PASS\"", 100, true),
+        ("FILTER=\"PASS\"", 300, false),
+        ("FILTER=\".\"", 300, true),
+        ("FILTER=\"lowDP\"", 200, true),
+        ("FILTER!=\"q10\"", 200, false),
+        ("TYPE=\"snp\"", 300, true),
+        ("TYPE=\"SNP\"", 100, true),
+        ("TYPE=\"indel\"", 400, true),
+        ("TYPE=\"del\"", 400, false),
+        ("GT[0]=\"het\"", 100, true),
+        ("GT[1]=\"hom\"", 100, true),
+        ("GT[1]=\"hom_alt\"", 100, true),
+        ("GT[1]=\"hom_ref\"", 100, false),
+        ("GT[0]=\"missing\"", 200, true),
+        ("GT[0]=\"het\"", 200, false),
+        ("GT[0]=\"het\"", 300, true),
+        ("GT[1]=\"missing\"", 400, true),
+        ("GT[0]=\"hom_alt\"", 500, true),
+        ("GT[1]!=\"het\"", 500, true),
+        (
+            "QUAL>=30 && INFO/DP>=10 && TYPE=\"snp\" && FILTER=\"PASS\"",
+            100,
+            true,
+        ),
+        ("QUAL>30 && INFO/DP>=10 || INFO/DB", 100, true),
+        ("QUAL>30 && (INFO/DP>=10 || INFO/DB)", 100, false),
+        ("INFO/DB || QUAL>40 && TYPE=\"indel\"", 400, true),
+        ("INFO/DB | QUAL>40 & TYPE='indel'", 500, true),
+        ("!(FILTER=\"PASS\") && !INFO/DP", 300, true),
+    ];
+
+    #[test]
+    fn test_expression_table() {
+        let (header, records) = VcfParser::new().parse_str(VCF).unwrap();
+
+        for &(expr, pos, expected) in CASES {
+            let filter = compile(expr, &header).unwrap_or_else(|e| panic!("{}: {}", expr, e));
+            let record = records.iter().find(|r| r.pos == pos).unwrap();
+            assert_eq!(
+                filter.matches(record),
+                expected,
+                "{} on record at {}",
+                expr,
+                pos
+            );
+        }
+    }
+
+    /// (expression, 1-based column, message fragment)
+    const ERRORS: &[(&str, usize, &str)] = &[
+        ("", 1, "empty expression"),
+        ("QUAL>", 6, "expected a number"),
+        ("QUAL>30 &&", 11, "unexpected end"),
+        ("(QUAL>30", 9, "expected ')'"),
+        ("QUAL>30)", 8, "expected '&&' or '||'"),
+        ("QUAL>30 INFO/DB", 9, "expected '&&' or '||'"),
+        ("QUAL>\"30\"", 6, "QUAL is compared with numbers"),
+        ("CHROM=1", 7, "CHROM is compared with quoted strings"),
+        ("CHROM<\"chr2\"", 6, "only be compared with '=' or '!='"),
+        ("QUAL", 5, "expected a comparison after 'QUAL'"),
+        ("INFO/XX>1", 1, "INFO/XX is not defined"),
+        ("QUAL>1 && INFO/DB>0", 18, "is a Flag"),
+        (
+            "INFO/GENE>3",
+            11,
+            "INFO/GENE is compared with quoted strings",
+        ),
+        ("INFO/DP=\"x\"", 9, "INFO/DP is compared with numbers"),
+        ("TYPE=\"mnp\"", 6, "unknown TYPE \"mnp\""),
+        ("GT[2]=\"het\"", 4, "sample index 2 is out of range"),
+        ("GT[0]=\"hetero\"", 7, "unknown GT[0]"),
+        ("GT=\"het\"", 3, "expected '[' after GT"),
+        ("all(QUAL)>1", 5, "all() takes an INFO key"),
+        ("all(INFO/AF)", 13, "expected a comparison after all(...)"),
+        ("CHROM=\"chr1", 7, "unterminated string"),
+        ("QUAL>1.2.3", 6, "invalid number '1.2.3'"),
+        ("QUAL>30 # comment", 9, "unexpected character '#'"),
+        ("GENE=\"é\" && x>1", 13, "INFO/x is not defined"),
+    ];
+
+    #[test]
+    fn test_errors_report_columns() {
+        let header = VcfParser::new().parse_str(VCF).unwrap().0;
+
+        for &(expr, column, fragment) in ERRORS {
+            match compile(expr, &header) {
+                Err(VcfError::InvalidFilterExpression { column: c, message }) => {
+                    assert_eq!(c, column, "{}: {}", expr, message);
+                    assert!(message.contains(fragment), "{}: {}", expr, message);
+                }
+                other => panic!(
+                    "{}: expected an error, got {:?}",
+                    expr,
+                    other.map(|f| f.expr().clone())
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_without_header() {
+        let expr: Expr = "any(INFO/NOT_DECLARED)>1 && !(GT[7]=\"het\")"
+            .parse()
+            .unwrap();
+        assert!(expr.needs_info());
+        assert!(expr.needs_samples());
+        assert_eq!(
+            expr,
+            Expr::And(
+                Box::new(Expr::Compare {
+                    field: Field::Info("NOT_DECLARED".to_string()),
+                    op: CompareOp::Gt,
+                    value: Literal::Number(1.0),
+                    quantifier: Quantifier::Any,
+                }),
+                Box::new(Expr::Not(Box::new(Expr::Compare {
+                    field: Field::Genotype(7),
+                    op: CompareOp::Eq,
+                    value: Literal::String("het".to_string()),
+                    quantifier: Quantifier::Any,
+                }))),
+            )
+        );
+
+        let expr = parse("QUAL>30 || CHROM=\"chr1\"").unwrap();
+        assert!(!expr.needs_info());
+        assert!(!expr.needs_samples());
+    }
+}
@@ -0,0 +1,392 @@
+//! Gene Models
+//!
+//! Gene coordinates from a BED file or the `gene` features of a GFF3
+//! file, indexed so records can be assigned to the genes they overlap
+//! without any consequence annotation.
+
+use crate::error::{VcfError, VcfResult};
+use crate::gzip;
+use crate::types::{VariantType, VcfRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+
+/// Strand of a gene
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Strand {
+    Forward,
+    Reverse,
+    Unknown,
+}
+
+impl Strand {
+    fn parse(value: &str) -> Self {
+        match value {
+            "+" => Strand::Forward,
+            "-" => Strand::Reverse,
+            _ => Strand::Unknown,
+        }
+    }
+}
+
+/// A gene's span in 1-based, inclusive coordinates, as in VCF
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneRegion {
+    pub name: String,
+    pub chrom: String,
+    pub start: u64,
+    pub end: u64,
+    pub strand: Strand,
+}
+
+/// Genes of one chromosome, sorted by start, with the largest end seen so
+/// far at each position so a lookup can stop scanning early
+#[derive(Debug, Clone, Default)]
+struct ChromIndex {
+    genes: Vec<usize>,
+    max_end: Vec<u64>,
+}
+
+/// A set of genes with an interval index over them
+#[derive(Debug, Clone, Default)]
+pub struct GeneModel {
+    genes: Vec<GeneRegion>,
+    index: HashMap<String, ChromIndex>,
+}
+
+impl GeneModel {
+    /// Model over `genes`; gene indices elsewhere refer to this order
+    pub fn new(genes: Vec<GeneRegion>) -> Self {
+        let mut index: HashMap<String, ChromIndex> = HashMap::new();
+        for (i, gene) in genes.iter().enumerate() {
+            index.entry(gene.chrom.clone()).or_default().genes.push(i);
+        }
+        for chrom in index.values_mut() {
+            chrom.genes.sort_by_key(|&i| (genes[i].start, i));
+            let mut max_end = 0;
+            chrom.max_end = chrom
+                .genes
+                .iter()
+                .map(|&i| {
+                    max_end = max_end.max(genes[i].end);
+                    max_end
+                })
+                .collect();
+        }
+        Self { genes, index }
+    }
+
+    /// Genes from BED text: chrom, 0-based start, end and optionally name,
+    /// score and strand; `track`, `browser` and `#` lines are skipped and
+    /// unnamed genes are named after their region
+    pub fn from_bed(text: &str) -> VcfResult<Self> {
+        let mut genes = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty()
+                || line.starts_with('#')
+                || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 3 {
+                return Err(model_error(n, "expected at least 3 columns"));
+            }
+            let start = parse_coordinate(n, fields[1])?;
+            let end = parse_coordinate(n, fields[2])?;
+            if end <= start {
+                return Err(model_error(n, "end must be after start"));
+            }
+
+            let chrom = fields[0].to_string();
+            let name = match fields.get(3) {
+                Some(name) if !name.is_empty() && *name != "." => name.to_string(),
+                _ => format!("{}:{}-{}", chrom, start + 1, end),
+            };
+            genes.push(GeneRegion {
+                name,
+                chrom,
+                start: start + 1,
+                end,
+                strand: Strand::parse(fields.get(5).copied().unwrap_or(".")),
+            });
+        }
+        Ok(Self::new(genes))
+    }
+
+    /// Genes from the `gene` features of GFF3 text, named by their `Name`,
+    /// `gene_name` or `ID` attribute; other feature types are ignored
+    pub fn from_gff3(text: &str) -> VcfResult<Self> {
+        let mut genes = Vec::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.starts_with("##FASTA") {
+                break;
+            }
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 9 {
+                return Err(model_error(n, "expected 9 columns"));
+            }
+            if fields[2] != "gene" {
+                continue;
+            }
+            let start = parse_coordinate(n, fields[3])?;
+            let end = parse_coordinate(n, fields[4])?;
+            if start == 0 || end < start {
+                return Err(model_error(n, "invalid gene span"));
+            }
+
+            let attribute = |key: &str| {
+                fields[8].split(';').find_map(|pair| {
+                    let (k, v) = pair.trim().split_once('=')?;
+                    (k == key).then_some(v)
+                })
+            };
+            let name = attribute("Name")
+                .or_else(|| attribute("gene_name"))
+                .or_else(|| attribute("ID"))
+                .ok_or_else(|| model_error(n, "gene has no Name or ID"))?;
+            genes.push(GeneRegion {
+                name: name.to_string(),
+                chrom: fields[0].to_string(),
+                start,
+                end,
+                strand: Strand::parse(fields[6]),
+            });
+        }
+        Ok(Self::new(genes))
+    }
+
+    /// Read a gene model file, plain or gzipped: GFF3 if the name ends in
+    /// `.gff` or `.gff3` (before any `.gz`), BED otherwise
+    pub fn open(path: impl AsRef<Path>) -> VcfResult<Self> {
+        let path = path.as_ref();
+        let mut text = String::new();
+        gzip::open_vcf(path)?
+            .read_to_string(&mut text)
+            .map_err(gzip::gzip_error)?;
+
+        let name = path.to_string_lossy();
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        if name.ends_with(".gff") || name.ends_with(".gff3") {
+            Self::from_gff3(&text)
+        } else {
+            Self::from_bed(&text)
+        }
+    }
+
+    pub fn genes(&self) -> &[GeneRegion] {
+        &self.genes
+    }
+
+    /// Indices of the genes overlapping `start..=end` on `chrom`, ascending
+    pub fn overlapping(&self, chrom: &str, start: u64, end: u64) -> Vec<usize> {
+        let Some(index) = self.index.get(chrom) else {
+            return Vec::new();
+        };
+        let candidates = index.genes.partition_point(|&i| self.genes[i].start <= end);
+
+        let mut found: Vec<usize> = (0..candidates)
+            .rev()
+            .take_while(|&k| index.max_end[k] >= start)
+            .map(|k| index.genes[k])
+            .filter(|&i| self.genes[i].end >= start)
+            .collect();
+        found.sort_unstable();
+        found
+    }
+}
+
+fn parse_coordinate(line: usize, value: &str) -> VcfResult<u64> {
+    value
+        .parse()
+        .map_err(|_| model_error(line, format!("invalid coordinate '{}'", value)))
+}
+
+/// Error for the 0-based line `index`
+fn model_error(index: usize, message: impl Into<String>) -> VcfError {
+    VcfError::InvalidGeneModel {
+        line: index + 1,
+        message: message.into(),
+    }
+}
+
+/// Genes each record's REF span overlaps; `None` for intergenic records
+pub fn assign_genes(records: &[VcfRecord], model: &GeneModel) -> Vec<Option<Vec<usize>>> {
+    records
+        .iter()
+        .map(|record| {
+            let genes = record_genes(record, model);
+            (!genes.is_empty()).then_some(genes)
+        })
+        .collect()
+}
+
+fn record_genes(record: &VcfRecord, model: &GeneModel) -> Vec<usize> {
+    let end = record.pos + record.reference.len().max(1) as u64 - 1;
+    model.overlapping(&record.chrom, record.pos, end)
+}
+
+/// Records overlapping one gene, by variant type
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneVariantCounts {
+    pub gene: String,
+    pub snps: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub complex: usize,
+    pub other: usize,
+}
+
+impl GeneVariantCounts {
+    pub fn total(&self) -> usize {
+        self.snps + self.insertions + self.deletions + self.complex + self.other
+    }
+}
+
+/// Per-gene record counts, in the model's gene order; a record counts
+/// towards every gene it overlaps
+pub fn gene_variant_counts(records: &[VcfRecord], model: &GeneModel) -> Vec<GeneVariantCounts> {
+    let mut counts: Vec<GeneVariantCounts> = model
+        .genes
+        .iter()
+        .map(|gene| GeneVariantCounts {
+            gene: gene.name.clone(),
+            ..Default::default()
+        })
+        .collect();
+
+    for record in records {
+        let genes = record_genes(record, model);
+        if genes.is_empty() {
+            continue;
+        }
+        let variant_type = record.variant_type();
+        for gene in genes {
+            let count = &mut counts[gene];
+            match variant_type {
+                VariantType::Snp => count.snps += 1,
+                VariantType::Insertion => count.insertions += 1,
+                VariantType::Deletion => count.deletions += 1,
+                VariantType::Complex => count.complex += 1,
+                VariantType::Other => count.other += 1,
+            }
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    // GENE_B overlaps GENE_A's tail; GENE_C is alone on chr1
+    const GENES_BED: &str = "track name=genes
+chr1\t999\t2000\tGENE_A\t0\t+
+chr1\t1799\t3000\tGENE_B\t0\t-
+chr1\t4999\t6000\tGENE_C\t0\t+
+";
+
+    const GENES_GFF3: &str = "##gff-version 3
+chr1\t.\tgene\t1000\t2000\t.\t+\t.\tID=gene:A;Name=GENE_A
+chr1\t.\tmRNA\t1000\t2000\t.\t+\t.\tID=tx:A;Parent=gene:A
+chr1\t.\tgene\t1800\t3000\t.\t-\t.\tID=gene:B;Name=GENE_B
+chr1\t.\tgene\t5000\t6000\t.\t+\t.\tID=GENE_C
+";
+
+    const VARIANTS: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t999\tbefore\tA\tG\t50\tPASS\t.
+chr1\t1000\ta_start\tA\tG\t50\tPASS\t.
+chr1\t1500\ta_inside\tA\tAT\t50\tPASS\t.
+chr1\t1900\tshared\tC\tT\t50\tPASS\t.
+chr1\t2000\ta_end\tCA\tC\t50\tPASS\t.
+chr1\t3000\tb_end\tG\tA\t50\tPASS\t.
+chr1\t3001\tafter_b\tG\tA\t50\tPASS\t.
+chr1\t4000\tbetween\tT\tC\t50\tPASS\t.
+chr1\t4998\tspans_c\tTTT\tT\t50\tPASS\t.
+chr2\t1500\tother_chrom\tA\tG\t50\tPASS\t.
+";
+
+    #[test]
+    fn test_parse_gene_models() {
+        let bed = GeneModel::from_bed(GENES_BED).unwrap();
+        let gff = GeneModel::from_gff3(GENES_GFF3).unwrap();
+        assert_eq!(bed.genes(), gff.genes());
+
+        let a = &bed.genes()[0];
+        assert_eq!((a.start, a.end, a.strand), (1000, 2000, Strand::Forward));
+        assert_eq!(gff.genes()[2].name, "GENE_C");
+
+        let unnamed = GeneModel::from_bed("chr1\t0\t10\n").unwrap();
+        assert_eq!(unnamed.genes()[0].name, "chr1:1-10");
+
+        for (bad, line) in [
+            ("chr1\t10\n", 1),
+            ("chr1\t0\t10\nchr1\tx\t20\n", 2),
+            ("chr1\t10\t10\n", 1),
+        ] {
+            match GeneModel::from_bed(bad) {
+                Err(e @ VcfError::InvalidGeneModel { .. }) => assert_eq!(e.line(), Some(line)),
+                other => panic!("expected a gene model error for {:?}, got {:?}", bad, other),
+            }
+        }
+        assert!(GeneModel::from_gff3("chr1\t.\tgene\t1\t5\t.\t+\t.\tNote=x\n").is_err());
+    }
+
+    #[test]
+    fn test_assign_genes() {
+        let model = GeneModel::from_bed(GENES_BED).unwrap();
+        let (_, records) = VcfParser::new().parse_str(VARIANTS).unwrap();
+
+        let assigned: Vec<(&str, Option<Vec<usize>>)> = records
+            .iter()
+            .map(|r| r.id.as_deref().unwrap())
+            .zip(assign_genes(&records, &model))
+            .collect();
+        assert_eq!(
+            assigned,
+            [
+                ("before", None),
+                ("a_start", Some(vec![0])),
+                ("a_inside", Some(vec![0])),
+                ("shared", Some(vec![0, 1])),
+                ("a_end", Some(vec![0, 1])),
+                ("b_end", Some(vec![1])),
+                ("after_b", None),
+                ("between", None),
+                ("spans_c", Some(vec![2])),
+                ("other_chrom", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gene_variant_counts() {
+        let model = GeneModel::from_gff3(GENES_GFF3).unwrap();
+        let (_, records) = VcfParser::new().parse_str(VARIANTS).unwrap();
+
+        let counts = gene_variant_counts(&records, &model);
+        assert_eq!(
+            counts[0],
+            GeneVariantCounts {
+                gene: "GENE_A".to_string(),
+                snps: 2,
+                insertions: 1,
+                deletions: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!((counts[1].snps, counts[1].deletions), (2, 1));
+        assert_eq!(counts[1].total(), 3);
+        assert_eq!(counts[2].total(), 1);
+        assert_eq!(counts[2].deletions, 1);
+    }
+}
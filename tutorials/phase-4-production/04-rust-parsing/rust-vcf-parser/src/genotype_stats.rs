@@ -0,0 +1,253 @@
+//! Genotype Counts
+//!
+//! Allele counts per record and genotype tallies per sample. Callers
+//! write missing calls as `.`, `./.` or `.|.`, write half calls such as
+//! `0/.`, or leave GT out of FORMAT altogether; the counts keep missing
+//! and absent GT apart and read half calls under a [`HalfCallPolicy`].
+
+use crate::concordance::GenotypeClass;
+use crate::types::{GenotypeState, HalfCallPolicy, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+
+/// Called alleles of one record, like the AN and AC INFO fields
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlleleCounts {
+    /// Called alleles
+    pub an: usize,
+    /// Copies of each ALT allele
+    pub ac: Vec<usize>,
+    /// Samples with a call
+    pub called: usize,
+    /// Samples whose GT is missing
+    pub missing: usize,
+    /// Samples without GT
+    pub not_provided: usize,
+}
+
+impl AlleleCounts {
+    /// ALT allele frequencies, `ac / an`; `None` when nothing is called
+    pub fn frequencies(&self) -> Option<Vec<f64>> {
+        (self.an > 0).then(|| {
+            self.ac
+                .iter()
+                .map(|&ac| ac as f64 / self.an as f64)
+                .collect()
+        })
+    }
+}
+
+/// AN and AC of `record` over its samples, with half calls read under
+/// `policy`; allele indices beyond the ALT alleles count towards AN only
+pub fn allele_counts(record: &VcfRecord, policy: HalfCallPolicy) -> AlleleCounts {
+    let mut counts = AlleleCounts {
+        ac: vec![0; record.alternate.len()],
+        ..AlleleCounts::default()
+    };
+    for sample in &record.samples {
+        let genotype = match sample.genotype_state(policy) {
+            GenotypeState::Called(genotype) => genotype,
+            GenotypeState::Missing => {
+                counts.missing += 1;
+                continue;
+            }
+            GenotypeState::NotProvided => {
+                counts.not_provided += 1;
+                continue;
+            }
+        };
+        counts.called += 1;
+        for allele in genotype.alleles.into_iter().flatten() {
+            counts.an += 1;
+            let alt = usize::from(allele).checked_sub(1);
+            if let Some(ac) = alt.and_then(|alt| counts.ac.get_mut(alt)) {
+                *ac += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Genotype tallies of one sample over a set of records
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SampleGenotypeStats {
+    pub name: String,
+    pub hom_ref: usize,
+    pub het: usize,
+    /// Only ALT alleles, so `1/2` and a haploid `1` count here
+    pub hom_alt: usize,
+    /// Missing GT, including half calls read as missing
+    pub missing: usize,
+    /// Records whose FORMAT has no GT for the sample
+    pub not_provided: usize,
+    /// Half calls as written, whichever class the policy put them in
+    pub half_calls: usize,
+}
+
+impl SampleGenotypeStats {
+    pub fn called(&self) -> usize {
+        self.hom_ref + self.het + self.hom_alt
+    }
+
+    /// Called over called plus missing; records without GT are left out
+    pub fn call_rate(&self) -> Option<f64> {
+        let total = self.called() + self.missing;
+        (total > 0).then(|| self.called() as f64 / total as f64)
+    }
+}
+
+/// Genotype tallies for each of the header's samples, with half calls
+/// read under `policy`
+///
+/// Classes are those of [`GenotypeClass`]. A record with fewer sample
+/// columns than the header counts as not providing GT for the rest.
+pub fn sample_genotype_stats(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    policy: HalfCallPolicy,
+) -> Vec<SampleGenotypeStats> {
+    let mut stats: Vec<SampleGenotypeStats> = header
+        .samples
+        .iter()
+        .map(|name| SampleGenotypeStats {
+            name: name.clone(),
+            ..SampleGenotypeStats::default()
+        })
+        .collect();
+
+    for record in records {
+        for (i, tally) in stats.iter_mut().enumerate() {
+            let Some(sample) = record.samples.get(i) else {
+                tally.not_provided += 1;
+                continue;
+            };
+            if !sample.gt_present && sample.genotype.is_none() {
+                tally.not_provided += 1;
+                continue;
+            }
+            tally.half_calls +=
+                usize::from(sample.genotype.as_ref().is_some_and(|g| g.is_half_call()));
+            match GenotypeClass::of_sample(Some(sample), policy) {
+                GenotypeClass::HomRef => tally.hom_ref += 1,
+                GenotypeClass::Het => tally.het += 1,
+                GenotypeClass::HomAlt => tally.hom_alt += 1,
+                GenotypeClass::Missing => tally.missing += 1,
+            }
+        }
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+    use crate::types::Genotype;
+
+    // One sample per encoding; the second record has no GT in FORMAT
+    const ENCODINGS: &str = "##fileformat=VCFv4.2
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read depth\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tdot\tdots\tphased_dots\tref_half\talt_half\tcalled
+chr1\t100\t.\tA\tG\t.\tPASS\t.\tGT:DP\t.:5\t./.:5\t.|.:5\t0/.:5\t./1:5\t0/1:5
+chr1\t200\t.\tC\tT\t.\tPASS\t.\tDP\t5\t5\t5\t5\t5\t5
+";
+
+    const POLICIES: [HalfCallPolicy; 3] = [
+        HalfCallPolicy::Missing,
+        HalfCallPolicy::Haploid,
+        HalfCallPolicy::Reference,
+    ];
+
+    #[test]
+    fn test_each_encoding_under_each_policy() {
+        use GenotypeClass::*;
+        let (_, records) = VcfParser::new().parse_str(ENCODINGS).unwrap();
+        let called = |gt: &str| GenotypeState::Called(Genotype::parse(gt).unwrap());
+
+        let expected_states = [
+            [
+                GenotypeState::Missing,
+                GenotypeState::Missing,
+                called("0/1"),
+            ],
+            [called("0"), called("1"), called("0/1")],
+            [called("0/0"), called("0/1"), called("0/1")],
+        ];
+        let expected_classes = [
+            [Missing, Missing, Het],
+            [HomRef, HomAlt, Het],
+            [HomRef, Het, Het],
+        ];
+
+        for (p, policy) in POLICIES.into_iter().enumerate() {
+            let states: Vec<GenotypeState> = records[0]
+                .samples
+                .iter()
+                .map(|s| s.genotype_state(policy))
+                .collect();
+            // `.`, `./.` and `.|.` are missing whatever the policy
+            assert!(
+                states[..3].iter().all(|s| *s == GenotypeState::Missing),
+                "{:?}",
+                policy
+            );
+            assert_eq!(states[3..], expected_states[p], "{:?}", policy);
+
+            let classes: Vec<GenotypeClass> = records[0]
+                .samples
+                .iter()
+                .map(|s| GenotypeClass::of_sample(Some(s), policy))
+                .collect();
+            assert_eq!(classes[..3], [Missing; 3]);
+            assert_eq!(classes[3..], expected_classes[p], "{:?}", policy);
+
+            for sample in &records[1].samples {
+                assert_eq!(sample.genotype_state(policy), GenotypeState::NotProvided);
+            }
+        }
+    }
+
+    #[test]
+    fn test_allele_counts_follow_policy() {
+        let (_, records) = VcfParser::new().parse_str(ENCODINGS).unwrap();
+        let counts = |policy| {
+            let c = allele_counts(&records[0], policy);
+            (c.an, c.ac, c.called, c.missing, c.not_provided)
+        };
+        assert_eq!(counts(HalfCallPolicy::Missing), (2, vec![1], 1, 5, 0));
+        assert_eq!(counts(HalfCallPolicy::Haploid), (4, vec![2], 3, 3, 0));
+        assert_eq!(counts(HalfCallPolicy::Reference), (6, vec![2], 3, 3, 0));
+
+        let absent = allele_counts(&records[1], HalfCallPolicy::Reference);
+        assert_eq!((absent.an, absent.not_provided), (0, 6));
+        assert_eq!(absent.frequencies(), None);
+        assert_eq!(
+            allele_counts(&records[0], HalfCallPolicy::Haploid).frequencies(),
+            Some(vec![0.5])
+        );
+    }
+
+    #[test]
+    fn test_sample_stats_follow_policy() {
+        let (header, records) = VcfParser::new().parse_str(ENCODINGS).unwrap();
+        for policy in POLICIES {
+            let stats = sample_genotype_stats(&records, &header, policy);
+            assert_eq!(stats.len(), 6);
+            for tally in &stats {
+                assert_eq!(tally.not_provided, 1, "{}", tally.name);
+                assert_eq!(tally.called() + tally.missing, 1, "{}", tally.name);
+            }
+            assert_eq!(stats[0].missing, 1);
+            assert_eq!(stats[0].call_rate(), Some(0.0));
+            assert_eq!((stats[3].half_calls, stats[4].half_calls), (1, 1));
+            assert_eq!(stats[5].het, 1);
+        }
+
+        let missing = sample_genotype_stats(&records, &header, HalfCallPolicy::Missing);
+        assert_eq!((missing[3].missing, missing[4].missing), (1, 1));
+        let haploid = sample_genotype_stats(&records, &header, HalfCallPolicy::Haploid);
+        assert_eq!((haploid[3].hom_ref, haploid[4].hom_alt), (1, 1));
+        let reference = sample_genotype_stats(&records, &header, HalfCallPolicy::Reference);
+        assert_eq!((reference[3].hom_ref, reference[4].het), (1, 1));
+    }
+}
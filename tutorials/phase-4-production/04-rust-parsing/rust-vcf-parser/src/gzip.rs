@@ -0,0 +1,138 @@
+//! Gzip / BGZF Input
+//!
+//! Transparent decompression for `.vcf.gz` files. BGZF, as written by
+//! bgzip and bcftools, is a series of concatenated gzip members, so every
+//! member is decoded rather than stopping after the first.
+
+use crate::error::{VcfError, VcfResult};
+use flate2::bufread::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// First two bytes of every gzip member
+pub const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Check whether `data` starts with the gzip magic bytes
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&GZIP_MAGIC)
+}
+
+/// Decompress gzip or BGZF bytes held in memory
+pub fn decompress(data: &[u8]) -> VcfResult<Vec<u8>> {
+    if !is_gzip(data) {
+        return Err(VcfError::InvalidFormat("input is not gzip compressed".to_string()));
+    }
+
+    let mut out = Vec::with_capacity(data.len() * 4);
+    MultiGzDecoder::new(data)
+        .read_to_end(&mut out)
+        .map_err(gzip_error)?;
+    Ok(out)
+}
+
+/// Open a VCF file for buffered reading, decompressing it if it is gzipped
+///
+/// Compression is detected from the magic bytes, not the extension. Read
+/// errors from the returned reader should go through [`gzip_error`] so a
+/// truncated file is reported as such.
+pub fn open_vcf(path: impl AsRef<Path>) -> VcfResult<Box<dyn BufRead + Send>> {
+    auto_decompress(BufReader::new(File::open(path)?))
+}
+
+/// Wrap `reader` in a gzip decoder if its data starts with the gzip magic
+pub fn auto_decompress<'a, R: BufRead + Send + 'a>(
+    mut reader: R,
+) -> VcfResult<Box<dyn BufRead + Send + 'a>> {
+    if is_gzip(reader.fill_buf()?) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Convert an error from reading gzip data into a [`VcfError`]
+///
+/// The decoder reports input that stops mid-member as an unexpected EOF;
+/// that becomes [`VcfError::TruncatedGzip`] and other errors pass through.
+pub fn gzip_error(e: io::Error) -> VcfError {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        VcfError::TruncatedGzip
+    } else {
+        VcfError::Io(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const VCF: &str = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\t.\tA\tG\t30\tPASS\t.
+chr1\t200\t.\tC\tT\t40\tPASS\t.
+";
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_decompress_concatenated_members() {
+        // BGZF-style: each block is its own gzip member
+        let (head, tail) = VCF.split_at(40);
+        let mut data = gzip(head.as_bytes());
+        data.extend(gzip(tail.as_bytes()));
+
+        assert!(is_gzip(&data));
+        assert_eq!(decompress(&data).unwrap(), VCF.as_bytes());
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let data = gzip(VCF.as_bytes());
+
+        assert!(matches!(
+            decompress(&data[..data.len() - 10]),
+            Err(VcfError::TruncatedGzip)
+        ));
+        assert!(matches!(
+            decompress(VCF.as_bytes()),
+            Err(VcfError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_open_vcf_detects_compression() {
+        let dir = std::env::temp_dir().join(format!("vcf-gzip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("plain.vcf");
+        let compressed = dir.join("compressed.vcf.gz");
+        std::fs::write(&plain, VCF).unwrap();
+        std::fs::write(&compressed, gzip(VCF.as_bytes())).unwrap();
+
+        for path in [&plain, &compressed] {
+            let mut text = String::new();
+            open_vcf(path).unwrap().read_to_string(&mut text).unwrap();
+            assert_eq!(text, VCF);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_auto_decompress_in_memory() {
+        let data = gzip(VCF.as_bytes());
+
+        for input in [VCF.as_bytes(), &data[..]] {
+            let mut text = String::new();
+            auto_decompress(input).unwrap().read_to_string(&mut text).unwrap();
+            assert_eq!(text, VCF);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Genomic HGVS Names
+//!
+//! Readable `g.` descriptions of each ALT allele, such as
+//! `chr17:g.41276045_41276046del`, for clinical tables. They are written
+//! against the chromosome name rather than an `NC_` accession, and
+//! without the reference sequence an indel is only shifted 3' as far as
+//! its own REF allows, so a deletion in a longer repeat may not be named
+//! at the position HGVS would choose.
+
+use crate::types::VcfRecord;
+
+impl VcfRecord {
+    /// `chrom:g.` description of each ALT allele, in ALT order
+    ///
+    /// Symbolic, breakend, `*` and missing alleles get `chrom:g.?`.
+    pub fn hgvs_g(&self) -> Vec<String> {
+        self.alternate
+            .iter()
+            .map(|alt| {
+                format!(
+                    "{}:g.{}",
+                    self.chrom,
+                    hgvs_g_change(self.pos, &self.reference, alt)
+                )
+            })
+            .collect()
+    }
+}
+
+/// The part of a `g.` description after `g.` for one allele at 1-based
+/// `pos`, e.g. `100A>G`, `201_202del` or `150_151insTT`
+///
+/// An insertion that repeats the bases just before it is a `dup`, as far
+/// as REF shows those bases. One before the first base, at POS 0 or 1,
+/// is `0_1ins`.
+pub fn hgvs_g_change(pos: u64, reference: &str, alt: &str) -> String {
+    let is_sequence = |allele: &str| {
+        !allele.is_empty()
+            && allele
+                .bytes()
+                .all(|b| matches!(b.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T' | b'N'))
+    };
+    if !is_sequence(reference) || !is_sequence(alt) {
+        return "?".to_string();
+    }
+    let reference = reference.to_ascii_uppercase();
+    let alt = alt.to_ascii_uppercase();
+    let (reference, alt) = (reference.as_bytes(), alt.as_bytes());
+    let full_reference = reference;
+
+    // Shared leading bases (the VCF anchor among them) first, so what is
+    // left starts as far 3' as the REF allows, then shared trailing bases
+    let prefix = reference
+        .iter()
+        .zip(alt)
+        .take_while(|(r, a)| r == a)
+        .count();
+    let (reference, alt) = (&reference[prefix..], &alt[prefix..]);
+    let suffix = reference
+        .iter()
+        .rev()
+        .zip(alt.iter().rev())
+        .take_while(|(r, a)| r == a)
+        .count();
+    let reference = &reference[..reference.len() - suffix];
+    let alt = &alt[..alt.len() - suffix];
+
+    // First and last REF positions left after trimming
+    let start = pos + prefix as u64;
+    let end = start + reference.len() as u64;
+    let range = || {
+        if reference.len() == 1 {
+            start.to_string()
+        } else {
+            format!("{}_{}", start, end - 1)
+        }
+    };
+    let bases = |seq: &[u8]| String::from_utf8_lossy(seq).into_owned();
+
+    match (reference.len(), alt.len()) {
+        (0, 0) => "=".to_string(),
+        (1, 1) => format!("{}{}>{}", start, bases(reference), bases(alt)),
+        (_, 0) => format!("{}del", range()),
+        // Repeats the shared bases just before it
+        (0, n) if prefix >= n && &full_reference[prefix - n..prefix] == alt => {
+            let first = start - n as u64;
+            if n == 1 {
+                format!("{}dup", first)
+            } else {
+                format!("{}_{}dup", first, start - 1)
+            }
+        }
+        // Before the first base of the sequence
+        (0, _) if start <= 1 => format!("0_1ins{}", bases(alt)),
+        // Between the last shared base and the next
+        (0, _) => format!("{}_{}ins{}", start - 1, start, bases(alt)),
+        _ => format!("{}delins{}", range(), bases(alt)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    fn hgvs(pos: u64, reference: &str, alt: &str) -> String {
+        hgvs_g_change(pos, reference, alt)
+    }
+
+    #[test]
+    fn test_each_variant_class() {
+        // SNV
+        assert_eq!(hgvs(100, "A", "G"), "100A>G");
+        // Deletions, with the anchor base left out of the range
+        assert_eq!(hgvs(199, "ATG", "A"), "200_201del");
+        assert_eq!(hgvs(41276044, "CTT", "C"), "41276045_41276046del");
+        assert_eq!(hgvs(500, "GA", "G"), "501del");
+        // Insertion between the anchor and the next base
+        assert_eq!(hgvs(150, "C", "CTT"), "150_151insTT");
+        // Insertions repeating the bases before them are duplications
+        assert_eq!(hgvs(100, "C", "CC"), "100dup");
+        assert_eq!(hgvs(100, "CAG", "CAGAG"), "101_102dup");
+        assert_eq!(hgvs(100, "CAG", "CAGTG"), "102_103insTG");
+        // Telomeric insertions before the first base, POS 0 included
+        assert_eq!(hgvs(0, "A", "TA"), "0_1insT");
+        assert_eq!(hgvs(1, "A", "TA"), "0_1insT");
+        // Delins, single and multi-base
+        assert_eq!(hgvs(300, "A", "TC"), "300delinsTC");
+        assert_eq!(hgvs(400, "CAGT", "CTTAT"), "401_402delinsTTA");
+        assert_eq!(hgvs(600, "AC", "GT"), "600_601delinsGT");
+        // Shared trailing bases are trimmed as well as the anchor
+        assert_eq!(hgvs(700, "TCAG", "TG"), "701_702del");
+        assert_eq!(hgvs(800, "ACGT", "ACCT"), "802G>C");
+        // Lower case is read as upper case
+        assert_eq!(hgvs(100, "a", "g"), "100A>G");
+        assert_eq!(hgvs(100, "A", "A"), "=");
+        // Within a repeat the REF's last copy is the one deleted
+        assert_eq!(hgvs(1, "ATTG", "ATG"), "3del");
+    }
+
+    #[test]
+    fn test_record_hgvs_g() {
+        let vcf = "##fileformat=VCFv4.2\n\
+                   #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+                   chr17\t41276044\t.\tCTT\tC,CT,CTTA\t.\tPASS\t.\n\
+                   chr1\t1000\t.\tA\t<DEL>,*\t.\tPASS\tSVTYPE=DEL;END=2000\n\
+                   chr2\t321681\t.\tG\tG]chr17:198982]\t.\tPASS\tSVTYPE=BND\n";
+        let (_, records) = VcfParser::new().parse_str(vcf).unwrap();
+        assert_eq!(
+            records[0].hgvs_g(),
+            [
+                "chr17:g.41276045_41276046del",
+                "chr17:g.41276046del",
+                "chr17:g.41276046_41276047insA",
+            ]
+        );
+        assert_eq!(records[1].hgvs_g(), ["chr1:g.?", "chr1:g.?"]);
+        assert_eq!(records[2].hgvs_g(), ["chr2:g.?"]);
+    }
+}
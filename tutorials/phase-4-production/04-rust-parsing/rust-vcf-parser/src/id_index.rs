@@ -0,0 +1,100 @@
+//! ID Lookup
+//!
+//! Maps each ID in the ID column (dbSNP rsIDs, COSMIC IDs, ...) to the
+//! records carrying it, for answering pasted lists of IDs without a scan
+//! per ID.
+
+use crate::types::VcfRecord;
+use std::collections::HashMap;
+
+/// Record indices by ID
+///
+/// A record whose ID column lists several IDs (`rs1;rs2`) is found under
+/// each of them.
+#[derive(Debug, Clone, Default)]
+pub struct IdIndex {
+    records: HashMap<String, Vec<usize>>,
+}
+
+impl IdIndex {
+    /// Index the IDs of `records`
+    pub fn build(records: &[VcfRecord]) -> Self {
+        let mut index: HashMap<String, Vec<usize>> = HashMap::new();
+        for (i, record) in records.iter().enumerate() {
+            for id in record.ids() {
+                let indices = index.entry(id.to_string()).or_default();
+                // The same ID twice in one record lists it once
+                if indices.last() != Some(&i) {
+                    indices.push(i);
+                }
+            }
+        }
+        Self { records: index }
+    }
+
+    /// Indices of the records with `id`, ascending; empty if none
+    pub fn lookup(&self, id: &str) -> &[usize] {
+        self.records.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Indices of the records with each of `ids`
+    ///
+    /// IDs are trimmed of surrounding whitespace, as in a pasted list.
+    /// Every non-blank ID gets an entry, empty when nothing matches.
+    pub fn lookup_many<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> HashMap<&'a str, Vec<usize>> {
+        ids.into_iter()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .map(|id| (id, self.lookup(id).to_vec()))
+            .collect()
+    }
+
+    /// Number of distinct IDs
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(pos: u64, id: Option<&str>) -> VcfRecord {
+        let mut record = VcfRecord::new("chr1", pos, "A", vec!["G"]);
+        record.id = id.map(String::from);
+        record
+    }
+
+    #[test]
+    fn test_multiple_ids_per_record() {
+        let records = [
+            record(100, Some("rs1;rs2")),
+            record(200, None),
+            record(300, Some("rs2")),
+            record(400, Some("COSV1;rs3;rs3")),
+        ];
+        assert_eq!(records[0].ids().collect::<Vec<_>>(), ["rs1", "rs2"]);
+        assert_eq!(records[1].ids().count(), 0);
+
+        let index = IdIndex::build(&records);
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.lookup("rs1"), [0]);
+        assert_eq!(index.lookup("rs2"), [0, 2]);
+        assert_eq!(index.lookup("rs3"), [3]);
+        assert_eq!(index.lookup("rs1;rs2"), [] as [usize; 0]);
+        assert_eq!(index.lookup("."), [] as [usize; 0]);
+
+        let found = index.lookup_many(["rs1", " rs2\r", "rs9", ""]);
+        assert_eq!(found.len(), 3);
+        assert_eq!(found["rs1"], [0]);
+        assert_eq!(found["rs2"], [0, 2]);
+        assert!(found["rs9"].is_empty());
+    }
+}
@@ -0,0 +1,765 @@
+//! Sorted Joins
+//!
+//! Merge-walks two coordinate-sorted VCF streams, pairing the records
+//! that describe the same variant, so whole-genome files can be compared
+//! or annotated without holding either one in memory.
+
+use crate::compare::{
+    dosage, sample_pairs, strip_chr, trim_alleles, CompareOptions, CompareResult, VariantDiff,
+    VariantKey,
+};
+use crate::diff::diff_records;
+use crate::error::{VcfError, VcfResult};
+use crate::parser::VcfIterator;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::Read;
+
+/// Order of the chromosomes in sorted input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChromOrder {
+    /// Byte order of the names, as `sort -k1,1` gives
+    Lexicographic,
+    /// Numbered chromosomes in numeric order, then X, Y and M or MT, then
+    /// any other name in byte order; a `chr` prefix is ignored
+    Natural,
+    /// The listed names in order, then unlisted ones in byte order
+    Listed(Vec<String>),
+}
+
+/// Sort key of a chromosome: a class, a number within it, then the name
+type ChromRank = (usize, u64, String);
+
+impl ChromOrder {
+    /// The order of the header's `##contig` lines, or natural order if it
+    /// has none
+    pub fn from_header(header: &VcfHeader) -> Self {
+        if header.contigs.is_empty() {
+            ChromOrder::Natural
+        } else {
+            ChromOrder::Listed(header.contigs.iter().map(|c| c.id.clone()).collect())
+        }
+    }
+
+    /// Whether chromosome `a` sorts before, with or after `b`
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.rank(a).cmp(&self.rank(b))
+    }
+
+    fn rank(&self, chrom: &str) -> ChromRank {
+        match self {
+            ChromOrder::Lexicographic => (0, 0, chrom.to_string()),
+            ChromOrder::Natural => {
+                let bare = strip_chr(chrom);
+                let (class, number) = match bare.parse::<u64>() {
+                    Ok(number) => (0, number),
+                    Err(_) => match bare.to_ascii_uppercase().as_str() {
+                        "X" => (1, 0),
+                        "Y" => (2, 0),
+                        "M" | "MT" => (3, 0),
+                        _ => (4, 0),
+                    },
+                };
+                (class, number, chrom.to_string())
+            }
+            ChromOrder::Listed(names) => {
+                let class = names.iter().position(|n| n == chrom).unwrap_or(names.len());
+                (class, 0, chrom.to_string())
+            }
+        }
+    }
+}
+
+/// A step of a [`SortedJoinIterator`]
+// Items are taken apart as soon as they are yielded, so boxing the pair
+// would only add an allocation
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum JoinItem {
+    /// Records of A and B for the same variant
+    Both(VcfRecord, VcfRecord),
+    OnlyA(VcfRecord),
+    OnlyB(VcfRecord),
+}
+
+/// One input of a join, one record ahead
+struct Side<R: Read> {
+    label: &'static str,
+    records: VcfIterator<R>,
+    head: Option<VcfRecord>,
+    /// Chromosome rank, position and line of the last record read
+    last: Option<(ChromRank, u64, usize)>,
+}
+
+impl<R: Read> Side<R> {
+    fn new(label: &'static str, records: VcfIterator<R>) -> Self {
+        Self {
+            label,
+            records,
+            head: None,
+            last: None,
+        }
+    }
+
+    /// Chromosome rank and position of the next record
+    fn key(&self) -> Option<(&ChromRank, u64)> {
+        self.head.as_ref()?;
+        self.last.as_ref().map(|(rank, pos, _)| (rank, *pos))
+    }
+
+    /// Read the next record, checking that it does not sort before the
+    /// one read last
+    fn advance(&mut self, order: &ChromOrder, strip_prefix: bool) -> VcfResult<()> {
+        self.head = None;
+        let Some(record) = self.records.next().transpose()? else {
+            return Ok(());
+        };
+        let line = self.records.line_number();
+        let chrom = if strip_prefix {
+            strip_chr(&record.chrom)
+        } else {
+            &record.chrom
+        };
+
+        // Ranked afresh only when the chromosome changes
+        let new_rank = match &self.last {
+            Some((last_rank, _, _)) if last_rank.2 == chrom => None,
+            _ => Some(order.rank(chrom)),
+        };
+        if let Some((last_rank, last_pos, last_line)) = &self.last {
+            let rank = new_rank.as_ref().unwrap_or(last_rank);
+            if (rank, record.pos) < (last_rank, *last_pos) {
+                return Err(VcfError::InvalidRecord {
+                    line,
+                    message: format!(
+                        "file {} is not sorted: {}:{} comes after {}:{} on line {}",
+                        self.label, record.chrom, record.pos, last_rank.2, last_pos, last_line
+                    ),
+                });
+            }
+        }
+        let rank = match new_rank {
+            Some(rank) => rank,
+            None => self
+                .last
+                .take()
+                .map(|(rank, _, _)| rank)
+                .unwrap_or_default(),
+        };
+        self.last = Some((rank, record.pos, line));
+        self.head = Some(record);
+        Ok(())
+    }
+
+    /// Every record at `key`, which must not sort after the next record
+    fn take_group(
+        &mut self,
+        key: &(ChromRank, u64),
+        order: &ChromOrder,
+        strip_prefix: bool,
+    ) -> VcfResult<Vec<VcfRecord>> {
+        let mut group = Vec::new();
+        while self
+            .key()
+            .is_some_and(|(rank, pos)| (rank, pos) == (&key.0, key.1))
+        {
+            group.extend(self.head.take());
+            self.advance(order, strip_prefix)?;
+        }
+        Ok(group)
+    }
+}
+
+/// Merge-walk of two VCF streams sorted by chromosome and position
+///
+/// Records at the same chromosome and position are paired when they have
+/// the same ALT alleles once shared bases are trimmed (in any order, and
+/// ignoring case), or with [`position_only`](Self::position_only) in the
+/// order they appear. Items for one position come out as A's records in
+/// file order, each paired or not, then B's unpaired ones. A record that
+/// sorts before the one read before it in the same file ends the join with
+/// [`VcfError::InvalidRecord`] naming both lines.
+pub struct SortedJoinIterator<R1: Read, R2: Read> {
+    a: Side<R1>,
+    b: Side<R2>,
+    order: ChromOrder,
+    position_only: bool,
+    strip_prefix: bool,
+    started: bool,
+    failed: bool,
+    pending: VecDeque<JoinItem>,
+}
+
+impl<R1: Read, R2: Read> SortedJoinIterator<R1, R2> {
+    pub fn new(a: VcfIterator<R1>, b: VcfIterator<R2>, chrom_order: ChromOrder) -> Self {
+        Self {
+            a: Side::new("A", a),
+            b: Side::new("B", b),
+            order: chrom_order,
+            position_only: false,
+            strip_prefix: false,
+            started: false,
+            failed: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Pair records on chromosome and position alone, as for annotation
+    /// from a source whose alleles may be written differently
+    pub fn position_only(mut self) -> Self {
+        self.position_only = true;
+        self
+    }
+
+    /// Treat `chr1` and `1` as the same chromosome, in both files and in
+    /// a [`ChromOrder::Listed`] order
+    pub fn ignore_chr_prefix(mut self) -> Self {
+        self.strip_prefix = true;
+        if let ChromOrder::Listed(names) = &mut self.order {
+            for name in names.iter_mut() {
+                *name = strip_chr(name).to_string();
+            }
+        }
+        self
+    }
+
+    pub fn header_a(&self) -> &VcfHeader {
+        self.a.records.header()
+    }
+
+    pub fn header_b(&self) -> &VcfHeader {
+        self.b.records.header()
+    }
+
+    /// Queue the items for the next position; `false` once both inputs
+    /// are exhausted
+    fn fill(&mut self) -> VcfResult<bool> {
+        if !self.started {
+            self.started = true;
+            self.a.advance(&self.order, self.strip_prefix)?;
+            self.b.advance(&self.order, self.strip_prefix)?;
+        }
+
+        let key = match (self.a.key(), self.b.key()) {
+            (None, None) => return Ok(false),
+            (Some(a), None) => a,
+            (None, Some(b)) => b,
+            (Some(a), Some(b)) => a.min(b),
+        };
+        let key = (key.0.clone(), key.1);
+        let group_a = self.a.take_group(&key, &self.order, self.strip_prefix)?;
+        let group_b = self.b.take_group(&key, &self.order, self.strip_prefix)?;
+
+        let mut group_b: Vec<(Option<VcfRecord>, Vec<AlleleKey>)> = group_b
+            .into_iter()
+            .map(|record| {
+                let key = self.allele_key(&record);
+                (Some(record), key)
+            })
+            .collect();
+        for record in group_a {
+            let key = self.allele_key(&record);
+            let partner = group_b
+                .iter_mut()
+                .find(|(b, b_key)| b.is_some() && *b_key == key)
+                .and_then(|(b, _)| b.take());
+            self.pending.push_back(match partner {
+                Some(b) => JoinItem::Both(record, b),
+                None => JoinItem::OnlyA(record),
+            });
+        }
+        self.pending.extend(
+            group_b
+                .into_iter()
+                .filter_map(|(b, _)| b.map(JoinItem::OnlyB)),
+        );
+        Ok(true)
+    }
+
+    /// What a record is matched on besides its position; empty when
+    /// matching on position alone
+    fn allele_key(&self, record: &VcfRecord) -> Vec<AlleleKey> {
+        if self.position_only {
+            return Vec::new();
+        }
+        let mut key = allele_keys(record);
+        key.sort();
+        key.dedup();
+        key
+    }
+}
+
+/// Trimmed position, REF and ALT of one allele
+type AlleleKey = (u64, String, String);
+
+/// Key of each ALT allele of `record`, in ALT order
+fn allele_keys(record: &VcfRecord) -> Vec<AlleleKey> {
+    record
+        .alternate
+        .iter()
+        .map(|alt| {
+            let (pos, reference, alternate) = trim_alleles(record.pos, &record.reference, alt);
+            (
+                pos,
+                reference.to_ascii_uppercase(),
+                alternate.to_ascii_uppercase(),
+            )
+        })
+        .collect()
+}
+
+/// For each ALT of `target`, the index of the same allele among the
+/// ALTs of `source`; `None` unless both list the same distinct alleles
+fn alt_order(target: &VcfRecord, source: &VcfRecord) -> Option<Vec<usize>> {
+    let source_keys = allele_keys(source);
+    let mut seen = vec![false; source_keys.len()];
+    let order: Vec<usize> = allele_keys(target)
+        .iter()
+        .map(|key| {
+            let index = source_keys.iter().position(|k| k == key)?;
+            (!std::mem::replace(&mut seen[index], true)).then_some(index)
+        })
+        .collect::<Option<_>>()?;
+    (order.len() == source_keys.len()).then_some(order)
+}
+
+impl<R1: Read, R2: Read> Iterator for SortedJoinIterator<R1, R2> {
+    type Item = VcfResult<JoinItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.pending.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.failed {
+                return None;
+            }
+            match self.fill() {
+                Ok(true) => {}
+                Ok(false) => return None,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// [`compare_callsets`](crate::compare_callsets) over two sorted streams,
+/// holding only the records at one position at a time
+///
+/// Records are paired as by [`SortedJoinIterator`], so a multi-allelic
+/// record only matches one with the same ALT alleles; split both files
+/// first to match allele by allele. Each ALT allele of a paired record is
+/// a shared variant, and of an unpaired one a variant of that file only.
+/// Genotypes are compared as in `compare_callsets`. Discordant keys and
+/// diffs are the first ones in file order rather than in key order.
+pub fn compare_sorted<R1: Read, R2: Read>(
+    a: VcfIterator<R1>,
+    b: VcfIterator<R2>,
+    chrom_order: ChromOrder,
+    options: &CompareOptions,
+) -> VcfResult<CompareResult> {
+    let pairs = sample_pairs(a.header(), b.header());
+    let mut join = SortedJoinIterator::new(a, b, chrom_order);
+    if options.strip_chr_prefix {
+        join = join.ignore_chr_prefix();
+    }
+    let passes = |record: &VcfRecord| !options.pass_only || record.filter == FilterStatus::Pass;
+
+    let mut result = CompareResult::default();
+    for item in join {
+        let (a, b) = match item? {
+            JoinItem::Both(a, b) => (Some(a), Some(b)),
+            JoinItem::OnlyA(a) => (Some(a), None),
+            JoinItem::OnlyB(b) => (None, Some(b)),
+        };
+        match (a.filter(passes), b.filter(passes)) {
+            (Some(a), Some(b)) => compare_pair(&a, &b, &pairs, options, &mut result),
+            (Some(a), None) => result.only_a += a.alternate.len(),
+            (None, Some(b)) => result.only_b += b.alternate.len(),
+            (None, None) => {}
+        }
+    }
+    Ok(result)
+}
+
+/// Count the ALT alleles of a paired record as shared and compare their
+/// genotypes
+fn compare_pair(
+    a: &VcfRecord,
+    b: &VcfRecord,
+    pairs: &[(usize, usize)],
+    options: &CompareOptions,
+    result: &mut CompareResult,
+) {
+    let chrom = if options.strip_chr_prefix {
+        strip_chr(&a.chrom)
+    } else {
+        &a.chrom
+    };
+    let key = |record: &VcfRecord, alt: &str| {
+        VariantKey::normalized(chrom, record.pos, &record.reference, alt)
+    };
+    let allele_dosage = |record: &VcfRecord, sample: usize, allele: usize| {
+        dosage(
+            record.samples.get(sample)?.genotype.as_ref()?,
+            allele as u8 + 1,
+        )
+    };
+
+    let mut first_key = None;
+    for (i, alt) in a.alternate.iter().enumerate() {
+        let variant = key(a, alt);
+        let Some(j) = b.alternate.iter().position(|alt| key(b, alt) == variant) else {
+            continue;
+        };
+        result.shared += 1;
+
+        let compared: Vec<bool> = pairs
+            .iter()
+            .filter_map(|&(sa, sb)| Some(allele_dosage(a, sa, i)? == allele_dosage(b, sb, j)?))
+            .collect();
+        let key = first_key.get_or_insert(variant);
+        if compared.is_empty() {
+            continue;
+        }
+        if compared.iter().all(|&same| same) {
+            result.concordant_genotypes += 1;
+        } else {
+            result.discordant_genotypes += 1;
+            if result.discordant.len() < options.max_discordant {
+                result.discordant.push(key.to_string());
+            }
+        }
+    }
+
+    if result.diffs.len() < options.max_diffs {
+        let fields = diff_records(a, b);
+        if let (false, Some(key)) = (fields.is_empty(), first_key) {
+            result.diffs.push(VariantDiff {
+                key: key.to_string(),
+                fields,
+            });
+        }
+    }
+}
+
+/// Options for [`annotate_sorted`]
+#[derive(Debug, Clone, Default)]
+pub struct AnnotateOptions {
+    /// INFO keys copied from the matching source record, replacing the
+    /// target's values; keys the source record lacks are left alone
+    pub info_keys: Vec<String>,
+    /// Copy the source ID into target records without one
+    pub copy_id: bool,
+    /// Match on chromosome and position alone
+    pub position_only: bool,
+}
+
+/// Counts from [`annotate_sorted`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnnotateSummary {
+    /// Target records passed on
+    pub records: usize,
+    /// Target records that had a matching source record
+    pub annotated: usize,
+}
+
+/// Copy INFO values and IDs from a sorted source file into the matching
+/// records of a sorted target, handing every target record to
+/// `on_record` in order
+///
+/// Records are matched as by [`SortedJoinIterator`]; source records
+/// without a target are dropped. Values of keys the source header
+/// declares `Number=A`, `R` or `G` are put in the target's ALT order;
+/// when the two records do not list the same alleles, as can happen with
+/// `position_only`, those keys are not copied. The caller adds the
+/// source's header definitions for the copied keys to the output header.
+pub fn annotate_sorted<R1: Read, R2: Read>(
+    target: VcfIterator<R1>,
+    source: VcfIterator<R2>,
+    chrom_order: ChromOrder,
+    options: &AnnotateOptions,
+    mut on_record: impl FnMut(VcfRecord),
+) -> VcfResult<AnnotateSummary> {
+    let source_header = source.header().clone();
+    let per_allele = |key: &str| {
+        source_header
+            .info_fields
+            .iter()
+            .any(|f| f.id == key && matches!(f.number.as_str(), "A" | "R" | "G"))
+    };
+    let mut join = SortedJoinIterator::new(target, source, chrom_order);
+    if options.position_only {
+        join = join.position_only();
+    }
+
+    let mut summary = AnnotateSummary::default();
+    for item in join {
+        let record = match item? {
+            JoinItem::Both(mut record, mut source) => {
+                // Per-allele values follow the target's ALT order
+                let aligned = alt_order(&record, &source).is_some_and(|order| {
+                    order.iter().enumerate().all(|(new, &old)| new == old)
+                        || source.reorder_alternates(&order, &source_header).is_ok()
+                });
+                for key in &options.info_keys {
+                    if !aligned && per_allele(key) {
+                        continue;
+                    }
+                    if let Some((key, value)) = source.info.get_key_value(key.as_str()) {
+                        record.info.insert(key.clone(), value.clone());
+                    }
+                }
+                if options.copy_id && record.id.is_none() {
+                    record.id = source.id;
+                }
+                summary.annotated += 1;
+                record
+            }
+            JoinItem::OnlyA(record) => record,
+            JoinItem::OnlyB(_) => continue,
+        };
+        summary.records += 1;
+        on_record(record);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shared: chr1:100, chr1:200 (ALTs in another order), chr2:50 and
+    // chr10:5. Only A: chr1:150, chr1:300 T>C. Only B: chr1:120,
+    // chr1:300 T>G, chr2:80. Natural order puts chr2 before chr10.
+    const A: &str = "##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1
+chr1\t100\t.\tA\tG\t50\tPASS\tDP=10\tGT\t0/1
+chr1\t150\t.\tC\tT\t50\tPASS\tDP=11\tGT\t1/1
+chr1\t200\t.\tG\tA,C\t50\tPASS\tDP=12\tGT\t1/2
+chr1\t300\t.\tT\tC\t50\tPASS\tDP=13\tGT\t0/1
+chr2\t50\t.\tAT\tA\t50\tPASS\tDP=14\tGT\t0/1
+chr10\t5\t.\tG\tC\t50\tq10\tDP=15\tGT\t0/1
+";
+
+    const B: &str = "##fileformat=VCFv4.2
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele frequency\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1
+chr1\t100\trs100\tA\tG\t50\tPASS\tAF=0.1\tGT\t0/1
+chr1\t120\trs120\tT\tA\t50\tPASS\tAF=0.2\tGT\t0/1
+chr1\t200\trs200\tG\tC,A\t50\tPASS\tAF=0.3,0.4\tGT\t2/1
+chr1\t300\trs300\tT\tG\t50\tPASS\tAF=0.5\tGT\t0/1
+chr2\t50\trs50\tATT\tAT\t50\tPASS\tAF=0.6\tGT\t1/1
+chr2\t80\trs80\tC\tG\t50\tPASS\tAF=0.7\tGT\t0/1
+chr10\t5\trs5\tG\tC\t50\tPASS\tAF=0.8\tGT\t0/1
+";
+
+    fn stream(text: &str) -> VcfIterator<&[u8]> {
+        VcfIterator::new(text.as_bytes()).unwrap()
+    }
+
+    /// Items as `both:`, `a:` or `b:` and the position
+    fn walk(join: SortedJoinIterator<&[u8], &[u8]>) -> Vec<String> {
+        join.map(|item| match item.unwrap() {
+            JoinItem::Both(a, b) => {
+                assert_eq!((&a.chrom, a.pos), (&b.chrom, b.pos));
+                format!("both:{}:{}", a.chrom, a.pos)
+            }
+            JoinItem::OnlyA(a) => format!("a:{}:{}", a.chrom, a.pos),
+            JoinItem::OnlyB(b) => format!("b:{}:{}", b.chrom, b.pos),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_join_interleaved_records() {
+        let join = SortedJoinIterator::new(stream(A), stream(B), ChromOrder::Natural);
+        assert_eq!(
+            walk(join),
+            [
+                "both:chr1:100",
+                "b:chr1:120",
+                "a:chr1:150",
+                "both:chr1:200",
+                "a:chr1:300",
+                "b:chr1:300",
+                "both:chr2:50",
+                "b:chr2:80",
+                "both:chr10:5",
+            ]
+        );
+
+        // Position alone pairs the differing alleles at chr1:300
+        let join = SortedJoinIterator::new(stream(A), stream(B), ChromOrder::Natural);
+        let items = walk(join.position_only());
+        assert_eq!(items[4], "both:chr1:300");
+        assert_eq!(items.len(), 8);
+    }
+
+    #[test]
+    fn test_unsorted_input_names_the_lines() {
+        // chr10 before chr2 is unsorted in natural order
+        let err = SortedJoinIterator::new(stream(A), stream(B), ChromOrder::Lexicographic)
+            .find_map(Result::err)
+            .unwrap();
+        assert!(
+            matches!(err, VcfError::InvalidRecord { line: 10, .. }),
+            "{}",
+            err
+        );
+        assert!(
+            err.to_string()
+                .contains("file A is not sorted: chr10:5 comes after chr2:50 on line 9"),
+            "{}",
+            err
+        );
+
+        let swapped = B.replace("chr1\t100\t", "chr1\t999\t");
+        let mut join = SortedJoinIterator::new(stream(A), stream(&swapped), ChromOrder::Natural);
+        let err = join.find_map(Result::err).unwrap();
+        assert!(
+            err.to_string()
+                .contains("line 6: file B is not sorted: chr1:120 comes after chr1:999 on line 5"),
+            "{}",
+            err
+        );
+        assert!(join.next().is_none());
+    }
+
+    #[test]
+    fn test_chrom_orders() {
+        assert_eq!(ChromOrder::Natural.compare("chr2", "chr10"), Ordering::Less);
+        assert_eq!(ChromOrder::Natural.compare("chrX", "22"), Ordering::Greater);
+        assert_eq!(
+            ChromOrder::Natural.compare("chrM", "chrY"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            ChromOrder::Lexicographic.compare("chr2", "chr10"),
+            Ordering::Greater
+        );
+
+        let mut header = VcfHeader::default();
+        assert_eq!(ChromOrder::from_header(&header), ChromOrder::Natural);
+        header.build_contigs_from_records(&[
+            VcfRecord::new("chrY", 1, "A", vec!["G"]),
+            VcfRecord::new("chr3", 1, "A", vec!["G"]),
+        ]);
+        let listed = ChromOrder::from_header(&header);
+        assert_eq!(listed.compare("chrY", "chr3"), Ordering::Less);
+        assert_eq!(listed.compare("chr3", "chr1"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_compare_sorted() {
+        let options = CompareOptions {
+            max_diffs: 10,
+            ..CompareOptions::default()
+        };
+        let result = compare_sorted(stream(A), stream(B), ChromOrder::Natural, &options).unwrap();
+        // 100, both alleles at 200, chr2:50 and chr10:5
+        assert_eq!(result.shared, 5);
+        assert_eq!((result.only_a, result.only_b), (2, 3));
+        // chr2:50 is het in A, hom-alt in B
+        assert_eq!(result.discordant, ["chr2:50:AT:A"]);
+        assert_eq!(result.concordant_genotypes, 4);
+        // Every paired record differs in ID and INFO
+        assert_eq!(result.diffs.len(), 4);
+
+        let pass_only = CompareOptions {
+            pass_only: true,
+            ..CompareOptions::default()
+        };
+        let result = compare_sorted(stream(A), stream(B), ChromOrder::Natural, &pass_only).unwrap();
+        assert_eq!((result.shared, result.only_a, result.only_b), (4, 2, 4));
+
+        // Agrees with the in-memory comparison
+        let (header_a, records_a) = crate::parser::VcfParser::new().parse_str(A).unwrap();
+        let (header_b, records_b) = crate::parser::VcfParser::new().parse_str(B).unwrap();
+        let in_memory = crate::compare::compare_callsets(
+            &header_a,
+            &records_a,
+            &header_b,
+            &records_b,
+            &CompareOptions::default(),
+        );
+        let streamed = compare_sorted(
+            stream(A),
+            stream(B),
+            ChromOrder::Natural,
+            &CompareOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            (streamed.shared, streamed.only_a, streamed.only_b),
+            (in_memory.shared, in_memory.only_a, in_memory.only_b)
+        );
+        assert_eq!(streamed.discordant, in_memory.discordant);
+    }
+
+    #[test]
+    fn test_annotate_sorted() {
+        let options = AnnotateOptions {
+            info_keys: vec!["AF".to_string()],
+            copy_id: true,
+            position_only: false,
+        };
+        let mut annotated = Vec::new();
+        let summary = annotate_sorted(stream(A), stream(B), ChromOrder::Natural, &options, |r| {
+            annotated.push(r)
+        })
+        .unwrap();
+        assert_eq!(
+            summary,
+            AnnotateSummary {
+                records: 6,
+                annotated: 4
+            }
+        );
+        assert_eq!(annotated.len(), 6);
+
+        let ids: Vec<Option<&str>> = annotated.iter().map(|r| r.id.as_deref()).collect();
+        assert_eq!(
+            ids,
+            [
+                Some("rs100"),
+                None,
+                Some("rs200"),
+                None,
+                Some("rs50"),
+                Some("rs5")
+            ]
+        );
+        assert!(matches!(annotated[0].info["AF"], InfoValue::Float(f) if f == 0.1));
+        // Source ALTs C,A; the target's A,C get their own frequencies
+        assert!(matches!(&annotated[2].info["AF"], InfoValue::FloatArray(af) if af == &[0.4, 0.3]));
+        assert!(matches!(annotated[0].info["DP"], InfoValue::Integer(10)));
+        assert!(!annotated[1].info.contains_key("AF"));
+
+        let by_position = AnnotateOptions {
+            position_only: true,
+            ..options
+        };
+        let mut annotated = Vec::new();
+        let summary = annotate_sorted(
+            stream(A),
+            stream(B),
+            ChromOrder::Natural,
+            &by_position,
+            |r| annotated.push(r),
+        )
+        .unwrap();
+        assert_eq!(summary.annotated, 5);
+        // chr1:300 T>C pairs with T>G, whose AF describes another allele
+        assert_eq!(annotated[3].id.as_deref(), Some("rs300"));
+        assert!(!annotated[3].info.contains_key("AF"));
+    }
+}
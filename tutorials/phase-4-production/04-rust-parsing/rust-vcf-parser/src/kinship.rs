@@ -0,0 +1,316 @@
+//! Kinship
+//!
+//! KING-robust kinship between samples (Manichaikul et al. 2010), for
+//! spotting duplicates and relatives in cohort QC. Expected values are
+//! 0.5 for duplicates or monozygotic twins, 0.25 for parent-child and
+//! full siblings, 0.125 for second-degree relatives and 0 for unrelated
+//! samples.
+
+use crate::types::{SampleData, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+
+/// Genotype counts behind one pair's estimate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairCounts {
+    /// Sites where both are heterozygous
+    pub het_het: u32,
+    /// Sites where they are opposite homozygotes
+    pub ibs0: u32,
+    /// Heterozygous sites of each sample, over the sites both are called
+    pub het_a: u32,
+    pub het_b: u32,
+}
+
+impl PairCounts {
+    /// Count one site from both samples' ALT allele counts
+    pub fn add(&mut self, a: u8, b: u8) {
+        self.het_a += u32::from(a == 1);
+        self.het_b += u32::from(b == 1);
+        self.het_het += u32::from(a == 1 && b == 1);
+        self.ibs0 += u32::from(a.abs_diff(b) == 2);
+    }
+
+    /// (N_Aa,Aa − 2·N_AA,aa) / (N_Aa(i) + N_Aa(j)); `None` if neither
+    /// sample has a heterozygous site
+    pub fn kinship(&self) -> Option<f64> {
+        let hets = self.het_a + self.het_b;
+        (hets > 0).then(|| (f64::from(self.het_het) - 2.0 * f64::from(self.ibs0)) / f64::from(hets))
+    }
+}
+
+/// Square kinship matrix over a header's samples
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KinshipMatrix {
+    /// Row and column order
+    pub samples: Vec<String>,
+    /// Row-major, `samples.len()` squared, with 0.5 on the diagonal and
+    /// NaN for pairs without a heterozygous site
+    pub data: Vec<f64>,
+    /// Sites counted
+    pub sites: usize,
+}
+
+impl KinshipMatrix {
+    pub fn rows(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols() + col]
+    }
+}
+
+/// Pair counts for every pair of samples, fed one record at a time
+///
+/// Only the counts are kept, so a file can be streamed through it. With a
+/// stride of `k`, only every `k`th record offered is used.
+#[derive(Debug, Clone)]
+pub struct KinshipAccumulator {
+    n_samples: usize,
+    stride: usize,
+    offered: usize,
+    sites: usize,
+    /// Upper triangle, row by row
+    pairs: Vec<PairCounts>,
+}
+
+impl KinshipAccumulator {
+    pub fn new(n_samples: usize) -> Self {
+        Self {
+            n_samples,
+            stride: 1,
+            offered: 0,
+            sites: 0,
+            pairs: vec![PairCounts::default(); n_samples * n_samples.saturating_sub(1) / 2],
+        }
+    }
+
+    /// Use every `stride`th record (0 is taken as 1)
+    pub fn with_stride(mut self, stride: usize) -> Self {
+        self.stride = stride.max(1);
+        self
+    }
+
+    /// Count `record` if it falls on the stride and is biallelic; each
+    /// pair skips it when either genotype is missing or not diploid
+    pub fn add(&mut self, record: &VcfRecord) {
+        self.offered += 1;
+        if !(self.offered - 1).is_multiple_of(self.stride) || record.alternate.len() != 1 {
+            return;
+        }
+        let dosages: Vec<Option<u8>> = (0..self.n_samples)
+            .map(|i| record.samples.get(i).and_then(diploid_dosage))
+            .collect();
+
+        let mut pair = 0;
+        for i in 0..self.n_samples {
+            for j in i + 1..self.n_samples {
+                if let (Some(a), Some(b)) = (dosages[i], dosages[j]) {
+                    self.pairs[pair].add(a, b);
+                }
+                pair += 1;
+            }
+        }
+        self.sites += 1;
+    }
+
+    /// Counts for samples `i` and `j`, `i != j`
+    pub fn pair(&self, i: usize, j: usize) -> PairCounts {
+        let (i, j) = (i.min(j), i.max(j));
+        // Pairs before row i, then the offset within it
+        let row_start = i * self.n_samples - i * (i + 1) / 2;
+        self.pairs[row_start + j - i - 1]
+    }
+
+    /// The kinship matrix, labelled with `samples`
+    pub fn finish(self, samples: Vec<String>) -> KinshipMatrix {
+        let n = self.n_samples;
+        let mut data = vec![0.5; n * n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let kinship = self.pair(i, j).kinship().unwrap_or(f64::NAN);
+                data[i * n + j] = kinship;
+                data[j * n + i] = kinship;
+            }
+        }
+        KinshipMatrix {
+            samples,
+            data,
+            sites: self.sites,
+        }
+    }
+}
+
+/// KING-robust kinship of two samples over the biallelic records
+///
+/// Sites where either genotype is missing or not diploid are skipped.
+/// `None` if a name is not in the header or neither sample has a
+/// heterozygous site.
+pub fn kinship_king(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    sample_a: &str,
+    sample_b: &str,
+) -> Option<f64> {
+    let column = |name: &str| header.samples.iter().position(|s| s == name);
+    let (a, b) = (column(sample_a)?, column(sample_b)?);
+
+    let mut counts = PairCounts::default();
+    for record in records.iter().filter(|r| r.alternate.len() == 1) {
+        let dosage = |i: usize| record.samples.get(i).and_then(diploid_dosage);
+        if let (Some(da), Some(db)) = (dosage(a), dosage(b)) {
+            counts.add(da, db);
+        }
+    }
+    counts.kinship()
+}
+
+/// Kinship of every pair of the header's samples
+///
+/// With `max_sites`, records are thinned to an even stride so that at
+/// most that many are used.
+pub fn kinship_matrix(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    max_sites: Option<usize>,
+) -> KinshipMatrix {
+    let stride = max_sites.map_or(1, |max| records.len().div_ceil(max.max(1)));
+    let mut accumulator = KinshipAccumulator::new(header.samples.len()).with_stride(stride);
+    for record in records {
+        accumulator.add(record);
+    }
+    accumulator.finish(header.samples.clone())
+}
+
+/// ALT allele count of a fully called biallelic diploid genotype
+fn diploid_dosage(sample: &SampleData) -> Option<u8> {
+    match sample.genotype.as_ref()?.alleles[..] {
+        [Some(a), Some(b)] if a <= 1 && b <= 1 => Some(a + b),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+    use crate::rng::SplitMix64;
+
+    /// Father, mother, their child, an unrelated sample and a duplicate
+    /// of the father, over `sites` SNPs with allele frequencies in 0.1..0.9
+    fn simulated_family(sites: usize, seed: u64) -> String {
+        let mut rng = SplitMix64::new(seed);
+        let mut vcf = String::from(
+            "##fileformat=VCFv4.2\n\
+             ##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">\n\
+             #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tDad\tMum\tKid\tStranger\tDadAgain\n",
+        );
+        for site in 0..sites {
+            let p = 0.1 + 0.8 * rng.next_f64();
+            let mut allele = || u8::from(rng.next_f64() < p);
+            let (dad, mum, stranger) = (
+                [allele(), allele()],
+                [allele(), allele()],
+                [allele(), allele()],
+            );
+            let pick = (rng.next_u64() & 1) as usize;
+            let kid = [dad[pick], mum[(rng.next_u64() & 1) as usize]];
+            let gt = |g: [u8; 2]| format!("{}/{}", g[0], g[1]);
+            vcf += &format!(
+                "chr1\t{}\t.\tA\tG\t.\tPASS\t.\tGT\t{}\t{}\t{}\t{}\t{}\n",
+                site + 1,
+                gt(dad),
+                gt(mum),
+                gt(kid),
+                gt(stranger),
+                gt(dad)
+            );
+        }
+        vcf
+    }
+
+    #[test]
+    fn test_king_parent_child_unrelated_and_duplicate() {
+        let (header, records) = VcfParser::new()
+            .parse_str(&simulated_family(4000, 7))
+            .unwrap();
+        let king = |a, b| kinship_king(&records, &header, a, b).unwrap();
+
+        assert!(
+            (king("Dad", "Kid") - 0.25).abs() < 0.05,
+            "{}",
+            king("Dad", "Kid")
+        );
+        assert!(
+            (king("Mum", "Kid") - 0.25).abs() < 0.05,
+            "{}",
+            king("Mum", "Kid")
+        );
+        assert!(king("Dad", "Mum").abs() < 0.05, "{}", king("Dad", "Mum"));
+        assert!(
+            king("Kid", "Stranger").abs() < 0.05,
+            "{}",
+            king("Kid", "Stranger")
+        );
+        assert_eq!(king("Dad", "DadAgain"), 0.5);
+        assert_eq!(king("Kid", "Dad"), king("Dad", "Kid"));
+        assert_eq!(kinship_king(&records, &header, "Dad", "Nobody"), None);
+    }
+
+    #[test]
+    fn test_counts_skip_missing_and_multiallelic_sites() {
+        let vcf = "##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tA\tB
+chr1\t1\t.\tA\tG\t.\tPASS\t.\tGT\t0/1\t0/1
+chr1\t2\t.\tA\tG\t.\tPASS\t.\tGT\t0/0\t1/1
+chr1\t3\t.\tA\tG\t.\tPASS\t.\tGT\t0/1\t0/0
+chr1\t4\t.\tA\tG\t.\tPASS\t.\tGT\t0/1\t./.
+chr1\t5\t.\tA\tG,T\t.\tPASS\t.\tGT\t0/1\t0/1
+chr1\t6\t.\tA\tG\t.\tPASS\t.\tGT\t0/1\t1
+";
+        let (header, records) = VcfParser::new().parse_str(vcf).unwrap();
+        // het-het 1, IBS0 1, hets 2 + 1: (1 - 2) / 3
+        let kinship = kinship_king(&records, &header, "A", "B").unwrap();
+        assert!((kinship + 1.0 / 3.0).abs() < 1e-12);
+
+        let mut accumulator = KinshipAccumulator::new(2);
+        records.iter().for_each(|r| accumulator.add(r));
+        assert_eq!(
+            accumulator.pair(1, 0),
+            PairCounts {
+                het_het: 1,
+                ibs0: 1,
+                het_a: 2,
+                het_b: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_matrix_matches_pairs_and_thins() {
+        let (header, records) = VcfParser::new()
+            .parse_str(&simulated_family(600, 11))
+            .unwrap();
+        let matrix = kinship_matrix(&records, &header, None);
+        assert_eq!((matrix.rows(), matrix.cols(), matrix.sites), (5, 5, 600));
+        assert_eq!(matrix.data.len(), 25);
+        assert_eq!(matrix.get(3, 3), 0.5);
+        assert_eq!(matrix.get(0, 2), matrix.get(2, 0));
+        assert_eq!(
+            Some(matrix.get(1, 2)),
+            kinship_king(&records, &header, "Mum", "Kid")
+        );
+
+        let thinned = kinship_matrix(&records, &header, Some(100));
+        assert_eq!(thinned.sites, 100);
+        let every_sixth: Vec<VcfRecord> = records.iter().step_by(6).cloned().collect();
+        assert_eq!(
+            thinned.data,
+            kinship_matrix(&every_sixth, &header, None).data
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Linkage Disequilibrium
+//!
+//! r² and D' between pairs of variants, for LocusZoom-style plots that
+//! colour each variant by its LD with an index variant. Phased genotypes
+//! give haplotypes directly; unphased diploid ones are resolved with the
+//! usual EM estimate of haplotype frequencies, as plink does.
+
+use crate::types::{SampleData, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+
+/// EM iterations stop once the double heterozygotes' cis fraction moves
+/// less than this
+const EM_TOLERANCE: f64 = 1e-12;
+const EM_MAX_ITERATIONS: usize = 1000;
+
+/// LD between two variants
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LdResult {
+    pub r2: f64,
+    /// |D'|, D normalised by its largest value for these allele
+    /// frequencies
+    pub d_prime: f64,
+    /// Samples with both genotypes called
+    pub n: usize,
+    /// Whether every sample's haplotypes were known, rather than any
+    /// being estimated by EM
+    pub phased: bool,
+}
+
+/// LD of `records[idx_a]` with `records[idx_b]` over the header's samples
+///
+/// Alleles are REF against any ALT. Samples are used when both genotypes
+/// are fully called with the same ploidy, one or two. Phased diploid and
+/// haploid calls contribute their haplotypes as they are; for unphased
+/// diploid calls haplotypes are known unless the sample is heterozygous
+/// at both variants, and those double heterozygotes are split between
+/// the two phasings by EM. `None` if an index is out of range, no sample
+/// is usable, or either variant is monomorphic in the samples used.
+pub fn ld_r2(
+    records: &[VcfRecord],
+    idx_a: usize,
+    idx_b: usize,
+    header: &VcfHeader,
+) -> Option<LdResult> {
+    let (a, b) = (records.get(idx_a)?, records.get(idx_b)?);
+    let n_samples = header.samples.len();
+    pair_ld(
+        &a.samples[..n_samples.min(a.samples.len())],
+        &b.samples[..n_samples.min(b.samples.len())],
+    )
+}
+
+/// LD of every variant near an index variant, as parallel arrays
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LdProfile {
+    /// Record indices, in input order; the index variant is included
+    pub indices: Vec<usize>,
+    pub positions: Vec<u64>,
+    pub r2: Vec<f64>,
+    pub d_prime: Vec<f64>,
+}
+
+/// LD with `records[index_variant]` of every record on its chromosome
+/// within `window_bp` of it
+///
+/// Records for which [`ld_r2`] has no answer, such as monomorphic ones,
+/// are left out. Empty if the index variant itself is monomorphic or out
+/// of range.
+pub fn ld_with_index(records: &[VcfRecord], index_variant: usize, window_bp: u64) -> LdProfile {
+    let mut profile = LdProfile::default();
+    let Some(index) = records.get(index_variant) else {
+        return profile;
+    };
+
+    for (i, record) in records.iter().enumerate() {
+        if record.chrom != index.chrom || record.pos.abs_diff(index.pos) > window_bp {
+            continue;
+        }
+        if let Some(ld) = pair_ld(&index.samples, &record.samples) {
+            profile.indices.push(i);
+            profile.positions.push(record.pos);
+            profile.r2.push(ld.r2);
+            profile.d_prime.push(ld.d_prime);
+        }
+    }
+    profile
+}
+
+/// Haplotype counts, indexed `[alt at A][alt at B]`
+type Haplotypes = [[f64; 2]; 2];
+
+fn pair_ld(a: &[SampleData], b: &[SampleData]) -> Option<LdResult> {
+    let mut known: Haplotypes = [[0.0; 2]; 2];
+    let mut double_hets = 0.0;
+    let mut n = 0;
+
+    for (sa, sb) in a.iter().zip(b) {
+        let (Some(alt_a), Some(alt_b)) = (alt_alleles(sa), alt_alleles(sb)) else {
+            continue;
+        };
+        if alt_a.len() != alt_b.len() || !(1..=2).contains(&alt_a.len()) {
+            continue;
+        }
+        n += 1;
+
+        let phased = |s: &SampleData| s.genotype.as_ref().is_some_and(|g| g.phased);
+        let known_pairs = alt_a.len() == 1
+            || (phased(sa) && phased(sb))
+            || alt_a[0] == alt_a[1]
+            || alt_b[0] == alt_b[1];
+        if !known_pairs {
+            double_hets += 1.0;
+            continue;
+        }
+        for (&x, &y) in alt_a.iter().zip(&alt_b) {
+            known[usize::from(x)][usize::from(y)] += 1.0;
+        }
+    }
+
+    let total = known.iter().flatten().sum::<f64>() + 2.0 * double_hets;
+    if n == 0 || total == 0.0 {
+        return None;
+    }
+    let p_a = (known[1][0] + known[1][1] + double_hets) / total;
+    let p_b = (known[0][1] + known[1][1] + double_hets) / total;
+    if p_a <= 0.0 || p_a >= 1.0 || p_b <= 0.0 || p_b >= 1.0 {
+        return None;
+    }
+
+    let p_ab = if double_hets > 0.0 {
+        em_cis_frequency(&known, double_hets, total)
+    } else {
+        known[1][1] / total
+    };
+    let d = p_ab - p_a * p_b;
+    let r2 = d * d / (p_a * (1.0 - p_a) * p_b * (1.0 - p_b));
+    let d_max = if d >= 0.0 {
+        (p_a * (1.0 - p_b)).min((1.0 - p_a) * p_b)
+    } else {
+        (p_a * p_b).min((1.0 - p_a) * (1.0 - p_b))
+    };
+
+    Some(LdResult {
+        r2: r2.min(1.0),
+        d_prime: (d / d_max).abs().min(1.0),
+        n,
+        phased: double_hets == 0.0,
+    })
+}
+
+/// Frequency of the ALT-ALT haplotype, with each double heterozygote
+/// split between ALT-ALT/REF-REF and ALT-REF/REF-ALT by EM
+fn em_cis_frequency(known: &Haplotypes, double_hets: f64, total: f64) -> f64 {
+    let frequencies = |cis: f64| -> Haplotypes {
+        let trans = 1.0 - cis;
+        [
+            [
+                (known[0][0] + double_hets * cis) / total,
+                (known[0][1] + double_hets * trans) / total,
+            ],
+            [
+                (known[1][0] + double_hets * trans) / total,
+                (known[1][1] + double_hets * cis) / total,
+            ],
+        ]
+    };
+
+    let mut cis = 0.5;
+    for _ in 0..EM_MAX_ITERATIONS {
+        let p = frequencies(cis);
+        let (cis_weight, trans_weight) = (p[1][1] * p[0][0], p[1][0] * p[0][1]);
+        let next = if cis_weight + trans_weight > 0.0 {
+            cis_weight / (cis_weight + trans_weight)
+        } else {
+            0.5
+        };
+        let converged = (next - cis).abs() < EM_TOLERANCE;
+        cis = next;
+        if converged {
+            break;
+        }
+    }
+    frequencies(cis)[1][1]
+}
+
+/// Whether each allele of a fully called genotype is an ALT
+fn alt_alleles(sample: &SampleData) -> Option<Vec<bool>> {
+    let genotype = sample.genotype.as_ref()?;
+    genotype.alleles.iter().map(|a| Some((*a)? > 0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    // Haplotypes (A, B): 3 ALT-ALT, 2 ALT-REF, 1 REF-ALT, 4 REF-REF, so
+    // pA = 0.5, pB = 0.4, D = 0.3 - 0.2 = 0.1, r² = 0.01 / 0.06 and
+    // D' = 0.1 / 0.2. No sample is heterozygous at both, so plink's EM
+    // has these as its exact solution.
+    const PHASED: &str = "##fileformat=VCFv4.2
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3\tS4\tS5
+chr1\t100\tA\tC\tT\t.\tPASS\t.\tGT\t0|0\t1|1\t0|1\t1|1\t0|0
+chr1\t200\tB\tG\tA\t.\tPASS\t.\tGT\t0|0\t1|1\t0|0\t1|0\t0|1
+chr1\t300\tC\tA\tG\t.\tPASS\t.\tGT\t0|0\t0|0\t0|0\t0|0\t0|0
+chr1\t150\tA2\tT\tC\t.\tPASS\t.\tGT\t0|0\t1|1\t0|1\t1|1\t0|0
+chr1\t9000\tfar\tA\tG\t.\tPASS\t.\tGT\t0|1\t1|1\t0|1\t1|1\t0|0
+chr2\t100\tother\tA\tG\t.\tPASS\t.\tGT\t0|1\t1|1\t0|1\t1|1\t0|0
+";
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_phased_r2_and_d_prime() {
+        let (header, records) = VcfParser::new().parse_str(PHASED).unwrap();
+        let ld = ld_r2(&records, 0, 1, &header).unwrap();
+        assert_close(ld.r2, 1.0 / 6.0);
+        assert_close(ld.d_prime, 0.5);
+        assert_eq!(ld.n, 5);
+        assert!(ld.phased);
+
+        // Symmetric, and perfect with an identical variant
+        assert_eq!(ld_r2(&records, 1, 0, &header), Some(ld));
+        assert_close(ld_r2(&records, 0, 3, &header).unwrap().r2, 1.0);
+
+        assert_eq!(ld_r2(&records, 0, 2, &header), None);
+        assert_eq!(ld_r2(&records, 0, 99, &header), None);
+    }
+
+    #[test]
+    fn test_unphased_em_matches_phased_when_fully_informative() {
+        let unphased = PHASED.replace('|', "/");
+        let (header, records) = VcfParser::new().parse_str(&unphased).unwrap();
+        let ld = ld_r2(&records, 0, 1, &header).unwrap();
+        assert_close(ld.r2, 1.0 / 6.0);
+        assert_close(ld.d_prime, 0.5);
+        assert!(ld.phased);
+
+        // A double heterozygote: phased it is cis, unphased EM splits it
+        let with_cis = |vcf: &str, gt: &str| {
+            vcf.replace("\tS5\n", "\tS5\tS6\n")
+                .replace("\t0|0\nchr1\t200", &format!("\t0|0\t{}\nchr1\t200", gt))
+                .replace("\t0|1\nchr1\t300", &format!("\t0|1\t{}\nchr1\t300", gt))
+        };
+        let (header, records) = VcfParser::new()
+            .parse_str(&with_cis(PHASED, "0|1"))
+            .unwrap();
+        let phased = ld_r2(&records, 0, 1, &header).unwrap();
+        assert_close(phased.r2, 0.015625 / (0.25 * 35.0 / 144.0));
+        assert!(phased.phased);
+
+        let (header, records) = VcfParser::new()
+            .parse_str(&with_cis(PHASED, "0/1"))
+            .unwrap();
+        let estimated = ld_r2(&records, 0, 1, &header).unwrap();
+        assert!(!estimated.phased);
+        assert_eq!(estimated.n, 6);
+        // Fixed point of x = (3 + x)(4 + x) / ((3 + x)(4 + x) + (3 - x)(2 - x))
+        assert!((estimated.r2 - 0.220955710589).abs() < 1e-9);
+        assert!((estimated.d_prime - 0.556181620359).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_ld_with_index_window() {
+        let (_, records) = VcfParser::new().parse_str(PHASED).unwrap();
+        let profile = ld_with_index(&records, 0, 1000);
+        // The monomorphic record, the distant one and chr2 are left out
+        assert_eq!(profile.indices, [0, 1, 3]);
+        assert_eq!(profile.positions, [100, 200, 150]);
+        assert_close(profile.r2[0], 1.0);
+        assert_close(profile.r2[1], 1.0 / 6.0);
+        assert_close(profile.d_prime[1], 0.5);
+
+        assert_eq!(ld_with_index(&records, 2, 1000), LdProfile::default());
+        assert_eq!(ld_with_index(&records, 99, 1000), LdProfile::default());
+    }
+}
@@ -6,7 +6,108 @@
 pub mod parser;
 pub mod types;
 pub mod error;
+pub mod compare;
+pub mod diff;
+pub mod summary;
+pub mod writer;
+pub mod gzip;
+pub mod region;
+pub mod tabix;
+pub mod view;
+pub mod assembly;
+pub mod filter_expr;
+pub mod trio;
+pub mod concordance;
+pub mod qc;
+pub mod roh;
+pub mod gene_model;
+pub mod consequence;
+pub mod manhattan;
+pub mod rng;
+pub mod thin;
+pub mod number;
+pub mod normalize;
+pub mod sv;
+pub mod somatic;
+pub mod id_index;
+pub mod dosage;
+pub mod ld;
+pub mod kinship;
+pub mod genotype_stats;
+pub mod join;
+pub mod sampling;
+pub mod fasta;
+pub mod ref_check;
+pub mod allelic;
+pub mod hgvs;
+pub mod store;
+pub mod split;
 
-pub use parser::VcfParser;
+#[cfg(feature = "capi")]
+pub mod ffi;
+
+#[cfg(feature = "tracing")]
+pub mod metrics;
+
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+
+pub use parser::{calculate_stats, calculate_stats_auto, VcfParser, VcfPushParser};
 pub use types::*;
 pub use error::VcfError;
+pub use compare::{
+    benchmark, compare_callsets, BenchmarkCounts, BenchmarkOptions, BenchmarkResult, CompareOptions,
+    CompareResult, VariantDiff, VariantKey,
+};
+pub use diff::{diff_headers, diff_records, diff_records_with_epsilon, FieldDiff};
+pub use summary::{DensityCollector, QualHistogram};
+pub use writer::{to_vcf_string, FloatFormat, VcfWriter};
+pub use region::Region;
+pub use id_index::IdIndex;
+pub use ld::{ld_r2, ld_with_index, LdProfile, LdResult};
+pub use kinship::{kinship_king, kinship_matrix, KinshipAccumulator, KinshipMatrix, PairCounts};
+pub use genotype_stats::{allele_counts, sample_genotype_stats, AlleleCounts, SampleGenotypeStats};
+pub use join::{annotate_sorted, compare_sorted, AnnotateOptions, AnnotateSummary, ChromOrder, JoinItem, SortedJoinIterator};
+pub use sampling::{sample_fraction, sample_records, ReservoirSampler};
+pub use fasta::FastaIndex;
+pub use ref_check::{
+    validate_ref_alleles, validate_ref_alleles_with_options, RefMismatch, RefValidationOptions,
+    RefValidationReport, RefValidator,
+};
+pub use allelic::{allelic_imbalance_test, ln_factorial, record_allelic_imbalance, record_allelic_imbalance_with_min_depth};
+pub use hgvs::hgvs_g_change;
+pub use store::VariantStore;
+pub use split::{
+    split_by_chromosome, split_by_sample, split_by_sample_with_options, split_to_files, SplitBy,
+    SplitFile, SplitOptions,
+};
+pub use dosage::{dosage_chunks, dosage_matrix, dosage_matrix_with_layout, DosageMatrix, MatrixLayout};
+pub use tabix::{IndexedVcfReader, TabixIndex};
+pub use view::{SampleView, VcfRecordView};
+pub use filter_expr::CompiledFilter;
+pub use thin::{thin_for_plot, thin_records, ThinStrategy};
+pub use number::{expected_value_count, genotype_index};
+pub use normalize::split_multiallelic;
+pub use somatic::{filter_somatic, SomaticCall, SomaticEvidence, SomaticOptions, TumorNormal};
+pub use sv::{pair_breakends, Breakend, BreakendPairs, Breakpoint, Orientation, SvEvent, SvKind};
+pub use manhattan::{manhattan_data, manhattan_data_with_max, ManhattanData};
+pub use gene_model::{
+    assign_genes, gene_variant_counts, GeneModel, GeneRegion, GeneVariantCounts, Strand,
+};
+pub use consequence::{predict_snp_consequence, CodonChange, Consequence, TranscriptModel};
+pub use roh::{detect_roh, RohOptions, RohSegment};
+pub use qc::{record_qc_flags, QcFlag, QcThresholds, VafSummary};
+pub use concordance::{
+    all_pairs_concordance, all_pairs_concordance_with_policy, genotype_concordance,
+    genotype_concordance_with_policy, ConcordanceMatrix, GenotypeClass, PairwiseConcordance,
+};
+pub use trio::{find_de_novo, DeNovoCall, DeNovoGates, DeNovoOptions, DeNovoResult, Trio};
+pub use assembly::{
+    detect_assembly, remap_records, Assembly, AssemblyGuess, CoordinateMapper, RemapSummary,
+};
+
+#[cfg(feature = "tracing")]
+pub use metrics::ParseMetrics;
+
+#[cfg(feature = "parallel")]
+pub use parser::calculate_stats_parallel;
@@ -0,0 +1,212 @@
+//! Manhattan Plot Data
+//!
+//! Association p-values from an INFO key laid out on a genome-wide x-axis,
+//! as parallel arrays ready for plotting.
+
+use crate::types::{InfoValue, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// −log10(p) used for p = 0, and the most any point is given, by
+/// [`manhattan_data`]
+pub const DEFAULT_MAX_NEG_LOG10_P: f64 = 300.0;
+
+/// Points of a Manhattan plot; `positions`, `neg_log10_p` and
+/// `chrom_index` are parallel, in record order
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ManhattanData {
+    /// POS plus the offset of the record's chromosome
+    pub positions: Vec<u64>,
+    pub neg_log10_p: Vec<f64>,
+    /// Index into `chromosomes`
+    pub chrom_index: Vec<u32>,
+    /// Chromosomes with at least one point, in x-axis order
+    pub chromosomes: Vec<String>,
+    /// Where each chromosome starts on the x-axis
+    pub offsets: Vec<u64>,
+    /// Midpoint of each chromosome on the x-axis, for axis labels
+    pub ticks: Vec<f64>,
+    /// Records without a usable value under the key
+    pub skipped: usize,
+    /// Points capped at the maximum −log10(p)
+    pub clamped: usize,
+}
+
+/// [`manhattan_data_with_max`] capped at [`DEFAULT_MAX_NEG_LOG10_P`]
+pub fn manhattan_data(
+    records: &[VcfRecord],
+    p_key: &str,
+    already_log10: bool,
+    header: Option<&VcfHeader>,
+) -> ManhattanData {
+    manhattan_data_with_max(
+        records,
+        p_key,
+        already_log10,
+        header,
+        DEFAULT_MAX_NEG_LOG10_P,
+    )
+}
+
+/// Manhattan plot points from the p-values under INFO `p_key`
+///
+/// With `already_log10` the value is taken as −log10(p) (e.g. `LOG10P`),
+/// otherwise as p, which must lie in 0..=1. p = 0 and anything above
+/// `max_neg_log10_p` are capped at it. Chromosomes follow the header's
+/// `##contig` order, then order of first appearance, and each takes its
+/// declared length on the axis (or up to its last point when the length
+/// is unknown or too short).
+pub fn manhattan_data_with_max(
+    records: &[VcfRecord],
+    p_key: &str,
+    already_log10: bool,
+    header: Option<&VcfHeader>,
+    max_neg_log10_p: f64,
+) -> ManhattanData {
+    let mut data = ManhattanData::default();
+
+    // Points against chromosomes in order of first appearance
+    let mut seen: Vec<(&str, u64)> = Vec::new();
+    let mut seen_index: HashMap<&str, usize> = HashMap::new();
+    let mut points: Vec<(usize, u64)> = Vec::new();
+    for record in records {
+        let Some(value) = record.info.get(p_key).and_then(info_number) else {
+            data.skipped += 1;
+            continue;
+        };
+        let y = if already_log10 {
+            value
+        } else if (0.0..=1.0).contains(&value) {
+            if value == 0.0 {
+                f64::INFINITY
+            } else {
+                -value.log10()
+            }
+        } else {
+            f64::NAN
+        };
+        if y.is_nan() {
+            data.skipped += 1;
+            continue;
+        }
+        if y > max_neg_log10_p {
+            data.clamped += 1;
+        }
+        data.neg_log10_p.push(y.min(max_neg_log10_p));
+
+        let chrom = *seen_index.entry(&record.chrom).or_insert_with(|| {
+            seen.push((&record.chrom, 0));
+            seen.len() - 1
+        });
+        seen[chrom].1 = seen[chrom].1.max(record.pos);
+        points.push((chrom, record.pos));
+    }
+
+    let contigs = header.map(|h| h.contigs.as_slice()).unwrap_or_default();
+    let contig_rank: HashMap<&str, usize> = contigs
+        .iter()
+        .enumerate()
+        .map(|(i, contig)| (contig.id.as_str(), i))
+        .collect();
+    let mut order: Vec<usize> = (0..seen.len()).collect();
+    order.sort_by_key(|&i| (contig_rank.get(seen[i].0).copied().unwrap_or(usize::MAX), i));
+
+    let mut plot_index = vec![0u32; seen.len()];
+    let mut offset = 0;
+    for (plotted, &i) in order.iter().enumerate() {
+        let (chrom, last_pos) = seen[i];
+        let declared = contig_rank
+            .get(chrom)
+            .and_then(|&rank| contigs[rank].length)
+            .unwrap_or(0);
+        let length = declared.max(last_pos);
+
+        plot_index[i] = plotted as u32;
+        data.chromosomes.push(chrom.to_string());
+        data.offsets.push(offset);
+        data.ticks.push(offset as f64 + length as f64 / 2.0);
+        offset += length;
+    }
+
+    for (chrom, pos) in points {
+        let plotted = plot_index[chrom];
+        data.positions.push(data.offsets[plotted as usize] + pos);
+        data.chrom_index.push(plotted);
+    }
+    data
+}
+
+/// A numeric INFO value, or the first of an array
+fn info_number(value: &InfoValue) -> Option<f64> {
+    match value {
+        InfoValue::Float(v) => Some(*v),
+        InfoValue::Integer(v) => Some(*v as f64),
+        InfoValue::FloatArray(v) => v.first().copied(),
+        InfoValue::IntegerArray(v) => v.first().map(|&v| v as f64),
+        InfoValue::String(v) => v.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    // chr2 comes first in the header; chrX has no ##contig line
+    const GWAS_VCF: &str = "##fileformat=VCFv4.2
+##contig=<ID=chr2,length=1000>
+##contig=<ID=chr1,length=2000>
+##INFO=<ID=P,Number=1,Type=Float,Description=\"P-value\">
+##INFO=<ID=LOG10P,Number=1,Type=Float,Description=\"-log10 P-value\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\t.\tA\tG\t.\tPASS\tP=0.01;LOG10P=2
+chr1\t1500\t.\tC\tT\t.\tPASS\tP=0;LOG10P=400
+chrX\t50\t.\tG\tA\t.\tPASS\tP=1;LOG10P=0
+chr2\t10\t.\tT\tC\t.\tPASS\tP=1e-8;LOG10P=8
+chr2\t20\t.\tT\tC\t.\tPASS\t.
+chrX\t300\t.\tG\tA\t.\tPASS\tP=2;LOG10P=0
+";
+
+    #[test]
+    fn test_manhattan_offsets() {
+        let (header, records) = VcfParser::new().parse_str(GWAS_VCF).unwrap();
+
+        let data = manhattan_data(&records, "P", false, Some(&header));
+        assert_eq!(data.chromosomes, ["chr2", "chr1", "chrX"]);
+        // chr2 and chr1 take their declared lengths; chrX runs to its
+        // last point
+        assert_eq!(data.offsets, [0, 1000, 3000]);
+        assert_eq!(data.ticks, [500.0, 2000.0, 3025.0]);
+        assert_eq!(data.positions, [1100, 2500, 3050, 10]);
+        assert_eq!(data.chrom_index, [1, 1, 2, 0]);
+        // No P at chr2:20, and P=2 is not a p-value
+        assert_eq!(data.skipped, 2);
+
+        let without_header = manhattan_data(&records, "P", false, None);
+        assert_eq!(without_header.chromosomes, ["chr1", "chrX", "chr2"]);
+        assert_eq!(without_header.offsets, [0, 1500, 1550]);
+        assert_eq!(without_header.positions, [100, 1500, 1550, 1560]);
+    }
+
+    #[test]
+    fn test_manhattan_zero_p_clamp() {
+        let (header, records) = VcfParser::new().parse_str(GWAS_VCF).unwrap();
+
+        let data = manhattan_data(&records, "P", false, Some(&header));
+        assert!((data.neg_log10_p[0] - 2.0).abs() < 1e-12);
+        assert_eq!(data.neg_log10_p[1], DEFAULT_MAX_NEG_LOG10_P);
+        assert_eq!(data.neg_log10_p[2], 0.0);
+        assert!((data.neg_log10_p[3] - 8.0).abs() < 1e-12);
+        assert_eq!(data.clamped, 1);
+
+        let data = manhattan_data_with_max(&records, "P", false, Some(&header), 5.0);
+        assert_eq!(data.neg_log10_p[1], 5.0);
+        assert_eq!(data.neg_log10_p[3], 5.0);
+        assert_eq!(data.clamped, 2);
+
+        let log10 = manhattan_data(&records, "LOG10P", true, Some(&header));
+        assert_eq!(log10.neg_log10_p, [2.0, 300.0, 0.0, 8.0, 0.0]);
+        assert_eq!((log10.skipped, log10.clamped), (1, 1));
+    }
+}
@@ -0,0 +1,223 @@
+//! Parse Metrics
+//!
+//! Timing and throughput collected by the parser when the `tracing`
+//! feature is enabled, alongside the spans and events it emits. Without
+//! the feature none of this is compiled and the parser does no extra work.
+
+use std::time::Duration;
+
+/// Records between sampled `parse_record` spans and progress events
+pub(crate) const RECORD_SAMPLE_INTERVAL: usize = 10_000;
+
+/// Counts and wall time for one parse
+///
+/// Filled in by [`VcfParser::parse`](crate::VcfParser::parse),
+/// [`VcfParser::read_header`](crate::VcfParser::read_header) and
+/// [`VcfIterator`](crate::parser::VcfIterator). A
+/// [`VcfPushParser`](crate::VcfPushParser) only updates the counts, since
+/// it cannot tell when its input ends.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseMetrics {
+    /// Time spent reading the header
+    pub header_time: Duration,
+    /// Time spent reading records, from the end of the header
+    pub records_time: Duration,
+    /// Header lines read, including `#CHROM`
+    pub header_lines: usize,
+    /// Record lines read, including invalid ones that were skipped
+    pub record_lines: usize,
+    /// Records parsed successfully
+    pub records: usize,
+    /// Bytes of (decompressed) input read, counting one per line terminator
+    pub bytes: u64,
+    /// Warnings generated, including ones beyond `max_warnings`
+    pub warnings: usize,
+}
+
+impl ParseMetrics {
+    /// Header and record time together
+    pub fn total_time(&self) -> Duration {
+        self.header_time + self.records_time
+    }
+
+    /// Records parsed per second of record time; 0 if no time was measured
+    pub fn records_per_sec(&self) -> f64 {
+        per_sec(self.records as f64, self.records_time)
+    }
+
+    /// Input bytes read per second of total time; 0 if no time was measured
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.bytes as f64, self.total_time())
+    }
+
+    /// Count one line of input
+    pub(crate) fn add_line(&mut self, line: &str) {
+        self.bytes += line.len() as u64 + 1;
+    }
+
+    /// Emit the summary event for a finished parse
+    pub(crate) fn report(&self) {
+        tracing::info!(
+            records = self.records,
+            record_lines = self.record_lines,
+            bytes = self.bytes,
+            warnings = self.warnings,
+            header_ms = self.header_time.as_secs_f64() * 1000.0,
+            records_ms = self.records_time.as_secs_f64() * 1000.0,
+            records_per_sec = self.records_per_sec(),
+            "parsed VCF"
+        );
+    }
+}
+
+fn per_sec(count: f64, time: Duration) -> f64 {
+    let secs = time.as_secs_f64();
+    if secs > 0.0 {
+        count / secs
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{VcfIterator, VcfParser};
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::Layer;
+
+    /// Layer recording span names, event messages and recorded span fields
+    #[derive(Clone, Default)]
+    struct Capture {
+        spans: Arc<Mutex<Vec<String>>>,
+        events: Arc<Mutex<Vec<String>>>,
+        recorded: Arc<Mutex<Vec<(String, String, u64)>>>,
+    }
+
+    struct Fields<'a> {
+        values: Vec<(&'a str, String)>,
+    }
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.values.push((field.name(), format!("{:?}", value)));
+        }
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, _: &Id, _: Context<'_, S>) {
+            self.spans.lock().unwrap().push(attrs.metadata().name().to_string());
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let name = ctx.span(id).unwrap().name().to_string();
+            let mut fields = Fields { values: Vec::new() };
+            values.record(&mut fields);
+            for (field, value) in fields.values {
+                self.recorded.lock().unwrap().push((
+                    name.clone(),
+                    field.to_string(),
+                    value.parse().unwrap(),
+                ));
+            }
+        }
+
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            let mut fields = Fields { values: Vec::new() };
+            event.record(&mut fields);
+            if let Some((_, message)) = fields.values.iter().find(|(name, _)| *name == "message") {
+                self.events.lock().unwrap().push(message.clone());
+            }
+        }
+    }
+
+    impl Capture {
+        fn count(list: &Mutex<Vec<String>>, name: &str) -> usize {
+            list.lock().unwrap().iter().filter(|n| *n == name).count()
+        }
+    }
+
+    const HEADER: &str = "##fileformat=VCFv4.2\n\
+                          ##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">\n\
+                          #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n";
+
+    /// Header plus `n` records and one invalid line
+    fn input(n: usize) -> String {
+        let mut vcf = HEADER.to_string();
+        for i in 0..n {
+            vcf.push_str(&format!("chr1\t{}\t.\tA\tG\t30\tPASS\tDP={}\n", i + 1, i % 50));
+        }
+        vcf.push_str("chr1\tnot_a_number\t.\tA\tG\t30\tPASS\t.\n");
+        vcf
+    }
+
+    #[test]
+    fn test_parse_spans_and_metrics() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let vcf = input(25_000);
+
+        let mut parser = VcfParser::new();
+        parser.skip_invalid = true;
+        tracing::subscriber::with_default(subscriber, || parser.parse_str(&vcf).unwrap());
+
+        assert_eq!(Capture::count(&capture.spans, "parse"), 1);
+        assert_eq!(Capture::count(&capture.spans, "parse_header"), 1);
+        // Lines 1, 10 001 and 20 001 are sampled
+        assert_eq!(Capture::count(&capture.spans, "parse_record"), 3);
+        assert_eq!(Capture::count(&capture.events, "parse progress"), 3);
+        assert_eq!(Capture::count(&capture.events, "parsed header"), 1);
+        assert_eq!(Capture::count(&capture.events, "parsed VCF"), 1);
+
+        let metrics = parser.metrics();
+        assert_eq!(metrics.header_lines, 3);
+        assert_eq!(metrics.record_lines, 25_001);
+        assert_eq!(metrics.records, 25_000);
+        assert_eq!(metrics.warnings, 1);
+        assert_eq!(metrics.bytes, vcf.len() as u64);
+        assert!(metrics.records_per_sec() > 0.0);
+        assert_eq!(metrics.total_time(), metrics.header_time + metrics.records_time);
+
+        let recorded = capture.recorded.lock().unwrap();
+        assert!(recorded.contains(&("parse".to_string(), "records".to_string(), 25_000)));
+        assert!(recorded.contains(&("parse".to_string(), "bytes".to_string(), vcf.len() as u64)));
+    }
+
+    #[test]
+    fn test_iterator_spans_and_metrics() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let vcf = input(100);
+
+        let (count, metrics) = tracing::subscriber::with_default(subscriber, || {
+            let mut records = VcfIterator::new(vcf.as_bytes()).unwrap();
+            let count = records.by_ref().filter(|r| r.is_ok()).count();
+            (count, records.metrics())
+        });
+
+        assert_eq!(count, 100);
+        assert_eq!(Capture::count(&capture.spans, "iterate"), 1);
+        assert_eq!(Capture::count(&capture.spans, "parse_header"), 1);
+        assert_eq!(Capture::count(&capture.spans, "parse_record"), 1);
+        assert_eq!(Capture::count(&capture.events, "parsed VCF"), 1);
+        assert_eq!(metrics.records, 100);
+        assert_eq!(metrics.record_lines, 101);
+        assert_eq!(metrics.bytes, vcf.len() as u64);
+    }
+
+    #[test]
+    fn test_metrics_reset_between_parses() {
+        let mut parser = VcfParser::new();
+        parser.skip_invalid = true;
+        parser.parse_str(&input(10)).unwrap();
+        parser.parse_str(&input(3)).unwrap();
+
+        assert_eq!(parser.metrics().records, 3);
+        assert_eq!(parser.metrics().bytes, input(3).len() as u64);
+    }
+}
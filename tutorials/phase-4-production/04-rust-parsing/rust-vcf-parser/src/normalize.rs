@@ -0,0 +1,464 @@
+//! Multi-allelic Records
+//!
+//! Turns a record with several ALT alleles into one biallelic record per
+//! allele, as `bcftools norm -m-` does, or reorders its ALT alleles,
+//! keeping per-allele and per-genotype fields in step either way.
+
+use crate::error::{VcfError, VcfResult};
+use crate::number::{expected_value_count, genotype_index};
+use crate::types::*;
+use std::collections::HashMap;
+
+/// One biallelic record per ALT allele of `record`
+///
+/// Each copy keeps CHROM, POS, ID, REF, QUAL and FILTER. Fields are
+/// subset by their header `Number`: `A` keeps the allele's value, `R`
+/// REF's and the allele's, and `G` the values of the genotypes made of
+/// REF and the allele (for a diploid triallelic PL, entries 0, 1, 2 for
+/// the first ALT and 0, 3, 5 for the second). INFO `G` fields are taken
+/// as diploid; FORMAT ones use the sample's GT ploidy. In GT the split
+/// allele becomes 1 and every other ALT allele 0, keeping phase and
+/// order, so `1/2` becomes `1/0` and `0/1`. Fields that are undeclared,
+/// missing (`.`) or hold an unexpected number of values are copied
+/// unchanged. A record with at most one ALT allele is returned as is.
+pub fn split_multiallelic(record: &VcfRecord, header: &VcfHeader) -> Vec<VcfRecord> {
+    let n_alts = record.alternate.len();
+    if n_alts <= 1 {
+        return vec![record.clone()];
+    }
+
+    let info_numbers: HashMap<&str, &str> = header
+        .info_fields
+        .iter()
+        .map(|f| (f.id.as_str(), f.number.as_str()))
+        .collect();
+    let format_numbers: HashMap<&str, &str> = header
+        .format_fields
+        .iter()
+        .map(|f| (f.id.as_str(), f.number.as_str()))
+        .collect();
+
+    (1..=n_alts)
+        .map(|allele| {
+            let mut split = record.clone();
+            split.alternate = vec![record.alternate[allele - 1].clone()];
+
+            for (key, value) in split.info.iter_mut() {
+                if let Some(number) = info_numbers.get(&**key) {
+                    *value = subset_info(value, number, n_alts, allele);
+                }
+            }
+
+            for sample in &mut split.samples {
+                let ploidy = sample.genotype.as_ref().map_or(2, |gt| gt.alleles.len());
+                for (key, value) in sample.fields.iter_mut() {
+                    if let Some(number) = format_numbers.get(key.as_str()) {
+                        if let Some(subset) = subset_values(value, number, n_alts, ploidy, allele) {
+                            *value = subset;
+                        }
+                    }
+                }
+                if let Some(gt) = &mut sample.genotype {
+                    for a in gt.alleles.iter_mut().flatten() {
+                        *a = u8::from(usize::from(*a) == allele);
+                    }
+                }
+            }
+
+            split
+        })
+        .collect()
+}
+
+/// Indices into a field's values that the record for `allele` keeps
+fn kept_indices(number: &str, ploidy: usize, allele: usize) -> Option<Vec<usize>> {
+    match number {
+        "A" => Some(vec![allele - 1]),
+        "R" => Some(vec![0, allele]),
+        // Genotypes with k copies of the allele and the rest REF, in the
+        // biallelic G order 0/0, 0/1, 1/1
+        "G" => Some(
+            (0..=ploidy)
+                .map(|k| {
+                    let mut alleles = vec![0; ploidy - k];
+                    alleles.resize(ploidy, allele);
+                    genotype_index(&alleles)
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// Comma-separated FORMAT `value` subset for `allele`, or `None` to keep it
+fn subset_values(
+    value: &str,
+    number: &str,
+    n_alts: usize,
+    ploidy: usize,
+    allele: usize,
+) -> Option<String> {
+    let indices = kept_indices(number, ploidy, allele)?;
+    let values: Vec<&str> = value.split(',').collect();
+    if value == "." || Some(values.len()) != expected_value_count(number, n_alts, ploidy) {
+        return None;
+    }
+    let kept: Vec<&str> = indices.iter().map(|&i| values[i]).collect();
+    Some(kept.join(","))
+}
+
+/// INFO `value` subset for `allele`, as the parser would type the result
+fn subset_info(value: &InfoValue, number: &str, n_alts: usize, allele: usize) -> InfoValue {
+    let Some(indices) = kept_indices(number, 2, allele) else {
+        return value.clone();
+    };
+    let expected = expected_value_count(number, n_alts, 2);
+
+    fn pick<T: Clone>(values: &[T], indices: &[usize]) -> Vec<T> {
+        indices.iter().map(|&i| values[i].clone()).collect()
+    }
+    match value {
+        InfoValue::IntegerArray(values) if Some(values.len()) == expected => {
+            match pick(values, &indices).as_slice() {
+                [single] => InfoValue::Integer(*single),
+                kept => InfoValue::IntegerArray(kept.to_vec()),
+            }
+        }
+        InfoValue::FloatArray(values) if Some(values.len()) == expected => {
+            match pick(values, &indices).as_slice() {
+                [single] => InfoValue::Float(*single),
+                kept => InfoValue::FloatArray(kept.to_vec()),
+            }
+        }
+        InfoValue::StringArray(values) if Some(values.len()) == expected => {
+            match pick(values, &indices).as_slice() {
+                [single] => InfoValue::String(single.clone()),
+                kept => InfoValue::StringArray(kept.to_vec()),
+            }
+        }
+        value => value.clone(),
+    }
+}
+
+impl VcfRecord {
+    /// Reorder the ALT alleles so that the `i`th is the one that was at
+    /// `order[i]` (0-based), remapping everything that refers to them
+    ///
+    /// GT allele indices are renumbered, keeping phase and allele order,
+    /// and fields declared in the header with `Number=A`, `R` or `G` are
+    /// permuted: a `1/2` genotype's PL value moves with it to wherever
+    /// `1/2` now sits. INFO `G` fields are taken as diploid; FORMAT ones
+    /// use the sample's GT ploidy, or 2 without a call. Undeclared and
+    /// fixed-count fields, and missing (`.`) values, are left alone.
+    ///
+    /// Fails without changing the record if `order` is not a permutation
+    /// of the ALT indices, a GT refers to an allele the record does not
+    /// have, or a field to permute holds the wrong number of values.
+    pub fn reorder_alternates(&mut self, order: &[usize], header: &VcfHeader) -> VcfResult<()> {
+        let n_alts = self.alternate.len();
+        let mut seen = vec![false; n_alts];
+        for &old in order {
+            if old >= n_alts || std::mem::replace(&mut seen[old], true) {
+                break;
+            }
+        }
+        if order.len() != n_alts || seen.contains(&false) {
+            return Err(VcfError::InvalidFormat(format!(
+                "{:?} is not an order of the {} ALT alleles at {}:{}",
+                order, n_alts, self.chrom, self.pos
+            )));
+        }
+
+        // New allele number of each old one, REF staying 0
+        let mut renumber = vec![0; n_alts + 1];
+        for (new, &old) in order.iter().enumerate() {
+            renumber[old + 1] = new + 1;
+        }
+
+        let mut reordered = self.clone();
+        reordered.alternate = order
+            .iter()
+            .map(|&old| self.alternate[old].clone())
+            .collect();
+
+        for (key, value) in reordered.info.iter_mut() {
+            let Some(number) = header
+                .info_fields
+                .iter()
+                .find(|f| f.id == **key)
+                .map(|f| &f.number)
+            else {
+                continue;
+            };
+            let Some(sources) = source_indices(number, n_alts, 2, order) else {
+                continue;
+            };
+            let permuted = match &*value {
+                InfoValue::IntegerArray(values) => {
+                    permute(values, &sources).map(InfoValue::IntegerArray)
+                }
+                InfoValue::FloatArray(values) => {
+                    permute(values, &sources).map(InfoValue::FloatArray)
+                }
+                InfoValue::StringArray(values) => {
+                    permute(values, &sources).map(InfoValue::StringArray)
+                }
+                InfoValue::String(text) if text == "." => continue,
+                // A lone value has nowhere to move
+                _ if sources.len() == 1 => continue,
+                _ => None,
+            };
+            *value = permuted
+                .ok_or_else(|| self.count_error(&format!("INFO {}", key), number, sources.len()))?;
+        }
+
+        for sample in &mut reordered.samples {
+            let ploidy = sample.genotype.as_ref().map_or(2, |gt| gt.alleles.len());
+            for (key, value) in sample.fields.iter_mut() {
+                let Some(number) = header
+                    .format_fields
+                    .iter()
+                    .find(|f| f.id == *key)
+                    .map(|f| &f.number)
+                else {
+                    continue;
+                };
+                let Some(sources) = source_indices(number, n_alts, ploidy, order) else {
+                    continue;
+                };
+                if value == "." {
+                    continue;
+                }
+                let values: Vec<&str> = value.split(',').collect();
+                let permuted = permute(&values, &sources).ok_or_else(|| {
+                    self.count_error(
+                        &format!("FORMAT {} of {}", key, sample.name),
+                        number,
+                        sources.len(),
+                    )
+                })?;
+                *value = permuted.join(",");
+            }
+
+            if let Some(gt) = &mut sample.genotype {
+                for allele in gt.alleles.iter_mut().flatten() {
+                    let Some(&new) = renumber.get(usize::from(*allele)) else {
+                        return Err(VcfError::InvalidFormat(format!(
+                            "GT of {} at {}:{} refers to allele {} but there are {} ALT alleles",
+                            sample.name, self.chrom, self.pos, allele, n_alts
+                        )));
+                    };
+                    *allele = new as u8;
+                }
+            }
+        }
+
+        *self = reordered;
+        Ok(())
+    }
+
+    /// Sort the ALT alleles by their text, with [`reorder_alternates`]
+    /// keeping GT and per-allele fields in step, so that the same alleles
+    /// always come in the same order
+    ///
+    /// [`reorder_alternates`]: VcfRecord::reorder_alternates
+    pub fn canonicalize_alt_order(&mut self, header: &VcfHeader) -> VcfResult<()> {
+        let mut order: Vec<usize> = (0..self.alternate.len()).collect();
+        order.sort_by(|&a, &b| self.alternate[a].cmp(&self.alternate[b]));
+        self.reorder_alternates(&order, header)
+    }
+
+    fn count_error(&self, field: &str, number: &str, expected: usize) -> VcfError {
+        VcfError::InvalidFormat(format!(
+            "{} at {}:{} is Number={} but does not hold {} values",
+            field, self.chrom, self.pos, number, expected
+        ))
+    }
+}
+
+/// For each position of a field's values after reordering, the position
+/// the value comes from; `None` for fields that do not follow the alleles
+fn source_indices(
+    number: &str,
+    n_alts: usize,
+    ploidy: usize,
+    order: &[usize],
+) -> Option<Vec<usize>> {
+    let old_allele = |new: usize| if new == 0 { 0 } else { order[new - 1] + 1 };
+    match number {
+        "A" => Some(order.to_vec()),
+        "R" => Some((0..=n_alts).map(old_allele).collect()),
+        "G" => {
+            let mut sources = vec![0; expected_value_count(number, n_alts, ploidy)?];
+            for genotype in genotypes(n_alts + 1, ploidy) {
+                let old: Vec<usize> = genotype.iter().map(|&a| old_allele(a)).collect();
+                sources[genotype_index(&genotype)] = genotype_index(&old);
+            }
+            Some(sources)
+        }
+        _ => None,
+    }
+}
+
+/// Every unordered genotype of `ploidy` alleles out of `n_alleles`, as
+/// sorted allele lists
+fn genotypes(n_alleles: usize, ploidy: usize) -> Vec<Vec<usize>> {
+    let mut genotypes = vec![Vec::new()];
+    for _ in 0..ploidy {
+        genotypes = genotypes
+            .into_iter()
+            .flat_map(|genotype: Vec<usize>| {
+                let lowest = genotype.last().copied().unwrap_or(0);
+                (lowest..n_alleles).map(move |allele| {
+                    let mut longer = genotype.clone();
+                    longer.push(allele);
+                    longer
+                })
+            })
+            .collect();
+    }
+    genotypes
+}
+
+/// `values` rearranged by `sources`, or `None` if there are not exactly
+/// as many values as sources
+fn permute<T: Clone>(values: &[T], sources: &[usize]) -> Option<Vec<T>> {
+    (values.len() == sources.len()).then(|| sources.iter().map(|&i| values[i].clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+    use crate::writer::to_vcf_string;
+
+    const TRIALLELIC: &str = "##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total Depth\">
+##INFO=<ID=AC,Number=A,Type=Integer,Description=\"Allele count\">
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele frequency\">
+##INFO=<ID=RD,Number=R,Type=Integer,Description=\"Reads per allele\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read depth\">
+##FORMAT=<ID=PL,Number=G,Type=Integer,Description=\"Phred-scaled genotype likelihoods\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3
+chr1\t100\trs1\tA\tC,G\t50\tPASS\tDP=30;AC=2,1;AF=0.4,0.2;RD=10,12,8\tGT:AD:DP:PL\t1/2:0,6,4:10:120,50,80,60,0,90\t0|1:5,5,0:10:40,0,70,55,65,99\t./.:.:.:.
+";
+
+    // `bcftools norm -m- -Ov` on TRIALLELIC
+    const BCFTOOLS_SPLIT: [&str; 2] = [
+        "chr1\t100\trs1\tA\tC\t50\tPASS\tDP=30;AC=2;AF=0.4;RD=10,12\tGT:AD:DP:PL\t1/0:0,6:10:120,50,80\t0|1:5,5:10:40,0,70\t./.:.:.:.",
+        "chr1\t100\trs1\tA\tG\t50\tPASS\tDP=30;AC=1;AF=0.2;RD=10,8\tGT:AD:DP:PL\t0/1:0,4:10:120,60,90\t0|0:5,0:10:40,55,99\t./.:.:.:.",
+    ];
+
+    #[test]
+    fn test_split_triallelic_matches_bcftools_norm() {
+        let (header, records) = VcfParser::new().parse_str(TRIALLELIC).unwrap();
+        let split = split_multiallelic(&records[0], &header);
+        assert_eq!(split.len(), 2);
+
+        let text = to_vcf_string(&header, &split, true).unwrap();
+        let lines: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+        // The writer prints a fully missing genotype as a single `.`
+        let expected: Vec<String> = BCFTOOLS_SPLIT
+            .iter()
+            .map(|line| line.replace("\t./.:", "\t.:"))
+            .collect();
+        assert_eq!(lines, expected);
+
+        assert!(matches!(split[0].info["AC"], InfoValue::Integer(2)));
+        assert!(matches!(split[1].info["AF"], InfoValue::Float(f) if f == 0.2));
+    }
+
+    #[test]
+    fn test_split_leaves_unexpected_counts_and_biallelic_records() {
+        let vcf = TRIALLELIC
+            .replace("120,50,80,60,0,90", "1,2,3")
+            .replace("AC=2,1", "AC=2,1,7");
+        let (header, records) = VcfParser::new().parse_str(&vcf).unwrap();
+        let split = split_multiallelic(&records[0], &header);
+        assert_eq!(split[1].samples[0].fields["PL"], "1,2,3");
+        assert!(matches!(&split[1].info["AC"], InfoValue::IntegerArray(ac) if ac.len() == 3));
+
+        // Haploid PL is Number=G with one value per allele
+        let vcf = TRIALLELIC.replace("1/2:0,6,4:10:120,50,80,60,0,90", "2:0,6,4:10:90,80,0");
+        let (header, records) = VcfParser::new().parse_str(&vcf).unwrap();
+        let split = split_multiallelic(&records[0], &header);
+        assert_eq!(split[0].samples[0].fields["PL"], "90,80");
+        assert_eq!(split[1].samples[0].fields["PL"], "90,0");
+
+        let biallelic = VcfRecord::new("chr1", 5, "A", vec!["T"]);
+        assert_eq!(split_multiallelic(&biallelic, &header).len(), 1);
+    }
+
+    fn data_lines(header: &VcfHeader, records: &[VcfRecord]) -> Vec<String> {
+        let text = to_vcf_string(header, records, true).unwrap();
+        text.lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_swap_alternates_remaps_every_field() {
+        let (header, records) = VcfParser::new().parse_str(TRIALLELIC).unwrap();
+        let mut record = records[0].clone();
+        record.reorder_alternates(&[1, 0], &header).unwrap();
+
+        assert_eq!(record.alternate, ["G", "C"]);
+        assert!(matches!(&record.info["AC"], InfoValue::IntegerArray(ac) if ac == &[1, 2]));
+        assert!(matches!(&record.info["AF"], InfoValue::FloatArray(af) if af == &[0.2, 0.4]));
+        assert!(matches!(&record.info["RD"], InfoValue::IntegerArray(rd) if rd == &[10, 8, 12]));
+        assert!(matches!(record.info["DP"], InfoValue::Integer(30)));
+
+        let s1 = &record.samples[0];
+        assert_eq!(s1.genotype.as_ref().unwrap().alleles, [Some(2), Some(1)]);
+        assert_eq!(s1.fields["AD"], "0,4,6");
+        // 0/0, 0/1, 1/1, 0/2, 1/2, 2/2 were 0/0, 0/2, 2/2, 0/1, 2/1, 1/1
+        assert_eq!(s1.fields["PL"], "120,60,90,50,0,80");
+        assert_eq!(s1.fields["DP"], "10");
+
+        let s2 = &record.samples[1];
+        assert_eq!(s2.genotype.as_ref().unwrap().alleles, [Some(0), Some(2)]);
+        assert!(s2.genotype.as_ref().unwrap().phased);
+        assert_eq!(s2.fields["AD"], "5,0,5");
+        assert_eq!(s2.fields["PL"], "40,55,99,0,65,70");
+        assert_eq!(record.samples[2].fields["PL"], ".");
+
+        // Sorting puts C before G again, giving back the original record
+        record.canonicalize_alt_order(&header).unwrap();
+        assert_eq!(
+            data_lines(&header, &[record]),
+            data_lines(&header, &records)
+        );
+    }
+
+    #[test]
+    fn test_reorder_rejects_bad_orders_and_counts() {
+        let (header, records) = VcfParser::new().parse_str(TRIALLELIC).unwrap();
+        for order in [&[0, 0][..], &[1], &[0, 2], &[2, 1, 0]] {
+            let mut record = records[0].clone();
+            assert!(
+                record.reorder_alternates(order, &header).is_err(),
+                "{:?}",
+                order
+            );
+        }
+
+        let short_pl = TRIALLELIC.replace("120,50,80,60,0,90", "1,2,3");
+        let (header, records) = VcfParser::new().parse_str(&short_pl).unwrap();
+        let mut record = records[0].clone();
+        let err = record.reorder_alternates(&[1, 0], &header).unwrap_err();
+        assert!(err.to_string().contains("FORMAT PL of S1"), "{}", err);
+        // Nothing was changed
+        assert_eq!(record.alternate, ["C", "G"]);
+        assert_eq!(record.samples[0].fields["AD"], "0,6,4");
+
+        let bad_gt = TRIALLELIC.replace("\t1/2:", "\t1/3:");
+        let (header, mut records) = VcfParser::new().parse_str(&bad_gt).unwrap();
+        assert!(records[0].reorder_alternates(&[1, 0], &header).is_err());
+        let short_af = TRIALLELIC.replace("AF=0.4,0.2", "AF=0.4");
+        let (header, mut records) = VcfParser::new().parse_str(&short_af).unwrap();
+        assert!(records[0].reorder_alternates(&[1, 0], &header).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+//! Field Value Counts
+//!
+//! How many values an INFO or FORMAT field should hold, from the `Number`
+//! of its header definition, and where each genotype's value sits in a
+//! `Number=G` list.
+
+/// Values a field declared with `number` holds at a site with `n_alts`
+/// ALT alleles, for a sample of the given ploidy
+///
+/// `A` is one per ALT allele, `R` one per allele including REF, and `G`
+/// one per unordered genotype, C(alleles + ploidy - 1, ploidy): 3 for a
+/// diploid biallelic site, 6 for a diploid triallelic one. Fixed counts
+/// are returned as they are. `.` (unknown) and anything unrecognised give
+/// `None`.
+pub fn expected_value_count(number: &str, n_alts: usize, ploidy: usize) -> Option<usize> {
+    match number {
+        "A" => Some(n_alts),
+        "R" => Some(n_alts + 1),
+        "G" => Some(binomial(n_alts + ploidy, ploidy)),
+        _ => number.parse().ok(),
+    }
+}
+
+/// Position of a genotype in a `Number=G` list, for any ploidy
+///
+/// Genotypes are ordered as the VCF spec lays them out: for a diploid,
+/// `0/0, 0/1, 1/1, 0/2, 1/2, 2/2, ...`, so `j/k` with `j <= k` is at
+/// `k(k+1)/2 + j`. The allele order in `alleles` does not matter.
+pub fn genotype_index(alleles: &[usize]) -> usize {
+    let mut sorted = alleles.to_vec();
+    sorted.sort_unstable();
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &allele)| binomial(allele + i, i + 1))
+        .sum()
+}
+
+/// n choose k
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_value_count() {
+        assert_eq!(expected_value_count("A", 2, 2), Some(2));
+        assert_eq!(expected_value_count("R", 2, 2), Some(3));
+        assert_eq!(expected_value_count("G", 1, 2), Some(3));
+        assert_eq!(expected_value_count("G", 2, 2), Some(6));
+        assert_eq!(expected_value_count("G", 3, 2), Some(10));
+        assert_eq!(expected_value_count("G", 2, 1), Some(3));
+        assert_eq!(expected_value_count("G", 1, 3), Some(4));
+        assert_eq!(expected_value_count("1", 5, 2), Some(1));
+        assert_eq!(expected_value_count("0", 1, 2), Some(0));
+        assert_eq!(expected_value_count(".", 1, 2), None);
+        assert_eq!(expected_value_count("X", 1, 2), None);
+    }
+
+    #[test]
+    fn test_genotype_index_follows_spec_order() {
+        let diploid: Vec<usize> = [[0, 0], [0, 1], [1, 1], [0, 2], [1, 2], [2, 2]]
+            .iter()
+            .map(|gt| genotype_index(gt))
+            .collect();
+        assert_eq!(diploid, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(genotype_index(&[2, 1]), 4);
+        assert_eq!(genotype_index(&[3]), 3);
+
+        // Triploid biallelic: 0/0/0, 0/0/1, 0/1/1, 1/1/1, then 0/0/2
+        let triploid: Vec<usize> = [[0, 0, 0], [0, 0, 1], [0, 1, 1], [1, 1, 1], [0, 0, 2]]
+            .iter()
+            .map(|gt| genotype_index(gt))
+            .collect();
+        assert_eq!(triploid, [0, 1, 2, 3, 4]);
+    }
+}
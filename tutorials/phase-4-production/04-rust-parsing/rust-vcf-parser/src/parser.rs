@@ -1,12 +1,27 @@
 //! VCF Parser Implementation
 //! 
 //! High-performance VCF file parser with streaming support
+//!
+//! Input may come from untrusted uploads, so nothing here indexes or
+//! slices by position: malformed input must surface as a [`VcfError`],
+//! never a panic. The `fuzz/` crate exercises this.
+
+#![deny(clippy::indexing_slicing)]
 
 use crate::error::{ParseWarning, VcfError, VcfResult, WarningCategory};
+#[cfg(feature = "tracing")]
+use crate::metrics::{ParseMetrics, RECORD_SAMPLE_INTERVAL};
 use crate::types::*;
 use memchr::memchr;
-use std::collections::HashMap;
+use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader, Read};
+use std::sync::Arc;
+#[cfg(feature = "tracing")]
+use std::time::{Duration, Instant};
+
+/// Most distinct INFO keys a parser shares between records
+const MAX_INTERNED_INFO_KEYS: usize = 1024;
 
 /// VCF Parser with configurable options
 pub struct VcfParser {
@@ -22,11 +37,34 @@ pub struct VcfParser {
     /// Collect warnings during parsing
     pub collect_warnings: bool,
     
+    /// Maximum number of warnings kept; later ones are only counted
+    pub max_warnings: Option<usize>,
+    
+    /// Fail once more than this many invalid records have been skipped
+    pub max_invalid_records: Option<usize>,
+    
+    /// Keep the original text of QUAL and float INFO values in
+    /// [`VcfRecord::float_text`], for writers that reproduce it
+    pub keep_float_text: bool,
+    
+    /// Invalid records skipped so far
+    invalid_records: usize,
+    
     /// Warnings collected during parsing
     warnings: Vec<ParseWarning>,
     
+    /// Number of warnings per category, including ones beyond `max_warnings`
+    warning_counts: HashMap<WarningCategory, usize>,
+    
     /// Current line number for error reporting
     current_line: usize,
+
+    /// INFO keys seen so far, shared between records
+    info_keys: HashSet<InfoKey, InfoHasher>,
+
+    /// Timing and counts for the current parse
+    #[cfg(feature = "tracing")]
+    metrics: ParseMetrics,
 }
 
 impl Default for VcfParser {
@@ -43,8 +81,16 @@ impl VcfParser {
             parse_samples: true,
             skip_invalid: false,
             collect_warnings: true,
+            max_warnings: None,
+            max_invalid_records: None,
+            keep_float_text: false,
+            invalid_records: 0,
             warnings: Vec::new(),
+            warning_counts: HashMap::new(),
             current_line: 0,
+            info_keys: HashSet::default(),
+            #[cfg(feature = "tracing")]
+            metrics: ParseMetrics::default(),
         }
     }
 
@@ -55,8 +101,16 @@ impl VcfParser {
             parse_samples: false,
             skip_invalid: true,
             collect_warnings: false,
+            max_warnings: None,
+            max_invalid_records: None,
+            keep_float_text: false,
+            invalid_records: 0,
             warnings: Vec::new(),
+            warning_counts: HashMap::new(),
             current_line: 0,
+            info_keys: HashSet::default(),
+            #[cfg(feature = "tracing")]
+            metrics: ParseMetrics::default(),
         }
     }
 
@@ -65,24 +119,89 @@ impl VcfParser {
         &self.warnings
     }
 
+    /// Number of warnings per category, including ones not kept
+    pub fn warning_counts(&self) -> &HashMap<WarningCategory, usize> {
+        &self.warning_counts
+    }
+
+    /// Total number of warnings, including ones not kept
+    pub fn warning_total(&self) -> usize {
+        self.warning_counts.values().sum()
+    }
+
+    /// Number of invalid records skipped under `skip_invalid`
+    pub fn invalid_records(&self) -> usize {
+        self.invalid_records
+    }
+
+    /// Timing and counts for the last [`parse`](Self::parse) or
+    /// [`read_header`](Self::read_header)
+    #[cfg(feature = "tracing")]
+    pub fn metrics(&self) -> &ParseMetrics {
+        &self.metrics
+    }
+
     /// Clear collected warnings
     pub fn clear_warnings(&mut self) {
         self.warnings.clear();
+        self.warning_counts.clear();
+    }
+
+    /// Record a warning for the current line if warnings are collected
+    fn warn(&mut self, message: String, category: WarningCategory) {
+        if !self.collect_warnings {
+            return;
+        }
+        *self.warning_counts.entry(category).or_insert(0) += 1;
+        if self.max_warnings.is_none_or(|max| self.warnings.len() < max) {
+            self.warnings
+                .push(ParseWarning::new(self.current_line, message, category));
+        }
+    }
+
+    /// Skip a recoverable record error under `skip_invalid`, or return it
+    pub(crate) fn skip_invalid_record(&mut self, error: VcfError) -> VcfResult<()> {
+        if !(self.skip_invalid && error.is_recoverable()) {
+            return Err(error);
+        }
+        self.invalid_records += 1;
+        if let Some(limit) = self.max_invalid_records {
+            if self.invalid_records > limit {
+                return Err(VcfError::TooManyInvalidRecords {
+                    line: self.current_line,
+                    limit,
+                });
+            }
+        }
+        self.warn(error.to_string(), WarningCategory::Other);
+        Ok(())
     }
 
     /// Parse VCF from a reader
     pub fn parse<R: Read>(&mut self, reader: R) -> VcfResult<(VcfHeader, Vec<VcfRecord>)> {
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "parse",
+            records = tracing::field::Empty,
+            bytes = tracing::field::Empty,
+            warnings = tracing::field::Empty,
+        )
+        .entered();
+
         let buf_reader = BufReader::new(reader);
         let mut lines = buf_reader.lines();
         
         self.current_line = 0;
-        self.warnings.clear();
+        self.invalid_records = 0;
+        self.clear_warnings();
 
         // Parse header
         let header = self.parse_header(&mut lines)?;
         
         // Parse records
         let mut records = Vec::new();
+        #[cfg(feature = "tracing")]
+        let started = Instant::now();
         
         for line_result in lines {
             self.current_line += 1;
@@ -92,24 +211,34 @@ impl VcfParser {
                 continue;
             }
 
-            match self.parse_record(&line, &header) {
+            match self.next_record(&line, &header) {
                 Ok(record) => records.push(record),
-                Err(e) if self.skip_invalid && e.is_recoverable() => {
-                    if self.collect_warnings {
-                        self.warnings.push(ParseWarning::new(
-                            self.current_line,
-                            e.to_string(),
-                            WarningCategory::Other,
-                        ));
-                    }
-                }
-                Err(e) => return Err(e),
+                Err(e) => self.skip_invalid_record(e)?,
             }
         }
 
+        #[cfg(feature = "tracing")]
+        {
+            self.finish_metrics(started.elapsed());
+            span.record("records", self.metrics.records);
+            span.record("bytes", self.metrics.bytes);
+            span.record("warnings", self.metrics.warnings);
+        }
+
         Ok((header, records))
     }
 
+    /// Parse only the header, reading no further than the `#CHROM` line
+    ///
+    /// Lines after the header are never read, so this is cheap even for
+    /// very large (or gzip-compressed) inputs.
+    pub fn read_header<R: BufRead>(&mut self, reader: R) -> VcfResult<VcfHeader> {
+        self.current_line = 0;
+        self.clear_warnings();
+
+        self.parse_header(&mut reader.lines())
+    }
+
     /// Parse VCF from a string
     pub fn parse_str(&mut self, content: &str) -> VcfResult<(VcfHeader, Vec<VcfRecord>)> {
         self.parse(content.as_bytes())
@@ -120,12 +249,23 @@ impl VcfParser {
         &mut self,
         lines: &mut std::io::Lines<B>,
     ) -> VcfResult<VcfHeader> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("parse_header").entered();
+        #[cfg(feature = "tracing")]
+        let started = Instant::now();
+        #[cfg(feature = "tracing")]
+        {
+            self.metrics = ParseMetrics::default();
+        }
+
         let mut header = VcfHeader::default();
         let mut found_header_line = false;
 
         for line_result in lines {
             self.current_line += 1;
             let line = line_result?;
+            #[cfg(feature = "tracing")]
+            self.metrics.add_line(&line);
 
             if line.starts_with("##") {
                 // Meta-information line
@@ -146,17 +286,25 @@ impl VcfParser {
             return Err(VcfError::MissingHeader);
         }
 
+        #[cfg(feature = "tracing")]
+        {
+            self.metrics.header_lines = self.current_line;
+            self.metrics.header_time = started.elapsed();
+            tracing::debug!(
+                lines = self.current_line,
+                samples = header.samples.len(),
+                "parsed header"
+            );
+        }
+
         Ok(header)
     }
 
     /// Parse a meta-information line (##key=value)
     fn parse_meta_line(&mut self, line: &str, header: &mut VcfHeader) -> VcfResult<()> {
-        let content = &line[2..]; // Skip ##
+        let content = line.strip_prefix("##").unwrap_or(line);
         
-        if let Some(eq_pos) = content.find('=') {
-            let key = &content[..eq_pos];
-            let value = &content[eq_pos + 1..];
-
+        if let Some((key, value)) = content.split_once('=') {
             match key {
                 "fileformat" => {
                     header.file_format = value.to_string();
@@ -169,6 +317,7 @@ impl VcfParser {
                         header.contigs.push(ContigInfo {
                             id: contig.get("ID").cloned().unwrap_or_default(),
                             length: contig.get("length").and_then(|l| l.parse().ok()),
+                            estimated: false,
                         });
                     }
                 }
@@ -200,7 +349,32 @@ impl VcfParser {
                         });
                     }
                 }
-                _ => {}
+                "SAMPLE" | "PEDIGREE" => match self.parse_structured_pairs(value) {
+                    Some(mut fields) if key == "SAMPLE" => {
+                        let id = fields
+                            .iter()
+                            .position(|(k, _)| k == "ID")
+                            .map(|i| fields.remove(i).1)
+                            .unwrap_or_default();
+                        // GATK writes `<ID=TUMOR,SampleName=...>`; an explicit
+                        // `##tumor_sample` line takes precedence
+                        let role = match id.as_str() {
+                            "TUMOR" => Some(&mut header.tumor_sample),
+                            "NORMAL" => Some(&mut header.normal_sample),
+                            _ => None,
+                        };
+                        if let Some(role) = role.filter(|role| role.is_none()) {
+                            let name = fields.iter().find(|(k, _)| k == "SampleName");
+                            *role = Some(name.map_or(&id, |(_, v)| v).clone());
+                        }
+                        header.sample_metadata.push(SampleMetadata { id, fields });
+                    }
+                    Some(fields) => header.pedigrees.push(Pedigree { fields }),
+                    None => header.other_meta.push((key.to_string(), value.to_string())),
+                },
+                "tumor_sample" => header.tumor_sample = Some(value.to_string()),
+                "normal_sample" => header.normal_sample = Some(value.to_string()),
+                _ => header.other_meta.push((key.to_string(), value.to_string())),
             }
         }
 
@@ -209,28 +383,43 @@ impl VcfParser {
 
     /// Parse structured field like <ID=XX,Number=1,Type=Integer,Description="...">
     fn parse_structured_field(&self, value: &str) -> Option<HashMap<String, String>> {
-        if !value.starts_with('<') || !value.ends_with('>') {
-            return None;
-        }
+        Some(self.parse_structured_pairs(value)?.into_iter().collect())
+    }
 
-        let inner = &value[1..value.len() - 1];
-        let mut fields = HashMap::new();
+    /// Parse a structured field into key-value pairs in file order
+    fn parse_structured_pairs(&self, value: &str) -> Option<Vec<(String, String)>> {
+        // A lone "<" starts and ends with a bracket, so strip them in turn
+        let inner = value.strip_prefix('<')?.strip_suffix('>')?;
+        let mut fields = Vec::new();
         let mut current_key = String::new();
         let mut current_value = String::new();
         let mut in_quotes = false;
         let mut in_value = false;
 
-        for ch in inner.chars() {
+        let mut chars = inner.chars().peekable();
+        while let Some(ch) = chars.next() {
+            // Inside quotes, `\"` and `\\` stand for the character itself
+            if in_quotes && ch == '\\' {
+                if let Some(escaped) = chars.next_if(|c| matches!(c, '"' | '\\')) {
+                    if in_value {
+                        current_value.push(escaped);
+                    } else {
+                        current_key.push(escaped);
+                    }
+                    continue;
+                }
+            }
             match ch {
                 '"' => {
                     in_quotes = !in_quotes;
                 }
-                '=' if !in_quotes => {
+                // Later `=` belong to the value, as in `URL=...?a=b`
+                '=' if !in_quotes && !in_value => {
                     in_value = true;
                 }
                 ',' if !in_quotes => {
                     if !current_key.is_empty() {
-                        fields.insert(current_key.clone(), current_value.clone());
+                        fields.push((current_key.clone(), current_value.clone()));
                     }
                     current_key.clear();
                     current_value.clear();
@@ -248,7 +437,7 @@ impl VcfParser {
 
         // Don't forget the last field
         if !current_key.is_empty() {
-            fields.insert(current_key, current_value);
+            fields.push((current_key, current_value));
         }
 
         Some(fields)
@@ -265,77 +454,146 @@ impl VcfParser {
         }
 
         // Extract sample names (columns after FORMAT)
-        if fields.len() > 9 {
-            header.samples = fields[9..].iter().map(|s| s.to_string()).collect();
+        if let Some(samples) = fields.get(9..) {
+            header.samples = samples.iter().map(|s| s.to_string()).collect();
         }
 
         Ok(())
     }
 
-    /// Parse a single VCF record line
-    fn parse_record(&self, line: &str, header: &VcfHeader) -> VcfResult<VcfRecord> {
-        // Use memchr for fast tab finding
-        let bytes = line.as_bytes();
-        let mut fields = Vec::with_capacity(10);
-        let mut start = 0;
+    /// Parse a record line as part of a parse
+    #[cfg(not(feature = "tracing"))]
+    #[inline]
+    fn next_record(&mut self, line: &str, header: &VcfHeader) -> VcfResult<VcfRecord> {
+        self.parse_record(line, header)
+    }
 
-        while let Some(pos) = memchr(b'\t', &bytes[start..]) {
-            fields.push(&line[start..start + pos]);
-            start = start + pos + 1;
+    /// Parse a record line as part of a parse, updating the metrics
+    ///
+    /// Every [`RECORD_SAMPLE_INTERVAL`]th line, starting with the first,
+    /// is parsed inside a `parse_record` span and followed by a progress
+    /// event, so tracing costs little per record.
+    #[cfg(feature = "tracing")]
+    fn next_record(&mut self, line: &str, header: &VcfHeader) -> VcfResult<VcfRecord> {
+        self.metrics.add_line(line);
+        self.metrics.record_lines += 1;
+
+        let sampled = (self.metrics.record_lines - 1).is_multiple_of(RECORD_SAMPLE_INTERVAL);
+        let record = if sampled {
+            tracing::trace_span!("parse_record", line = self.current_line)
+                .in_scope(|| self.parse_record(line, header))
+        } else {
+            self.parse_record(line, header)
+        };
+
+        if record.is_ok() {
+            self.metrics.records += 1;
+        }
+        if sampled {
+            tracing::debug!(
+                record_lines = self.metrics.record_lines,
+                bytes = self.metrics.bytes,
+                warnings = self.warning_total(),
+                "parse progress"
+            );
         }
-        fields.push(&line[start..]);
+        record
+    }
 
-        if fields.len() < 8 {
+    /// Record the time spent on records and report the finished parse
+    #[cfg(feature = "tracing")]
+    fn finish_metrics(&mut self, records_time: Duration) {
+        self.metrics.records_time = records_time;
+        self.metrics.warnings = self.warning_total();
+        self.metrics.report();
+    }
+
+    /// Parse a single VCF record line
+    pub(crate) fn parse_record(&mut self, line: &str, header: &VcfHeader) -> VcfResult<VcfRecord> {
+        // Use memchr for fast tab finding; a tab is ASCII, so splitting at
+        // one always lands on a char boundary. The eight fixed columns go in
+        // an array and FORMAT plus the samples stay as one slice, split
+        // lazily, so no line needs a heap-allocated field list.
+        let mut fixed = [""; 8];
+        let mut found = 0;
+        let mut rest = Some(line);
+
+        for slot in fixed.iter_mut() {
+            let Some(remaining) = rest else {
+                break;
+            };
+            match memchr(b'\t', remaining.as_bytes()) {
+                Some(pos) => {
+                    let (field, tail) = remaining.split_at(pos);
+                    *slot = field;
+                    rest = tail.strip_prefix('\t');
+                }
+                None => {
+                    *slot = remaining;
+                    rest = None;
+                }
+            }
+            found += 1;
+        }
+
+        if found < 8 {
             return Err(VcfError::invalid_record(
                 self.current_line,
-                format!("Expected at least 8 fields, found {}", fields.len()),
+                format!("Expected at least 8 fields, found {}", found),
             ));
         }
+        let [chrom, pos, id, reference, alternate, qual_text, filter, info_text] = fixed;
 
         // Parse required fields
-        let chrom = fields[0].to_string();
+        let chrom = chrom.to_string();
         
-        let pos: u64 = fields[1]
+        let pos: u64 = pos
             .parse()
-            .map_err(|_| VcfError::invalid_position(self.current_line, fields[1]))?;
+            .map_err(|_| VcfError::invalid_position(self.current_line, pos))?;
 
-        let id = if fields[2] == "." {
+        let id = if id == "." {
             None
         } else {
-            Some(fields[2].to_string())
+            Some(id.to_string())
         };
 
-        let reference = fields[3].to_string();
+        let reference = reference.to_string();
         
-        let alternate: Vec<String> = if fields[4] == "." {
+        let alternate: Vec<String> = if alternate == "." {
             Vec::new()
         } else {
-            fields[4].split(',').map(String::from).collect()
+            alternate.split(',').map(String::from).collect()
         };
 
-        let qual = if fields[5] == "." {
+        let qual = if qual_text == "." {
             None
         } else {
-            fields[5]
+            qual_text
                 .parse()
-                .map_err(|_| VcfError::invalid_quality(self.current_line, fields[5]))
+                .map_err(|_| VcfError::invalid_quality(self.current_line, qual_text))
                 .ok()
         };
 
-        let filter = self.parse_filter(fields[6]);
+        let filter = self.parse_filter(filter);
 
         // Parse INFO field
         let info = if self.parse_info {
-            self.parse_info_field(fields[7])
+            self.parse_info_field(info_text)
         } else {
-            HashMap::new()
+            InfoMap::default()
         };
-
-        // Parse samples
-        let samples = if self.parse_samples && fields.len() > 9 {
-            self.parse_samples(&fields[8..], &header.samples)
+        let float_text = if self.keep_float_text {
+            float_text(qual.is_some().then_some(qual_text), info_text, &info)
         } else {
-            Vec::new()
+            None
+        };
+
+        // Parse samples (FORMAT plus at least one sample column)
+        let samples = match rest.and_then(|r| r.split_once('\t')) {
+            Some((format, sample_columns)) if self.parse_samples => {
+                self.parse_samples(format, sample_columns, &header.samples)
+            }
+            _ => Vec::new(),
         };
 
         Ok(VcfRecord {
@@ -348,6 +606,7 @@ impl VcfParser {
             filter,
             info,
             samples,
+            float_text,
         })
     }
 
@@ -361,36 +620,50 @@ impl VcfParser {
     }
 
     /// Parse INFO field
-    fn parse_info_field(&self, value: &str) -> HashMap<String, InfoValue> {
-        let mut info = HashMap::new();
+    fn parse_info_field(&mut self, value: &str) -> InfoMap {
+        let mut info = InfoMap::default();
 
         if value == "." {
             return info;
         }
 
         for item in value.split(';') {
-            if let Some(eq_pos) = item.find('=') {
-                let key = &item[..eq_pos];
-                let val = &item[eq_pos + 1..];
-                info.insert(key.to_string(), self.parse_info_value(val));
+            if let Some((key, val)) = item.split_once('=') {
+                info.insert(self.intern_info_key(key), self.parse_info_value(val));
             } else {
                 // Flag field (no value)
-                info.insert(item.to_string(), InfoValue::Flag);
+                info.insert(self.intern_info_key(item), InfoValue::Flag);
             }
         }
 
         info
     }
 
+    /// Shared allocation for an INFO key
+    ///
+    /// At most [`MAX_INTERNED_INFO_KEYS`] keys are kept, so input with
+    /// endless distinct keys cannot grow the table without bound; keys
+    /// past the limit get their own allocation.
+    fn intern_info_key(&mut self, key: &str) -> InfoKey {
+        if let Some(shared) = self.info_keys.get(key) {
+            return Arc::clone(shared);
+        }
+
+        let shared: InfoKey = Arc::from(key);
+        if self.info_keys.len() < MAX_INTERNED_INFO_KEYS {
+            self.info_keys.insert(Arc::clone(&shared));
+        }
+        shared
+    }
+
     /// Parse INFO field value, trying to determine type
     fn parse_info_value(&self, value: &str) -> InfoValue {
         // Check for array (contains comma)
         if value.contains(',') {
-            let parts: Vec<&str> = value.split(',').collect();
+            let parts = || value.split(',');
             
             // Try integer array
-            if let Some(ints) = parts
-                .iter()
+            if let Some(ints) = parts()
                 .map(|p| p.parse::<i64>().ok())
                 .collect::<Option<Vec<_>>>()
             {
@@ -398,8 +671,7 @@ impl VcfParser {
             }
             
             // Try float array
-            if let Some(floats) = parts
-                .iter()
+            if let Some(floats) = parts()
                 .map(|p| p.parse::<f64>().ok())
                 .collect::<Option<Vec<_>>>()
             {
@@ -407,7 +679,7 @@ impl VcfParser {
             }
             
             // String array
-            return InfoValue::StringArray(parts.iter().map(|s| s.to_string()).collect());
+            return InfoValue::StringArray(parts().map(String::from).collect());
         }
 
         // Single value
@@ -421,35 +693,37 @@ impl VcfParser {
         InfoValue::String(value.to_string())
     }
 
-    /// Parse sample columns
-    fn parse_samples(&self, fields: &[&str], sample_names: &[String]) -> Vec<SampleData> {
-        if fields.is_empty() {
-            return Vec::new();
-        }
-
-        let format_keys: Vec<&str> = fields[0].split(':').collect();
-        let mut samples = Vec::with_capacity(fields.len() - 1);
-
-        for (i, sample_field) in fields[1..].iter().enumerate() {
+    /// Parse the tab-separated sample columns against the FORMAT keys
+    fn parse_samples(
+        &self,
+        format: &str,
+        sample_columns: &str,
+        sample_names: &[String],
+    ) -> Vec<SampleData> {
+        let format_keys: SmallVec<[&str; 8]> = format.split(':').collect();
+        let field_count = format_keys.iter().filter(|key| **key != "GT").count();
+        let mut samples = Vec::with_capacity(sample_names.len());
+
+        for (i, sample_field) in sample_columns.split('\t').enumerate() {
             let name = sample_names
                 .get(i)
                 .cloned()
                 .unwrap_or_else(|| format!("SAMPLE_{}", i));
 
-            let values: Vec<&str> = sample_field.split(':').collect();
             let mut sample_data = SampleData {
                 name,
                 genotype: None,
-                fields: HashMap::new(),
+                gt_present: false,
+                fields: HashMap::with_capacity(field_count),
             };
 
-            for (j, key) in format_keys.iter().enumerate() {
-                if let Some(value) = values.get(j) {
-                    if *key == "GT" {
-                        sample_data.genotype = Genotype::parse(value);
-                    } else {
-                        sample_data.fields.insert(key.to_string(), value.to_string());
-                    }
+            // Trailing keys with no value are left out, as with a short sample
+            for (key, value) in format_keys.iter().zip(sample_field.split(':')) {
+                if *key == "GT" {
+                    sample_data.genotype = Genotype::parse(value);
+                    sample_data.gt_present = true;
+                } else {
+                    sample_data.fields.insert(key.to_string(), value.to_string());
                 }
             }
 
@@ -460,12 +734,42 @@ impl VcfParser {
     }
 }
 
+/// Original text of QUAL and of the INFO values parsed as floats, or
+/// `None` if there is neither
+fn float_text(qual: Option<&str>, info_text: &str, info: &InfoMap) -> Option<Box<FloatText>> {
+    let mut text = FloatText {
+        qual: qual.map(String::from),
+        info: Vec::new(),
+    };
+    for (key, value) in info_text.split(';').filter_map(|item| item.split_once('=')) {
+        if let Some((key, InfoValue::Float(_) | InfoValue::FloatArray(_))) =
+            info.get_key_value(key)
+        {
+            text.info.push((Arc::clone(key), value.to_string()));
+        }
+    }
+
+    (text.qual.is_some() || !text.info.is_empty()).then(|| Box::new(text))
+}
+
 /// Iterator-based parser for streaming large files
 pub struct VcfIterator<R: Read> {
     reader: std::io::Lines<BufReader<R>>,
     parser: VcfParser,
     header: VcfHeader,
     current_line: usize,
+
+    /// Span covering the whole iteration
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+
+    /// When record parsing started
+    #[cfg(feature = "tracing")]
+    started: Instant,
+
+    /// Set once the input is exhausted and the metrics are final
+    #[cfg(feature = "tracing")]
+    finished: bool,
 }
 
 impl<R: Read> VcfIterator<R> {
@@ -474,8 +778,18 @@ impl<R: Read> VcfIterator<R> {
         let buf_reader = BufReader::new(reader);
         let mut lines = buf_reader.lines();
         let mut parser = VcfParser::new();
+        #[cfg(feature = "tracing")]
+        let span = tracing::info_span!(
+            "iterate",
+            records = tracing::field::Empty,
+            bytes = tracing::field::Empty,
+            warnings = tracing::field::Empty,
+        );
         
         // Parse header first
+        #[cfg(feature = "tracing")]
+        let header = span.in_scope(|| parser.parse_header(&mut lines))?;
+        #[cfg(not(feature = "tracing"))]
         let header = parser.parse_header(&mut lines)?;
         let current_line = parser.current_line;
 
@@ -484,6 +798,12 @@ impl<R: Read> VcfIterator<R> {
             parser,
             header,
             current_line,
+            #[cfg(feature = "tracing")]
+            span,
+            #[cfg(feature = "tracing")]
+            started: Instant::now(),
+            #[cfg(feature = "tracing")]
+            finished: false,
         })
     }
 
@@ -491,28 +811,179 @@ impl<R: Read> VcfIterator<R> {
     pub fn header(&self) -> &VcfHeader {
         &self.header
     }
+
+    /// Line number in the input of the last line read, counting header
+    /// lines; after a record is returned, the line it came from
+    pub fn line_number(&self) -> usize {
+        self.current_line
+    }
+
+    /// Timing and counts so far; record time runs until the input ends
+    #[cfg(feature = "tracing")]
+    pub fn metrics(&self) -> ParseMetrics {
+        let mut metrics = self.parser.metrics.clone();
+        if !self.finished {
+            metrics.records_time = self.started.elapsed();
+            metrics.warnings = self.parser.warning_total();
+        }
+        metrics
+    }
+
+    /// Finalize and report the metrics once the input is exhausted
+    #[cfg(feature = "tracing")]
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+        self.finished = true;
+        self.parser.finish_metrics(self.started.elapsed());
+        let metrics = &self.parser.metrics;
+        self.span.record("records", metrics.records);
+        self.span.record("bytes", metrics.bytes);
+        self.span.record("warnings", metrics.warnings);
+    }
 }
 
 impl<R: Read> Iterator for VcfIterator<R> {
     type Item = VcfResult<VcfRecord>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        #[cfg(feature = "tracing")]
+        let _span = self.span.clone().entered();
+
         loop {
             match self.reader.next() {
                 Some(Ok(line)) => {
                     self.current_line += 1;
+                    self.parser.current_line = self.current_line;
                     
                     if line.is_empty() {
                         continue;
                     }
 
-                    return Some(self.parser.parse_record(&line, &self.header));
+                    return Some(self.parser.next_record(&line, &self.header));
                 }
                 Some(Err(e)) => return Some(Err(VcfError::Io(e))),
-                None => return None,
+                None => {
+                    #[cfg(feature = "tracing")]
+                    self.finish();
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Push-based parser for content that arrives line by line
+///
+/// Lines are fed one at a time (without their line terminator); header
+/// lines are consumed until `#CHROM`, after which each line yields a record.
+/// Behaves like [`VcfParser::parse`] with the wrapped parser's options.
+pub struct VcfPushParser {
+    parser: VcfParser,
+    header: VcfHeader,
+    header_complete: bool,
+}
+
+impl VcfPushParser {
+    /// Create a push parser using the options of `parser`
+    pub fn new(mut parser: VcfParser) -> Self {
+        parser.current_line = 0;
+        parser.invalid_records = 0;
+        parser.clear_warnings();
+        #[cfg(feature = "tracing")]
+        {
+            parser.metrics = ParseMetrics::default();
+        }
+
+        Self {
+            parser,
+            header: VcfHeader::default(),
+            header_complete: false,
+        }
+    }
+
+    /// Header parsed so far
+    pub fn header(&self) -> &VcfHeader {
+        &self.header
+    }
+
+    /// Whether the `#CHROM` line has been seen
+    pub fn header_complete(&self) -> bool {
+        self.header_complete
+    }
+
+    /// Warnings collected so far
+    pub fn warnings(&self) -> &[ParseWarning] {
+        self.parser.warnings()
+    }
+
+    /// Number of warnings per category so far
+    pub fn warning_counts(&self) -> &HashMap<WarningCategory, usize> {
+        self.parser.warning_counts()
+    }
+
+    /// Counts so far; no times are measured for pushed input
+    #[cfg(feature = "tracing")]
+    pub fn metrics(&self) -> ParseMetrics {
+        ParseMetrics {
+            warnings: self.parser.warning_total(),
+            ..self.parser.metrics.clone()
+        }
+    }
+
+    /// Consume the parser, returning the header
+    pub fn into_header(self) -> VcfHeader {
+        self.header
+    }
+
+    /// Feed one line, returning a record if the line held one
+    ///
+    /// A trailing `\r` is ignored. Header and empty lines, and invalid
+    /// records skipped under `skip_invalid`, return `Ok(None)`.
+    pub fn push_line(&mut self, line: &str) -> VcfResult<Option<VcfRecord>> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        self.parser.current_line += 1;
+
+        if !self.header_complete {
+            #[cfg(feature = "tracing")]
+            {
+                self.parser.metrics.add_line(line);
+                self.parser.metrics.header_lines += 1;
+            }
+            if line.starts_with("##") {
+                self.header.meta_lines.push(line.to_string());
+                self.parser.parse_meta_line(line, &mut self.header)?;
+            } else if line.starts_with("#CHROM") {
+                self.parser.parse_header_line(line, &mut self.header)?;
+                self.header_complete = true;
+            } else if !line.is_empty() {
+                return Err(VcfError::MissingHeader);
+            }
+            return Ok(None);
+        }
+
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        match self.parser.next_record(line, &self.header) {
+            Ok(record) => Ok(Some(record)),
+            Err(e) => {
+                self.parser.skip_invalid_record(e)?;
+                Ok(None)
             }
         }
     }
+
+    /// Check that the input ended after a complete header
+    pub fn finish(&self) -> VcfResult<()> {
+        if self.header_complete {
+            Ok(())
+        } else {
+            Err(VcfError::MissingHeader)
+        }
+    }
 }
 
 /// Calculate statistics from VCF records in parallel
@@ -527,18 +998,7 @@ pub fn calculate_stats_parallel(records: &[VcfRecord]) -> VcfStats {
             stats
         })
         .reduce(VcfStats::new, |mut a, b| {
-            a.total_records += b.total_records;
-            a.snps += b.snps;
-            a.insertions += b.insertions;
-            a.deletions += b.deletions;
-            a.complex += b.complex;
-            a.passed_filter += b.passed_filter;
-            a.failed_filter += b.failed_filter;
-            for chrom in b.chromosomes {
-                if !a.chromosomes.contains(&chrom) {
-                    a.chromosomes.push(chrom);
-                }
-            }
+            a.merge(b);
             a
         })
 }
@@ -552,7 +1012,31 @@ pub fn calculate_stats(records: &[VcfRecord]) -> VcfStats {
     stats
 }
 
+/// Fewest records worth handing to the thread pool
+#[cfg(feature = "parallel")]
+const PARALLEL_STATS_MIN_RECORDS: usize = 4096;
+
+/// Calculate statistics from VCF records, in parallel when it pays
+///
+/// Uses [`calculate_stats_parallel`] when the `parallel` feature is on,
+/// the current rayon pool has more than one thread and there are enough
+/// records; otherwise [`calculate_stats`]. Either way the result is the
+/// same.
+pub fn calculate_stats_auto(records: &[VcfRecord]) -> VcfStats {
+    #[cfg(feature = "parallel")]
+    if uses_parallel_stats(records.len()) {
+        return calculate_stats_parallel(records);
+    }
+    calculate_stats(records)
+}
+
+#[cfg(feature = "parallel")]
+fn uses_parallel_stats(n_records: usize) -> bool {
+    n_records >= PARALLEL_STATS_MIN_RECORDS && rayon::current_num_threads() > 1
+}
+
 #[cfg(test)]
+#[allow(clippy::indexing_slicing)]
 mod tests {
     use super::*;
 
@@ -620,6 +1104,110 @@ chr2	300	rs456	C	T,G	50	q10	DP=70	GT:DP	1/2:35	0/1:40
         }
     }
 
+    #[test]
+    fn test_info_keys_shared_and_looked_up_by_str() {
+        let (_, parsed) = VcfParser::new().parse_str(SAMPLE_VCF).unwrap();
+        let streamed: Vec<_> = VcfIterator::new(SAMPLE_VCF.as_bytes())
+            .unwrap()
+            .collect::<VcfResult<_>>()
+            .unwrap();
+        let mut push = VcfPushParser::new(VcfParser::new());
+        let pushed: Vec<_> = SAMPLE_VCF
+            .lines()
+            .filter_map(|line| push.push_line(line).unwrap())
+            .collect();
+
+        for records in [&parsed, &streamed, &pushed] {
+            let key = String::from("DP");
+            assert!(matches!(records[0].info.get("DP"), Some(InfoValue::Integer(50))));
+            assert!(matches!(records[1].info.get(key.as_str()), Some(InfoValue::Integer(60))));
+            assert!(matches!(records[2].info["DP"], InfoValue::Integer(70)));
+            assert!(!records[0].info.contains_key("XX"));
+
+            // One allocation per key, shared by every record
+            let (a, _) = records[0].info.get_key_value("DP").unwrap();
+            let (b, _) = records[2].info.get_key_value("DP").unwrap();
+            assert!(Arc::ptr_eq(a, b));
+        }
+
+        // Keys serialize as plain strings
+        let json = serde_json::to_value(&parsed[0]).unwrap();
+        assert_eq!(json["info"]["DP"], serde_json::json!({ "Integer": 50 }));
+        let back: VcfRecord = serde_json::from_value(json).unwrap();
+        assert!(matches!(back.info.get("DP"), Some(InfoValue::Integer(50))));
+    }
+
+    #[test]
+    fn test_info_key_table_is_bounded() {
+        let mut content = format!("{}\n", COLUMNS);
+        for i in 0..MAX_INTERNED_INFO_KEYS + 10 {
+            content.push_str(&format!("chr1\t{}\t.\tA\tG\t.\t.\tK{}=1;DP=2\n", i + 1, i));
+        }
+
+        let mut parser = VcfParser::new();
+        let (_, records) = parser.parse_str(&content).unwrap();
+
+        assert_eq!(parser.info_keys.len(), MAX_INTERNED_INFO_KEYS);
+        let last = records.last().unwrap();
+        assert!(last.info.contains_key(format!("K{}", MAX_INTERNED_INFO_KEYS + 9).as_str()));
+        let (first_dp, _) = records[0].info.get_key_value("DP").unwrap();
+        let (last_dp, _) = last.info.get_key_value("DP").unwrap();
+        assert!(Arc::ptr_eq(first_dp, last_dp));
+    }
+
+    #[test]
+    fn test_sample_pedigree_and_other_meta_lines() {
+        let content = "##fileformat=VCFv4.2\n\
+                       ##source=myCaller v1\n\
+                       ##SAMPLE=<ID=S1,Assay=WGS,URL=\"https://x.org/?a=b\">\n\
+                       ##PEDIGREE=<ID=S3,Father=S1,Mother=S2>\n\
+                       ##SAMPLE=unstructured\n\
+                       ##GATKCommandLine=<ID=Tool,CommandLine=\"a b=c\">\n\
+                       #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n";
+        let (header, _) = VcfParser::new().parse_str(content).unwrap();
+
+        assert_eq!(header.sample_metadata.len(), 1);
+        assert_eq!(header.sample_metadata[0].id, "S1");
+        assert_eq!(header.sample_metadata[0].get("Assay"), Some("WGS"));
+        assert_eq!(header.sample_metadata[0].get("URL"), Some("https://x.org/?a=b"));
+        assert_eq!(header.sample_metadata[0].get("ID"), None);
+
+        assert_eq!(header.pedigrees.len(), 1);
+        assert_eq!(header.pedigrees[0].get("ID"), Some("S3"));
+        assert_eq!(header.pedigrees[0].get("Mother"), Some("S2"));
+
+        assert_eq!(
+            header.other_meta,
+            [
+                ("source".to_string(), "myCaller v1".to_string()),
+                ("SAMPLE".to_string(), "unstructured".to_string()),
+                (
+                    "GATKCommandLine".to_string(),
+                    "<ID=Tool,CommandLine=\"a b=c\">".to_string()
+                ),
+            ]
+        );
+        assert_eq!(header.meta_lines.len(), 6);
+    }
+
+    #[test]
+    fn test_tumor_normal_sample_lines() {
+        let gatk = "##fileformat=VCFv4.2\n\
+                    ##SAMPLE=<ID=NORMAL,SampleName=HCC1143_normal>\n\
+                    ##SAMPLE=<ID=TUMOR>\n\
+                    ##tumor_sample=HCC1143_tumor\n\
+                    #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n";
+        let (header, _) = VcfParser::new().parse_str(gatk).unwrap();
+        assert_eq!(header.tumor_sample.as_deref(), Some("HCC1143_tumor"));
+        assert_eq!(header.normal_sample.as_deref(), Some("HCC1143_normal"));
+        assert!(header.other_meta.is_empty());
+        assert_eq!(header.sample_metadata.len(), 2);
+
+        let gatk = gatk.replace("##tumor_sample=HCC1143_tumor\n", "");
+        let (header, _) = VcfParser::new().parse_str(&gatk).unwrap();
+        assert_eq!(header.tumor_sample.as_deref(), Some("TUMOR"));
+    }
+
     #[test]
     fn test_variant_types() {
         let mut parser = VcfParser::new();
@@ -654,6 +1242,88 @@ chr2	300	rs456	C	T,G	50	q10	DP=70	GT:DP	1/2:35	0/1:40
         assert!(records[0].samples.is_empty());
     }
 
+    #[test]
+    fn test_push_parser_matches_parse() {
+        let (header, records) = VcfParser::new().parse_str(SAMPLE_VCF).unwrap();
+
+        let mut push = VcfPushParser::new(VcfParser::new());
+        let mut pushed = Vec::new();
+        for line in SAMPLE_VCF.split('\n') {
+            pushed.extend(push.push_line(&format!("{}\r", line)).unwrap());
+        }
+        push.finish().unwrap();
+
+        assert_eq!(push.header().samples, header.samples);
+        assert_eq!(push.header().meta_lines, header.meta_lines);
+        assert_eq!(pushed.len(), records.len());
+        for (a, b) in pushed.iter().zip(&records) {
+            assert_eq!((&a.chrom, a.pos, &a.alternate), (&b.chrom, b.pos, &b.alternate));
+            assert_eq!(a.samples.len(), b.samples.len());
+        }
+
+        let mut incomplete = VcfPushParser::new(VcfParser::new());
+        incomplete.push_line("##fileformat=VCFv4.2").unwrap();
+        assert!(incomplete.finish().is_err());
+        assert!(incomplete.push_line("chr1\t1\t.\tA\tG\t.\t.\t.").is_err());
+    }
+
+    #[test]
+    fn test_warnings_capped_but_counted() {
+        let bad = "chr1\tabc\t.\tA\tG\t10\tPASS\t.\n".repeat(5);
+        let content = format!("{}{}", SAMPLE_VCF, bad);
+
+        let mut parser = VcfParser::new();
+        parser.skip_invalid = true;
+        parser.max_warnings = Some(2);
+        let (_, records) = parser.parse_str(&content).unwrap();
+
+        assert_eq!(records.len(), 3);
+        assert_eq!(parser.warnings().len(), 2);
+        assert_eq!(parser.warnings()[0].line, 9);
+        assert_eq!(parser.warning_total(), 5);
+        assert_eq!(parser.warning_counts()[&WarningCategory::Other], 5);
+    }
+
+    #[test]
+    fn test_max_invalid_records() {
+        let bad = "chr1\tabc\t.\tA\tG\t10\tPASS\t.\n".repeat(3);
+        let content = format!("{}{}", SAMPLE_VCF, bad);
+
+        let mut parser = VcfParser::new();
+        parser.skip_invalid = true;
+        parser.max_invalid_records = Some(3);
+        parser.parse_str(&content).unwrap();
+        assert_eq!(parser.invalid_records(), 3);
+
+        parser.max_invalid_records = Some(2);
+        let err = parser.parse_str(&content).unwrap_err();
+        assert!(matches!(err, VcfError::TooManyInvalidRecords { line: 11, limit: 2 }));
+        assert_eq!(err.line(), Some(11));
+
+        // The push parser enforces the same limit
+        let mut stream = VcfPushParser::new(parser);
+        let results: Vec<_> = content.lines().map(|l| stream.push_line(l)).collect();
+        assert!(results[..10].iter().all(|r| r.is_ok()));
+        assert!(results[10].is_err());
+    }
+
+    #[test]
+    fn test_read_header_stops_at_chrom_line() {
+        // Anything after #CHROM, even invalid UTF-8, is never read
+        let mut input = SAMPLE_VCF.split("chr1").next().unwrap().as_bytes().to_vec();
+        input.extend_from_slice(&[0xff, 0xfe, b'\n']);
+
+        let header = VcfParser::new().read_header(&input[..]).unwrap();
+
+        assert_eq!(header.samples, vec!["SAMPLE1", "SAMPLE2"]);
+        assert_eq!(header.info_fields[0].id, "DP");
+        assert_eq!(header.format_fields.len(), 2);
+        assert!(matches!(
+            VcfParser::new().read_header(&b"##fileformat=VCFv4.2\n"[..]),
+            Err(VcfError::MissingHeader)
+        ));
+    }
+
     #[test]
     fn test_iterator() {
         let iter = VcfIterator::new(SAMPLE_VCF.as_bytes()).unwrap();
@@ -663,4 +1333,174 @@ chr2	300	rs456	C	T,G	50	q10	DP=70	GT:DP	1/2:35	0/1:40
         let records: Vec<_> = iter.filter_map(|r| r.ok()).collect();
         assert_eq!(records.len(), 3);
     }
+
+    /// 10 000 records in the form the writer emits, so they round-trip
+    fn synthetic_vcf() -> String {
+        let mut vcf = String::from(
+            "##fileformat=VCFv4.2\n\
+             ##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">\n\
+             ##INFO=<ID=AF,Number=A,Type=Float,Description=\"Frequency\">\n\
+             ##INFO=<ID=DB,Number=0,Type=Flag,Description=\"dbSNP\">\n\
+             ##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">\n\
+             ##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Depths\">\n\
+             #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3\n",
+        );
+        for i in 0..10_000 {
+            vcf.push_str(&format!(
+                "chr{}\t{}\t{}\tA\t{}\t{}\t{}\tDP={};AF=0.{},0.5{}\tGT:AD",
+                i / 2500 + 1,
+                i + 1,
+                if i % 4 == 0 { format!("rs{}", i) } else { ".".to_string() },
+                if i % 6 == 0 { "AT,G" } else { "C" },
+                if i % 9 == 0 { ".".to_string() } else { format!("{}.5", i % 70) },
+                ["PASS", ".", "q10;s50"][i % 3],
+                i % 80,
+                i % 9 + 1,
+                if i % 2 == 0 { ";DB" } else { "" },
+            ));
+            for s in 0..3 {
+                let gt = ["0/0", "0/1", "1|1", "1/2"][(i + s) % 4];
+                vcf.push_str(&format!("\t{}:{},{}", gt, i % 30, s));
+            }
+            vcf.push('\n');
+        }
+        vcf
+    }
+
+    #[test]
+    fn test_synthetic_records_round_trip() {
+        let vcf = synthetic_vcf();
+        let (header, records) = VcfParser::new().parse_str(&vcf).unwrap();
+        assert_eq!(records.len(), 10_000);
+        assert_eq!(crate::writer::to_vcf_string(&header, &records, true).unwrap(), vcf);
+
+        // Streaming yields the same records
+        let streamed: Vec<_> = VcfIterator::new(vcf.as_bytes())
+            .unwrap()
+            .collect::<VcfResult<_>>()
+            .unwrap();
+        assert_eq!(crate::writer::to_vcf_string(&header, &streamed, true).unwrap(), vcf);
+    }
+
+    const COLUMNS: &str = "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO";
+
+    #[test]
+    fn test_malformed_header_lines_do_not_panic() {
+        let meta_lines = [
+            "##",
+            "##é",
+            "##=",
+            "##INFO",
+            "##INFO=",
+            "##INFO=<",
+            "##INFO=>",
+            "##INFO=<ID=DP",
+            "##INFO=<ID=DP,Description=\"unterminated>",
+            "##contig=<ID=chr1,length=é>",
+            "##FORMAT=<=,=,,>",
+        ];
+
+        for meta in meta_lines {
+            let content = format!("{}\n{}\n", meta, COLUMNS);
+            let (header, records) = VcfParser::new().parse_str(&content).unwrap();
+            assert_eq!(header.meta_lines, vec![meta], "{:?}", meta);
+            assert!(records.is_empty());
+        }
+
+        let header = VcfParser::new().parse_str("##INFO=<>\n#CHROM\n");
+        assert!(matches!(header, Err(VcfError::InvalidHeader(_))));
+    }
+
+    #[test]
+    fn test_malformed_records_do_not_panic() {
+        let lines = [
+            "",
+            "\t",
+            "\t\t\t\t\t\t\t",
+            "é\té\té\té\té\té\té",
+            "chr1\t1é\t.\tA\tG\t.\t.\t.",
+            "chr1\t1\t.\tA\tG\té\t.\t.",
+            "chr1\t1\t.\tA\tG\t.\t.\té=é;=;é",
+            "chr1\t1\t.\tA\tG\t.\t.\t.\t",
+            "chr1\t1\t.\tA\tG\t.\t.\t.\t\t",
+            "chr1\t1\t.\tA\tG\t.\t.\t.\tGT\t|/|\t999/é\t:::",
+            "chr1\t1\t.\tA\t,,\t.\t;\t;",
+        ];
+
+        for line in lines {
+            let content = format!("{}\n{}\n", COLUMNS, line);
+            let mut parser = VcfParser::new();
+            parser.skip_invalid = true;
+            let (_, records) = parser.parse_str(&content).unwrap();
+
+            for record in &records {
+                record.variant_type();
+                for gt in record.samples.iter().filter_map(|s| s.genotype.as_ref()) {
+                    assert!(!(gt.is_hom_ref() && gt.is_het()), "{:?}", line);
+                    gt.is_hom_alt();
+                }
+            }
+            assert!(records.len() + parser.invalid_records() <= 1, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_multibyte_fields_split_on_tabs() {
+        let content = format!("{}\nchré\t7\tidé\tA\tG\t.\tfé;b\tK=é\n", COLUMNS);
+        let (_, records) = VcfParser::new().parse_str(&content).unwrap();
+
+        assert_eq!(records[0].chrom, "chré");
+        assert_eq!(records[0].id.as_deref(), Some("idé"));
+        assert_eq!(
+            records[0].filter,
+            FilterStatus::Failed(vec!["fé".to_string(), "b".to_string()])
+        );
+        assert!(matches!(&records[0].info["K"], InfoValue::String(v) if v == "é"));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_calculate_stats_parallel_matches_serial() {
+        let vcf = crate::test_support::gen_vcf(5000, 0, 0, 11);
+        let (_, records) = VcfParser::new().parse_str(&vcf).unwrap();
+
+        let serial = serde_json::to_value(calculate_stats(&records)).unwrap();
+        let parallel = serde_json::to_value(calculate_stats_parallel(&records)).unwrap();
+        assert_eq!(parallel, serial);
+    }
+
+    #[test]
+    fn test_calculate_stats_auto_matches_serial() {
+        let (_, records) = VcfParser::new().parse_str(SAMPLE_VCF).unwrap();
+        assert_eq!(
+            serde_json::to_value(calculate_stats_auto(&records)).unwrap(),
+            serde_json::to_value(calculate_stats(&records)).unwrap()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_calculate_stats_auto_dispatch() {
+        let vcf = crate::test_support::gen_vcf(PARALLEL_STATS_MIN_RECORDS, 0, 0, 5);
+        let (_, records) = VcfParser::new().parse_str(&vcf).unwrap();
+        let serial = serde_json::to_value(calculate_stats(&records)).unwrap();
+        let pool = |threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+        };
+
+        // One thread: the serial path
+        pool(1).install(|| {
+            assert!(!uses_parallel_stats(records.len()));
+            assert_eq!(serde_json::to_value(calculate_stats_auto(&records)).unwrap(), serial);
+        });
+        // Several threads: the parallel path, unless the input is small
+        pool(4).install(|| {
+            assert!(uses_parallel_stats(records.len()));
+            assert!(!uses_parallel_stats(records.len() - 1));
+            assert_eq!(serde_json::to_value(calculate_stats_auto(&records)).unwrap(), serial);
+        });
+    }
 }
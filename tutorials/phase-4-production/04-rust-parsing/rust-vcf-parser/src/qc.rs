@@ -0,0 +1,207 @@
+//! Per-Sample Read QC
+//!
+//! Variant allele fractions and allele balance derived from AD and DP,
+//! summarised per record and turned into QC flags.
+
+use crate::types::VcfRecord;
+use serde::{Deserialize, Serialize};
+
+/// VAF of one ALT allele across the samples of a record
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VafSummary {
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+    /// Samples with a VAF; the others lack AD or depth
+    pub samples: usize,
+}
+
+impl VafSummary {
+    /// Summary of [`SampleData::vaf`](crate::SampleData::vaf) over the
+    /// record's samples; `None` if no sample has one
+    pub fn from_record(record: &VcfRecord, alt_index: usize) -> Option<Self> {
+        let mut vafs: Vec<f64> = record
+            .samples
+            .iter()
+            .filter_map(|sample| sample.vaf(alt_index))
+            .collect();
+        if vafs.is_empty() {
+            return None;
+        }
+        vafs.sort_by(f64::total_cmp);
+
+        let mid = vafs.len() / 2;
+        let median = if vafs.len().is_multiple_of(2) {
+            (vafs[mid - 1] + vafs[mid]) / 2.0
+        } else {
+            vafs[mid]
+        };
+        Some(Self {
+            min: vafs[0],
+            median,
+            max: vafs[vafs.len() - 1],
+            samples: vafs.len(),
+        })
+    }
+}
+
+/// Limits for [`record_qc_flags`]
+#[derive(Debug, Clone)]
+pub struct QcThresholds {
+    /// Lowest acceptable het allele balance
+    pub min_allele_balance: f64,
+    /// Highest acceptable het allele balance
+    pub max_allele_balance: f64,
+    /// Samples with DP below this are flagged
+    pub min_depth: i64,
+}
+
+impl Default for QcThresholds {
+    fn default() -> Self {
+        Self {
+            min_allele_balance: 0.2,
+            max_allele_balance: 0.8,
+            min_depth: 10,
+        }
+    }
+}
+
+/// A QC problem with one sample of a record
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "flag", rename_all = "snake_case")]
+pub enum QcFlag {
+    /// Het whose allele balance is outside the allowed range
+    AbOutlier { sample: usize, allele_balance: f64 },
+    /// DP below the minimum
+    DpLow { sample: usize, depth: i64 },
+}
+
+/// QC flags for every sample of a record, in sample order
+///
+/// Samples without the AD or DP a check needs are not flagged by it.
+pub fn record_qc_flags(record: &VcfRecord, thresholds: &QcThresholds) -> Vec<QcFlag> {
+    let mut flags = Vec::new();
+
+    for (sample, data) in record.samples.iter().enumerate() {
+        if let Some(allele_balance) = data.allele_balance() {
+            if allele_balance < thresholds.min_allele_balance
+                || allele_balance > thresholds.max_allele_balance
+            {
+                flags.push(QcFlag::AbOutlier {
+                    sample,
+                    allele_balance,
+                });
+            }
+        }
+        if let Some(depth) = data.int_field("DP") {
+            if depth < thresholds.min_depth {
+                flags.push(QcFlag::DpLow { sample, depth });
+            }
+        }
+    }
+
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    // CLEAN is a 0.5 het, SKEWED a 0.05 het and NO_AD has no AD at all
+    const QC_VCF: &str = "##fileformat=VCFv4.2
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read Depth\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tCLEAN\tSKEWED\tNO_AD
+chr1\t100\t.\tA\tG\t50\tPASS\t.\tGT:AD:DP\t0/1:15,15:30\t0/1:19,1:20\t0/1:.:8
+chr1\t200\t.\tC\tT,G\t50\tPASS\t.\tGT:AD:DP\t1/2:0,10,30:40\t0/1:.,6:24\t0/1:0,0:0
+";
+
+    fn parse() -> Vec<VcfRecord> {
+        VcfParser::new().parse_str(QC_VCF).unwrap().1
+    }
+
+    #[test]
+    fn test_sample_vaf() {
+        let records = parse();
+        let [clean, skewed, no_ad] = &records[0].samples[..] else {
+            panic!("expected three samples");
+        };
+
+        assert_eq!(clean.vaf(1), Some(0.5));
+        assert_eq!(clean.allele_balance(), Some(0.5));
+        assert_eq!(skewed.vaf(1), Some(0.05));
+        assert_eq!(skewed.allele_balance(), Some(0.05));
+        assert_eq!(no_ad.vaf(1), None);
+        assert_eq!(no_ad.allele_balance(), None);
+        assert_eq!(clean.vaf(2), None);
+
+        let [multi, partial_ad, zero_depth] = &records[1].samples[..] else {
+            panic!("expected three samples");
+        };
+        // 1/2 balances the two ALTs against each other
+        assert_eq!(multi.vaf(2), Some(0.75));
+        assert_eq!(multi.allele_balance(), Some(0.75));
+        // REF reads are missing, so DP is the denominator
+        assert_eq!(partial_ad.vaf(1), Some(0.25));
+        assert_eq!(partial_ad.allele_balance(), None);
+        assert_eq!(zero_depth.vaf(1), None);
+        assert_eq!(zero_depth.allele_balance(), None);
+    }
+
+    #[test]
+    fn test_vaf_summary() {
+        let records = parse();
+
+        let summary = VafSummary::from_record(&records[0], 1).unwrap();
+        assert_eq!(summary.samples, 2);
+        assert_eq!(summary.min, 0.05);
+        assert_eq!(summary.median, 0.275);
+        assert_eq!(summary.max, 0.5);
+
+        let summary = VafSummary::from_record(&records[1], 1).unwrap();
+        assert_eq!(
+            (summary.min, summary.median, summary.max),
+            (0.25, 0.25, 0.25)
+        );
+        assert!(VafSummary::from_record(&records[0], 2).is_none());
+    }
+
+    #[test]
+    fn test_record_qc_flags() {
+        let records = parse();
+        let thresholds = QcThresholds::default();
+
+        assert_eq!(
+            record_qc_flags(&records[0], &thresholds),
+            [
+                QcFlag::AbOutlier {
+                    sample: 1,
+                    allele_balance: 0.05
+                },
+                QcFlag::DpLow {
+                    sample: 2,
+                    depth: 8
+                },
+            ]
+        );
+        assert_eq!(
+            record_qc_flags(&records[1], &thresholds),
+            [QcFlag::DpLow {
+                sample: 2,
+                depth: 0
+            }]
+        );
+
+        let strict = QcThresholds {
+            max_allele_balance: 0.7,
+            min_depth: 25,
+            ..Default::default()
+        };
+        assert_eq!(record_qc_flags(&records[1], &strict).len(), 3);
+
+        let json = serde_json::to_value(&record_qc_flags(&records[0], &thresholds)[1]).unwrap();
+        assert_eq!(json["flag"], "dp_low");
+    }
+}
@@ -0,0 +1,257 @@
+//! Reference Allele Checks
+//!
+//! Compares each record's REF with the reference genome at its position.
+//! A few mismatches point at bad records; many mean the file was called
+//! against a different build than the FASTA, which otherwise goes
+//! unnoticed.
+
+use crate::error::VcfResult;
+use crate::fasta::FastaIndex;
+use crate::thin::random_subset;
+use crate::types::VcfRecord;
+use serde::{Deserialize, Serialize};
+
+/// Mismatch rate above which a file is taken to use another build
+pub const DEFAULT_WRONG_BUILD_RATE: f64 = 0.1;
+
+/// Options for [`validate_ref_alleles_with_options`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RefValidationOptions {
+    /// Check a random subset of this many records instead of all
+    pub sample_size: Option<usize>,
+    /// Seed for the subset
+    pub seed: u64,
+    /// Mismatch rate above which the report calls the file likely to be
+    /// on another build
+    pub wrong_build_rate: f64,
+}
+
+impl Default for RefValidationOptions {
+    fn default() -> Self {
+        Self {
+            sample_size: None,
+            seed: 0,
+            wrong_build_rate: DEFAULT_WRONG_BUILD_RATE,
+        }
+    }
+}
+
+/// A record whose REF differs from the reference genome
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefMismatch {
+    /// Index of the record as given to the validator
+    pub index: usize,
+    pub chrom: String,
+    pub pos: u64,
+    pub vcf_ref: String,
+    /// Reference bases at the record's position, as long as REF where
+    /// the sequence allows
+    pub genome_ref: String,
+}
+
+/// Result of checking REF alleles against a reference
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RefValidationReport {
+    /// Records compared with the reference
+    pub checked: usize,
+    pub matched: usize,
+    pub mismatched: Vec<RefMismatch>,
+    /// Records on a chromosome the FASTA does not have
+    pub skipped_no_contig: usize,
+    /// Records with a symbolic or breakend ALT allele
+    pub skipped_symbolic: usize,
+    /// Whether the mismatch rate is above the threshold
+    pub likely_wrong_build: bool,
+}
+
+impl RefValidationReport {
+    /// Mismatched over checked records; `None` if nothing was checked
+    pub fn mismatch_rate(&self) -> Option<f64> {
+        (self.checked > 0).then(|| self.mismatched.len() as f64 / self.checked as f64)
+    }
+}
+
+/// Checks records one at a time, for callers streaming a file
+pub struct RefValidator<'a> {
+    fasta: &'a FastaIndex,
+    wrong_build_rate: f64,
+    report: RefValidationReport,
+}
+
+impl<'a> RefValidator<'a> {
+    pub fn new(fasta: &'a FastaIndex, wrong_build_rate: f64) -> Self {
+        Self {
+            fasta,
+            wrong_build_rate,
+            report: RefValidationReport::default(),
+        }
+    }
+
+    /// Compare `record`'s REF with the reference, case-insensitively;
+    /// `index` is echoed in any mismatch
+    pub fn check(&mut self, index: usize, record: &VcfRecord) -> VcfResult<()> {
+        if record.alternate.iter().any(|alt| is_symbolic(alt)) {
+            self.report.skipped_symbolic += 1;
+            return Ok(());
+        }
+        let start = record.pos.saturating_sub(1);
+        let len = record.reference.len() as u64;
+        let Some(genome_ref) = self.fasta.fetch(&record.chrom, start, len)? else {
+            self.report.skipped_no_contig += 1;
+            return Ok(());
+        };
+
+        self.report.checked += 1;
+        if genome_ref.eq_ignore_ascii_case(&record.reference) {
+            self.report.matched += 1;
+            return Ok(());
+        }
+        self.report.mismatched.push(RefMismatch {
+            index,
+            chrom: record.chrom.clone(),
+            pos: record.pos,
+            vcf_ref: record.reference.clone(),
+            genome_ref,
+        });
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> RefValidationReport {
+        self.report.likely_wrong_build = self
+            .report
+            .mismatch_rate()
+            .is_some_and(|rate| rate > self.wrong_build_rate);
+        self.report
+    }
+}
+
+/// Check every record's REF against `fasta`
+pub fn validate_ref_alleles(
+    records: &[VcfRecord],
+    fasta: &FastaIndex,
+) -> VcfResult<RefValidationReport> {
+    validate_ref_alleles_with_options(records, fasta, &RefValidationOptions::default())
+}
+
+/// Check records' REF against `fasta`, all of them or a random subset
+pub fn validate_ref_alleles_with_options(
+    records: &[VcfRecord],
+    fasta: &FastaIndex,
+    options: &RefValidationOptions,
+) -> VcfResult<RefValidationReport> {
+    let indices = match options.sample_size {
+        Some(k) if k < records.len() => random_subset(records.len(), k, options.seed),
+        _ => (0..records.len()).collect(),
+    };
+    let mut validator = RefValidator::new(fasta, options.wrong_build_rate);
+    for i in indices {
+        validator.check(i, &records[i])?;
+    }
+    Ok(validator.finish())
+}
+
+/// Symbolic (`<DEL>`) or breakend (`G]17:198982]`) ALT
+fn is_symbolic(alt: &str) -> bool {
+    alt.starts_with('<') || alt.contains('[') || alt.contains(']')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const CHR1: &str = "ACGTTGCAAGGCTTACCGATGCATGCTAGCTAGGATCCATGCAAGTCGATCGTAGCTAGT";
+
+    fn fasta() -> FastaIndex {
+        let mut text = String::from(">chr1\n");
+        for line in CHR1.as_bytes().chunks(25) {
+            text += std::str::from_utf8(line).unwrap();
+            text.push('\n');
+        }
+        text += ">chr2\nNNNNACGT\n";
+        FastaIndex::from_bytes(text.into_bytes()).unwrap()
+    }
+
+    /// Bases `pos..pos + len` of chr1, 1-based
+    fn bases(pos: usize, len: usize) -> &'static str {
+        &CHR1[pos - 1..pos - 1 + len]
+    }
+
+    fn records(lines: &[(&str, usize, &str, &str)]) -> Vec<VcfRecord> {
+        let mut vcf =
+            String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+        for (chrom, pos, reference, alt) in lines {
+            vcf += &format!(
+                "{}\t{}\t.\t{}\t{}\t.\tPASS\t.\n",
+                chrom, pos, reference, alt
+            );
+        }
+        VcfParser::new().parse_str(&vcf).unwrap().1
+    }
+
+    #[test]
+    fn test_one_deliberate_mismatch() {
+        let lower = bases(26, 4).to_ascii_lowercase();
+        let records = records(&[
+            ("chr1", 1, bases(1, 1), "G"),
+            ("chr1", 10, bases(10, 3), "G"),
+            // Crosses a FASTA line break and is written in lower case
+            ("chr1", 24, bases(24, 4), "A"),
+            ("chr1", 26, &lower, "A"),
+            // The genome has C here
+            ("chr1", 16, "T", "G"),
+            ("chr1", 40, bases(40, 1), "<DEL>"),
+            ("chr1", 41, bases(41, 1), "G]chr2:5]"),
+            ("chr3", 5, "A", "G"),
+        ]);
+        assert_eq!(bases(16, 1), "C");
+
+        let report = validate_ref_alleles(&records, &fasta()).unwrap();
+        assert_eq!((report.checked, report.matched), (5, 4));
+        assert_eq!((report.skipped_symbolic, report.skipped_no_contig), (2, 1));
+        assert_eq!(
+            report.mismatched,
+            [RefMismatch {
+                index: 4,
+                chrom: "chr1".to_string(),
+                pos: 16,
+                vcf_ref: "T".to_string(),
+                genome_ref: "C".to_string(),
+            }]
+        );
+        assert_eq!(report.mismatch_rate(), Some(0.2));
+        assert!(report.likely_wrong_build);
+
+        let lenient = RefValidationOptions {
+            wrong_build_rate: 0.25,
+            ..RefValidationOptions::default()
+        };
+        let report = validate_ref_alleles_with_options(&records, &fasta(), &lenient).unwrap();
+        assert!(!report.likely_wrong_build);
+    }
+
+    #[test]
+    fn test_sample_size_checks_a_subset() {
+        let lines: Vec<(&str, usize, &str, &str)> = (1..=50)
+            .map(|pos| ("chr1", pos, bases(pos, 1), "N"))
+            .collect();
+        let correct = records(&lines);
+        let options = |seed| RefValidationOptions {
+            sample_size: Some(10),
+            seed,
+            ..RefValidationOptions::default()
+        };
+        let report = validate_ref_alleles_with_options(&correct, &fasta(), &options(3)).unwrap();
+        assert_eq!((report.checked, report.matched), (10, 10));
+        assert!(!report.likely_wrong_build);
+
+        // Shifted a base, most REFs fail
+        let shifted: Vec<(&str, usize, &str, &str)> = (1..50)
+            .map(|pos| ("chr1", pos + 1, bases(pos, 1), "N"))
+            .collect();
+        let report =
+            validate_ref_alleles_with_options(&records(&shifted), &fasta(), &options(3)).unwrap();
+        assert_eq!(report.checked, 10);
+        assert!(report.likely_wrong_build, "{:?}", report.mismatch_rate());
+    }
+}
@@ -0,0 +1,150 @@
+//! Genomic Regions
+//!
+//! Parsing of samtools-style locus strings (`chr17`, `chr17:41196312`,
+//! `chr17:41,196,312-41,277,500`) used to select records by position.
+
+use crate::error::{VcfError, VcfResult};
+use crate::types::VcfRecord;
+use std::fmt;
+use std::str::FromStr;
+
+/// Genomic region: `chrom`, `chrom:start` or `chrom:start-end`
+///
+/// Coordinates are 1-based and inclusive; a missing end means "to the end
+/// of the contig".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub chrom: String,
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+impl Region {
+    /// Region covering a whole contig
+    pub fn contig(chrom: impl Into<String>) -> Self {
+        Self {
+            chrom: chrom.into(),
+            start: 1,
+            end: None,
+        }
+    }
+
+    /// Parse a locus string; thousands separators are accepted
+    pub fn parse(region: &str) -> VcfResult<Self> {
+        let region = region.trim();
+        if region.is_empty() {
+            return Err(VcfError::InvalidRegion("empty region".to_string()));
+        }
+
+        let position = |s: &str| s.trim().replace(',', "").parse::<u64>().ok();
+
+        // Names may themselves contain ':', so only split when the suffix is a range
+        let parsed = region.rsplit_once(':').and_then(|(chrom, range)| {
+            let (start, end) = match range.split_once('-') {
+                Some((start, end)) => (position(start)?, Some(position(end)?)),
+                None => (position(range)?, None),
+            };
+            Some((chrom, start, end))
+        });
+
+        let (chrom, start, end) = parsed.unwrap_or((region, 1, None));
+
+        if chrom.is_empty() {
+            return Err(VcfError::InvalidRegion(format!(
+                "missing contig name in '{}'",
+                region
+            )));
+        }
+        if start == 0 {
+            return Err(VcfError::InvalidRegion(format!(
+                "start must be >= 1 in '{}'",
+                region
+            )));
+        }
+        if end.is_some_and(|end| end < start) {
+            return Err(VcfError::InvalidRegion(format!(
+                "end is before start in '{}'",
+                region
+            )));
+        }
+
+        Ok(Self {
+            chrom: chrom.to_string(),
+            start,
+            end,
+        })
+    }
+
+    /// Check whether a position on `chrom` falls in the region
+    pub fn contains(&self, chrom: &str, pos: u64) -> bool {
+        chrom == self.chrom && pos >= self.start && self.end.is_none_or(|end| pos <= end)
+    }
+
+    /// Check whether a record's position falls in the region
+    pub fn contains_record(&self, record: &VcfRecord) -> bool {
+        self.contains(&record.chrom, record.pos)
+    }
+}
+
+impl FromStr for Region {
+    type Err = VcfError;
+
+    fn from_str(s: &str) -> VcfResult<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "{}:{}-{}", self.chrom, self.start, end),
+            None if self.start == 1 => write!(f, "{}", self.chrom),
+            None => write!(f, "{}:{}", self.chrom, self.start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_region() {
+        assert_eq!(Region::parse("chr17").unwrap(), Region::contig("chr17"));
+        assert_eq!(
+            Region::parse(" chr17:41,196,312-41,277,500 ").unwrap(),
+            Region {
+                chrom: "chr17".to_string(),
+                start: 41_196_312,
+                end: Some(41_277_500),
+            }
+        );
+        assert_eq!(
+            Region::parse("HLA-A*01:01:100").unwrap(),
+            Region {
+                chrom: "HLA-A*01:01".to_string(),
+                start: 100,
+                end: None,
+            }
+        );
+
+        for bad in ["", ":1-5", "chr1:0-5", "chr1:10-5"] {
+            assert!(matches!(Region::parse(bad), Err(VcfError::InvalidRegion(_))));
+        }
+    }
+
+    #[test]
+    fn test_contains_and_display() {
+        let region: Region = "chr1:100-200".parse().unwrap();
+
+        assert!(region.contains("chr1", 100));
+        assert!(region.contains("chr1", 200));
+        assert!(!region.contains("chr1", 201));
+        assert!(!region.contains("chr2", 150));
+        assert!(Region::contig("chr1").contains("chr1", u64::MAX));
+
+        assert_eq!(region.to_string(), "chr1:100-200");
+        assert_eq!(Region::parse("chrX:5").unwrap().to_string(), "chrX:5");
+        assert_eq!(Region::contig("chrX").to_string(), "chrX");
+    }
+}
@@ -0,0 +1,240 @@
+//! Runs of Homozygosity
+//!
+//! Sliding-window detection of long homozygous stretches in one sample,
+//! along the lines of PLINK's `--homozyg`.
+
+use crate::concordance::GenotypeClass;
+use crate::types::VcfRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Window and segment limits for [`detect_roh`]
+#[derive(Debug, Clone)]
+pub struct RohOptions {
+    /// Variants per sliding window
+    pub window_size: usize,
+    /// Het genotypes a homozygous window may contain
+    pub max_hets: usize,
+    /// Missing genotypes a homozygous window may contain
+    pub max_missing: usize,
+    /// Shortest segment reported, in bp
+    pub min_length: u64,
+    /// Fewest variants in a reported segment
+    pub min_variants: usize,
+}
+
+impl Default for RohOptions {
+    fn default() -> Self {
+        Self {
+            window_size: 50,
+            max_hets: 1,
+            max_missing: 5,
+            min_length: 1_000_000,
+            min_variants: 100,
+        }
+    }
+}
+
+/// A run of homozygosity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RohSegment {
+    pub chrom: String,
+    /// Position of the first variant
+    pub start: u64,
+    /// Position of the last variant
+    pub end: u64,
+    /// Variants in the segment, hets and missing genotypes included
+    pub variants: usize,
+    pub hets: usize,
+}
+
+impl RohSegment {
+    /// Length in bp from the first variant to the last
+    pub fn length(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// The segment as a BED line (0-based start, exclusive end) named `name`
+    pub fn to_bed(&self, name: &str) -> String {
+        format!("{}\t{}\t{}\t{}", self.chrom, self.start - 1, self.end, name)
+    }
+}
+
+/// Runs of homozygosity for `sample`, chromosome by chromosome in order
+/// of first appearance
+///
+/// Records need not be sorted; each chromosome's genotypes are put in
+/// position order first. Every window of `window_size` consecutive
+/// variants within the het and missing limits marks its variants, runs of
+/// marked variants are trimmed to start and end on homozygous genotypes,
+/// and runs long enough in both bp and variants become segments. A sample
+/// absent from a record counts as missing there.
+pub fn detect_roh(records: &[VcfRecord], sample: &str, opts: RohOptions) -> Vec<RohSegment> {
+    let mut chroms: Vec<(&str, Vec<(u64, GenotypeClass)>)> = Vec::new();
+    let mut chrom_index: HashMap<&str, usize> = HashMap::new();
+    for record in records {
+        let data = record.samples.iter().find(|s| s.name == sample);
+        let class = GenotypeClass::of(data.and_then(|s| s.genotype.as_ref()));
+        let index = *chrom_index.entry(&record.chrom).or_insert_with(|| {
+            chroms.push((&record.chrom, Vec::new()));
+            chroms.len() - 1
+        });
+        chroms[index].1.push((record.pos, class));
+    }
+
+    let mut segments = Vec::new();
+    for (chrom, mut sites) in chroms {
+        sites.sort_by_key(|&(pos, _)| pos);
+        chrom_segments(chrom, &sites, &opts, &mut segments);
+    }
+    segments
+}
+
+fn chrom_segments(
+    chrom: &str,
+    sites: &[(u64, GenotypeClass)],
+    opts: &RohOptions,
+    segments: &mut Vec<RohSegment>,
+) {
+    let window = opts.window_size.max(1);
+    let is = |i: usize, class: GenotypeClass| sites[i].1 == class;
+    let is_hom = |i: usize| is(i, GenotypeClass::HomRef) || is(i, GenotypeClass::HomAlt);
+
+    // Whether each site lies in at least one homozygous window
+    let mut covered = vec![false; sites.len()];
+    let (mut hets, mut missing, mut filled) = (0, 0, 0);
+    for i in 0..sites.len() {
+        hets += is(i, GenotypeClass::Het) as usize;
+        missing += is(i, GenotypeClass::Missing) as usize;
+        if i >= window {
+            hets -= is(i - window, GenotypeClass::Het) as usize;
+            missing -= is(i - window, GenotypeClass::Missing) as usize;
+        }
+        if i + 1 >= window && hets <= opts.max_hets && missing <= opts.max_missing {
+            let from = (i + 1 - window).max(filled);
+            covered[from..=i].fill(true);
+            filled = i + 1;
+        }
+    }
+
+    let mut i = 0;
+    while i < sites.len() {
+        if !covered[i] {
+            i += 1;
+            continue;
+        }
+        let mut end = i;
+        while end + 1 < sites.len() && covered[end + 1] {
+            end += 1;
+        }
+        let next = end + 1;
+
+        let mut start = i;
+        while start < end && !is_hom(start) {
+            start += 1;
+        }
+        while end > start && !is_hom(end) {
+            end -= 1;
+        }
+        let segment = RohSegment {
+            chrom: chrom.to_string(),
+            start: sites[start].0,
+            end: sites[end].0,
+            variants: end - start + 1,
+            hets: (start..=end).filter(|&j| is(j, GenotypeClass::Het)).count(),
+        };
+        if is_hom(start)
+            && segment.variants >= opts.min_variants
+            && segment.length() >= opts.min_length
+        {
+            segments.push(segment);
+        }
+        i = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    /// 150 variants a kb apart where every fifth genotype is hom-alt and
+    /// the rest are het, except that sites 50..100 of S1 are homozygous
+    /// (with one missing call); S2 keeps the het-rich background throughout
+    fn planted_vcf() -> String {
+        let mut vcf = String::from(
+            "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\n",
+        );
+        for i in 0..150 {
+            let background = if i % 5 == 2 { "1/1" } else { "0/1" };
+            let s1 = match i {
+                70 => "./.",
+                50..100 if i % 2 == 0 => "0/0",
+                50..100 => "1/1",
+                _ => background,
+            };
+            vcf += &format!(
+                "chr1\t{}\t.\tA\tG\t50\tPASS\t.\tGT\t{}\t{}\n",
+                (i + 1) * 1000,
+                s1,
+                background
+            );
+        }
+        vcf
+    }
+
+    fn options() -> RohOptions {
+        RohOptions {
+            window_size: 20,
+            max_hets: 1,
+            max_missing: 2,
+            min_length: 10_000,
+            min_variants: 20,
+        }
+    }
+
+    #[test]
+    fn test_detect_roh() {
+        let (_, records) = VcfParser::new().parse_str(&planted_vcf()).unwrap();
+
+        // Windows reaching one het past either edge qualify, but the
+        // segment is trimmed back to the planted stretch
+        let segments = detect_roh(&records, "S1", options());
+        assert_eq!(
+            segments,
+            [RohSegment {
+                chrom: "chr1".to_string(),
+                start: 51_000,
+                end: 100_000,
+                variants: 50,
+                hets: 0,
+            }]
+        );
+        assert_eq!(segments[0].length(), 49_001);
+        assert_eq!(segments[0].to_bed("S1"), "chr1\t50999\t100000\tS1");
+
+        assert!(detect_roh(&records, "S2", options()).is_empty());
+        assert!(detect_roh(&records, "NOBODY", options()).is_empty());
+
+        let strict = RohOptions {
+            min_variants: 51,
+            ..options()
+        };
+        assert!(detect_roh(&records, "S1", strict).is_empty());
+        let strict = RohOptions {
+            max_missing: 0,
+            ..options()
+        };
+        assert_eq!(detect_roh(&records, "S1", strict).len(), 2);
+    }
+
+    #[test]
+    fn test_detect_roh_sorts_input() {
+        let (_, mut records) = VcfParser::new().parse_str(&planted_vcf()).unwrap();
+        records.reverse();
+
+        let segments = detect_roh(&records, "S1", options());
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start, segments[0].end), (51_000, 100_000));
+    }
+}
@@ -0,0 +1,192 @@
+//! Record Sampling
+//!
+//! Uniform random samples of a record stream for quick-look previews, so
+//! a UI can show records from across a whole file rather than its first
+//! lines. Samples are reproducible for a given seed.
+
+use crate::error::VcfResult;
+use crate::parser::VcfIterator;
+use crate::rng::SplitMix64;
+use crate::types::{VcfHeader, VcfRecord};
+use std::io::Read;
+
+/// Uniform sample of up to `capacity` items from a stream of unknown
+/// length (Vitter's algorithm R)
+///
+/// Every item offered ends up in the sample with the same probability,
+/// `capacity / seen`, and memory stays at `capacity` items.
+#[derive(Debug, Clone)]
+pub struct ReservoirSampler<T> {
+    capacity: usize,
+    rng: SplitMix64,
+    seen: usize,
+    /// Kept items with their offer index, so they can be put back in order
+    items: Vec<(usize, T)>,
+}
+
+impl<T> ReservoirSampler<T> {
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Self {
+            capacity,
+            rng: SplitMix64::new(seed),
+            seen: 0,
+            items: Vec::with_capacity(capacity.min(1 << 16)),
+        }
+    }
+
+    /// Offer the next item of the stream
+    pub fn offer(&mut self, item: T) {
+        let index = self.seen;
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push((index, item));
+            return;
+        }
+        // Uniform in 0..seen; it replaces a kept item with probability
+        // capacity / seen
+        let slot = ((self.rng.next_f64() * self.seen as f64) as usize).min(index);
+        if let Some(kept) = self.items.get_mut(slot) {
+            *kept = (index, item);
+        }
+    }
+
+    /// Items offered so far
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// Items currently kept
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The sample, in the order the items were offered
+    pub fn into_vec(self) -> Vec<T> {
+        let mut items = self.items;
+        items.sort_unstable_by_key(|(index, _)| *index);
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+/// Uniform random sample of `n` records from `reader`, in file order
+///
+/// Returns the header, the sample and the number of records read. With
+/// `n` at least that number every record is returned. The file is
+/// streamed, so memory stays at `n` records.
+pub fn sample_records<R: Read>(
+    reader: R,
+    n: usize,
+    seed: u64,
+) -> VcfResult<(VcfHeader, Vec<VcfRecord>, usize)> {
+    let mut records = VcfIterator::new(reader)?;
+    let mut sampler = ReservoirSampler::new(n, seed);
+    for record in records.by_ref() {
+        sampler.offer(record?);
+    }
+    let seen = sampler.seen();
+    Ok((records.header().clone(), sampler.into_vec(), seen))
+}
+
+/// Each record of `reader` kept independently with probability
+/// `fraction`, in file order
+///
+/// Returns the header, the sample and the number of records read. Unlike
+/// [`sample_records`] the sample size varies around `fraction` times the
+/// record count.
+pub fn sample_fraction<R: Read>(
+    reader: R,
+    fraction: f64,
+    seed: u64,
+) -> VcfResult<(VcfHeader, Vec<VcfRecord>, usize)> {
+    let mut records = VcfIterator::new(reader)?;
+    let mut rng = SplitMix64::new(seed);
+    let mut sample = Vec::new();
+    let mut seen = 0;
+    for record in records.by_ref() {
+        let record = record?;
+        seen += 1;
+        if rng.next_f64() < fraction {
+            sample.push(record);
+        }
+    }
+    Ok((records.header().clone(), sample, seen))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered_vcf(n: usize) -> String {
+        let mut vcf =
+            String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+        for pos in 1..=n {
+            vcf += &format!("chr1\t{}\t.\tA\tG\t.\tPASS\t.\n", pos);
+        }
+        vcf
+    }
+
+    #[test]
+    fn test_inclusion_is_uniform() {
+        const SEEDS: u64 = 1000;
+        const N: usize = 10;
+        let vcf = numbered_vcf(100);
+
+        let mut included = [0u32; 100];
+        for seed in 0..SEEDS {
+            let (_, sample, seen) = sample_records(vcf.as_bytes(), N, seed).unwrap();
+            assert_eq!((sample.len(), seen), (N, 100));
+            assert!(sample.windows(2).all(|w| w[0].pos < w[1].pos));
+            for record in &sample {
+                included[record.pos as usize - 1] += 1;
+            }
+        }
+
+        // Each record is expected in n/100 of the samples, 100 here, with
+        // a binomial SD of about 9.5
+        for (i, &count) in included.iter().enumerate() {
+            assert!(count.abs_diff(100) < 45, "record {}: {}", i + 1, count);
+        }
+        // Nor are early or late records favoured as a group
+        let first_half: u32 = included[..50].iter().sum();
+        assert!(first_half.abs_diff(5000) < 250, "{}", first_half);
+    }
+
+    #[test]
+    fn test_sample_is_reproducible_and_bounded() {
+        let vcf = numbered_vcf(50);
+        let positions = |n, seed| {
+            let (_, sample, _) = sample_records(vcf.as_bytes(), n, seed).unwrap();
+            sample.iter().map(|r| r.pos).collect::<Vec<_>>()
+        };
+        assert_eq!(positions(5, 42), positions(5, 42));
+        assert_ne!(positions(5, 42), positions(5, 43));
+        assert_eq!(positions(100, 1), (1..=50).collect::<Vec<_>>());
+        assert!(positions(0, 1).is_empty());
+
+        let (_, sample, seen) = sample_records(numbered_vcf(0).as_bytes(), 5, 1).unwrap();
+        assert_eq!((sample.len(), seen), (0, 0));
+    }
+
+    #[test]
+    fn test_sample_fraction() {
+        let vcf = numbered_vcf(2000);
+        let (_, sample, seen) = sample_fraction(vcf.as_bytes(), 0.25, 7).unwrap();
+        assert_eq!(seen, 2000);
+        // Binomial SD about 19
+        assert!(sample.len().abs_diff(500) < 100, "{}", sample.len());
+        assert!(sample.windows(2).all(|w| w[0].pos < w[1].pos));
+
+        assert!(sample_fraction(vcf.as_bytes(), 0.0, 7)
+            .unwrap()
+            .1
+            .is_empty());
+        assert_eq!(
+            sample_fraction(vcf.as_bytes(), 1.0, 7).unwrap().1.len(),
+            2000
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Somatic Calls
+//!
+//! Tumor/normal read support for records from somatic callers such as
+//! Mutect2 and Strelka, and the usual VAF and depth gates on it. The
+//! tumor and normal columns come from the header's `##tumor_sample` and
+//! `##normal_sample` lines (see [`VcfHeader::tumor_sample`]) or are named
+//! explicitly.
+
+use crate::error::{VcfError, VcfResult};
+use crate::types::{SampleData, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+
+/// Sample columns of a tumor and its matched normal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TumorNormal {
+    pub tumor: usize,
+    pub normal: usize,
+}
+
+impl TumorNormal {
+    /// Pair from sample names; `None` if either is not in the header
+    pub fn new(header: &VcfHeader, tumor: &str, normal: &str) -> Option<Self> {
+        let column = |name: &str| header.samples.iter().position(|s| s == name);
+        Some(Self {
+            tumor: column(tumor)?,
+            normal: column(normal)?,
+        })
+    }
+
+    /// Pair named by the header's tumor and normal sample lines
+    pub fn from_header(header: &VcfHeader) -> Option<Self> {
+        Self::new(
+            header,
+            header.tumor_sample.as_deref()?,
+            header.normal_sample.as_deref()?,
+        )
+    }
+}
+
+/// Read support for a record's ALT allele in the tumor and the normal
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SomaticEvidence {
+    /// The ALT allele with the highest tumor VAF (1-based, as in GT)
+    pub allele: usize,
+    pub tumor_vaf: f64,
+    /// The normal's VAF for the same allele
+    pub normal_vaf: f64,
+    /// FORMAT DP, or the AD total when DP is missing
+    pub tumor_dp: i64,
+    pub normal_dp: i64,
+}
+
+impl VcfRecord {
+    /// Tumor and normal support for this record, with the samples named by
+    /// the header; `None` if the header names no pair or either sample
+    /// lacks AD for the allele (see [`SampleData::vaf`])
+    pub fn somatic_evidence(&self, header: &VcfHeader) -> Option<SomaticEvidence> {
+        self.somatic_evidence_for(&TumorNormal::from_header(header)?)
+    }
+
+    /// Tumor and normal support for this record in the given columns
+    pub fn somatic_evidence_for(&self, pair: &TumorNormal) -> Option<SomaticEvidence> {
+        let tumor = self.samples.get(pair.tumor)?;
+        let normal = self.samples.get(pair.normal)?;
+
+        let (allele, tumor_vaf) = (1..=self.alternate.len())
+            .filter_map(|allele| Some((allele, tumor.vaf(allele)?)))
+            .fold(None, |best: Option<(usize, f64)>, candidate| match best {
+                Some(best) if best.1 >= candidate.1 => Some(best),
+                _ => Some(candidate),
+            })?;
+
+        Some(SomaticEvidence {
+            allele,
+            tumor_vaf,
+            normal_vaf: normal.vaf(allele)?,
+            tumor_dp: depth(tumor)?,
+            normal_dp: depth(normal)?,
+        })
+    }
+}
+
+/// FORMAT DP, falling back to the sum of a complete AD
+fn depth(sample: &SampleData) -> Option<i64> {
+    sample
+        .int_field("DP")
+        .or_else(|| sample.int_array_field("AD")?.into_iter().sum())
+}
+
+/// Gates for [`filter_somatic`]; `None` disables a gate
+#[derive(Debug, Clone)]
+pub struct SomaticOptions {
+    /// Tumor sample name, for files without a `##tumor_sample` line or to
+    /// override it
+    pub tumor_sample: Option<String>,
+    /// Normal sample name, likewise
+    pub normal_sample: Option<String>,
+    pub min_tumor_vaf: Option<f64>,
+    pub max_normal_vaf: Option<f64>,
+    pub min_tumor_depth: Option<i64>,
+    pub min_normal_depth: Option<i64>,
+}
+
+impl Default for SomaticOptions {
+    fn default() -> Self {
+        Self {
+            tumor_sample: None,
+            normal_sample: None,
+            min_tumor_vaf: Some(0.05),
+            max_normal_vaf: Some(0.02),
+            min_tumor_depth: Some(10),
+            min_normal_depth: Some(10),
+        }
+    }
+}
+
+impl SomaticOptions {
+    /// Whether `evidence` passes every enabled gate
+    pub fn passes(&self, evidence: &SomaticEvidence) -> bool {
+        let (min_tumor_vaf, max_normal_vaf) = (self.min_tumor_vaf, self.max_normal_vaf);
+        let (min_tumor_dp, min_normal_dp) = (self.min_tumor_depth, self.min_normal_depth);
+        min_tumor_vaf.is_none_or(|min| evidence.tumor_vaf >= min)
+            && max_normal_vaf.is_none_or(|max| evidence.normal_vaf <= max)
+            && min_tumor_dp.is_none_or(|min| evidence.tumor_dp >= min)
+            && min_normal_dp.is_none_or(|min| evidence.normal_dp >= min)
+    }
+}
+
+/// A record that passed [`filter_somatic`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SomaticCall {
+    /// Index of the record in the input
+    pub record: usize,
+    pub evidence: SomaticEvidence,
+}
+
+/// Records whose tumor/normal support passes the gates in `options`
+///
+/// Sample names in `options` take precedence over the header's. Fails
+/// when no tumor or normal sample is named, or a named sample is not a
+/// column of the file. Records without evidence (see
+/// [`VcfRecord::somatic_evidence_for`]) are dropped.
+pub fn filter_somatic(
+    records: &[VcfRecord],
+    header: &VcfHeader,
+    options: &SomaticOptions,
+) -> VcfResult<Vec<SomaticCall>> {
+    let tumor = role(&options.tumor_sample, &header.tumor_sample, "tumor")?;
+    let normal = role(&options.normal_sample, &header.normal_sample, "normal")?;
+    let pair = TumorNormal::new(header, tumor, normal).ok_or_else(|| {
+        VcfError::InvalidHeader(format!(
+            "tumor '{}' or normal '{}' is not a sample column",
+            tumor, normal
+        ))
+    })?;
+
+    Ok(records
+        .iter()
+        .enumerate()
+        .filter_map(|(record, r)| {
+            let evidence = r.somatic_evidence_for(&pair)?;
+            options
+                .passes(&evidence)
+                .then_some(SomaticCall { record, evidence })
+        })
+        .collect())
+}
+
+/// Sample named in the options, else by the header line
+fn role<'a>(name: &'a Option<String>, line: &'a Option<String>, what: &str) -> VcfResult<&'a str> {
+    name.as_deref().or(line.as_deref()).ok_or_else(|| {
+        VcfError::InvalidHeader(format!(
+            "no {} sample: the header has no ##{}_sample line and none was given",
+            what, what
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const MUTECT2: &str = "##fileformat=VCFv4.2
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read depth\">
+##normal_sample=HCC1143_normal
+##tumor_sample=HCC1143_tumor
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tHCC1143_normal\tHCC1143_tumor
+chr1\t100\t.\tA\tT\t.\tPASS\t.\tGT:AD:DP\t0/0:40,0:40\t0/1:30,10:40
+chr1\t200\t.\tC\tG\t.\tPASS\t.\tGT:AD:DP\t0/0:38,2:40\t0/1:20,20:40
+chr1\t300\t.\tG\tA,T\t.\tPASS\t.\tGT:AD\t0/0:25,0,0\t0/1/2:15,3,12
+chr1\t400\t.\tT\tC\t.\tPASS\t.\tGT:AD:DP\t0/0:8,0:8\t0/1:12,3:15
+chr1\t500\t.\tA\tG\t.\tPASS\t.\tGT\t0/0\t0/1
+";
+
+    #[test]
+    fn test_roles_and_vaf_from_header() {
+        let (header, records) = VcfParser::new().parse_str(MUTECT2).unwrap();
+        assert_eq!(
+            TumorNormal::from_header(&header),
+            Some(TumorNormal {
+                tumor: 1,
+                normal: 0
+            })
+        );
+
+        assert_eq!(
+            records[0].somatic_evidence(&header),
+            Some(SomaticEvidence {
+                allele: 1,
+                tumor_vaf: 0.25,
+                normal_vaf: 0.0,
+                tumor_dp: 40,
+                normal_dp: 40,
+            })
+        );
+        assert_eq!(
+            records[1].somatic_evidence(&header).unwrap().normal_vaf,
+            0.05
+        );
+
+        // Highest tumor VAF allele; depth from AD without DP
+        let multi = records[2].somatic_evidence(&header).unwrap();
+        assert_eq!((multi.allele, multi.tumor_vaf), (2, 0.4));
+        assert_eq!((multi.tumor_dp, multi.normal_dp), (30, 25));
+
+        assert_eq!(records[4].somatic_evidence(&header), None);
+    }
+
+    #[test]
+    fn test_filter_somatic_gates() {
+        let (header, records) = VcfParser::new().parse_str(MUTECT2).unwrap();
+        let kept = |options: &SomaticOptions| -> Vec<usize> {
+            filter_somatic(&records, &header, options)
+                .unwrap()
+                .iter()
+                .map(|call| call.record)
+                .collect()
+        };
+
+        // 200 has normal VAF 0.05, 400 a normal depth of 8
+        assert_eq!(kept(&SomaticOptions::default()), [0, 2]);
+        let lenient = SomaticOptions {
+            max_normal_vaf: Some(0.1),
+            min_normal_depth: None,
+            ..SomaticOptions::default()
+        };
+        assert_eq!(kept(&lenient), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_filter_somatic_sample_overrides() {
+        let bare = MUTECT2
+            .replace("##normal_sample=HCC1143_normal\n", "")
+            .replace("##tumor_sample=HCC1143_tumor\n", "");
+        let (header, records) = VcfParser::new().parse_str(&bare).unwrap();
+        assert_eq!(records[0].somatic_evidence(&header), None);
+        assert!(matches!(
+            filter_somatic(&records, &header, &SomaticOptions::default()),
+            Err(VcfError::InvalidHeader(_))
+        ));
+
+        // Swapping the roles turns the normal's zero VAF into the tumor's
+        let swapped = SomaticOptions {
+            tumor_sample: Some("HCC1143_normal".to_string()),
+            normal_sample: Some("HCC1143_tumor".to_string()),
+            min_tumor_vaf: None,
+            max_normal_vaf: None,
+            ..SomaticOptions::default()
+        };
+        let calls = filter_somatic(&records, &header, &swapped).unwrap();
+        assert_eq!(calls[0].evidence.tumor_vaf, 0.0);
+        assert_eq!(calls[0].evidence.normal_vaf, 0.25);
+
+        let unknown = SomaticOptions {
+            tumor_sample: Some("nope".to_string()),
+            ..swapped
+        };
+        assert!(filter_somatic(&records, &header, &unknown).is_err());
+    }
+}
@@ -0,0 +1,490 @@
+//! Splitting Callsets
+//!
+//! One callset per sample, each with a single-sample header, or one per
+//! chromosome with the original header. The in-memory splits suit a
+//! loaded file; [`split_to_files`] streams its input, so a cohort far
+//! larger than memory can be split with only one record held at a time.
+
+use crate::error::VcfResult;
+use crate::genotype_stats::allele_counts;
+use crate::parser::VcfIterator;
+use crate::types::{GenotypeState, HalfCallPolicy, InfoValue, VcfHeader, VcfRecord};
+use crate::writer::VcfWriter;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter, Read};
+use std::path::{Path, PathBuf};
+
+/// Options for per-sample splits
+#[derive(Debug, Clone, Default)]
+pub struct SplitOptions {
+    /// Leave a record out of a sample's output when that sample is
+    /// hom-ref, has a missing call or has no GT
+    pub drop_hom_ref: bool,
+    /// Recompute the AC, AN and AF INFO values a record already has from
+    /// the one sample left, since the cohort's values no longer describe it
+    pub recompute_allele_counts: bool,
+}
+
+/// What [`split_to_files`] writes one file per
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitBy {
+    Sample,
+    Chromosome,
+}
+
+/// One file written by [`split_to_files`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitFile {
+    /// Sample or chromosome name
+    pub name: String,
+    pub path: PathBuf,
+    /// Records written
+    pub records: usize,
+}
+
+/// One callset per sample of `header`, keeping every record
+pub fn split_by_sample(
+    header: &VcfHeader,
+    records: &[VcfRecord],
+) -> Vec<(String, VcfHeader, Vec<VcfRecord>)> {
+    split_by_sample_with_options(header, records, &SplitOptions::default())
+}
+
+/// One callset per sample of `header`, in column order
+///
+/// Each header names only its sample, with the other samples' `##SAMPLE`
+/// lines left out, and each record keeps only that sample's column.
+pub fn split_by_sample_with_options(
+    header: &VcfHeader,
+    records: &[VcfRecord],
+    options: &SplitOptions,
+) -> Vec<(String, VcfHeader, Vec<VcfRecord>)> {
+    header
+        .samples
+        .iter()
+        .enumerate()
+        .map(|(column, name)| {
+            let subset = records
+                .iter()
+                .filter_map(|record| sample_record(record, column, options))
+                .collect();
+            (name.clone(), sample_header(header, name), subset)
+        })
+        .collect()
+}
+
+/// Records grouped by chromosome, in order of each chromosome's first
+/// record; the original header describes every group
+pub fn split_by_chromosome(records: &[VcfRecord]) -> Vec<(String, Vec<VcfRecord>)> {
+    let mut groups: Vec<(String, Vec<VcfRecord>)> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for record in records {
+        let group = *index.entry(&record.chrom).or_insert_with(|| {
+            groups.push((record.chrom.clone(), Vec::new()));
+            groups.len() - 1
+        });
+        groups[group].1.push(record.clone());
+    }
+    groups
+}
+
+/// Split the VCF in `reader` into files in `dir`, streaming
+///
+/// File names are `template` with `{}` replaced by the sample or
+/// chromosome name, `/` and `\` in it becoming `_`; a template without
+/// `{}` is an error, as are two names that give the same file name,
+/// such as a repeated sample or `a/b` and `a_b`. Per-sample files are
+/// all opened before the first record is read, so a cohort needs as
+/// many open files as samples; per-chromosome files are opened as their
+/// chromosomes appear. `options` apply to per-sample splits only.
+/// Returns the files in sample or first-appearance order.
+pub fn split_to_files<R: Read>(
+    reader: R,
+    dir: impl AsRef<Path>,
+    template: &str,
+    by: SplitBy,
+    options: &SplitOptions,
+) -> VcfResult<Vec<SplitFile>> {
+    if !template.contains("{}") {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("file name template '{}' has no {{}}", template),
+        )
+        .into());
+    }
+    let dir = dir.as_ref();
+    // Paths handed out so far, so no file is created twice
+    let mut claimed = HashSet::new();
+    let mut path_for = |name: &str| -> VcfResult<PathBuf> {
+        let path = dir.join(template.replace("{}", &name.replace(['/', '\\'], "_")));
+        if !claimed.insert(path.clone()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "'{}' would be written to {}, already in use",
+                    name,
+                    path.display()
+                ),
+            )
+            .into());
+        }
+        Ok(path)
+    };
+    let create =
+        |path: &Path| -> VcfResult<BufWriter<File>> { Ok(BufWriter::new(File::create(path)?)) };
+
+    let mut records = VcfIterator::new(reader)?;
+    match by {
+        SplitBy::Sample => {
+            let header = records.header().clone();
+            let headers: Vec<VcfHeader> = header
+                .samples
+                .iter()
+                .map(|name| sample_header(&header, name))
+                .collect();
+            // Every path is checked before any file is created
+            let paths = header
+                .samples
+                .iter()
+                .map(|name| path_for(name))
+                .collect::<VcfResult<Vec<_>>>()?;
+            let mut files = Vec::with_capacity(headers.len());
+            let mut writers = Vec::with_capacity(headers.len());
+            for ((name, sample_header), path) in header.samples.iter().zip(&headers).zip(paths) {
+                let mut writer = VcfWriter::new(create(&path)?, sample_header);
+                writer.write_header()?;
+                writers.push(writer);
+                files.push(SplitFile {
+                    name: name.clone(),
+                    path,
+                    records: 0,
+                });
+            }
+
+            for record in records.by_ref() {
+                let record = record?;
+                for (column, writer) in writers.iter_mut().enumerate() {
+                    if let Some(subset) = sample_record(&record, column, options) {
+                        writer.write_record(&subset)?;
+                        files[column].records += 1;
+                    }
+                }
+            }
+            for writer in writers {
+                writer.into_inner()?;
+            }
+            Ok(files)
+        }
+        SplitBy::Chromosome => {
+            let header = records.header().clone();
+            let mut files: Vec<SplitFile> = Vec::new();
+            let mut writers = Vec::new();
+            let mut index: HashMap<String, usize> = HashMap::new();
+
+            for record in records.by_ref() {
+                let record = record?;
+                let file = match index.get(&record.chrom) {
+                    Some(&file) => file,
+                    None => {
+                        let path = path_for(&record.chrom)?;
+                        let mut writer = VcfWriter::new(create(&path)?, &header);
+                        writer.write_header()?;
+                        writers.push(writer);
+                        files.push(SplitFile {
+                            name: record.chrom.clone(),
+                            path,
+                            records: 0,
+                        });
+                        index.insert(record.chrom.clone(), files.len() - 1);
+                        files.len() - 1
+                    }
+                };
+                writers[file].write_record(&record)?;
+                files[file].records += 1;
+            }
+            for writer in writers {
+                writer.into_inner()?;
+            }
+            Ok(files)
+        }
+    }
+}
+
+/// `header` with `name` as its only sample
+fn sample_header(header: &VcfHeader, name: &str) -> VcfHeader {
+    let mut subset = header.clone();
+    subset.samples = vec![name.to_string()];
+    subset.sample_metadata.retain(|meta| meta.id == name);
+    subset
+        .meta_lines
+        .retain(|line| sample_line_id(line).is_none_or(|id| id == name));
+    subset
+}
+
+/// ID of a `##SAMPLE=<ID=...>` line
+fn sample_line_id(line: &str) -> Option<&str> {
+    let fields = line.strip_prefix("##SAMPLE=<")?.strip_suffix('>')?;
+    fields
+        .split(',')
+        .find_map(|field| field.strip_prefix("ID="))
+}
+
+/// `record` with only the sample in `column`, or `None` if
+/// `options.drop_hom_ref` leaves it out
+fn sample_record(record: &VcfRecord, column: usize, options: &SplitOptions) -> Option<VcfRecord> {
+    let sample = record.samples.get(column);
+    if options.drop_hom_ref {
+        let carries_alt = sample.is_some_and(|sample| {
+            matches!(
+                sample.genotype_state(HalfCallPolicy::Missing),
+                GenotypeState::Called(genotype) if !genotype.is_hom_ref()
+            )
+        });
+        if !carries_alt {
+            return None;
+        }
+    }
+
+    // Built field by field so the other samples are never cloned
+    let mut subset = VcfRecord {
+        chrom: record.chrom.clone(),
+        pos: record.pos,
+        id: record.id.clone(),
+        reference: record.reference.clone(),
+        alternate: record.alternate.clone(),
+        qual: record.qual,
+        filter: record.filter.clone(),
+        info: record.info.clone(),
+        samples: sample.cloned().into_iter().collect(),
+        float_text: record.float_text.clone(),
+    };
+    if options.recompute_allele_counts {
+        recompute_allele_counts(&mut subset);
+    }
+    Some(subset)
+}
+
+/// Replace the AC, AN and AF values `record` has with ones counted from
+/// its samples; AF is removed when no allele is called
+fn recompute_allele_counts(record: &mut VcfRecord) {
+    let counts = allele_counts(record, HalfCallPolicy::Missing);
+    let ac: Vec<i64> = counts.ac.iter().map(|&ac| ac as i64).collect();
+    if let Some(value) = record.info.get_mut("AN") {
+        *value = InfoValue::Integer(counts.an as i64);
+    }
+    if let Some(value) = record.info.get_mut("AC") {
+        *value = match ac[..] {
+            [ac] => InfoValue::Integer(ac),
+            _ => InfoValue::IntegerArray(ac),
+        };
+    }
+    if record.info.contains_key("AF") {
+        match counts.frequencies() {
+            Some(frequencies) => {
+                let value = match frequencies[..] {
+                    [af] => InfoValue::Float(af),
+                    _ => InfoValue::FloatArray(frequencies),
+                };
+                record.info.insert("AF".into(), value);
+            }
+            None => {
+                record.info.remove("AF");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::VcfError;
+    use crate::parser::VcfParser;
+
+    const VCF: &str = "##fileformat=VCFv4.2\n\
+                       ##INFO=<ID=AC,Number=A,Type=Integer,Description=\"Allele count\">\n\
+                       ##INFO=<ID=AN,Number=1,Type=Integer,Description=\"Allele number\">\n\
+                       ##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele frequency\">\n\
+                       ##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">\n\
+                       ##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">\n\
+                       ##SAMPLE=<ID=S1,Assay=WGS>\n\
+                       ##SAMPLE=<ID=S2,Assay=WES>\n\
+                       #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\n\
+                       chr1\t100\t.\tA\tG\t50\tPASS\tAC=3;AN=4;AF=0.75\tGT:DP\t0/1:11\t1/1:22\n\
+                       chr2\t200\t.\tC\tT\t50\tPASS\tAC=1;AN=4;AF=0.25\tGT:DP\t0/0:12\t0/1:23\n\
+                       chr1\t300\t.\tG\tA,C\t50\tPASS\tAC=1,1;AN=2;AF=0.5,0.5\tGT:DP\t./.:13\t1/2:24\n";
+
+    fn dp(record: &VcfRecord) -> Option<i64> {
+        record.samples[0].int_field("DP")
+    }
+
+    #[test]
+    fn test_split_by_sample_drops_other_samples() {
+        let (header, records) = VcfParser::new().parse_str(VCF).unwrap();
+        let split = split_by_sample(&header, &records);
+        assert_eq!(split.len(), 2);
+
+        let (name, s2_header, s2_records) = &split[1];
+        assert_eq!(name, "S2");
+        assert_eq!(s2_header.samples, ["S2"]);
+        assert_eq!(s2_header.sample_metadata.len(), 1);
+        assert_eq!(s2_header.sample_metadata[0].id, "S2");
+        assert!(!s2_header
+            .meta_lines
+            .iter()
+            .any(|line| line.contains("ID=S1")));
+        assert!(s2_header
+            .meta_lines
+            .iter()
+            .any(|line| line.contains("ID=S2")));
+        assert_eq!(s2_records.len(), 3);
+        assert!(s2_records
+            .iter()
+            .all(|r| r.samples.len() == 1 && r.samples[0].name == "S2"));
+        let depths: Vec<_> = s2_records.iter().map(dp).collect();
+        assert_eq!(depths, [Some(22), Some(23), Some(24)]);
+        // INFO is left as it was without the option
+        assert!(matches!(
+            s2_records[0].info.get("AC"),
+            Some(InfoValue::Integer(3))
+        ));
+
+        let text = crate::writer::to_vcf_string(s2_header, s2_records.iter(), true).unwrap();
+        assert!(text.contains("#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS2\n"));
+        assert!(!text.contains(":11") && !text.contains("S1"));
+    }
+
+    #[test]
+    fn test_drop_hom_ref_and_recompute_counts() {
+        let (header, records) = VcfParser::new().parse_str(VCF).unwrap();
+        let options = SplitOptions {
+            drop_hom_ref: true,
+            recompute_allele_counts: true,
+        };
+        let split = split_by_sample_with_options(&header, &records, &options);
+
+        // S1 is hom-ref at chr2:200 and missing at chr1:300
+        let s1 = &split[0].2;
+        assert_eq!(s1.len(), 1);
+        assert_eq!(s1[0].pos, 100);
+        assert!(matches!(s1[0].info.get("AC"), Some(InfoValue::Integer(1))));
+        assert!(matches!(s1[0].info.get("AN"), Some(InfoValue::Integer(2))));
+        assert!(matches!(s1[0].info.get("AF"), Some(InfoValue::Float(0.5))));
+
+        let s2 = &split[1].2;
+        assert_eq!(s2.len(), 3);
+        assert!(matches!(s2[0].info.get("AC"), Some(InfoValue::Integer(2))));
+        assert!(matches!(s2[0].info.get("AF"), Some(InfoValue::Float(1.0))));
+        assert!(matches!(s2[2].info.get("AC"), Some(InfoValue::IntegerArray(ac)) if ac == &[1, 1]));
+        assert!(matches!(s2[2].info.get("AN"), Some(InfoValue::Integer(2))));
+
+        // A missing call kept without drop_hom_ref has no AF
+        let kept = split_by_sample_with_options(
+            &header,
+            &records,
+            &SplitOptions {
+                recompute_allele_counts: true,
+                ..SplitOptions::default()
+            },
+        );
+        let missing = &kept[0].2[2];
+        assert!(matches!(
+            missing.info.get("AN"),
+            Some(InfoValue::Integer(0))
+        ));
+        assert!(!missing.info.contains_key("AF"));
+    }
+
+    #[test]
+    fn test_split_by_chromosome_and_to_files() {
+        let (_, records) = VcfParser::new().parse_str(VCF).unwrap();
+        let groups = split_by_chromosome(&records);
+        let names: Vec<&str> = groups.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["chr1", "chr2"]);
+        let positions: Vec<u64> = groups[0].1.iter().map(|r| r.pos).collect();
+        assert_eq!(positions, [100, 300]);
+        assert!(groups[0].1.iter().all(|r| r.samples.len() == 2));
+
+        let dir = std::env::temp_dir().join(format!("vcf-split-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let options = SplitOptions {
+            drop_hom_ref: true,
+            ..SplitOptions::default()
+        };
+        let files = split_to_files(
+            VCF.as_bytes(),
+            &dir,
+            "cohort.{}.vcf",
+            SplitBy::Sample,
+            &options,
+        )
+        .unwrap();
+        let counts: Vec<(&str, usize)> =
+            files.iter().map(|f| (f.name.as_str(), f.records)).collect();
+        assert_eq!(counts, [("S1", 1), ("S2", 3)]);
+        let (header, s1) = VcfParser::new()
+            .parse_str(&std::fs::read_to_string(dir.join("cohort.S1.vcf")).unwrap())
+            .unwrap();
+        assert_eq!(header.samples, ["S1"]);
+        assert_eq!(dp(&s1[0]), Some(11));
+
+        let files = split_to_files(
+            VCF.as_bytes(),
+            &dir,
+            "{}.vcf",
+            SplitBy::Chromosome,
+            &SplitOptions::default(),
+        )
+        .unwrap();
+        let counts: Vec<(&str, usize)> =
+            files.iter().map(|f| (f.name.as_str(), f.records)).collect();
+        assert_eq!(counts, [("chr1", 2), ("chr2", 1)]);
+        let (header, chr1) = VcfParser::new()
+            .parse_str(&std::fs::read_to_string(&files[0].path).unwrap())
+            .unwrap();
+        assert_eq!(header.samples, ["S1", "S2"]);
+        assert_eq!(chr1.len(), 2);
+
+        assert!(split_to_files(
+            VCF.as_bytes(),
+            &dir,
+            "out.vcf",
+            SplitBy::Chromosome,
+            &SplitOptions::default()
+        )
+        .is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_to_files_rejects_colliding_names() {
+        let dir = std::env::temp_dir().join(format!("vcf-split-clash-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let kept = dir.join("a_b.vcf");
+        std::fs::write(&kept, "kept").unwrap();
+        let split = |vcf: &str, by| {
+            split_to_files(vcf.as_bytes(), &dir, "{}.vcf", by, &SplitOptions::default())
+        };
+        let is_invalid_input = |result: VcfResult<Vec<SplitFile>>| matches!(result, Err(VcfError::Io(e)) if e.kind() == io::ErrorKind::InvalidInput);
+
+        // a/b and a_b both become a_b.vcf; nothing is written
+        let samples = "##fileformat=VCFv4.2\n\
+                       #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\ta/b\ta_b\n\
+                       chr1\t100\t.\tA\tG\t50\tPASS\t.\tGT\t0/1\t1/1\n";
+        assert!(is_invalid_input(split(samples, SplitBy::Sample)));
+        assert_eq!(std::fs::read_to_string(&kept).unwrap(), "kept");
+
+        // As do the chromosomes a/b and a_b
+        let chroms = "##fileformat=VCFv4.2\n\
+                      #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+                      a/b\t100\t.\tA\tG\t50\tPASS\t.\n\
+                      a_b\t100\t.\tA\tG\t50\tPASS\t.\n";
+        std::fs::remove_file(&kept).unwrap();
+        assert!(is_invalid_input(split(chroms, SplitBy::Chromosome)));
+        let written = std::fs::read_to_string(&kept).unwrap();
+        assert_eq!(written.lines().filter(|l| !l.starts_with('#')).count(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,455 @@
+//! Variant Store
+//!
+//! Records held in memory for interactive curation: FILTER and INFO
+//! edits and record removal, each undoable, with stats kept current. A
+//! viewer applies edits in place rather than copying the record array.
+
+use crate::error::{VcfError, VcfResult};
+use crate::parser::calculate_stats_auto;
+use crate::types::{
+    FilterDefinition, FilterStatus, InfoDefinition, InfoKey, InfoValue, VcfHeader, VcfRecord,
+    VcfStats,
+};
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+
+/// Edits kept for undo unless set otherwise
+pub const DEFAULT_UNDO_DEPTH: usize = 100;
+
+/// One change to the records; applying it returns the change that
+/// reverts it
+#[derive(Debug, Clone)]
+enum Edit {
+    /// FILTER to give each record
+    Filter(Vec<(usize, FilterStatus)>),
+    /// INFO value to give each record under the key; `None` removes it
+    Info(InfoKey, Vec<(usize, Option<InfoValue>)>),
+    /// Records to remove, by ascending index
+    Remove(Vec<usize>),
+    /// Records to put back, by the ascending index each ends up at
+    Insert(Vec<(usize, VcfRecord)>),
+}
+
+impl Edit {
+    fn is_empty(&self) -> bool {
+        match self {
+            Edit::Filter(changes) => changes.is_empty(),
+            Edit::Info(_, changes) => changes.is_empty(),
+            Edit::Remove(indices) => indices.is_empty(),
+            Edit::Insert(entries) => entries.is_empty(),
+        }
+    }
+
+    fn apply(self, records: &mut Vec<VcfRecord>, stats: &mut OnceCell<VcfStats>) -> Edit {
+        match self {
+            Edit::Filter(changes) => {
+                let mut stats = stats.get_mut();
+                let reverted = changes
+                    .into_iter()
+                    .map(|(i, filter)| {
+                        let previous = std::mem::replace(&mut records[i].filter, filter);
+                        if let Some(stats) = stats.as_deref_mut() {
+                            if let Some(count) = filter_count(stats, &previous) {
+                                *count -= 1;
+                            }
+                            if let Some(count) = filter_count(stats, &records[i].filter) {
+                                *count += 1;
+                            }
+                        }
+                        (i, previous)
+                    })
+                    .collect();
+                Edit::Filter(reverted)
+            }
+            // INFO is not part of the stats
+            Edit::Info(key, changes) => {
+                let reverted = changes
+                    .into_iter()
+                    .map(|(i, value)| {
+                        let info = &mut records[i].info;
+                        let previous = match value {
+                            Some(value) => info.insert(key.clone(), value),
+                            None => info.remove(&key),
+                        };
+                        (i, previous)
+                    })
+                    .collect();
+                Edit::Info(key, reverted)
+            }
+            Edit::Remove(indices) => {
+                stats.take();
+                let mut removed = Vec::with_capacity(indices.len());
+                let mut kept = Vec::with_capacity(records.len() - indices.len());
+                let mut next = indices.iter().peekable();
+                for (i, record) in std::mem::take(records).into_iter().enumerate() {
+                    if next.next_if_eq(&&i).is_some() {
+                        removed.push((i, record));
+                    } else {
+                        kept.push(record);
+                    }
+                }
+                *records = kept;
+                Edit::Insert(removed)
+            }
+            Edit::Insert(entries) => {
+                stats.take();
+                let indices = entries.iter().map(|(i, _)| *i).collect();
+                let mut merged = Vec::with_capacity(records.len() + entries.len());
+                let mut kept = std::mem::take(records).into_iter();
+                for (i, record) in entries {
+                    merged.extend(kept.by_ref().take(i - merged.len()));
+                    merged.push(record);
+                }
+                merged.extend(kept);
+                *records = merged;
+                Edit::Remove(indices)
+            }
+        }
+    }
+}
+
+/// The stats counter a FILTER status adds to, if any
+fn filter_count<'a>(stats: &'a mut VcfStats, filter: &FilterStatus) -> Option<&'a mut usize> {
+    match filter {
+        FilterStatus::Pass => Some(&mut stats.passed_filter),
+        FilterStatus::Failed(_) => Some(&mut stats.failed_filter),
+        FilterStatus::Missing => None,
+    }
+}
+
+/// Header and records open for editing, with undo and redo
+///
+/// Edits take record indices into the current records, in any order
+/// and with repeats ignored; an index out of range fails the whole edit.
+/// Undo goes back up to the undo depth of edits, and a new edit clears
+/// what could be redone.
+#[derive(Debug)]
+pub struct VariantStore {
+    header: VcfHeader,
+    records: Vec<VcfRecord>,
+    /// Stats over `records`, computed on first use after a removal
+    stats: OnceCell<VcfStats>,
+    undo_depth: usize,
+    /// Edits that revert the last edits, most recent last
+    undo: VecDeque<Edit>,
+    /// Edits that redo undone edits, most recent last
+    redo: Vec<Edit>,
+}
+
+impl VariantStore {
+    pub fn new(header: VcfHeader, records: Vec<VcfRecord>) -> Self {
+        Self {
+            header,
+            records,
+            stats: OnceCell::new(),
+            undo_depth: DEFAULT_UNDO_DEPTH,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+        }
+    }
+
+    /// Keep at most `depth` edits for undo, dropping the oldest
+    pub fn set_undo_depth(&mut self, depth: usize) {
+        self.undo_depth = depth;
+        while self.undo.len() > depth {
+            self.undo.pop_front();
+        }
+    }
+
+    pub fn header(&self) -> &VcfHeader {
+        &self.header
+    }
+
+    pub fn records(&self) -> &[VcfRecord] {
+        &self.records
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn into_parts(self) -> (VcfHeader, Vec<VcfRecord>) {
+        (self.header, self.records)
+    }
+
+    /// Stats over the current records
+    pub fn stats(&self) -> &VcfStats {
+        self.stats
+            .get_or_init(|| calculate_stats_auto(&self.records))
+    }
+
+    /// Set the FILTER of the records at `indices`
+    pub fn set_filter(&mut self, indices: &[usize], filter: FilterStatus) -> VcfResult<()> {
+        let indices = self.checked(indices)?;
+        let changes = indices.into_iter().map(|i| (i, filter.clone())).collect();
+        self.edit(Edit::Filter(changes));
+        Ok(())
+    }
+
+    /// Set INFO `key` to `value` on the records at `indices`, replacing
+    /// any value they had
+    ///
+    /// The header is left as it is; see [`VariantStore::declare_info`].
+    pub fn add_info(&mut self, indices: &[usize], key: &str, value: InfoValue) -> VcfResult<()> {
+        let indices = self.checked(indices)?;
+        let changes = indices
+            .into_iter()
+            .map(|i| (i, Some(value.clone())))
+            .collect();
+        self.edit(Edit::Info(InfoKey::from(key), changes));
+        Ok(())
+    }
+
+    /// Remove the records at `indices`; later records move up
+    pub fn remove_records(&mut self, indices: &[usize]) -> VcfResult<()> {
+        let indices = self.checked(indices)?;
+        self.edit(Edit::Remove(indices));
+        Ok(())
+    }
+
+    /// Revert the last edit; `false` if there is none to revert
+    pub fn undo(&mut self) -> bool {
+        let Some(edit) = self.undo.pop_back() else {
+            return false;
+        };
+        let redo = edit.apply(&mut self.records, &mut self.stats);
+        self.redo.push(redo);
+        true
+    }
+
+    /// Reapply the last undone edit; `false` if there is none
+    pub fn redo(&mut self) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        let undo = edit.apply(&mut self.records, &mut self.stats);
+        self.push_undo(undo);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Add an `##INFO` line for `definition` unless the header already
+    /// defines its ID; not an edit, so undo leaves it in place
+    pub fn declare_info(&mut self, definition: InfoDefinition) {
+        if self
+            .header
+            .info_fields
+            .iter()
+            .any(|f| f.id == definition.id)
+        {
+            return;
+        }
+        self.header.meta_lines.push(format!(
+            "##INFO=<ID={},Number={},Type={},Description=\"{}\">",
+            definition.id, definition.number, definition.field_type, definition.description
+        ));
+        self.header.info_fields.push(definition);
+    }
+
+    /// Add a `##FILTER` line for `definition` unless the header already
+    /// defines its ID; not an edit, so undo leaves it in place
+    pub fn declare_filter(&mut self, definition: FilterDefinition) {
+        if self.header.filters.iter().any(|f| f.id == definition.id) {
+            return;
+        }
+        self.header.meta_lines.push(format!(
+            "##FILTER=<ID={},Description=\"{}\">",
+            definition.id, definition.description
+        ));
+        self.header.filters.push(definition);
+    }
+
+    /// `indices` sorted and deduplicated, or the first out of range
+    fn checked(&self, indices: &[usize]) -> VcfResult<Vec<usize>> {
+        if let Some(&index) = indices.iter().find(|&&i| i >= self.records.len()) {
+            return Err(VcfError::RecordOutOfRange {
+                index,
+                len: self.records.len(),
+            });
+        }
+        let mut indices = indices.to_vec();
+        indices.sort_unstable();
+        indices.dedup();
+        Ok(indices)
+    }
+
+    fn edit(&mut self, edit: Edit) {
+        if edit.is_empty() {
+            return;
+        }
+        let undo = edit.apply(&mut self.records, &mut self.stats);
+        self.push_undo(undo);
+        self.redo.clear();
+    }
+
+    fn push_undo(&mut self, edit: Edit) {
+        self.undo.push_back(edit);
+        while self.undo.len() > self.undo_depth {
+            self.undo.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{calculate_stats, VcfParser};
+
+    const VCF: &str = "##fileformat=VCFv4.2\n\
+                       #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+                       chr1\t100\t.\tA\tG\t50\tPASS\tDP=10\n\
+                       chr1\t200\t.\tAT\tA\t40\tq10\t.\n\
+                       chr2\t300\t.\tC\tT\t30\t.\t.\n\
+                       chr3\t400\t.\tG\tGA\t20\tPASS\t.\n";
+
+    fn store() -> VariantStore {
+        let (header, records) = VcfParser::new().parse_str(VCF).unwrap();
+        VariantStore::new(header, records)
+    }
+
+    fn positions(store: &VariantStore) -> Vec<u64> {
+        store.records().iter().map(|r| r.pos).collect()
+    }
+
+    fn filters(store: &VariantStore) -> Vec<String> {
+        store
+            .records()
+            .iter()
+            .map(|r| match &r.filter {
+                FilterStatus::Pass => "PASS".to_string(),
+                FilterStatus::Missing => ".".to_string(),
+                FilterStatus::Failed(names) => names.join(";"),
+            })
+            .collect()
+    }
+
+    /// Cached stats agree with a recount of the current records
+    fn assert_stats(store: &VariantStore) {
+        let expected = serde_json::to_value(calculate_stats(store.records())).unwrap();
+        assert_eq!(serde_json::to_value(store.stats()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_edits_undo_and_redo() {
+        let mut store = store();
+        assert_eq!(
+            (store.stats().passed_filter, store.stats().failed_filter),
+            (2, 1)
+        );
+
+        let rejected = FilterStatus::Failed(vec!["REJECTED".to_string()]);
+        store.set_filter(&[2, 0, 2], rejected).unwrap();
+        assert_eq!(filters(&store), ["REJECTED", "q10", "REJECTED", "PASS"]);
+        assert_eq!(
+            (store.stats().passed_filter, store.stats().failed_filter),
+            (1, 3)
+        );
+        assert_stats(&store);
+
+        store
+            .add_info(&[0, 3], "REVIEWED", InfoValue::Flag)
+            .unwrap();
+        assert!(matches!(
+            store.records()[3].info.get("REVIEWED"),
+            Some(InfoValue::Flag)
+        ));
+        store.add_info(&[0], "DP", InfoValue::Integer(12)).unwrap();
+
+        store.remove_records(&[3, 1]).unwrap();
+        assert_eq!(positions(&store), [100, 300]);
+        assert_eq!(store.stats().total_records, 2);
+        assert_eq!(store.stats().chromosomes, ["chr1", "chr2"]);
+        assert_stats(&store);
+
+        // Back through the removal, the DP change and the flag
+        assert!(store.undo());
+        assert_eq!(positions(&store), [100, 200, 300, 400]);
+        assert_stats(&store);
+        assert!(store.undo());
+        assert!(matches!(
+            store.records()[0].info.get("DP"),
+            Some(InfoValue::Integer(10))
+        ));
+        assert!(store.undo());
+        assert!(store
+            .records()
+            .iter()
+            .all(|r| !r.info.contains_key("REVIEWED")));
+        assert!(store.undo());
+        assert_eq!(filters(&store), ["PASS", "q10", ".", "PASS"]);
+        assert_eq!(
+            (store.stats().passed_filter, store.stats().failed_filter),
+            (2, 1)
+        );
+        assert!(!store.undo());
+
+        assert!(store.redo());
+        assert!(store.redo());
+        assert_eq!(filters(&store), ["REJECTED", "q10", "REJECTED", "PASS"]);
+        assert!(store.records()[0].info.contains_key("REVIEWED"));
+        assert_stats(&store);
+
+        // A new edit drops what could still be redone
+        store.remove_records(&[0]).unwrap();
+        assert!(!store.can_redo());
+        assert_eq!(positions(&store), [200, 300, 400]);
+        assert_stats(&store);
+        assert!(store.undo());
+        assert_eq!(positions(&store), [100, 200, 300, 400]);
+        assert!(store.records()[0].info.contains_key("REVIEWED"));
+    }
+
+    #[test]
+    fn test_undo_depth_and_bad_indices() {
+        let mut store = store();
+        store.set_undo_depth(2);
+        for i in 0..3 {
+            store.set_filter(&[i], FilterStatus::Missing).unwrap();
+        }
+        assert!(store.undo() && store.undo());
+        assert!(!store.undo());
+        assert_eq!(filters(&store), [".", "q10", ".", "PASS"]);
+
+        assert!(matches!(
+            store.remove_records(&[1, 4]),
+            Err(VcfError::RecordOutOfRange { index: 4, len: 4 })
+        ));
+        assert_eq!(store.len(), 4);
+        store.remove_records(&[]).unwrap();
+        store.set_filter(&[], FilterStatus::Pass).unwrap();
+        assert!(!store.can_undo());
+    }
+
+    #[test]
+    fn test_declared_lines_are_written() {
+        let mut store = store();
+        store.declare_filter(FilterDefinition {
+            id: "REJECTED".to_string(),
+            description: "Rejected by a curator".to_string(),
+        });
+        store.declare_filter(FilterDefinition {
+            id: "REJECTED".to_string(),
+            description: "Again".to_string(),
+        });
+        store
+            .set_filter(&[1], FilterStatus::Failed(vec!["REJECTED".to_string()]))
+            .unwrap();
+        store.remove_records(&[0]).unwrap();
+
+        let vcf = crate::writer::to_vcf_string(store.header(), store.records(), false).unwrap();
+        assert_eq!(vcf.matches("##FILTER=<ID=REJECTED").count(), 1);
+        assert!(vcf.contains("chr1\t200\t.\tAT\tA\t40\tREJECTED\t.\n"));
+        assert!(!vcf.contains("\t100\t"));
+    }
+}
@@ -0,0 +1,235 @@
+//! Binned Summaries
+//!
+//! Single-pass collectors for plotting: per-chromosome variant density
+//! and QUAL histograms.
+
+use crate::types::{VariantType, VcfRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Variant counts for one genomic bin
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DensityBin {
+    /// 0-based start of the bin
+    pub start: u64,
+    pub count: usize,
+    pub snps: usize,
+    /// Insertions and deletions
+    pub indels: usize,
+}
+
+/// Density bins for one chromosome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChromDensity {
+    pub chrom: String,
+    pub bins: Vec<DensityBin>,
+}
+
+/// Collects variant counts in fixed-size bins per chromosome
+///
+/// Bins run contiguously from position 1 to the last bin holding a record,
+/// so empty bins are included with zero counts. Chromosomes keep their
+/// order of first appearance.
+#[derive(Debug, Clone)]
+pub struct DensityCollector {
+    bin_size: u64,
+    chromosomes: Vec<ChromDensity>,
+    index: HashMap<String, usize>,
+}
+
+impl DensityCollector {
+    /// Create a collector; `bin_size` must be at least 1
+    pub fn new(bin_size: u64) -> Self {
+        Self {
+            bin_size: bin_size.max(1),
+            chromosomes: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn update(&mut self, record: &VcfRecord) {
+        let idx = match self.index.get(&record.chrom) {
+            Some(&idx) => idx,
+            None => {
+                let idx = self.chromosomes.len();
+                self.index.insert(record.chrom.clone(), idx);
+                self.chromosomes.push(ChromDensity {
+                    chrom: record.chrom.clone(),
+                    bins: Vec::new(),
+                });
+                idx
+            }
+        };
+
+        let bin_size = self.bin_size;
+        let bins = &mut self.chromosomes[idx].bins;
+        let bin = (record.pos.saturating_sub(1) / bin_size) as usize;
+        while bins.len() <= bin {
+            let start = bins.len() as u64 * bin_size;
+            bins.push(DensityBin {
+                start,
+                ..Default::default()
+            });
+        }
+
+        let bin = &mut bins[bin];
+        bin.count += 1;
+        match record.variant_type() {
+            VariantType::Snp => bin.snps += 1,
+            VariantType::Insertion | VariantType::Deletion => bin.indels += 1,
+            VariantType::Complex | VariantType::Other => {}
+        }
+    }
+
+    /// Finished per-chromosome bins
+    pub fn finish(self) -> Vec<ChromDensity> {
+        self.chromosomes
+    }
+}
+
+/// Histogram of QUAL values
+///
+/// Bin `i` covers `[edges[i], edges[i + 1])`. Values at or above the last
+/// edge go to `overflow`, negative values to the first bin, and records
+/// without QUAL are only counted in `missing`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualHistogram {
+    pub edges: Vec<f64>,
+    pub counts: Vec<usize>,
+    pub overflow: usize,
+    pub missing: usize,
+}
+
+impl QualHistogram {
+    /// Bins of `bin_width` from 0 up to (at least) `max_qual`
+    ///
+    /// Returns `None` unless `bin_width` is positive and `max_qual` is
+    /// non-negative, both finite.
+    pub fn new(bin_width: f64, max_qual: f64) -> Option<Self> {
+        let valid_width = bin_width.is_finite() && bin_width > 0.0;
+        if !valid_width || !max_qual.is_finite() || max_qual < 0.0 {
+            return None;
+        }
+
+        let n_bins = ((max_qual / bin_width).ceil() as usize).max(1);
+        Some(Self {
+            edges: (0..=n_bins).map(|i| i as f64 * bin_width).collect(),
+            counts: vec![0; n_bins],
+            overflow: 0,
+            missing: 0,
+        })
+    }
+
+    pub fn update(&mut self, record: &VcfRecord) {
+        let qual = match record.qual {
+            Some(q) if !q.is_nan() => q,
+            _ => {
+                self.missing += 1;
+                return;
+            }
+        };
+
+        let width = self.edges[1];
+        let bin = (qual.max(0.0) / width).floor() as usize;
+        match self.counts.get_mut(bin) {
+            Some(count) => *count += 1,
+            None => self.overflow += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(chrom: &str, pos: u64, reference: &str, alt: &str, qual: Option<f64>) -> VcfRecord {
+        let mut record = VcfRecord::new(chrom, pos, reference, vec![alt]);
+        record.qual = qual;
+        record
+    }
+
+    #[test]
+    fn test_density_bins() {
+        let records = [
+            record("chr1", 1, "A", "G", None),
+            record("chr1", 100, "A", "AT", None),
+            record("chr1", 101, "AT", "A", None),
+            record("chr1", 350, "AC", "GT", None),
+            record("chr2", 42, "C", "T", None),
+        ];
+
+        let mut collector = DensityCollector::new(100);
+        for r in &records {
+            collector.update(r);
+        }
+        let density = collector.finish();
+
+        assert_eq!(density.len(), 2);
+        let chr1 = &density[0];
+        assert_eq!(chr1.chrom, "chr1");
+        assert_eq!(chr1.bins.len(), 4);
+        assert_eq!(
+            chr1.bins[0],
+            DensityBin {
+                start: 0,
+                count: 2,
+                snps: 1,
+                indels: 1
+            }
+        );
+        assert_eq!(
+            chr1.bins[1],
+            DensityBin {
+                start: 100,
+                count: 1,
+                snps: 0,
+                indels: 1
+            }
+        );
+        assert_eq!(chr1.bins[2].count, 0);
+        assert_eq!(
+            chr1.bins[3],
+            DensityBin {
+                start: 300,
+                count: 1,
+                snps: 0,
+                indels: 0
+            }
+        );
+
+        // Single-record chromosome
+        assert_eq!(
+            density[1].bins,
+            vec![DensityBin {
+                start: 0,
+                count: 1,
+                snps: 1,
+                indels: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn test_qual_histogram() {
+        let mut histogram = QualHistogram::new(10.0, 25.0).unwrap();
+        assert_eq!(histogram.edges, vec![0.0, 10.0, 20.0, 30.0]);
+
+        for qual in [
+            Some(0.0),
+            Some(9.9),
+            Some(10.0),
+            Some(29.9),
+            Some(30.0),
+            Some(99.0),
+            None,
+        ] {
+            histogram.update(&record("chr1", 1, "A", "G", qual));
+        }
+        assert_eq!(histogram.counts, vec![2, 1, 1]);
+        assert_eq!(histogram.overflow, 2);
+        assert_eq!(histogram.missing, 1);
+
+        assert!(QualHistogram::new(0.0, 10.0).is_none());
+        assert!(QualHistogram::new(5.0, f64::NAN).is_none());
+    }
+}
@@ -0,0 +1,393 @@
+//! Breakend Pairing
+//!
+//! Structural variant callers write one rearrangement as two or four BND
+//! records, each holding one side of a junction in its ALT
+//! (`G]chr17:198982]`) and linked by the `EVENT` or `MATEID` INFO keys.
+//! [`pair_breakends`] groups those records back into events and says
+//! what kind of rearrangement each junction looks like.
+
+use crate::types::{InfoValue, VcfRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Which side of a breakpoint is joined to the mate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Orientation {
+    /// Sequence up to the position is kept and joined on its right
+    /// (`t[p[`, `t]p]` on the record's own side)
+    Left,
+    /// Sequence from the position on is kept and joined on its left
+    /// (`]p]t`, `[p[t`)
+    Right,
+}
+
+/// One side of a junction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub chrom: String,
+    pub pos: u64,
+    pub orientation: Orientation,
+}
+
+/// A BND ALT allele: the record's own side and its mate's
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakend {
+    pub local: Breakpoint,
+    pub mate: Breakpoint,
+}
+
+impl Breakend {
+    /// Parse the BND form of `alt` for a record at `chrom:pos`
+    ///
+    /// Takes the four bracket forms `t[p[`, `t]p]`, `]p]t` and `[p[t`;
+    /// anything else, including single breakends (`.A`, `A.`), is `None`.
+    pub fn parse(chrom: &str, pos: u64, alt: &str) -> Option<Self> {
+        let bracket_at = alt.find(['[', ']'])?;
+        let bracket = alt[bracket_at..].chars().next()?;
+
+        let (local_orientation, mate, bases) = if bracket_at == 0 {
+            let rest = &alt[1..];
+            let close = rest.find(bracket)?;
+            (Orientation::Right, &rest[..close], &rest[close + 1..])
+        } else {
+            let inner = alt[bracket_at + 1..].strip_suffix(bracket)?;
+            (Orientation::Left, inner, &alt[..bracket_at])
+        };
+        if bases.is_empty() || bases.contains(['[', ']']) {
+            return None;
+        }
+
+        // Contig names may contain `:`, so split at the last one
+        let (mate_chrom, mate_pos) = mate.rsplit_once(':')?;
+        let mate_pos = mate_pos.parse().ok()?;
+        // `[` points right of the mate position, `]` left of it
+        let mate_orientation = if bracket == '[' {
+            Orientation::Right
+        } else {
+            Orientation::Left
+        };
+
+        Some(Self {
+            local: Breakpoint {
+                chrom: chrom.to_string(),
+                pos,
+                orientation: local_orientation,
+            },
+            mate: Breakpoint {
+                chrom: mate_chrom.to_string(),
+                pos: mate_pos,
+                orientation: mate_orientation,
+            },
+        })
+    }
+
+    /// The record's first BND ALT allele
+    pub fn of_record(record: &VcfRecord) -> Option<Self> {
+        record
+            .alternate
+            .iter()
+            .find_map(|alt| Self::parse(&record.chrom, record.pos, alt))
+    }
+}
+
+/// Rearrangement a junction implies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SvKind {
+    /// Same chromosome, the left of the lower breakpoint joined to the
+    /// right of the upper one: the sequence between is lost
+    DeletionLike,
+    /// Same chromosome, the right of the lower breakpoint joined to the
+    /// left of the upper one: the sequence between is repeated
+    DuplicationLike,
+    /// Same chromosome, both sides facing the same way
+    Inversion,
+    /// Breakpoints on different chromosomes
+    Translocation,
+}
+
+impl SvKind {
+    /// Kind of a junction between `first` and `second`, `first` being the
+    /// lower breakpoint
+    fn of_junction(first: &Breakpoint, second: &Breakpoint) -> Self {
+        if first.chrom != second.chrom {
+            return SvKind::Translocation;
+        }
+        match (first.orientation, second.orientation) {
+            (Orientation::Left, Orientation::Right) => SvKind::DeletionLike,
+            (Orientation::Right, Orientation::Left) => SvKind::DuplicationLike,
+            _ => SvKind::Inversion,
+        }
+    }
+}
+
+/// Breakend records that describe one rearrangement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SvEvent {
+    /// The `EVENT` value the records share, if they were grouped by it
+    pub event_id: Option<String>,
+    pub kind: SvKind,
+    /// Lower side of the first member's junction, by chromosome and
+    /// position
+    pub first: Breakpoint,
+    /// Upper side of that junction
+    pub second: Breakpoint,
+    /// Indices of the member records, ascending
+    pub records: Vec<usize>,
+}
+
+/// Outcome of [`pair_breakends`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BreakendPairs {
+    /// Events in order of their first record
+    pub events: Vec<SvEvent>,
+    /// BND records with no mate among the records, ascending
+    pub unpaired: Vec<usize>,
+}
+
+/// Group BND records into events
+///
+/// Records whose ALT is in BND form are grouped by their `EVENT` value;
+/// the rest are paired when their `MATEID`s name each other's ID. An
+/// `EVENT` with a single record, or a record whose mate is missing, is
+/// reported as unpaired. Each event is classified from the junction in
+/// its first record's ALT. Records that are not breakends are ignored.
+pub fn pair_breakends(records: &[VcfRecord]) -> BreakendPairs {
+    let breakends: Vec<(usize, Breakend)> = records
+        .iter()
+        .enumerate()
+        .filter_map(|(i, record)| Some((i, Breakend::of_record(record)?)))
+        .collect();
+
+    let mut groups: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    let mut event_groups: HashMap<String, usize> = HashMap::new();
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    let mut without_event = Vec::new();
+
+    for &(i, _) in &breakends {
+        let record = &records[i];
+        match record.info.get("EVENT").and_then(text) {
+            Some(event) => {
+                let group = *event_groups.entry(event.clone()).or_insert_with(|| {
+                    groups.push((Some(event), Vec::new()));
+                    groups.len() - 1
+                });
+                groups[group].1.push(i);
+            }
+            None => {
+                if let Some(id) = record.id.as_deref() {
+                    by_id.insert(id, i);
+                }
+                without_event.push(i);
+            }
+        }
+    }
+
+    let mut paired = vec![false; records.len()];
+    for &i in &without_event {
+        if paired[i] {
+            continue;
+        }
+        let mate = mate_ids(&records[i])
+            .into_iter()
+            .filter_map(|id| by_id.get(id.as_str()).copied())
+            .find(|&j| {
+                j != i
+                    && !paired[j]
+                    && records[i]
+                        .id
+                        .as_ref()
+                        .is_some_and(|id| mate_ids(&records[j]).contains(id))
+            });
+        if let Some(j) = mate {
+            paired[i] = true;
+            paired[j] = true;
+            groups.push((None, vec![i, j]));
+        }
+    }
+
+    let breakend_of: HashMap<usize, &Breakend> = breakends.iter().map(|(i, b)| (*i, b)).collect();
+    let mut result = BreakendPairs::default();
+    for (event_id, mut members) in groups {
+        if members.len() < 2 {
+            result.unpaired.extend(members);
+            continue;
+        }
+        members.sort_unstable();
+        let junction = breakend_of[&members[0]];
+        let (first, second) = ordered(&junction.local, &junction.mate);
+        result.events.push(SvEvent {
+            event_id,
+            kind: SvKind::of_junction(first, second),
+            first: first.clone(),
+            second: second.clone(),
+            records: members,
+        });
+    }
+    result
+        .unpaired
+        .extend(without_event.into_iter().filter(|&i| !paired[i]));
+    result.unpaired.sort_unstable();
+    result.events.sort_by_key(|event| event.records[0]);
+    result
+}
+
+/// The two sides of a junction, lower chromosome and position first
+fn ordered<'a>(a: &'a Breakpoint, b: &'a Breakpoint) -> (&'a Breakpoint, &'a Breakpoint) {
+    if (&a.chrom, a.pos) <= (&b.chrom, b.pos) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn text(value: &InfoValue) -> Option<String> {
+    match value {
+        InfoValue::String(s) => Some(s.clone()),
+        InfoValue::Integer(i) => Some(i.to_string()),
+        _ => None,
+    }
+}
+
+/// IDs listed in a record's `MATEID`
+fn mate_ids(record: &VcfRecord) -> Vec<String> {
+    match record.info.get("MATEID") {
+        Some(InfoValue::StringArray(ids)) => ids.clone(),
+        Some(value) => text(value).into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const HEADER: &str = "##fileformat=VCFv4.2
+##INFO=<ID=SVTYPE,Number=1,Type=String,Description=\"Type of structural variant\">
+##INFO=<ID=MATEID,Number=.,Type=String,Description=\"ID of mate breakends\">
+##INFO=<ID=EVENT,Number=1,Type=String,Description=\"ID of event associated to breakend\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+";
+
+    fn records(lines: &[&str]) -> Vec<VcfRecord> {
+        let vcf = lines
+            .iter()
+            .fold(HEADER.to_string(), |vcf, line| vcf + line + "\n");
+        VcfParser::new().parse_str(&vcf).unwrap().1
+    }
+
+    fn breakpoint(chrom: &str, pos: u64, orientation: Orientation) -> Breakpoint {
+        Breakpoint {
+            chrom: chrom.into(),
+            pos,
+            orientation,
+        }
+    }
+
+    #[test]
+    fn test_parse_bnd_forms() {
+        let parse =
+            |alt| Breakend::parse("chr2", 321681, alt).map(|b| (b.local.orientation, b.mate));
+        let mate = |orientation| breakpoint("chr17", 198982, orientation);
+        assert_eq!(
+            parse("G]chr17:198982]"),
+            Some((Orientation::Left, mate(Orientation::Left)))
+        );
+        assert_eq!(
+            parse("G[chr17:198982["),
+            Some((Orientation::Left, mate(Orientation::Right)))
+        );
+        assert_eq!(
+            parse("]chr17:198982]A"),
+            Some((Orientation::Right, mate(Orientation::Left)))
+        );
+        assert_eq!(
+            parse("[chr17:198982[A"),
+            Some((Orientation::Right, mate(Orientation::Right)))
+        );
+
+        let contig = Breakend::parse("chr2", 5, "C[<HLA-A*01:01>:12[").unwrap();
+        assert_eq!(
+            contig.mate,
+            breakpoint("<HLA-A*01:01>", 12, Orientation::Right)
+        );
+
+        for alt in [
+            "G",
+            "<DEL>",
+            ".A",
+            "G]chr17:198982[",
+            "G]chr17]",
+            "]chr17:1]",
+            "G[chr17:x[",
+        ] {
+            assert_eq!(Breakend::parse("chr2", 1, alt), None, "{}", alt);
+        }
+    }
+
+    #[test]
+    fn test_pair_translocation_by_mateid() {
+        let records = records(&[
+            "chr2\t321681\tbnd_W\tG\tG]chr17:198982]\t6\tPASS\tSVTYPE=BND;MATEID=bnd_Y",
+            "chr13\t50\t.\tA\tT\t6\tPASS\t.",
+            "chr17\t198982\tbnd_Y\tA\tA]chr2:321681]\t6\tPASS\tSVTYPE=BND;MATEID=bnd_W",
+            "chr13\t123456\tbnd_U\tC\tC[chr2:321682[\t6\tPASS\tSVTYPE=BND;MATEID=bnd_V",
+        ]);
+
+        let pairs = pair_breakends(&records);
+        assert_eq!(pairs.unpaired, [3]);
+        assert_eq!(
+            pairs.events,
+            [SvEvent {
+                event_id: None,
+                kind: SvKind::Translocation,
+                first: breakpoint("chr17", 198982, Orientation::Left),
+                second: breakpoint("chr2", 321681, Orientation::Left),
+                records: vec![0, 2],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_classify_intrachromosomal_events() {
+        let records = records(&[
+            // Deletion of 1001-4999
+            "chr1\t1000\tdel_1\tA\tA[chr1:5000[\t50\tPASS\tSVTYPE=BND;MATEID=del_2",
+            "chr1\t5000\tdel_2\tT\t]chr1:1000]T\t50\tPASS\tSVTYPE=BND;MATEID=del_1",
+            // Tandem duplication of 20000-29999
+            "chr1\t20000\tdup_1\tC\t]chr1:29999]C\t50\tPASS\tSVTYPE=BND;MATEID=dup_2",
+            "chr1\t29999\tdup_2\tG\tG[chr1:20000[\t50\tPASS\tSVTYPE=BND;MATEID=dup_1",
+            // Inversion of 40001-50000 as four breakends sharing an EVENT
+            "chr1\t40000\tinv_1\tT\tT]chr1:50000]\t50\tPASS\tSVTYPE=BND;EVENT=INV1",
+            "chr1\t40001\tinv_2\tA\t[chr1:50001[A\t50\tPASS\tSVTYPE=BND;EVENT=INV1",
+            "chr1\t50000\tinv_3\tG\tG]chr1:40000]\t50\tPASS\tSVTYPE=BND;EVENT=INV1",
+            "chr1\t50001\tinv_4\tC\t[chr1:40001[C\t50\tPASS\tSVTYPE=BND;EVENT=INV1",
+            "chr1\t60000\tlone\tC\tC[chr1:70000[\t50\tPASS\tSVTYPE=BND;EVENT=LONE",
+        ]);
+
+        let pairs = pair_breakends(&records);
+        let summary: Vec<(SvKind, Vec<usize>)> = pairs
+            .events
+            .iter()
+            .map(|event| (event.kind, event.records.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (SvKind::DeletionLike, vec![0, 1]),
+                (SvKind::DuplicationLike, vec![2, 3]),
+                (SvKind::Inversion, vec![4, 5, 6, 7]),
+            ]
+        );
+        assert_eq!(
+            pairs.events[0].first,
+            breakpoint("chr1", 1000, Orientation::Left)
+        );
+        assert_eq!(
+            pairs.events[0].second,
+            breakpoint("chr1", 5000, Orientation::Right)
+        );
+        assert_eq!(pairs.events[2].event_id.as_deref(), Some("INV1"));
+        assert_eq!(pairs.unpaired, [8]);
+    }
+}
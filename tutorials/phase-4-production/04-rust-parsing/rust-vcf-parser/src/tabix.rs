@@ -0,0 +1,361 @@
+//! Tabix-Indexed Random Access
+//!
+//! Region queries on bgzip-compressed VCFs using a `.tbi` index, so only
+//! the compressed blocks overlapping a region are read. Positions in the
+//! index are stored as BGZF virtual offsets: the file offset of a
+//! compressed block in the upper 48 bits and an offset into its
+//! decompressed data in the lower 16.
+
+use crate::error::{VcfError, VcfResult};
+use crate::gzip;
+use crate::parser::VcfParser;
+use crate::region::Region;
+use crate::types::{VcfHeader, VcfRecord};
+use flate2::bufread::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// Magic bytes at the start of a decompressed `.tbi` file
+const TBI_MAGIC: &[u8; 4] = b"TBI\x01";
+
+/// Bin holding per-reference metadata rather than records
+const METADATA_BIN: u32 = 37450;
+
+/// Each linear index entry covers 2^14 bases
+const LINEAR_SHIFT: u32 = 14;
+
+/// Largest position the tabix binning scheme covers (2^29)
+const MAX_POSITION: u64 = 1 << 29;
+
+/// Chunk of a BGZF file between two virtual offsets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Chunk {
+    start: u64,
+    end: u64,
+}
+
+/// Index for one reference sequence
+#[derive(Debug, Clone, Default)]
+struct ReferenceIndex {
+    bins: Vec<(u32, Vec<Chunk>)>,
+    /// Smallest virtual offset of a record overlapping each 16 kb window
+    linear: Vec<u64>,
+}
+
+/// Parsed tabix (`.tbi`) index
+#[derive(Debug, Clone)]
+pub struct TabixIndex {
+    names: Vec<String>,
+    references: Vec<ReferenceIndex>,
+}
+
+impl TabixIndex {
+    /// Read and parse a `.tbi` file
+    pub fn from_path(path: impl AsRef<Path>) -> VcfResult<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Parse a `.tbi` index from its (BGZF-compressed) bytes
+    pub fn from_bytes(data: &[u8]) -> VcfResult<Self> {
+        let data = gzip::decompress(data)?;
+        let mut input = IndexReader { data: &data };
+
+        if input.bytes(4)? != TBI_MAGIC {
+            return Err(VcfError::InvalidIndex("missing TBI magic".to_string()));
+        }
+        let n_ref = input.count()?;
+        // format, col_seq, col_beg, col_end, meta and skip
+        input.bytes(6 * 4)?;
+        let names_len = input.count()?;
+        let names = input
+            .bytes(names_len)?
+            .split(|&b| b == 0)
+            .filter(|name| !name.is_empty())
+            .map(|name| String::from_utf8_lossy(name).into_owned())
+            .collect::<Vec<_>>();
+        if names.len() != n_ref {
+            return Err(VcfError::InvalidIndex(format!(
+                "{} sequence names for {} references",
+                names.len(),
+                n_ref
+            )));
+        }
+
+        let mut references = Vec::with_capacity(n_ref);
+        for _ in 0..n_ref {
+            let mut reference = ReferenceIndex::default();
+            for _ in 0..input.count()? {
+                let bin = input.u32()?;
+                let chunks = (0..input.count()?)
+                    .map(|_| {
+                        Ok(Chunk {
+                            start: input.u64()?,
+                            end: input.u64()?,
+                        })
+                    })
+                    .collect::<VcfResult<Vec<_>>>()?;
+                if bin != METADATA_BIN {
+                    reference.bins.push((bin, chunks));
+                }
+            }
+            reference.linear = (0..input.count()?)
+                .map(|_| input.u64())
+                .collect::<VcfResult<_>>()?;
+            references.push(reference);
+        }
+
+        Ok(Self { names, references })
+    }
+
+    /// Sequence names in index order
+    pub fn sequence_names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Virtual offset to start reading from for `region`, or `None` if
+    /// no indexed record can overlap it
+    fn start_offset(&self, region: &Region) -> Option<u64> {
+        let idx = self.names.iter().position(|n| *n == region.chrom)?;
+        let reference = &self.references[idx];
+
+        // Tabix bins are 0-based and half-open
+        let beg = region.start.saturating_sub(1).min(MAX_POSITION - 1);
+        let end = region.end.unwrap_or(MAX_POSITION).clamp(beg + 1, MAX_POSITION);
+
+        let window = (beg >> LINEAR_SHIFT) as usize;
+        let min_offset = match reference.linear.len() {
+            0 => 0,
+            n => reference.linear[window.min(n - 1)],
+        };
+
+        let bins = region_bins(beg, end);
+        reference
+            .bins
+            .iter()
+            .filter(|(bin, _)| bins.contains(bin))
+            .flat_map(|(_, chunks)| chunks)
+            .filter(|chunk| chunk.end > min_offset)
+            .map(|chunk| chunk.start.max(min_offset))
+            .min()
+    }
+}
+
+/// Bins that may hold records overlapping `[beg, end)`
+fn region_bins(beg: u64, end: u64) -> Vec<u32> {
+    let end = end - 1;
+    let mut bins = vec![0];
+    for (offset, shift) in [(1, 26), (9, 23), (73, 20), (585, 17), (4681, 14)] {
+        bins.extend((offset + (beg >> shift))..=(offset + (end >> shift)));
+    }
+    bins.into_iter().map(|b| b as u32).collect()
+}
+
+/// Little-endian cursor over decompressed index bytes
+struct IndexReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> IndexReader<'a> {
+    fn bytes(&mut self, n: usize) -> VcfResult<&'a [u8]> {
+        if self.data.len() < n {
+            return Err(VcfError::InvalidIndex("unexpected end of index".to_string()));
+        }
+        let (head, tail) = self.data.split_at(n);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> VcfResult<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> VcfResult<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    /// Non-negative `int32_t` count
+    fn count(&mut self) -> VcfResult<usize> {
+        let n = self.u32()? as i32;
+        usize::try_from(n).map_err(|_| VcfError::InvalidIndex(format!("negative count {}", n)))
+    }
+}
+
+/// Random-access reader for a bgzipped VCF with a tabix index
+///
+/// The index is parsed once and the file kept open, so a reader can
+/// serve many queries. Records match a region by POS, like
+/// [`Region::contains_record`]; the file must be coordinate-sorted, as
+/// tabix requires.
+pub struct IndexedVcfReader {
+    file: File,
+    index: TabixIndex,
+    header: VcfHeader,
+    parser: VcfParser,
+}
+
+impl IndexedVcfReader {
+    /// Open `vcf_path`, using `tbi_path` or else `<vcf_path>.tbi`
+    pub fn open(vcf_path: impl AsRef<Path>, tbi_path: Option<&Path>) -> VcfResult<Self> {
+        let vcf_path = vcf_path.as_ref();
+        let tbi_path = match tbi_path {
+            Some(path) => path.to_path_buf(),
+            None => {
+                let mut path = PathBuf::from(vcf_path).into_os_string();
+                path.push(".tbi");
+                PathBuf::from(path)
+            }
+        };
+
+        let mut parser = VcfParser::new();
+        let header = parser.read_header(gzip::open_vcf(vcf_path)?)?;
+
+        Ok(Self {
+            file: File::open(vcf_path)?,
+            index: TabixIndex::from_path(tbi_path)?,
+            header,
+            parser,
+        })
+    }
+
+    /// Parse records with the options of `parser`
+    pub fn with_parser(mut self, parser: VcfParser) -> Self {
+        self.parser = parser;
+        self
+    }
+
+    /// Header of the VCF
+    pub fn header(&self) -> &VcfHeader {
+        &self.header
+    }
+
+    /// Contigs present in the index
+    pub fn chromosomes(&self) -> &[String] {
+        self.index.sequence_names()
+    }
+
+    /// Records in `region`, in file order
+    pub fn query(&mut self, region: &Region) -> VcfResult<Vec<VcfRecord>> {
+        let mut records = Vec::new();
+        self.for_each(region, |record| {
+            records.push(record);
+            Ok(())
+        })?;
+        Ok(records)
+    }
+
+    /// Number of records in `region`
+    pub fn query_count(&mut self, region: &Region) -> VcfResult<usize> {
+        let mut count = 0;
+        self.for_each(region, |_| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    /// Hand each record in `region` to `on_record` as it is parsed
+    pub fn for_each(
+        &mut self,
+        region: &Region,
+        mut on_record: impl FnMut(VcfRecord) -> VcfResult<()>,
+    ) -> VcfResult<()> {
+        let Some(offset) = self.index.start_offset(region) else {
+            return Ok(());
+        };
+
+        self.file.seek(SeekFrom::Start(offset >> 16))?;
+        let mut reader = BufReader::new(MultiGzDecoder::new(BufReader::new(&mut self.file)));
+        io::copy(&mut (&mut reader).take(offset & 0xffff), &mut io::sink())
+            .map_err(gzip::gzip_error)?;
+
+        for line in reader.lines() {
+            let line = line.map_err(gzip::gzip_error)?;
+            if line.is_empty() {
+                continue;
+            }
+
+            let record = match self.parser.parse_record(&line, &self.header) {
+                Ok(record) => record,
+                Err(e) => {
+                    self.parser.skip_invalid_record(e)?;
+                    continue;
+                }
+            };
+
+            // Sorted input: the region is over once its contig or end is passed
+            if record.chrom != region.chrom || region.end.is_some_and(|end| record.pos > end) {
+                break;
+            }
+            if region.contains_record(&record) {
+                on_record(record)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../tests/data")
+            .join(name)
+    }
+
+    #[test]
+    fn test_region_bins() {
+        assert_eq!(region_bins(0, 1), vec![0, 1, 9, 73, 585, 4681]);
+        // A 16 kb boundary adds the next smallest bin only
+        assert_eq!(region_bins(16383, 16385), vec![0, 1, 9, 73, 585, 4681, 4682]);
+    }
+
+    #[test]
+    fn test_query_matches_full_parse() {
+        let path = fixture("indexed.vcf.gz");
+        let mut reader = IndexedVcfReader::open(&path, None).unwrap();
+        let (_, all) = VcfParser::new()
+            .parse(gzip::open_vcf(&path).unwrap())
+            .unwrap();
+
+        assert_eq!(reader.chromosomes(), ["chr1", "chr2", "chrX"]);
+        assert_eq!(reader.header().samples, ["SAMPLE1", "SAMPLE2"]);
+
+        for locus in ["chr1", "chr1:15000-40000", "chr1:299000-400000", "chr2:1-5000", "chrX"] {
+            let region = Region::parse(locus).unwrap();
+            let expected: Vec<_> = all
+                .iter()
+                .filter(|r| region.contains_record(r))
+                .map(|r| (r.chrom.clone(), r.pos))
+                .collect();
+            let found: Vec<_> = reader
+                .query(&region)
+                .unwrap()
+                .into_iter()
+                .map(|r| (r.chrom, r.pos))
+                .collect();
+
+            assert!(!expected.is_empty(), "{}", locus);
+            assert_eq!(found, expected, "{}", locus);
+            assert_eq!(reader.query_count(&region).unwrap(), expected.len());
+        }
+
+        assert_eq!(reader.query_count(&Region::parse("chr3").unwrap()).unwrap(), 0);
+        assert_eq!(reader.query_count(&Region::parse("chr2:900000").unwrap()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_invalid_index() {
+        let data = std::fs::read(fixture("indexed.vcf.gz")).unwrap();
+
+        assert!(matches!(
+            TabixIndex::from_bytes(&data),
+            Err(VcfError::InvalidIndex(_))
+        ));
+        assert!(IndexedVcfReader::open(fixture("indexed.vcf.gz"), Some(&fixture("missing.tbi")))
+            .is_err());
+    }
+}
@@ -0,0 +1,329 @@
+//! Synthetic VCF Generation
+//!
+//! Reproducible VCF text shaped like real callsets, for load testing and
+//! benchmarks. Enable with the `test-support` feature.
+
+use crate::rng::SplitMix64;
+use std::fmt::Write;
+
+/// Number of chromosomes records are spread over
+const CHROMOSOMES: usize = 22;
+
+/// INFO keys filled in before any synthetic `KEYn` ones:
+/// (ID, Number, Type, Description)
+const INFO_CATALOG: [(&str, &str, &str, &str); 8] = [
+    ("DP", "1", "Integer", "Combined depth across samples"),
+    ("AF", "A", "Float", "Allele frequency"),
+    ("AC", "A", "Integer", "Allele count in genotypes"),
+    ("AN", "1", "Integer", "Total called alleles"),
+    ("MQ", "1", "Float", "RMS mapping quality"),
+    ("QD", "1", "Float", "Variant confidence by depth"),
+    ("FS", "1", "Float", "Phred-scaled strand bias"),
+    ("SOR", "1", "Float", "Symmetric odds ratio of strand bias"),
+];
+
+const BASES: [char; 4] = ['A', 'C', 'G', 'T'];
+
+/// VCF text with `n_records` sorted records over chr1-chr22
+///
+/// Each record carries `info_keys` INFO fields (DP, AF, AC, AN, MQ, QD, FS
+/// and SOR first, then integer `KEYn` fields) and, with samples,
+/// `GT:AD:DP:GQ:PL` for each of `n_samples` columns. Variants are mostly
+/// SNPs with some indels and multi-allelic sites; QUAL, FILTER, IDs and
+/// missing genotypes vary as in typical callsets. The same arguments
+/// always give the same text.
+pub fn gen_vcf(n_records: usize, n_samples: usize, info_keys: usize, seed: u64) -> String {
+    let mut rng = SplitMix64::new(seed);
+    let mut vcf = String::with_capacity(n_records * (80 + info_keys * 8 + n_samples * 24));
+
+    vcf.push_str("##fileformat=VCFv4.2\n");
+    vcf.push_str("##FILTER=<ID=LowQual,Description=\"Low quality\">\n");
+    for chrom in 1..=CHROMOSOMES {
+        let _ = writeln!(
+            vcf,
+            "##contig=<ID=chr{},length={}>",
+            chrom,
+            contig_length(chrom)
+        );
+    }
+    for k in 0..info_keys {
+        let (id, number, kind, description) = info_definition(k);
+        let _ = writeln!(
+            vcf,
+            "##INFO=<ID={},Number={},Type={},Description=\"{}\">",
+            id, number, kind, description
+        );
+    }
+    if n_samples > 0 {
+        vcf.push_str(
+            "##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">\n\
+             ##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">\n\
+             ##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read depth\">\n\
+             ##FORMAT=<ID=GQ,Number=1,Type=Integer,Description=\"Genotype quality\">\n\
+             ##FORMAT=<ID=PL,Number=G,Type=Integer,Description=\"Phred-scaled genotype likelihoods\">\n",
+        );
+    }
+    vcf.push_str("#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO");
+    if n_samples > 0 {
+        vcf.push_str("\tFORMAT");
+        for s in 0..n_samples {
+            let _ = write!(vcf, "\tSAMPLE{}", s + 1);
+        }
+    }
+    vcf.push('\n');
+
+    let mut chrom = 0;
+    let mut pos = 0u64;
+    for i in 0..n_records {
+        let record_chrom = i * CHROMOSOMES / n_records + 1;
+        if record_chrom != chrom {
+            chrom = record_chrom;
+            pos = 0;
+        }
+        pos += 1 + below(&mut rng, 2000);
+        write_record(&mut vcf, &mut rng, chrom, pos, n_samples, info_keys);
+    }
+    vcf
+}
+
+/// Roughly human chromosome lengths, shrinking with the chromosome number
+fn contig_length(chrom: usize) -> u64 {
+    250_000_000 - (chrom as u64 - 1) * 9_000_000
+}
+
+fn info_definition(k: usize) -> (String, &'static str, &'static str, String) {
+    match INFO_CATALOG.get(k) {
+        Some(&(id, number, kind, description)) => {
+            (id.to_string(), number, kind, description.to_string())
+        }
+        None => {
+            let n = k - INFO_CATALOG.len() + 1;
+            (
+                format!("KEY{}", n),
+                "1",
+                "Integer",
+                format!("Synthetic key {}", n),
+            )
+        }
+    }
+}
+
+/// Uniform in `0..n`
+fn below(rng: &mut SplitMix64, n: u64) -> u64 {
+    rng.next_u64() % n
+}
+
+fn random_base(rng: &mut SplitMix64) -> char {
+    BASES[below(rng, 4) as usize]
+}
+
+/// A base other than `base`
+fn other_base(rng: &mut SplitMix64, base: char) -> char {
+    let i = BASES.iter().position(|&b| b == base).unwrap_or(0);
+    BASES[(i + 1 + below(rng, 3) as usize) % 4]
+}
+
+fn write_record(
+    vcf: &mut String,
+    rng: &mut SplitMix64,
+    chrom: usize,
+    pos: u64,
+    n_samples: usize,
+    info_keys: usize,
+) {
+    // 80% SNP, 8% deletion, 7% insertion, 5% multi-allelic SNP
+    let first = random_base(rng);
+    let mut reference = first.to_string();
+    let mut alts: Vec<String> = Vec::with_capacity(2);
+    match below(rng, 100) {
+        0..=79 => alts.push(other_base(rng, first).to_string()),
+        80..=87 => {
+            for _ in 0..1 + below(rng, 6) {
+                reference.push(random_base(rng));
+            }
+            alts.push(first.to_string());
+        }
+        88..=94 => {
+            let mut alt = first.to_string();
+            for _ in 0..1 + below(rng, 6) {
+                alt.push(random_base(rng));
+            }
+            alts.push(alt);
+        }
+        _ => {
+            let a = other_base(rng, first);
+            let mut b = other_base(rng, first);
+            while b == a {
+                b = other_base(rng, first);
+            }
+            alts.push(a.to_string());
+            alts.push(b.to_string());
+        }
+    }
+
+    let _ = write!(vcf, "chr{}\t{}\t", chrom, pos);
+    if below(rng, 10) < 4 {
+        let _ = write!(vcf, "rs{}", 1 + below(rng, 900_000_000));
+    } else {
+        vcf.push('.');
+    }
+    let _ = write!(vcf, "\t{}\t{}\t", reference, alts.join(","));
+
+    let qual = below(rng, 100_000);
+    if qual < 3_000 {
+        vcf.push('.');
+    } else {
+        let _ = write!(vcf, "{}.{:02}", qual / 100, qual % 100);
+    }
+    vcf.push_str(if qual < 8_000 {
+        "\tLowQual\t"
+    } else {
+        "\tPASS\t"
+    });
+
+    let depth = 10 + below(rng, 40 * (n_samples.max(1) as u64));
+    if info_keys == 0 {
+        vcf.push('.');
+    }
+    for k in 0..info_keys {
+        if k > 0 {
+            vcf.push(';');
+        }
+        match INFO_CATALOG.get(k).map(|entry| entry.0) {
+            Some("DP") => {
+                let _ = write!(vcf, "DP={}", depth);
+            }
+            Some("AF") => {
+                vcf.push_str("AF=");
+                for (a, _) in alts.iter().enumerate() {
+                    let sep = if a > 0 { "," } else { "" };
+                    let _ = write!(vcf, "{}0.{:04}", sep, below(rng, 10_000));
+                }
+            }
+            Some("AC") => {
+                vcf.push_str("AC=");
+                for (a, _) in alts.iter().enumerate() {
+                    let sep = if a > 0 { "," } else { "" };
+                    let _ = write!(
+                        vcf,
+                        "{}{}",
+                        sep,
+                        below(rng, 2 * n_samples.max(1) as u64 + 1)
+                    );
+                }
+            }
+            Some("AN") => {
+                let _ = write!(vcf, "AN={}", 2 * n_samples.max(1));
+            }
+            Some(id) => {
+                let value = below(rng, 100_000);
+                let _ = write!(vcf, "{}={}.{:03}", id, value / 1000, value % 1000);
+            }
+            None => {
+                let _ = write!(
+                    vcf,
+                    "KEY{}={}",
+                    k - INFO_CATALOG.len() + 1,
+                    below(rng, 1000)
+                );
+            }
+        }
+    }
+
+    if n_samples > 0 {
+        vcf.push_str("\tGT:AD:DP:GQ:PL");
+        for _ in 0..n_samples {
+            vcf.push('\t');
+            write_sample(vcf, rng, alts.len());
+        }
+    }
+    vcf.push('\n');
+}
+
+/// One `GT:AD:DP:GQ:PL` column for a site with `n_alts` ALT alleles
+fn write_sample(vcf: &mut String, rng: &mut SplitMix64, n_alts: usize) {
+    // 60% hom-ref, 25% het, 10% hom-alt, 5% missing
+    let roll = below(rng, 100);
+    if roll >= 95 {
+        vcf.push_str("./.:.:.:.:.");
+        return;
+    }
+    let alt = 1 + below(rng, n_alts as u64) as usize;
+    let (a, b) = match roll {
+        0..=59 => (0, 0),
+        60..=84 => (0, alt),
+        _ => (alt, alt),
+    };
+    let separator = if below(rng, 10) == 0 { '|' } else { '/' };
+    let _ = write!(vcf, "{}{}{}:", a, separator, b);
+
+    let depth = 5 + below(rng, 60);
+    let mut remaining = depth;
+    for allele in 0..=n_alts {
+        let reads = if allele == n_alts {
+            remaining
+        } else if allele == a || allele == b {
+            below(rng, remaining + 1)
+        } else {
+            below(rng, remaining.min(2) + 1)
+        };
+        remaining -= reads;
+        let sep = if allele > 0 { "," } else { "" };
+        let _ = write!(vcf, "{}{}", sep, reads);
+    }
+    let _ = write!(vcf, ":{}:{}:", depth, below(rng, 100));
+
+    // Number=G: one likelihood per unordered genotype
+    let genotypes = (n_alts + 1) * (n_alts + 2) / 2;
+    for g in 0..genotypes {
+        let sep = if g > 0 { "," } else { "" };
+        let _ = write!(vcf, "{}{}", sep, below(rng, 1000));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    #[test]
+    fn test_gen_vcf_parses_cleanly() {
+        for (n_records, n_samples, info_keys, seed) in [
+            (0, 0, 0, 1),
+            (100, 0, 0, 2),
+            (500, 0, 4, 3),
+            (300, 3, 8, 4),
+            (200, 40, 20, 5),
+        ] {
+            let vcf = gen_vcf(n_records, n_samples, info_keys, seed);
+            let mut parser = VcfParser::new();
+            let (header, records) = parser.parse_str(&vcf).unwrap();
+
+            let params = (n_records, n_samples, info_keys, seed);
+            assert_eq!(records.len(), n_records, "{:?}", params);
+            assert_eq!(parser.warning_total(), 0, "{:?}", params);
+            assert_eq!(header.samples.len(), n_samples);
+            assert_eq!(header.info_fields.len(), info_keys);
+            for record in &records {
+                assert_eq!(record.info.len(), info_keys);
+                assert_eq!(record.samples.len(), n_samples);
+            }
+            assert!(records
+                .windows(2)
+                .all(|w| w[0].chrom != w[1].chrom || w[0].pos < w[1].pos));
+        }
+    }
+
+    #[test]
+    fn test_gen_vcf_is_seeded() {
+        assert_eq!(gen_vcf(50, 2, 3, 9), gen_vcf(50, 2, 3, 9));
+        assert_ne!(gen_vcf(50, 2, 3, 9), gen_vcf(50, 2, 3, 10));
+
+        let (_, records) = VcfParser::new().parse_str(&gen_vcf(1000, 0, 0, 7)).unwrap();
+        let chromosomes: std::collections::HashSet<_> =
+            records.iter().map(|r| r.chrom.as_str()).collect();
+        assert_eq!(chromosomes.len(), CHROMOSOMES);
+        assert!(records.iter().any(|r| r.alternate.len() == 2));
+        assert!(records.iter().any(|r| r.qual.is_none()));
+    }
+}
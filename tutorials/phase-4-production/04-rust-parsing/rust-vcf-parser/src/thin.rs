@@ -0,0 +1,205 @@
+//! Thinning for Plots
+//!
+//! Pick a subset of records to draw when there are far more than a plot
+//! can usefully show. Every strategy returns kept record indices in
+//! ascending order.
+
+use crate::rng::SplitMix64;
+use crate::types::VcfRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Seed [`thin_for_plot`] uses when it samples at random
+pub const PLOT_THIN_SEED: u64 = 0x5EED;
+
+/// How [`thin_records`] picks records
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ThinStrategy {
+    /// Every nth record, starting with the first
+    EveryNth(usize),
+    /// Up to `per_window` records in each `window`-bp bin of each
+    /// chromosome, highest QUAL first (records without QUAL last, ties by
+    /// input order)
+    PerWindowMax { window: u64, per_window: usize },
+    /// `fraction` of the records, chosen at random but reproducibly for a
+    /// given `seed`
+    RandomFraction { fraction: f64, seed: u64 },
+}
+
+/// Indices of the records `strategy` keeps, ascending
+pub fn thin_records(records: &[VcfRecord], strategy: ThinStrategy) -> Vec<usize> {
+    match strategy {
+        ThinStrategy::EveryNth(n) => (0..records.len()).step_by(n.max(1)).collect(),
+        ThinStrategy::PerWindowMax { window, per_window } => {
+            per_window_max(records, window.max(1), per_window)
+        }
+        ThinStrategy::RandomFraction { fraction, seed } => {
+            let n = records.len();
+            let k = (fraction.clamp(0.0, 1.0) * n as f64).round() as usize;
+            random_subset(n, k, seed)
+        }
+    }
+}
+
+fn per_window_max(records: &[VcfRecord], window: u64, per_window: usize) -> Vec<usize> {
+    let mut windows: HashMap<(&str, u64), Vec<usize>> = HashMap::new();
+    for (i, record) in records.iter().enumerate() {
+        let bin = record.pos.saturating_sub(1) / window;
+        windows.entry((&record.chrom, bin)).or_default().push(i);
+    }
+
+    let mut kept = Vec::new();
+    for mut members in windows.into_values() {
+        // Stable, so equal QUALs keep input order
+        members.sort_by(|&a, &b| {
+            let qual = |i: usize| records[i].qual.unwrap_or(f64::NEG_INFINITY);
+            qual(b).total_cmp(&qual(a))
+        });
+        kept.extend(members.into_iter().take(per_window));
+    }
+    kept.sort_unstable();
+    kept
+}
+
+/// Exactly `k` of `0..n`, ascending (Knuth's selection sampling)
+pub(crate) fn random_subset(n: usize, k: usize, seed: u64) -> Vec<usize> {
+    let mut rng = SplitMix64::new(seed);
+    let mut kept = Vec::with_capacity(k);
+    for i in 0..n {
+        if kept.len() == k {
+            break;
+        }
+        let needed = (k - kept.len()) as f64;
+        if ((n - i) as f64) * rng.next_f64() < needed {
+            kept.push(i);
+        }
+    }
+    kept
+}
+
+/// About `target_points` record indices spread like the full set
+///
+/// Keeps everything when there are few enough records. Otherwise records
+/// sorted by position get [`ThinStrategy::EveryNth`], which spaces points
+/// evenly; unsorted ones get a seeded [`ThinStrategy::RandomFraction`] of
+/// exactly `target_points`. Both keep the variant density shape, which
+/// per-window caps would flatten.
+pub fn thin_for_plot(records: &[VcfRecord], target_points: usize) -> Vec<usize> {
+    let n = records.len();
+    if n <= target_points {
+        return (0..n).collect();
+    }
+
+    let strategy = if is_position_sorted(records) {
+        ThinStrategy::EveryNth(n.div_ceil(target_points.max(1)))
+    } else {
+        ThinStrategy::RandomFraction {
+            fraction: target_points as f64 / n as f64,
+            seed: PLOT_THIN_SEED,
+        }
+    };
+    thin_records(records, strategy)
+}
+
+/// Whether each chromosome's records are contiguous and in position order
+fn is_position_sorted(records: &[VcfRecord]) -> bool {
+    let mut finished: Vec<&str> = Vec::new();
+    records.windows(2).all(|pair| {
+        let (a, b) = (&pair[0], &pair[1]);
+        if a.chrom == b.chrom {
+            return a.pos <= b.pos;
+        }
+        finished.push(&a.chrom);
+        !finished.contains(&b.chrom.as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    fn records(lines: &[(&str, u64, &str)]) -> Vec<VcfRecord> {
+        let mut vcf =
+            String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+        for (chrom, pos, qual) in lines {
+            vcf += &format!("{}\t{}\t.\tA\tG\t{}\tPASS\t.\n", chrom, pos, qual);
+        }
+        VcfParser::new().parse_str(&vcf).unwrap().1
+    }
+
+    #[test]
+    fn test_every_nth() {
+        let records = records(&[("chr1", 1, "."); 7]);
+        assert_eq!(thin_records(&records, ThinStrategy::EveryNth(3)), [0, 3, 6]);
+        assert_eq!(thin_records(&records, ThinStrategy::EveryNth(0)).len(), 7);
+    }
+
+    #[test]
+    fn test_per_window_max() {
+        let records = records(&[
+            ("chr1", 1, "10"),
+            ("chr1", 50, "90"),
+            ("chr1", 100, "."),
+            ("chr1", 99, "40"),
+            ("chr1", 101, "5"),
+            ("chr2", 10, "1"),
+            ("chr1", 150, "7"),
+            ("chr1", 120, "7"),
+        ]);
+
+        // chr1:1-100 keeps QUAL 90 and 40 and chr1:101-200 both QUAL 7s;
+        // with one per window the earlier QUAL 7 record wins the tie
+        let strategy = ThinStrategy::PerWindowMax {
+            window: 100,
+            per_window: 2,
+        };
+        assert_eq!(thin_records(&records, strategy), [1, 3, 5, 6, 7]);
+
+        let strategy = ThinStrategy::PerWindowMax {
+            window: 100,
+            per_window: 1,
+        };
+        assert_eq!(thin_records(&records, strategy), [1, 5, 6]);
+    }
+
+    #[test]
+    fn test_random_fraction_is_deterministic() {
+        let records = records(&[("chr1", 1, "."); 1000]);
+        let sample = |seed| {
+            thin_records(
+                &records,
+                ThinStrategy::RandomFraction {
+                    fraction: 0.1,
+                    seed,
+                },
+            )
+        };
+
+        let kept = sample(42);
+        assert_eq!(kept.len(), 100);
+        assert!(kept.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(kept, sample(42));
+        assert_ne!(kept, sample(43));
+    }
+
+    #[test]
+    fn test_thin_for_plot() {
+        let sorted: Vec<(&str, u64, &str)> = (1..=100).map(|pos| ("chr1", pos, ".")).collect();
+        let sorted = records(&sorted);
+        assert_eq!(thin_for_plot(&sorted, 200).len(), 100);
+        assert_eq!(
+            thin_for_plot(&sorted, 25),
+            (0..100).step_by(4).collect::<Vec<_>>()
+        );
+
+        let mut unsorted = sorted.clone();
+        unsorted.swap(0, 99);
+        let kept = thin_for_plot(&unsorted, 25);
+        assert_eq!(kept.len(), 25);
+        assert_eq!(kept, thin_for_plot(&unsorted, 25));
+
+        let interleaved = records(&[("chr1", 1, "."), ("chr2", 1, "."), ("chr1", 2, ".")]);
+        assert!(!is_position_sorted(&interleaved));
+    }
+}
@@ -0,0 +1,293 @@
+//! Trio Analysis
+//!
+//! De novo variant detection for a child and both parents, with the
+//! usual quality gates on child GQ, parental depth and parental alt reads.
+
+use crate::types::{FilterStatus, Genotype, Pedigree, SampleData, VcfHeader, VcfRecord};
+use serde::{Deserialize, Serialize};
+
+/// Sample columns of a child and its parents
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trio {
+    pub child: usize,
+    pub father: usize,
+    pub mother: usize,
+}
+
+impl Trio {
+    /// Trio from sample names; `None` if any is not in the header
+    pub fn new(header: &VcfHeader, child: &str, father: &str, mother: &str) -> Option<Self> {
+        let column = |name: &str| header.samples.iter().position(|s| s == name);
+        Some(Self {
+            child: column(child)?,
+            father: column(father)?,
+            mother: column(mother)?,
+        })
+    }
+
+    /// Trio from a `##PEDIGREE` line naming the child (`ID` or `Child`),
+    /// `Father` and `Mother`
+    pub fn from_pedigree(header: &VcfHeader, pedigree: &Pedigree) -> Option<Self> {
+        let child = pedigree.get("ID").or_else(|| pedigree.get("Child"))?;
+        Self::new(
+            header,
+            child,
+            pedigree.get("Father")?,
+            pedigree.get("Mother")?,
+        )
+    }
+
+    /// Every complete trio among the header's `##PEDIGREE` lines
+    pub fn from_header(header: &VcfHeader) -> Vec<Self> {
+        header
+            .pedigrees
+            .iter()
+            .filter_map(|pedigree| Self::from_pedigree(header, pedigree))
+            .collect()
+    }
+}
+
+/// Gates for [`find_de_novo`]; `None` disables a gate
+#[derive(Debug, Clone)]
+pub struct DeNovoOptions {
+    /// Minimum child GQ
+    pub min_child_gq: Option<i64>,
+    /// Minimum DP in each parent
+    pub min_parent_depth: Option<i64>,
+    /// Maximum fraction of a parent's AD supporting the candidate allele
+    pub max_parent_alt_fraction: Option<f64>,
+    /// Only consider records with FILTER = PASS
+    pub pass_only: bool,
+}
+
+impl Default for DeNovoOptions {
+    fn default() -> Self {
+        Self {
+            min_child_gq: Some(20),
+            min_parent_depth: Some(10),
+            max_parent_alt_fraction: Some(0.05),
+            pass_only: false,
+        }
+    }
+}
+
+/// Which gates a call was checked against
+///
+/// An enabled gate is skipped, and left `false` here, when the FORMAT
+/// field it needs is missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeNovoGates {
+    pub child_gq: bool,
+    pub parent_depth: bool,
+    pub parent_alt_fraction: bool,
+    pub pass: bool,
+}
+
+/// A candidate de novo allele that passed every applied gate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeNovoCall {
+    /// Index of the record in the input
+    pub record: usize,
+    /// The de novo ALT allele (1-based, as in GT)
+    pub allele: usize,
+    pub child_genotype: Genotype,
+    pub gates: DeNovoGates,
+}
+
+/// Outcome of [`find_de_novo`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeNovoResult {
+    pub calls: Vec<DeNovoCall>,
+    /// Sites skipped because a trio member's genotype is missing or partial
+    pub missing_genotype_sites: usize,
+    /// Candidate alleles rejected by a gate
+    pub rejected: usize,
+}
+
+/// Find alleles carried by the child but by neither parent
+///
+/// Each ALT allele of a multi-allelic site is a separate candidate.
+pub fn find_de_novo(records: &[VcfRecord], trio: &Trio, options: &DeNovoOptions) -> DeNovoResult {
+    let mut result = DeNovoResult::default();
+
+    for (index, record) in records.iter().enumerate() {
+        if options.pass_only && record.filter != FilterStatus::Pass {
+            continue;
+        }
+        let member = |column: usize| {
+            let sample = record.samples.get(column)?;
+            let genotype = sample.genotype.as_ref()?;
+            genotype
+                .alleles
+                .iter()
+                .all(Option::is_some)
+                .then_some((sample, genotype))
+        };
+        let (Some(child), Some(father), Some(mother)) =
+            (member(trio.child), member(trio.father), member(trio.mother))
+        else {
+            result.missing_genotype_sites += 1;
+            continue;
+        };
+
+        for allele in 1..=record.alternate.len() {
+            let carries = |genotype: &Genotype| {
+                genotype
+                    .alleles
+                    .iter()
+                    .any(|a| a.is_some_and(|a| a as usize == allele))
+            };
+            if !carries(child.1) || carries(father.1) || carries(mother.1) {
+                continue;
+            }
+
+            match check_gates(child.0, [father.0, mother.0], allele, options) {
+                Some(mut gates) => {
+                    gates.pass = options.pass_only;
+                    result.calls.push(DeNovoCall {
+                        record: index,
+                        allele,
+                        child_genotype: child.1.clone(),
+                        gates,
+                    });
+                }
+                None => result.rejected += 1,
+            }
+        }
+    }
+
+    result
+}
+
+/// Gates applied, or `None` if one failed
+fn check_gates(
+    child: &SampleData,
+    parents: [&SampleData; 2],
+    allele: usize,
+    options: &DeNovoOptions,
+) -> Option<DeNovoGates> {
+    let mut gates = DeNovoGates::default();
+
+    if let (Some(min), Some(gq)) = (options.min_child_gq, child.int_field("GQ")) {
+        gates.child_gq = true;
+        if gq < min {
+            return None;
+        }
+    }
+
+    for parent in parents {
+        if let (Some(min), Some(depth)) = (options.min_parent_depth, parent.int_field("DP")) {
+            gates.parent_depth = true;
+            if depth < min {
+                return None;
+            }
+        }
+
+        if let (Some(max), Some(fraction)) = (
+            options.max_parent_alt_fraction,
+            alt_fraction(parent, allele),
+        ) {
+            gates.parent_alt_fraction = true;
+            if fraction > max {
+                return None;
+            }
+        }
+    }
+
+    Some(gates)
+}
+
+/// Fraction of a sample's AD reads supporting `allele`, if AD covers it
+/// and has any reads
+fn alt_fraction(sample: &SampleData, allele: usize) -> Option<f64> {
+    let depths = sample.int_array_field("AD")?;
+    let alt = (*depths.get(allele)?)?;
+    let total: i64 = depths.iter().flatten().sum();
+    (total > 0).then(|| alt as f64 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const TRIO_VCF: &str = "##fileformat=VCFv4.2
+##PEDIGREE=<ID=KID,Father=DAD,Mother=MUM>
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=GQ,Number=1,Type=Integer,Description=\"Genotype Quality\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read Depth\">
+##FORMAT=<ID=AD,Number=R,Type=Integer,Description=\"Allelic depths\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tDAD\tMUM\tKID
+chr1\t100\t.\tA\tG\t50\tPASS\t.\tGT:GQ:DP:AD\t0/0:60:30:30,0\t0/0:60:28:27,1\t0/1:55:29:15,14
+chr1\t200\t.\tC\tT\t50\tPASS\t.\tGT:GQ:DP:AD\t0/0:40:25:20,5\t0/0:60:30:30,0\t0/1:55:30:16,14
+chr1\t300\t.\tG\tA\t50\tPASS\t.\tGT:GQ:DP:AD\t0/1:60:30:15,15\t0/0:60:30:30,0\t0/1:60:30:14,16
+chr1\t400\t.\tT\tC\t50\tPASS\t.\tGT:GQ:DP:AD\t0/0:60:30:30,0\t./.:0:0:0,0\t0/1:60:30:14,16
+chr1\t500\t.\tA\tG,T\t50\tPASS\t.\tGT:GQ:DP:AD\t0/1:60:30:15,15,0\t0/0:60:30:30,0,0\t1/2:60:30:0,14,16
+chr1\t600\t.\tG\tC\t50\tPASS\t.\tGT:GQ:DP:AD\t0/0:60:30:30,0\t0/0:60:30:30,0\t0/1:12:30:15,15
+chr1\t700\t.\tC\tG\t50\tPASS\t.\tGT:GQ:DP:AD\t0/0:60:6:6,0\t0/0:60:30:30,0\t0/1:60:30:15,15
+chr1\t800\t.\tT\tA\t50\tlowQ\t.\tGT\t0/0\t0/0\t0/1
+";
+
+    fn parse() -> (VcfHeader, Vec<VcfRecord>) {
+        VcfParser::new().parse_str(TRIO_VCF).unwrap()
+    }
+
+    #[test]
+    fn test_trio_from_pedigree() {
+        let (header, _) = parse();
+        let trios = Trio::from_header(&header);
+
+        assert_eq!(
+            trios,
+            [Trio {
+                child: 2,
+                father: 0,
+                mother: 1
+            }]
+        );
+        assert_eq!(Trio::new(&header, "KID", "DAD", "NOBODY"), None);
+    }
+
+    #[test]
+    fn test_find_de_novo() {
+        let (header, records) = parse();
+        let trio = Trio::new(&header, "KID", "DAD", "MUM").unwrap();
+
+        let result = find_de_novo(&records, &trio, &DeNovoOptions::default());
+        let found: Vec<(u64, usize)> = result
+            .calls
+            .iter()
+            .map(|call| (records[call.record].pos, call.allele))
+            .collect();
+        // 100 is clean, 500 allele T is new while G came from DAD, and 800
+        // has no FORMAT fields to gate on; 200 fails DAD's AD, 600 the
+        // child GQ and 700 DAD's depth; 300 is inherited
+        assert_eq!(found, [(100, 1), (500, 2), (800, 1)]);
+        assert_eq!(result.rejected, 3);
+        assert_eq!(result.missing_genotype_sites, 1);
+
+        let clean = &result.calls[0];
+        assert_eq!(clean.child_genotype.alleles, [Some(0), Some(1)]);
+        assert_eq!(
+            clean.gates,
+            DeNovoGates {
+                child_gq: true,
+                parent_depth: true,
+                parent_alt_fraction: true,
+                pass: false,
+            }
+        );
+        assert_eq!(result.calls[2].gates, DeNovoGates::default());
+
+        // Loosening the AD gate lets 200 through; PASS-only drops 800
+        let options = DeNovoOptions {
+            max_parent_alt_fraction: Some(0.25),
+            pass_only: true,
+            ..Default::default()
+        };
+        let result = find_de_novo(&records, &trio, &options);
+        let positions: Vec<u64> = result.calls.iter().map(|c| records[c.record].pos).collect();
+        assert_eq!(positions, [100, 200, 500]);
+        assert!(result.calls.iter().all(|c| c.gates.pass));
+    }
+}
@@ -4,6 +4,23 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// INFO key; the parser shares one allocation between every record that
+/// uses the same key. Look entries up by `&str`: `record.info.get("DP")`.
+pub type InfoKey = Arc<str>;
+
+/// Hasher for INFO maps: aHash with the default `ahash` feature
+#[cfg(feature = "ahash")]
+pub type InfoHasher = ahash::RandomState;
+
+/// Hasher for INFO maps: the standard SipHash without the `ahash` feature
+#[cfg(not(feature = "ahash"))]
+pub type InfoHasher = std::collections::hash_map::RandomState;
+
+/// INFO key-value pairs of a record; serialized as a map of plain strings
+pub type InfoMap = HashMap<InfoKey, InfoValue, InfoHasher>;
 
 /// Represents a VCF file header
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,7 +46,31 @@ pub struct VcfHeader {
     /// Sample names from header line
     pub samples: Vec<String>,
     
-    /// Raw meta-information lines
+    /// `##SAMPLE=<ID=...>` lines
+    #[serde(default)]
+    pub sample_metadata: Vec<SampleMetadata>,
+    
+    /// `##PEDIGREE=<...>` lines
+    #[serde(default)]
+    pub pedigrees: Vec<Pedigree>,
+    
+    /// Tumor sample of a somatic callset, from `##tumor_sample` or a
+    /// `##SAMPLE=<ID=TUMOR,...>` line
+    #[serde(default)]
+    pub tumor_sample: Option<String>,
+    
+    /// Matched normal sample, from `##normal_sample` or a
+    /// `##SAMPLE=<ID=NORMAL,...>` line
+    #[serde(default)]
+    pub normal_sample: Option<String>,
+    
+    /// Other `##key=value` lines, in file order, e.g. `source` or
+    /// `GATKCommandLine`; the value is kept verbatim
+    #[serde(default)]
+    pub other_meta: Vec<(String, String)>,
+    
+    /// Raw meta-information lines, in file order; the writer reproduces
+    /// these exactly when present
     pub meta_lines: Vec<String>,
 }
 
@@ -43,16 +84,124 @@ impl Default for VcfHeader {
             format_fields: Vec::new(),
             filters: Vec::new(),
             samples: Vec::new(),
+            sample_metadata: Vec::new(),
+            pedigrees: Vec::new(),
+            tumor_sample: None,
+            normal_sample: None,
+            other_meta: Vec::new(),
             meta_lines: Vec::new(),
         }
     }
 }
 
+impl VcfHeader {
+    /// Add contig entries for the chromosomes of `records` that have none
+    ///
+    /// New entries are appended in first-seen order with the largest
+    /// position observed as their length, and flagged as estimated;
+    /// estimated entries already present are lengthened as needed.
+    /// Declared lengths are left alone.
+    pub fn build_contigs_from_records(&mut self, records: &[VcfRecord]) {
+        let mut index: HashMap<String, usize> = self
+            .contigs
+            .iter()
+            .enumerate()
+            .map(|(i, contig)| (contig.id.clone(), i))
+            .collect();
+
+        for record in records {
+            match index.get(&record.chrom) {
+                Some(&i) => {
+                    let contig = &mut self.contigs[i];
+                    if contig.estimated {
+                        contig.length = contig.length.max(Some(record.pos));
+                    }
+                }
+                None => {
+                    index.insert(record.chrom.clone(), self.contigs.len());
+                    self.contigs.push(ContigInfo {
+                        id: record.chrom.clone(),
+                        length: Some(record.pos),
+                        estimated: true,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Records whose chromosome has no contig entry, or whose position is
+    /// beyond the contig's declared length
+    pub fn validate_positions_against_contigs(&self, records: &[VcfRecord]) -> Vec<ValidationIssue> {
+        let lengths: HashMap<&str, Option<u64>> = self
+            .contigs
+            .iter()
+            .map(|contig| (contig.id.as_str(), contig.length))
+            .collect();
+
+        records
+            .iter()
+            .enumerate()
+            .filter_map(|(i, record)| {
+                let kind = match lengths.get(record.chrom.as_str()) {
+                    None => ValidationIssueKind::UndeclaredContig,
+                    Some(Some(length)) if record.pos > *length => {
+                        ValidationIssueKind::BeyondContigEnd { length: *length }
+                    }
+                    Some(_) => return None,
+                };
+                Some(ValidationIssue {
+                    record: i,
+                    chrom: record.chrom.clone(),
+                    pos: record.pos,
+                    kind,
+                })
+            })
+            .collect()
+    }
+}
+
 /// Contig (chromosome) information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContigInfo {
     pub id: String,
     pub length: Option<u64>,
+    /// Length inferred from records rather than declared in the header
+    #[serde(default)]
+    pub estimated: bool,
+}
+
+/// A record that does not fit the header's contig dictionary
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    /// Index of the record in the slice that was checked
+    pub record: usize,
+    pub chrom: String,
+    pub pos: u64,
+    pub kind: ValidationIssueKind,
+}
+
+/// What is wrong with a [`ValidationIssue`]'s record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ValidationIssueKind {
+    /// The chromosome has no `##contig` entry
+    UndeclaredContig,
+    /// The position is past the contig's declared length
+    BeyondContigEnd { length: u64 },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ValidationIssueKind::UndeclaredContig => {
+                write!(f, "contig {} is not declared in the header", self.chrom)
+            }
+            ValidationIssueKind::BeyondContigEnd { length } => write!(
+                f,
+                "position {} is beyond the end of {} (length {})",
+                self.pos, self.chrom, length
+            ),
+        }
+    }
 }
 
 /// INFO field definition from header
@@ -80,6 +229,43 @@ pub struct FilterDefinition {
     pub description: String,
 }
 
+/// `##SAMPLE=<ID=...>` metadata for one sample
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SampleMetadata {
+    pub id: String,
+    /// Fields other than `ID`, in file order, with quotes removed
+    pub fields: Vec<(String, String)>,
+}
+
+impl SampleMetadata {
+    /// Value of a field other than `ID`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        field(&self.fields, key)
+    }
+}
+
+/// A `##PEDIGREE=<...>` line, e.g. `<ID=Child,Father=Dad,Mother=Mum>` or
+/// `<Derived=Tumor,Original=Normal>`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Pedigree {
+    /// All fields, in file order, with quotes removed
+    pub fields: Vec<(String, String)>,
+}
+
+impl Pedigree {
+    /// Value of a field
+    pub fn get(&self, key: &str) -> Option<&str> {
+        field(&self.fields, key)
+    }
+}
+
+fn field<'a>(fields: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    fields
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
 /// Represents a single VCF variant record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VcfRecord {
@@ -89,7 +275,8 @@ pub struct VcfRecord {
     /// 1-based position
     pub pos: u64,
     
-    /// Variant identifier (e.g., rs number)
+    /// Variant identifier (e.g., rs number), as written; several IDs are
+    /// kept as one `;`-separated string, see [`VcfRecord::ids`]
     pub id: Option<String>,
     
     /// Reference allele
@@ -105,10 +292,38 @@ pub struct VcfRecord {
     pub filter: FilterStatus,
     
     /// INFO field key-value pairs
-    pub info: HashMap<String, InfoValue>,
+    pub info: InfoMap,
     
     /// Sample genotypes and data
     pub samples: Vec<SampleData>,
+
+    /// Original text of QUAL and float INFO values, if the parser kept it
+    #[serde(default)]
+    pub float_text: Option<Box<FloatText>>,
+}
+
+/// QUAL and float INFO values as written in the input
+///
+/// Parsers with `keep_float_text` set attach this to each record, so a
+/// writer can reproduce `30.10` rather than `30.1`. The parsed numbers
+/// are unaffected and remain what comparisons use.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FloatText {
+    /// QUAL column, if it held a number
+    pub qual: Option<String>,
+    /// INFO entries whose value parsed as a Float or FloatArray
+    pub info: Vec<(InfoKey, String)>,
+}
+
+impl FloatText {
+    /// Original text of an INFO value; the last one wins for a repeated key
+    pub fn info(&self, key: &str) -> Option<&str> {
+        self.info
+            .iter()
+            .rev()
+            .find(|(k, _)| &**k == key)
+            .map(|(_, text)| text.as_str())
+    }
 }
 
 impl VcfRecord {
@@ -122,11 +337,22 @@ impl VcfRecord {
             alternate: alternate.into_iter().map(String::from).collect(),
             qual: None,
             filter: FilterStatus::Pass,
-            info: HashMap::new(),
+            info: InfoMap::default(),
             samples: Vec::new(),
+            float_text: None,
         }
     }
 
+    /// The IDs in the ID column, which may list several separated by `;`
+    /// (`rs1;rs2`); none for a missing ID
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.id
+            .as_deref()
+            .unwrap_or_default()
+            .split(';')
+            .filter(|id| !id.is_empty() && *id != ".")
+    }
+    
     /// Check if variant is a SNP (single nucleotide polymorphism)
     pub fn is_snp(&self) -> bool {
         self.reference.len() == 1 
@@ -188,12 +414,100 @@ pub struct SampleData {
     /// Genotype (e.g., "0/1", "1|1")
     pub genotype: Option<Genotype>,
     
+    /// Whether FORMAT gave the sample a GT value, even `.` or `./.`;
+    /// tells a missing call from no GT at all when `genotype` is `None`
+    #[serde(default)]
+    pub gt_present: bool,
+    
     /// Additional format fields
     pub fields: HashMap<String, String>,
 }
 
+impl SampleData {
+    /// GT with half calls read under `policy`
+    pub fn genotype_state(&self, policy: HalfCallPolicy) -> GenotypeState {
+        match &self.genotype {
+            Some(genotype) => genotype
+                .clone()
+                .with_half_call_policy(policy)
+                .map_or(GenotypeState::Missing, GenotypeState::Called),
+            None if self.gt_present => GenotypeState::Missing,
+            None => GenotypeState::NotProvided,
+        }
+    }
+
+    /// FORMAT value as an integer, e.g. DP or GQ; `None` if absent, `.`
+    /// or not a number
+    pub fn int_field(&self, key: &str) -> Option<i64> {
+        self.fields.get(key)?.parse().ok()
+    }
+
+    /// Comma-separated FORMAT value as integers, e.g. AD; `.` and
+    /// non-numeric elements are `None`
+    pub fn int_array_field(&self, key: &str) -> Option<Vec<Option<i64>>> {
+        let value = self.fields.get(key)?;
+        if value == "." {
+            return None;
+        }
+        Some(value.split(',').map(|v| v.parse().ok()).collect())
+    }
+
+    /// Variant allele fraction of allele `alt_index` (as numbered in GT):
+    /// its AD reads over the AD total, or over DP when AD has missing
+    /// elements or no reads; `None` without AD for the allele or any depth
+    pub fn vaf(&self, alt_index: usize) -> Option<f64> {
+        let depths = self.int_array_field("AD")?;
+        let alt = (*depths.get(alt_index)?)?;
+        let total: Option<i64> = depths.iter().copied().sum();
+        let total = match total {
+            Some(total) if total > 0 => total,
+            _ => self.int_field("DP")?,
+        };
+        (total > 0).then(|| alt as f64 / total as f64)
+    }
+
+    /// For a diploid het, the higher allele's share of the reads for the
+    /// two called alleles (0.5 is balanced); `None` otherwise
+    pub fn allele_balance(&self) -> Option<f64> {
+        let genotype = self.genotype.as_ref()?;
+        let [Some(a), Some(b)] = genotype.alleles[..] else {
+            return None;
+        };
+        if a == b {
+            return None;
+        }
+        let depths = self.int_array_field("AD")?;
+        let low = (*depths.get(a.min(b) as usize)?)?;
+        let high = (*depths.get(a.max(b) as usize)?)?;
+        (low + high > 0).then(|| high as f64 / (low + high) as f64)
+    }
+}
+
+/// How a half call, a genotype with some but not all alleles missing
+/// such as `./1` or `0/.`, is read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HalfCallPolicy {
+    /// The whole call is missing
+    #[default]
+    Missing,
+    /// The called alleles are a call of lower ploidy: `./1` is `1`
+    Haploid,
+    /// Missing alleles are REF: `./1` is `0/1`
+    Reference,
+}
+
+/// A sample's GT, with a missing call told apart from no GT at all
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GenotypeState {
+    /// FORMAT has no GT for the sample
+    NotProvided,
+    /// GT is `.`, `./.` or the like, or a half call read as missing
+    Missing,
+    Called(Genotype),
+}
+
 /// Genotype representation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Genotype {
     /// Allele indices (0 = ref, 1+ = alt)
     pub alleles: Vec<Option<u8>>,
@@ -204,8 +518,11 @@ pub struct Genotype {
 
 impl Genotype {
     /// Parse genotype string (e.g., "0/1", "1|1", "./.")
+    ///
+    /// A call with every allele missing, at any ploidy, is `None`. Half
+    /// calls are kept as written; see [`Genotype::parse_with`].
     pub fn parse(s: &str) -> Option<Self> {
-        if s == "." || s == "./." || s == ".|." {
+        if s.split(['/', '|']).all(|a| a == ".") {
             return None;
         }
 
@@ -226,6 +543,35 @@ impl Genotype {
         Some(Self { alleles, phased })
     }
 
+    /// Parse with half calls read under `policy`; `None` for a missing call
+    pub fn parse_with(s: &str, policy: HalfCallPolicy) -> Option<Self> {
+        Self::parse(s)?.with_half_call_policy(policy)
+    }
+
+    /// Whether some but not all alleles are missing
+    pub fn is_half_call(&self) -> bool {
+        self.alleles.iter().any(Option::is_none) && self.alleles.iter().any(Option::is_some)
+    }
+
+    /// This genotype with any missing allele resolved by `policy`; `None`
+    /// if that leaves no call
+    pub fn with_half_call_policy(mut self, policy: HalfCallPolicy) -> Option<Self> {
+        if self.alleles.iter().all(Option::is_some) {
+            return Some(self);
+        }
+        match policy {
+            HalfCallPolicy::Missing => return None,
+            HalfCallPolicy::Haploid => self.alleles.retain(Option::is_some),
+            HalfCallPolicy::Reference if self.is_half_call() => {
+                for allele in &mut self.alleles {
+                    allele.get_or_insert(0);
+                }
+            }
+            HalfCallPolicy::Reference => return None,
+        }
+        (!self.alleles.is_empty()).then_some(self)
+    }
+
     /// Check if genotype is homozygous reference
     pub fn is_hom_ref(&self) -> bool {
         self.alleles.iter().all(|a| *a == Some(0))
@@ -233,14 +579,20 @@ impl Genotype {
 
     /// Check if genotype is heterozygous
     pub fn is_het(&self) -> bool {
-        let non_missing: Vec<_> = self.alleles.iter().filter_map(|a| *a).collect();
-        non_missing.len() >= 2 && non_missing.iter().any(|a| *a != non_missing[0])
+        let mut non_missing = self.alleles.iter().filter_map(|a| *a);
+        match non_missing.next() {
+            Some(first) => non_missing.any(|a| a != first),
+            None => false,
+        }
     }
 
     /// Check if genotype is homozygous alternate
     pub fn is_hom_alt(&self) -> bool {
-        let non_missing: Vec<_> = self.alleles.iter().filter_map(|a| *a).collect();
-        !non_missing.is_empty() && non_missing.iter().all(|a| *a > 0 && *a == non_missing[0])
+        let mut non_missing = self.alleles.iter().filter_map(|a| *a);
+        match non_missing.next() {
+            Some(first) => first > 0 && non_missing.all(|a| a == first),
+            None => false,
+        }
     }
 }
 
@@ -265,6 +617,25 @@ pub struct VcfStats {
     pub passed_filter: usize,
     pub failed_filter: usize,
     pub chromosomes: Vec<String>,
+    /// SNP alleles that are A<->G or C<->T changes
+    #[serde(default)]
+    pub transitions: usize,
+    /// All other single-base substitutions between A, C, G and T
+    #[serde(default)]
+    pub transversions: usize,
+    /// Counts per chromosome, in the same order as `chromosomes`
+    #[serde(default)]
+    pub per_chromosome: Vec<ChromosomeStats>,
+}
+
+/// Record counts for one chromosome
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChromosomeStats {
+    pub chrom: String,
+    pub records: usize,
+    pub snps: usize,
+    /// Insertions, deletions and complex indels
+    pub indels: usize,
 }
 
 impl VcfStats {
@@ -275,8 +646,18 @@ impl VcfStats {
     pub fn update(&mut self, record: &VcfRecord) {
         self.total_records += 1;
         
-        match record.variant_type() {
-            VariantType::Snp => self.snps += 1,
+        let variant_type = record.variant_type();
+        match variant_type {
+            VariantType::Snp => {
+                self.snps += 1;
+                for alt in &record.alternate {
+                    match substitution_class(&record.reference, alt) {
+                        Some(true) => self.transitions += 1,
+                        Some(false) => self.transversions += 1,
+                        None => {}
+                    }
+                }
+            }
             VariantType::Insertion => self.insertions += 1,
             VariantType::Deletion => self.deletions += 1,
             VariantType::Complex => self.complex += 1,
@@ -289,16 +670,173 @@ impl VcfStats {
             FilterStatus::Missing => {}
         }
 
-        if !self.chromosomes.contains(&record.chrom) {
-            self.chromosomes.push(record.chrom.clone());
+        let chrom = self.chromosome_mut(&record.chrom);
+        chrom.records += 1;
+        match variant_type {
+            VariantType::Snp => chrom.snps += 1,
+            VariantType::Insertion | VariantType::Deletion | VariantType::Complex => {
+                chrom.indels += 1
+            }
+            VariantType::Other => {}
+        }
+    }
+
+    /// Add the counts of `other`, e.g. from another file or thread
+    ///
+    /// Chromosomes new to `self` are appended in `other`'s order.
+    pub fn merge(&mut self, other: VcfStats) {
+        self.total_records += other.total_records;
+        self.snps += other.snps;
+        self.insertions += other.insertions;
+        self.deletions += other.deletions;
+        self.complex += other.complex;
+        self.passed_filter += other.passed_filter;
+        self.failed_filter += other.failed_filter;
+        self.transitions += other.transitions;
+        self.transversions += other.transversions;
+
+        for theirs in other.per_chromosome {
+            let ours = self.chromosome_mut(&theirs.chrom);
+            ours.records += theirs.records;
+            ours.snps += theirs.snps;
+            ours.indels += theirs.indels;
+        }
+        for chrom in other.chromosomes {
+            if !self.chromosomes.contains(&chrom) {
+                self.chromosomes.push(chrom);
+            }
+        }
+    }
+
+    /// Transition/transversion ratio; `None` without transversions
+    pub fn ts_tv_ratio(&self) -> Option<f64> {
+        if self.transversions == 0 {
+            None
+        } else {
+            Some(self.transitions as f64 / self.transversions as f64)
         }
     }
+
+    /// Entry for `chrom`, added (and listed in `chromosomes`) if new
+    fn chromosome_mut(&mut self, chrom: &str) -> &mut ChromosomeStats {
+        // Sorted input keeps hitting the last chromosome
+        let idx = match self.per_chromosome.iter().rposition(|c| c.chrom == chrom) {
+            Some(idx) => idx,
+            None => {
+                if !self.chromosomes.iter().any(|c| c == chrom) {
+                    self.chromosomes.push(chrom.to_string());
+                }
+                self.per_chromosome.push(ChromosomeStats {
+                    chrom: chrom.to_string(),
+                    ..Default::default()
+                });
+                self.per_chromosome.len() - 1
+            }
+        };
+        &mut self.per_chromosome[idx]
+    }
+}
+
+/// `Some(true)` for a transition, `Some(false)` for a transversion and
+/// `None` unless both alleles are single A, C, G or T bases
+fn substitution_class(reference: &str, alt: &str) -> Option<bool> {
+    let purine = |base: &str| match base.to_ascii_uppercase().as_str() {
+        "A" | "G" => Some(true),
+        "C" | "T" => Some(false),
+        _ => None,
+    };
+    let (from, to) = (purine(reference)?, purine(alt)?);
+    if reference.eq_ignore_ascii_case(alt) {
+        return None;
+    }
+    Some(from == to)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn header_with_contigs(contigs: &[(&str, Option<u64>)]) -> VcfHeader {
+        VcfHeader {
+            contigs: contigs
+                .iter()
+                .map(|&(id, length)| ContigInfo {
+                    id: id.to_string(),
+                    length,
+                    estimated: false,
+                })
+                .collect(),
+            ..VcfHeader::default()
+        }
+    }
+
+    #[test]
+    fn test_build_contigs_from_records() {
+        let records = [
+            VcfRecord::new("chr2", 500, "A", vec!["G"]),
+            VcfRecord::new("chr1", 40, "A", vec!["G"]),
+            VcfRecord::new("chr2", 900, "A", vec!["G"]),
+            VcfRecord::new("chrM", 16000, "A", vec!["G"]),
+            VcfRecord::new("chr2", 700, "A", vec!["G"]),
+        ];
+        let mut header = header_with_contigs(&[("chrM", Some(16569))]);
+        header.build_contigs_from_records(&records);
+
+        let contigs: Vec<_> = header
+            .contigs
+            .iter()
+            .map(|c| (c.id.as_str(), c.length, c.estimated))
+            .collect();
+        assert_eq!(
+            contigs,
+            [
+                ("chrM", Some(16569), false),
+                ("chr2", Some(900), true),
+                ("chr1", Some(40), true),
+            ]
+        );
+
+        // Estimated lengths grow with later records
+        header.build_contigs_from_records(&[VcfRecord::new("chr1", 80, "A", vec!["G"])]);
+        assert_eq!(header.contigs[2].length, Some(80));
+        assert!(header.validate_positions_against_contigs(&records).is_empty());
+    }
+
+    #[test]
+    fn test_validate_positions_against_contigs() {
+        let header = header_with_contigs(&[("chr1", Some(1000)), ("chr2", None)]);
+        let records = [
+            VcfRecord::new("chr1", 1000, "A", vec!["G"]),
+            VcfRecord::new("chr1", 1001, "A", vec!["G"]),
+            VcfRecord::new("chr2", 5_000_000, "A", vec!["G"]),
+            VcfRecord::new("chr3", 10, "A", vec!["G"]),
+        ];
+
+        let issues = header.validate_positions_against_contigs(&records);
+        assert_eq!(
+            issues,
+            [
+                ValidationIssue {
+                    record: 1,
+                    chrom: "chr1".to_string(),
+                    pos: 1001,
+                    kind: ValidationIssueKind::BeyondContigEnd { length: 1000 },
+                },
+                ValidationIssue {
+                    record: 3,
+                    chrom: "chr3".to_string(),
+                    pos: 10,
+                    kind: ValidationIssueKind::UndeclaredContig,
+                },
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "position 1001 is beyond the end of chr1 (length 1000)"
+        );
+        assert_eq!(issues[1].to_string(), "contig chr3 is not declared in the header");
+    }
+
     #[test]
     fn test_genotype_parse() {
         let gt = Genotype::parse("0/1").unwrap();
@@ -315,6 +853,103 @@ mod tests {
         assert!(gt.is_hom_ref());
 
         assert!(Genotype::parse("./.").is_none());
+        assert!(Genotype::parse(".|.|.").is_none());
+        assert!(Genotype::parse("./1").is_some());
+    }
+
+    #[test]
+    fn test_half_call_policies() {
+        let parse = |gt: &str, policy| Genotype::parse_with(gt, policy).map(|g| g.alleles);
+        let policies = [
+            HalfCallPolicy::Missing,
+            HalfCallPolicy::Haploid,
+            HalfCallPolicy::Reference,
+        ];
+        for policy in policies {
+            assert_eq!(parse("./.", policy), None);
+            assert_eq!(parse("0|1", policy), Some(vec![Some(0), Some(1)]));
+        }
+        assert_eq!(parse("./1", HalfCallPolicy::Missing), None);
+        assert_eq!(parse("./1", HalfCallPolicy::Haploid), Some(vec![Some(1)]));
+        assert_eq!(
+            parse("./1", HalfCallPolicy::Reference),
+            Some(vec![Some(0), Some(1)])
+        );
+        assert_eq!(
+            parse("1|.|2", HalfCallPolicy::Reference),
+            Some(vec![Some(1), Some(0), Some(2)])
+        );
+        assert!(Genotype::parse("0/.").unwrap().is_half_call());
+        assert!(!Genotype::parse("0/1").unwrap().is_half_call());
+
+        let mut sample = SampleData {
+            name: "S1".to_string(),
+            genotype: None,
+            gt_present: false,
+            fields: HashMap::new(),
+        };
+        let state = |sample: &SampleData| sample.genotype_state(HalfCallPolicy::Reference);
+        assert_eq!(state(&sample), GenotypeState::NotProvided);
+        sample.gt_present = true;
+        assert_eq!(state(&sample), GenotypeState::Missing);
+    }
+
+    #[test]
+    fn test_sample_int_fields() {
+        let sample = SampleData {
+            name: "S1".to_string(),
+            genotype: Genotype::parse("0/1"),
+            gt_present: true,
+            fields: [("DP", "31"), ("GQ", "."), ("AD", "20,.,11"), ("PL", ".")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        };
+
+        assert_eq!(sample.int_field("DP"), Some(31));
+        assert_eq!(sample.int_field("GQ"), None);
+        assert_eq!(sample.int_field("MQ"), None);
+        assert_eq!(sample.int_array_field("AD"), Some(vec![Some(20), None, Some(11)]));
+        assert_eq!(sample.int_array_field("PL"), None);
+    }
+
+    #[test]
+    fn test_stats_ts_tv_and_per_chromosome() {
+        let mut stats = VcfStats::new();
+        for (chrom, reference, alt) in [
+            ("chr1", "A", "G"),
+            ("chr1", "C", "T"),
+            ("chr1", "A", "C"),
+            ("chr2", "AT", "A"),
+            ("chr1", "G", "N"),
+        ] {
+            stats.update(&VcfRecord::new(chrom, 1, reference, vec![alt]));
+        }
+
+        assert_eq!((stats.transitions, stats.transversions), (2, 1));
+        assert_eq!(stats.ts_tv_ratio(), Some(2.0));
+        assert_eq!(stats.chromosomes, vec!["chr1", "chr2"]);
+        assert_eq!(
+            stats.per_chromosome[0],
+            ChromosomeStats {
+                chrom: "chr1".to_string(),
+                records: 4,
+                snps: 4,
+                indels: 0,
+            }
+        );
+        assert_eq!(stats.per_chromosome[1].indels, 1);
+
+        let mut other = VcfStats::new();
+        other.update(&VcfRecord::new("chr3", 1, "T", vec!["C"]));
+        other.update(&VcfRecord::new("chr2", 5, "G", vec!["T"]));
+        stats.merge(other);
+
+        assert_eq!(stats.total_records, 7);
+        assert_eq!((stats.transitions, stats.transversions), (3, 2));
+        assert_eq!(stats.chromosomes, vec!["chr1", "chr2", "chr3"]);
+        assert_eq!(stats.per_chromosome[1].records, 2);
+        assert_eq!(stats.per_chromosome[2].chrom, "chr3");
     }
 
     #[test]
@@ -0,0 +1,220 @@
+//! Record Views for Bindings
+//!
+//! The JavaScript-facing shape of a record, shared by the Node and WASM
+//! bindings so a field added here shows up in both. Maps are kept in key
+//! order, so the same input always serializes to the same bytes.
+
+use crate::types::{FilterStatus, Genotype, InfoValue, SampleData, VariantType, VcfRecord};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Variant type labels, indexed by [`variant_type_code`]
+pub const VARIANT_TYPE_LABELS: [&str; 5] = ["SNP", "INS", "DEL", "COMPLEX", "OTHER"];
+
+/// Stable numeric code for a variant type; index into [`VARIANT_TYPE_LABELS`]
+pub fn variant_type_code(variant_type: VariantType) -> u8 {
+    match variant_type {
+        VariantType::Snp => 0,
+        VariantType::Insertion => 1,
+        VariantType::Deletion => 2,
+        VariantType::Complex => 3,
+        VariantType::Other => 4,
+    }
+}
+
+/// Label for a variant type, e.g. "SNP" or "DEL"
+pub fn variant_type_label(variant_type: VariantType) -> &'static str {
+    VARIANT_TYPE_LABELS[variant_type_code(variant_type) as usize]
+}
+
+/// Case-insensitive inverse of [`variant_type_label`]
+pub fn variant_type_from_label(label: &str) -> Option<VariantType> {
+    [
+        VariantType::Snp,
+        VariantType::Insertion,
+        VariantType::Deletion,
+        VariantType::Complex,
+        VariantType::Other,
+    ]
+    .into_iter()
+    .find(|t| variant_type_label(*t).eq_ignore_ascii_case(label))
+}
+
+/// FILTER column as written: "PASS", "." or the failed filters joined with ";"
+pub fn filter_string(filter: &FilterStatus) -> String {
+    match filter {
+        FilterStatus::Pass => "PASS".to_string(),
+        FilterStatus::Missing => ".".to_string(),
+        FilterStatus::Failed(filters) => filters.join(";"),
+    }
+}
+
+/// Format a genotype the way it appears in the VCF ("0/1", "1|.")
+pub fn genotype_string(genotype: &Genotype) -> String {
+    let separator = if genotype.phased { "|" } else { "/" };
+    genotype
+        .alleles
+        .iter()
+        .map(|a| a.map_or_else(|| ".".to_string(), |a| a.to_string()))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// Record as handed to JavaScript
+///
+/// `info` and `samples` are only present when they were asked for, so
+/// fast-mode results stay lean; missing optional values are left out
+/// rather than serialized as null.
+#[derive(Debug, Clone, Serialize)]
+pub struct VcfRecordView {
+    pub chrom: String,
+    pub pos: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub reference: String,
+    pub alternate: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qual: Option<f64>,
+    pub filter: String,
+    pub variant_type: String,
+    /// `chrom:g.` HGVS name of each ALT allele (see [`VcfRecord::hgvs_g`])
+    pub hgvs_g: Vec<String>,
+    pub is_snp: bool,
+    pub is_insertion: bool,
+    pub is_deletion: bool,
+    /// INFO values as numbers, strings, arrays or `true` for flags
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_info")]
+    pub info: Option<BTreeMap<String, InfoValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub samples: Option<Vec<SampleView>>,
+}
+
+impl VcfRecordView {
+    /// Convert a record, leaving out INFO / samples when not requested
+    ///
+    /// Strings are moved out of the record rather than copied.
+    pub fn new(record: VcfRecord, include_info: bool, include_samples: bool) -> Self {
+        let variant_type = variant_type_label(record.variant_type()).to_string();
+        let hgvs_g = record.hgvs_g();
+
+        Self {
+            is_snp: record.is_snp(),
+            is_insertion: record.is_insertion(),
+            is_deletion: record.is_deletion(),
+            filter: filter_string(&record.filter),
+            variant_type,
+            hgvs_g,
+            chrom: record.chrom,
+            pos: record.pos,
+            id: record.id,
+            reference: record.reference,
+            alternate: record.alternate,
+            qual: record.qual,
+            info: include_info.then(|| {
+                record
+                    .info
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect()
+            }),
+            samples: include_samples
+                .then(|| record.samples.into_iter().map(SampleView::from).collect()),
+        }
+    }
+}
+
+impl From<VcfRecord> for VcfRecordView {
+    fn from(record: VcfRecord) -> Self {
+        Self::new(record, true, true)
+    }
+}
+
+/// Sample genotype and FORMAT fields as handed to JavaScript
+#[derive(Debug, Clone, Serialize)]
+pub struct SampleView {
+    pub name: String,
+    /// Genotype as written, e.g. "0/1" or "1|0"; absent when missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gt: Option<String>,
+    pub phased: bool,
+    pub fields: BTreeMap<String, String>,
+}
+
+impl From<SampleData> for SampleView {
+    fn from(sample: SampleData) -> Self {
+        let phased = sample.genotype.as_ref().is_some_and(|g| g.phased);
+        Self {
+            name: sample.name,
+            gt: sample.genotype.as_ref().map(genotype_string),
+            phased,
+            fields: sample.fields.into_iter().collect(),
+        }
+    }
+}
+
+/// INFO value that serializes with natural JS types: numbers, arrays,
+/// strings and `true` for flags
+pub struct InfoValueView<'a>(pub &'a InfoValue);
+
+impl Serialize for InfoValueView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            InfoValue::Flag => serializer.serialize_bool(true),
+            InfoValue::Integer(i) => serializer.serialize_i64(*i),
+            InfoValue::Float(f) => serializer.serialize_f64(*f),
+            InfoValue::String(s) => serializer.serialize_str(s),
+            InfoValue::IntegerArray(v) => v.serialize(serializer),
+            InfoValue::FloatArray(v) => v.serialize(serializer),
+            InfoValue::StringArray(v) => v.serialize(serializer),
+        }
+    }
+}
+
+fn serialize_info<S: Serializer>(
+    info: &Option<BTreeMap<String, InfoValue>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match info {
+        Some(info) => serializer.collect_map(info.iter().map(|(k, v)| (k, InfoValueView(v)))),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    /// Fixture and expected JSON shared with the binding tests
+    const FIXTURE: &str = include_str!("../../tests/data/shared-records.vcf");
+    const SNAPSHOT: &str = include_str!("../../tests/data/shared-records.json");
+
+    #[test]
+    fn test_views_match_snapshot() {
+        let (_, records) = VcfParser::new().parse_str(FIXTURE).unwrap();
+        let views: Vec<_> = records.into_iter().map(VcfRecordView::from).collect();
+
+        assert_eq!(serde_json::to_string_pretty(&views).unwrap(), SNAPSHOT.trim_end());
+    }
+
+    #[test]
+    fn test_view_leaves_out_unrequested_fields() {
+        let (_, mut records) = VcfParser::new().parse_str(FIXTURE).unwrap();
+        let view = VcfRecordView::new(records.remove(0), false, false);
+        let json = serde_json::to_value(&view).unwrap();
+
+        assert!(json.get("info").is_none());
+        assert!(json.get("samples").is_none());
+        assert_eq!(json["variant_type"], "SNP");
+    }
+
+    #[test]
+    fn test_variant_type_labels() {
+        for label in VARIANT_TYPE_LABELS {
+            let variant_type = variant_type_from_label(label).unwrap();
+            assert_eq!(variant_type_label(variant_type), label);
+        }
+        assert_eq!(variant_type_from_label("del"), Some(VariantType::Deletion));
+        assert_eq!(variant_type_from_label("MNP"), None);
+    }
+}
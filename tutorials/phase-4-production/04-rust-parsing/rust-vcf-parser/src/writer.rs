@@ -0,0 +1,670 @@
+//! VCF Writer
+//!
+//! Serializes parsed headers and records back to tab-separated VCF text.
+
+use crate::error::VcfResult;
+use crate::types::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+/// Writes a header and records as VCF text
+///
+/// INFO keys and FORMAT keys follow the order of their header definitions,
+/// with undeclared keys after them in sorted order, since records keep
+/// them in hash maps. Records parsed without INFO get `.` in the INFO
+/// column; sample columns are written only when enabled with
+/// [`VcfWriter::with_samples`]. QUAL and float INFO values follow the
+/// writer's [`FloatFormat`].
+pub struct VcfWriter<'h, W: Write> {
+    out: W,
+    header: &'h VcfHeader,
+    include_samples: bool,
+    float_format: FloatFormat,
+    info_rank: HashMap<&'h str, usize>,
+    format_rank: HashMap<&'h str, usize>,
+}
+
+impl<'h, W: Write> VcfWriter<'h, W> {
+    /// Create a writer for records described by `header`
+    pub fn new(out: W, header: &'h VcfHeader) -> Self {
+        Self {
+            out,
+            header,
+            include_samples: !header.samples.is_empty(),
+            float_format: FloatFormat::default(),
+            info_rank: rank(header.info_fields.iter().map(|f| f.id.as_str())),
+            format_rank: rank(header.format_fields.iter().map(|f| f.id.as_str())),
+        }
+    }
+
+    /// Whether to write FORMAT and sample columns (default: if the header
+    /// names samples)
+    pub fn with_samples(mut self, include: bool) -> Self {
+        self.include_samples = include && !self.header.samples.is_empty();
+        self
+    }
+
+    /// How to write QUAL and float INFO values (default:
+    /// [`FloatFormat::Original`])
+    pub fn with_float_format(mut self, format: FloatFormat) -> Self {
+        self.float_format = format;
+        self
+    }
+
+    /// Write the meta lines and the `#CHROM` line
+    ///
+    /// A parsed header's raw `meta_lines` are written as they were read,
+    /// so unmodeled lines keep their place; contigs added by
+    /// [`VcfHeader::build_contigs_from_records`] go after the existing
+    /// `##contig` lines. A header built in code (no `meta_lines`) is
+    /// written from its fields in a canonical order: fileformat,
+    /// reference, contigs, INFO, FILTER, FORMAT, SAMPLE, PEDIGREE, then
+    /// the other lines.
+    pub fn write_header(&mut self) -> VcfResult<()> {
+        let lines = if self.header.meta_lines.is_empty() {
+            canonical_meta_lines(self.header)
+        } else {
+            meta_lines_with_estimated_contigs(self.header)
+        };
+        for line in lines {
+            writeln!(self.out, "{}", line)?;
+        }
+
+        write!(self.out, "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO")?;
+        if self.include_samples {
+            write!(self.out, "\tFORMAT\t{}", self.header.samples.join("\t"))?;
+        }
+        writeln!(self.out)?;
+        Ok(())
+    }
+
+    /// Write one record line
+    pub fn write_record(&mut self, record: &VcfRecord) -> VcfResult<()> {
+        let mut line = String::new();
+
+        let _ = write!(
+            line,
+            "{}\t{}\t{}\t{}\t{}\t",
+            record.chrom,
+            record.pos,
+            record.id.as_deref().unwrap_or("."),
+            record.reference,
+            or_missing(record.alternate.join(",")),
+        );
+        match record.qual {
+            Some(qual) => {
+                let original = record.float_text.as_ref().and_then(|t| t.qual.as_deref());
+                line.push_str(&self.float_format.render(&[qual], original, false));
+            }
+            None => line.push('.'),
+        }
+        line.push('\t');
+        line.push_str(&match &record.filter {
+            FilterStatus::Pass => "PASS".to_string(),
+            FilterStatus::Missing => ".".to_string(),
+            FilterStatus::Failed(filters) => filters.join(";"),
+        });
+        line.push('\t');
+        line.push_str(&self.info_column(record));
+
+        if self.include_samples {
+            self.push_samples(&mut line, record);
+        }
+
+        writeln!(self.out, "{}", line)?;
+        Ok(())
+    }
+
+    /// Finish writing and return the underlying writer
+    pub fn into_inner(mut self) -> VcfResult<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    fn info_column(&self, record: &VcfRecord) -> String {
+        let mut keys: Vec<&str> = record.info.keys().map(|k| &**k).collect();
+        keys.sort_by_key(|&k| (self.info_rank.get(k).copied().unwrap_or(usize::MAX), k));
+
+        let entries: Vec<String> = keys
+            .into_iter()
+            .map(|key| match &record.info[key] {
+                InfoValue::Flag => key.to_string(),
+                value => format!("{}={}", key, self.info_text(record, key, value)),
+            })
+            .collect();
+
+        or_missing(entries.join(";"))
+    }
+
+    fn info_text(&self, record: &VcfRecord, key: &str, value: &InfoValue) -> String {
+        let floats = match value {
+            InfoValue::Float(f) => std::slice::from_ref(f),
+            InfoValue::FloatArray(values) => values,
+            value => return info_value(value),
+        };
+        let original = record.float_text.as_ref().and_then(|t| t.info(key));
+        self.float_format.render(floats, original, true)
+    }
+
+    fn push_samples(&self, line: &mut String, record: &VcfRecord) {
+        let has_gt = self.format_rank.contains_key("GT")
+            || record
+                .samples
+                .iter()
+                .any(|s| s.gt_present || s.genotype.is_some());
+
+        let mut keys: Vec<&str> = Vec::new();
+        for sample in &record.samples {
+            for key in sample.fields.keys() {
+                if !keys.contains(&key.as_str()) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys.sort_by_key(|&k| (self.format_rank.get(k).copied().unwrap_or(usize::MAX), k));
+        if has_gt {
+            keys.insert(0, "GT");
+        }
+
+        line.push('\t');
+        line.push_str(&or_missing(keys.join(":")));
+
+        for i in 0..self.header.samples.len() {
+            line.push('\t');
+            let Some(sample) = record.samples.get(i) else {
+                line.push('.');
+                continue;
+            };
+            let values: Vec<String> = keys
+                .iter()
+                .map(|&key| match key {
+                    "GT" => sample
+                        .genotype
+                        .as_ref()
+                        .map_or_else(|| ".".to_string(), genotype),
+                    _ => sample
+                        .fields
+                        .get(key)
+                        .cloned()
+                        .unwrap_or_else(|| ".".into()),
+                })
+                .collect();
+            line.push_str(&or_missing(values.join(":")));
+        }
+    }
+}
+
+/// How a writer renders QUAL and float INFO values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatFormat {
+    /// The text the value was parsed from, when the parser kept it (see
+    /// `VcfParser::keep_float_text`) and it still reads back as the same
+    /// number; otherwise as [`FloatFormat::Shortest`]
+    #[default]
+    Original,
+    /// The shortest text that reads back as the same number, e.g. `30.1`
+    /// or `1e-5` for INFO and `0.00001` for QUAL. A whole number is `30`
+    /// as QUAL but `30.0` in INFO, which would otherwise read back as an
+    /// Integer.
+    Shortest,
+    /// This many digits after the decimal point, e.g. `30.10` with 2;
+    /// with 0, whole INFO floats read back as Integers
+    FixedDecimals(usize),
+}
+
+impl FloatFormat {
+    /// Comma-separated `values`; `original` is the parsed text, if kept
+    fn render(self, values: &[f64], original: Option<&str>, info: bool) -> String {
+        if let (FloatFormat::Original, Some(text)) = (self, original) {
+            if reads_back_as(text, values) {
+                return text.to_string();
+            }
+        }
+        values
+            .iter()
+            .map(|&value| match self {
+                FloatFormat::FixedDecimals(digits) => format!("{:.*}", digits, value),
+                // Debug keeps a `.0` on whole numbers
+                _ if info => format!("{:?}", value),
+                _ => value.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Whether `text` parses to exactly `values`, so a record changed since
+/// parsing does not get its stale text written
+fn reads_back_as(text: &str, values: &[f64]) -> bool {
+    let mut parts = text.split(',');
+    values.iter().all(|value| {
+        parts
+            .next()
+            .and_then(|part| part.parse::<f64>().ok())
+            .is_some_and(|parsed| parsed.to_bits() == value.to_bits())
+    }) && parts.next().is_none()
+}
+
+/// Write a complete VCF document to a string
+pub fn to_vcf_string<'a>(
+    header: &VcfHeader,
+    records: impl IntoIterator<Item = &'a VcfRecord>,
+    include_samples: bool,
+) -> VcfResult<String> {
+    let mut writer = VcfWriter::new(Vec::new(), header).with_samples(include_samples);
+    writer.write_header()?;
+    for record in records {
+        writer.write_record(record)?;
+    }
+
+    let bytes = writer.into_inner()?;
+    Ok(String::from_utf8(bytes).expect("VCF output is built from strings"))
+}
+
+fn rank<'h>(ids: impl Iterator<Item = &'h str>) -> HashMap<&'h str, usize> {
+    let mut ranks = HashMap::new();
+    for (i, id) in ids.enumerate() {
+        ranks.entry(id).or_insert(i);
+    }
+    ranks
+}
+
+/// Meta lines for `header` built from its structured fields
+pub fn canonical_meta_lines(header: &VcfHeader) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if header.file_format.starts_with("VCF") {
+        lines.push(format!("##fileformat={}", header.file_format));
+    } else {
+        lines.push(format!("##fileformat=VCFv{}", header.file_format));
+    }
+    if let Some(reference) = &header.reference {
+        lines.push(format!("##reference={}", reference));
+    }
+    lines.extend(header.contigs.iter().map(contig_line));
+    for info in &header.info_fields {
+        lines.push(format!(
+            "##INFO=<ID={},Number={},Type={},Description={}>",
+            info.id,
+            info.number,
+            info.field_type,
+            quoted(&info.description)
+        ));
+    }
+    for filter in &header.filters {
+        lines.push(format!(
+            "##FILTER=<ID={},Description={}>",
+            filter.id,
+            quoted(&filter.description)
+        ));
+    }
+    for format in &header.format_fields {
+        lines.push(format!(
+            "##FORMAT=<ID={},Number={},Type={},Description={}>",
+            format.id,
+            format.number,
+            format.field_type,
+            quoted(&format.description)
+        ));
+    }
+    for sample in &header.sample_metadata {
+        let id = [("ID".to_string(), sample.id.clone())];
+        lines.push(format!(
+            "##SAMPLE={}",
+            structured(id.iter().chain(&sample.fields))
+        ));
+    }
+    for pedigree in &header.pedigrees {
+        lines.push(format!("##PEDIGREE={}", structured(&pedigree.fields)));
+    }
+    if let Some(tumor) = &header.tumor_sample {
+        lines.push(format!("##tumor_sample={}", tumor));
+    }
+    if let Some(normal) = &header.normal_sample {
+        lines.push(format!("##normal_sample={}", normal));
+    }
+    for (key, value) in &header.other_meta {
+        lines.push(format!("##{}={}", key, value));
+    }
+
+    lines
+}
+
+/// The raw meta lines plus a line for each estimated contig, placed after
+/// the last `##contig` line, or else before the first INFO, FILTER or
+/// FORMAT line
+fn meta_lines_with_estimated_contigs(header: &VcfHeader) -> Vec<String> {
+    let mut lines = header.meta_lines.clone();
+    let estimated: Vec<String> = header
+        .contigs
+        .iter()
+        .filter(|contig| contig.estimated)
+        .map(contig_line)
+        .collect();
+    if estimated.is_empty() {
+        return lines;
+    }
+
+    let after_contigs = lines
+        .iter()
+        .rposition(|line| line.starts_with("##contig="))
+        .map(|i| i + 1);
+    let before_definitions = || {
+        lines.iter().position(|line| {
+            ["##INFO=", "##FILTER=", "##FORMAT="]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+    };
+    let at = after_contigs
+        .or_else(before_definitions)
+        .unwrap_or(lines.len());
+    lines.splice(at..at, estimated);
+    lines
+}
+
+/// The `##contig` meta line for `contig`
+pub fn contig_line(contig: &ContigInfo) -> String {
+    match contig.length {
+        Some(length) => format!("##contig=<ID={},length={}>", contig.id, length),
+        None => format!("##contig=<ID={}>", contig.id),
+    }
+}
+
+/// `<key=value,...>`, quoting values that need it
+fn structured<'a>(fields: impl IntoIterator<Item = &'a (String, String)>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|(key, value)| {
+            let needs_quotes = key == "Description"
+                || value
+                    .chars()
+                    .any(|c| matches!(c, ',' | '"' | '<' | '>') || c.is_whitespace());
+            if needs_quotes {
+                format!("{}={}", key, quoted(value))
+            } else {
+                format!("{}={}", key, value)
+            }
+        })
+        .collect();
+    format!("<{}>", fields.join(","))
+}
+
+/// `value` in double quotes, with `\` and `"` escaped as VCF 4.3 requires
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn or_missing(value: String) -> String {
+    if value.is_empty() {
+        ".".to_string()
+    } else {
+        value
+    }
+}
+
+pub(crate) fn info_value(value: &InfoValue) -> String {
+    fn join<T: ToString>(values: &[T]) -> String {
+        values
+            .iter()
+            .map(T::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    match value {
+        InfoValue::Flag => String::new(),
+        InfoValue::Integer(i) => i.to_string(),
+        // Debug keeps a `.0` on whole numbers, which would otherwise read
+        // back as integers
+        InfoValue::Float(f) => format!("{:?}", f),
+        InfoValue::String(s) => s.clone(),
+        InfoValue::IntegerArray(values) => join(values),
+        InfoValue::FloatArray(values) => values
+            .iter()
+            .map(|f| format!("{:?}", f))
+            .collect::<Vec<_>>()
+            .join(","),
+        InfoValue::StringArray(values) => values.join(","),
+    }
+}
+
+pub(crate) fn genotype(gt: &Genotype) -> String {
+    let separator = if gt.phased { "|" } else { "/" };
+    gt.alleles
+        .iter()
+        .map(|a| a.map_or_else(|| ".".to_string(), |a| a.to_string()))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::VcfParser;
+
+    const SAMPLE_VCF: &str = "##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total Depth\">
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele Frequency\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description=\"Read Depth\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tSAMPLE1\tSAMPLE2
+chr1\t100\trs123\tA\tG\t30\tPASS\tDP=50;AF=0.5;DB\tGT:DP\t0/1:25\t1|1:30
+chr1\t200\t.\tAT\tA\t40.5\tq10;s50\tDP=60\tGT:DP\t0/0:28\t./.:32
+chr2\t300\t.\tC\t.\t.\t.\t.\tGT:DP\t0/1:35\t0/1:40
+";
+
+    #[test]
+    fn test_round_trip() {
+        let (header, records) = VcfParser::new().parse_str(SAMPLE_VCF).unwrap();
+        let text = to_vcf_string(&header, &records, true).unwrap();
+
+        let expected = SAMPLE_VCF.replace("\t./.:32", "\t.:32");
+        assert_eq!(text, expected);
+    }
+
+    const RICH_HEADER: &str = "##fileformat=VCFv4.2
+##source=HaplotypeCaller
+##GATKCommandLine=<ID=HaplotypeCaller,CommandLine=\"HaplotypeCaller -R ref.fa -I in.bam\",Version=4.2.0.0>
+##reference=file:///ref/GRCh38.fa
+##contig=<ID=chr1,length=248956422>
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total Depth\">
+##FILTER=<ID=q10,Description=\"Quality below 10\">
+##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">
+##SAMPLE=<ID=Tumor,Genomes=Somatic,Mixture=.8,Description=\"Primary tumour, biopsy 2\">
+##SAMPLE=<ID=Normal,Genomes=Germline,Mixture=1.,Description=\"Blood\">
+##PEDIGREE=<Derived=Tumor,Original=Normal>
+##bcftools_viewCommand=view -i 'QUAL>10' in.vcf.gz; Date=Tue Mar  5 10:00:00 2024
+##myPipeline.step=3
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tTumor\tNormal
+chr1\t100\t.\tA\tG\t30\tPASS\tDP=50\tGT\t0/1\t0/0
+";
+
+    #[test]
+    fn test_header_round_trip_keeps_unmodeled_lines() {
+        let (header, records) = VcfParser::new().parse_str(RICH_HEADER).unwrap();
+        assert_eq!(to_vcf_string(&header, &records, true).unwrap(), RICH_HEADER);
+
+        // Trailing whitespace on a meta line is kept too
+        let padded = RICH_HEADER.replace("##source=HaplotypeCaller", "##source=HaplotypeCaller  ");
+        let (header, records) = VcfParser::new().parse_str(&padded).unwrap();
+        assert_eq!(to_vcf_string(&header, &records, true).unwrap(), padded);
+    }
+
+    #[test]
+    fn test_canonical_header_without_meta_lines() {
+        let (mut header, _) = VcfParser::new().parse_str(RICH_HEADER).unwrap();
+        header.meta_lines.clear();
+
+        let text = to_vcf_string(&header, &[], true).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "##fileformat=VCFv4.2",
+                "##reference=file:///ref/GRCh38.fa",
+                "##contig=<ID=chr1,length=248956422>",
+                "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total Depth\">",
+                "##FILTER=<ID=q10,Description=\"Quality below 10\">",
+                "##FORMAT=<ID=GT,Number=1,Type=String,Description=\"Genotype\">",
+                "##SAMPLE=<ID=Tumor,Genomes=Somatic,Mixture=.8,Description=\"Primary tumour, biopsy 2\">",
+                "##SAMPLE=<ID=Normal,Genomes=Germline,Mixture=1.,Description=\"Blood\">",
+                "##PEDIGREE=<Derived=Tumor,Original=Normal>",
+                "##source=HaplotypeCaller",
+                "##GATKCommandLine=<ID=HaplotypeCaller,CommandLine=\"HaplotypeCaller -R ref.fa -I in.bam\",Version=4.2.0.0>",
+                "##bcftools_viewCommand=view -i 'QUAL>10' in.vcf.gz; Date=Tue Mar  5 10:00:00 2024",
+                "##myPipeline.step=3",
+                "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tTumor\tNormal",
+            ]
+        );
+
+        // The canonical text parses back to the same structure
+        let (reparsed, _) = VcfParser::new().parse_str(&text).unwrap();
+        assert_eq!(reparsed.sample_metadata, header.sample_metadata);
+        assert_eq!(reparsed.pedigrees, header.pedigrees);
+        assert_eq!(reparsed.other_meta, header.other_meta);
+
+        // A header built in code gets a valid fileformat line
+        let built = to_vcf_string(&VcfHeader::default(), &[], false).unwrap();
+        assert!(built.starts_with("##fileformat=VCFv4.2\n#CHROM"));
+    }
+
+    #[test]
+    fn test_estimated_contigs_written_with_raw_meta_lines() {
+        let (mut header, records) = VcfParser::new().parse_str(SAMPLE_VCF).unwrap();
+        header.build_contigs_from_records(&records);
+
+        let text = to_vcf_string(&header, &records, true).unwrap();
+        let lines: Vec<&str> = text.lines().take(4).collect();
+        assert_eq!(
+            lines,
+            [
+                "##fileformat=VCFv4.2",
+                "##contig=<ID=chr1,length=200>",
+                "##contig=<ID=chr2,length=300>",
+                "##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Total Depth\">",
+            ]
+        );
+
+        // New contigs follow the declared ones
+        let (mut header, records) = VcfParser::new().parse_str(RICH_HEADER).unwrap();
+        header.build_contigs_from_records(&[VcfRecord::new("chrX", 7, "A", vec!["G"])]);
+        let text = to_vcf_string(&header, &records, true).unwrap();
+        assert!(text
+            .contains("##contig=<ID=chr1,length=248956422>\n##contig=<ID=chrX,length=7>\n##INFO"));
+    }
+
+    #[test]
+    fn test_without_samples() {
+        let mut parser = VcfParser::new();
+        parser.parse_samples = false;
+        let (header, records) = parser.parse_str(SAMPLE_VCF).unwrap();
+        let text = to_vcf_string(&header, &records[..1], false).unwrap();
+
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[5], "#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO");
+        assert_eq!(
+            lines[6],
+            "chr1\t100\trs123\tA\tG\t30\tPASS\tDP=50;AF=0.5;DB"
+        );
+        assert!(text.ends_with('\n'));
+    }
+
+    /// QUAL and INFO written as each text: `30` reads as an INFO Integer,
+    /// the rest as Floats
+    const FLOAT_TEXTS: [&str; 5] = ["30", "30.0", "30.10", "1e-5", "-0.0"];
+
+    fn float_vcf(texts: &[&str]) -> String {
+        let mut vcf = String::from(
+            "##fileformat=VCFv4.2\n\
+             ##INFO=<ID=X,Number=.,Type=Float,Description=\"Value\">\n\
+             #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n",
+        );
+        for (i, text) in texts.iter().enumerate() {
+            vcf += &format!("chr1\t{}\t.\tA\tG\t{}\tPASS\tX={}\n", i + 1, text, text);
+        }
+        vcf
+    }
+
+    fn write_floats(records: &[VcfRecord], header: &VcfHeader, format: FloatFormat) -> Vec<String> {
+        let mut writer = VcfWriter::new(Vec::new(), header).with_float_format(format);
+        for record in records {
+            writer.write_record(record).unwrap();
+        }
+        let text = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        text.lines()
+            .map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                format!("{} {}", fields[5], fields[7])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_float_format_round_trip() {
+        let vcf = float_vcf(&FLOAT_TEXTS);
+        let mut parser = VcfParser::new();
+        parser.keep_float_text = true;
+        let (header, records) = parser.parse_str(&vcf).unwrap();
+
+        // Original text survives exactly, and re-parses to the same numbers
+        let original = write_floats(&records, &header, FloatFormat::Original);
+        assert_eq!(
+            original,
+            [
+                "30 X=30",
+                "30.0 X=30.0",
+                "30.10 X=30.10",
+                "1e-5 X=1e-5",
+                "-0.0 X=-0.0"
+            ]
+        );
+        let (_, plain) = VcfParser::new().parse_str(&vcf).unwrap();
+        for (kept, plain) in records.iter().zip(&plain) {
+            assert_eq!(kept.qual.map(f64::to_bits), plain.qual.map(f64::to_bits));
+            assert!(plain.float_text.is_none());
+        }
+        assert_eq!(
+            write_floats(&plain, &header, FloatFormat::Original),
+            write_floats(&plain, &header, FloatFormat::Shortest)
+        );
+
+        assert_eq!(
+            write_floats(&records, &header, FloatFormat::Shortest),
+            [
+                "30 X=30",
+                "30 X=30.0",
+                "30.1 X=30.1",
+                "0.00001 X=1e-5",
+                "-0 X=-0.0"
+            ]
+        );
+        assert_eq!(
+            write_floats(&records, &header, FloatFormat::FixedDecimals(2)),
+            [
+                "30.00 X=30",
+                "30.00 X=30.00",
+                "30.10 X=30.10",
+                "0.00 X=0.00",
+                "-0.00 X=-0.00"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_original_float_text_dropped_once_value_changes() {
+        let mut parser = VcfParser::new();
+        parser.keep_float_text = true;
+        let (header, mut records) = parser.parse_str(&float_vcf(&["30.10"])).unwrap();
+
+        records[0].qual = Some(40.0);
+        records[0]
+            .info
+            .insert("X".into(), InfoValue::FloatArray(vec![30.1, 2.0]));
+        assert_eq!(
+            write_floats(&records, &header, FloatFormat::Original),
+            ["40 X=30.1,2.0"]
+        );
+    }
+}
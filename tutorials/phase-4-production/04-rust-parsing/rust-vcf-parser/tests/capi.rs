@@ -0,0 +1,45 @@
+//! Builds and runs the C test program in `tests/c` against the cdylib
+
+#![cfg(all(feature = "capi", unix))]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[test]
+fn test_c_program() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // Cargo leaves the freshly built cdylib next to this test binary in
+    // target/<profile>/deps
+    let lib_dir = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .map(PathBuf::from)
+        .unwrap();
+    let exe = lib_dir.join("test_capi");
+
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir.join("../tests/c/test_capi.c"))
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .args(["-lvcf_parser", "-lm", "-o"])
+        .arg(&exe)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling test_capi.c failed");
+
+    // Cargo's own library path may also hold a stale copy from a build
+    // without the feature, so point the loader at this one explicitly
+    let output = Command::new(&exe)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .arg(manifest_dir.join("../tests/data/shared-records.vcf"))
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,103 @@
+//! Optional APIs give the same answers as the default path
+//!
+//! Every block is compiled only under its feature, so running the suite
+//! with `--no-default-features`, `--all-features` and each feature on its
+//! own covers the feature matrix:
+//!
+//! ```bash
+//! for f in ahash capi tracing parallel test-support; do
+//!     cargo test -p vcf-parser --no-default-features --features $f
+//! done
+//! ```
+
+use vcf_parser::{calculate_stats, calculate_stats_auto, InfoValue, VcfParser};
+
+const FIXTURE: &str = include_str!("../../tests/data/shared-records.vcf");
+
+fn stats_json(stats: vcf_parser::VcfStats) -> serde_json::Value {
+    serde_json::to_value(stats).unwrap()
+}
+
+#[test]
+fn test_default_path() {
+    let (header, records) = VcfParser::new().parse_str(FIXTURE).unwrap();
+    assert_eq!(header.samples, ["SAMPLE1", "SAMPLE2"]);
+    assert_eq!(records.len(), 5);
+    // Whichever hasher the `ahash` feature picks, lookups are by `&str`
+    assert!(matches!(
+        records[0].info.get("DP"),
+        Some(InfoValue::Integer(50))
+    ));
+    assert_eq!(
+        stats_json(calculate_stats_auto(&records)),
+        stats_json(calculate_stats(&records))
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_stats() {
+    let (_, records) = VcfParser::new().parse_str(FIXTURE).unwrap();
+    assert_eq!(
+        stats_json(vcf_parser::calculate_stats_parallel(&records)),
+        stats_json(calculate_stats(&records))
+    );
+}
+
+#[cfg(feature = "tracing")]
+#[test]
+fn test_tracing_metrics() {
+    let mut parser = VcfParser::new();
+    let (_, records) = parser.parse_str(FIXTURE).unwrap();
+    let (_, plain) = VcfParser::new().parse_str(FIXTURE).unwrap();
+    assert_eq!(
+        serde_json::to_value(&records).unwrap(),
+        serde_json::to_value(&plain).unwrap()
+    );
+
+    let metrics = parser.metrics();
+    assert_eq!(metrics.records, records.len());
+    assert_eq!(metrics.record_lines, records.len());
+    assert_eq!(metrics.bytes, FIXTURE.len() as u64);
+}
+
+#[cfg(feature = "test-support")]
+#[test]
+fn test_generated_vcf_is_reproducible() {
+    use vcf_parser::test_support::gen_vcf;
+
+    let vcf = gen_vcf(200, 3, 8, 42);
+    assert_eq!(vcf, gen_vcf(200, 3, 8, 42));
+    let (header, records) = VcfParser::new().parse_str(&vcf).unwrap();
+    assert_eq!((header.samples.len(), records.len()), (3, 200));
+}
+
+#[cfg(feature = "capi")]
+#[test]
+fn test_capi_stats() {
+    use std::ffi::CString;
+    use std::ptr;
+    use vcf_parser::ffi::{vcf_free, vcf_parse_file, vcf_stats, FfiStats, VcfErrorCode};
+
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/data/shared-records.vcf"
+    );
+    let path = CString::new(path).unwrap();
+    let mut handle = ptr::null_mut();
+    let mut ffi_stats = FfiStats::default();
+    // SAFETY: `path` is NUL-terminated, the out pointers are live, and the
+    // handle is freed once
+    unsafe {
+        assert_eq!(vcf_parse_file(path.as_ptr(), &mut handle), VcfErrorCode::Ok);
+        assert_eq!(vcf_stats(handle, &mut ffi_stats), VcfErrorCode::Ok);
+        vcf_free(handle);
+    }
+
+    let (_, records) = VcfParser::new().parse_str(FIXTURE).unwrap();
+    let stats = calculate_stats(&records);
+    assert_eq!(ffi_stats.total_records, stats.total_records as u64);
+    assert_eq!(ffi_stats.snps, stats.snps as u64);
+    assert_eq!(ffi_stats.passed_filter, stats.passed_filter as u64);
+    assert_eq!(ffi_stats.chromosomes, stats.chromosomes.len() as u64);
+}
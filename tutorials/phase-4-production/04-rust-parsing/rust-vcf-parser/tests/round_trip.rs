@@ -0,0 +1,484 @@
+//! Property tests: records written by `VcfWriter` parse back unchanged
+//!
+//! The parser infers INFO types from the text, so the strategies only
+//! build values it can tell apart: single-element arrays are generated as
+//! scalars and String values never look numeric.
+
+use proptest::collection::{btree_set, vec};
+use proptest::prelude::*;
+use vcf_parser::{
+    to_vcf_string, ContigInfo, FilterDefinition, FilterStatus, FormatDefinition, Genotype,
+    InfoDefinition, InfoValue, SampleData, VcfHeader, VcfParser, VcfRecord,
+};
+
+const TYPES: [&str; 4] = ["Integer", "Float", "String", "Character"];
+const NUMBERS: [&str; 6] = ["1", "2", "A", "R", "G", "."];
+
+fn identifier() -> impl Strategy<Value = String> {
+    "[A-Za-z_][A-Za-z0-9_]{0,7}"
+}
+
+/// Text that does not parse as a number, so it stays a String
+fn word() -> impl Strategy<Value = String> {
+    "[A-Za-z][A-Za-z0-9_.+-]{0,9}".prop_filter("numeric", |s| s.parse::<f64>().is_err())
+}
+
+/// Header descriptions, including the characters that need quoting
+fn description() -> impl Strategy<Value = String> {
+    r#"[A-Za-z0-9 ,;=<>"\\/().-]{0,24}"#
+}
+
+fn finite_float() -> impl Strategy<Value = f64> {
+    prop_oneof![
+        any::<f64>().prop_filter("finite", |f| f.is_finite()),
+        Just(0.0),
+        Just(-0.0),
+        Just(1.0),
+        Just(30.1),
+        Just(1e-5),
+        (-1000i32..1000).prop_map(f64::from),
+    ]
+}
+
+fn info_definition(id: String) -> impl Strategy<Value = InfoDefinition> {
+    let flag = description().prop_map({
+        let id = id.clone();
+        move |description| InfoDefinition {
+            id: id.clone(),
+            number: "0".to_string(),
+            field_type: "Flag".to_string(),
+            description,
+        }
+    });
+    let valued = (
+        prop::sample::select(&NUMBERS[..]),
+        prop::sample::select(&TYPES[..]),
+        description(),
+    )
+        .prop_map(move |(number, field_type, description)| InfoDefinition {
+            id: id.clone(),
+            number: number.to_string(),
+            field_type: field_type.to_string(),
+            description,
+        });
+    prop_oneof![1 => flag, 4 => valued]
+}
+
+fn format_definition(id: String) -> impl Strategy<Value = FormatDefinition> {
+    (
+        prop::sample::select(&NUMBERS[..]),
+        prop::sample::select(&TYPES[..3]),
+        description(),
+    )
+        .prop_map(move |(number, field_type, description)| FormatDefinition {
+            id: id.clone(),
+            number: number.to_string(),
+            field_type: field_type.to_string(),
+            description,
+        })
+}
+
+/// A header as built in code (no raw meta lines); GT is always declared
+/// when there are samples
+fn header() -> impl Strategy<Value = VcfHeader> {
+    (
+        prop::sample::select(&["VCFv4.1", "VCFv4.2", "VCFv4.3"][..]),
+        btree_set(identifier(), 0..6),
+        btree_set(identifier().prop_map(|id| format!("F{}", id)), 0..4),
+        btree_set(identifier(), 0..3),
+        btree_set(identifier(), 0..4),
+        vec(
+            ("chr[0-9XYM]{1,2}", prop::option::of(1u64..=300_000_000)),
+            0..3,
+        ),
+    )
+        .prop_flat_map(
+            |(file_format, info_ids, format_ids, filter_ids, samples, contigs)| {
+                let info = info_ids
+                    .into_iter()
+                    .map(info_definition)
+                    .collect::<Vec<_>>();
+                let format = format_ids
+                    .into_iter()
+                    .map(format_definition)
+                    .collect::<Vec<_>>();
+                let filters = filter_ids
+                    .into_iter()
+                    .filter(|id| id != "PASS")
+                    .map(|id| description().prop_map(move |d| (id.clone(), d)))
+                    .collect::<Vec<_>>();
+                (
+                    Just(file_format),
+                    info,
+                    format,
+                    filters,
+                    Just(samples),
+                    Just(contigs),
+                )
+            },
+        )
+        .prop_map(
+            |(file_format, info_fields, mut format_fields, filters, samples, contigs)| {
+                if !samples.is_empty() {
+                    format_fields.insert(
+                        0,
+                        FormatDefinition {
+                            id: "GT".to_string(),
+                            number: "1".to_string(),
+                            field_type: "String".to_string(),
+                            description: "Genotype".to_string(),
+                        },
+                    );
+                }
+                let mut contig_ids: Vec<String> = Vec::new();
+                let contigs = contigs
+                    .into_iter()
+                    .filter(|(id, _)| {
+                        let new = !contig_ids.contains(id);
+                        contig_ids.push(id.clone());
+                        new
+                    })
+                    .map(|(id, length)| ContigInfo {
+                        id,
+                        length,
+                        estimated: false,
+                    })
+                    .collect();
+                VcfHeader {
+                    file_format: file_format.to_string(),
+                    contigs,
+                    info_fields,
+                    format_fields,
+                    filters: filters
+                        .into_iter()
+                        .map(|(id, description)| FilterDefinition { id, description })
+                        .collect(),
+                    samples: samples.into_iter().collect(),
+                    ..VcfHeader::default()
+                }
+            },
+        )
+}
+
+/// How many values a Number takes at a site with `n_alts` ALT alleles
+fn value_count(number: &str, n_alts: usize) -> BoxedStrategy<usize> {
+    match number {
+        "1" => Just(1).boxed(),
+        "2" => Just(2).boxed(),
+        "A" => Just(n_alts.max(1)).boxed(),
+        "R" => Just(n_alts + 1).boxed(),
+        "G" => Just((n_alts + 1) * (n_alts + 2) / 2).boxed(),
+        _ => (1usize..4).boxed(),
+    }
+}
+
+fn info_value(definition: &InfoDefinition, n_alts: usize) -> BoxedStrategy<InfoValue> {
+    if definition.field_type == "Flag" {
+        return Just(InfoValue::Flag).boxed();
+    }
+    let field_type = definition.field_type.clone();
+    value_count(&definition.number, n_alts)
+        .prop_flat_map(move |count| match (field_type.as_str(), count) {
+            ("Integer", 1) => any::<i64>().prop_map(InfoValue::Integer).boxed(),
+            ("Integer", n) => vec(any::<i64>(), n)
+                .prop_map(InfoValue::IntegerArray)
+                .boxed(),
+            ("Float", 1) => finite_float().prop_map(InfoValue::Float).boxed(),
+            ("Float", n) => vec(finite_float(), n)
+                .prop_map(InfoValue::FloatArray)
+                .boxed(),
+            ("Character", 1) => "[A-Za-z]".prop_map(InfoValue::String).boxed(),
+            ("Character", n) => vec("[A-Za-z]", n).prop_map(InfoValue::StringArray).boxed(),
+            (_, 1) => word().prop_map(InfoValue::String).boxed(),
+            (_, n) => vec(word(), n).prop_map(InfoValue::StringArray).boxed(),
+        })
+        .boxed()
+}
+
+fn format_value(definition: &FormatDefinition) -> BoxedStrategy<String> {
+    let one: BoxedStrategy<String> = match definition.field_type.as_str() {
+        "Integer" => any::<i32>().prop_map(|v| v.to_string()).boxed(),
+        "Float" => finite_float().prop_map(|v| format!("{:?}", v)).boxed(),
+        _ => word().boxed(),
+    };
+    prop_oneof![
+        1 => Just(".".to_string()),
+        6 => vec(one, 1..4).prop_map(|values| values.join(",")),
+    ]
+    .boxed()
+}
+
+/// Genotypes of ploidy 1-3, normalised as the parser reads them: a fully
+/// missing call (`.`, `./.`, `.|.|.`, ...) is `None`
+fn genotype() -> impl Strategy<Value = Option<Genotype>> {
+    prop_oneof![
+        1 => Just(None),
+        6 => (vec(prop::option::weighted(0.8, 0u8..4), 1..=3), any::<bool>())
+            .prop_map(|(alleles, phased)| {
+                if alleles.iter().all(Option::is_none) {
+                    return None;
+                }
+                // A haploid call has no separator to carry phase
+                let phased = phased && alleles.len() > 1;
+                Some(Genotype { alleles, phased })
+            }),
+    ]
+}
+
+fn allele() -> impl Strategy<Value = String> {
+    prop_oneof![
+        8 => "[ACGT]{1,8}",
+        1 => prop::sample::select(&["<DEL>", "<DUP>", "<INS>", "<*>", "*"][..])
+            .prop_map(String::from),
+    ]
+}
+
+fn record(header: &VcfHeader) -> impl Strategy<Value = VcfRecord> {
+    let header = header.clone();
+    vec(allele(), 0..3).prop_flat_map(move |alternate| {
+        let n_alts = alternate.len();
+        let info = header
+            .info_fields
+            .iter()
+            .map(|d| {
+                prop::option::of(info_value(d, n_alts).prop_map({
+                    let id = d.id.clone();
+                    move |v| (id.clone(), v)
+                }))
+            })
+            .collect::<Vec<_>>();
+        let fields: Vec<FormatDefinition> = header
+            .format_fields
+            .iter()
+            .filter(|d| d.id != "GT")
+            .cloned()
+            .collect();
+        let samples = header
+            .samples
+            .iter()
+            .map(|name| {
+                let values = fields.iter().map(format_value).collect::<Vec<_>>();
+                (Just(name.clone()), genotype(), values)
+            })
+            .collect::<Vec<_>>();
+        let filter_ids: Vec<String> = header.filters.iter().map(|f| f.id.clone()).collect();
+        let n_filters = filter_ids.len();
+        let filter = if filter_ids.is_empty() {
+            prop_oneof![Just(FilterStatus::Pass), Just(FilterStatus::Missing)].boxed()
+        } else {
+            prop_oneof![
+                Just(FilterStatus::Pass),
+                Just(FilterStatus::Missing),
+                prop::sample::subsequence(filter_ids, 1..=n_filters).prop_map(FilterStatus::Failed),
+            ]
+            .boxed()
+        };
+        let field_ids: Vec<String> = fields.iter().map(|d| d.id.clone()).collect();
+        let present = prop::sample::subsequence(field_ids.clone(), 0..=field_ids.len());
+
+        (
+            "chr[0-9XYM]{1,2}|[A-Za-z][A-Za-z0-9_.]{0,11}",
+            1u64..=u64::MAX,
+            prop::option::of("(rs[0-9]{1,9}|[A-Za-z][A-Za-z0-9_]{0,7})"),
+            "[ACGTN]{1,8}",
+            Just(alternate.clone()),
+            prop::option::of(finite_float().prop_map(f64::abs)),
+            filter,
+            info,
+            samples,
+            present,
+            Just(field_ids),
+        )
+            .prop_map(
+                |(
+                    chrom,
+                    pos,
+                    id,
+                    reference,
+                    alternate,
+                    qual,
+                    filter,
+                    info,
+                    samples,
+                    present,
+                    field_ids,
+                )| {
+                    let samples = samples
+                        .into_iter()
+                        .map(|(name, genotype, values)| SampleData {
+                            name,
+                            gt_present: genotype.is_some(),
+                            genotype,
+                            // Every sample lists the same keys, as in a
+                            // real FORMAT column
+                            fields: field_ids
+                                .iter()
+                                .cloned()
+                                .zip(values)
+                                .filter(|(key, _)| present.contains(key))
+                                .collect(),
+                        })
+                        .collect();
+                    let mut record = VcfRecord::new(&chrom, pos, &reference, Vec::new());
+                    record.id = id;
+                    record.alternate = alternate;
+                    record.qual = qual;
+                    record.filter = filter;
+                    record.info = info
+                        .into_iter()
+                        .flatten()
+                        .map(|(key, value)| (key.into(), value))
+                        .collect();
+                    record.samples = samples;
+                    record
+                },
+            )
+    })
+}
+
+fn header_and_records() -> impl Strategy<Value = (VcfHeader, Vec<VcfRecord>)> {
+    header().prop_flat_map(|header| {
+        let records = vec(record(&header), 1..4);
+        (Just(header), records)
+    })
+}
+
+fn same_float(a: f64, b: f64) -> bool {
+    a.to_bits() == b.to_bits()
+}
+
+fn same_info(a: &InfoValue, b: &InfoValue) -> bool {
+    match (a, b) {
+        (InfoValue::Flag, InfoValue::Flag) => true,
+        (InfoValue::Integer(a), InfoValue::Integer(b)) => a == b,
+        (InfoValue::Float(a), InfoValue::Float(b)) => same_float(*a, *b),
+        (InfoValue::String(a), InfoValue::String(b)) => a == b,
+        (InfoValue::IntegerArray(a), InfoValue::IntegerArray(b)) => a == b,
+        (InfoValue::FloatArray(a), InfoValue::FloatArray(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_float(*a, *b))
+        }
+        (InfoValue::StringArray(a), InfoValue::StringArray(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn assert_same_record(written: &VcfRecord, parsed: &VcfRecord) -> Result<(), TestCaseError> {
+    prop_assert_eq!(&parsed.chrom, &written.chrom);
+    prop_assert_eq!(parsed.pos, written.pos);
+    prop_assert_eq!(&parsed.id, &written.id);
+    prop_assert_eq!(&parsed.reference, &written.reference);
+    prop_assert_eq!(&parsed.alternate, &written.alternate);
+    prop_assert_eq!(
+        parsed.qual.map(f64::to_bits),
+        written.qual.map(f64::to_bits)
+    );
+    prop_assert_eq!(&parsed.filter, &written.filter);
+
+    prop_assert_eq!(parsed.info.len(), written.info.len());
+    for (key, value) in &written.info {
+        let back = parsed.info.get(key);
+        prop_assert!(
+            back.is_some_and(|back| same_info(value, back)),
+            "INFO {}: wrote {:?}, read {:?}",
+            key,
+            value,
+            back
+        );
+    }
+
+    prop_assert_eq!(parsed.samples.len(), written.samples.len());
+    for (ours, theirs) in written.samples.iter().zip(&parsed.samples) {
+        prop_assert_eq!(&theirs.name, &ours.name);
+        prop_assert_eq!(
+            theirs.genotype.as_ref().map(|g| (&g.alleles, g.phased)),
+            ours.genotype.as_ref().map(|g| (&g.alleles, g.phased))
+        );
+        prop_assert_eq!(&theirs.fields, &ours.fields);
+    }
+    Ok(())
+}
+
+fn definitions<T, K: Ord>(items: &[T], key: impl Fn(&T) -> K) -> Vec<K> {
+    let mut keys: Vec<K> = items.iter().map(key).collect();
+    keys.sort();
+    keys
+}
+
+proptest! {
+    /// parse(write(x)) == x for the header's definitions and every record
+    /// field
+    #[test]
+    fn write_then_parse_preserves_fields((header, records) in header_and_records()) {
+        let text = to_vcf_string(&header, &records, true).unwrap();
+        let mut parser = VcfParser::new();
+        let (parsed_header, parsed) = parser.parse_str(&text).unwrap();
+        prop_assert_eq!(parser.warning_total(), 0, "{}", text);
+
+        prop_assert_eq!(&parsed_header.file_format, &header.file_format);
+        prop_assert_eq!(&parsed_header.samples, &header.samples);
+        prop_assert_eq!(
+            definitions(&parsed_header.contigs, |c| (c.id.clone(), c.length)),
+            definitions(&header.contigs, |c| (c.id.clone(), c.length))
+        );
+        let info = |d: &InfoDefinition| {
+            (d.id.clone(), d.number.clone(), d.field_type.clone(), d.description.clone())
+        };
+        prop_assert_eq!(
+            definitions(&parsed_header.info_fields, info),
+            definitions(&header.info_fields, info)
+        );
+        let format = |d: &FormatDefinition| {
+            (d.id.clone(), d.number.clone(), d.field_type.clone(), d.description.clone())
+        };
+        prop_assert_eq!(
+            definitions(&parsed_header.format_fields, format),
+            definitions(&header.format_fields, format)
+        );
+        let filter = |d: &FilterDefinition| (d.id.clone(), d.description.clone());
+        prop_assert_eq!(
+            definitions(&parsed_header.filters, filter),
+            definitions(&header.filters, filter)
+        );
+
+        prop_assert_eq!(parsed.len(), records.len());
+        for (written, parsed) in records.iter().zip(&parsed) {
+            assert_same_record(written, parsed)?;
+        }
+    }
+
+    /// Text written from parsed records is a fixed point of parse + write
+    #[test]
+    fn parse_then_write_is_a_fixed_point((header, records) in header_and_records()) {
+        let text = to_vcf_string(&header, &records, true).unwrap();
+        let (header, records) = VcfParser::new().parse_str(&text).unwrap();
+        let once = to_vcf_string(&header, &records, true).unwrap();
+        let (header, records) = VcfParser::new().parse_str(&once).unwrap();
+        let twice = to_vcf_string(&header, &records, true).unwrap();
+        prop_assert_eq!(once, twice);
+    }
+}
+
+#[test]
+fn float_info_values_stay_floats() {
+    let header = VcfHeader {
+        info_fields: vec![InfoDefinition {
+            id: "AF".to_string(),
+            number: "A".to_string(),
+            field_type: "Float".to_string(),
+            description: "Allele frequency".to_string(),
+        }],
+        ..VcfHeader::default()
+    };
+    let mut record = VcfRecord::new("chr1", 1, "A", vec!["G", "T"]);
+    record
+        .info
+        .insert("AF".into(), InfoValue::FloatArray(vec![1.0, -0.0]));
+
+    let text = to_vcf_string(&header, [&record], false).unwrap();
+    let (_, parsed) = VcfParser::new().parse_str(&text).unwrap();
+    assert!(
+        same_info(&parsed[0].info["AF"], &record.info["AF"]),
+        "{}",
+        text
+    );
+}
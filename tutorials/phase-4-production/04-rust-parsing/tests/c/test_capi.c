@@ -0,0 +1,101 @@
+/*
+ * Tests for the C API (rust-vcf-parser `capi` feature)
+ *
+ * Built and run by rust-vcf-parser/tests/capi.rs:
+ *   cd rust-vcf-parser && cargo test --features capi --test capi
+ *
+ * Usage: test_capi <shared-records.vcf>
+ */
+
+#include <math.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "vcf_parser.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                   \
+    do {                                                              \
+        if (!(cond)) {                                                \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,   \
+                    __LINE__, #cond);                                 \
+            failures++;                                               \
+        }                                                             \
+    } while (0)
+
+/* Read a string field, sizing the buffer with a first NULL call */
+static char *record_chrom(const VcfHandle *handle, size_t index) {
+    size_t size = vcf_record_chrom(handle, index, NULL, 0);
+    char *buf = size ? malloc(size) : NULL;
+    if (buf) {
+        vcf_record_chrom(handle, index, buf, size);
+    }
+    return buf;
+}
+
+int main(int argc, char **argv) {
+    if (argc != 2) {
+        fprintf(stderr, "usage: %s <shared-records.vcf>\n", argv[0]);
+        return 2;
+    }
+
+    VcfHandle *handle = NULL;
+    VcfErrorCode code = vcf_parse_file(argv[1], &handle);
+    if (code != VCF_ERROR_CODE_OK) {
+        char message[256];
+        vcf_last_error_message(message, sizeof message);
+        fprintf(stderr, "vcf_parse_file failed (%d): %s\n", code, message);
+        return 1;
+    }
+
+    CHECK(vcf_record_count(handle) == 5);
+
+    char *chrom = record_chrom(handle, 4);
+    CHECK(chrom && strcmp(chrom, "chrX") == 0);
+    free(chrom);
+
+    CHECK(vcf_record_pos(handle, 0) == 100);
+    CHECK(vcf_record_qual(handle, 2) == 50.5);
+    CHECK(isnan(vcf_record_qual(handle, 1)));
+
+    char buf[16];
+    CHECK(vcf_record_alt(handle, 2, buf, sizeof buf) == 4);
+    CHECK(strcmp(buf, "T,G") == 0);
+    CHECK(vcf_record_filter(handle, 1, buf, sizeof buf) == 10);
+    CHECK(strcmp(buf, "q10;lowDP") == 0);
+    CHECK(vcf_record_id(handle, 1, buf, sizeof buf) == 2);
+    CHECK(strcmp(buf, ".") == 0);
+
+    /* A buffer that is too small is not written */
+    strcpy(buf, "unchanged");
+    CHECK(vcf_record_ref(handle, 4, buf, 4) == 5);
+    CHECK(strcmp(buf, "unchanged") == 0);
+
+    VcfStats stats;
+    CHECK(vcf_stats(handle, &stats) == VCF_ERROR_CODE_OK);
+    CHECK(stats.total_records == 5);
+    CHECK(stats.snps == 2);
+    CHECK(stats.insertions == 1);
+    CHECK(stats.deletions == 2);
+    CHECK(stats.chromosomes == 3);
+
+    /* Errors are reported through codes and the last error message */
+    CHECK(vcf_record_chrom(handle, 5, buf, sizeof buf) == 0);
+    CHECK(vcf_last_error_message(buf, sizeof buf) > 1);
+    CHECK(vcf_stats(handle, NULL) == VCF_ERROR_CODE_NULL_POINTER);
+
+    vcf_free(handle);
+
+    VcfHandle *missing = NULL;
+    CHECK(vcf_parse_file("does-not-exist.vcf", &missing) == VCF_ERROR_CODE_IO);
+    CHECK(missing == NULL);
+
+    if (failures) {
+        fprintf(stderr, "%d check(s) failed\n", failures);
+        return 1;
+    }
+    printf("C API: all checks passed\n");
+    return 0;
+}
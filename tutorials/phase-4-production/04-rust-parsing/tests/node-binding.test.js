@@ -0,0 +1,696 @@
+/**
+ * Tests for the Node.js native binding
+ *
+ * Needs the built module: run `npm run build:node` and copy
+ * target/release/libvcf_parser_node.so (.dylib / .dll) to
+ * node-binding/vcf-parser-node.node. Skipped when it is missing.
+ */
+
+import { describe, it, expect, beforeAll, afterAll } from 'vitest';
+import { createRequire } from 'module';
+import { existsSync, mkdtempSync, readFileSync, rmSync, truncateSync, writeFileSync } from 'fs';
+import { gzipSync } from 'zlib';
+import { tmpdir } from 'os';
+import { join, dirname } from 'path';
+import { fileURLToPath } from 'url';
+
+const __dirname = dirname(fileURLToPath(import.meta.url));
+const BINDING_PATH = join(__dirname, '..', 'node-binding', 'vcf-parser-node.node');
+const binding = existsSync(BINDING_PATH) ? createRequire(import.meta.url)(BINDING_PATH) : null;
+
+const SAMPLE_VCF = `##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description="Total Depth">
+##FORMAT=<ID=GT,Number=1,Type=String,Description="Genotype">
+#CHROM	POS	ID	REF	ALT	QUAL	FILTER	INFO	FORMAT	SAMPLE1	SAMPLE2
+chr1	100	rs123	A	G	30	PASS	DP=50	GT	0/1	1/1
+chr1	200	.	AT	A	40	PASS	DP=60	GT	0/0	0/1
+chr2	300	rs456	C	T,G	50	q10	DP=70	GT	1/2	0/1
+chr2	400	rs789	G	GATC	60	PASS	DP=80	GT	0/1	1/1
+chrX	500	.	AGTC	A	70	PASS	DP=90	GT	0/0	0/1
+`;
+
+describe.skipIf(!binding)('Node binding async parsing', () => {
+  let dir;
+  let vcfPath;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+    vcfPath = join(dir, 'sample.vcf');
+    writeFileSync(vcfPath, SAMPLE_VCF);
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('parseFileAsync resolves with the same result as parseFile', async () => {
+    const parser = new binding.VcfParserNode();
+    const sync = parser.parseFile(vcfPath);
+    const pending = parser.parseFileAsync(vcfPath);
+
+    expect(pending).toBeInstanceOf(Promise);
+    const result = await pending;
+
+    expect(result.records).toHaveLength(sync.records.length);
+    expect(result.stats.totalRecords).toBe(5);
+    expect(result.stats.snps).toBe(sync.stats.snps);
+    expect(result.stats.deletions).toBe(sync.stats.deletions);
+    expect(result.header.samples).toEqual(['SAMPLE1', 'SAMPLE2']);
+    expect(result.records[2].filter).toBe('q10');
+  });
+
+  it('parseStringAsync matches parseString', async () => {
+    const parser = binding.VcfParserNode.fast();
+    const result = await parser.parseStringAsync(SAMPLE_VCF);
+
+    expect(result.stats).toEqual(parser.parseString(SAMPLE_VCF).stats);
+  });
+
+  it('getStatsAsync matches getStats', async () => {
+    const parser = new binding.VcfParserNode();
+
+    expect(await parser.getStatsAsync(vcfPath)).toEqual(parser.getStats(vcfPath));
+  });
+
+  it('rejects with the same errors as the sync path', async () => {
+    const parser = new binding.VcfParserNode();
+
+    await expect(parser.parseFileAsync(join(dir, 'missing.vcf'))).rejects.toThrow(
+      /Failed to read file/
+    );
+    await expect(parser.parseStringAsync('chr1\t100\t.\tA\tG')).rejects.toThrow(/Parse error/);
+  });
+
+  it('can be aborted before it starts', async () => {
+    const parser = new binding.VcfParserNode();
+    const controller = new AbortController();
+    const pending = parser.parseFileAsync(vcfPath, controller.signal);
+    controller.abort();
+
+    await expect(pending).rejects.toThrow(/AbortError/);
+  });
+});
+
+describe.skipIf(!binding)('Node binding record streaming', () => {
+  let dir;
+  let vcfPath;
+  let largePath;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+    vcfPath = join(dir, 'sample.vcf');
+    writeFileSync(vcfPath, SAMPLE_VCF);
+
+    // Enough records to span several callback batches
+    const lines = [];
+    for (let i = 1; i <= 2500; i++) {
+      lines.push(`chr1\t${i}\t.\tA\t${i % 3 ? 'G' : 'AT'}\t30\tPASS\tDP=10\tGT\t0/1\t1/1`);
+    }
+    largePath = join(dir, 'large.vcf');
+    writeFileSync(largePath, SAMPLE_VCF.split('chr1')[0] + lines.join('\n') + '\n');
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('streams the same records and stats as parseFile', async () => {
+    const parser = new binding.VcfParserNode();
+    const records = [];
+    const stats = await parser.forEachRecord(vcfPath, (batch) => records.push(...batch));
+    const expected = parser.parseFile(vcfPath);
+
+    expect(records).toEqual(expected.records);
+    expect(stats).toEqual(expected.stats);
+  });
+
+  it('delivers large files in ordered batches of at most 1000', async () => {
+    const parser = new binding.VcfParserNode();
+    const sizes = [];
+    const positions = [];
+    await parser.forEachRecord(largePath, (batch) => {
+      sizes.push(batch.length);
+      positions.push(...batch.map((r) => r.pos));
+    });
+
+    expect(sizes).toEqual([1000, 1000, 500]);
+    expect(positions).toEqual(parser.parseFile(largePath).records.map((r) => r.pos));
+  });
+
+  it('rejects on unreadable or invalid input', async () => {
+    const parser = new binding.VcfParserNode();
+    const badPath = join(dir, 'bad.vcf');
+    writeFileSync(badPath, 'chr1\t100\t.\tA\tG\n');
+
+    await expect(parser.forEachRecord(join(dir, 'missing.vcf'), () => {})).rejects.toThrow(
+      /Failed to read file/
+    );
+    await expect(parser.forEachRecord(badPath, () => {})).rejects.toThrow(/Parse error/);
+  });
+});
+
+describe.skipIf(!binding)('Node binding gzip input', () => {
+  let dir;
+  let plainPath;
+  let gzPath;
+  let bgzfPath;
+
+  // parseTimeMs differs between runs
+  const contents = ({ parseTimeMs, ...rest }) => rest;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+    plainPath = join(dir, 'sample.vcf');
+    gzPath = join(dir, 'sample.vcf.gz');
+    bgzfPath = join(dir, 'blocks.vcf.gz');
+    writeFileSync(plainPath, SAMPLE_VCF);
+    writeFileSync(gzPath, gzipSync(SAMPLE_VCF));
+
+    // BGZF is a series of independent gzip members
+    const half = SAMPLE_VCF.length / 2;
+    writeFileSync(
+      bgzfPath,
+      Buffer.concat([gzipSync(SAMPLE_VCF.slice(0, half)), gzipSync(SAMPLE_VCF.slice(half))])
+    );
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('parses gzip and BGZF files like the plain-text file', async () => {
+    const parser = new binding.VcfParserNode();
+    const expected = contents(parser.parseFile(plainPath));
+
+    expect(contents(parser.parseFile(gzPath))).toEqual(expected);
+    expect(contents(parser.parseFile(bgzfPath))).toEqual(expected);
+    expect(contents(await parser.parseFileAsync(bgzfPath))).toEqual(expected);
+    expect(parser.getStats(gzPath)).toEqual(parser.getStats(plainPath));
+  });
+
+  it('streams gzip files through forEachRecord', async () => {
+    const parser = new binding.VcfParserNode();
+    const records = [];
+    await parser.forEachRecord(bgzfPath, (batch) => records.push(...batch));
+
+    expect(records).toEqual(parser.parseFile(plainPath).records);
+  });
+
+  it('parseBufferGzip parses compressed bytes in memory', () => {
+    const parser = new binding.VcfParserNode();
+
+    expect(contents(parser.parseBufferGzip(gzipSync(SAMPLE_VCF)))).toEqual(
+      contents(parser.parseFile(plainPath))
+    );
+    expect(() => parser.parseBufferGzip(Buffer.from(SAMPLE_VCF))).toThrow(/not gzip/);
+  });
+
+  it('reports truncated gzip input explicitly', async () => {
+    const parser = new binding.VcfParserNode();
+    const compressed = gzipSync(SAMPLE_VCF);
+    const truncated = compressed.subarray(0, compressed.length - 20);
+    const truncatedPath = join(dir, 'truncated.vcf.gz');
+    writeFileSync(truncatedPath, truncated);
+
+    expect(() => parser.parseFile(truncatedPath)).toThrow(/Truncated gzip stream/);
+    expect(() => parser.parseBufferGzip(truncated)).toThrow(/Truncated gzip stream/);
+    await expect(parser.forEachRecord(truncatedPath, () => {})).rejects.toThrow(
+      /Truncated gzip stream/
+    );
+  });
+});
+
+describe.skipIf(!binding)('Node binding 64-bit positions', () => {
+  it('keeps positions above u32::MAX exact', () => {
+    const parser = new binding.VcfParserNode();
+    const header = SAMPLE_VCF.split('chr1')[0];
+    const result = parser.parseString(
+      header + 'chrUn\t5000000123\t.\tA\tG\t30\tPASS\tDP=5\tGT\t0/1\t0/0\n'
+    );
+
+    expect(result.records[0].pos).toBe(5000000123);
+    expect(result.stats.totalRecords).toBe(1);
+  });
+});
+
+describe.skipIf(!binding)('Node binding INFO and sample data', () => {
+  it('exposes INFO values with natural JS types', () => {
+    const { records } = new binding.VcfParserNode().parseString(SAMPLE_VCF);
+
+    expect(records[0].info.DP).toBe(50);
+    expect(records[4].info).toEqual({ DP: 90 });
+  });
+
+  it('exposes sample genotypes and FORMAT fields', () => {
+    const { records } = new binding.VcfParserNode().parseString(SAMPLE_VCF);
+
+    expect(records[0].samples[0].gt).toBe('0/1');
+    expect(records[0].samples.map((s) => s.name)).toEqual(['SAMPLE1', 'SAMPLE2']);
+    expect(records[2].samples[0]).toMatchObject({ gt: '1/2', phased: false });
+  });
+
+  it('leaves info and samples out when they are not parsed', () => {
+    const fast = binding.VcfParserNode.fast().parseString(SAMPLE_VCF);
+    expect(fast.records[0].info).toBeUndefined();
+    expect(fast.records[0].samples).toBeUndefined();
+
+    const parser = new binding.VcfParserNode();
+    parser.setParseSamples(false);
+    const { records } = parser.parseString(SAMPLE_VCF);
+    expect(records[0].info.DP).toBe(50);
+    expect(records[0].samples).toBeUndefined();
+  });
+});
+
+describe.skipIf(!binding)('Node binding query', () => {
+  it('filters by chromosome range and quality', () => {
+    const parser = new binding.VcfParserNode();
+    const result = parser.query(Buffer.from(SAMPLE_VCF), {
+      chrom: 'chr2',
+      start: 250,
+      end: 450,
+      minQual: 55,
+    });
+
+    expect(result.totalMatched).toBe(1);
+    expect(result.records.map((r) => r.pos)).toEqual([400]);
+  });
+
+  it('accepts a locus string with PASS-only and variant type filters', () => {
+    const parser = new binding.VcfParserNode();
+    const result = parser.query(Buffer.from(SAMPLE_VCF), {
+      region: 'chr2:1-1,000',
+      passOnly: true,
+      variantTypes: ['ins', 'SNP'],
+    });
+
+    // chr2:300 fails its filter, chr2:400 is an insertion
+    expect(result.totalMatched).toBe(1);
+    expect(result.records[0].variantType).toBe('INS');
+  });
+
+  it('reports every match when offset and limit truncate', () => {
+    const parser = new binding.VcfParserNode();
+    const result = parser.query(Buffer.from(SAMPLE_VCF), {
+      variantTypes: ['DEL'],
+      offset: 1,
+      limit: 5,
+    });
+
+    expect(result.totalMatched).toBe(2);
+    expect(result.records.map((r) => `${r.chrom}:${r.pos}`)).toEqual(['chrX:500']);
+    expect(parser.query(Buffer.from(SAMPLE_VCF), { limit: 2 }).records).toHaveLength(2);
+  });
+
+  it('rejects invalid options', () => {
+    const parser = new binding.VcfParserNode();
+    const input = Buffer.from(SAMPLE_VCF);
+
+    expect(() => parser.query(input, { region: 'chr1:10-5' })).toThrow(/Invalid region/);
+    expect(() => parser.query(input, { region: 'chr1', chrom: 'chr1' })).toThrow(/not both/);
+    expect(() => parser.query(input, { variantTypes: ['MNP'] })).toThrow(/Unknown variant type/);
+  });
+
+  it('applies a filter expression', () => {
+    const parser = new binding.VcfParserNode();
+    parser.setParseInfo(false);
+    const input = Buffer.from(SAMPLE_VCF);
+
+    const result = parser.query(input, {
+      filterExpr: 'INFO/DP>=60 && (GT[0]="het" || TYPE="del") && FILTER="PASS"',
+    });
+    expect(result.records.map((r) => r.pos)).toEqual([200, 400, 500]);
+    expect(result.records[0].info).toBeUndefined();
+
+    expect(() => parser.query(input, { filterExpr: 'QUAL>' })).toThrow(/column 6/);
+    expect(() => parser.query(input, { filterExpr: 'INFO/AF>0.1' })).toThrow(
+      /INFO\/AF is not defined/
+    );
+  });
+});
+
+describe.skipIf(!binding)('Node binding invalid records and warnings', () => {
+  // Line 6 has a non-numeric POS
+  const MALFORMED_VCF = SAMPLE_VCF.replace('chr1\t200\t', 'chr1\tabc\t');
+
+  it('throws with the line number when invalid records are not skipped', async () => {
+    const parser = new binding.VcfParserNode();
+    parser.setSkipInvalid(false);
+
+    let error;
+    try {
+      parser.parseString(MALFORMED_VCF);
+    } catch (e) {
+      error = e;
+    }
+    expect(error.message).toMatch(/Invalid position 'abc' at line 6/);
+    expect(error.code).toBe('INVALID_POSITION');
+    expect(error.line).toBe(6);
+    expect(error.field).toBe('POS');
+
+    await parser.parseStringAsync(MALFORMED_VCF).catch((e) => (error = e));
+    expect(error.line).toBe(6);
+    expect(() => parser.query(Buffer.from(MALFORMED_VCF))).toThrow(/at line 6/);
+  });
+
+  it('skips invalid records with one warning by default', () => {
+    const result = new binding.VcfParserNode().parseString(MALFORMED_VCF);
+
+    expect(result.records).toHaveLength(4);
+    expect(result.warnings).toHaveLength(1);
+    expect(result.warnings[0]).toMatchObject({ line: 6, category: 'other' });
+    expect(result.warningSummary).toEqual({ total: 1, byCategory: { other: 1 } });
+  });
+
+  it('fails once more than maxInvalidRecords are skipped', () => {
+    const parser = new binding.VcfParserNode();
+    parser.setMaxInvalidRecords(0);
+    expect(() => parser.parseString(MALFORMED_VCF)).toThrow(/Too many invalid records/);
+
+    parser.setMaxInvalidRecords(null);
+    parser.setCollectWarnings(false);
+    const result = parser.parseString(MALFORMED_VCF);
+    expect(result.records).toHaveLength(4);
+    expect(result.warnings).toHaveLength(0);
+  });
+});
+
+describe.skipIf(!binding)('Node binding columnar output', () => {
+  let dir;
+  let vcfPath;
+  let afPath;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+    vcfPath = join(dir, 'sample.vcf');
+    writeFileSync(vcfPath, SAMPLE_VCF);
+
+    afPath = join(dir, 'af.vcf');
+    const withAf = SAMPLE_VCF.replace(
+      '##FORMAT',
+      '##INFO=<ID=AF,Number=A,Type=Float,Description="Allele Frequency">\n##FORMAT'
+    ).replace('DP=50', 'DP=50;AF=0.25');
+    writeFileSync(afPath, withAf);
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('returns typed columns matching parseFile', () => {
+    const parser = new binding.VcfParserNode();
+    const columns = parser.parseColumnar(vcfPath);
+    const { records } = parser.parseFile(vcfPath);
+
+    expect(columns.positions).toBeInstanceOf(Float64Array);
+    expect(columns.chromIds).toBeInstanceOf(Uint16Array);
+    expect(columns.typeCodes).toBeInstanceOf(Uint8Array);
+    for (const column of ['positions', 'quals', 'chromIds', 'refs', 'alts', 'typeCodes']) {
+      expect(columns[column]).toHaveLength(records.length);
+    }
+
+    expect(Array.from(columns.positions)).toEqual(records.map((r) => r.pos));
+    expect(columns.chromNames).toEqual(['chr1', 'chr2', 'chrX']);
+    expect(Array.from(columns.chromIds)).toEqual([0, 0, 1, 1, 2]);
+    expect(columns.alts[2]).toBe('T,G');
+    expect(columns.typeLabels[columns.typeCodes[1]]).toBe(records[1].variantType);
+    expect(columns.info).toBeUndefined();
+  });
+
+  it('extracts INFO columns using the header types', () => {
+    const columns = new binding.VcfParserNode().parseColumnar(afPath, {
+      infoColumns: ['DP', 'AF'],
+    });
+
+    expect(columns.info.DP).toBeInstanceOf(Int32Array);
+    expect(Array.from(columns.info.DP)).toEqual([50, 60, 70, 80, 90]);
+    expect(columns.info.AF).toBeInstanceOf(Float64Array);
+    expect(columns.info.AF[0]).toBe(0.25);
+    expect(Number.isNaN(columns.info.AF[1])).toBe(true);
+  });
+
+  it('rejects INFO columns missing from the header', () => {
+    const parser = new binding.VcfParserNode();
+
+    expect(() => parser.parseColumnar(vcfPath, { infoColumns: ['AF'] })).toThrow(
+      /not defined in the header/
+    );
+  });
+});
+
+describe.skipIf(!binding)('Node binding streaming stats', () => {
+  let dir;
+  let largePath;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+    const lines = [];
+    for (let i = 1; i <= 100000; i++) {
+      const chrom = i <= 60000 ? 'chr1' : 'chr2';
+      const alt = ['G', 'C', 'AT', 'T'][i % 4];
+      const filter = i % 10 ? 'PASS' : 'q10';
+      lines.push(`${chrom}\t${i}\t.\tA\t${alt}\t30\t${filter}\tDP=10\tGT\t0/1\t1/1`);
+    }
+    largePath = join(dir, 'large.vcf');
+    writeFileSync(largePath, SAMPLE_VCF.split('chr1')[0] + lines.join('\n') + '\n');
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('counts a 100k-record file like parseFile without returning records', () => {
+    const parser = new binding.VcfParserNode();
+    const stats = parser.getStats(largePath);
+
+    expect(stats.records).toBeUndefined();
+    expect(stats).toEqual(parser.parseFile(largePath).stats);
+    expect(stats.totalRecords).toBe(100000);
+    expect(stats.failedFilter).toBe(10000);
+  });
+
+  it('reports Ts/Tv and per-chromosome counts', async () => {
+    const stats = await new binding.VcfParserNode().getStatsAsync(largePath);
+
+    // A>G is a transition; A>C and A>T are transversions
+    expect(stats.transitions).toBe(25000);
+    expect(stats.transversions).toBe(50000);
+    expect(stats.tsTvRatio).toBe(0.5);
+    expect(stats.perChromosome).toEqual([
+      { chrom: 'chr1', records: 60000, snps: 45000, indels: 15000 },
+      { chrom: 'chr2', records: 40000, snps: 30000, indels: 10000 },
+    ]);
+  });
+
+  it('rejects invalid files', () => {
+    const badPath = join(dir, 'bad.vcf');
+    writeFileSync(badPath, 'chr1\t100\t.\tA\tG\n');
+
+    expect(() => new binding.VcfParserNode().getStats(badPath)).toThrow(/Parse error/);
+  });
+});
+
+describe.skipIf(!binding)('Node binding header inspection', () => {
+  const HEADER = SAMPLE_VCF.split('chr1')[0].replace(
+    '##INFO',
+    '##contig=<ID=chr1,length=248956422>\n##FILTER=<ID=q10,Description="Quality below 10">\n##INFO'
+  );
+  let dir;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('returns header definitions as objects', () => {
+    const path = join(dir, 'sample.vcf');
+    writeFileSync(path, HEADER + SAMPLE_VCF.slice(SAMPLE_VCF.indexOf('chr1\t')));
+
+    const header = new binding.VcfParserNode().parseHeaderFile(path);
+
+    expect(header.samples).toEqual(['SAMPLE1', 'SAMPLE2']);
+    expect(header.infoFields).toEqual([
+      { id: 'DP', number: '1', fieldType: 'Integer', description: 'Total Depth' },
+    ]);
+    expect(header.formatFields.map((f) => f.id)).toEqual(['GT']);
+    expect(header.contigs).toEqual([{ id: 'chr1', length: 248956422 }]);
+    expect(header.filters).toEqual([{ id: 'q10', description: 'Quality below 10' }]);
+  });
+
+  it('never reads past the #CHROM line of a 1GB file', () => {
+    // Sparse file whose body is unreadable: invalid UTF-8 then NUL bytes
+    const path = join(dir, 'huge.vcf');
+    writeFileSync(path, Buffer.concat([Buffer.from(HEADER), Buffer.from([0xff, 0xfe])]));
+    truncateSync(path, 1024 ** 3);
+
+    expect(new binding.VcfParserNode().parseHeaderFile(path).samples).toEqual([
+      'SAMPLE1',
+      'SAMPLE2',
+    ]);
+  });
+
+  it('decompresses only the blocks holding the header', () => {
+    // A full read would hit the truncated second member
+    const body = gzipSync(SAMPLE_VCF.slice(SAMPLE_VCF.indexOf('chr1\t')).repeat(1000));
+    const path = join(dir, 'huge.vcf.gz');
+    writeFileSync(path, Buffer.concat([gzipSync(HEADER), body.subarray(0, body.length / 2)]));
+
+    const parser = new binding.VcfParserNode();
+    expect(parser.parseHeaderFile(path).infoFields[0].id).toBe('DP');
+    expect(() => parser.parseFile(path)).toThrow(/Truncated gzip/);
+  });
+
+  it('rejects files without a #CHROM line', () => {
+    const path = join(dir, 'headless.vcf');
+    writeFileSync(path, '##fileformat=VCFv4.2\n');
+
+    expect(() => new binding.VcfParserNode().parseHeaderFile(path)).toThrow(/Parse error/);
+  });
+});
+
+describe.skipIf(!binding)('Node binding multi-file stats', () => {
+  let dir;
+  let paths;
+  let fullPath;
+
+  beforeAll(() => {
+    dir = mkdtempSync(join(tmpdir(), 'vcf-node-'));
+    fullPath = join(dir, 'all.vcf');
+    writeFileSync(fullPath, SAMPLE_VCF);
+    const header = SAMPLE_VCF.split('chr1')[0];
+    const records = SAMPLE_VCF.slice(SAMPLE_VCF.indexOf('chr1\t')).trimEnd().split('\n');
+    // Per-chromosome files, as a cohort split by contig would be
+    paths = ['chr1', 'chr2', 'chrX'].map((chrom) => {
+      const path = join(dir, `${chrom}.vcf`);
+      const lines = records.filter((line) => line.startsWith(`${chrom}\t`));
+      writeFileSync(path, header + lines.join('\n') + '\n');
+      return path;
+    });
+  });
+
+  afterAll(() => {
+    rmSync(dir, { recursive: true, force: true });
+  });
+
+  it('merges per-file stats into the sum of the individual stats', async () => {
+    const parser = new binding.VcfParserNode();
+    const { stats, errors } = await parser.getStatsMulti(paths, 2);
+    const single = paths.map((path) => parser.getStats(path));
+    const sum = (key) => single.reduce((total, s) => total + s[key], 0);
+
+    expect(errors).toEqual([]);
+    for (const key of ['totalRecords', 'snps', 'insertions', 'deletions', 'passedFilter']) {
+      expect(stats[key]).toBe(sum(key));
+    }
+    expect(stats.transitions).toBe(sum('transitions'));
+    expect(stats.transversions).toBe(sum('transversions'));
+    expect(stats.chromosomes).toEqual(['chr1', 'chr2', 'chrX']);
+    expect(stats).toEqual(parser.getStats(fullPath));
+  });
+
+  it('lists failing files without failing the batch', async () => {
+    const parser = new binding.VcfParserNode();
+    const missing = join(dir, 'missing.vcf');
+    const { stats, errors } = await parser.getStatsMulti([paths[0], missing, paths[1]], 4);
+
+    expect(stats.totalRecords).toBe(4);
+    expect(errors).toHaveLength(1);
+    expect(errors[0].path).toBe(missing);
+    expect(errors[0].message).toMatch(/Failed to read file/);
+  });
+
+  it('rejects on the first failure with failFast', async () => {
+    const parser = new binding.VcfParserNode();
+    const missing = join(dir, 'missing.vcf');
+
+    await expect(
+      parser.getStatsMulti([...paths, missing], 1, { failFast: true })
+    ).rejects.toThrow(/missing\.vcf: Failed to read file/);
+    expect(() => parser.getStatsMulti(paths, 0)).toThrow(/concurrency/);
+  });
+});
+
+describe.skipIf(!binding)('Node binding indexed queries', () => {
+  // bgzipped fixture with its tabix index alongside
+  const vcfPath = join(__dirname, 'data', 'indexed.vcf.gz');
+  let all;
+
+  beforeAll(() => {
+    all = new binding.VcfParserNode().parseFile(vcfPath).records;
+  });
+
+  const inRegion = (chrom, start, end) =>
+    all.filter((r) => r.chrom === chrom && r.pos >= start && r.pos <= end);
+
+  it('lists the indexed contigs', () => {
+    expect(binding.IndexedVcf.open(vcfPath).chromosomes()).toEqual(['chr1', 'chr2', 'chrX']);
+  });
+
+  it('returns the same records as a full parse', () => {
+    const indexed = binding.IndexedVcf.open(vcfPath, `${vcfPath}.tbi`);
+
+    for (const [chrom, start, end] of [
+      ['chr1', 15000, 40000],
+      ['chr1', 299000, 400000],
+      ['chr2', 1, 5000],
+      ['chrX', 1, 1e9],
+    ]) {
+      const expected = inRegion(chrom, start, end);
+      expect(expected.length).toBeGreaterThan(0);
+      expect(indexed.query(`${chrom}:${start}-${end}`)).toEqual(expected);
+      expect(indexed.queryCount(`${chrom}:${start}-${end}`)).toBe(expected.length);
+    }
+    expect(indexed.query('chr3')).toEqual([]);
+  });
+
+  it('applies query options and serves concurrent async queries', async () => {
+    const indexed = binding.IndexedVcf.open(vcfPath);
+    const passing = inRegion('chr1', 1, 100000).filter((r) => r.filter === 'PASS');
+
+    const results = await Promise.all([
+      indexed.queryAsync('chr1:1-100000', { passOnly: true }),
+      indexed.queryAsync('chr2'),
+      indexed.queryAsync('chr1:1-100000', { passOnly: true, offset: 2, limit: 3 }),
+    ]);
+
+    expect(results[0]).toEqual(passing);
+    expect(results[1]).toEqual(inRegion('chr2', 1, Infinity));
+    expect(results[2]).toEqual(passing.slice(2, 5));
+  });
+
+  it('rejects bad regions and missing indexes', () => {
+    const indexed = binding.IndexedVcf.open(vcfPath);
+
+    expect(() => indexed.query('chr1:500-100')).toThrow(/Invalid region/);
+    expect(() => binding.IndexedVcf.open(vcfPath, join(__dirname, 'missing.tbi'))).toThrow(
+      /Failed to read file/
+    );
+  });
+});
+
+describe.skipIf(!binding)('Node binding shared record shape', () => {
+  // Same fixture and snapshot as the core and WASM binding tests
+  const fixturePath = join(__dirname, 'data', 'shared-records.vcf');
+  const snapshot = readFileSync(join(__dirname, 'data', 'shared-records.json'), 'utf8');
+
+  // napi exposes the shared snake_case fields in camelCase
+  const snakeCase = (value) => {
+    if (Array.isArray(value)) return value.map(snakeCase);
+    if (value === null || typeof value !== 'object') return value;
+    return Object.fromEntries(
+      Object.entries(value).map(([key, v]) => [
+        key.replace(/[A-Z]/g, (c) => `_${c.toLowerCase()}`),
+        key === 'info' || key === 'fields' ? v : snakeCase(v),
+      ])
+    );
+  };
+
+  it('serializes records exactly like the snapshot', () => {
+    const { records } = new binding.VcfParserNode().parseFile(fixturePath);
+
+    expect(JSON.stringify(snakeCase(records))).toBe(JSON.stringify(JSON.parse(snapshot)));
+  });
+});
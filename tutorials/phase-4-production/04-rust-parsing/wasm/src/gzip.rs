@@ -0,0 +1,299 @@
+//! Streaming gzip / BGZF decompression
+//!
+//! Inflates concatenated gzip members as compressed bytes arrive. BGZF
+//! files are a series of small gzip members, so they decode the same way.
+//! Only the output of the current chunk is held in memory.
+
+use flate2::{Crc, Decompress, FlushDecompress, Status};
+use std::fmt;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const DEFLATE_METHOD: u8 = 8;
+
+const FHCRC: u8 = 0x02;
+const FEXTRA: u8 = 0x04;
+const FNAME: u8 = 0x08;
+const FCOMMENT: u8 = 0x10;
+const FRESERVED: u8 = 0xe0;
+
+/// Size of the scratch buffer inflated into per call
+const OUTPUT_BUFFER: usize = 32 * 1024;
+
+/// Errors from gzip decompression
+#[derive(Debug, Clone, PartialEq)]
+pub enum GzipError {
+    /// Input does not start with the gzip magic bytes
+    NotGzip,
+    /// Input ended in the middle of a gzip member
+    Truncated,
+    /// Malformed header, deflate data or checksum
+    Corrupt(String),
+}
+
+impl GzipError {
+    /// Stable SCREAMING_SNAKE_CASE code for the error kind
+    pub fn code(&self) -> &'static str {
+        match self {
+            GzipError::NotGzip => "NOT_GZIP",
+            GzipError::Truncated => "GZIP_TRUNCATED",
+            GzipError::Corrupt(_) => "GZIP_CORRUPT",
+        }
+    }
+}
+
+impl fmt::Display for GzipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GzipError::NotGzip => write!(f, "input is not gzip compressed"),
+            GzipError::Truncated => write!(f, "gzip stream is truncated"),
+            GzipError::Corrupt(message) => write!(f, "corrupt gzip stream: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GzipError {}
+
+enum State {
+    Header,
+    Body,
+    Trailer,
+}
+
+/// Push-based decoder for (multi-member) gzip data
+pub struct GzipDecoder {
+    input: Vec<u8>,
+    state: State,
+    inflate: Decompress,
+    crc: Crc,
+    members: usize,
+    started: bool,
+}
+
+impl Default for GzipDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GzipDecoder {
+    pub fn new() -> Self {
+        Self {
+            input: Vec::new(),
+            state: State::Header,
+            inflate: Decompress::new(false),
+            crc: Crc::new(),
+            members: 0,
+            started: false,
+        }
+    }
+
+    /// Feed compressed bytes, appending the decompressed output to `out`
+    ///
+    /// Incomplete headers, deflate blocks and trailers are buffered until
+    /// the next call.
+    pub fn push(&mut self, chunk: &[u8], out: &mut Vec<u8>) -> Result<(), GzipError> {
+        self.started |= !chunk.is_empty();
+        self.input.extend_from_slice(chunk);
+
+        let mut pos = 0;
+        loop {
+            let available = &self.input[pos..];
+            match self.state {
+                State::Header => {
+                    if available.is_empty() {
+                        break;
+                    }
+                    if !available.iter().zip(GZIP_MAGIC).all(|(&a, b)| a == b) {
+                        return Err(if self.members == 0 {
+                            GzipError::NotGzip
+                        } else {
+                            GzipError::Corrupt("unexpected data after gzip member".into())
+                        });
+                    }
+                    let Some(len) = header_length(available)? else {
+                        break;
+                    };
+                    pos += len;
+                    self.inflate.reset(false);
+                    self.crc.reset();
+                    self.state = State::Body;
+                }
+                State::Body => {
+                    let mut buffer = [0u8; OUTPUT_BUFFER];
+                    let (in_before, out_before) =
+                        (self.inflate.total_in(), self.inflate.total_out());
+                    let status = self
+                        .inflate
+                        .decompress(available, &mut buffer, FlushDecompress::None)
+                        .map_err(|e| GzipError::Corrupt(e.to_string()))?;
+                    let consumed = (self.inflate.total_in() - in_before) as usize;
+                    let produced = (self.inflate.total_out() - out_before) as usize;
+
+                    pos += consumed;
+                    self.crc.update(&buffer[..produced]);
+                    out.extend_from_slice(&buffer[..produced]);
+
+                    if status == Status::StreamEnd {
+                        self.state = State::Trailer;
+                    } else if consumed == 0 && produced == 0 {
+                        break;
+                    }
+                }
+                State::Trailer => {
+                    if available.len() < 8 {
+                        break;
+                    }
+                    let crc = u32::from_le_bytes(available[0..4].try_into().unwrap());
+                    let size = u32::from_le_bytes(available[4..8].try_into().unwrap());
+                    if crc != self.crc.sum() || size != self.crc.amount() {
+                        return Err(GzipError::Corrupt("checksum mismatch".into()));
+                    }
+                    pos += 8;
+                    self.members += 1;
+                    self.state = State::Header;
+                }
+            }
+        }
+
+        self.input.drain(..pos);
+        Ok(())
+    }
+
+    /// Check that the input ended on a member boundary
+    pub fn finish(&self) -> Result<(), GzipError> {
+        match self.state {
+            State::Header if self.input.is_empty() && self.members > 0 => Ok(()),
+            State::Header if !self.started => Err(GzipError::NotGzip),
+            _ => Err(GzipError::Truncated),
+        }
+    }
+}
+
+/// Length of the gzip member header at the start of `data`
+///
+/// Returns `None` if more bytes are needed.
+fn header_length(data: &[u8]) -> Result<Option<usize>, GzipError> {
+    if data.len() < 10 {
+        return Ok(None);
+    }
+    if data[2] != DEFLATE_METHOD {
+        return Err(GzipError::Corrupt(format!(
+            "unsupported compression method {}",
+            data[2]
+        )));
+    }
+    let flags = data[3];
+    if flags & FRESERVED != 0 {
+        return Err(GzipError::Corrupt("reserved header flags set".into()));
+    }
+
+    let mut len = 10;
+    if flags & FEXTRA != 0 {
+        let Some(xlen) = data.get(len..len + 2) else {
+            return Ok(None);
+        };
+        len += 2 + u16::from_le_bytes([xlen[0], xlen[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            // Zero-terminated string
+            let Some(end) = data
+                .get(len..)
+                .and_then(|rest| rest.iter().position(|&b| b == 0))
+            else {
+                return Ok(None);
+            };
+            len += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        len += 2;
+    }
+
+    Ok((data.len() >= len).then_some(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::{Compression, GzBuilder};
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn decode(data: &[u8], chunk_size: usize) -> Result<Vec<u8>, GzipError> {
+        let mut decoder = GzipDecoder::new();
+        let mut out = Vec::new();
+        for chunk in data.chunks(chunk_size) {
+            decoder.push(chunk, &mut out)?;
+        }
+        decoder.finish()?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_decode_in_small_chunks() {
+        let text = b"chr1\t100\t.\tA\tG\n".repeat(500);
+        let compressed = gzip(&text);
+
+        assert_eq!(decode(&compressed, 1).unwrap(), text);
+        assert_eq!(decode(&compressed, 7).unwrap(), text);
+        assert_eq!(decode(&compressed, compressed.len()).unwrap(), text);
+    }
+
+    #[test]
+    fn test_decode_multiple_members_and_header_fields() {
+        // BGZF-like: several members, each with an extra field and a name
+        let mut data = Vec::new();
+        let mut expected = Vec::new();
+        for part in [&b"first block\n"[..], b"second block\n", b""] {
+            let mut encoder = GzBuilder::new()
+                .extra(b"BC\x02\x00\x00\x00".to_vec())
+                .filename("part.vcf")
+                .comment("bgzf")
+                .write(Vec::new(), Compression::fast());
+            encoder.write_all(part).unwrap();
+            data.extend(encoder.finish().unwrap());
+            expected.extend_from_slice(part);
+        }
+
+        let mut decoder = GzipDecoder::new();
+        let mut out = Vec::new();
+        for chunk in data.chunks(3) {
+            decoder.push(chunk, &mut out).unwrap();
+        }
+        decoder.finish().unwrap();
+        assert_eq!(out, expected);
+        assert_eq!(decoder.members, 3);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let compressed = gzip(&b"##fileformat=VCFv4.2\n".repeat(50));
+
+        assert_eq!(
+            decode(b"##fileformat=VCFv4.2\n", 4),
+            Err(GzipError::NotGzip)
+        );
+        assert_eq!(decode(b"", 4), Err(GzipError::NotGzip));
+        assert_eq!(
+            decode(&compressed[..compressed.len() - 3], 4),
+            Err(GzipError::Truncated)
+        );
+        assert_eq!(decode(&compressed[..5], 4), Err(GzipError::Truncated));
+
+        let mut bad_crc = compressed.clone();
+        let n = bad_crc.len();
+        bad_crc[n - 6] ^= 0xff;
+        assert_eq!(decode(&bad_crc, 4).unwrap_err().code(), "GZIP_CORRUPT");
+
+        let mut trailing = compressed.clone();
+        trailing.extend_from_slice(b"junk");
+        assert_eq!(decode(&trailing, 4).unwrap_err().code(), "GZIP_CORRUPT");
+    }
+}
@@ -3,12 +3,54 @@
 //! This module provides WASM bindings for the Rust VCF parser,
 //! enabling high-performance VCF parsing in the browser.
 
+mod gzip;
+
+use gzip::{GzipDecoder, GzipError};
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use vcf_parser::{
-    types::{VcfStats as RustVcfStats, VariantType as RustVariantType},
-    VcfParser as RustParser,
+    compare_callsets,
+    error::{ParseWarning, VcfError, WarningCategory},
+    manhattan::{manhattan_data_with_max, DEFAULT_MAX_NEG_LOG10_P},
+    thin::thin_for_plot,
+    to_vcf_string,
+    types::{
+        ContigInfo, FilterDefinition, FilterStatus, FormatDefinition, InfoDefinition, InfoValue,
+        SampleData, VcfHeader as RustVcfHeader, VcfRecord as RustVcfRecord,
+        VcfStats as RustVcfStats,
+    },
+    view::{variant_type_code, variant_type_from_label, variant_type_label, VARIANT_TYPE_LABELS},
+    CompareOptions, CompareResult, DensityCollector, IdIndex, QualHistogram, Region,
+    ReservoirSampler, VariantDiff, VariantStore, VariantType, VcfParser as RustParser,
+    VcfPushParser, VcfRecordView,
 };
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
+
+/// Warnings returned to JavaScript unless `setMaxWarnings` says otherwise
+const DEFAULT_MAX_WARNINGS: usize = 100;
+
+/// Compressed bytes handed to the gzip decoder at a time
+const GZIP_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Size of a WebAssembly memory page
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+/// INFO flag set by `WasmVcfDocument::markReviewed`
+const REVIEWED_FLAG: &str = "REVIEWED";
+
+/// FILTER set by `WasmVcfDocument::markRejected`
+const REJECTED_FILTER: &str = "REJECTED";
+
+/// Leading bytes of `WasmVcfDocument::toBinary` output
+const BINARY_MAGIC: &[u8; 4] = b"VCFB";
+
+/// Layout version of `toBinary` output; bump when any serialized type changes
+const BINARY_VERSION: u16 = 5;
+
+/// Filter results `statsForFilter` keeps, most recently used first
+const STATS_CACHE_SIZE: usize = 8;
 
 // Initialize panic hook for better error messages
 #[wasm_bindgen(start)]
@@ -25,6 +67,10 @@ pub struct WasmVcfHeader {
     samples: Vec<String>,
     info_field_count: usize,
     format_field_count: usize,
+    info_fields: Vec<InfoDefinition>,
+    format_fields: Vec<FormatDefinition>,
+    filters: Vec<FilterDefinition>,
+    contigs: Vec<ContigInfo>,
 }
 
 #[wasm_bindgen]
@@ -48,20 +94,154 @@ impl WasmVcfHeader {
     pub fn samples(&self) -> JsValue {
         serde_wasm_bindgen::to_value(&self.samples).unwrap()
     }
+
+    /// `##INFO` definitions: `[{id, number, type, description}]`
+    #[wasm_bindgen(js_name = infoFields)]
+    pub fn info_fields(&self) -> Result<JsValue, JsValue> {
+        let fields: Vec<FieldDefinitionJs> = self.info_fields.iter().map(Into::into).collect();
+        to_js_value(&fields)
+    }
+
+    /// `##FORMAT` definitions: `[{id, number, type, description}]`
+    #[wasm_bindgen(js_name = formatFields)]
+    pub fn format_fields(&self) -> Result<JsValue, JsValue> {
+        let fields: Vec<FieldDefinitionJs> = self.format_fields.iter().map(Into::into).collect();
+        to_js_value(&fields)
+    }
+
+    /// `##FILTER` definitions: `[{id, description}]`
+    #[wasm_bindgen]
+    pub fn filters(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&self.filters)
+    }
+
+    /// `##contig` definitions: `[{id, length}]`, `length` undefined when unknown
+    #[wasm_bindgen]
+    pub fn contigs(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&self.contigs)
+    }
+
+    /// The `##INFO` definition with `id`, or undefined
+    #[wasm_bindgen(js_name = getInfoDefinition)]
+    pub fn get_info_definition(&self, id: &str) -> Result<JsValue, JsValue> {
+        match self.info_fields.iter().find(|f| f.id == id) {
+            Some(field) => to_js_value(&FieldDefinitionJs::from(field)),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+}
+
+impl From<&RustVcfHeader> for WasmVcfHeader {
+    fn from(header: &RustVcfHeader) -> Self {
+        Self {
+            file_format: header.file_format.clone(),
+            reference: header.reference.clone(),
+            samples: header.samples.clone(),
+            info_field_count: header.info_fields.len(),
+            format_field_count: header.format_fields.len(),
+            info_fields: header.info_fields.clone(),
+            format_fields: header.format_fields.clone(),
+            filters: header.filters.clone(),
+            contigs: header.contigs.clone(),
+        }
+    }
+}
+
+/// INFO or FORMAT definition for JavaScript
+#[derive(Serialize)]
+struct FieldDefinitionJs<'a> {
+    id: &'a str,
+    number: &'a str,
+    #[serde(rename = "type")]
+    field_type: &'a str,
+    description: &'a str,
+}
+
+impl<'a> From<&'a InfoDefinition> for FieldDefinitionJs<'a> {
+    fn from(field: &'a InfoDefinition) -> Self {
+        Self {
+            id: &field.id,
+            number: &field.number,
+            field_type: &field.field_type,
+            description: &field.description,
+        }
+    }
+}
+
+impl<'a> From<&'a FormatDefinition> for FieldDefinitionJs<'a> {
+    fn from(field: &'a FormatDefinition) -> Self {
+        Self {
+            id: &field.id,
+            number: &field.number,
+            field_type: &field.field_type,
+            description: &field.description,
+        }
+    }
+}
+
+/// Sort keys accepted by `WasmVcfDocument::getPage`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SortKey {
+    Pos,
+    Qual,
+    Chrom,
+    VariantType,
+}
+
+impl SortKey {
+    fn parse(name: &str) -> Result<Self, JsValue> {
+        match name {
+            "pos" => Ok(SortKey::Pos),
+            "qual" => Ok(SortKey::Qual),
+            "chrom" => Ok(SortKey::Chrom),
+            "variant_type" => Ok(SortKey::VariantType),
+            _ => Err(ErrorJs::new(
+                "INVALID_ARGUMENT",
+                format!(
+                    "Unknown sort key '{}' (expected pos, qual, chrom or variant_type)",
+                    name
+                ),
+            )
+            .into()),
+        }
+    }
 }
 
-/// VCF Record for JavaScript
-#[derive(Serialize, Deserialize, Clone)]
-pub struct WasmVcfRecord {
-    pub chrom: String,
-    pub pos: u64,
-    pub id: Option<String>,
-    pub reference: String,
-    pub alternate: Vec<String>,
-    pub qual: Option<f64>,
-    pub filter: String,
-    pub variant_type: String,
-    pub is_snp: bool,
+/// Compare strings with runs of digits ordered by value, so `chr2` sorts
+/// before `chr10`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    fn digits(s: &[u8]) -> usize {
+        s.iter().take_while(|c| c.is_ascii_digit()).count()
+    }
+    fn trim_zeros(s: &[u8]) -> &[u8] {
+        let zeros = s.iter().take_while(|&&c| c == b'0').count();
+        &s[zeros..]
+    }
+
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        let (len_a, len_b) = (digits(a), digits(b));
+        let ordering = if len_a > 0 && len_b > 0 {
+            let (x, y) = (trim_zeros(&a[..len_a]), trim_zeros(&b[..len_b]));
+            let ordering = x.len().cmp(&y.len()).then_with(|| x.cmp(y));
+            a = &a[len_a..];
+            b = &b[len_b..];
+            ordering
+        } else {
+            match (a.split_first(), b.split_first()) {
+                (None, None) => return Ordering::Equal,
+                (Some((x, rest_a)), Some((y, rest_b))) => {
+                    a = rest_a;
+                    b = rest_b;
+                    x.cmp(y)
+                }
+                (x, y) => return x.is_some().cmp(&y.is_some()),
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
 }
 
 /// VCF Statistics for JavaScript
@@ -127,11 +307,31 @@ impl WasmVcfStats {
     }
 }
 
+impl From<RustVcfStats> for WasmVcfStats {
+    fn from(stats: RustVcfStats) -> Self {
+        Self {
+            total_records: stats.total_records,
+            snps: stats.snps,
+            insertions: stats.insertions,
+            deletions: stats.deletions,
+            complex: stats.complex,
+            passed_filter: stats.passed_filter,
+            failed_filter: stats.failed_filter,
+            chromosomes: stats.chromosomes,
+        }
+    }
+}
+
 /// WebAssembly VCF Parser
+#[derive(Serialize, Deserialize)]
 #[wasm_bindgen]
 pub struct WasmVcfParser {
     parse_info: bool,
     parse_samples: bool,
+    collect_warnings: bool,
+    max_warnings: usize,
+    skip_invalid: bool,
+    max_records: Option<usize>,
 }
 
 #[wasm_bindgen]
@@ -142,6 +342,10 @@ impl WasmVcfParser {
         Self {
             parse_info: true,
             parse_samples: true,
+            collect_warnings: true,
+            max_warnings: DEFAULT_MAX_WARNINGS,
+            skip_invalid: true,
+            max_records: None,
         }
     }
 
@@ -151,6 +355,10 @@ impl WasmVcfParser {
         Self {
             parse_info: false,
             parse_samples: false,
+            collect_warnings: true,
+            max_warnings: DEFAULT_MAX_WARNINGS,
+            skip_invalid: true,
+            max_records: None,
         }
     }
 
@@ -166,85 +374,69 @@ impl WasmVcfParser {
         self.parse_samples = value;
     }
 
+    /// Configure collection of warnings for skipped records
+    #[wasm_bindgen(js_name = setCollectWarnings)]
+    pub fn set_collect_warnings(&mut self, value: bool) {
+        self.collect_warnings = value;
+    }
+
+    /// Configure how many warnings are returned; all are still counted
+    /// in the warning summary
+    #[wasm_bindgen(js_name = setMaxWarnings)]
+    pub fn set_max_warnings(&mut self, value: usize) {
+        self.max_warnings = value;
+    }
+
+    /// Configure whether malformed records are skipped (the default) or
+    /// fail the whole parse
+    #[wasm_bindgen(js_name = setSkipInvalid)]
+    pub fn set_skip_invalid(&mut self, value: bool) {
+        self.skip_invalid = value;
+    }
+
+    /// Keep at most `value` records (no limit if undefined)
+    ///
+    /// Records past the limit are still parsed and counted in the
+    /// statistics but not retained; results and documents then report
+    /// `truncated`. Applies to streaming parsers created afterwards too.
+    #[wasm_bindgen(js_name = setMaxRecords)]
+    pub fn set_max_records(&mut self, value: Option<usize>) {
+        self.max_records = value;
+    }
+
     /// Parse VCF content string
+    ///
+    /// Malformed records are skipped and reported in `warnings` (line,
+    /// category, message) and `warning_summary` (total, by_category).
+    /// `truncated` is set when records were dropped by `setMaxRecords`.
     #[wasm_bindgen]
     pub fn parse(&self, content: &str) -> Result<JsValue, JsValue> {
         let start = get_performance_now();
-        
-        let mut parser = RustParser::new();
-        parser.parse_info = self.parse_info;
-        parser.parse_samples = self.parse_samples;
-        parser.skip_invalid = true;
 
-        let (header, records) = parser
-            .parse_str(content)
-            .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+        let mut stream = self.parse_lines(content)?;
+        stream.finish()?;
 
-        // Calculate statistics
-        let mut stats = RustVcfStats::new();
-        for record in &records {
-            stats.update(record);
-        }
-
-        let parse_time = get_performance_now() - start;
+        self.parse_result(stream, start)
+    }
 
-        // Convert records to serializable format
-        let js_records: Vec<WasmVcfRecord> = records
-            .into_iter()
-            .map(|r| {
-                let variant_type = match r.variant_type() {
-                    RustVariantType::Snp => "SNP",
-                    RustVariantType::Insertion => "INS",
-                    RustVariantType::Deletion => "DEL",
-                    RustVariantType::Complex => "COMPLEX",
-                    RustVariantType::Other => "OTHER",
-                }
-                .to_string();
-
-                let filter = match &r.filter {
-                    vcf_parser::types::FilterStatus::Pass => "PASS".to_string(),
-                    vcf_parser::types::FilterStatus::Missing => ".".to_string(),
-                    vcf_parser::types::FilterStatus::Failed(f) => f.join(";"),
-                };
-
-                WasmVcfRecord {
-                    chrom: r.chrom,
-                    pos: r.pos,
-                    id: r.id,
-                    reference: r.reference,
-                    alternate: r.alternate,
-                    qual: r.qual,
-                    filter,
-                    variant_type,
-                    is_snp: r.is_snp(),
-                }
-            })
-            .collect();
+    /// Parse gzip or BGZF compressed VCF bytes
+    ///
+    /// Returns the same object as `parse`. Data is inflated block by block
+    /// straight into the parser, so the decompressed text is never held in
+    /// full. Errors carry a `kind` of "not_gzip", "truncated" or "corrupt"
+    /// for decompression failures.
+    #[wasm_bindgen(js_name = parseGzip)]
+    pub fn parse_gzip(&self, data: &[u8]) -> Result<JsValue, JsValue> {
+        let start = get_performance_now();
 
-        // Create result object
-        let result = ParseResultJs {
-            header: HeaderJs {
-                file_format: header.file_format,
-                reference: header.reference,
-                samples: header.samples,
-                info_field_count: header.info_fields.len(),
-                format_field_count: header.format_fields.len(),
-            },
-            records: js_records,
-            stats: StatsJs {
-                total_records: stats.total_records,
-                snps: stats.snps,
-                insertions: stats.insertions,
-                deletions: stats.deletions,
-                complex: stats.complex,
-                passed_filter: stats.passed_filter,
-                failed_filter: stats.failed_filter,
-                chromosomes: stats.chromosomes,
-            },
-            parse_time_ms: parse_time,
-        };
+        let mut stream = self.stream_parser(true);
+        stream.gzip = Some(GzipDecoder::new());
+        for chunk in data.chunks(GZIP_CHUNK_SIZE) {
+            stream.append_gzip_chunk(chunk)?;
+        }
+        stream.finish()?;
 
-        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+        self.parse_result(stream, start)
     }
 
     /// Parse and return only statistics (faster for large files)
@@ -252,22 +444,9 @@ impl WasmVcfParser {
     pub fn parse_stats(&self, content: &str) -> Result<WasmVcfStats, JsValue> {
         let mut parser = RustParser::fast();
 
-        let (_, records) = parser
-            .parse_str(content)
-            .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
-
-        let stats = vcf_parser::calculate_stats(&records);
+        let (_, records) = parser.parse_str(content).map_err(vcf_error)?;
 
-        Ok(WasmVcfStats {
-            total_records: stats.total_records,
-            snps: stats.snps,
-            insertions: stats.insertions,
-            deletions: stats.deletions,
-            complex: stats.complex,
-            passed_filter: stats.passed_filter,
-            failed_filter: stats.failed_filter,
-            chromosomes: stats.chromosomes,
-        })
+        Ok(vcf_parser::calculate_stats(&records).into())
     }
 
     /// Get header information only
@@ -275,46 +454,61 @@ impl WasmVcfParser {
     pub fn parse_header(&self, content: &str) -> Result<WasmVcfHeader, JsValue> {
         let mut parser = RustParser::new();
 
-        let (header, _) = parser
-            .parse_str(content)
-            .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+        let (header, _) = parser.parse_str(content).map_err(vcf_error)?;
 
-        Ok(WasmVcfHeader {
-            file_format: header.file_format,
-            reference: header.reference,
-            samples: header.samples,
-            info_field_count: header.info_fields.len(),
-            format_field_count: header.format_fields.len(),
-        })
+        Ok(WasmVcfHeader::from(&header))
+    }
+
+    /// Parse once into a document that can be queried repeatedly
+    #[wasm_bindgen(js_name = parseDocument)]
+    pub fn parse_document(&self, content: &str) -> Result<WasmVcfDocument, JsValue> {
+        self.parse_lines(content)?.into_document()
+    }
+
+    /// Create a parser for content delivered in chunks
+    ///
+    /// With `retain_records` false only statistics are kept, so memory stays
+    /// flat regardless of file size.
+    #[wasm_bindgen(js_name = streamParser)]
+    pub fn stream_parser(&self, retain_records: bool) -> WasmVcfStreamParser {
+        WasmVcfStreamParser {
+            parser: VcfPushParser::new(self.rust_parser()),
+            options: self.options(),
+            pending: String::new(),
+            pending_bytes: Vec::new(),
+            gzip: None,
+            stats: RustVcfStats::new(),
+            records: retain_records.then(Vec::new),
+            sampler: None,
+            truncated: false,
+        }
+    }
+
+    /// Create a chunk parser that keeps a uniform random sample of `n`
+    /// records, for previews of files too large to hold
+    ///
+    /// Statistics still cover every record, and `intoDocument` hands over
+    /// the sample in file order. The same `seed` gives the same sample.
+    #[wasm_bindgen(js_name = streamSampler)]
+    pub fn stream_sampler(&self, n: usize, seed: u32) -> WasmVcfStreamParser {
+        WasmVcfStreamParser {
+            sampler: Some(ReservoirSampler::new(n, u64::from(seed))),
+            ..self.stream_parser(false)
+        }
     }
 
     /// Filter records by chromosome
     #[wasm_bindgen(js_name = filterByChromosome)]
     pub fn filter_by_chromosome(&self, content: &str, chrom: &str) -> Result<JsValue, JsValue> {
-        let mut parser = RustParser::new();
-        parser.skip_invalid = true;
-
-        let (_, records) = parser
-            .parse_str(content)
-            .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
+        let (_, records) = self.rust_parser().parse_str(content).map_err(vcf_error)?;
 
-        let filtered: Vec<WasmVcfRecord> = records
+        let filtered: Vec<VcfRecordView> = records
             .into_iter()
             .filter(|r| r.chrom == chrom)
-            .map(|r| WasmVcfRecord {
-                chrom: r.chrom,
-                pos: r.pos,
-                id: r.id,
-                reference: r.reference.clone(),
-                alternate: r.alternate.clone(),
-                qual: r.qual,
-                filter: "PASS".to_string(),
-                variant_type: format!("{:?}", r.variant_type()),
-                is_snp: r.is_snp(),
-            })
+            .map(|r| self.to_wasm_record(r))
             .collect();
 
-        serde_wasm_bindgen::to_value(&filtered).map_err(|e| JsValue::from_str(&e.to_string()))
+        to_js_value(&filtered)
     }
 
     /// Filter records by position range
@@ -326,116 +520,1478 @@ impl WasmVcfParser {
         start: u64,
         end: u64,
     ) -> Result<JsValue, JsValue> {
-        let mut parser = RustParser::new();
-        parser.skip_invalid = true;
+        let (_, records) = self.rust_parser().parse_str(content).map_err(vcf_error)?;
 
-        let (_, records) = parser
-            .parse_str(content)
-            .map_err(|e| JsValue::from_str(&format!("Parse error: {}", e)))?;
-
-        let filtered: Vec<WasmVcfRecord> = records
+        let filtered: Vec<VcfRecordView> = records
             .into_iter()
             .filter(|r| r.chrom == chrom && r.pos >= start && r.pos <= end)
-            .map(|r| WasmVcfRecord {
-                chrom: r.chrom,
-                pos: r.pos,
-                id: r.id,
-                reference: r.reference.clone(),
-                alternate: r.alternate.clone(),
-                qual: r.qual,
-                filter: "PASS".to_string(),
-                variant_type: format!("{:?}", r.variant_type()),
-                is_snp: r.is_snp(),
-            })
+            .map(|r| self.to_wasm_record(r))
             .collect();
 
-        serde_wasm_bindgen::to_value(&filtered).map_err(|e| JsValue::from_str(&e.to_string()))
+        to_js_value(&filtered)
     }
 }
 
-impl Default for WasmVcfParser {
-    fn default() -> Self {
-        Self::new()
+impl WasmVcfParser {
+    /// Feed every line of `content` to a record-retaining stream parser,
+    /// so `max_records` applies while parsing rather than afterwards
+    fn parse_lines(&self, content: &str) -> Result<WasmVcfStreamParser, JsValue> {
+        let mut stream = self.stream_parser(true);
+        for line in content.split('\n') {
+            stream.push_line(line)?;
+        }
+        Ok(stream)
     }
-}
 
-// Helper structs for JSON serialization
-#[derive(Serialize)]
-struct ParseResultJs {
-    header: HeaderJs,
-    records: Vec<WasmVcfRecord>,
-    stats: StatsJs,
-    parse_time_ms: f64,
-}
+    /// Build the `parse` result object from a finished stream parser
+    fn parse_result(&self, stream: WasmVcfStreamParser, start: f64) -> Result<JsValue, JsValue> {
+        let stats = stream.stats.clone();
+        let truncated = stream.truncated;
+        let (header, records, report) = stream.into_parts();
 
-#[derive(Serialize)]
-struct HeaderJs {
-    file_format: String,
-    reference: Option<String>,
-    samples: Vec<String>,
-    info_field_count: usize,
-    format_field_count: usize,
-}
+        let parse_time = get_performance_now() - start;
 
-#[derive(Serialize)]
-struct StatsJs {
-    total_records: usize,
-    snps: usize,
-    insertions: usize,
-    deletions: usize,
-    complex: usize,
-    passed_filter: usize,
-    failed_filter: usize,
-    chromosomes: Vec<String>,
-}
+        // Convert records to serializable format
+        let js_records: Vec<VcfRecordView> = records
+            .into_iter()
+            .map(|r| self.to_wasm_record(r))
+            .collect();
 
-/// Get performance.now() from JavaScript
-fn get_performance_now() -> f64 {
-    web_sys::window()
-        .and_then(|w| w.performance())
-        .map(|p| p.now())
-        .unwrap_or(0.0)
+        // Create result object
+        let result = ParseResultJs {
+            header: HeaderJs {
+                file_format: header.file_format,
+                reference: header.reference,
+                samples: header.samples,
+                info_field_count: header.info_fields.len(),
+                format_field_count: header.format_fields.len(),
+            },
+            records: js_records,
+            stats: StatsJs {
+                total_records: stats.total_records,
+                snps: stats.snps,
+                insertions: stats.insertions,
+                deletions: stats.deletions,
+                complex: stats.complex,
+                passed_filter: stats.passed_filter,
+                failed_filter: stats.failed_filter,
+                chromosomes: stats.chromosomes,
+            },
+            truncated,
+            warnings: report.warnings,
+            warning_summary: report.summary,
+            parse_time_ms: parse_time,
+        };
+
+        to_js_value(&result)
+    }
+
+    fn options(&self) -> WasmVcfParser {
+        WasmVcfParser {
+            parse_info: self.parse_info,
+            parse_samples: self.parse_samples,
+            collect_warnings: self.collect_warnings,
+            max_warnings: self.max_warnings,
+            skip_invalid: self.skip_invalid,
+            max_records: self.max_records,
+        }
+    }
+
+    /// Core parser configured with this parser's options
+    fn rust_parser(&self) -> RustParser {
+        let mut parser = RustParser::new();
+        parser.parse_info = self.parse_info;
+        parser.parse_samples = self.parse_samples;
+        parser.skip_invalid = self.skip_invalid;
+        parser.collect_warnings = self.collect_warnings;
+        parser.max_warnings = Some(self.max_warnings);
+        parser
+    }
+
+    /// Convert a record, leaving out INFO / samples when not requested
+    fn to_wasm_record(&self, record: RustVcfRecord) -> VcfRecordView {
+        VcfRecordView::new(record, self.parse_info, self.parse_samples)
+    }
 }
 
-/// Convenience function: Parse VCF string
-#[wasm_bindgen(js_name = parseVcf)]
-pub fn parse_vcf(content: &str) -> Result<JsValue, JsValue> {
-    let parser = WasmVcfParser::new();
-    parser.parse(content)
+impl Default for WasmVcfParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-/// Convenience function: Get VCF stats
-#[wasm_bindgen(js_name = getVcfStats)]
-pub fn get_vcf_stats(content: &str) -> Result<WasmVcfStats, JsValue> {
-    let parser = WasmVcfParser::new();
-    parser.parse_stats(content)
+/// Parsed VCF kept in WASM memory for repeated queries
+///
+/// Queries run over the retained records without re-parsing. Call
+/// `free()` from JavaScript to release the memory.
+#[wasm_bindgen]
+pub struct WasmVcfDocument {
+    /// Header and records, with the edit history
+    store: VariantStore,
+    report: ParseReport,
+    options: WasmVcfParser,
+    /// Whether records were dropped by `setMaxRecords`
+    truncated: bool,
+    /// Record order per `getPage` sort key and direction, built on first use
+    sort_cache: RefCell<HashMap<(SortKey, bool), Vec<u32>>>,
+    /// `statsForFilter` results keyed by serialized options, LRU first
+    stats_cache: RefCell<VecDeque<(String, RustVcfStats)>>,
+    /// ID lookup for `findById`, built on first use
+    id_index: RefCell<Option<IdIndex>>,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use wasm_bindgen_test::*;
+#[wasm_bindgen]
+impl WasmVcfDocument {
+    /// Number of records, for pagination
+    #[wasm_bindgen(getter, js_name = recordCount)]
+    pub fn record_count(&self) -> usize {
+        self.store.records().len()
+    }
 
-    const SAMPLE_VCF: &str = r#"##fileformat=VCFv4.2
-##INFO=<ID=DP,Number=1,Type=Integer,Description="Total Depth">
-#CHROM	POS	ID	REF	ALT	QUAL	FILTER	INFO
-chr1	100	rs123	A	G	30	PASS	DP=50
-chr1	200	.	AT	A	40	PASS	DP=60
-"#;
+    /// Number of records, for sizing a paginated view
+    #[wasm_bindgen(js_name = totalRecords)]
+    pub fn total_records(&self) -> usize {
+        self.store.records().len()
+    }
 
-    #[wasm_bindgen_test]
-    fn test_parse() {
-        let parser = WasmVcfParser::new();
-        let result = parser.parse(SAMPLE_VCF);
-        assert!(result.is_ok());
+    /// Whether records past the parser's `setMaxRecords` limit were dropped
+    #[wasm_bindgen(getter)]
+    pub fn truncated(&self) -> bool {
+        self.truncated
     }
 
-    #[wasm_bindgen_test]
-    fn test_parse_stats() {
-        let parser = WasmVcfParser::new();
-        let stats = parser.parse_stats(SAMPLE_VCF).unwrap();
-        assert_eq!(stats.total_records(), 2);
-        assert_eq!(stats.snps(), 1);
+    /// Rough number of bytes of WASM memory held by the document
+    ///
+    /// Counts the record structs and the lengths of their strings, INFO
+    /// values and sample fields; allocator and hash table overhead is
+    /// not included.
+    #[wasm_bindgen(js_name = memoryEstimateBytes)]
+    pub fn memory_estimate_bytes(&self) -> usize {
+        let header: usize = self
+            .store
+            .header()
+            .meta_lines
+            .iter()
+            .chain(&self.store.header().samples)
+            .map(string_bytes)
+            .sum();
+        header + self.store.records().iter().map(record_bytes).sum::<usize>()
+    }
+
+    /// Header information
+    #[wasm_bindgen]
+    pub fn header(&self) -> WasmVcfHeader {
+        WasmVcfHeader::from(self.store.header())
+    }
+
+    /// Warnings for skipped records: `[{line, category, message}]`
+    #[wasm_bindgen]
+    pub fn warnings(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&self.report.warnings)
+    }
+
+    /// Warning counts: `{total, by_category}`, including warnings beyond
+    /// the returned maximum
+    #[wasm_bindgen(js_name = warningSummary)]
+    pub fn warning_summary(&self) -> Result<JsValue, JsValue> {
+        to_js_value(&self.report.summary)
+    }
+
+    /// Statistics over all records, kept current through edits
+    #[wasm_bindgen]
+    pub fn stats(&self) -> WasmVcfStats {
+        self.store.stats().clone().into()
+    }
+
+    /// Statistics over the records matching `options`
+    ///
+    /// `options` takes the filters of the Node binding's `query`:
+    /// `region` or `chrom` / `start` / `end` (1-based, inclusive),
+    /// `minQual` (records without QUAL never pass), `passOnly` and
+    /// `variantTypes` ("SNP", "INS", "DEL", "COMPLEX", "OTHER"); omit it
+    /// for all records. The last few results are cached by option values,
+    /// so toggling between filters does not rescan. No matching records
+    /// gives zeroed stats.
+    #[wasm_bindgen(js_name = statsForFilter)]
+    pub fn stats_for_filter(&self, options: JsValue) -> Result<WasmVcfStats, JsValue> {
+        let (filter, key) = RecordFilter::from_js(options)?;
+        if let Some(stats) = self.cached_stats(&key) {
+            return Ok(stats.into());
+        }
+
+        let mut stats = RustVcfStats::new();
+        for record in self.store.records().iter().filter(|r| filter.matches(r)) {
+            stats.update(record);
+        }
+        self.cache_stats(key, stats.clone());
+        Ok(stats.into())
+    }
+
+    /// Number of records matching `options` (see `statsForFilter`),
+    /// without computing the rest of the stats
+    #[wasm_bindgen(js_name = countForFilter)]
+    pub fn count_for_filter(&self, options: JsValue) -> Result<u32, JsValue> {
+        let (filter, key) = RecordFilter::from_js(options)?;
+        if let Some(stats) = self.cached_stats(&key) {
+            return Ok(stats.total_records as u32);
+        }
+        Ok(self
+            .store
+            .records()
+            .iter()
+            .filter(|r| filter.matches(r))
+            .count() as u32)
+    }
+
+    /// Records `offset..offset + limit`
+    #[wasm_bindgen(js_name = getRecords)]
+    pub fn get_records(&self, offset: usize, limit: usize) -> Result<JsValue, JsValue> {
+        self.records_to_js(self.store.records().iter().skip(offset).take(limit))
+    }
+
+    /// Records `offset..offset + limit` of the document sorted by `sort_by`
+    ///
+    /// Sort keys are "pos", "qual" (missing QUAL last in either
+    /// direction), "chrom" (natural order, so chr2 precedes chr10, then by
+    /// position) and "variant_type" (in `typeCodeLabels` order). Records
+    /// with equal keys keep their file order. Without `sort_by` records
+    /// come in file order, reversed if `descending`. Each ordering is
+    /// computed once and cached, so later pages only convert `limit`
+    /// records. Offsets past the end give an empty array.
+    #[wasm_bindgen(js_name = getPage)]
+    pub fn get_page(
+        &self,
+        offset: u32,
+        limit: u32,
+        sort_by: Option<String>,
+        descending: bool,
+    ) -> Result<JsValue, JsValue> {
+        let (offset, limit) = (offset as usize, limit as usize);
+        let Some(key) = sort_by.as_deref().map(SortKey::parse).transpose()? else {
+            let page = if descending {
+                self.records_to_js(self.store.records().iter().rev().skip(offset).take(limit))
+            } else {
+                self.records_to_js(self.store.records().iter().skip(offset).take(limit))
+            };
+            return page;
+        };
+
+        let mut cache = self.sort_cache.borrow_mut();
+        let order = cache
+            .entry((key, descending))
+            .or_insert_with(|| self.sort_order(key, descending));
+        let end = order.len().min(offset.saturating_add(limit));
+        let start = offset.min(end);
+        self.records_to_js(
+            order[start..end]
+                .iter()
+                .map(|&i| &self.store.records()[i as usize]),
+        )
+    }
+
+    /// Records on one chromosome
+    #[wasm_bindgen(js_name = filterByChromosome)]
+    pub fn filter_by_chromosome(&self, chrom: &str) -> Result<JsValue, JsValue> {
+        self.records_to_js(self.store.records().iter().filter(|r| r.chrom == chrom))
+    }
+
+    /// Records on a chromosome within [start, end] (1-based, inclusive)
+    #[wasm_bindgen(js_name = filterByRange)]
+    pub fn filter_by_range(&self, chrom: &str, start: u64, end: u64) -> Result<JsValue, JsValue> {
+        self.records_to_js(
+            self.store
+                .records()
+                .iter()
+                .filter(|r| r.chrom == chrom && r.pos >= start && r.pos <= end),
+        )
+    }
+
+    /// Records with QUAL >= `min`; records without QUAL are excluded
+    #[wasm_bindgen(js_name = filterByQuality)]
+    pub fn filter_by_quality(&self, min: f64) -> Result<JsValue, JsValue> {
+        self.records_to_js(
+            self.store
+                .records()
+                .iter()
+                .filter(|r| r.qual.is_some_and(|q| q >= min)),
+        )
+    }
+
+    /// Records of one type: "SNP", "INS", "DEL", "COMPLEX" or "OTHER"
+    /// (case-insensitive)
+    #[wasm_bindgen(js_name = filterByType)]
+    pub fn filter_by_type(&self, variant_type: &str) -> Result<JsValue, JsValue> {
+        self.records_to_js(
+            self.store.records().iter().filter(|r| {
+                variant_type_label(r.variant_type()).eq_ignore_ascii_case(variant_type)
+            }),
+        )
+    }
+
+    /// Records carrying any of `ids`, such as a pasted list of rsIDs
+    ///
+    /// Returns `{records, missing}`: the matching records in file order,
+    /// each once, and the requested IDs that matched nothing. A record
+    /// with several IDs (`rs1;rs2`) matches each of them; IDs are trimmed
+    /// and blank ones ignored. The index is built on the first call.
+    #[wasm_bindgen(js_name = findById)]
+    pub fn find_by_id(&self, ids: Vec<String>) -> Result<JsValue, JsValue> {
+        let mut index = self.id_index.borrow_mut();
+        let index = index.get_or_insert_with(|| IdIndex::build(self.store.records()));
+
+        let mut missing = Vec::new();
+        let mut matched = Vec::new();
+        for id in ids.iter().map(|id| id.trim()).filter(|id| !id.is_empty()) {
+            match index.lookup(id) {
+                [] => missing.push(id.to_string()),
+                found => matched.extend_from_slice(found),
+            }
+        }
+        matched.sort_unstable();
+        matched.dedup();
+
+        let records = matched
+            .into_iter()
+            .map(|i| self.options.to_wasm_record(self.store.records()[i].clone()))
+            .collect();
+        to_js_value(&IdMatches { records, missing })
+    }
+
+    /// Export records as VCF text
+    ///
+    /// Writes the stored header meta lines, the `#CHROM` line and the
+    /// records at `indices` (all records if omitted), in the given order or
+    /// sorted by chromosome and position when `sort` is set. Chromosomes
+    /// sort by `##contig` order, then by first appearance. INFO and sample
+    /// columns are included when they were parsed.
+    #[wasm_bindgen(js_name = toVcfString)]
+    pub fn to_vcf_string(&self, indices: Option<Vec<u32>>, sort: bool) -> Result<String, JsValue> {
+        let mut selected: Vec<&RustVcfRecord> = match indices {
+            Some(indices) => indices
+                .iter()
+                .map(|&i| {
+                    self.store.records().get(i as usize).ok_or_else(|| {
+                        ErrorJs::new(
+                            "INVALID_ARGUMENT",
+                            format!("Record index {} out of range", i),
+                        )
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            None => self.store.records().iter().collect(),
+        };
+
+        if sort {
+            let rank = self.chrom_rank();
+            selected.sort_by_key(|r| (rank[r.chrom.as_str()], r.pos));
+        }
+
+        self.write_vcf(selected)
+    }
+
+    /// Export the records of a chromosome range as VCF text
+    ///
+    /// Same selection as `filterByRange`, written like `toVcfString`.
+    #[wasm_bindgen(js_name = filteredToVcf)]
+    pub fn filtered_to_vcf(&self, chrom: &str, start: u64, end: u64) -> Result<String, JsValue> {
+        self.write_vcf(
+            self.store
+                .records()
+                .iter()
+                .filter(|r| r.chrom == chrom && r.pos >= start && r.pos <= end),
+        )
+    }
+
+    /// Flag the records at `indices` as reviewed with INFO `REVIEWED`
+    ///
+    /// Edits change the document in place: queries, `stats` and
+    /// `toVcfString` see them, and `undo` reverts them. The flag is
+    /// declared in the header on first use.
+    #[wasm_bindgen(js_name = markReviewed)]
+    pub fn mark_reviewed(&mut self, indices: Vec<u32>) -> Result<(), JsValue> {
+        self.store.declare_info(InfoDefinition {
+            id: REVIEWED_FLAG.to_string(),
+            number: "0".to_string(),
+            field_type: "Flag".to_string(),
+            description: "Reviewed by a curator".to_string(),
+        });
+        let indices = record_indices(indices);
+        self.edit(|store| store.add_info(&indices, REVIEWED_FLAG, InfoValue::Flag))
+    }
+
+    /// Set the FILTER of the records at `indices` to `REJECTED`
+    #[wasm_bindgen(js_name = markRejected)]
+    pub fn mark_rejected(&mut self, indices: Vec<u32>) -> Result<(), JsValue> {
+        self.store.declare_filter(FilterDefinition {
+            id: REJECTED_FILTER.to_string(),
+            description: "Rejected by a curator".to_string(),
+        });
+        let indices = record_indices(indices);
+        let rejected = FilterStatus::Failed(vec![REJECTED_FILTER.to_string()]);
+        self.edit(|store| store.set_filter(&indices, rejected))
+    }
+
+    /// Remove the records at `indices`; later records move up
+    #[wasm_bindgen(js_name = removeRecords)]
+    pub fn remove_records(&mut self, indices: Vec<u32>) -> Result<(), JsValue> {
+        let indices = record_indices(indices);
+        self.edit(|store| store.remove_records(&indices))
+    }
+
+    /// Revert the last edit; `false` if there was none
+    #[wasm_bindgen]
+    pub fn undo(&mut self) -> bool {
+        let undone = self.store.undo();
+        self.clear_caches();
+        undone
+    }
+
+    /// Reapply the last undone edit; `false` if there was none
+    #[wasm_bindgen]
+    pub fn redo(&mut self) -> bool {
+        let redone = self.store.redo();
+        self.clear_caches();
+        redone
+    }
+
+    /// Keep at most `depth` edits for `undo` (default 100)
+    #[wasm_bindgen(js_name = setUndoDepth)]
+    pub fn set_undo_depth(&mut self, depth: u32) {
+        self.store.set_undo_depth(depth as usize);
+    }
+
+    /// Compare this document (A) with another (B)
+    ///
+    /// See `compareVcfs` for options and result. Genotypes are only
+    /// compared when both documents were parsed with samples.
+    #[wasm_bindgen(js_name = compareWith)]
+    pub fn compare_with(
+        &self,
+        other: &WasmVcfDocument,
+        options: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let options = compare_options(options)?;
+        let result = compare_callsets(
+            self.store.header(),
+            self.store.records(),
+            other.store.header(),
+            other.store.records(),
+            &options,
+        );
+        to_js_value(&CompareResultJs::from(result))
+    }
+
+    /// Encode the document as a compact binary buffer
+    ///
+    /// The buffer holds the header, records, warnings and parse options
+    /// behind a magic and version prefix. Post its `buffer` from a Worker
+    /// as a transferable and rebuild the document with `fromBinary`.
+    #[wasm_bindgen(js_name = toBinary)]
+    pub fn to_binary(&self) -> Result<js_sys::Uint8Array, JsValue> {
+        let mut bytes = BINARY_MAGIC.to_vec();
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bincode::serialize_into(
+            &mut bytes,
+            &(
+                self.store.header(),
+                self.store.records(),
+                &self.report,
+                &self.options,
+                self.truncated,
+            ),
+        )
+        .map_err(|e| ErrorJs::new("INVALID_BINARY", e.to_string()))?;
+        Ok(js_sys::Uint8Array::from(bytes.as_slice()))
+    }
+
+    /// Rebuild a document from `toBinary` output
+    ///
+    /// Fails with code `INVALID_BINARY` if the data is not a document
+    /// buffer or is corrupt, and `UNSUPPORTED_VERSION` if it was written
+    /// by a different version of the format.
+    #[wasm_bindgen(js_name = fromBinary)]
+    pub fn from_binary(data: &[u8]) -> Result<WasmVcfDocument, JsValue> {
+        let prefix = BINARY_MAGIC.len() + 2;
+        if data.len() < prefix || !data.starts_with(BINARY_MAGIC) {
+            return Err(ErrorJs::new("INVALID_BINARY", "Data is not a binary VCF document").into());
+        }
+        let version = u16::from_le_bytes([data[4], data[5]]);
+        if version != BINARY_VERSION {
+            return Err(ErrorJs::new(
+                "UNSUPPORTED_VERSION",
+                format!(
+                    "Binary VCF document version {} is not supported (expected {})",
+                    version, BINARY_VERSION
+                ),
+            )
+            .into());
+        }
+
+        let (header, records, report, options, truncated) =
+            bincode::deserialize(&data[prefix..])
+                .map_err(|e| ErrorJs::new("INVALID_BINARY", e.to_string()))?;
+        let mut document = WasmVcfDocument::new(header, records, report, options);
+        document.truncated = truncated;
+        Ok(document)
+    }
+
+    /// POS of every record, in record order
+    #[wasm_bindgen]
+    pub fn positions(&self) -> js_sys::Float64Array {
+        let values: Vec<f64> = self.store.records().iter().map(|r| r.pos as f64).collect();
+        js_sys::Float64Array::from(values.as_slice())
+    }
+
+    /// QUAL of every record; NaN where missing
+    #[wasm_bindgen]
+    pub fn quals(&self) -> js_sys::Float64Array {
+        let values: Vec<f64> = self
+            .store
+            .records()
+            .iter()
+            .map(|r| r.qual.unwrap_or(f64::NAN))
+            .collect();
+        js_sys::Float64Array::from(values.as_slice())
+    }
+
+    /// Variant type code of every record; see `typeCodeLabels`
+    #[wasm_bindgen(js_name = variantTypeCodes)]
+    pub fn variant_type_codes(&self) -> js_sys::Uint8Array {
+        let codes: Vec<u8> = self
+            .store
+            .records()
+            .iter()
+            .map(|r| variant_type_code(r.variant_type()))
+            .collect();
+        js_sys::Uint8Array::from(codes.as_slice())
+    }
+
+    /// Labels for variant type codes: `typeCodeLabels()[code]`
+    #[wasm_bindgen(js_name = typeCodeLabels)]
+    pub fn type_code_labels(&self) -> js_sys::Array {
+        VARIANT_TYPE_LABELS
+            .iter()
+            .map(|&l| JsValue::from_str(l))
+            .collect()
+    }
+
+    /// Chromosome code of every record; see `chromNames`
+    ///
+    /// Codes number chromosomes in order of first appearance. Fails if
+    /// there are more than 65536 distinct chromosomes.
+    #[wasm_bindgen(js_name = chromCodes)]
+    pub fn chrom_codes(&self) -> Result<js_sys::Uint16Array, JsValue> {
+        let (names, codes) = self.chrom_index();
+        if names.len() > u16::MAX as usize + 1 {
+            return Err(ErrorJs::new(
+                "TOO_MANY_CHROMOSOMES",
+                "Too many chromosomes for 16-bit codes",
+            )
+            .into());
+        }
+
+        let codes: Vec<u16> = codes.into_iter().map(|c| c as u16).collect();
+        Ok(js_sys::Uint16Array::from(codes.as_slice()))
+    }
+
+    /// Binned variant counts per chromosome
+    ///
+    /// Returns `[{chrom, bins: [{start, count, snps, indels}]}]` with
+    /// chromosomes in order of first appearance and contiguous bins
+    /// (0-based `start`) up to the last one holding a record.
+    #[wasm_bindgen(js_name = variantDensity)]
+    pub fn variant_density(&self, bin_size: u32) -> Result<JsValue, JsValue> {
+        if bin_size == 0 {
+            return Err(ErrorJs::new("INVALID_ARGUMENT", "Bin size must be at least 1").into());
+        }
+
+        let mut collector = DensityCollector::new(bin_size as u64);
+        for record in self.store.records() {
+            collector.update(record);
+        }
+        to_js_value(&collector.finish())
+    }
+
+    /// Histogram of QUAL values
+    ///
+    /// Returns `{edges, counts, overflow, missingQual}`: bins of `binWidth`
+    /// from 0 to at least `maxQual`, a count of values at or above the last
+    /// edge, and the number of records without QUAL (not binned).
+    #[wasm_bindgen(js_name = qualHistogram)]
+    pub fn qual_histogram(&self, bin_width: f64, max_qual: f64) -> Result<JsValue, JsValue> {
+        let mut histogram = QualHistogram::new(bin_width, max_qual).ok_or_else(|| {
+            ErrorJs::new(
+                "INVALID_ARGUMENT",
+                "Bin width must be positive and maxQual non-negative",
+            )
+        })?;
+        for record in self.store.records() {
+            histogram.update(record);
+        }
+
+        to_js_value(&QualHistogramJs {
+            edges: histogram.edges,
+            counts: histogram.counts,
+            overflow: histogram.overflow,
+            missing_qual: histogram.missing,
+        })
+    }
+
+    /// Chromosome names indexed by chromosome code
+    #[wasm_bindgen(js_name = chromNames)]
+    pub fn chrom_names(&self) -> js_sys::Array {
+        let (names, _) = self.chrom_index();
+        names.into_iter().map(JsValue::from_str).collect()
+    }
+
+    /// Indices of about `target` records, spread like the full set, for
+    /// plotting; index `positions()`, `quals()` and the other column
+    /// getters with them to draw only the kept records
+    #[wasm_bindgen(js_name = thinnedIndices)]
+    pub fn thinned_indices(&self, target: u32) -> js_sys::Uint32Array {
+        let kept: Vec<u32> = thin_for_plot(self.store.records(), target as usize)
+            .into_iter()
+            .map(|i| i as u32)
+            .collect();
+        js_sys::Uint32Array::from(kept.as_slice())
+    }
+
+    /// Manhattan plot points from p-values under INFO `pKey`
+    ///
+    /// Returns `{positions, negLog10P, chromIndex, chromosomes, offsets,
+    /// ticks, skipped, clamped}`: parallel `Float64Array`s of genome-wide
+    /// position and −log10(p) with a `Uint32Array` of indices into
+    /// `chromosomes`, then each chromosome's start and label position on
+    /// the x-axis. With `alreadyLog10` the key holds −log10(p) (e.g.
+    /// `LOG10P`). p = 0 is capped at `maxNegLog10P` (default 300). Needs
+    /// a document parsed with INFO.
+    #[wasm_bindgen(js_name = manhattanData)]
+    pub fn manhattan_data(
+        &self,
+        p_key: &str,
+        already_log10: bool,
+        max_neg_log10_p: Option<f64>,
+    ) -> Result<JsValue, JsValue> {
+        let data = manhattan_data_with_max(
+            self.store.records(),
+            p_key,
+            already_log10,
+            Some(self.store.header()),
+            max_neg_log10_p.unwrap_or(DEFAULT_MAX_NEG_LOG10_P),
+        );
+
+        let positions: Vec<f64> = data.positions.iter().map(|&p| p as f64).collect();
+        let offsets: Vec<f64> = data.offsets.iter().map(|&o| o as f64).collect();
+        let chromosomes: js_sys::Array = data
+            .chromosomes
+            .iter()
+            .map(|c| JsValue::from_str(c))
+            .collect();
+
+        let result = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| {
+            js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).map(|_| ())
+        };
+        set(
+            "positions",
+            js_sys::Float64Array::from(positions.as_slice()).into(),
+        )?;
+        set(
+            "negLog10P",
+            js_sys::Float64Array::from(data.neg_log10_p.as_slice()).into(),
+        )?;
+        set(
+            "chromIndex",
+            js_sys::Uint32Array::from(data.chrom_index.as_slice()).into(),
+        )?;
+        set("chromosomes", chromosomes.into())?;
+        set(
+            "offsets",
+            js_sys::Float64Array::from(offsets.as_slice()).into(),
+        )?;
+        set(
+            "ticks",
+            js_sys::Float64Array::from(data.ticks.as_slice()).into(),
+        )?;
+        set("skipped", JsValue::from(data.skipped as u32))?;
+        set("clamped", JsValue::from(data.clamped as u32))?;
+        Ok(result.into())
+    }
+}
+
+impl WasmVcfDocument {
+    fn new(
+        header: RustVcfHeader,
+        records: Vec<RustVcfRecord>,
+        report: ParseReport,
+        options: WasmVcfParser,
+    ) -> Self {
+        Self {
+            store: VariantStore::new(header, records),
+            report,
+            options,
+            truncated: false,
+            sort_cache: RefCell::new(HashMap::new()),
+            stats_cache: RefCell::new(VecDeque::new()),
+            id_index: RefCell::new(None),
+        }
+    }
+
+    /// Apply an edit to the store, dropping caches built from the old
+    /// records
+    fn edit(
+        &mut self,
+        edit: impl FnOnce(&mut VariantStore) -> Result<(), VcfError>,
+    ) -> Result<(), JsValue> {
+        edit(&mut self.store).map_err(vcf_error)?;
+        self.clear_caches();
+        Ok(())
+    }
+
+    fn clear_caches(&mut self) {
+        self.sort_cache.get_mut().clear();
+        self.stats_cache.get_mut().clear();
+        *self.id_index.get_mut() = None;
+    }
+
+    /// Cached stats for `key`, marked as most recently used
+    fn cached_stats(&self, key: &str) -> Option<RustVcfStats> {
+        let mut cache = self.stats_cache.borrow_mut();
+        let index = cache.iter().position(|(k, _)| k == key)?;
+        let entry = cache.remove(index)?;
+        let stats = entry.1.clone();
+        cache.push_front(entry);
+        Some(stats)
+    }
+
+    fn cache_stats(&self, key: String, stats: RustVcfStats) {
+        let mut cache = self.stats_cache.borrow_mut();
+        cache.push_front((key, stats));
+        cache.truncate(STATS_CACHE_SIZE);
+    }
+
+    /// Record indices in `getPage` order for `key`
+    fn sort_order(&self, key: SortKey, descending: bool) -> Vec<u32> {
+        let directed = |ordering: Ordering| {
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+
+        let mut order: Vec<u32> = (0..self.store.records().len() as u32).collect();
+        order.sort_by(|&a, &b| {
+            let records = self.store.records();
+            let (a, b) = (&records[a as usize], &records[b as usize]);
+            match key {
+                SortKey::Pos => directed(a.pos.cmp(&b.pos)),
+                SortKey::Qual => match (a.qual, b.qual) {
+                    (Some(x), Some(y)) => directed(x.total_cmp(&y)),
+                    (x, y) => x.is_none().cmp(&y.is_none()),
+                },
+                SortKey::Chrom => directed(natural_cmp(&a.chrom, &b.chrom).then(a.pos.cmp(&b.pos))),
+                SortKey::VariantType => directed(
+                    variant_type_code(a.variant_type()).cmp(&variant_type_code(b.variant_type())),
+                ),
+            }
+        });
+        order
+    }
+
+    /// Distinct chromosomes in order of first appearance, and each
+    /// record's index into them
+    fn chrom_index(&self) -> (Vec<&str>, Vec<usize>) {
+        let mut names: Vec<&str> = Vec::new();
+        let mut lookup: HashMap<&str, usize> = HashMap::new();
+
+        let codes = self
+            .store
+            .records()
+            .iter()
+            .map(|r| {
+                *lookup.entry(r.chrom.as_str()).or_insert_with(|| {
+                    names.push(&r.chrom);
+                    names.len() - 1
+                })
+            })
+            .collect();
+
+        (names, codes)
+    }
+
+    /// Sort rank of each chromosome: `##contig` order, then first appearance
+    fn chrom_rank(&self) -> HashMap<&str, usize> {
+        let mut rank: HashMap<&str, usize> = HashMap::new();
+        let contigs = self.store.header().contigs.iter().map(|c| c.id.as_str());
+        for chrom in contigs.chain(self.store.records().iter().map(|r| r.chrom.as_str())) {
+            let next = rank.len();
+            rank.entry(chrom).or_insert(next);
+        }
+        rank
+    }
+
+    fn write_vcf<'a>(
+        &'a self,
+        records: impl IntoIterator<Item = &'a RustVcfRecord>,
+    ) -> Result<String, JsValue> {
+        to_vcf_string(self.store.header(), records, self.options.parse_samples).map_err(vcf_error)
+    }
+
+    fn records_to_js<'a>(
+        &self,
+        records: impl Iterator<Item = &'a RustVcfRecord>,
+    ) -> Result<JsValue, JsValue> {
+        let records: Vec<VcfRecordView> = records
+            .map(|r| self.options.to_wasm_record(r.clone()))
+            .collect();
+        to_js_value(&records)
+    }
+}
+
+/// Incremental parser fed with chunks of a VCF file
+///
+/// Chunks may split lines (and, for `appendBytes`, UTF-8 sequences)
+/// anywhere; incomplete trailing data is buffered until the next chunk.
+#[wasm_bindgen]
+pub struct WasmVcfStreamParser {
+    parser: VcfPushParser,
+    options: WasmVcfParser,
+    pending: String,
+    pending_bytes: Vec<u8>,
+    gzip: Option<GzipDecoder>,
+    stats: RustVcfStats,
+    records: Option<Vec<RustVcfRecord>>,
+    /// Set in sampling mode instead of `records`
+    sampler: Option<ReservoirSampler<RustVcfRecord>>,
+    truncated: bool,
+}
+
+#[wasm_bindgen]
+impl WasmVcfStreamParser {
+    /// Append a chunk of text, returning the number of records it completed
+    #[wasm_bindgen(js_name = appendChunk)]
+    pub fn append_chunk(&mut self, chunk: &str) -> Result<usize, JsValue> {
+        if !self.pending_bytes.is_empty() {
+            return Err(ErrorJs::new("UTF8", "Incomplete UTF-8 sequence before text chunk").into());
+        }
+        self.push_text(chunk)
+    }
+
+    /// Append a chunk of UTF-8 bytes, returning the number of records it completed
+    #[wasm_bindgen(js_name = appendBytes)]
+    pub fn append_bytes(&mut self, chunk: &[u8]) -> Result<usize, JsValue> {
+        let mut bytes = std::mem::take(&mut self.pending_bytes);
+        bytes.extend_from_slice(chunk);
+
+        let valid = match std::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            // Sequence cut off at the end of the chunk
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(ErrorJs::new("UTF8", e.to_string()).into()),
+        };
+
+        self.pending_bytes = bytes.split_off(valid);
+        let text = std::str::from_utf8(&bytes).expect("validated above");
+        self.push_text(text)
+    }
+
+    /// Append a chunk of gzip / BGZF compressed bytes
+    ///
+    /// Each chunk is inflated and parsed before returning, so memory is
+    /// bounded by the chunk size rather than the file size. Returns the
+    /// number of records completed by this chunk.
+    #[wasm_bindgen(js_name = appendGzipChunk)]
+    pub fn append_gzip_chunk(&mut self, chunk: &[u8]) -> Result<usize, JsValue> {
+        let mut inflated = Vec::new();
+        self.gzip
+            .get_or_insert_with(GzipDecoder::new)
+            .push(chunk, &mut inflated)?;
+        self.append_bytes(&inflated)
+    }
+
+    /// Number of records parsed so far
+    #[wasm_bindgen(getter, js_name = recordsParsed)]
+    pub fn records_parsed(&self) -> usize {
+        self.stats.total_records
+    }
+
+    /// Whether retained records have hit the parser's `setMaxRecords` limit
+    #[wasm_bindgen(getter)]
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Statistics over the records parsed so far
+    #[wasm_bindgen(js_name = currentStats)]
+    pub fn current_stats(&self) -> WasmVcfStats {
+        self.stats.clone().into()
+    }
+
+    /// Parse any final unterminated line and return the final statistics
+    #[wasm_bindgen]
+    pub fn finish(&mut self) -> Result<WasmVcfStats, JsValue> {
+        if !self.pending_bytes.is_empty() {
+            return Err(
+                ErrorJs::new("UTF8", "Input ends with an incomplete UTF-8 sequence").into(),
+            );
+        }
+
+        if let Some(gzip) = &self.gzip {
+            gzip.finish()?;
+        }
+
+        let line = std::mem::take(&mut self.pending);
+        self.push_line(&line)?;
+        self.parser.finish().map_err(vcf_error)?;
+
+        Ok(self.current_stats())
+    }
+
+    /// Finish parsing and hand the retained or sampled records over as a
+    /// document
+    ///
+    /// Fails for a stats-only parser.
+    #[wasm_bindgen(js_name = intoDocument)]
+    pub fn into_document(mut self) -> Result<WasmVcfDocument, JsValue> {
+        self.finish()?;
+
+        let records = self.take_records().ok_or_else(|| {
+            ErrorJs::new(
+                "INVALID_STATE",
+                "Stream parser was created without retaining records",
+            )
+        })?;
+
+        let report = ParseReport::new(self.parser.warnings(), self.parser.warning_counts());
+        let header = self.parser.into_header();
+        let mut document = WasmVcfDocument::new(header, records, report, self.options);
+        document.truncated = self.truncated;
+        Ok(document)
+    }
+}
+
+impl WasmVcfStreamParser {
+    /// Header, retained or sampled records (empty in stats-only mode) and
+    /// warnings
+    fn into_parts(mut self) -> (RustVcfHeader, Vec<RustVcfRecord>, ParseReport) {
+        let report = ParseReport::new(self.parser.warnings(), self.parser.warning_counts());
+        let records = self.take_records().unwrap_or_default();
+        (self.parser.into_header(), records, report)
+    }
+
+    fn take_records(&mut self) -> Option<Vec<RustVcfRecord>> {
+        self.records
+            .take()
+            .or_else(|| self.sampler.take().map(ReservoirSampler::into_vec))
+    }
+
+    /// Parse the complete lines of `text`, buffering any trailing partial line
+    fn push_text(&mut self, text: &str) -> Result<usize, JsValue> {
+        self.pending.push_str(text);
+
+        let Some(last_newline) = self.pending.rfind('\n') else {
+            return Ok(0);
+        };
+        let rest = self.pending.split_off(last_newline + 1);
+        let complete = std::mem::replace(&mut self.pending, rest);
+
+        let before = self.stats.total_records;
+        for line in complete[..last_newline].split('\n') {
+            self.push_line(line)?;
+        }
+
+        Ok(self.stats.total_records - before)
+    }
+
+    fn push_line(&mut self, line: &str) -> Result<(), JsValue> {
+        let record = self.parser.push_line(line).map_err(vcf_error)?;
+
+        if let Some(record) = record {
+            self.stats.update(&record);
+            if let Some(sampler) = &mut self.sampler {
+                sampler.offer(record);
+            } else if let Some(records) = &mut self.records {
+                if self
+                    .options
+                    .max_records
+                    .is_none_or(|max| records.len() < max)
+                {
+                    records.push(record);
+                } else {
+                    self.truncated = true;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error thrown to JavaScript
+///
+/// Converted into a JS `Error` (so it prints its message) carrying
+/// `code`, `message` and, where known, the `line` and VCF `field`.
+#[derive(Serialize)]
+struct ErrorJs {
+    code: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+}
+
+impl ErrorJs {
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            line: None,
+            field: None,
+        }
+    }
+}
+
+impl From<ErrorJs> for JsValue {
+    fn from(error: ErrorJs) -> Self {
+        let js_error = js_sys::Error::new(&error.message);
+        if let Ok(properties) = to_js_value(&error) {
+            js_sys::Object::assign(&js_error, properties.unchecked_ref());
+        }
+        js_error.into()
+    }
+}
+
+impl From<GzipError> for JsValue {
+    fn from(error: GzipError) -> Self {
+        ErrorJs::new(error.code(), error.to_string()).into()
+    }
+}
+
+/// Convert a core parser error for `map_err`
+fn vcf_error(error: VcfError) -> JsValue {
+    ErrorJs {
+        code: error.code(),
+        message: error.to_string(),
+        line: error.line(),
+        field: error.field().map(String::from),
+    }
+    .into()
+}
+
+// Helper structs for JSON serialization
+#[derive(Serialize)]
+struct ParseResultJs {
+    header: HeaderJs,
+    records: Vec<VcfRecordView>,
+    stats: StatsJs,
+    truncated: bool,
+    warnings: Vec<WarningJs>,
+    warning_summary: WarningSummaryJs,
+    parse_time_ms: f64,
+}
+
+/// Result of `findById`
+#[derive(Serialize)]
+struct IdMatches {
+    records: Vec<VcfRecordView>,
+    missing: Vec<String>,
+}
+
+/// Warnings kept from a parse together with their totals
+#[derive(Serialize, Deserialize)]
+struct ParseReport {
+    warnings: Vec<WarningJs>,
+    summary: WarningSummaryJs,
+}
+
+impl ParseReport {
+    fn new(warnings: &[ParseWarning], counts: &HashMap<WarningCategory, usize>) -> Self {
+        Self {
+            warnings: warnings
+                .iter()
+                .map(|w| WarningJs {
+                    line: w.line,
+                    category: w.category.as_str().to_string(),
+                    message: w.message.clone(),
+                })
+                .collect(),
+            summary: WarningSummaryJs {
+                total: counts.values().sum(),
+                by_category: counts
+                    .iter()
+                    .map(|(c, &n)| (c.as_str().to_string(), n))
+                    .collect(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct WarningJs {
+    line: usize,
+    category: String,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WarningSummaryJs {
+    total: usize,
+    by_category: BTreeMap<String, usize>,
+}
+
+#[derive(Serialize)]
+struct HeaderJs {
+    file_format: String,
+    reference: Option<String>,
+    samples: Vec<String>,
+    info_field_count: usize,
+    format_field_count: usize,
+}
+
+/// `statsForFilter` options, named as in the Node binding's `QueryOptions`
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct RecordFilterJs {
+    region: Option<String>,
+    chrom: Option<String>,
+    start: Option<f64>,
+    end: Option<f64>,
+    min_qual: Option<f64>,
+    pass_only: bool,
+    variant_types: Option<Vec<String>>,
+}
+
+/// Parsed `RecordFilterJs`
+struct RecordFilter {
+    region: Option<Region>,
+    start: u64,
+    end: u64,
+    min_qual: Option<f64>,
+    pass_only: bool,
+    variant_types: Option<Vec<VariantType>>,
+}
+
+impl RecordFilter {
+    /// Filter for `options` (undefined or null for none), with the options
+    /// re-serialized as a cache key so equivalent objects share an entry
+    fn from_js(options: JsValue) -> Result<(Self, String), JsValue> {
+        let invalid = |message: String| ErrorJs::new("INVALID_ARGUMENT", message);
+        let options: RecordFilterJs = if options.is_undefined() || options.is_null() {
+            RecordFilterJs::default()
+        } else {
+            serde_wasm_bindgen::from_value(options).map_err(|e| invalid(e.to_string()))?
+        };
+        let key = serde_json::to_string(&options).map_err(|e| invalid(e.to_string()))?;
+
+        let region = match &options.region {
+            Some(_)
+                if options.chrom.is_some() || options.start.is_some() || options.end.is_some() =>
+            {
+                return Err(
+                    invalid("Pass either region or chrom/start/end, not both".to_string()).into(),
+                );
+            }
+            Some(region) => Some(Region::parse(region).map_err(|e| invalid(e.to_string()))?),
+            None => options.chrom.clone().map(Region::contig),
+        };
+        let variant_types = match &options.variant_types {
+            Some(names) => Some(
+                names
+                    .iter()
+                    .map(|name| {
+                        variant_type_from_label(name).ok_or_else(|| {
+                            invalid(format!(
+                                "Unknown variant type '{}' (expected SNP, INS, DEL, COMPLEX or OTHER)",
+                                name
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+
+        let filter = Self {
+            region,
+            start: options.start.map_or(0, |p| p.max(0.0) as u64),
+            end: options.end.map_or(u64::MAX, |p| p.max(0.0) as u64),
+            min_qual: options.min_qual,
+            pass_only: options.pass_only,
+            variant_types,
+        };
+        Ok((filter, key))
+    }
+
+    fn matches(&self, record: &RustVcfRecord) -> bool {
+        self.region
+            .as_ref()
+            .is_none_or(|region| region.contains_record(record))
+            && record.pos >= self.start
+            && record.pos <= self.end
+            && self
+                .min_qual
+                .is_none_or(|min| record.qual.is_some_and(|qual| qual >= min))
+            && (!self.pass_only || record.filter == FilterStatus::Pass)
+            && self
+                .variant_types
+                .as_ref()
+                .is_none_or(|types| types.contains(&record.variant_type()))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct CompareOptionsJs {
+    strip_chr_prefix: bool,
+    pass_only: bool,
+    max_discordant: usize,
+    max_diffs: usize,
+}
+
+impl Default for CompareOptionsJs {
+    fn default() -> Self {
+        let defaults = CompareOptions::default();
+        Self {
+            strip_chr_prefix: defaults.strip_chr_prefix,
+            pass_only: defaults.pass_only,
+            max_discordant: defaults.max_discordant,
+            max_diffs: defaults.max_diffs,
+        }
+    }
+}
+
+/// Read comparison options; `undefined` / `null` give the defaults
+fn compare_options(options: JsValue) -> Result<CompareOptions, JsValue> {
+    let options: CompareOptionsJs = if options.is_undefined() || options.is_null() {
+        CompareOptionsJs::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)
+            .map_err(|e| ErrorJs::new("INVALID_ARGUMENT", e.to_string()))?
+    };
+
+    Ok(CompareOptions {
+        strip_chr_prefix: options.strip_chr_prefix,
+        pass_only: options.pass_only,
+        max_discordant: options.max_discordant,
+        max_diffs: options.max_diffs,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompareResultJs {
+    shared: usize,
+    only_a: usize,
+    only_b: usize,
+    concordant_genotypes: usize,
+    discordant_genotypes: usize,
+    genotype_concordance: Option<f64>,
+    discordant: Vec<String>,
+    diffs: Vec<VariantDiff>,
+}
+
+impl From<CompareResult> for CompareResultJs {
+    fn from(result: CompareResult) -> Self {
+        Self {
+            genotype_concordance: result.genotype_concordance(),
+            shared: result.shared,
+            only_a: result.only_a,
+            only_b: result.only_b,
+            concordant_genotypes: result.concordant_genotypes,
+            discordant_genotypes: result.discordant_genotypes,
+            discordant: result.discordant,
+            diffs: result.diffs,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QualHistogramJs {
+    edges: Vec<f64>,
+    counts: Vec<usize>,
+    overflow: usize,
+    missing_qual: usize,
+}
+
+#[derive(Serialize)]
+struct StatsJs {
+    total_records: usize,
+    snps: usize,
+    insertions: usize,
+    deletions: usize,
+    complex: usize,
+    passed_filter: usize,
+    failed_filter: usize,
+    chromosomes: Vec<String>,
+}
+
+/// Size of a heap-allocated string, including its `String` handle
+/// Record indices from JavaScript
+fn record_indices(indices: Vec<u32>) -> Vec<usize> {
+    indices.into_iter().map(|i| i as usize).collect()
+}
+
+fn string_bytes(s: impl AsRef<str>) -> usize {
+    std::mem::size_of::<String>() + s.as_ref().len()
+}
+
+/// Estimated memory of one record, for `memoryEstimateBytes`
+fn record_bytes(record: &RustVcfRecord) -> usize {
+    // CHROM, REF and ID live inline in the record; only their text is extra
+    let inline =
+        record.chrom.len() + record.reference.len() + record.id.as_ref().map_or(0, String::len);
+    let alternate: usize = record.alternate.iter().map(string_bytes).sum();
+
+    let filter = match &record.filter {
+        FilterStatus::Failed(filters) => filters.iter().map(string_bytes).sum(),
+        _ => 0,
+    };
+
+    let info: usize = record
+        .info
+        .iter()
+        .map(|(key, value)| {
+            let payload = match value {
+                InfoValue::String(s) => s.len(),
+                InfoValue::IntegerArray(v) => v.len() * std::mem::size_of::<i64>(),
+                InfoValue::FloatArray(v) => v.len() * std::mem::size_of::<f64>(),
+                InfoValue::StringArray(v) => v.iter().map(string_bytes).sum(),
+                _ => 0,
+            };
+            string_bytes(key) + std::mem::size_of::<InfoValue>() + payload
+        })
+        .sum();
+
+    let samples: usize = record
+        .samples
+        .iter()
+        .map(|sample| {
+            let genotype = sample.genotype.as_ref().map_or(0, |g| g.alleles.len() * 2);
+            let fields: usize = sample
+                .fields
+                .iter()
+                .map(|(k, v)| string_bytes(k) + string_bytes(v))
+                .sum();
+            std::mem::size_of::<SampleData>() + sample.name.len() + genotype + fields
+        })
+        .sum();
+
+    std::mem::size_of::<RustVcfRecord>() + inline + alternate + filter + info + samples
+}
+
+/// Serialize to plain JS values (maps become objects rather than `Map`s)
+fn to_js_value<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true))
+        .map_err(|e| {
+            let error = js_sys::Error::new(&e.to_string());
+            error.set_name("SerializationError");
+            error.into()
+        })
+}
+
+/// Get performance.now() from JavaScript
+#[cfg(target_arch = "wasm32")]
+fn get_performance_now() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0)
+}
+
+/// No JavaScript clock off wasm, so native builds report zero parse time
+#[cfg(not(target_arch = "wasm32"))]
+fn get_performance_now() -> f64 {
+    0.0
+}
+
+/// Compare two VCF callsets
+///
+/// Variants are matched per ALT allele after trimming bases shared by REF
+/// and ALT. `options` may set `stripChrPrefix` (treat `chr1` as `1`),
+/// `passOnly` (ignore non-PASS records), `maxDiscordant` (default 100) and
+/// `maxDiffs` (default 0). Returns `{shared, onlyA, onlyB,
+/// concordantGenotypes, discordantGenotypes, genotypeConcordance,
+/// discordant, diffs}` where `genotypeConcordance` is null if no genotypes
+/// could be compared, `discordant` lists `chrom:pos:ref:alt` keys with
+/// mismatched genotypes and `diffs` gives up to `maxDiffs` shared variants
+/// whose records differ as `{key, fields: [{field, a, b}]}`.
+#[wasm_bindgen(js_name = compareVcfs)]
+pub fn compare_vcfs(
+    content_a: &str,
+    content_b: &str,
+    options: JsValue,
+) -> Result<JsValue, JsValue> {
+    let parser = WasmVcfParser::new();
+    let a = parser.parse_document(content_a)?;
+    let b = parser.parse_document(content_b)?;
+    a.compare_with(&b, options)
+}
+
+/// Current size of the module's linear memory in 64 KiB pages
+///
+/// WASM memory only grows, so this is the high-water mark; compare with
+/// the 65536-page (4 GiB) ceiling to show headroom.
+#[wasm_bindgen(js_name = wasmMemoryPages)]
+pub fn wasm_memory_pages() -> u32 {
+    let memory: js_sys::WebAssembly::Memory = wasm_bindgen::memory().unchecked_into();
+    let bytes = js_sys::ArrayBuffer::from(memory.buffer()).byte_length() as usize;
+    (bytes / WASM_PAGE_SIZE) as u32
+}
+
+/// Convenience function: Parse VCF string
+#[wasm_bindgen(js_name = parseVcf)]
+pub fn parse_vcf(content: &str) -> Result<JsValue, JsValue> {
+    let parser = WasmVcfParser::new();
+    parser.parse(content)
+}
+
+/// Convenience function: Get VCF stats
+#[wasm_bindgen(js_name = getVcfStats)]
+pub fn get_vcf_stats(content: &str) -> Result<WasmVcfStats, JsValue> {
+    let parser = WasmVcfParser::new();
+    parser.parse_stats(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::*;
+
+    const SAMPLE_VCF: &str = r#"##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description="Total Depth">
+##FORMAT=<ID=GT,Number=1,Type=String,Description="Genotype">
+##FORMAT=<ID=DP,Number=1,Type=Integer,Description="Read Depth">
+#CHROM	POS	ID	REF	ALT	QUAL	FILTER	INFO	FORMAT	SAMPLE1	SAMPLE2
+chr1	100	rs123	A	G	30	PASS	DP=50;DB	GT:DP	0/1:25	1|1:30
+chr1	200	.	AT	A	40	PASS	DP=60	GT:DP	0/0:28	0/1:32
+chr2	300	rs456	C	T,G	50	q10	DP=70;AF=0.25,0.5	GT:DP	1/2:35	./.:40
+"#;
+
+    fn field(value: &JsValue, key: &str) -> JsValue {
+        js_sys::Reflect::get(value, &JsValue::from_str(key)).unwrap()
+    }
+
+    fn first_record(parser: &WasmVcfParser) -> JsValue {
+        let result = parser.parse(SAMPLE_VCF).unwrap();
+        js_sys::Reflect::get_u32(&field(&result, "records"), 0).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse() {
+        let parser = WasmVcfParser::new();
+        let result = parser.parse(SAMPLE_VCF);
+        assert!(result.is_ok());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_stats() {
+        let parser = WasmVcfParser::new();
+        let stats = parser.parse_stats(SAMPLE_VCF).unwrap();
+        assert_eq!(stats.total_records(), 3);
+        assert_eq!(stats.snps(), 2);
         assert_eq!(stats.deletions(), 1);
     }
 
@@ -445,4 +2001,949 @@ chr1	200	.	AT	A	40	PASS	DP=60
         let header = parser.parse_header(SAMPLE_VCF).unwrap();
         assert_eq!(header.file_format(), "VCFv4.2");
     }
+
+    #[wasm_bindgen_test]
+    fn test_header_dictionary() {
+        let content = SAMPLE_VCF.replacen(
+            "##INFO",
+            "##contig=<ID=chr1,length=248956422>\n##contig=<ID=chr2>\n\
+             ##FILTER=<ID=q10,Description=\"Quality below 10, or unknown\">\n##INFO",
+            1,
+        );
+        let header = WasmVcfParser::new().parse_header(&content).unwrap();
+        let json = |value: JsValue| js_sys::JSON::stringify(&value).unwrap();
+
+        let dp = header.get_info_definition("DP").unwrap();
+        assert_eq!(
+            json(dp.clone()),
+            r#"{"id":"DP","number":"1","type":"Integer","description":"Total Depth"}"#
+        );
+        assert_eq!(
+            json(js_sys::Reflect::get_u32(&header.info_fields().unwrap(), 0).unwrap()),
+            json(dp)
+        );
+        assert!(header.get_info_definition("AF").unwrap().is_undefined());
+
+        let format = js_sys::Array::from(&header.format_fields().unwrap());
+        assert_eq!(format.length(), 2);
+        assert_eq!(
+            field(&format.get(1), "description").as_string().as_deref(),
+            Some("Read Depth")
+        );
+
+        assert_eq!(
+            json(header.filters().unwrap()),
+            r#"[{"id":"q10","description":"Quality below 10, or unknown"}]"#
+        );
+        assert_eq!(
+            json(header.contigs().unwrap()),
+            r#"[{"id":"chr1","length":248956422},{"id":"chr2"}]"#
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_info_and_samples() {
+        let record = first_record(&WasmVcfParser::new());
+
+        let info = field(&record, "info");
+        assert_eq!(field(&info, "DP").as_f64(), Some(50.0));
+        assert_eq!(field(&info, "DB").as_bool(), Some(true));
+
+        let samples = field(&record, "samples");
+        let sample1 = js_sys::Reflect::get_u32(&samples, 0).unwrap();
+        assert_eq!(
+            field(&sample1, "name").as_string().as_deref(),
+            Some("SAMPLE1")
+        );
+        assert_eq!(field(&sample1, "gt").as_string().as_deref(), Some("0/1"));
+        assert_eq!(field(&sample1, "phased").as_bool(), Some(false));
+        assert_eq!(
+            field(&field(&sample1, "fields"), "DP")
+                .as_string()
+                .as_deref(),
+            Some("25")
+        );
+
+        let sample2 = js_sys::Reflect::get_u32(&samples, 1).unwrap();
+        assert_eq!(field(&sample2, "gt").as_string().as_deref(), Some("1|1"));
+        assert_eq!(field(&sample2, "phased").as_bool(), Some(true));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_records_match_shared_snapshot() {
+        // Same fixture and snapshot as the core and Node binding tests
+        const FIXTURE: &str = include_str!("../../tests/data/shared-records.vcf");
+        const SNAPSHOT: &str = include_str!("../../tests/data/shared-records.json");
+
+        let records = field(&WasmVcfParser::new().parse(FIXTURE).unwrap(), "records");
+        let expected = js_sys::JSON::parse(SNAPSHOT).unwrap();
+
+        assert_eq!(
+            js_sys::JSON::stringify(&records).unwrap().as_string(),
+            js_sys::JSON::stringify(&expected).unwrap().as_string()
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_fast_parse_omits_info_and_samples() {
+        let record = first_record(&WasmVcfParser::fast());
+        assert!(field(&record, "info").is_undefined());
+        assert!(field(&record, "samples").is_undefined());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_records_carry_hgvs_names() {
+        let records = field(&WasmVcfParser::fast().parse(SAMPLE_VCF).unwrap(), "records");
+        let hgvs = |i: u32| -> Vec<String> {
+            let record = js_sys::Reflect::get_u32(&records, i).unwrap();
+            js_sys::Array::from(&field(&record, "hgvs_g"))
+                .iter()
+                .filter_map(|name| name.as_string())
+                .collect()
+        };
+        assert_eq!(hgvs(0), ["chr1:g.100A>G"]);
+        assert_eq!(hgvs(1), ["chr1:g.201del"]);
+        assert_eq!(hgvs(2), ["chr2:g.300C>T", "chr2:g.300C>G"]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_filters_keep_filter_status_and_type() {
+        let parser = WasmVcfParser::new();
+        let parsed = field(&field(&parser.parse(SAMPLE_VCF).unwrap(), "records"), "2");
+
+        let by_chrom = parser.filter_by_chromosome(SAMPLE_VCF, "chr2").unwrap();
+        let by_range = parser
+            .filter_by_range(SAMPLE_VCF, "chr2", 250, 350)
+            .unwrap();
+
+        for filtered in [by_chrom, by_range] {
+            assert_eq!(js_sys::Array::from(&filtered).length(), 1);
+            let record = js_sys::Reflect::get_u32(&filtered, 0).unwrap();
+            assert_eq!(field(&record, "filter").as_string().as_deref(), Some("q10"));
+            assert_eq!(
+                field(&record, "variant_type").as_string().as_deref(),
+                Some("SNP")
+            );
+            assert_eq!(
+                field(&record, "variant_type").as_string(),
+                field(&parsed, "variant_type").as_string()
+            );
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_repeated_queries() {
+        let document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        assert_eq!(document.record_count(), 3);
+        assert_eq!(document.header().file_format(), "VCFv4.2");
+        assert_eq!(document.stats().total_records(), 3);
+
+        let length = |value: JsValue| js_sys::Array::from(&value).length();
+
+        let chr1 = document.filter_by_chromosome("chr1").unwrap();
+        assert_eq!(length(chr1), 2);
+
+        let high_quality = document.filter_by_quality(45.0).unwrap();
+        assert_eq!(length(high_quality.clone()), 1);
+        let record = js_sys::Reflect::get_u32(&high_quality, 0).unwrap();
+        assert_eq!(field(&record, "pos").as_f64(), Some(300.0));
+
+        let deletions = document.filter_by_type("del").unwrap();
+        assert_eq!(length(deletions), 1);
+
+        assert_eq!(
+            length(document.filter_by_range("chr1", 150, 250).unwrap()),
+            1
+        );
+        assert_eq!(length(document.get_records(1, 10).unwrap()), 2);
+
+        // Queries leave the document intact
+        assert_eq!(length(document.filter_by_chromosome("chr1").unwrap()), 2);
+        assert_eq!(document.record_count(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_find_by_id() {
+        let vcf = SAMPLE_VCF.replace("\trs456\t", "\trs456;rs789\t");
+        let document = WasmVcfParser::new().parse_document(&vcf).unwrap();
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect();
+
+        let found = document
+            .find_by_id(ids(&["rs789", " rs123 ", "rs456", "rs0"]))
+            .unwrap();
+        let records = field(&found, "records");
+        assert_eq!(js_sys::Array::from(&records).length(), 2);
+        let first = js_sys::Reflect::get_u32(&records, 0).unwrap();
+        assert_eq!(field(&first, "pos").as_f64(), Some(100.0));
+        let missing: Vec<String> =
+            serde_wasm_bindgen::from_value(field(&found, "missing")).unwrap();
+        assert_eq!(missing, ["rs0"]);
+
+        let found = document.find_by_id(ids(&["rs456"])).unwrap();
+        let record = js_sys::Reflect::get_u32(&field(&found, "records"), 0).unwrap();
+        assert_eq!(
+            field(&record, "id").as_string().as_deref(),
+            Some("rs456;rs789")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_max_records_truncates_but_counts() {
+        let mut parser = WasmVcfParser::new();
+        parser.set_max_records(Some(1));
+
+        let result = parser.parse(SAMPLE_VCF).unwrap();
+        assert_eq!(
+            field(&field(&result, "stats"), "total_records").as_f64(),
+            Some(3.0)
+        );
+        assert_eq!(js_sys::Array::from(&field(&result, "records")).length(), 1);
+        assert_eq!(field(&result, "truncated").as_bool(), Some(true));
+
+        let document = parser.parse_document(SAMPLE_VCF).unwrap();
+        assert_eq!(document.record_count(), 1);
+        assert!(document.truncated());
+
+        let mut stream = parser.stream_parser(true);
+        stream.append_chunk(SAMPLE_VCF).unwrap();
+        assert!(stream.truncated());
+        assert_eq!(stream.finish().unwrap().total_records(), 3);
+        assert_eq!(stream.into_document().unwrap().record_count(), 1);
+
+        parser.set_max_records(None);
+        let result = parser.parse(SAMPLE_VCF).unwrap();
+        assert_eq!(field(&result, "truncated").as_bool(), Some(false));
+        assert!(!parser.parse_document(SAMPLE_VCF).unwrap().truncated());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_memory_estimate() {
+        let full = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        let fast = WasmVcfParser::fast().parse_document(SAMPLE_VCF).unwrap();
+        let mut limited = WasmVcfParser::new();
+        limited.set_max_records(Some(1));
+        let limited = limited.parse_document(SAMPLE_VCF).unwrap();
+
+        let estimate = full.memory_estimate_bytes();
+        assert!(estimate > 3 * std::mem::size_of::<RustVcfRecord>());
+        assert!(fast.memory_estimate_bytes() < estimate);
+        assert!(limited.memory_estimate_bytes() < estimate);
+
+        assert!(wasm_memory_pages() > 0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_get_page() {
+        let mut content =
+            String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+        for (chrom, pos, reference, qual) in [
+            ("chr10", 100, "A", "10"),
+            ("chr2", 200, "AT", "50"),
+            ("chr2", 150, "C", "30"),
+            ("chr1", 400, "G", "50"),
+            ("chrX", 500, "T", "."),
+            ("chr10", 50, "A", "30"),
+            ("chr2", 700, "C", "20"),
+        ] {
+            content += &format!(
+                "{}\t{}\t.\t{}\tG\t{}\tPASS\t.\n",
+                chrom, pos, reference, qual
+            );
+        }
+        let document = WasmVcfParser::new().parse_document(&content).unwrap();
+        assert_eq!(document.total_records(), 7);
+
+        let page = |offset, limit, sort_by: Option<&str>, descending| -> Vec<(String, f64)> {
+            let records = document
+                .get_page(offset, limit, sort_by.map(String::from), descending)
+                .unwrap();
+            js_sys::Array::from(&records)
+                .iter()
+                .map(|r| {
+                    (
+                        field(&r, "chrom").as_string().unwrap(),
+                        field(&r, "pos").as_f64().unwrap(),
+                    )
+                })
+                .collect()
+        };
+        let positions = |records: Vec<(String, f64)>| -> Vec<f64> {
+            records.into_iter().map(|(_, pos)| pos).collect()
+        };
+
+        // Page 2 by QUAL descending: the two QUAL 30 records in file order
+        assert_eq!(positions(page(2, 2, Some("qual"), true)), vec![150.0, 50.0]);
+        assert_eq!(
+            positions(page(0, 2, Some("qual"), true)),
+            vec![200.0, 400.0]
+        );
+        // Missing QUAL sorts last in both directions
+        assert_eq!(positions(page(6, 10, Some("qual"), true)), vec![500.0]);
+        assert_eq!(
+            positions(page(0, 2, Some("qual"), false)),
+            vec![100.0, 700.0]
+        );
+        assert_eq!(positions(page(6, 1, Some("qual"), false)), vec![500.0]);
+
+        let by_chrom: Vec<String> = page(0, 7, Some("chrom"), false)
+            .into_iter()
+            .map(|(chrom, pos)| format!("{}:{}", chrom, pos))
+            .collect();
+        assert_eq!(
+            by_chrom,
+            [
+                "chr1:400",
+                "chr2:150",
+                "chr2:200",
+                "chr2:700",
+                "chr10:50",
+                "chr10:100",
+                "chrX:500"
+            ]
+        );
+        assert_eq!(
+            positions(page(0, 1, Some("variant_type"), true)),
+            vec![200.0]
+        );
+        assert_eq!(positions(page(0, 1, None, true)), vec![700.0]);
+        assert_eq!(
+            positions(page(1, 2, Some("pos"), false)),
+            vec![100.0, 150.0]
+        );
+
+        assert!(page(7, 5, Some("pos"), false).is_empty());
+        assert!(page(100, 5, None, false).is_empty());
+        assert_eq!(document.sort_cache.borrow().len(), 5);
+
+        let error = document
+            .get_page(0, 5, Some("depth".into()), false)
+            .unwrap_err();
+        assert_eq!(
+            field(&error, "code").as_string().as_deref(),
+            Some("INVALID_ARGUMENT")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("chr2", "chr10"), Ordering::Less);
+        assert_eq!(natural_cmp("chr10", "chrX"), Ordering::Less);
+        assert_eq!(natural_cmp("chr1", "chr1_random"), Ordering::Less);
+        assert_eq!(natural_cmp("chr01", "chr1"), Ordering::Equal);
+        assert_eq!(natural_cmp("2", "10"), Ordering::Less);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_columns() {
+        let document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        let records = document.get_records(0, 10).unwrap();
+        let n = document.record_count() as u32;
+
+        let positions = document.positions();
+        let quals = document.quals();
+        let types = document.variant_type_codes();
+        let chroms = document.chrom_codes().unwrap();
+        assert_eq!(positions.length(), n);
+        assert_eq!(quals.length(), n);
+        assert_eq!(types.length(), n);
+        assert_eq!(chroms.length(), n);
+
+        let labels = document.type_code_labels();
+        let names = document.chrom_names();
+        assert_eq!(names.length(), 2);
+
+        for i in 0..n {
+            let record = js_sys::Reflect::get_u32(&records, i).unwrap();
+            assert_eq!(Some(positions.get_index(i)), field(&record, "pos").as_f64());
+            assert_eq!(Some(quals.get_index(i)), field(&record, "qual").as_f64());
+            assert_eq!(
+                labels.get(types.get_index(i) as u32).as_string(),
+                field(&record, "variant_type").as_string()
+            );
+            assert_eq!(
+                names.get(chroms.get_index(i) as u32).as_string(),
+                field(&record, "chrom").as_string()
+            );
+        }
+        assert_eq!(types.get_index(1), 2);
+        assert_eq!(chroms.to_vec(), vec![0, 0, 1]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_stats_for_filter() {
+        let document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        let options = |json: &str| js_sys::JSON::parse(json).unwrap();
+
+        // QUAL 30 falls below the threshold; 40 and 50 remain
+        let stats = document
+            .stats_for_filter(options(r#"{"minQual": 35}"#))
+            .unwrap();
+        assert_eq!(stats.total_records(), 2);
+        assert_eq!(stats.snps(), 1);
+        assert_eq!(stats.deletions(), 1);
+        assert_eq!(stats.passed_filter(), 1);
+        assert_eq!(stats.failed_filter(), 1);
+        assert_eq!(
+            document
+                .count_for_filter(options(r#"{"minQual": 35}"#))
+                .unwrap(),
+            2
+        );
+        assert_eq!(document.stats_cache.borrow().len(), 1);
+
+        let pass_chr1 = options(r#"{"chrom": "chr1", "passOnly": true, "minQual": 35}"#);
+        assert_eq!(document.count_for_filter(pass_chr1).unwrap(), 1);
+        assert_eq!(document.count_for_filter(JsValue::UNDEFINED).unwrap(), 3);
+
+        // Nothing passes: zeroed stats rather than an error
+        let none = document
+            .stats_for_filter(options(r#"{"minQual": 1000}"#))
+            .unwrap();
+        assert_eq!(none.total_records(), 0);
+        assert_eq!(none.snps(), 0);
+        assert!(document
+            .stats_for_filter(options(r#"{"variantTypes": ["BOGUS"]}"#))
+            .is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_thinned_indices() {
+        let document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        assert_eq!(document.thinned_indices(10).to_vec(), vec![0, 1, 2]);
+        assert_eq!(document.thinned_indices(2).to_vec(), vec![0, 2]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_manhattan_data() {
+        let content = SAMPLE_VCF
+            .replace("DP=50;DB", "DP=50;P=0.001")
+            .replace("DP=70;", "DP=70;P=0;");
+        let content = content.replacen("##INFO", "##contig=<ID=chr1,length=1000>\n##INFO", 1);
+        let document = WasmVcfParser::new().parse_document(&content).unwrap();
+
+        let data = document.manhattan_data("P", false, None).unwrap();
+        let floats = |key: &str| js_sys::Float64Array::from(field(&data, key)).to_vec();
+        assert_eq!(floats("positions"), vec![100.0, 1300.0]);
+        assert_eq!(floats("offsets"), vec![0.0, 1000.0]);
+        assert_eq!(floats("negLog10P")[1], 300.0);
+        assert_eq!(
+            js_sys::Uint32Array::from(field(&data, "chromIndex")).to_vec(),
+            vec![0, 1]
+        );
+        assert_eq!(field(&data, "skipped").as_f64(), Some(1.0));
+        assert_eq!(field(&data, "clamped").as_f64(), Some(1.0));
+
+        let capped = document.manhattan_data("P", false, Some(2.5)).unwrap();
+        let capped = js_sys::Float64Array::from(field(&capped, "negLog10P")).to_vec();
+        assert_eq!(capped, vec![2.5, 2.5]);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_missing_qual_is_nan() {
+        let content = SAMPLE_VCF.replace("\t30\tPASS", "\t.\tPASS");
+        let document = WasmVcfParser::fast().parse_document(&content).unwrap();
+        assert!(document.quals().get_index(0).is_nan());
+        assert_eq!(document.quals().get_index(1), 40.0);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_density_and_histogram() {
+        let content = SAMPLE_VCF.replace("\t30\tPASS", "\t.\tPASS");
+        let document = WasmVcfParser::fast().parse_document(&content).unwrap();
+        let number = |value: &JsValue, key: &str| field(value, key).as_f64().unwrap();
+
+        let density = document.variant_density(150).unwrap();
+        assert_eq!(js_sys::Array::from(&density).length(), 2);
+        let chr1 = js_sys::Reflect::get_u32(&density, 0).unwrap();
+        let bins = field(&chr1, "bins");
+        assert_eq!(js_sys::Array::from(&bins).length(), 2);
+        let first = js_sys::Reflect::get_u32(&bins, 0).unwrap();
+        assert_eq!(number(&first, "count"), 1.0);
+        assert_eq!(number(&first, "snps"), 1.0);
+        let second = js_sys::Reflect::get_u32(&bins, 1).unwrap();
+        assert_eq!(number(&second, "start"), 150.0);
+        assert_eq!(number(&second, "indels"), 1.0);
+
+        // Single-record chromosome, then a different bin size on the same document
+        let chr2 = js_sys::Reflect::get_u32(&density, 1).unwrap();
+        assert_eq!(field(&chr2, "chrom").as_string().as_deref(), Some("chr2"));
+        assert_eq!(js_sys::Array::from(&field(&chr2, "bins")).length(), 2);
+        let coarse = document.variant_density(1000).unwrap();
+        let chr1 = js_sys::Reflect::get_u32(&coarse, 0).unwrap();
+        assert_eq!(js_sys::Array::from(&field(&chr1, "bins")).length(), 1);
+        assert!(document.variant_density(0).is_err());
+
+        let histogram = document.qual_histogram(25.0, 45.0).unwrap();
+        let counts = field(&histogram, "counts");
+        assert_eq!(js_sys::Array::from(&counts).to_vec().len(), 2);
+        assert_eq!(
+            js_sys::Reflect::get_u32(&counts, 1).unwrap().as_f64(),
+            Some(1.0)
+        );
+        assert_eq!(number(&histogram, "overflow"), 1.0);
+        assert_eq!(number(&histogram, "missingQual"), 1.0);
+        assert!(document.qual_histogram(0.0, 10.0).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_reports_warnings() {
+        // Malformed POS on line 7, between the first and second records
+        let mut lines: Vec<&str> = SAMPLE_VCF.lines().collect();
+        lines.insert(6, "chr1\tabc\t.\tA\tG\t10\tPASS\t.");
+        let content = lines.join("\n");
+
+        let result = WasmVcfParser::new().parse(&content).unwrap();
+        assert_eq!(js_sys::Array::from(&field(&result, "records")).length(), 3);
+
+        let warnings = field(&result, "warnings");
+        assert_eq!(js_sys::Array::from(&warnings).length(), 1);
+        let warning = js_sys::Reflect::get_u32(&warnings, 0).unwrap();
+        assert_eq!(field(&warning, "line").as_f64(), Some(7.0));
+        assert_eq!(
+            field(&warning, "category").as_string().as_deref(),
+            Some("other")
+        );
+        assert!(field(&warning, "message")
+            .as_string()
+            .unwrap()
+            .contains("abc"));
+
+        let summary = field(&result, "warning_summary");
+        assert_eq!(field(&summary, "total").as_f64(), Some(1.0));
+        assert_eq!(
+            field(&field(&summary, "by_category"), "other").as_f64(),
+            Some(1.0)
+        );
+
+        // Capped warnings are still counted; disabled collection reports nothing
+        let mut parser = WasmVcfParser::new();
+        parser.set_max_warnings(0);
+        let document = parser.parse_document(&content).unwrap();
+        assert_eq!(
+            js_sys::Array::from(&document.warnings().unwrap()).length(),
+            0
+        );
+        let summary = document.warning_summary().unwrap();
+        assert_eq!(field(&summary, "total").as_f64(), Some(1.0));
+
+        parser.set_collect_warnings(false);
+        let result = parser.parse(&content).unwrap();
+        let summary = field(&result, "warning_summary");
+        assert_eq!(field(&summary, "total").as_f64(), Some(0.0));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_structured_errors() {
+        let records_only = SAMPLE_VCF
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let error = WasmVcfParser::new().parse(&records_only).unwrap_err();
+        assert_eq!(
+            field(&error, "code").as_string().as_deref(),
+            Some("MISSING_HEADER")
+        );
+        assert!(error.is_instance_of::<js_sys::Error>());
+        assert!(field(&error, "line").is_undefined());
+        let message = field(&error, "message").as_string().unwrap();
+        assert_eq!(
+            js_sys::Error::from(error).to_string().as_string(),
+            Some(format!("Error: {}", message))
+        );
+
+        // Bad POS on line 7 fails the parse when invalid records are not skipped
+        let mut lines: Vec<&str> = SAMPLE_VCF.lines().collect();
+        lines.insert(6, "chr1\tabc\t.\tA\tG\t10\tPASS\t.");
+        let mut parser = WasmVcfParser::new();
+        parser.set_skip_invalid(false);
+        let error = parser.parse_document(&lines.join("\n")).err().unwrap();
+        assert_eq!(
+            field(&error, "code").as_string().as_deref(),
+            Some("INVALID_POSITION")
+        );
+        assert_eq!(field(&error, "line").as_f64(), Some(7.0));
+        assert_eq!(field(&error, "field").as_string().as_deref(), Some("POS"));
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_vcf_round_trip() {
+        let document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+
+        let exported = document.to_vcf_string(None, false).unwrap();
+        assert!(exported.ends_with('\n'));
+        assert!(exported
+            .contains("#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tSAMPLE1\tSAMPLE2\n"));
+
+        let reparsed = WasmVcfParser::new().parse_document(&exported).unwrap();
+        assert_eq!(reparsed.record_count(), document.record_count());
+        let record = first_record_of(&reparsed, 2);
+        assert_eq!(field(&record, "pos").as_f64(), Some(300.0));
+        assert_eq!(field(&record, "filter").as_string().as_deref(), Some("q10"));
+        let info = field(&record, "info");
+        assert_eq!(
+            js_sys::JSON::stringify(&field(&info, "AF")).unwrap(),
+            "[0.25,0.5]"
+        );
+        let sample = js_sys::Reflect::get_u32(&field(&record, "samples"), 0).unwrap();
+        assert_eq!(field(&sample, "gt").as_string().as_deref(), Some("1/2"));
+
+        // Selected indices, sorted back into genome order
+        let subset = document.to_vcf_string(Some(vec![2, 0]), true).unwrap();
+        let subset = WasmVcfParser::new().parse_document(&subset).unwrap();
+        assert_eq!(subset.record_count(), 2);
+        assert_eq!(
+            field(&first_record_of(&subset, 0), "pos").as_f64(),
+            Some(100.0)
+        );
+        assert!(document.to_vcf_string(Some(vec![3]), false).is_err());
+
+        let range = document.filtered_to_vcf("chr1", 150, 250).unwrap();
+        let range = WasmVcfParser::fast().parse_document(&range).unwrap();
+        assert_eq!(range.record_count(), 1);
+        assert_eq!(range.stats().deletions(), 1);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_edits_and_undo() {
+        let mut document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        let counts = |document: &WasmVcfDocument| {
+            let stats = document.stats();
+            (
+                stats.total_records(),
+                stats.passed_filter(),
+                stats.failed_filter(),
+            )
+        };
+        let filter = |document: &WasmVcfDocument, index| {
+            field(&first_record_of(document, index), "filter").as_string()
+        };
+        let reviewed = |document: &WasmVcfDocument, index| {
+            field(
+                &field(&first_record_of(document, index), "info"),
+                "REVIEWED",
+            )
+            .as_bool()
+        };
+        assert_eq!(counts(&document), (3, 2, 1));
+
+        document.mark_reviewed(vec![0, 2]).unwrap();
+        document.mark_rejected(vec![1]).unwrap();
+        assert_eq!(reviewed(&document, 2), Some(true));
+        assert_eq!(filter(&document, 1).as_deref(), Some("REJECTED"));
+        assert_eq!(counts(&document), (3, 1, 2));
+
+        let exported = document.to_vcf_string(None, false).unwrap();
+        assert!(exported.contains("##INFO=<ID=REVIEWED,Number=0,Type=Flag,"));
+        assert!(exported.contains("##FILTER=<ID=REJECTED,"));
+        assert!(exported.contains("\t40\tREJECTED\t"));
+
+        document.remove_records(vec![0]).unwrap();
+        assert_eq!(document.record_count(), 2);
+        assert_eq!(counts(&document), (2, 0, 2));
+        assert_eq!(filter(&document, 0).as_deref(), Some("REJECTED"));
+        assert!(!document
+            .to_vcf_string(None, false)
+            .unwrap()
+            .contains("\t100\t"));
+
+        // Undo the removal, the rejection, then the review flags
+        assert!(document.undo());
+        assert_eq!(counts(&document), (3, 1, 2));
+        assert_eq!(reviewed(&document, 0), Some(true));
+        assert!(document.undo());
+        assert_eq!(filter(&document, 1).as_deref(), Some("PASS"));
+        assert_eq!(counts(&document), (3, 2, 1));
+        assert!(document.undo());
+        assert_eq!(reviewed(&document, 0), None);
+        assert!(!document.undo());
+        // The header keeps the flag's definition; no record carries it
+        let exported = document.to_vcf_string(None, false).unwrap();
+        assert!(exported
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .all(|line| !line.contains("REVIEWED")));
+
+        assert!(document.redo());
+        assert_eq!(reviewed(&document, 2), Some(true));
+        assert_eq!(counts(&document), (3, 2, 1));
+        assert!(document.remove_records(vec![3]).is_err());
+        assert_eq!(document.record_count(), 3);
+    }
+
+    fn first_record_of(document: &WasmVcfDocument, index: usize) -> JsValue {
+        let records = document.get_records(index, 1).unwrap();
+        js_sys::Reflect::get_u32(&records, 0).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_compare_vcfs() {
+        // One extra record and SAMPLE1's genotype at chr1:100 flipped
+        let other = SAMPLE_VCF.replace("0/1:25\t1|1:30", "1/1:25\t1|1:30")
+            + "chr3\t400\t.\tG\tA\t60\tPASS\t.\tGT:DP\t0/1:20\t0/1:22\n";
+
+        let result = compare_vcfs(SAMPLE_VCF, &other, JsValue::UNDEFINED).unwrap();
+        let number = |key: &str| field(&result, key).as_f64().unwrap();
+        // chr2:300 has two ALT alleles, so four keys are shared
+        assert_eq!(number("shared"), 4.0);
+        assert_eq!(number("onlyA"), 0.0);
+        assert_eq!(number("onlyB"), 1.0);
+        assert_eq!(number("discordantGenotypes"), 1.0);
+        assert_eq!(number("genotypeConcordance"), 0.75);
+        let discordant = field(&result, "discordant");
+        assert_eq!(
+            js_sys::Reflect::get_u32(&discordant, 0)
+                .unwrap()
+                .as_string()
+                .as_deref(),
+            Some("chr1:100:A:G")
+        );
+
+        // Document handles and options: PASS-only drops chr2, prefix stripping
+        let a = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        let b = WasmVcfParser::new()
+            .parse_document(&other.replace("chr", ""))
+            .unwrap();
+        let options = js_sys::JSON::parse(
+            r#"{"stripChrPrefix": true, "passOnly": true, "maxDiscordant": 0}"#,
+        )
+        .unwrap();
+        let result = a.compare_with(&b, options).unwrap();
+        assert_eq!(field(&result, "shared").as_f64(), Some(2.0));
+        assert_eq!(field(&result, "onlyB").as_f64(), Some(1.0));
+        assert_eq!(
+            js_sys::Array::from(&field(&result, "discordant")).length(),
+            0
+        );
+        assert!(a.compare_with(&b, JsValue::from_str("nope")).is_err());
+
+        // Field-level diffs of the flipped genotype, on request
+        let options = js_sys::JSON::parse(r#"{"maxDiffs": 5}"#).unwrap();
+        let result = compare_vcfs(SAMPLE_VCF, &other, options).unwrap();
+        let diffs = js_sys::Array::from(&field(&result, "diffs"));
+        assert_eq!(diffs.length(), 1);
+        let diff = diffs.get(0);
+        assert_eq!(
+            field(&diff, "key").as_string().as_deref(),
+            Some("chr1:100:A:G")
+        );
+        let fields = js_sys::Array::from(&field(&diff, "fields"));
+        assert_eq!(
+            field(&fields.get(0), "field").as_string().as_deref(),
+            Some("samples[SAMPLE1].GT")
+        );
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_binary_round_trip() {
+        let with_warning = SAMPLE_VCF.to_string() + "chr3\tabc\t.\tA\tG\t10\tPASS\t.\n";
+        let document = WasmVcfParser::new().parse_document(&with_warning).unwrap();
+        let bytes = document.to_binary().unwrap().to_vec();
+        assert!(bytes.starts_with(b"VCFB"));
+
+        let restored = WasmVcfDocument::from_binary(&bytes).unwrap();
+        assert_eq!(restored.record_count(), 3);
+        let (stats, restored_stats) = (document.stats(), restored.stats());
+        assert_eq!(restored_stats.total_records(), stats.total_records());
+        assert_eq!(restored_stats.snps(), stats.snps());
+        assert_eq!(restored_stats.deletions(), stats.deletions());
+
+        let json = |value: JsValue| js_sys::JSON::stringify(&value).unwrap();
+        for index in 0..3 {
+            assert_eq!(
+                json(first_record_of(&restored, index)),
+                json(first_record_of(&document, index))
+            );
+        }
+        assert_eq!(
+            json(restored.warning_summary().unwrap()),
+            json(document.warning_summary().unwrap())
+        );
+        assert_eq!(
+            restored.to_vcf_string(None, false).unwrap(),
+            document.to_vcf_string(None, false).unwrap()
+        );
+
+        // Options travel with the document
+        let fast = WasmVcfParser::fast().parse_document(SAMPLE_VCF).unwrap();
+        let fast = WasmVcfDocument::from_binary(&fast.to_binary().unwrap().to_vec()).unwrap();
+        assert!(field(&first_record_of(&fast, 0), "info").is_undefined());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_document_binary_rejects_bad_input() {
+        let document = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+        let bytes = document.to_binary().unwrap().to_vec();
+        let code = |data: &[u8]| {
+            let error = WasmVcfDocument::from_binary(data).err().unwrap();
+            field(&error, "code").as_string().unwrap()
+        };
+
+        assert_eq!(code(SAMPLE_VCF.as_bytes()), "INVALID_BINARY");
+        assert_eq!(code(b"VCF"), "INVALID_BINARY");
+        assert_eq!(code(&bytes[..bytes.len() / 2]), "INVALID_BINARY");
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+        assert_eq!(code(&newer), "UNSUPPORTED_VERSION");
+    }
+
+    fn stream_bytes(content: &[u8], chunk_size: usize) -> WasmVcfStreamParser {
+        let mut stream = WasmVcfParser::new().stream_parser(true);
+        for chunk in content.chunks(chunk_size) {
+            stream.append_bytes(chunk).unwrap();
+        }
+        stream
+    }
+
+    #[wasm_bindgen_test]
+    fn test_stream_parse_matches_one_shot() {
+        let expected = WasmVcfParser::new().parse_document(SAMPLE_VCF).unwrap();
+
+        let mut stream = stream_bytes(SAMPLE_VCF.as_bytes(), 7);
+        let stats = stream.finish().unwrap();
+        assert_eq!(stats.total_records(), expected.stats().total_records());
+        assert_eq!(stats.snps(), expected.stats().snps());
+        assert_eq!(stats.deletions(), expected.stats().deletions());
+        let json = |value: JsValue| js_sys::JSON::stringify(&value).unwrap();
+        assert_eq!(
+            json(stats.chromosomes()),
+            json(expected.stats().chromosomes())
+        );
+
+        let document = stream.into_document().unwrap();
+        assert_eq!(
+            json(document.header().samples()),
+            json(expected.header().samples())
+        );
+
+        let streamed = document.get_records(0, 10).unwrap();
+        let one_shot = expected.get_records(0, 10).unwrap();
+        assert_eq!(js_sys::Array::from(&streamed).length(), 3);
+        for i in 0..3 {
+            let a = js_sys::Reflect::get_u32(&streamed, i).unwrap();
+            let b = js_sys::Reflect::get_u32(&one_shot, i).unwrap();
+            for key in [
+                "chrom",
+                "pos",
+                "reference",
+                "alternate",
+                "qual",
+                "filter",
+                "variant_type",
+            ] {
+                assert_eq!(json(field(&a, key)), json(field(&b, key)));
+            }
+            let (info_a, info_b) = (field(&a, "info"), field(&b, "info"));
+            assert_eq!(json(field(&info_a, "DP")), json(field(&info_b, "DP")));
+        }
+    }
+
+    #[wasm_bindgen_test]
+    fn test_stream_text_chunks_and_progress() {
+        let mut stream = WasmVcfParser::fast().stream_parser(false);
+        let lines: Vec<&str> = SAMPLE_VCF.split_inclusive('\n').collect();
+        let (header, body) = lines.split_at(lines.len() - 3);
+
+        assert_eq!(stream.append_chunk(&header.concat()).unwrap(), 0);
+        assert_eq!(stream.append_chunk(body[0]).unwrap(), 1);
+        // Second record split mid-line
+        let (head, tail) = body[1].split_at(5);
+        assert_eq!(stream.append_chunk(head).unwrap(), 0);
+        assert_eq!(stream.append_chunk(tail).unwrap(), 1);
+        assert_eq!(stream.records_parsed(), 2);
+        assert_eq!(stream.current_stats().deletions(), 1);
+
+        // Final line without a trailing newline is parsed by finish()
+        stream.append_chunk(body[2].trim_end()).unwrap();
+        assert_eq!(stream.records_parsed(), 2);
+        assert_eq!(stream.finish().unwrap().total_records(), 3);
+
+        // Stats-only mode has no document to hand over
+        assert!(stream.into_document().is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_stream_sampler_keeps_n_records() {
+        let sample = |n, seed| {
+            let mut stream = WasmVcfParser::new().stream_sampler(n, seed);
+            stream.append_chunk(SAMPLE_VCF).unwrap();
+            assert_eq!(stream.finish().unwrap().total_records(), 3);
+            stream.into_document().unwrap()
+        };
+
+        let document = sample(2, 9);
+        assert_eq!(document.record_count(), 2);
+        let positions = document.positions().to_vec();
+        assert!(positions[0] < positions[1]);
+        assert_eq!(sample(2, 9).positions().to_vec(), positions);
+        assert_eq!(sample(10, 9).record_count(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_stream_bytes_split_utf8() {
+        let content = SAMPLE_VCF.replacen(
+            "##fileformat=VCFv4.2\n",
+            "##fileformat=VCFv4.2\n##source=Génome→Ω\n",
+            1,
+        );
+
+        let document = stream_bytes(content.as_bytes(), 1).into_document().unwrap();
+        assert_eq!(document.record_count(), 3);
+        assert_eq!(
+            js_sys::Array::from(&document.header().samples()).length(),
+            2
+        );
+
+        let mut truncated = WasmVcfParser::new().stream_parser(false);
+        truncated.append_bytes(&"Ω".as_bytes()[..1]).unwrap();
+        assert!(truncated.finish().is_err());
+        assert!(WasmVcfParser::new()
+            .stream_parser(false)
+            .append_bytes(&[0xff, b'\n'])
+            .is_err());
+    }
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_gzip() {
+        let compressed = gzip(SAMPLE_VCF.as_bytes());
+
+        let result = WasmVcfParser::new().parse_gzip(&compressed).unwrap();
+        let records = field(&result, "records");
+        assert_eq!(js_sys::Array::from(&records).length(), 3);
+        assert_eq!(
+            field(&field(&result, "stats"), "total_records").as_f64(),
+            Some(3.0)
+        );
+
+        let mut stream = WasmVcfParser::new().stream_parser(false);
+        for chunk in compressed.chunks(5) {
+            stream.append_gzip_chunk(chunk).unwrap();
+        }
+        assert_eq!(stream.finish().unwrap().total_records(), 3);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_parse_gzip_errors() {
+        let code = |error: JsValue| field(&error, "code").as_string().unwrap();
+        let parser = WasmVcfParser::new();
+
+        let error = parser.parse_gzip(SAMPLE_VCF.as_bytes()).unwrap_err();
+        assert_eq!(code(error), "NOT_GZIP");
+
+        let compressed = gzip(SAMPLE_VCF.as_bytes());
+        let error = parser
+            .parse_gzip(&compressed[..compressed.len() / 2])
+            .unwrap_err();
+        assert_eq!(code(error), "GZIP_TRUNCATED");
+    }
 }
@@ -110,6 +110,29 @@ export async function oddsRatio(a, b, c, d, confidence = 0.95) {
   };
 }
 
+/**
+ * Per-variant case/control association test over a VCF
+ * @param {string} vcfContent - VCF text with sample genotypes
+ * @param {string[]} caseSamples - Case sample names
+ * @param {string[]} controlSamples - Control sample names
+ * @param {string} test - 'fisher' (allelic) or 'trend' (Cochran-Armitage)
+ * @returns {Promise<Object>} chrom, pos, ref, alt, p, fdr and or arrays, one entry per variant
+ */
+export async function associationTest(vcfContent, caseSamples, controlSamples, test = 'fisher') {
+  const wasm = await initWasm();
+  const result = wasm.association_test(vcfContent, caseSamples, controlSamples, test);
+
+  return {
+    chrom: result.chrom,
+    pos: Array.from(result.pos),
+    ref: result.ref,
+    alt: result.alt,
+    p: Array.from(result.p),
+    fdr: Array.from(result.fdr),
+    or: Array.from(result.or),
+  };
+}
+
 // ============================================
 // Clustering Functions
 // ============================================
@@ -207,6 +230,41 @@ export async function gcContentWindows(sequence, windowSize, step) {
   return Array.from(wasm.gc_content_windows(sequence, windowSize, step));
 }
 
+/**
+ * GC content in sliding windows with window coordinates
+ * @param {string} sequence - DNA sequence
+ * @param {number} windowSize - Window size
+ * @param {number} step - Step size
+ * @returns {Promise<Object>} 0-based window starts, exclusive ends and GC values
+ */
+export async function gcContentWindowsPositions(sequence, windowSize, step) {
+  const wasm = await initWasm();
+  const result = wasm.gc_content_windows_positions(sequence, windowSize, step);
+
+  return {
+    starts: Array.from(result.starts()),
+    ends: Array.from(result.ends()),
+    values: Array.from(result.values()),
+  };
+}
+
+/**
+ * Cumulative GC skew with predicted origin and terminus
+ * @param {string} sequence - DNA sequence
+ * @param {number} windowSize - Window size
+ * @returns {Promise<Object>} Cumulative skew per window and extreme positions
+ */
+export async function gcSkew(sequence, windowSize) {
+  const wasm = await initWasm();
+  const result = wasm.gc_skew_analysis(sequence, windowSize);
+
+  return {
+    cumulative: Array.from(result.cumulative()),
+    origin: result.origin(),
+    terminus: result.terminus(),
+  };
+}
+
 /**
  * Count k-mers in sequence
  * @param {string} sequence - DNA sequence
@@ -324,11 +382,12 @@ export async function spearmanCorrelation(x, y) {
  * @param {number[]} matrix - Flattened matrix (genes x samples)
  * @param {number} rows - Number of rows (genes)
  * @param {number} cols - Number of columns (samples)
+ * @param {string} [method='pearson'] - 'pearson', 'spearman' or 'kendall'
  * @returns {Promise<Object>} Correlation matrix
  */
-export async function correlationMatrix(matrix, rows, cols) {
+export async function correlationMatrix(matrix, rows, cols, method = 'pearson') {
   const wasm = await initWasm();
-  const result = wasm.correlation_matrix(new Float64Array(matrix), rows, cols);
+  const result = wasm.correlation_matrix(new Float64Array(matrix), rows, cols, method);
 
   return {
     data: Array.from(result.data()),
@@ -338,15 +397,16 @@ export async function correlationMatrix(matrix, rows, cols) {
 }
 
 /**
- * Z-score normalize matrix (row-wise)
+ * Z-score normalize matrix
  * @param {number[]} matrix - Flattened matrix
  * @param {number} rows - Number of rows
  * @param {number} cols - Number of columns
+ * @param {number} axis - 0 to normalize each row, 1 to normalize each column
  * @returns {Promise<Object>} Normalized matrix
  */
-export async function zscoreNormalize(matrix, rows, cols) {
+export async function zscoreNormalize(matrix, rows, cols, axis = 0) {
   const wasm = await initWasm();
-  const result = wasm.zscore_normalize(new Float64Array(matrix), rows, cols);
+  const result = wasm.zscore_normalize(new Float64Array(matrix), rows, cols, axis);
 
   return {
     data: Array.from(result.data()),
@@ -399,12 +459,15 @@ export default {
   fisherTest,
   fisherTestBatch,
   oddsRatio,
+  associationTest,
   kmeans,
   kmeansBest,
   elbowAnalysis,
   silhouetteScore,
   gcContent,
   gcContentWindows,
+  gcContentWindowsPositions,
+  gcSkew,
   countKmers,
   reverseComplement,
   transcribe,
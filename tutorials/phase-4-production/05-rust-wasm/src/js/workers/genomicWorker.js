@@ -137,9 +137,9 @@ const handlers = {
   /**
    * Correlation matrix
    */
-  async correlation({ matrix, rows, cols }) {
+  async correlation({ matrix, rows, cols, method = 'pearson' }) {
     const w = await initWasm();
-    const result = w.correlation_matrix(new Float64Array(matrix), rows, cols);
+    const result = w.correlation_matrix(new Float64Array(matrix), rows, cols, method);
 
     return {
       data: Array.from(result.data()),
@@ -165,9 +165,9 @@ const handlers = {
   /**
    * Z-score normalization
    */
-  async zscore({ matrix, rows, cols }) {
+  async zscore({ matrix, rows, cols, axis = 0 }) {
     const w = await initWasm();
-    const result = w.zscore_normalize(new Float64Array(matrix), rows, cols);
+    const result = w.zscore_normalize(new Float64Array(matrix), rows, cols, axis);
 
     return {
       data: Array.from(result.data()),
@@ -0,0 +1,48 @@
+//! Throughput of the sequence kernels on synthetic DNA
+//!
+//! Run with `cargo bench`. Inputs come from a fixed seed, so every run
+//! counts the same sequence.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use genomic_wasm::{count_kmers_detailed, count_kmers_packed};
+
+const SEED: u64 = 11;
+
+/// Uniform random ACGT from a linear congruential generator
+fn random_dna(len: usize, seed: u64) -> String {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            b"ACGT"[(state >> 62) as usize] as char
+        })
+        .collect()
+}
+
+/// 2-bit packed k-mer counting over 1 Mb, forward and canonical, against
+/// counting k-mers as strings
+fn bench_kmers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("count_kmers");
+    group.sample_size(20);
+
+    let sequence = random_dna(1_000_000, SEED);
+    group.throughput(Throughput::Bytes(sequence.len() as u64));
+    for k in [8, 16] {
+        group.bench_with_input(BenchmarkId::new("packed", k), &k, |b, &k| {
+            b.iter(|| count_kmers_packed(black_box(&sequence), k, false))
+        });
+        group.bench_with_input(BenchmarkId::new("packed_canonical", k), &k, |b, &k| {
+            b.iter(|| count_kmers_packed(black_box(&sequence), k, true))
+        });
+        group.bench_with_input(BenchmarkId::new("strings", k), &k, |b, &k| {
+            b.iter(|| count_kmers_detailed(black_box(&sequence), k))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_kmers);
+criterion_main!(benches);
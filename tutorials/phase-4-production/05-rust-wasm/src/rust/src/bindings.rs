@@ -0,0 +1,205 @@
+//! JavaScript Bindings
+//!
+//! Thin `#[wasm_bindgen]` wrappers over the analysis modules, which are
+//! plain Rust so they build and test natively. Result types are exported
+//! under their original names as newtypes around the native structs.
+//! Only compiled for `wasm32`.
+
+mod association;
+mod hclust;
+mod matrix;
+mod protein;
+mod pwm;
+mod regression;
+mod restriction;
+mod sequence;
+
+use js_sys::Math;
+use vcf_parser::rng::SplitMix64;
+use wasm_bindgen::prelude::*;
+
+use crate::distributions::benjamini_hochberg as bh_adjust;
+use crate::{cluster, fisher};
+
+/// Generator for `seed`, or for a fresh random seed when it is omitted
+fn rng(seed: Option<u32>) -> SplitMix64 {
+    let seed = seed.map_or_else(|| (Math::random() * u64::MAX as f64) as u64, u64::from);
+    SplitMix64::new(seed)
+}
+
+/// Result of K-means clustering
+#[wasm_bindgen(js_name = KMeansResult)]
+pub struct WasmKMeansResult(cluster::KMeansResult);
+
+#[wasm_bindgen(js_class = KMeansResult)]
+impl WasmKMeansResult {
+    /// Get cluster assignments for each point
+    pub fn assignments(&self) -> Vec<u32> {
+        self.0.assignments.clone()
+    }
+
+    /// Get centroid coordinates (flattened: [x1,y1,x2,y2,...])
+    pub fn centroids(&self) -> Vec<f64> {
+        self.0.centroids.clone()
+    }
+
+    /// Number of iterations until convergence
+    pub fn iterations(&self) -> u32 {
+        self.0.iterations
+    }
+
+    /// Whether the algorithm converged
+    pub fn converged(&self) -> bool {
+        self.0.converged
+    }
+
+    /// Within-cluster sum of squares (inertia)
+    pub fn inertia(&self) -> f64 {
+        self.0.inertia
+    }
+}
+
+/// K-means clustering; pass `seed` for reproducible initialization
+#[wasm_bindgen]
+pub fn kmeans(
+    data: &[f64],
+    k: usize,
+    dims: usize,
+    max_iter: u32,
+    seed: Option<u32>,
+) -> WasmKMeansResult {
+    WasmKMeansResult(cluster::kmeans(data, k, dims, max_iter, &mut rng(seed)))
+}
+
+/// K-means with custom tolerance
+#[wasm_bindgen]
+pub fn kmeans_with_tolerance(
+    data: &[f64],
+    k: usize,
+    dims: usize,
+    max_iter: u32,
+    tolerance: f64,
+    seed: Option<u32>,
+) -> WasmKMeansResult {
+    WasmKMeansResult(cluster::kmeans_with_tolerance(
+        data,
+        k,
+        dims,
+        max_iter,
+        tolerance,
+        &mut rng(seed),
+    ))
+}
+
+/// Run k-means multiple times and return best result
+#[wasm_bindgen]
+pub fn kmeans_best(
+    data: &[f64],
+    k: usize,
+    dims: usize,
+    max_iter: u32,
+    n_init: u32,
+    seed: Option<u32>,
+) -> WasmKMeansResult {
+    WasmKMeansResult(cluster::kmeans_best(
+        data,
+        k,
+        dims,
+        max_iter,
+        n_init,
+        &mut rng(seed),
+    ))
+}
+
+/// Elbow method: calculate inertia for different k values
+#[wasm_bindgen]
+pub fn elbow_analysis(
+    data: &[f64],
+    dims: usize,
+    max_k: usize,
+    max_iter: u32,
+    seed: Option<u32>,
+) -> Vec<f64> {
+    cluster::elbow_analysis(data, dims, max_k, max_iter, &mut rng(seed))
+}
+
+/// Silhouette score for clustering quality
+#[wasm_bindgen]
+pub fn silhouette_score(data: &[f64], assignments: &[u32], dims: usize) -> f64 {
+    cluster::silhouette_score(data, assignments, dims)
+}
+
+/// Two-tailed Fisher's exact test for a 2x2 table
+#[wasm_bindgen]
+pub fn fisher_exact(a: u32, b: u32, c: u32, d: u32) -> f64 {
+    fisher::fisher_exact(a, b, c, d)
+}
+
+/// One-tailed Fisher's exact test (less than)
+#[wasm_bindgen]
+pub fn fisher_exact_left(a: u32, b: u32, c: u32, d: u32) -> f64 {
+    fisher::fisher_exact_left(a, b, c, d)
+}
+
+/// One-tailed Fisher's exact test (greater than)
+#[wasm_bindgen]
+pub fn fisher_exact_right(a: u32, b: u32, c: u32, d: u32) -> f64 {
+    fisher::fisher_exact_right(a, b, c, d)
+}
+
+/// Two-tailed Fisher's exact test
+#[wasm_bindgen]
+pub fn fisher_exact_two_tailed(a: u32, b: u32, c: u32, d: u32) -> f64 {
+    fisher::fisher_exact_two_tailed(a, b, c, d)
+}
+
+/// Fisher's exact test for a flat array of [a, b, c, d, ...] tables
+#[wasm_bindgen]
+pub fn fisher_exact_batch(tables: &[u32]) -> Vec<f64> {
+    fisher::fisher_exact_batch(tables)
+}
+
+/// Exact two-sided binomial p-value for a het's REF and ALT read counts
+/// against the expected ALT share (0.5 for a balanced het)
+#[wasm_bindgen]
+pub fn allelic_imbalance_test(ref_count: u32, alt_count: u32, expected_ratio: f64) -> f64 {
+    fisher::allelic_imbalance_test(ref_count, alt_count, expected_ratio)
+}
+
+/// Benjamini-Hochberg adjusted p-values; NaN entries, such as untested
+/// samples, stay NaN and are not counted as tests
+#[wasm_bindgen]
+pub fn benjamini_hochberg(p_values: &[f64]) -> Vec<f64> {
+    bh_adjust(p_values)
+}
+
+/// Odds ratio calculation for 2x2 table
+#[wasm_bindgen]
+pub fn odds_ratio(a: u32, b: u32, c: u32, d: u32) -> f64 {
+    fisher::odds_ratio(a, b, c, d)
+}
+
+/// Odds ratio with confidence interval
+#[wasm_bindgen(js_name = OddsRatioResult)]
+pub struct WasmOddsRatioResult(fisher::OddsRatioResult);
+
+#[wasm_bindgen(js_class = OddsRatioResult)]
+impl WasmOddsRatioResult {
+    pub fn odds_ratio(&self) -> f64 {
+        self.0.odds_ratio
+    }
+    pub fn ci_lower(&self) -> f64 {
+        self.0.ci_lower
+    }
+    pub fn ci_upper(&self) -> f64 {
+        self.0.ci_upper
+    }
+    pub fn p_value(&self) -> f64 {
+        self.0.p_value
+    }
+}
+
+#[wasm_bindgen]
+pub fn odds_ratio_ci(a: u32, b: u32, c: u32, d: u32, confidence: f64) -> WasmOddsRatioResult {
+    WasmOddsRatioResult(fisher::odds_ratio_ci(a, b, c, d, confidence))
+}
@@ -0,0 +1,44 @@
+use js_sys::{Array, Float64Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::association;
+
+/// Per-variant case/control association test over a VCF
+///
+/// `test` is `"fisher"` (allelic 2x2) or `"trend"` (Cochran-Armitage).
+/// Returns an object with `chrom`, `ref` and `alt` arrays of strings and
+/// `pos`, `p`, `fdr` (BH-adjusted) and allelic `or` Float64Arrays, one
+/// entry per record. Fails if a sample name is not in the VCF header.
+#[wasm_bindgen]
+pub fn association_test(
+    content: &str,
+    case_samples: Vec<String>,
+    control_samples: Vec<String>,
+    test: &str,
+) -> Result<JsValue, String> {
+    let results = association::association_test(content, &case_samples, &control_samples, test)?;
+
+    let result = Object::new();
+    let strings = [
+        ("chrom", &results.chrom),
+        ("ref", &results.reference),
+        ("alt", &results.alt),
+    ];
+    for (key, values) in strings {
+        let array: Array = values.iter().map(|v| JsValue::from_str(v)).collect();
+        let _ = Reflect::set(&result, &JsValue::from_str(key), &array);
+    }
+
+    let numbers = [
+        ("pos", &results.pos),
+        ("p", &results.p_values),
+        ("fdr", &results.fdr),
+        ("or", &results.odds_ratios),
+    ];
+    for (key, values) in numbers {
+        let array = Float64Array::from(values.as_slice());
+        let _ = Reflect::set(&result, &JsValue::from_str(key), &array);
+    }
+
+    Ok(result.into())
+}
@@ -0,0 +1,10 @@
+use wasm_bindgen::prelude::*;
+
+use super::matrix::WasmMatrixResult;
+use crate::hclust;
+
+/// Leaf order for heatmap display of a symmetric distance matrix
+#[wasm_bindgen]
+pub fn optimal_leaf_order(dist: &WasmMatrixResult) -> Vec<u32> {
+    hclust::optimal_leaf_order(&dist.0)
+}
@@ -0,0 +1,378 @@
+use js_sys::Float64Array;
+use wasm_bindgen::prelude::*;
+
+use crate::matrix;
+
+/// Matrix multiplication result
+#[wasm_bindgen(js_name = MatrixResult)]
+pub struct WasmMatrixResult(pub(super) matrix::MatrixResult);
+
+#[wasm_bindgen(js_class = MatrixResult)]
+impl WasmMatrixResult {
+    pub fn data(&self) -> Vec<f64> {
+        self.0.data()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.0.rows()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.0.cols()
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.0.get(row, col)
+    }
+
+    /// Number of non-fatal issues encountered (e.g. skipped columns)
+    pub fn warnings(&self) -> u32 {
+        self.0.warnings()
+    }
+
+    /// Move the data out without copying (consumes the result)
+    pub fn take_data(self) -> Vec<f64> {
+        self.0.take_data()
+    }
+
+    /// Lossy f32 export for visualization (half the transfer size)
+    pub fn data_f32(&self) -> Vec<f32> {
+        self.0.data_f32()
+    }
+
+    /// Copy the data once into a typed array (e.g. for WebGL upload)
+    pub fn to_float64_array(&self) -> Float64Array {
+        Float64Array::from(self.0.as_slice())
+    }
+
+    /// Values of a single row; empty if out of bounds
+    pub fn row(&self, i: usize) -> Vec<f64> {
+        self.0.row(i)
+    }
+
+    /// Values of a single column; empty if out of bounds
+    pub fn col(&self, j: usize) -> Vec<f64> {
+        self.0.col(j)
+    }
+
+    /// Rectangular slice [row_start, row_end) x [col_start, col_end)
+    pub fn submatrix(
+        &self,
+        row_start: usize,
+        row_end: usize,
+        col_start: usize,
+        col_end: usize,
+    ) -> WasmMatrixResult {
+        WasmMatrixResult(self.0.submatrix(row_start, row_end, col_start, col_end))
+    }
+}
+
+/// Matrix-matrix multiplication
+/// A (m x k) * B (k x n) = C (m x n)
+#[wasm_bindgen]
+pub fn matmul(a: &[f64], b: &[f64], m: usize, k: usize, n: usize) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::matmul(a, b, m, k, n))
+}
+
+/// Cache-blocked matrix multiplication with explicit tuning
+#[wasm_bindgen]
+pub fn matmul_blocked(
+    a: &[f64],
+    b: &[f64],
+    m: usize,
+    k: usize,
+    n: usize,
+    block_size: usize,
+    transpose_b: bool,
+) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::matmul_blocked(
+        a,
+        b,
+        m,
+        k,
+        n,
+        block_size,
+        transpose_b,
+    ))
+}
+
+/// Single-precision matrix multiplication (half the memory bandwidth)
+#[wasm_bindgen]
+pub fn matmul_f32(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Vec<f32> {
+    matrix::matmul_f32(a, b, m, k, n)
+}
+
+/// Matrix transpose
+#[wasm_bindgen]
+pub fn transpose(matrix: &[f64], rows: usize, cols: usize) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::transpose(matrix, rows, cols))
+}
+
+/// Permute rows and columns in one pass
+#[wasm_bindgen]
+pub fn reorder_matrix(
+    matrix: &[f64],
+    rows: usize,
+    cols: usize,
+    row_order: &[u32],
+    col_order: &[u32],
+) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::reorder_matrix(
+        matrix, rows, cols, row_order, col_order,
+    ))
+}
+
+/// Calculate mean of each row (axis 0) or each column (axis 1)
+#[wasm_bindgen]
+pub fn means(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> Vec<f64> {
+    matrix::means(matrix, rows, cols, axis)
+}
+
+/// Calculate standard deviation of each row (axis 0) or each column (axis 1)
+#[wasm_bindgen]
+pub fn stds(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> Vec<f64> {
+    matrix::stds(matrix, rows, cols, axis)
+}
+
+/// Calculate mean of each row
+#[wasm_bindgen]
+pub fn row_means(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    matrix::row_means(matrix, rows, cols)
+}
+
+/// Calculate mean of each column
+#[wasm_bindgen]
+pub fn col_means(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    matrix::col_means(matrix, rows, cols)
+}
+
+/// Calculate standard deviation of each row
+#[wasm_bindgen]
+pub fn row_stds(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    matrix::row_stds(matrix, rows, cols)
+}
+
+/// Calculate standard deviation of each column
+#[wasm_bindgen]
+pub fn col_stds(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    matrix::col_stds(matrix, rows, cols)
+}
+
+/// Z-score normalize each row (axis 0) or each column (axis 1)
+#[wasm_bindgen]
+pub fn zscore_normalize(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::zscore_normalize(matrix, rows, cols, axis))
+}
+
+/// Robust z-score normalize each row (axis 0) or each column (axis 1)
+#[wasm_bindgen]
+pub fn robust_zscore(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::robust_zscore(matrix, rows, cols, axis))
+}
+
+/// Quantile normalize rows (axis 0) or columns (axis 1) to a common distribution
+#[wasm_bindgen]
+pub fn quantile_normalize(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::quantile_normalize(matrix, rows, cols, axis))
+}
+
+/// Counts-per-million normalization (columns are samples/cells)
+#[wasm_bindgen]
+pub fn cpm_normalize(matrix: &[f64], rows: usize, cols: usize, log1p: bool) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::cpm_normalize(matrix, rows, cols, log1p))
+}
+
+/// Calculate sample variance of each row
+#[wasm_bindgen]
+pub fn row_variances(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    matrix::row_variances(matrix, rows, cols)
+}
+
+/// Most variable rows selected by `top_variable_rows`
+#[wasm_bindgen(js_name = TopRowsResult)]
+pub struct WasmTopRowsResult(matrix::TopRowsResult);
+
+#[wasm_bindgen(js_class = TopRowsResult)]
+impl WasmTopRowsResult {
+    /// Selected row indices, most variable first
+    pub fn indices(&self) -> Vec<u32> {
+        self.0.indices()
+    }
+
+    /// Variance of each selected row
+    pub fn variances(&self) -> Vec<f64> {
+        self.0.variances()
+    }
+
+    /// Submatrix of the selected rows (in `indices` order)
+    pub fn submatrix(&self) -> WasmMatrixResult {
+        WasmMatrixResult(self.0.submatrix())
+    }
+}
+
+/// Select the `n` rows with the highest variance (e.g. top variable genes)
+#[wasm_bindgen]
+pub fn top_variable_rows(matrix: &[f64], rows: usize, cols: usize, n: usize) -> WasmTopRowsResult {
+    WasmTopRowsResult(matrix::top_variable_rows(matrix, rows, cols, n))
+}
+
+/// Calculate correlation between all row pairs
+/// Returns correlation matrix (rows x rows)
+#[wasm_bindgen]
+pub fn correlation_matrix(
+    matrix: &[f64],
+    rows: usize,
+    cols: usize,
+    method: &str,
+) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::correlation_matrix(matrix, rows, cols, method))
+}
+
+/// Pearson correlation between two vectors
+#[wasm_bindgen]
+pub fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
+    matrix::pearson_correlation(x, y)
+}
+
+/// Pearson correlation using pairwise-complete observations
+#[wasm_bindgen]
+pub fn pearson_correlation_pairwise(x: &[f64], y: &[f64]) -> f64 {
+    matrix::pearson_correlation_pairwise(x, y)
+}
+
+/// Pairwise-complete matrix statistic with per-cell observation counts
+#[wasm_bindgen(js_name = PairwiseResult)]
+pub struct WasmPairwiseResult(matrix::PairwiseResult);
+
+#[wasm_bindgen(js_class = PairwiseResult)]
+impl WasmPairwiseResult {
+    /// Statistic for each row pair (rows x rows)
+    pub fn values(&self) -> WasmMatrixResult {
+        WasmMatrixResult(self.0.values())
+    }
+
+    /// Number of complete observations behind each cell (rows x rows)
+    pub fn counts(&self) -> WasmMatrixResult {
+        WasmMatrixResult(self.0.counts())
+    }
+}
+
+/// Pearson correlation between all row pairs using pairwise-complete observations
+#[wasm_bindgen]
+pub fn correlation_matrix_pairwise(matrix: &[f64], rows: usize, cols: usize) -> WasmPairwiseResult {
+    WasmPairwiseResult(matrix::correlation_matrix_pairwise(matrix, rows, cols))
+}
+
+/// Covariance between all row pairs using pairwise-complete observations
+#[wasm_bindgen]
+pub fn covariance_matrix_pairwise(matrix: &[f64], rows: usize, cols: usize) -> WasmPairwiseResult {
+    WasmPairwiseResult(matrix::covariance_matrix_pairwise(matrix, rows, cols))
+}
+
+/// Partial correlation of x and y controlling for covariates
+#[wasm_bindgen]
+pub fn partial_correlation(
+    x: &[f64],
+    y: &[f64],
+    z: &[f64],
+    n_covariates: usize,
+) -> Result<f64, String> {
+    matrix::partial_correlation(x, y, z, n_covariates)
+}
+
+/// Partial correlation between all row pairs controlling for covariates
+#[wasm_bindgen]
+pub fn partial_correlation_matrix(
+    matrix: &[f64],
+    rows: usize,
+    cols: usize,
+    covariates: &[f64],
+    n_cov: usize,
+) -> Result<WasmMatrixResult, String> {
+    matrix::partial_correlation_matrix(matrix, rows, cols, covariates, n_cov).map(WasmMatrixResult)
+}
+
+/// Spearman correlation (rank-based)
+#[wasm_bindgen]
+pub fn spearman_correlation(x: &[f64], y: &[f64]) -> f64 {
+    matrix::spearman_correlation(x, y)
+}
+
+/// Kendall's tau-b rank correlation
+#[wasm_bindgen]
+pub fn kendall_tau(x: &[f64], y: &[f64]) -> f64 {
+    matrix::kendall_tau(x, y)
+}
+
+/// Test statistic with its p-value
+#[wasm_bindgen(js_name = TestResult)]
+pub struct WasmTestResult(matrix::TestResult);
+
+#[wasm_bindgen(js_class = TestResult)]
+impl WasmTestResult {
+    pub fn statistic(&self) -> f64 {
+        self.0.statistic()
+    }
+
+    pub fn p_value(&self) -> f64 {
+        self.0.p_value()
+    }
+}
+
+/// Kendall's tau-b with a two-tailed p-value
+#[wasm_bindgen]
+pub fn kendall_tau_test(x: &[f64], y: &[f64]) -> WasmTestResult {
+    WasmTestResult(matrix::kendall_tau_test(x, y))
+}
+
+/// Calculate covariance matrix
+#[wasm_bindgen]
+pub fn covariance_matrix(matrix: &[f64], rows: usize, cols: usize) -> WasmMatrixResult {
+    WasmMatrixResult(matrix::covariance_matrix(matrix, rows, cols))
+}
+
+/// Element-wise matrix addition
+#[wasm_bindgen]
+pub fn matrix_add(a: &[f64], b: &[f64]) -> Vec<f64> {
+    matrix::matrix_add(a, b)
+}
+
+/// Scalar multiplication
+#[wasm_bindgen]
+pub fn matrix_scale(matrix: &[f64], scalar: f64) -> Vec<f64> {
+    matrix::matrix_scale(matrix, scalar)
+}
+
+/// Sum of all elements
+#[wasm_bindgen]
+pub fn matrix_sum(matrix: &[f64]) -> f64 {
+    matrix::matrix_sum(matrix)
+}
+
+/// Find min and max values
+#[wasm_bindgen(js_name = MinMax)]
+pub struct WasmMinMax(matrix::MinMax);
+
+#[wasm_bindgen(js_class = MinMax)]
+impl WasmMinMax {
+    pub fn min(&self) -> f64 {
+        self.0.min()
+    }
+
+    pub fn max(&self) -> f64 {
+        self.0.max()
+    }
+
+    pub fn min_index(&self) -> usize {
+        self.0.min_index()
+    }
+
+    pub fn max_index(&self) -> usize {
+        self.0.max_index()
+    }
+}
+
+#[wasm_bindgen]
+pub fn matrix_minmax(matrix: &[f64]) -> WasmMinMax {
+    WasmMinMax(matrix::matrix_minmax(matrix))
+}
@@ -0,0 +1,64 @@
+use wasm_bindgen::prelude::*;
+
+use crate::protein;
+
+/// Summary of a protein sequence
+#[wasm_bindgen(js_name = ProteinResult)]
+pub struct WasmProteinResult(protein::ProteinResult);
+
+#[wasm_bindgen(js_class = ProteinResult)]
+impl WasmProteinResult {
+    /// Number of standard residues
+    pub fn length(&self) -> usize {
+        self.0.length()
+    }
+
+    /// Molecular weight in Da
+    pub fn molecular_weight(&self) -> f64 {
+        self.0.molecular_weight()
+    }
+
+    pub fn isoelectric_point(&self) -> f64 {
+        self.0.isoelectric_point()
+    }
+
+    /// Grand average of hydropathy (mean Kyte-Doolittle score)
+    pub fn gravy(&self) -> f64 {
+        self.0.gravy()
+    }
+
+    /// The 20 standard residues, alphabetically by one-letter code
+    pub fn residues(&self) -> Vec<String> {
+        self.0.residues()
+    }
+
+    /// Fraction of the standard residues for each entry of `residues()`
+    pub fn fractions(&self) -> Vec<f64> {
+        self.0.fractions()
+    }
+
+    /// Residue counts aligned with `residues()`
+    pub fn counts(&self) -> Vec<u32> {
+        self.0.counts()
+    }
+
+    pub fn stop_count(&self) -> u32 {
+        self.0.stop_count()
+    }
+
+    pub fn unknown_count(&self) -> u32 {
+        self.0.unknown_count()
+    }
+}
+
+/// Molecular weight, pI, GRAVY and composition of a protein
+#[wasm_bindgen]
+pub fn protein_properties(protein: &str, monoisotopic: bool) -> WasmProteinResult {
+    WasmProteinResult(protein::protein_properties(protein, monoisotopic))
+}
+
+/// Kyte-Doolittle hydropathy in sliding windows (step 1)
+#[wasm_bindgen]
+pub fn hydrophobicity_profile(protein: &str, window: usize) -> Vec<f64> {
+    protein::hydrophobicity_profile(protein, window)
+}
@@ -0,0 +1,151 @@
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::pwm::{self, Pwm, UNIFORM_BACKGROUND};
+
+/// Position weight matrix over A, C, G, T
+#[wasm_bindgen(js_name = Pwm)]
+pub struct WasmPwm(pwm::Pwm);
+
+#[wasm_bindgen(js_class = Pwm)]
+impl WasmPwm {
+    /// Motif width in columns
+    pub fn length(&self) -> usize {
+        self.0.length()
+    }
+
+    /// Best achievable score (consensus sequence)
+    pub fn max_score(&self) -> f64 {
+        self.0.max_score()
+    }
+
+    /// Worst achievable score
+    pub fn min_score(&self) -> f64 {
+        self.0.min_score()
+    }
+
+    /// Absolute threshold for a fraction of the score range (0 = min, 1 = max)
+    pub fn threshold_for_fraction(&self, fraction: f64) -> f64 {
+        self.0.threshold_for_fraction(fraction)
+    }
+
+    /// Most likely base per column
+    pub fn consensus(&self) -> String {
+        self.0.consensus()
+    }
+
+    /// Scan a sequence (see `Pwm::scan`)
+    pub fn find_hits(&self, sequence: &str, threshold: f64, both_strands: bool) -> WasmPwmHits {
+        WasmPwmHits(self.0.find_hits(sequence, threshold, both_strands))
+    }
+
+    /// `{ length, pseudocount, background, counts, probabilities, scores }`
+    ///
+    /// Matrices are arrays of `[A, C, G, T]` rows, one per column of the motif,
+    /// ready for drawing a sequence logo.
+    pub fn to_js(&self) -> JsValue {
+        let rows = |matrix: &[[f64; 4]]| {
+            matrix
+                .iter()
+                .map(|row| row.iter().map(|&v| JsValue::from_f64(v)).collect::<Array>())
+                .collect::<Array>()
+        };
+        let pwm = &self.0;
+        let background: Array = pwm
+            .background()
+            .iter()
+            .map(|&v| JsValue::from_f64(v))
+            .collect();
+
+        let result = Object::new();
+        let set = |key: &str, value: &JsValue| {
+            let _ = Reflect::set(&result, &JsValue::from_str(key), value);
+        };
+        set("length", &JsValue::from_f64(pwm.length() as f64));
+        set("pseudocount", &JsValue::from_f64(pwm.pseudocount()));
+        set("background", &background);
+        set("counts", &rows(pwm.counts()));
+        set("probabilities", &rows(&pwm.probabilities()));
+        set("scores", &rows(pwm.scores()));
+        result.into()
+    }
+
+    /// Rebuild from the `counts` matrix (and optional `pseudocount` and
+    /// `background`) of an object produced by `to_js`
+    pub fn from_js(value: &JsValue) -> Result<WasmPwm, String> {
+        let field =
+            |key: &str| Reflect::get(value, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED);
+        let four = |row: &JsValue| -> Result<[f64; 4], String> {
+            let row = Array::from(row);
+            if row.length() != 4 {
+                return Err("Each PWM row needs 4 values (A, C, G, T)".to_string());
+            }
+            let mut values = [0.0; 4];
+            for (b, v) in values.iter_mut().enumerate() {
+                *v = row
+                    .get(b as u32)
+                    .as_f64()
+                    .ok_or("PWM values must be numbers")?;
+            }
+            Ok(values)
+        };
+
+        let counts = field("counts");
+        if !Array::is_array(&counts) {
+            return Err("Expected a `counts` array".to_string());
+        }
+        let counts = Array::from(&counts)
+            .iter()
+            .map(|row| four(&row))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let pseudocount = field("pseudocount").as_f64().unwrap_or(1.0);
+        let background = match field("background") {
+            b if b.is_undefined() => UNIFORM_BACKGROUND,
+            b => four(&b)?,
+        };
+
+        Pwm::from_counts(counts, pseudocount, background).map(WasmPwm)
+    }
+}
+
+/// PWM hits as parallel arrays
+#[wasm_bindgen(js_name = PwmHits)]
+pub struct WasmPwmHits(pwm::PwmHits);
+
+#[wasm_bindgen(js_class = PwmHits)]
+impl WasmPwmHits {
+    pub fn count(&self) -> usize {
+        self.0.count()
+    }
+
+    /// 0-based start positions on the forward strand
+    pub fn positions(&self) -> Vec<u32> {
+        self.0.positions()
+    }
+
+    /// 1 for the forward strand, -1 for the reverse strand
+    pub fn strands(&self) -> Vec<i8> {
+        self.0.strands()
+    }
+
+    pub fn scores(&self) -> Vec<f64> {
+        self.0.scores()
+    }
+}
+
+/// Build a PWM from equal-length aligned sites against a uniform background
+#[wasm_bindgen]
+pub fn build_pwm(sequences: Vec<String>, pseudocount: f64) -> Result<WasmPwm, String> {
+    pwm::build_pwm(sequences, pseudocount).map(WasmPwm)
+}
+
+/// `build_pwm` with background frequencies for A, C, G, T
+#[wasm_bindgen]
+pub fn build_pwm_with_background(
+    sequences: Vec<String>,
+    pseudocount: f64,
+    background: Vec<f64>,
+) -> Result<WasmPwm, String> {
+    pwm::build_pwm_with_background(sequences, pseudocount, background).map(WasmPwm)
+}
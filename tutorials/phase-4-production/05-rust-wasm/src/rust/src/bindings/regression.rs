@@ -0,0 +1,66 @@
+use js_sys::{Float64Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+use crate::regression;
+
+/// Result of a simple linear regression (y = intercept + slope * x)
+#[wasm_bindgen(js_name = RegressionResult)]
+pub struct WasmRegressionResult(regression::RegressionResult);
+
+#[wasm_bindgen(js_class = RegressionResult)]
+impl WasmRegressionResult {
+    /// Fitted slope
+    pub fn slope(&self) -> f64 {
+        self.0.slope()
+    }
+
+    /// Fitted intercept
+    pub fn intercept(&self) -> f64 {
+        self.0.intercept()
+    }
+
+    /// Coefficient of determination
+    pub fn r_squared(&self) -> f64 {
+        self.0.r_squared()
+    }
+
+    /// Two-tailed p-value for slope != 0 (t-distribution, n - 2 df)
+    pub fn p_value(&self) -> f64 {
+        self.0.p_value()
+    }
+
+    /// Standard error of the slope
+    pub fn std_err(&self) -> f64 {
+        self.0.std_err()
+    }
+}
+
+/// Ordinary least squares fit of y on x
+#[wasm_bindgen]
+pub fn linear_regression(x: &[f64], y: &[f64]) -> WasmRegressionResult {
+    WasmRegressionResult(regression::linear_regression(x, y))
+}
+
+/// Fit one regression per row (genes x samples) against a shared covariate
+///
+/// Returns an object with `slopes`, `intercepts`, `r_squared` and
+/// BH-adjusted `p_values` as Float64Arrays of length `rows`.
+#[wasm_bindgen]
+pub fn linear_regression_batch(matrix: &[f64], rows: usize, cols: usize, x: &[f64]) -> JsValue {
+    let batch = regression::linear_regression_batch(matrix, rows, cols, x);
+
+    let result = Object::new();
+    let fields = [
+        ("slopes", &batch.slopes),
+        ("intercepts", &batch.intercepts),
+        ("r_squared", &batch.r_squared),
+        ("p_values", &batch.p_values),
+    ];
+
+    for (key, values) in fields {
+        let array = Float64Array::from(values.as_slice());
+        let _ = Reflect::set(&result, &JsValue::from_str(key), &array);
+    }
+
+    result.into()
+}
@@ -0,0 +1,101 @@
+use js_sys::{Array, Object, Reflect, Uint32Array};
+use wasm_bindgen::prelude::*;
+
+use crate::restriction::{self, Enzyme};
+
+/// Read the `enzymes` argument of `restriction_sites`
+fn enzymes_from_js(enzymes: &JsValue) -> Result<Vec<Enzyme>, String> {
+    if Array::is_array(enzymes) {
+        return Array::from(enzymes)
+            .iter()
+            .map(|name| {
+                let name = name.as_string().ok_or("Enzyme names must be strings")?;
+                Enzyme::builtin(&name)
+            })
+            .collect();
+    }
+
+    if !enzymes.is_object() {
+        return Err("Expected an array of enzyme names or an object of enzymes".to_string());
+    }
+
+    Object::entries(&Object::from(enzymes.clone()))
+        .iter()
+        .map(|entry| {
+            let entry = Array::from(&entry);
+            let name = entry.get(0).as_string().unwrap_or_default();
+            let spec = entry.get(1);
+
+            if !spec.is_object() {
+                return Enzyme::builtin(&name);
+            }
+
+            let field = |key: &str| {
+                Reflect::get(&spec, &JsValue::from_str(key)).unwrap_or(JsValue::UNDEFINED)
+            };
+            let pattern = field("pattern")
+                .as_string()
+                .ok_or_else(|| format!("Enzyme {} needs a `pattern` string", name))?;
+            let cut = field("cut")
+                .as_f64()
+                .ok_or_else(|| format!("Enzyme {} needs a numeric `cut` offset", name))?;
+            let cut_complement = field("cut_complement").as_f64().map(|c| c as i64);
+
+            Ok(Enzyme::new(&name, &pattern, cut as i64, cut_complement))
+        })
+        .collect()
+}
+
+/// Restriction map of a sequence
+///
+/// `enzymes` is either an array of built-in enzyme names (e.g.
+/// `["EcoRI", "BamHI"]`) or an object mapping names to
+/// `{ pattern, cut, cut_complement? }` (IUPAC patterns allowed; cut
+/// offsets are counted from the start of the site and `cut_complement`
+/// defaults to the palindromic position). An object value that is not an
+/// object selects the built-in enzyme of that name.
+///
+/// # Returns
+/// Array of `{ name, pattern, sites, cuts_top, cuts_bottom, fragments }`
+/// with Uint32Array fields; sites are 0-based, cuts count the bases to
+/// the left of the cut.
+#[wasm_bindgen]
+pub fn restriction_sites(
+    sequence: &str,
+    enzymes: JsValue,
+    circular: bool,
+) -> Result<JsValue, String> {
+    let enzymes = enzymes_from_js(&enzymes)?;
+    let digests = restriction::restriction_sites(sequence, &enzymes, circular)?;
+    let result = Array::new();
+
+    for (enzyme, digest) in enzymes.iter().zip(&digests) {
+        let entry = Object::new();
+
+        let _ = Reflect::set(
+            &entry,
+            &JsValue::from_str("name"),
+            &JsValue::from_str(&enzyme.name),
+        );
+        let _ = Reflect::set(
+            &entry,
+            &JsValue::from_str("pattern"),
+            &JsValue::from_str(&enzyme.pattern),
+        );
+
+        let fields = [
+            ("sites", &digest.sites),
+            ("cuts_top", &digest.cuts_top),
+            ("cuts_bottom", &digest.cuts_bottom),
+            ("fragments", &digest.fragments),
+        ];
+        for (key, values) in fields {
+            let array = Uint32Array::from(values.as_slice());
+            let _ = Reflect::set(&entry, &JsValue::from_str(key), &array);
+        }
+
+        result.push(&entry);
+    }
+
+    Ok(result.into())
+}
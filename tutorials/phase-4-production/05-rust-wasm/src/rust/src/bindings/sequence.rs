@@ -0,0 +1,937 @@
+use wasm_bindgen::prelude::*;
+
+use super::matrix::WasmMatrixResult;
+use crate::sequence;
+
+/// Calculate GC content of a DNA sequence
+#[wasm_bindgen]
+pub fn gc_content(sequence: &str) -> f64 {
+    sequence::gc_content(sequence)
+}
+
+/// Calculate GC content in sliding windows
+#[wasm_bindgen]
+pub fn gc_content_windows(sequence: &str, window_size: usize, step: usize) -> Vec<f64> {
+    sequence::gc_content_windows(sequence, window_size, step)
+}
+
+/// Per-window values with their 0-based, half-open coordinates
+#[wasm_bindgen(js_name = WindowedValues)]
+pub struct WasmWindowedValues(sequence::WindowedValues);
+
+#[wasm_bindgen(js_class = WindowedValues)]
+impl WasmWindowedValues {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn starts(&self) -> Vec<u32> {
+        self.0.starts()
+    }
+
+    /// Exclusive window ends; the last may be short of `start + window_size`
+    pub fn ends(&self) -> Vec<u32> {
+        self.0.ends()
+    }
+
+    pub fn values(&self) -> Vec<f64> {
+        self.0.values()
+    }
+}
+
+/// Calculate GC content in sliding windows, with window coordinates
+#[wasm_bindgen]
+pub fn gc_content_windows_positions(
+    sequence: &str,
+    window_size: usize,
+    step: usize,
+) -> WasmWindowedValues {
+    WasmWindowedValues(sequence::gc_content_windows_positions(
+        sequence,
+        window_size,
+        step,
+    ))
+}
+
+/// Calculate GC skew (G - C) / (G + C) in sliding windows
+#[wasm_bindgen]
+pub fn gc_skew_windows(sequence: &str, window: usize, step: usize) -> Vec<f64> {
+    sequence::gc_skew_windows(sequence, window, step)
+}
+
+/// Cumulative GC skew over consecutive non-overlapping windows
+#[wasm_bindgen]
+pub fn cumulative_gc_skew(sequence: &str, window: usize) -> Vec<f64> {
+    sequence::cumulative_gc_skew(sequence, window)
+}
+
+/// Cumulative GC skew with the positions of its extremes
+#[wasm_bindgen(js_name = SkewResult)]
+pub struct WasmSkewResult(sequence::SkewResult);
+
+#[wasm_bindgen(js_class = SkewResult)]
+impl WasmSkewResult {
+    pub fn cumulative(&self) -> Vec<f64> {
+        self.0.cumulative()
+    }
+
+    /// Index of the window where cumulative skew is lowest
+    pub fn min_index(&self) -> usize {
+        self.0.min_index()
+    }
+
+    /// Index of the window where cumulative skew is highest
+    pub fn max_index(&self) -> usize {
+        self.0.max_index()
+    }
+
+    /// Predicted origin: 0-based position just past the minimum window
+    pub fn origin(&self) -> usize {
+        self.0.origin()
+    }
+
+    /// Predicted terminus: 0-based position just past the maximum window
+    pub fn terminus(&self) -> usize {
+        self.0.terminus()
+    }
+}
+
+/// Cumulative GC skew and its argmin / argmax (see `cumulative_gc_skew`)
+#[wasm_bindgen]
+pub fn gc_skew_analysis(sequence: &str, window: usize) -> WasmSkewResult {
+    WasmSkewResult(sequence::gc_skew_analysis(sequence, window))
+}
+
+/// CpG islands as parallel arrays
+#[wasm_bindgen(js_name = CpgIslandResult)]
+pub struct WasmCpgIslandResult(sequence::CpgIslandResult);
+
+#[wasm_bindgen(js_class = CpgIslandResult)]
+impl WasmCpgIslandResult {
+    pub fn count(&self) -> usize {
+        self.0.count()
+    }
+
+    /// 1-based start positions
+    pub fn starts(&self) -> Vec<u32> {
+        self.0.starts()
+    }
+
+    /// 1-based inclusive end positions
+    pub fn ends(&self) -> Vec<u32> {
+        self.0.ends()
+    }
+
+    pub fn lengths(&self) -> Vec<u32> {
+        self.0.lengths()
+    }
+
+    /// GC fraction of each island
+    pub fn gc_content(&self) -> Vec<f64> {
+        self.0.gc_content()
+    }
+
+    pub fn oe_ratios(&self) -> Vec<f64> {
+        self.0.oe_ratios()
+    }
+}
+
+/// Detect CpG islands (see `find_cpg_islands`)
+#[wasm_bindgen]
+pub fn cpg_islands(
+    sequence: &str,
+    min_length: usize,
+    min_gc: f64,
+    min_oe_ratio: f64,
+) -> WasmCpgIslandResult {
+    WasmCpgIslandResult(sequence::cpg_islands(
+        sequence,
+        min_length,
+        min_gc,
+        min_oe_ratio,
+    ))
+}
+
+/// Count k-mers in a sequence
+#[wasm_bindgen]
+pub fn count_kmers(sequence: &str, k: usize) -> String {
+    sequence::count_kmers(sequence, k)
+}
+
+/// K-mer result structure for more complex queries
+#[wasm_bindgen(js_name = KmerResult)]
+pub struct WasmKmerResult(sequence::KmerResult);
+
+#[wasm_bindgen(js_class = KmerResult)]
+impl WasmKmerResult {
+    pub fn kmers(&self) -> Vec<String> {
+        self.0.kmers()
+    }
+
+    pub fn counts(&self) -> Vec<u32> {
+        self.0.counts()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.0.total()
+    }
+
+    pub fn unique_count(&self) -> usize {
+        self.0.unique_count()
+    }
+}
+
+#[wasm_bindgen]
+pub fn count_kmers_detailed(sequence: &str, k: usize) -> WasmKmerResult {
+    WasmKmerResult(sequence::count_kmers_detailed(sequence, k))
+}
+
+/// Count canonical k-mers (k <= 32) using a 2-bit packed encoding
+#[wasm_bindgen]
+pub fn count_kmers_canonical(sequence: &str, k: usize) -> WasmKmerResult {
+    WasmKmerResult(sequence::count_kmers_canonical(sequence, k))
+}
+
+/// Packed k-mer counter (k <= 32) with optional canonical counting
+#[wasm_bindgen]
+pub fn count_kmers_packed(sequence: &str, k: usize, canonical: bool) -> WasmKmerResult {
+    WasmKmerResult(sequence::count_kmers_packed(sequence, k, canonical))
+}
+
+/// Normalized canonical k-mer spectrum of a sequence
+#[wasm_bindgen(js_name = KmerProfile)]
+pub struct WasmKmerProfile(sequence::KmerProfile);
+
+#[wasm_bindgen(js_class = KmerProfile)]
+impl WasmKmerProfile {
+    pub fn k(&self) -> usize {
+        self.0.k()
+    }
+
+    /// Number of k-mers counted
+    pub fn total(&self) -> u32 {
+        self.0.total()
+    }
+
+    /// Number of distinct canonical k-mers
+    pub fn distinct(&self) -> usize {
+        self.0.distinct()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Canonical k-mer frequency profile (k <= 32)
+#[wasm_bindgen]
+pub fn kmer_profile(sequence: &str, k: usize) -> WasmKmerProfile {
+    WasmKmerProfile(sequence::kmer_profile(sequence, k))
+}
+
+/// Distance between two k-mer profiles
+#[wasm_bindgen]
+pub fn kmer_distance(a: &WasmKmerProfile, b: &WasmKmerProfile, metric: &str) -> f64 {
+    sequence::kmer_distance(&a.0, &b.0, metric)
+}
+
+/// One-shot k-mer distance between two sequences (see `kmer_distance`)
+#[wasm_bindgen]
+pub fn compare_sequences_kmer(seq1: &str, seq2: &str, k: usize, metric: &str) -> f64 {
+    sequence::compare_sequences_kmer(seq1, seq2, k, metric)
+}
+
+/// Reverse complement of DNA sequence
+#[wasm_bindgen]
+pub fn reverse_complement(sequence: &str) -> String {
+    sequence::reverse_complement(sequence)
+}
+
+/// Transcribe DNA to RNA
+#[wasm_bindgen]
+pub fn transcribe(sequence: &str) -> String {
+    sequence::transcribe(sequence)
+}
+
+/// Translate RNA (or DNA) to protein (single frame, standard code)
+#[wasm_bindgen]
+pub fn translate(sequence: &str) -> String {
+    sequence::translate(sequence)
+}
+
+/// Translate one reading frame with an NCBI genetic code
+#[wasm_bindgen]
+pub fn translate_frame(sequence: &str, frame: i8, genetic_code: u8) -> Result<String, String> {
+    sequence::translate_frame(sequence, frame, genetic_code)
+}
+
+/// Translations of all six frames, in the order +1, +2, +3, -1, -2, -3
+#[wasm_bindgen]
+pub fn translate_six_frames(sequence: &str, genetic_code: u8) -> Result<Vec<String>, String> {
+    sequence::translate_six_frames(sequence, genetic_code)
+}
+
+/// Open reading frames found by `find_orfs`
+#[wasm_bindgen(js_name = OrfResult)]
+pub struct WasmOrfResult(sequence::OrfResult);
+
+#[wasm_bindgen(js_class = OrfResult)]
+impl WasmOrfResult {
+    pub fn count(&self) -> usize {
+        self.0.count()
+    }
+
+    pub fn frames(&self) -> Vec<i8> {
+        self.0.frames()
+    }
+
+    pub fn starts(&self) -> Vec<u32> {
+        self.0.starts()
+    }
+
+    pub fn ends(&self) -> Vec<u32> {
+        self.0.ends()
+    }
+
+    pub fn lengths(&self) -> Vec<u32> {
+        self.0.lengths()
+    }
+
+    /// Translated proteins (without the terminal '*')
+    pub fn proteins(&self) -> Vec<String> {
+        self.0.proteins()
+    }
+}
+
+/// Find ATG-initiated open reading frames in all six frames
+#[wasm_bindgen]
+pub fn find_orfs(sequence: &str, min_length_aa: usize, require_stop: bool) -> WasmOrfResult {
+    WasmOrfResult(sequence::find_orfs(sequence, min_length_aa, require_stop))
+}
+
+/// `find_orfs` with control over reporting nested ORFs
+#[wasm_bindgen]
+pub fn find_orfs_with_options(
+    sequence: &str,
+    min_length_aa: usize,
+    require_stop: bool,
+    include_nested: bool,
+) -> WasmOrfResult {
+    WasmOrfResult(sequence::find_orfs_with_options(
+        sequence,
+        min_length_aa,
+        require_stop,
+        include_nested,
+    ))
+}
+
+/// Pairwise alignment with traceback
+#[wasm_bindgen(js_name = AlignmentResult)]
+pub struct WasmAlignmentResult(sequence::AlignmentResult);
+
+#[wasm_bindgen(js_class = AlignmentResult)]
+impl WasmAlignmentResult {
+    pub fn score(&self) -> i32 {
+        self.0.score()
+    }
+
+    pub fn start1(&self) -> u32 {
+        self.0.start1()
+    }
+
+    pub fn end1(&self) -> u32 {
+        self.0.end1()
+    }
+
+    pub fn start2(&self) -> u32 {
+        self.0.start2()
+    }
+
+    pub fn end2(&self) -> u32 {
+        self.0.end2()
+    }
+
+    /// seq1 with `-` at gap positions
+    pub fn aligned1(&self) -> String {
+        self.0.aligned1()
+    }
+
+    /// seq2 with `-` at gap positions
+    pub fn aligned2(&self) -> String {
+        self.0.aligned2()
+    }
+
+    pub fn cigar(&self) -> String {
+        self.0.cigar()
+    }
+
+    /// Percent identity over the alignment length
+    pub fn identity(&self) -> f64 {
+        self.0.identity()
+    }
+
+    /// Why the alignment was not computed (e.g. too large), if it failed
+    pub fn error(&self) -> Option<String> {
+        self.0.error()
+    }
+}
+
+/// Smith-Waterman local alignment with affine gaps and traceback
+#[wasm_bindgen]
+pub fn local_align(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> WasmAlignmentResult {
+    WasmAlignmentResult(sequence::local_align(
+        seq1,
+        seq2,
+        match_score,
+        mismatch,
+        gap_open,
+        gap_extend,
+    ))
+}
+
+/// `local_align` with a custom limit on the traceback matrix size (cells = bytes)
+#[wasm_bindgen]
+pub fn local_align_with_limit(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    max_cells: usize,
+) -> WasmAlignmentResult {
+    WasmAlignmentResult(sequence::local_align_with_limit(
+        seq1,
+        seq2,
+        match_score,
+        mismatch,
+        gap_open,
+        gap_extend,
+        max_cells,
+    ))
+}
+
+/// Needleman-Wunsch global alignment with affine gaps and traceback
+#[wasm_bindgen]
+pub fn global_align(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> WasmAlignmentResult {
+    WasmAlignmentResult(sequence::global_align(
+        seq1,
+        seq2,
+        match_score,
+        mismatch,
+        gap_open,
+        gap_extend,
+    ))
+}
+
+/// `global_align` with a custom limit on the traceback matrix size (cells = bytes)
+#[wasm_bindgen]
+pub fn global_align_with_limit(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    max_cells: usize,
+) -> WasmAlignmentResult {
+    WasmAlignmentResult(sequence::global_align_with_limit(
+        seq1,
+        seq2,
+        match_score,
+        mismatch,
+        gap_open,
+        gap_extend,
+        max_cells,
+    ))
+}
+
+/// `global_align`, optionally leaving end gaps unscored
+#[wasm_bindgen]
+pub fn global_align_with_options(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    penalize_end_gaps: bool,
+) -> WasmAlignmentResult {
+    WasmAlignmentResult(sequence::global_align_with_options(
+        seq1,
+        seq2,
+        match_score,
+        mismatch,
+        gap_open,
+        gap_extend,
+        penalize_end_gaps,
+    ))
+}
+
+/// Simple Needleman-Wunsch alignment score
+/// Returns alignment score (not full traceback) with a linear gap penalty
+#[wasm_bindgen]
+pub fn alignment_score(seq1: &str, seq2: &str, match_score: i32, mismatch: i32, gap: i32) -> i32 {
+    sequence::alignment_score(seq1, seq2, match_score, mismatch, gap)
+}
+
+/// Melting temperature of an oligo in °C
+#[wasm_bindgen]
+pub fn melting_temperature(
+    sequence: &str,
+    method: &str,
+    na_conc_mm: f64,
+    oligo_conc_nm: f64,
+) -> f64 {
+    sequence::melting_temperature(sequence, method, na_conc_mm, oligo_conc_nm)
+}
+
+/// Primer / oligo summary for design panels
+#[wasm_bindgen(js_name = OligoResult)]
+pub struct WasmOligoResult(sequence::OligoResult);
+
+#[wasm_bindgen(js_class = OligoResult)]
+impl WasmOligoResult {
+    pub fn length(&self) -> usize {
+        self.0.length()
+    }
+
+    /// GC fraction of the unambiguous bases
+    pub fn gc(&self) -> f64 {
+        self.0.gc()
+    }
+
+    pub fn tm_wallace(&self) -> f64 {
+        self.0.tm_wallace()
+    }
+
+    pub fn tm_nn(&self) -> f64 {
+        self.0.tm_nn()
+    }
+
+    pub fn molecular_weight(&self) -> f64 {
+        self.0.molecular_weight()
+    }
+
+    /// Self-complementary stem of about five or more pairs with a loop of
+    /// three or more bases; always false above 200 nt
+    pub fn has_hairpin_risk(&self) -> bool {
+        self.0.has_hairpin_risk()
+    }
+
+    pub fn has_ambiguous(&self) -> bool {
+        self.0.has_ambiguous()
+    }
+}
+
+/// Length, GC, melting temperatures, weight and hairpin risk of an oligo
+#[wasm_bindgen]
+pub fn oligo_properties(sequence: &str) -> WasmOligoResult {
+    WasmOligoResult(sequence::oligo_properties(sequence))
+}
+
+/// Find all occurrences of pattern in text (case-insensitive)
+#[wasm_bindgen]
+pub fn find_pattern(text: &str, pattern: &str) -> Vec<u32> {
+    sequence::find_pattern(text, pattern)
+}
+
+/// Motif hits with the strand they were found on
+#[wasm_bindgen(js_name = MotifHits)]
+pub struct WasmMotifHits(sequence::MotifHits);
+
+#[wasm_bindgen(js_class = MotifHits)]
+impl WasmMotifHits {
+    pub fn count(&self) -> usize {
+        self.0.count()
+    }
+
+    pub fn positions(&self) -> Vec<u32> {
+        self.0.positions()
+    }
+
+    pub fn strands(&self) -> Vec<i8> {
+        self.0.strands()
+    }
+}
+
+/// Find an IUPAC degenerate pattern (e.g. `GAANNNNTTC`) on the forward strand
+#[wasm_bindgen]
+pub fn find_pattern_iupac(text: &str, pattern: &str) -> Vec<u32> {
+    sequence::find_pattern_iupac(text, pattern)
+}
+
+/// Find an IUPAC pattern, optionally on both strands and with ambiguous text
+#[wasm_bindgen]
+pub fn find_motif(
+    text: &str,
+    pattern: &str,
+    both_strands: bool,
+    text_ambiguity: bool,
+) -> WasmMotifHits {
+    WasmMotifHits(sequence::find_motif(
+        text,
+        pattern,
+        both_strands,
+        text_ambiguity,
+    ))
+}
+
+/// Per-position base fractions of equal-length aligned sequences
+#[wasm_bindgen]
+pub fn base_frequency_matrix(sequences: Vec<String>) -> WasmMatrixResult {
+    WasmMatrixResult(sequence::base_frequency_matrix(sequences))
+}
+
+/// `base_frequency_matrix`, optionally with a sixth column for gaps
+#[wasm_bindgen]
+pub fn base_frequency_matrix_with_gaps(
+    sequences: Vec<String>,
+    count_gaps: bool,
+) -> WasmMatrixResult {
+    WasmMatrixResult(sequence::base_frequency_matrix_with_gaps(
+        sequences, count_gaps,
+    ))
+}
+
+/// Consensus of equal-length aligned sequences
+#[wasm_bindgen]
+pub fn consensus_sequence(sequences: Vec<String>, min_fraction: f64) -> String {
+    sequence::consensus_sequence(sequences, min_fraction)
+}
+
+/// Hamming distance between two equal-length sequences (case-insensitive)
+#[wasm_bindgen]
+pub fn hamming_distance(seq1: &str, seq2: &str) -> Option<u32> {
+    sequence::hamming_distance(seq1, seq2)
+}
+
+/// Levenshtein (edit) distance, case-insensitive
+#[wasm_bindgen]
+pub fn levenshtein(seq1: &str, seq2: &str) -> u32 {
+    sequence::levenshtein(seq1, seq2)
+}
+
+/// Levenshtein distance if it is at most `max_distance`, otherwise `None`
+#[wasm_bindgen]
+pub fn levenshtein_banded(seq1: &str, seq2: &str, max_distance: u32) -> Option<u32> {
+    sequence::levenshtein_banded(seq1, seq2, max_distance)
+}
+
+/// Similarity as 1 - levenshtein / max(len1, len2); 1.0 for two empty sequences
+#[wasm_bindgen]
+pub fn sequence_identity(seq1: &str, seq2: &str) -> f64 {
+    sequence::sequence_identity(seq1, seq2)
+}
+
+/// A single FASTA record
+#[wasm_bindgen(js_name = FastaRecord)]
+pub struct WasmFastaRecord(sequence::FastaRecord);
+
+#[wasm_bindgen(js_class = FastaRecord)]
+impl WasmFastaRecord {
+    /// Identifier (header text up to the first whitespace)
+    pub fn id(&self) -> String {
+        self.0.id()
+    }
+
+    /// Rest of the header line after the identifier
+    pub fn description(&self) -> String {
+        self.0.description()
+    }
+
+    /// Sequence with line breaks removed (case preserved)
+    pub fn sequence(&self) -> String {
+        self.0.sequence()
+    }
+
+    pub fn length(&self) -> usize {
+        self.0.length()
+    }
+}
+
+/// Parsed FASTA file
+#[wasm_bindgen(js_name = FastaResult)]
+pub struct WasmFastaResult(sequence::FastaResult);
+
+#[wasm_bindgen(js_class = FastaResult)]
+impl WasmFastaResult {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.0.ids()
+    }
+
+    pub fn descriptions(&self) -> Vec<String> {
+        self.0.descriptions()
+    }
+
+    pub fn lengths(&self) -> Vec<u32> {
+        self.0.lengths()
+    }
+
+    pub fn sequences(&self) -> Vec<String> {
+        self.0.sequences()
+    }
+
+    /// Record at `index`, if any
+    pub fn record(&self, index: usize) -> Option<WasmFastaRecord> {
+        self.0.record(index).map(WasmFastaRecord)
+    }
+}
+
+/// Parse a (multi-record) FASTA file
+#[wasm_bindgen]
+pub fn parse_fasta(content: &str) -> WasmFastaResult {
+    WasmFastaResult(sequence::parse_fasta(content))
+}
+
+/// Contig length summary of an assembly
+#[wasm_bindgen(js_name = AssemblyStats)]
+pub struct WasmAssemblyStats(sequence::AssemblyStats);
+
+#[wasm_bindgen(js_class = AssemblyStats)]
+impl WasmAssemblyStats {
+    pub fn n_contigs(&self) -> u32 {
+        self.0.n_contigs()
+    }
+
+    pub fn total_length(&self) -> f64 {
+        self.0.total_length()
+    }
+
+    pub fn min(&self) -> f64 {
+        self.0.min()
+    }
+
+    pub fn max(&self) -> f64 {
+        self.0.max()
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.0.mean()
+    }
+
+    pub fn n50(&self) -> f64 {
+        self.0.n50()
+    }
+
+    pub fn l50(&self) -> u32 {
+        self.0.l50()
+    }
+
+    pub fn n90(&self) -> f64 {
+        self.0.n90()
+    }
+
+    pub fn l90(&self) -> u32 {
+        self.0.l90()
+    }
+
+    /// GC fraction over all ACGT bases
+    pub fn gc(&self) -> Option<f64> {
+        self.0.gc()
+    }
+}
+
+/// Assembly statistics from contig lengths
+#[wasm_bindgen]
+pub fn assembly_stats(lengths: &[f64]) -> WasmAssemblyStats {
+    WasmAssemblyStats(sequence::assembly_stats(lengths))
+}
+
+/// Assembly statistics of the records in FASTA content, with overall GC
+#[wasm_bindgen]
+pub fn assembly_stats_from_fasta(content: &str) -> WasmAssemblyStats {
+    WasmAssemblyStats(sequence::assembly_stats_from_fasta(content))
+}
+
+/// Random access into a FASTA file via a `.fai`-style index
+#[wasm_bindgen(js_name = FastaIndex)]
+pub struct WasmFastaIndex(sequence::FastaIndex);
+
+#[wasm_bindgen(js_class = FastaIndex)]
+impl WasmFastaIndex {
+    /// Index FASTA content on the fly
+    #[wasm_bindgen(constructor)]
+    pub fn new(content: &str) -> Result<WasmFastaIndex, String> {
+        sequence::FastaIndex::new(content).map(WasmFastaIndex)
+    }
+
+    /// Use a precomputed `.fai` index for `content`
+    pub fn from_fai(content: &str, fai: &str) -> Result<WasmFastaIndex, String> {
+        sequence::FastaIndex::from_fai(content, fai).map(WasmFastaIndex)
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.0.ids()
+    }
+
+    pub fn lengths(&self) -> Vec<u32> {
+        self.0.lengths()
+    }
+
+    /// Serialize the index in `.fai` format
+    pub fn to_fai(&self) -> String {
+        self.0.to_fai()
+    }
+
+    /// Sequence of `id` from `start` to `end` (1-based, inclusive)
+    pub fn fetch(&self, id: &str, start: usize, end: usize) -> Result<String, String> {
+        self.0.fetch(id, start, end)
+    }
+
+    /// Fetch a region string such as `chr1:100-200`
+    pub fn fetch_region(&self, region: &str) -> Result<String, String> {
+        self.0.fetch_region(region)
+    }
+}
+
+/// Fetch a region (e.g. `chr1:100-200`) from FASTA content
+#[wasm_bindgen]
+pub fn fetch_region(content: &str, region: &str) -> Result<String, String> {
+    sequence::fetch_region(content, region)
+}
+
+/// Summary statistics for a FASTQ file
+#[wasm_bindgen(js_name = FastqStats)]
+pub struct WasmFastqStats(sequence::FastqStats);
+
+#[wasm_bindgen(js_class = FastqStats)]
+impl WasmFastqStats {
+    pub fn n_reads(&self) -> u32 {
+        self.0.n_reads()
+    }
+
+    /// Number of malformed records that were skipped
+    pub fn malformed(&self) -> u32 {
+        self.0.malformed()
+    }
+
+    pub fn min_len(&self) -> u32 {
+        self.0.min_len()
+    }
+
+    pub fn max_len(&self) -> u32 {
+        self.0.max_len()
+    }
+
+    pub fn mean_len(&self) -> f64 {
+        self.0.mean_len()
+    }
+
+    /// Mean Phred quality at each cycle, over reads long enough to cover it
+    pub fn mean_quality_per_position(&self) -> Vec<f64> {
+        self.0.mean_quality_per_position()
+    }
+
+    /// GC content over A/C/G/T bases
+    pub fn gc_fraction(&self) -> f64 {
+        self.0.gc_fraction()
+    }
+
+    /// Fraction of bases with quality >= 20
+    pub fn q20_fraction(&self) -> f64 {
+        self.0.q20_fraction()
+    }
+
+    /// Fraction of bases with quality >= 30
+    pub fn q30_fraction(&self) -> f64 {
+        self.0.q30_fraction()
+    }
+}
+
+/// QC statistics for Phred+33 FASTQ content
+#[wasm_bindgen]
+pub fn parse_fastq(content: &str) -> WasmFastqStats {
+    WasmFastqStats(sequence::parse_fastq(content))
+}
+
+/// QC statistics for FASTQ content with an explicit Phred offset (33 or 64)
+#[wasm_bindgen]
+pub fn parse_fastq_with_offset(content: &str, offset: u8) -> WasmFastqStats {
+    WasmFastqStats(sequence::parse_fastq_with_offset(content, offset))
+}
+
+/// Phred scores of a quality string; empty if any character is below the
+/// offset or above `~`
+#[wasm_bindgen]
+pub fn phred_to_scores(qual: &str, offset: u8) -> Vec<u8> {
+    sequence::phred_to_scores(qual, offset)
+}
+
+/// Arithmetic mean Phred score; NaN if empty or invalid
+#[wasm_bindgen]
+pub fn mean_quality(qual: &str, offset: u8) -> f64 {
+    sequence::mean_quality(qual, offset)
+}
+
+/// Per-base error probabilities 10^(-Q/10); empty if invalid
+#[wasm_bindgen]
+pub fn error_probability(qual: &str, offset: u8) -> Vec<f64> {
+    sequence::error_probability(qual, offset)
+}
+
+/// Guess the Phred offset of a quality string: 33 or 64
+#[wasm_bindgen]
+pub fn guess_offset(qual: &str) -> u8 {
+    sequence::guess_offset(qual)
+}
+
+/// Quality-trimmed read: bases `start..end` (0-based, end exclusive)
+#[wasm_bindgen(js_name = TrimResult)]
+pub struct WasmTrimResult(sequence::TrimResult);
+
+#[wasm_bindgen(js_class = TrimResult)]
+impl WasmTrimResult {
+    pub fn start(&self) -> u32 {
+        self.0.start()
+    }
+
+    pub fn end(&self) -> u32 {
+        self.0.end()
+    }
+
+    pub fn trimmed_seq(&self) -> String {
+        self.0.trimmed_seq()
+    }
+
+    pub fn trimmed_qual(&self) -> String {
+        self.0.trimmed_qual()
+    }
+}
+
+/// BWA-style quality trimming of both read ends
+#[wasm_bindgen]
+pub fn quality_trim(
+    sequence: &str,
+    qual: &str,
+    threshold: u8,
+    offset: u8,
+) -> Result<WasmTrimResult, String> {
+    sequence::quality_trim(sequence, qual, threshold, offset).map(WasmTrimResult)
+}
@@ -2,55 +2,39 @@
 //!
 //! Efficient clustering algorithm for gene expression data,
 //! single-cell analysis, and dimensionality reduction results.
+//!
+//! Initialization draws from a caller-supplied [`SplitMix64`], so a given
+//! seed always clusters the same way.
 
-use wasm_bindgen::prelude::*;
-use js_sys::Math;
+use vcf_parser::rng::SplitMix64;
 
 /// Result of K-means clustering
-#[wasm_bindgen]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct KMeansResult {
-    assignments: Vec<u32>,
-    centroids: Vec<f64>,
-    iterations: u32,
-    converged: bool,
-    inertia: f64,
-}
-
-#[wasm_bindgen]
-impl KMeansResult {
-    /// Get cluster assignments for each point
-    pub fn assignments(&self) -> Vec<u32> {
-        self.assignments.clone()
-    }
-    
-    /// Get centroid coordinates (flattened: [x1,y1,x2,y2,...])
-    pub fn centroids(&self) -> Vec<f64> {
-        self.centroids.clone()
-    }
-    
+    /// Cluster of each point
+    pub assignments: Vec<u32>,
+    /// Centroid coordinates (flattened: [x1,y1,x2,y2,...])
+    pub centroids: Vec<f64>,
     /// Number of iterations until convergence
-    pub fn iterations(&self) -> u32 {
-        self.iterations
-    }
-    
+    pub iterations: u32,
     /// Whether the algorithm converged
-    pub fn converged(&self) -> bool {
-        self.converged
-    }
-    
-    /// Within-cluster sum of squares (inertia)
-    pub fn inertia(&self) -> f64 {
-        self.inertia
-    }
+    pub converged: bool,
+    /// Within-cluster sum of squares
+    pub inertia: f64,
+}
+
+/// Uniform index in `0..n`
+fn random_index(rng: &mut SplitMix64, n: usize) -> usize {
+    ((rng.next_f64() * n as f64) as usize).min(n - 1)
 }
 
 /// K-means++ initialization for better starting centroids
-fn kmeans_plus_plus(data: &[f64], k: usize, dims: usize) -> Vec<f64> {
+fn kmeans_plus_plus(data: &[f64], k: usize, dims: usize, rng: &mut SplitMix64) -> Vec<f64> {
     let n_points = data.len() / dims;
     let mut centroids = Vec::with_capacity(k * dims);
     
     // First centroid: random point
-    let first_idx = (Math::random() * n_points as f64) as usize;
+    let first_idx = random_index(rng, n_points);
     centroids.extend_from_slice(&data[first_idx * dims..(first_idx + 1) * dims]);
     
     // Remaining centroids: weighted by distance squared
@@ -67,7 +51,7 @@ fn kmeans_plus_plus(data: &[f64], k: usize, dims: usize) -> Vec<f64> {
         
         // Select next centroid with probability proportional to distance^2
         let total: f64 = distances.iter().sum();
-        let threshold = Math::random() * total;
+        let threshold = rng.next_f64() * total;
         let mut cumsum = 0.0;
         
         for (i, &dist) in distances.iter().enumerate() {
@@ -81,7 +65,7 @@ fn kmeans_plus_plus(data: &[f64], k: usize, dims: usize) -> Vec<f64> {
     
     // Handle edge case where we didn't add enough centroids
     while centroids.len() < k * dims {
-        let idx = (Math::random() * n_points as f64) as usize;
+        let idx = random_index(rng, n_points);
         centroids.extend_from_slice(&data[idx * dims..(idx + 1) * dims]);
     }
     
@@ -104,7 +88,6 @@ fn assign_points(
     assignments: &mut [u32],
     dims: usize,
 ) -> bool {
-    let k = centroids.len() / dims;
     let mut changed = false;
     
     for (i, point) in data.chunks(dims).enumerate() {
@@ -183,43 +166,37 @@ fn calculate_inertia(
 /// * `k` - Number of clusters
 /// * `dims` - Dimensions per point (default: 2)
 /// * `max_iter` - Maximum iterations
-/// * `tolerance` - Convergence tolerance
+/// * `rng` - Source for k-means++ initialization
 /// 
 /// # Returns
 /// KMeansResult with assignments, centroids, and metadata
-#[wasm_bindgen]
 pub fn kmeans(
     data: &[f64],
     k: usize,
     dims: usize,
     max_iter: u32,
+    rng: &mut SplitMix64,
 ) -> KMeansResult {
-    kmeans_with_tolerance(data, k, dims, max_iter, 1e-4)
+    kmeans_with_tolerance(data, k, dims, max_iter, 1e-4, rng)
 }
 
 /// K-means with custom tolerance
-#[wasm_bindgen]
 pub fn kmeans_with_tolerance(
     data: &[f64],
     k: usize,
     dims: usize,
     max_iter: u32,
     tolerance: f64,
+    rng: &mut SplitMix64,
 ) -> KMeansResult {
-    let n_points = data.len() / dims;
+    let n_points = data.len().checked_div(dims).unwrap_or(0);
     
     if n_points == 0 || k == 0 || k > n_points {
-        return KMeansResult {
-            assignments: vec![],
-            centroids: vec![],
-            iterations: 0,
-            converged: false,
-            inertia: 0.0,
-        };
+        return KMeansResult::default();
     }
     
     // Initialize with k-means++
-    let mut centroids = kmeans_plus_plus(data, k, dims);
+    let mut centroids = kmeans_plus_plus(data, k, dims, rng);
     let mut assignments = vec![0u32; n_points];
     let mut prev_inertia = f64::MAX;
     
@@ -258,19 +235,22 @@ pub fn kmeans_with_tolerance(
 }
 
 /// Run k-means multiple times and return best result
-#[wasm_bindgen]
+///
+/// Each run continues the same `rng`, so runs start from different
+/// centroids.
 pub fn kmeans_best(
     data: &[f64],
     k: usize,
     dims: usize,
     max_iter: u32,
     n_init: u32,
+    rng: &mut SplitMix64,
 ) -> KMeansResult {
     let mut best_result: Option<KMeansResult> = None;
     let mut best_inertia = f64::MAX;
     
     for _ in 0..n_init {
-        let result = kmeans(data, k, dims, max_iter);
+        let result = kmeans(data, k, dims, max_iter, rng);
         
         if result.inertia < best_inertia {
             best_inertia = result.inertia;
@@ -278,39 +258,32 @@ pub fn kmeans_best(
         }
     }
     
-    best_result.unwrap_or_else(|| KMeansResult {
-        assignments: vec![],
-        centroids: vec![],
-        iterations: 0,
-        converged: false,
-        inertia: 0.0,
-    })
+    best_result.unwrap_or_default()
 }
 
 /// Elbow method: calculate inertia for different k values
-#[wasm_bindgen]
 pub fn elbow_analysis(
     data: &[f64],
     dims: usize,
     max_k: usize,
     max_iter: u32,
+    rng: &mut SplitMix64,
 ) -> Vec<f64> {
     (1..=max_k)
         .map(|k| {
-            let result = kmeans_best(data, k, dims, max_iter, 3);
+            let result = kmeans_best(data, k, dims, max_iter, 3, rng);
             result.inertia
         })
         .collect()
 }
 
 /// Silhouette score for clustering quality
-#[wasm_bindgen]
 pub fn silhouette_score(
     data: &[f64],
     assignments: &[u32],
     dims: usize,
 ) -> f64 {
-    let n_points = data.len() / dims;
+    let n_points = data.len().checked_div(dims).unwrap_or(0);
     
     if n_points < 2 {
         return 0.0;
@@ -372,38 +345,69 @@ pub fn silhouette_score(
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    /// Two clear clusters
+    const TWO_CLUSTERS: [f64; 12] = [
+        0.0, 0.0,
+        0.1, 0.1,
+        0.0, 0.1,
+        10.0, 10.0,
+        10.1, 10.1,
+        10.0, 10.1,
+    ];
     
     #[test]
     fn test_kmeans_simple() {
-        // Two clear clusters
-        let data = vec![
-            0.0, 0.0,
-            0.1, 0.1,
-            0.0, 0.1,
-            10.0, 10.0,
-            10.1, 10.1,
-            10.0, 10.1,
-        ];
+        let result = kmeans(&TWO_CLUSTERS, 2, 2, 100, &mut SplitMix64::new(1));
         
-        let result = kmeans(&data, 2, 2, 100);
-        
-        assert_eq!(result.assignments.len(), 3);
+        assert_eq!(result.assignments.len(), 6);
         assert!(result.converged);
         
         // Points should be in different clusters
         assert_ne!(result.assignments[0], result.assignments[3]);
+        assert_eq!(result.assignments[0..3], [result.assignments[0]; 3]);
+        assert_eq!(result.assignments[3..6], [result.assignments[3]; 3]);
+        assert!(result.inertia < 0.1);
+    }
+
+    #[test]
+    fn test_kmeans_seeded() {
+        let run = |seed| kmeans(&TWO_CLUSTERS, 3, 2, 100, &mut SplitMix64::new(seed));
+        assert_eq!(run(7), run(7));
+
+        // Invalid input gives an empty result rather than panicking
+        let mut rng = SplitMix64::new(7);
+        assert_eq!(kmeans(&TWO_CLUSTERS, 7, 2, 100, &mut rng), KMeansResult::default());
+        assert_eq!(kmeans(&TWO_CLUSTERS, 2, 0, 100, &mut rng), KMeansResult::default());
+        assert_eq!(kmeans_best(&TWO_CLUSTERS, 2, 2, 100, 0, &mut rng), KMeansResult::default());
     }
     
     #[test]
     fn test_elbow() {
+        let mut rng = SplitMix64::new(42);
         let data: Vec<f64> = (0..100)
-            .flat_map(|i| vec![(i % 3) as f64 * 10.0 + Math::random(), Math::random()])
+            .flat_map(|i| vec![(i % 3) as f64 * 10.0 + rng.next_f64(), rng.next_f64()])
             .collect();
         
-        let inertias = elbow_analysis(&data, 2, 5, 50);
+        let inertias = elbow_analysis(&data, 2, 5, 50, &mut SplitMix64::new(1));
         
         assert_eq!(inertias.len(), 5);
-        // Inertia should generally decrease with more clusters
+        // Inertia should generally decrease with more clusters, with the
+        // elbow at the three generated clusters
         assert!(inertias[0] >= inertias[4]);
+        assert!(inertias[2] < inertias[1] / 10.0);
+        assert_eq!(inertias, elbow_analysis(&data, 2, 5, 50, &mut SplitMix64::new(1)));
+    }
+
+    #[test]
+    fn test_silhouette() {
+        let separated = silhouette_score(&TWO_CLUSTERS, &[0, 0, 0, 1, 1, 1], 2);
+        assert!(separated > 0.95);
+
+        // Splitting each true cluster across labels scores far worse
+        let mixed = silhouette_score(&TWO_CLUSTERS, &[0, 1, 0, 1, 0, 1], 2);
+        assert!(mixed < 0.0);
+
+        assert_eq!(silhouette_score(&[1.0, 2.0], &[0], 2), 0.0);
     }
 }
@@ -0,0 +1,261 @@
+//! Probability Distributions
+//!
+//! Special functions and distribution tails shared by the
+//! statistical tests in this crate.
+
+/// Natural log of the gamma function (Lanczos approximation, g = 7)
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        // Reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+
+    let x = x - 1.0;
+    let mut sum = COEFFS[0];
+    for (i, &c) in COEFFS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Continued fraction for the incomplete beta function (modified Lentz)
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITER: usize = 300;
+    const EPS: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+
+    let qab = a + b;
+    let qap = a + 1.0;
+    let qam = a - 1.0;
+
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+
+    for m in 1..=MAX_ITER {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+
+        // Even step
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+
+        // Odd step
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+
+        if (delta - 1.0).abs() < EPS {
+            break;
+        }
+    }
+
+    h
+}
+
+/// Regularized incomplete beta function I_x(a, b)
+pub(crate) fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x.is_nan() || a <= 0.0 || b <= 0.0 {
+        return f64::NAN;
+    }
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b)
+        + a * x.ln()
+        + b * (1.0 - x).ln();
+    let front = ln_front.exp();
+
+    // Use the symmetry relation where the continued fraction converges fastest
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Two-tailed p-value for a Student's t statistic with `df` degrees of freedom
+pub(crate) fn student_t_two_tailed(t: f64, df: f64) -> f64 {
+    if t.is_nan() || df.is_nan() || df <= 0.0 {
+        return f64::NAN;
+    }
+    if t.is_infinite() {
+        return 0.0;
+    }
+
+    incomplete_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// Regularized upper incomplete gamma function Q(a, x)
+pub(crate) fn incomplete_gamma_upper(a: f64, x: f64) -> f64 {
+    const MAX_ITER: usize = 500;
+    const EPS: f64 = 1e-15;
+    const TINY: f64 = 1e-300;
+
+    if x.is_nan() || a <= 0.0 {
+        return f64::NAN;
+    }
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    let ln_front = a * x.ln() - x - ln_gamma(a);
+
+    if x < a + 1.0 {
+        // Series for P(a, x), then complement
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        let mut ap = a;
+        for _ in 0..MAX_ITER {
+            ap += 1.0;
+            term *= x / ap;
+            sum += term;
+            if term.abs() < sum.abs() * EPS {
+                break;
+            }
+        }
+        1.0 - sum * ln_front.exp()
+    } else {
+        // Continued fraction for Q(a, x) (modified Lentz)
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / TINY;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..=MAX_ITER {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = b + an / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPS {
+                break;
+            }
+        }
+        h * ln_front.exp()
+    }
+}
+
+/// Two-tailed p-value for a standard normal z statistic
+pub(crate) fn normal_two_tailed(z: f64) -> f64 {
+    if z.is_nan() {
+        return f64::NAN;
+    }
+
+    // erfc(|z| / sqrt(2)) = Q(1/2, z^2 / 2)
+    incomplete_gamma_upper(0.5, z * z / 2.0)
+}
+
+/// Benjamini-Hochberg adjusted p-values
+///
+/// NaN entries are left as NaN and excluded from the number of tests.
+pub(crate) fn benjamini_hochberg(p_values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..p_values.len())
+        .filter(|&i| !p_values[i].is_nan())
+        .collect();
+    order.sort_by(|&a, &b| p_values[a].total_cmp(&p_values[b]));
+
+    let m = order.len() as f64;
+    let mut adjusted = vec![f64::NAN; p_values.len()];
+    let mut running_min: f64 = 1.0;
+
+    for (rank, &idx) in order.iter().enumerate().rev() {
+        let q = p_values[idx] * m / (rank + 1) as f64;
+        running_min = running_min.min(q);
+        adjusted[idx] = running_min;
+    }
+
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ln_gamma() {
+        // Γ(5) = 24, Γ(0.5) = √π
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_student_t() {
+        // Reference values from R: 2 * pt(-2.0, 10), 2 * pt(-1.0, 3)
+        assert!((student_t_two_tailed(2.0, 10.0) - 0.073_388_034_770_740_38).abs() < 1e-10);
+        assert!((student_t_two_tailed(-1.0, 3.0) - 0.391_002_218_955_770_64).abs() < 1e-10);
+        assert!((student_t_two_tailed(0.0, 5.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_normal_two_tailed() {
+        // Reference values from R: 2 * pnorm(-1.96), 2 * pnorm(-0.5), 2 * pnorm(-6)
+        assert!((normal_two_tailed(1.96) - 0.049_995_790_296_440_87).abs() < 1e-12);
+        assert!((normal_two_tailed(-0.5) - 0.617_075_077_451_973_8).abs() < 1e-12);
+        assert!((normal_two_tailed(6.0) - 1.973_175_290_075_402e-9).abs() < 1e-18);
+        assert!((normal_two_tailed(0.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_benjamini_hochberg() {
+        // Reference values from R: p.adjust(c(0.01, 0.04, 0.03, 0.2), "BH")
+        let adjusted = benjamini_hochberg(&[0.01, 0.04, f64::NAN, 0.03, 0.2]);
+        let expected = [0.04, 0.053_333_333_333_333_33, f64::NAN, 0.053_333_333_333_333_33, 0.2];
+
+        for (a, e) in adjusted.iter().zip(expected.iter()) {
+            if e.is_nan() {
+                assert!(a.is_nan());
+            } else {
+                assert!((a - e).abs() < 1e-12);
+            }
+        }
+    }
+}
@@ -3,35 +3,18 @@
 //! Computes exact p-values for 2x2 contingency tables,
 //! commonly used in mutation significance analysis.
 
-use wasm_bindgen::prelude::*;
+pub use vcf_parser::allelic::allelic_imbalance_test;
+use vcf_parser::allelic::ln_factorial;
 
-/// Calculate log factorial using Stirling's approximation for large n
+/// Log factorial, the same one the allelic imbalance test uses
 fn log_factorial(n: u32) -> f64 {
-    if n <= 1 {
-        return 0.0;
-    }
-    
-    // Use lookup table for small values
-    const LOOKUP: [f64; 21] = [
-        0.0, 0.0, 0.693147, 1.791759, 3.178054, 4.787492,
-        6.579251, 8.525161, 10.604603, 12.801827, 15.104413,
-        17.502308, 19.987214, 22.552164, 25.191221, 27.899271,
-        30.671860, 33.505073, 36.395445, 39.339884, 42.335616
-    ];
-    
-    if n <= 20 {
-        return LOOKUP[n as usize];
-    }
-    
-    // Stirling's approximation for larger n
-    let n = n as f64;
-    (n + 0.5) * n.ln() - n + 0.918938533204673  // 0.5 * ln(2π)
+    ln_factorial(u64::from(n))
 }
 
 /// Calculate hypergeometric probability for a 2x2 table
 fn hypergeometric_prob(a: u32, b: u32, c: u32, d: u32) -> f64 {
     let n = a + b + c + d;
-    
+
     let log_p = log_factorial(a + b) + log_factorial(c + d)
               + log_factorial(a + c) + log_factorial(b + d)
               - log_factorial(a) - log_factorial(b)
@@ -51,24 +34,22 @@ fn hypergeometric_prob(a: u32, b: u32, c: u32, d: u32) -> f64 {
 /// * `c` - Count in cell (2,1): Group A without feature
 /// * `d` - Count in cell (2,2): Group B without feature
 /// 
-/// # Example (from JavaScript)
-/// ```javascript
-/// const pvalue = fisher_exact(10, 2, 3, 15);
-/// console.log(`P-value: ${pvalue}`);
+/// # Example
+/// ```
+/// let p = genomic_wasm::fisher_exact(10, 2, 3, 15);
+/// assert!(p < 0.001);
 /// ```
-#[wasm_bindgen]
 pub fn fisher_exact(a: u32, b: u32, c: u32, d: u32) -> f64 {
     fisher_exact_two_tailed(a, b, c, d)
 }
 
 /// One-tailed Fisher's exact test (less than)
-#[wasm_bindgen]
 pub fn fisher_exact_left(a: u32, b: u32, c: u32, d: u32) -> f64 {
     let row1 = a + b;
     let col1 = a + c;
     let n = a + b + c + d;
     
-    let min_a = if row1 + col1 > n { row1 + col1 - n } else { 0 };
+    let min_a = (row1 + col1).saturating_sub(n);
     
     let mut p_sum = 0.0;
     for i in min_a..=a {
@@ -82,7 +63,6 @@ pub fn fisher_exact_left(a: u32, b: u32, c: u32, d: u32) -> f64 {
 }
 
 /// One-tailed Fisher's exact test (greater than)
-#[wasm_bindgen]
 pub fn fisher_exact_right(a: u32, b: u32, c: u32, d: u32) -> f64 {
     let row1 = a + b;
     let col1 = a + c;
@@ -102,13 +82,12 @@ pub fn fisher_exact_right(a: u32, b: u32, c: u32, d: u32) -> f64 {
 }
 
 /// Two-tailed Fisher's exact test
-#[wasm_bindgen]
 pub fn fisher_exact_two_tailed(a: u32, b: u32, c: u32, d: u32) -> f64 {
     let row1 = a + b;
     let col1 = a + c;
     let n = a + b + c + d;
     
-    let min_a = if row1 + col1 > n { row1 + col1 - n } else { 0 };
+    let min_a = (row1 + col1).saturating_sub(n);
     let max_a = row1.min(col1);
     
     // Calculate observed probability
@@ -140,9 +119,8 @@ pub fn fisher_exact_two_tailed(a: u32, b: u32, c: u32, d: u32) -> f64 {
 /// 
 /// # Returns
 /// Array of p-values, one for each table
-#[wasm_bindgen]
 pub fn fisher_exact_batch(tables: &[u32]) -> Vec<f64> {
-    if tables.len() % 4 != 0 {
+    if !tables.len().is_multiple_of(4) {
         return vec![];
     }
     
@@ -153,7 +131,6 @@ pub fn fisher_exact_batch(tables: &[u32]) -> Vec<f64> {
 }
 
 /// Odds ratio calculation for 2x2 table
-#[wasm_bindgen]
 pub fn odds_ratio(a: u32, b: u32, c: u32, d: u32) -> f64 {
     if b == 0 || c == 0 {
         return f64::INFINITY;
@@ -162,23 +139,14 @@ pub fn odds_ratio(a: u32, b: u32, c: u32, d: u32) -> f64 {
 }
 
 /// Odds ratio with confidence interval
-#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct OddsRatioResult {
-    odds_ratio: f64,
-    ci_lower: f64,
-    ci_upper: f64,
-    p_value: f64,
-}
-
-#[wasm_bindgen]
-impl OddsRatioResult {
-    pub fn odds_ratio(&self) -> f64 { self.odds_ratio }
-    pub fn ci_lower(&self) -> f64 { self.ci_lower }
-    pub fn ci_upper(&self) -> f64 { self.ci_upper }
-    pub fn p_value(&self) -> f64 { self.p_value }
+    pub odds_ratio: f64,
+    pub ci_lower: f64,
+    pub ci_upper: f64,
+    pub p_value: f64,
 }
 
-#[wasm_bindgen]
 pub fn odds_ratio_ci(a: u32, b: u32, c: u32, d: u32, confidence: f64) -> OddsRatioResult {
     let or = odds_ratio(a, b, c, d);
     
@@ -229,5 +197,54 @@ mod tests {
         let tables = vec![1, 9, 11, 3, 10, 2, 3, 15];
         let results = fisher_exact_batch(&tables);
         assert_eq!(results.len(), 2);
+        assert_eq!(results[0], fisher_exact(1, 9, 11, 3));
+        assert!(fisher_exact_batch(&[1, 2, 3]).is_empty());
+    }
+
+    #[test]
+    fn test_one_tailed() {
+        // Left and right tails share the observed table
+        let left = fisher_exact_left(1, 9, 11, 3);
+        let right = fisher_exact_right(1, 9, 11, 3);
+        assert!((left - 0.001380).abs() < 0.0005);
+        assert!((left + right - 1.0 - hypergeometric_prob(1, 9, 11, 3)).abs() < 1e-4);
+
+        // A table at the edge of its margins has nothing more extreme
+        assert!((fisher_exact_two_tailed(0, 5, 5, 0) - 2.0 * hypergeometric_prob(0, 5, 5, 0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_odds_ratio_ci() {
+        let result = odds_ratio_ci(10, 2, 3, 15, 0.95);
+        assert!((result.odds_ratio - 25.0).abs() < 1e-9);
+        assert!(result.ci_lower < 25.0 && 25.0 < result.ci_upper);
+        assert_eq!(result.p_value, fisher_exact(10, 2, 3, 15));
+
+        let wider = odds_ratio_ci(10, 2, 3, 15, 0.99);
+        assert!(wider.ci_lower < result.ci_lower && wider.ci_upper > result.ci_upper);
+        assert_eq!(odds_ratio(1, 0, 3, 4), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_allelic_imbalance_bh() {
+        use crate::distributions::benjamini_hochberg;
+        use vcf_parser::{record_allelic_imbalance, VcfParser};
+
+        let vcf = "##fileformat=VCFv4.2\n\
+                   #CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tS1\tS2\tS3\n\
+                   chr1\t100\t.\tA\tG\t50\tPASS\t.\tGT:AD\t0/1:28,12\t0/0:30,0\t0/1:15,15\n";
+        let (_, records) = VcfParser::new().parse_str(vcf).unwrap();
+        // Untested samples go in as NaN, which the correction leaves out
+        let p_values: Vec<f64> = record_allelic_imbalance(&records[0], 0.5)
+            .into_iter()
+            .map(|(_, p)| p.unwrap_or(f64::NAN))
+            .collect();
+        assert!((p_values[0] - 0.016_589_003_374_974_71).abs() < 1e-12);
+
+        let adjusted = benjamini_hochberg(&p_values);
+        assert!((adjusted[0] - 2.0 * p_values[0]).abs() < 1e-12);
+        assert!(adjusted[1].is_nan());
+        assert_eq!(adjusted[2], 1.0);
+        assert_eq!(allelic_imbalance_test(12, 28, 0.5), p_values[0]);
     }
 }
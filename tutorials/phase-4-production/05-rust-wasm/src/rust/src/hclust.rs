@@ -0,0 +1,192 @@
+//! Hierarchical Clustering and Leaf Ordering
+//!
+//! Average-linkage agglomeration and dendrogram leaf ordering
+//! for heatmap display of correlation and distance matrices.
+
+use crate::matrix::MatrixResult;
+
+/// Distance lookup that treats NaN as maximally dissimilar
+#[inline]
+fn distance(dist: &[f64], n: usize, i: usize, j: usize) -> f64 {
+    let d = dist[i * n + j];
+    if d.is_nan() { f64::INFINITY } else { d }
+}
+
+/// Average-linkage (UPGMA) merge tree via the nearest-neighbor chain algorithm
+///
+/// Leaves are nodes 0..n; the k-th merge creates node n + k. Each entry
+/// holds the two child nodes, lower-indexed cluster first.
+fn average_linkage(dist: &[f64], n: usize) -> Vec<(usize, usize)> {
+    let mut d: Vec<f64> = (0..n * n).map(|idx| distance(dist, n, idx / n, idx % n)).collect();
+    let mut size = vec![1usize; n];
+    let mut active = vec![true; n];
+    let mut node: Vec<usize> = (0..n).collect();
+    let mut merges = Vec::with_capacity(n.saturating_sub(1));
+    let mut chain: Vec<usize> = Vec::with_capacity(n);
+
+    while merges.len() + 1 < n {
+        if chain.is_empty() {
+            chain.extend(active.iter().position(|&a| a));
+        }
+
+        let a = chain[chain.len() - 1];
+        let prev = chain.len().checked_sub(2).map(|i| chain[i]);
+
+        // Nearest active neighbor; ties keep the previous chain element
+        let mut best = prev;
+        let mut best_dist = prev.map_or(f64::INFINITY, |p| d[a * n + p]);
+        for k in (0..n).filter(|&k| active[k] && k != a) {
+            if best.is_none() || d[a * n + k] < best_dist {
+                best = Some(k);
+                best_dist = d[a * n + k];
+            }
+        }
+
+        let b = match best {
+            Some(b) => b,
+            None => break,
+        };
+
+        if Some(b) != prev {
+            chain.push(b);
+            continue;
+        }
+
+        // Reciprocal nearest neighbors: merge into the lower slot
+        chain.truncate(chain.len() - 2);
+        let (lo, hi) = (a.min(b), a.max(b));
+        merges.push((node[lo], node[hi]));
+
+        let total = (size[lo] + size[hi]) as f64;
+        for k in (0..n).filter(|&k| active[k] && k != lo && k != hi) {
+            let merged = (size[lo] as f64 * d[lo * n + k] + size[hi] as f64 * d[hi * n + k]) / total;
+            d[lo * n + k] = merged;
+            d[k * n + lo] = merged;
+        }
+
+        active[hi] = false;
+        size[lo] += size[hi];
+        node[lo] = n + merges.len() - 1;
+    }
+
+    merges
+}
+
+/// Gruvaeus-Wainer ordering of the leaves of a merge tree
+///
+/// At each merge the two subtrees are flipped so that the closest pair of
+/// their end leaves becomes adjacent.
+fn gruvaeus_wainer(merges: &[(usize, usize)], dist: &[f64], n: usize) -> Vec<usize> {
+    let mut orders: Vec<Vec<usize>> = (0..n).map(|i| vec![i]).collect();
+
+    for &(l, r) in merges {
+        let mut left = std::mem::take(&mut orders[l]);
+        let mut right = std::mem::take(&mut orders[r]);
+
+        // (flip left, flip right) candidates, first minimum wins
+        let join_distance = |flip_left: bool, flip_right: bool| {
+            let end = if flip_left { left[0] } else { left[left.len() - 1] };
+            let start = if flip_right { right[right.len() - 1] } else { right[0] };
+            distance(dist, n, end, start)
+        };
+
+        let mut best = (false, false);
+        let mut best_dist = join_distance(false, false);
+        for candidate in [(true, false), (false, true), (true, true)] {
+            let d = join_distance(candidate.0, candidate.1);
+            if d < best_dist {
+                best = candidate;
+                best_dist = d;
+            }
+        }
+
+        if best.0 {
+            left.reverse();
+        }
+        if best.1 {
+            right.reverse();
+        }
+        left.extend(right);
+        orders.push(left);
+    }
+
+    orders.pop().unwrap_or_default()
+}
+
+/// Leaf order for heatmap display of a symmetric distance matrix
+///
+/// Builds an average-linkage tree and orders its leaves with the
+/// Gruvaeus-Wainer heuristic so similar rows sit next to each other.
+/// For a correlation matrix pass `1 - r` as the distance. NaN distances
+/// are treated as maximally dissimilar. Returns a permutation of 0..n
+/// (deterministic for identical input), or an empty vector if the matrix
+/// is not square.
+pub fn optimal_leaf_order(dist: &MatrixResult) -> Vec<u32> {
+    let n = dist.rows();
+    if n == 0 || dist.cols() != n {
+        return vec![];
+    }
+
+    let merges = average_linkage(dist.as_slice(), n);
+
+    gruvaeus_wainer(&merges, dist.as_slice(), n)
+        .into_iter()
+        .map(|i| i as u32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrix::transpose;
+
+    fn distance_matrix(values: Vec<f64>, n: usize) -> MatrixResult {
+        // Transpose of a symmetric matrix is itself; used to build a MatrixResult
+        transpose(&values, n, n)
+    }
+
+    #[test]
+    fn test_leaf_order_groups_blocks() {
+        // Rows interleaved across three blocks of sizes 4, 3 and 5
+        let blocks = [0, 1, 2, 0, 2, 1, 2, 0, 2, 1, 0, 2];
+        let n = blocks.len();
+
+        let correlation: Vec<f64> = (0..n * n)
+            .map(|idx| {
+                let (i, j) = (idx / n, idx % n);
+                if i == j {
+                    1.0
+                } else if blocks[i] == blocks[j] {
+                    0.9 - 0.01 * (i + j) as f64 / n as f64
+                } else {
+                    0.1
+                }
+            })
+            .collect();
+        let dist = distance_matrix(correlation.iter().map(|r| 1.0 - r).collect(), n);
+
+        let order = optimal_leaf_order(&dist);
+
+        let mut sorted = order.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..n as u32).collect::<Vec<_>>());
+
+        // Each block occupies one contiguous run
+        let labels: Vec<usize> = order.iter().map(|&i| blocks[i as usize]).collect();
+        let runs = 1 + labels.windows(2).filter(|w| w[0] != w[1]).count();
+        assert_eq!(runs, 3);
+
+        assert_eq!(optimal_leaf_order(&dist), order);
+    }
+
+    #[test]
+    fn test_leaf_order_edge_cases() {
+        assert_eq!(optimal_leaf_order(&distance_matrix(vec![0.0], 1)), vec![0]);
+        assert!(optimal_leaf_order(&transpose(&[0.0, 1.0], 1, 2)).is_empty());
+
+        // A path 0-2-1 in distance space should be laid out end to end
+        let dist = distance_matrix(vec![0.0, 2.0, 1.0, 2.0, 0.0, 1.5, 1.0, 1.5, 0.0], 3);
+        let order = optimal_leaf_order(&dist);
+        assert_eq!(order[1], 2);
+    }
+}
@@ -5,13 +5,28 @@
 
 mod fisher;
 mod cluster;
+mod hclust;
 mod sequence;
+mod restriction;
+mod pwm;
+mod protein;
 mod matrix;
+mod regression;
+mod distributions;
+mod association;
+#[cfg(target_arch = "wasm32")]
+mod bindings;
 
 pub use fisher::*;
 pub use cluster::*;
+pub use hclust::*;
 pub use sequence::*;
+pub use restriction::*;
+pub use pwm::*;
+pub use protein::*;
 pub use matrix::*;
+pub use regression::*;
+pub use association::*;
 
 use wasm_bindgen::prelude::*;
 
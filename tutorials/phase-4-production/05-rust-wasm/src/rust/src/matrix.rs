@@ -3,17 +3,18 @@
 //! Fast matrix operations for gene expression analysis,
 //! correlation calculations, and dimensionality reduction.
 
-use wasm_bindgen::prelude::*;
+use crate::distributions::normal_two_tailed;
+use crate::regression::CovariateDesign;
 
 /// Matrix multiplication result
-#[wasm_bindgen]
+#[derive(Clone)]
 pub struct MatrixResult {
     data: Vec<f64>,
     rows: usize,
     cols: usize,
+    warnings: u32,
 }
 
-#[wasm_bindgen]
 impl MatrixResult {
     pub fn data(&self) -> Vec<f64> {
         self.data.clone()
@@ -33,47 +34,267 @@ impl MatrixResult {
         }
         self.data[row * self.cols + col]
     }
+    
+    /// Number of non-fatal issues encountered (e.g. skipped columns)
+    pub fn warnings(&self) -> u32 {
+        self.warnings
+    }
+    
+    /// Move the data out without copying (consumes the result)
+    pub fn take_data(self) -> Vec<f64> {
+        self.data
+    }
+    
+    /// Lossy f32 export for visualization (half the transfer size)
+    pub fn data_f32(&self) -> Vec<f32> {
+        self.data.iter().map(|&x| x as f32).collect()
+    }
+    
+    /// Values of a single row; empty if out of bounds
+    pub fn row(&self, i: usize) -> Vec<f64> {
+        if i >= self.rows {
+            return vec![];
+        }
+        self.data[i * self.cols..(i + 1) * self.cols].to_vec()
+    }
+    
+    /// Values of a single column; empty if out of bounds
+    pub fn col(&self, j: usize) -> Vec<f64> {
+        if j >= self.cols {
+            return vec![];
+        }
+        (0..self.rows).map(|i| self.data[i * self.cols + j]).collect()
+    }
+    
+    /// Rectangular slice [row_start, row_end) x [col_start, col_end)
+    ///
+    /// Returns an empty result if the ranges are out of bounds or reversed.
+    pub fn submatrix(
+        &self,
+        row_start: usize,
+        row_end: usize,
+        col_start: usize,
+        col_end: usize,
+    ) -> MatrixResult {
+        if row_start > row_end || col_start > col_end || row_end > self.rows || col_end > self.cols {
+            return MatrixResult {
+                data: vec![],
+                rows: 0,
+                cols: 0,
+                warnings: 0,
+            };
+        }
+        
+        let mut data = Vec::with_capacity((row_end - row_start) * (col_end - col_start));
+        for i in row_start..row_end {
+            data.extend_from_slice(&self.data[i * self.cols + col_start..i * self.cols + col_end]);
+        }
+        
+        MatrixResult {
+            data,
+            rows: row_end - row_start,
+            cols: col_end - col_start,
+            warnings: 0,
+        }
+    }
+}
+
+impl MatrixResult {
+    pub(crate) fn new(data: Vec<f64>, rows: usize, cols: usize) -> MatrixResult {
+        MatrixResult {
+            data,
+            rows,
+            cols,
+            warnings: 0,
+        }
+    }
+    
+    /// Borrow the row-major data without copying
+    pub(crate) fn as_slice(&self) -> &[f64] {
+        &self.data
+    }
+}
+
+/// Default tile size for blocked matrix multiplication
+const DEFAULT_BLOCK_SIZE: usize = 64;
+
+/// Multiply-add count (m * k * n) above which `matmul` switches to the blocked kernel
+const BLOCKED_MATMUL_THRESHOLD: usize = 64 * 64 * 64;
+
+/// Straightforward i-p-j kernel; C must be zero-initialized
+fn matmul_naive_into<T>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize)
+where
+    T: Copy + std::ops::AddAssign + std::ops::Mul<Output = T>,
+{
+    for i in 0..m {
+        for p in 0..k {
+            let a_ip = a[i * k + p];
+            for j in 0..n {
+                c[i * n + j] += a_ip * b[p * n + j];
+            }
+        }
+    }
+}
+
+/// Cache-blocked i-p-j kernel; C must be zero-initialized
+///
+/// Tiles are visited so that each C[i][j] still accumulates over p in
+/// ascending order, giving the same result as the naive kernel.
+fn matmul_blocked_into<T>(a: &[T], b: &[T], c: &mut [T], m: usize, k: usize, n: usize, bs: usize)
+where
+    T: Copy + std::ops::AddAssign + std::ops::Mul<Output = T>,
+{
+    for ii in (0..m).step_by(bs) {
+        let i_end = (ii + bs).min(m);
+        for pp in (0..k).step_by(bs) {
+            let p_end = (pp + bs).min(k);
+            for jj in (0..n).step_by(bs) {
+                let j_end = (jj + bs).min(n);
+                for i in ii..i_end {
+                    for p in pp..p_end {
+                        let a_ip = a[i * k + p];
+                        let b_row = &b[p * n + jj..p * n + j_end];
+                        for (c_ij, &b_pj) in c[i * n + jj..i * n + j_end].iter_mut().zip(b_row) {
+                            *c_ij += a_ip * b_pj;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Blocked kernel over a pre-transposed B (n x k) for contiguous dot products
+fn matmul_transposed_into<T>(a: &[T], bt: &[T], c: &mut [T], m: usize, k: usize, n: usize, bs: usize)
+where
+    T: Copy + std::ops::AddAssign + std::ops::Mul<Output = T>,
+{
+    for ii in (0..m).step_by(bs) {
+        let i_end = (ii + bs).min(m);
+        for jj in (0..n).step_by(bs) {
+            let j_end = (jj + bs).min(n);
+            for i in ii..i_end {
+                let a_row = &a[i * k..(i + 1) * k];
+                for j in jj..j_end {
+                    let bt_row = &bt[j * k..(j + 1) * k];
+                    let c_ij = &mut c[i * n + j];
+                    for (&x, &y) in a_row.iter().zip(bt_row) {
+                        *c_ij += x * y;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Transpose into a new row-major buffer
+fn transpose_slice<T: Copy + Default>(matrix: &[T], rows: usize, cols: usize) -> Vec<T> {
+    let mut result = vec![T::default(); rows * cols];
+    for i in 0..rows {
+        for j in 0..cols {
+            result[j * rows + i] = matrix[i * cols + j];
+        }
+    }
+    result
+}
+
+/// Shared driver for the f64 and f32 entry points
+fn matmul_with<T>(a: &[T], b: &[T], m: usize, k: usize, n: usize, block_size: usize, transpose_b: bool) -> Vec<T>
+where
+    T: Copy + Default + std::ops::AddAssign + std::ops::Mul<Output = T>,
+{
+    let mut c = vec![T::default(); m * n];
+    let bs = if block_size == 0 { DEFAULT_BLOCK_SIZE } else { block_size };
+
+    if transpose_b {
+        let bt = transpose_slice(b, k, n);
+        matmul_transposed_into(a, &bt, &mut c, m, k, n, bs);
+    } else if m * k * n >= BLOCKED_MATMUL_THRESHOLD || block_size != 0 {
+        matmul_blocked_into(a, b, &mut c, m, k, n, bs);
+    } else {
+        matmul_naive_into(a, b, &mut c, m, k, n);
+    }
+
+    c
 }
 
 /// Matrix-matrix multiplication
 /// A (m x k) * B (k x n) = C (m x n)
-#[wasm_bindgen]
+///
+/// Small products use a simple loop; larger ones (m * k * n of at least
+/// 64^3) use the cache-blocked kernel with 64 x 64 tiles.
 pub fn matmul(a: &[f64], b: &[f64], m: usize, k: usize, n: usize) -> MatrixResult {
     if a.len() != m * k || b.len() != k * n {
         return MatrixResult {
             data: vec![],
             rows: 0,
             cols: 0,
+            warnings: 0,
         };
     }
     
-    let mut result = vec![0.0; m * n];
-    
-    // Basic matrix multiplication with loop reordering for cache efficiency
-    for i in 0..m {
-        for p in 0..k {
-            let a_ip = a[i * k + p];
-            for j in 0..n {
-                result[i * n + j] += a_ip * b[p * n + j];
-            }
-        }
+    MatrixResult {
+        data: matmul_with(a, b, m, k, n, 0, false),
+        rows: m,
+        cols: n,
+        warnings: 0,
     }
-    
+}
+
+/// Cache-blocked matrix multiplication with explicit tuning
+///
+/// # Arguments
+/// * `block_size` - Tile edge length (0 selects the default of 64)
+/// * `transpose_b` - Pre-transpose B so the inner loop reads both
+///   operands contiguously (costs one extra copy of B)
+pub fn matmul_blocked(
+    a: &[f64],
+    b: &[f64],
+    m: usize,
+    k: usize,
+    n: usize,
+    block_size: usize,
+    transpose_b: bool,
+) -> MatrixResult {
+    if a.len() != m * k || b.len() != k * n {
+        return MatrixResult {
+            data: vec![],
+            rows: 0,
+            cols: 0,
+            warnings: 0,
+        };
+    }
+
+    let bs = if block_size == 0 { DEFAULT_BLOCK_SIZE } else { block_size };
+
     MatrixResult {
-        data: result,
+        data: matmul_with(a, b, m, k, n, bs, transpose_b),
         rows: m,
         cols: n,
+        warnings: 0,
+    }
+}
+
+/// Single-precision matrix multiplication (half the memory bandwidth)
+///
+/// Returns the flattened m x n product, or an empty array on a
+/// dimension mismatch.
+pub fn matmul_f32(a: &[f32], b: &[f32], m: usize, k: usize, n: usize) -> Vec<f32> {
+    if a.len() != m * k || b.len() != k * n {
+        return vec![];
     }
+
+    matmul_with(a, b, m, k, n, 0, false)
 }
 
 /// Matrix transpose
-#[wasm_bindgen]
 pub fn transpose(matrix: &[f64], rows: usize, cols: usize) -> MatrixResult {
     if matrix.len() != rows * cols {
         return MatrixResult {
             data: vec![],
             rows: 0,
             cols: 0,
+            warnings: 0,
         };
     }
     
@@ -89,104 +310,533 @@ pub fn transpose(matrix: &[f64], rows: usize, cols: usize) -> MatrixResult {
         data: result,
         rows: cols,
         cols: rows,
+        warnings: 0,
     }
 }
 
-/// Calculate mean of each row
-#[wasm_bindgen]
-pub fn row_means(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
-    if matrix.len() != rows * cols || cols == 0 {
-        return vec![];
+/// Strides for walking a row-major matrix one lane at a time
+///
+/// Axis 0 treats each row as a lane, axis 1 treats each column as a lane.
+struct AxisLayout {
+    lanes: usize,
+    len: usize,
+    lane_stride: usize,
+    step: usize,
+}
+
+impl AxisLayout {
+    fn new(rows: usize, cols: usize, axis: u8) -> Option<AxisLayout> {
+        match axis {
+            0 => Some(AxisLayout { lanes: rows, len: cols, lane_stride: cols, step: 1 }),
+            1 => Some(AxisLayout { lanes: cols, len: rows, lane_stride: 1, step: cols }),
+            _ => None,
+        }
     }
-    
-    (0..rows)
-        .map(|i| {
-            let row_start = i * cols;
-            let row_sum: f64 = matrix[row_start..row_start + cols].iter().sum();
-            row_sum / cols as f64
+
+    fn index(&self, lane: usize, k: usize) -> usize {
+        lane * self.lane_stride + k * self.step
+    }
+
+    fn lane<'a>(&'a self, matrix: &'a [f64], lane: usize) -> impl Iterator<Item = f64> + 'a {
+        (0..self.len).map(move |k| matrix[self.index(lane, k)])
+    }
+}
+
+/// Check that `order` is a permutation of 0..n
+fn is_permutation(order: &[u32], n: usize) -> bool {
+    if order.len() != n {
+        return false;
+    }
+
+    let mut seen = vec![false; n];
+    order.iter().all(|&i| {
+        let i = i as usize;
+        i < n && !std::mem::replace(&mut seen[i], true)
+    })
+}
+
+/// Permute rows and columns in one pass
+///
+/// `row_order[i]` is the source row placed at output row `i` (likewise for
+/// columns). An empty order leaves that axis unchanged; any other order
+/// must be a permutation or an empty result is returned.
+pub fn reorder_matrix(
+    matrix: &[f64],
+    rows: usize,
+    cols: usize,
+    row_order: &[u32],
+    col_order: &[u32],
+) -> MatrixResult {
+    let identity = |n: usize| (0..n as u32).collect::<Vec<u32>>();
+    let row_order = if row_order.is_empty() { identity(rows) } else { row_order.to_vec() };
+    let col_order = if col_order.is_empty() { identity(cols) } else { col_order.to_vec() };
+
+    if matrix.len() != rows * cols
+        || !is_permutation(&row_order, rows)
+        || !is_permutation(&col_order, cols)
+    {
+        return MatrixResult {
+            data: vec![],
+            rows: 0,
+            cols: 0,
+            warnings: 0,
+        };
+    }
+
+    let data: Vec<f64> = row_order
+        .iter()
+        .flat_map(|&i| {
+            let row = &matrix[i as usize * cols..(i as usize + 1) * cols];
+            col_order.iter().map(move |&j| row[j as usize])
+        })
+        .collect();
+
+    MatrixResult { data, rows, cols, warnings: 0 }
+}
+
+/// Calculate mean of each row (axis 0) or each column (axis 1)
+pub fn means(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> Vec<f64> {
+    let layout = match AxisLayout::new(rows, cols, axis) {
+        Some(layout) if matrix.len() == rows * cols && layout.len > 0 => layout,
+        _ => return vec![],
+    };
+
+    (0..layout.lanes)
+        .map(|lane| layout.lane(matrix, lane).sum::<f64>() / layout.len as f64)
+        .collect()
+}
+
+/// Calculate standard deviation of each row (axis 0) or each column (axis 1)
+pub fn stds(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> Vec<f64> {
+    let layout = match AxisLayout::new(rows, cols, axis) {
+        Some(layout) if matrix.len() == rows * cols && layout.len > 1 => layout,
+        _ => return vec![],
+    };
+
+    let means = means(matrix, rows, cols, axis);
+
+    (0..layout.lanes)
+        .map(|lane| {
+            let mean = means[lane];
+            let variance: f64 = layout
+                .lane(matrix, lane)
+                .map(|x| (x - mean).powi(2))
+                .sum::<f64>() / (layout.len - 1) as f64;
+            variance.sqrt()
         })
         .collect()
 }
 
+/// Calculate mean of each row
+pub fn row_means(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    means(matrix, rows, cols, 0)
+}
+
 /// Calculate mean of each column
-#[wasm_bindgen]
 pub fn col_means(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
-    if matrix.len() != rows * cols || rows == 0 {
-        return vec![];
+    means(matrix, rows, cols, 1)
+}
+
+/// Calculate standard deviation of each row
+pub fn row_stds(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    stds(matrix, rows, cols, 0)
+}
+
+/// Calculate standard deviation of each column
+pub fn col_stds(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+    stds(matrix, rows, cols, 1)
+}
+
+/// Z-score normalize each row (axis 0) or each column (axis 1)
+pub fn zscore_normalize(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> MatrixResult {
+    let layout = match AxisLayout::new(rows, cols, axis) {
+        Some(layout) if matrix.len() == rows * cols => layout,
+        _ => {
+            return MatrixResult {
+                data: vec![],
+                rows: 0,
+                cols: 0,
+                warnings: 0,
+            };
+        }
+    };
+    
+    let means = means(matrix, rows, cols, axis);
+    let stds = stds(matrix, rows, cols, axis);
+    
+    let mut data = vec![0.0; rows * cols];
+    
+    for (lane, &mean) in means.iter().enumerate() {
+        let std = match stds.get(lane) {
+            Some(&s) if s > 0.0 => s,
+            _ => 1.0,
+        };
+        
+        for k in 0..layout.len {
+            let idx = layout.index(lane, k);
+            data[idx] = (matrix[idx] - mean) / std;
+        }
     }
     
-    (0..cols)
-        .map(|j| {
-            let col_sum: f64 = (0..rows).map(|i| matrix[i * cols + j]).sum();
-            col_sum / rows as f64
+    MatrixResult { data, rows, cols, warnings: 0 }
+}
+
+/// Median of a slice of non-NaN values (reorders the slice)
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Robust z-score normalize each row (axis 0) or each column (axis 1)
+///
+/// Centers each lane by its median and scales by 1.4826 * MAD, falling
+/// back to the standard deviation when the MAD is zero. NaN entries are
+/// ignored in the statistics and stay NaN in the output.
+pub fn robust_zscore(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> MatrixResult {
+    let layout = match AxisLayout::new(rows, cols, axis) {
+        Some(layout) if matrix.len() == rows * cols => layout,
+        _ => {
+            return MatrixResult {
+                data: vec![],
+                rows: 0,
+                cols: 0,
+                warnings: 0,
+            };
+        }
+    };
+
+    let mut data = vec![f64::NAN; rows * cols];
+
+    for lane in 0..layout.lanes {
+        let mut valid: Vec<f64> = layout.lane(matrix, lane).filter(|x| !x.is_nan()).collect();
+
+        if valid.is_empty() {
+            continue;
+        }
+
+        let center = median(&mut valid);
+        let mut deviations: Vec<f64> = valid.iter().map(|x| (x - center).abs()).collect();
+        let mad = median(&mut deviations);
+
+        let scale = if mad > 0.0 {
+            1.4826 * mad
+        } else if valid.len() > 1 {
+            let mean = valid.iter().sum::<f64>() / valid.len() as f64;
+            let variance = valid.iter().map(|x| (x - mean).powi(2)).sum::<f64>()
+                / (valid.len() - 1) as f64;
+            if variance > 0.0 { variance.sqrt() } else { 1.0 }
+        } else {
+            1.0
+        };
+
+        for k in 0..layout.len {
+            let idx = layout.index(lane, k);
+            if !matrix[idx].is_nan() {
+                data[idx] = (matrix[idx] - center) / scale;
+            }
+        }
+    }
+
+    MatrixResult { data, rows, cols, warnings: 0 }
+}
+
+/// Linearly interpolate a sorted slice at a fractional index
+fn interpolate_sorted(sorted: &[f64], position: f64) -> f64 {
+    let lower = position.floor() as usize;
+    let upper = position.ceil() as usize;
+
+    if lower == upper || upper >= sorted.len() {
+        return sorted[lower.min(sorted.len() - 1)];
+    }
+
+    let frac = position - lower as f64;
+    sorted[lower] * (1.0 - frac) + sorted[upper] * frac
+}
+
+/// Quantile normalize rows (axis 0) or columns (axis 1) to a common distribution
+///
+/// Use axis 1 when columns are samples. Each lane is ranked, the values
+/// at each rank are averaged across lanes to form the reference
+/// distribution, and every entry is replaced by the reference value for
+/// its rank. Tied entries receive the mean of the reference values
+/// spanned by the tie. Lanes with NaN entries are mapped onto the
+/// reference by interpolation and keep their NaNs.
+pub fn quantile_normalize(matrix: &[f64], rows: usize, cols: usize, axis: u8) -> MatrixResult {
+    let layout = match AxisLayout::new(rows, cols, axis) {
+        Some(layout) if matrix.len() == rows * cols && layout.len > 0 => layout,
+        _ => {
+            return MatrixResult {
+                data: vec![],
+                rows: 0,
+                cols: 0,
+                warnings: 0,
+            };
+        }
+    };
+    let len = layout.len;
+
+    // Sorted non-NaN values for each lane, paired with their flat index
+    let lanes: Vec<Vec<(usize, f64)>> = (0..layout.lanes)
+        .map(|lane| {
+            let mut values: Vec<(usize, f64)> = (0..len)
+                .map(|k| {
+                    let idx = layout.index(lane, k);
+                    (idx, matrix[idx])
+                })
+                .filter(|(_, x)| !x.is_nan())
+                .collect();
+            values.sort_by(|a, b| a.1.total_cmp(&b.1));
+            values
         })
-        .collect()
+        .collect();
+
+    // Reference distribution: mean of each quantile across lanes
+    let mut reference = vec![0.0; len];
+    let mut contributing = 0usize;
+
+    for values in lanes.iter().filter(|v| !v.is_empty()) {
+        let sorted: Vec<f64> = values.iter().map(|&(_, x)| x).collect();
+        let step = if len > 1 { (sorted.len() - 1) as f64 / (len - 1) as f64 } else { 0.0 };
+
+        for (k, value) in reference.iter_mut().enumerate() {
+            *value += interpolate_sorted(&sorted, k as f64 * step);
+        }
+        contributing += 1;
+    }
+
+    if contributing == 0 {
+        return MatrixResult {
+            data: matrix.to_vec(),
+            rows,
+            cols,
+            warnings: 0,
+        };
+    }
+
+    for value in reference.iter_mut() {
+        *value /= contributing as f64;
+    }
+
+    let mut data = vec![f64::NAN; rows * cols];
+
+    for values in &lanes {
+        let n = values.len();
+        let step = if n > 1 { (len - 1) as f64 / (n - 1) as f64 } else { 0.0 };
+
+        let mut start = 0;
+        while start < n {
+            // Find ties
+            let mut end = start;
+            while end < n && values[end].1 == values[start].1 {
+                end += 1;
+            }
+
+            let tied_mean = (start..end)
+                .map(|k| interpolate_sorted(&reference, k as f64 * step))
+                .sum::<f64>() / (end - start) as f64;
+
+            for &(idx, _) in &values[start..end] {
+                data[idx] = tied_mean;
+            }
+            start = end;
+        }
+    }
+
+    MatrixResult { data, rows, cols, warnings: 0 }
 }
 
-/// Calculate standard deviation of each row
-#[wasm_bindgen]
-pub fn row_stds(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
+/// Counts-per-million normalization (columns are samples/cells)
+///
+/// Scales each column to sum to 1e6, then optionally applies log1p.
+/// Columns with a zero total are left as zeros and counted in
+/// `warnings()`.
+pub fn cpm_normalize(matrix: &[f64], rows: usize, cols: usize, log1p: bool) -> MatrixResult {
+    if matrix.len() != rows * cols {
+        return MatrixResult {
+            data: vec![],
+            rows: 0,
+            cols: 0,
+            warnings: 0,
+        };
+    }
+
+    let totals: Vec<f64> = (0..cols)
+        .map(|j| (0..rows).map(|i| matrix[i * cols + j]).sum())
+        .collect();
+    let warnings = totals.iter().filter(|&&t| t == 0.0).count() as u32;
+
+    let data: Vec<f64> = matrix
+        .iter()
+        .enumerate()
+        .map(|(idx, &x)| {
+            let total = totals[idx % cols];
+            if total == 0.0 {
+                return 0.0;
+            }
+            let cpm = x / total * 1e6;
+            if log1p { cpm.ln_1p() } else { cpm }
+        })
+        .collect();
+
+    MatrixResult { data, rows, cols, warnings }
+}
+
+/// Calculate sample variance of each row
+pub fn row_variances(matrix: &[f64], rows: usize, cols: usize) -> Vec<f64> {
     if matrix.len() != rows * cols || cols < 2 {
         return vec![];
     }
-    
+
     let means = row_means(matrix, rows, cols);
-    
+
     (0..rows)
         .map(|i| {
             let row_start = i * cols;
             let mean = means[i];
-            let variance: f64 = matrix[row_start..row_start + cols]
+            matrix[row_start..row_start + cols]
                 .iter()
                 .map(|&x| (x - mean).powi(2))
-                .sum::<f64>() / (cols - 1) as f64;
-            variance.sqrt()
+                .sum::<f64>() / (cols - 1) as f64
         })
         .collect()
 }
 
-/// Z-score normalize matrix (row-wise)
-#[wasm_bindgen]
-pub fn zscore_normalize(matrix: &[f64], rows: usize, cols: usize) -> MatrixResult {
-    if matrix.len() != rows * cols {
-        return MatrixResult {
+/// Most variable rows selected by `top_variable_rows`
+pub struct TopRowsResult {
+    indices: Vec<u32>,
+    variances: Vec<f64>,
+    data: Vec<f64>,
+    cols: usize,
+}
+
+impl TopRowsResult {
+    /// Selected row indices, most variable first
+    pub fn indices(&self) -> Vec<u32> {
+        self.indices.clone()
+    }
+
+    /// Variance of each selected row
+    pub fn variances(&self) -> Vec<f64> {
+        self.variances.clone()
+    }
+
+    /// Submatrix of the selected rows (in `indices` order)
+    pub fn submatrix(&self) -> MatrixResult {
+        MatrixResult {
+            data: self.data.clone(),
+            rows: self.indices.len(),
+            cols: self.cols,
+            warnings: 0,
+        }
+    }
+}
+
+/// Select the `n` rows with the highest variance (e.g. top variable genes)
+///
+/// Uses a partial selection, so only the selected rows are sorted.
+/// Rows with NaN variance rank last.
+pub fn top_variable_rows(matrix: &[f64], rows: usize, cols: usize, n: usize) -> TopRowsResult {
+    let variances = row_variances(matrix, rows, cols);
+    if variances.is_empty() || n == 0 {
+        return TopRowsResult {
+            indices: vec![],
+            variances: vec![],
             data: vec![],
-            rows: 0,
             cols: 0,
         };
     }
-    
-    let means = row_means(matrix, rows, cols);
-    let stds = row_stds(matrix, rows, cols);
-    
-    let data: Vec<f64> = (0..rows)
-        .flat_map(|i| {
-            let mean = means[i];
-            let std = if stds[i] > 0.0 { stds[i] } else { 1.0 };
-            
-            (0..cols).map(move |j| {
-                (matrix[i * cols + j] - mean) / std
-            })
-        })
-        .collect();
-    
-    MatrixResult { data, rows, cols }
+
+    // Highest variance first, NaN last, ties broken by row index
+    let key = |i: usize| if variances[i].is_nan() { f64::NEG_INFINITY } else { variances[i] };
+    let by_variance = |a: &usize, b: &usize| key(*b).total_cmp(&key(*a)).then(a.cmp(b));
+
+    let n = n.min(rows);
+    let mut order: Vec<usize> = (0..rows).collect();
+    if n < rows {
+        order.select_nth_unstable_by(n - 1, by_variance);
+        order.truncate(n);
+    }
+    order.sort_by(by_variance);
+
+    let mut data = Vec::with_capacity(n * cols);
+    for &i in &order {
+        data.extend_from_slice(&matrix[i * cols..(i + 1) * cols]);
+    }
+
+    TopRowsResult {
+        indices: order.iter().map(|&i| i as u32).collect(),
+        variances: order.iter().map(|&i| variances[i]).collect(),
+        data,
+        cols,
+    }
 }
 
-/// Calculate Pearson correlation between all row pairs
+/// Calculate correlation between all row pairs
 /// Returns correlation matrix (rows x rows)
-#[wasm_bindgen]
-pub fn correlation_matrix(matrix: &[f64], rows: usize, cols: usize) -> MatrixResult {
+///
+/// `method` is one of `"pearson"`, `"spearman"` or `"kendall"` (tau-b);
+/// unknown methods return an empty result.
+///
+/// The Pearson path (z-score then dot products) is the fastest and
+/// assumes the matrix has no missing values; a single NaN poisons its
+/// whole row and column. Use `correlation_matrix_pairwise` for matrices
+/// with NaNs.
+pub fn correlation_matrix(matrix: &[f64], rows: usize, cols: usize, method: &str) -> MatrixResult {
+    let empty = MatrixResult {
+        data: vec![],
+        rows: 0,
+        cols: 0,
+        warnings: 0,
+    };
+
     if matrix.len() != rows * cols || cols < 2 {
-        return MatrixResult {
-            data: vec![],
-            rows: 0,
-            cols: 0,
-        };
+        return empty;
+    }
+
+    match method {
+        "pearson" => pearson_matrix(matrix, rows, cols),
+        "spearman" => {
+            let ranked: Vec<f64> = matrix.chunks(cols).flat_map(rank).collect();
+            pearson_matrix(&ranked, rows, cols)
+        }
+        "kendall" => {
+            let mut corr = vec![0.0; rows * rows];
+
+            for i in 0..rows {
+                let row_i = &matrix[i * cols..(i + 1) * cols];
+                for j in i..rows {
+                    let row_j = &matrix[j * cols..(j + 1) * cols];
+                    let tau = kendall_tau(row_i, row_j);
+                    corr[i * rows + j] = tau;
+                    corr[j * rows + i] = tau;
+                }
+            }
+
+            MatrixResult {
+                data: corr,
+                rows,
+                cols: rows,
+                warnings: 0,
+            }
+        }
+        _ => empty,
     }
-    
+}
+
+/// Pearson correlation matrix via z-scored rows
+fn pearson_matrix(matrix: &[f64], rows: usize, cols: usize) -> MatrixResult {
     // First z-score normalize
-    let normalized = zscore_normalize(matrix, rows, cols);
+    let normalized = zscore_normalize(matrix, rows, cols, 0);
     let norm_data = &normalized.data;
     
     let mut corr = vec![0.0; rows * rows];
@@ -206,11 +856,11 @@ pub fn correlation_matrix(matrix: &[f64], rows: usize, cols: usize) -> MatrixRes
         data: corr,
         rows,
         cols: rows,
+        warnings: 0,
     }
 }
 
 /// Pearson correlation between two vectors
-#[wasm_bindgen]
 pub fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
     if x.len() != y.len() || x.is_empty() {
         return f64::NAN;
@@ -240,8 +890,184 @@ pub fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
     cov / (var_x * var_y).sqrt()
 }
 
+/// Minimum number of complete pairs for a pairwise statistic
+const MIN_PAIRWISE_OBS: usize = 3;
+
+/// Value pairs at the indices where neither vector is NaN
+fn complete_pairs(x: &[f64], y: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    x.iter()
+        .zip(y.iter())
+        .filter(|(a, b)| !a.is_nan() && !b.is_nan())
+        .map(|(&a, &b)| (a, b))
+        .unzip()
+}
+
+/// Pearson correlation using pairwise-complete observations
+///
+/// Index pairs where either value is NaN are dropped. Returns NaN when
+/// fewer than 3 complete pairs remain.
+pub fn pearson_correlation_pairwise(x: &[f64], y: &[f64]) -> f64 {
+    if x.len() != y.len() {
+        return f64::NAN;
+    }
+
+    let (xs, ys) = complete_pairs(x, y);
+    if xs.len() < MIN_PAIRWISE_OBS {
+        return f64::NAN;
+    }
+
+    pearson_correlation(&xs, &ys)
+}
+
+/// Pairwise-complete matrix statistic with per-cell observation counts
+pub struct PairwiseResult {
+    values: MatrixResult,
+    counts: MatrixResult,
+}
+
+impl PairwiseResult {
+    /// Statistic for each row pair (rows x rows)
+    pub fn values(&self) -> MatrixResult {
+        self.values.clone()
+    }
+
+    /// Number of complete observations behind each cell (rows x rows)
+    pub fn counts(&self) -> MatrixResult {
+        self.counts.clone()
+    }
+}
+
+/// Apply a pairwise-complete statistic to every row pair
+fn pairwise_rows<F>(matrix: &[f64], rows: usize, cols: usize, stat: F) -> PairwiseResult
+where
+    F: Fn(&[f64], &[f64]) -> f64,
+{
+    if matrix.len() != rows * cols {
+        let empty = MatrixResult {
+            data: vec![],
+            rows: 0,
+            cols: 0,
+            warnings: 0,
+        };
+        return PairwiseResult {
+            values: empty.clone(),
+            counts: empty,
+        };
+    }
+
+    let mut values = vec![f64::NAN; rows * rows];
+    let mut counts = vec![0.0; rows * rows];
+
+    for i in 0..rows {
+        let row_i = &matrix[i * cols..(i + 1) * cols];
+        for j in i..rows {
+            let row_j = &matrix[j * cols..(j + 1) * cols];
+            let (xs, ys) = complete_pairs(row_i, row_j);
+
+            let value = if xs.len() < MIN_PAIRWISE_OBS { f64::NAN } else { stat(&xs, &ys) };
+
+            values[i * rows + j] = value;
+            values[j * rows + i] = value;
+            counts[i * rows + j] = xs.len() as f64;
+            counts[j * rows + i] = xs.len() as f64;
+        }
+    }
+
+    // Cells with too few complete pairs
+    let warnings = (0..rows)
+        .flat_map(|i| (i..rows).map(move |j| (i, j)))
+        .filter(|&(i, j)| values[i * rows + j].is_nan())
+        .count() as u32;
+
+    PairwiseResult {
+        values: MatrixResult { data: values, rows, cols: rows, warnings },
+        counts: MatrixResult { data: counts, rows, cols: rows, warnings: 0 },
+    }
+}
+
+/// Pearson correlation between all row pairs using pairwise-complete observations
+///
+/// Slower than `correlation_matrix` (each pair is recomputed from
+/// scratch) but tolerates missing values the way pandas' `DataFrame.corr()`
+/// does. Cells with fewer than 3 complete pairs are NaN and counted in
+/// `values().warnings()`.
+pub fn correlation_matrix_pairwise(matrix: &[f64], rows: usize, cols: usize) -> PairwiseResult {
+    pairwise_rows(matrix, rows, cols, pearson_correlation)
+}
+
+/// Covariance between all row pairs using pairwise-complete observations
+///
+/// NaN-tolerant counterpart of `covariance_matrix`, matching pandas'
+/// `DataFrame.cov()` (sample covariance over the complete pairs).
+pub fn covariance_matrix_pairwise(matrix: &[f64], rows: usize, cols: usize) -> PairwiseResult {
+    pairwise_rows(matrix, rows, cols, |x, y| {
+        let n = x.len() as f64;
+        let mean_x = x.iter().sum::<f64>() / n;
+        let mean_y = y.iter().sum::<f64>() / n;
+        x.iter()
+            .zip(y.iter())
+            .map(|(a, b)| (a - mean_x) * (b - mean_y))
+            .sum::<f64>() / (n - 1.0)
+    })
+}
+
+/// Partial correlation of x and y controlling for covariates
+///
+/// # Arguments
+/// * `z` - Covariates, flattened observations x covariates
+///   (`x.len()` rows, `n_covariates` columns)
+///
+/// x and y are residualized against an intercept plus the covariate
+/// columns by least squares, and the residuals are correlated.
+/// Returns an error if the covariates are rank-deficient.
+pub fn partial_correlation(
+    x: &[f64],
+    y: &[f64],
+    z: &[f64],
+    n_covariates: usize,
+) -> Result<f64, String> {
+    if x.len() != y.len() {
+        return Err(format!("x has {} values but y has {}", x.len(), y.len()));
+    }
+
+    let design = CovariateDesign::new(z, x.len(), n_covariates)?;
+    Ok(pearson_correlation(&design.residuals(x), &design.residuals(y)))
+}
+
+/// Partial correlation between all row pairs controlling for covariates
+///
+/// # Arguments
+/// * `covariates` - Flattened samples x covariates (`cols` rows, `n_cov` columns)
+///
+/// Every row is residualized against the covariates before computing
+/// the Pearson correlation matrix (rows x rows). Returns an error if the
+/// covariates are rank-deficient.
+pub fn partial_correlation_matrix(
+    matrix: &[f64],
+    rows: usize,
+    cols: usize,
+    covariates: &[f64],
+    n_cov: usize,
+) -> Result<MatrixResult, String> {
+    if matrix.len() != rows * cols || cols < 2 {
+        return Ok(MatrixResult {
+            data: vec![],
+            rows: 0,
+            cols: 0,
+            warnings: 0,
+        });
+    }
+
+    let design = CovariateDesign::new(covariates, cols, n_cov)?;
+    let residuals: Vec<f64> = matrix
+        .chunks(cols)
+        .flat_map(|row| design.residuals(row))
+        .collect();
+
+    Ok(pearson_matrix(&residuals, rows, cols))
+}
+
 /// Spearman correlation (rank-based)
-#[wasm_bindgen]
 pub fn spearman_correlation(x: &[f64], y: &[f64]) -> f64 {
     if x.len() != y.len() || x.is_empty() {
         return f64::NAN;
@@ -278,14 +1104,210 @@ fn rank(values: &[f64]) -> Vec<f64> {
     ranks
 }
 
+/// Tie statistics for the tau-b correction and its variance
+#[derive(Default)]
+struct TieStats {
+    /// Number of tied pairs: sum of t(t-1)/2
+    pairs: u64,
+    /// Sum of t(t-1)(t-2)
+    v1: f64,
+    /// Sum of t(t-1)(2t+5)
+    v2: f64,
+}
+
+impl TieStats {
+    fn add_group(&mut self, t: u64) {
+        if t > 1 {
+            let t_f = t as f64;
+            self.pairs += t * (t - 1) / 2;
+            self.v1 += t_f * (t_f - 1.0) * (t_f - 2.0);
+            self.v2 += t_f * (t_f - 1.0) * (2.0 * t_f + 5.0);
+        }
+    }
+
+    /// Tie statistics of an already-sorted slice
+    fn from_sorted(sorted: &[f64]) -> Self {
+        let mut stats = TieStats::default();
+        let mut i = 0;
+        while i < sorted.len() {
+            let mut j = i;
+            while j < sorted.len() && sorted[j] == sorted[i] {
+                j += 1;
+            }
+            stats.add_group((j - i) as u64);
+            i = j;
+        }
+        stats
+    }
+}
+
+/// Concordance counts behind Kendall's tau
+struct KendallCounts {
+    n: u64,
+    /// Concordant minus discordant pairs
+    score: f64,
+    x_ties: TieStats,
+    y_ties: TieStats,
+}
+
+/// Sort `values` in place, returning the number of strict inversions
+fn merge_sort_inversions(values: &mut [f64], buffer: &mut Vec<f64>) -> u64 {
+    let n = values.len();
+    if n < 2 {
+        return 0;
+    }
+
+    let mid = n / 2;
+    let mut inversions = merge_sort_inversions(&mut values[..mid], buffer)
+        + merge_sort_inversions(&mut values[mid..], buffer);
+
+    buffer.clear();
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < n {
+        if values[j] < values[i] {
+            // Every remaining left element is greater than values[j]
+            inversions += (mid - i) as u64;
+            buffer.push(values[j]);
+            j += 1;
+        } else {
+            buffer.push(values[i]);
+            i += 1;
+        }
+    }
+    buffer.extend_from_slice(&values[i..mid]);
+    buffer.extend_from_slice(&values[j..n]);
+    values.copy_from_slice(buffer);
+
+    inversions
+}
+
+/// Knight's O(n log n) concordance counting
+fn kendall_counts(x: &[f64], y: &[f64]) -> KendallCounts {
+    let n = x.len();
+
+    // Sort by x, breaking ties by y
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&a, &b| x[a].total_cmp(&x[b]).then(y[a].total_cmp(&y[b])));
+
+    let mut x_ties = TieStats::default();
+    let mut joint_ties = 0u64;
+
+    let mut i = 0;
+    while i < n {
+        let mut j = i;
+        while j < n && x[order[j]] == x[order[i]] {
+            j += 1;
+        }
+        x_ties.add_group((j - i) as u64);
+
+        // Within an x tie, y is sorted, so joint ties are adjacent
+        let mut k = i;
+        while k < j {
+            let mut l = k;
+            while l < j && y[order[l]] == y[order[k]] {
+                l += 1;
+            }
+            let t = (l - k) as u64;
+            joint_ties += t * t.saturating_sub(1) / 2;
+            k = l;
+        }
+        i = j;
+    }
+
+    // Discordant pairs are the inversions of y in x order
+    let mut ys: Vec<f64> = order.iter().map(|&i| y[i]).collect();
+    let mut buffer = Vec::with_capacity(n);
+    let swaps = merge_sort_inversions(&mut ys, &mut buffer);
+    let y_ties = TieStats::from_sorted(&ys);
+
+    let n0 = (n as u64) * (n as u64).saturating_sub(1) / 2;
+    let score = n0 as f64 - x_ties.pairs as f64 - y_ties.pairs as f64 + joint_ties as f64
+        - 2.0 * swaps as f64;
+
+    KendallCounts {
+        n: n as u64,
+        score,
+        x_ties,
+        y_ties,
+    }
+}
+
+/// Kendall's tau-b rank correlation
+///
+/// Uses the O(n log n) merge-sort algorithm with tie corrections for
+/// both vectors. Returns NaN for mismatched lengths, n < 2, NaN input,
+/// or a constant vector.
+pub fn kendall_tau(x: &[f64], y: &[f64]) -> f64 {
+    if x.len() != y.len() || x.len() < 2 {
+        return f64::NAN;
+    }
+    if x.iter().chain(y.iter()).any(|v| v.is_nan()) {
+        return f64::NAN;
+    }
+
+    let counts = kendall_counts(x, y);
+    let n0 = (counts.n * (counts.n - 1) / 2) as f64;
+    let denom = ((n0 - counts.x_ties.pairs as f64) * (n0 - counts.y_ties.pairs as f64)).sqrt();
+
+    if denom == 0.0 {
+        return f64::NAN;
+    }
+
+    counts.score / denom
+}
+
+/// Test statistic with its p-value
+pub struct TestResult {
+    statistic: f64,
+    p_value: f64,
+}
+
+impl TestResult {
+    pub fn statistic(&self) -> f64 { self.statistic }
+    pub fn p_value(&self) -> f64 { self.p_value }
+}
+
+/// Kendall's tau-b with a two-tailed p-value
+///
+/// The p-value uses the tie-corrected normal approximation
+/// (as scipy's `kendalltau(..., method="asymptotic")`).
+pub fn kendall_tau_test(x: &[f64], y: &[f64]) -> TestResult {
+    let tau = kendall_tau(x, y);
+    if tau.is_nan() {
+        return TestResult {
+            statistic: f64::NAN,
+            p_value: f64::NAN,
+        };
+    }
+
+    let counts = kendall_counts(x, y);
+    let n = counts.n as f64;
+    let m = n * (n - 1.0);
+    let (xt, yt) = (&counts.x_ties, &counts.y_ties);
+
+    let mut variance = (m * (2.0 * n + 5.0) - xt.v2 - yt.v2) / 18.0
+        + 2.0 * xt.pairs as f64 * yt.pairs as f64 / m;
+    if n > 2.0 {
+        variance += xt.v1 * yt.v1 / (9.0 * m * (n - 2.0));
+    }
+
+    TestResult {
+        statistic: tau,
+        p_value: normal_two_tailed(counts.score / variance.sqrt()),
+    }
+}
+
 /// Calculate covariance matrix
-#[wasm_bindgen]
+///
+/// Assumes no missing values; use `covariance_matrix_pairwise` for
+/// matrices with NaNs.
 pub fn covariance_matrix(matrix: &[f64], rows: usize, cols: usize) -> MatrixResult {
     if matrix.len() != rows * cols || cols < 2 {
         return MatrixResult {
             data: vec![],
             rows: 0,
             cols: 0,
+            warnings: 0,
         };
     }
     
@@ -309,11 +1331,11 @@ pub fn covariance_matrix(matrix: &[f64], rows: usize, cols: usize) -> MatrixResu
         data: cov,
         rows,
         cols: rows,
+        warnings: 0,
     }
 }
 
 /// Element-wise matrix addition
-#[wasm_bindgen]
 pub fn matrix_add(a: &[f64], b: &[f64]) -> Vec<f64> {
     if a.len() != b.len() {
         return vec![];
@@ -323,19 +1345,16 @@ pub fn matrix_add(a: &[f64], b: &[f64]) -> Vec<f64> {
 }
 
 /// Scalar multiplication
-#[wasm_bindgen]
 pub fn matrix_scale(matrix: &[f64], scalar: f64) -> Vec<f64> {
     matrix.iter().map(|x| x * scalar).collect()
 }
 
 /// Sum of all elements
-#[wasm_bindgen]
 pub fn matrix_sum(matrix: &[f64]) -> f64 {
     matrix.iter().sum()
 }
 
 /// Find min and max values
-#[wasm_bindgen]
 pub struct MinMax {
     min: f64,
     max: f64,
@@ -343,7 +1362,6 @@ pub struct MinMax {
     max_idx: usize,
 }
 
-#[wasm_bindgen]
 impl MinMax {
     pub fn min(&self) -> f64 { self.min }
     pub fn max(&self) -> f64 { self.max }
@@ -351,7 +1369,6 @@ impl MinMax {
     pub fn max_index(&self) -> usize { self.max_idx }
 }
 
-#[wasm_bindgen]
 pub fn matrix_minmax(matrix: &[f64]) -> MinMax {
     if matrix.is_empty() {
         return MinMax {
@@ -409,7 +1426,7 @@ mod tests {
     #[test]
     fn test_zscore() {
         let matrix = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
-        let result = zscore_normalize(&matrix, 2, 3);
+        let result = zscore_normalize(&matrix, 2, 3, 0);
         
         // Check that means are ~0 for each row
         for i in 0..2 {
@@ -419,4 +1436,404 @@ mod tests {
             assert!(row_mean.abs() < 1e-10);
         }
     }
+
+    #[test]
+    fn test_robust_zscore() {
+        let matrix = vec![
+            1.0, 2.0, 3.0, 4.0, 100.0,
+            1.0, 1.0, f64::NAN, 1.0, 5.0,
+        ];
+        let result = robust_zscore(&matrix, 2, 5, 0);
+
+        // Row 0: median 3, MAD 1
+        assert!((result.get(0, 0) - (-2.0 / 1.4826)).abs() < 1e-10);
+        assert!((result.get(0, 2)).abs() < 1e-10);
+
+        // Row 1: MAD is zero, falls back to std of [1, 1, 1, 5] = 2
+        assert!((result.get(1, 4) - 2.0).abs() < 1e-10);
+        assert!(result.get(1, 2).is_nan());
+    }
+
+    #[test]
+    fn test_quantile_normalize() {
+        // 4 genes x 3 samples, no ties within a column
+        let matrix = vec![
+            5.0, 4.0, 3.0,
+            2.0, 1.0, 4.0,
+            3.0, 6.0, 6.5,
+            4.0, 2.0, 8.0,
+        ];
+        let result = quantile_normalize(&matrix, 4, 3, 1);
+
+        let sorted_col = |j: usize| {
+            let mut col: Vec<f64> = (0..4).map(|i| result.get(i, j)).collect();
+            col.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            col
+        };
+
+        let reference = sorted_col(0);
+        for j in 1..3 {
+            for (a, b) in reference.iter().zip(sorted_col(j).iter()) {
+                assert!((a - b).abs() < 1e-10);
+            }
+        }
+        assert!((reference[0] - 2.0).abs() < 1e-10);
+        assert!((reference[3] - 19.0 / 3.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_quantile_normalize_ties_and_nan() {
+        // Column 1 has a tie at the top two ranks
+        let matrix = vec![
+            5.0, 4.0, 3.0,
+            2.0, 1.0, 4.0,
+            3.0, 4.0, 6.0,
+            4.0, 2.0, 8.0,
+        ];
+        let result = quantile_normalize(&matrix, 4, 3, 1);
+
+        let tied = (14.0 / 3.0 + 17.0 / 3.0) / 2.0;
+        assert!((result.get(0, 1) - tied).abs() < 1e-10);
+        assert!((result.get(2, 1) - tied).abs() < 1e-10);
+        assert!((result.get(1, 1) - 2.0).abs() < 1e-10);
+
+        let mut with_nan = matrix.clone();
+        with_nan[4] = f64::NAN;
+        let result = quantile_normalize(&with_nan, 4, 3, 1);
+        assert!(result.get(1, 1).is_nan());
+        assert!(result.get(0, 1).is_finite());
+    }
+
+    #[test]
+    fn test_cpm_normalize() {
+        let matrix = vec![
+            10.0, 0.0, 3.0,
+            30.0, 0.0, 1.0,
+            60.0, 0.0, 6.0,
+        ];
+        let result = cpm_normalize(&matrix, 3, 3, false);
+
+        for j in [0, 2] {
+            let col_sum: f64 = (0..3).map(|i| result.get(i, j)).sum();
+            assert!((col_sum - 1e6).abs() < 1e-6);
+        }
+        assert_eq!(result.get(1, 1), 0.0);
+        assert_eq!(result.warnings(), 1);
+
+        let logged = cpm_normalize(&matrix, 3, 3, true);
+        assert!((logged.get(0, 0) - 100_000f64.ln_1p()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_top_variable_rows_matches_full_sort() {
+        // Deterministic pseudo-random matrix (LCG)
+        let (rows, cols) = (50, 8);
+        let mut state = 12345u64;
+        let matrix: Vec<f64> = (0..rows * cols)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 33) as f64 / (1u64 << 31) as f64 * ((state % 7) as f64 + 1.0)
+            })
+            .collect();
+
+        let variances = row_variances(&matrix, rows, cols);
+        let mut expected: Vec<usize> = (0..rows).collect();
+        expected.sort_by(|&a, &b| variances[b].partial_cmp(&variances[a]).unwrap());
+
+        let result = top_variable_rows(&matrix, rows, cols, 10);
+        let indices: Vec<usize> = result.indices().iter().map(|&i| i as usize).collect();
+        assert_eq!(indices, expected[..10].to_vec());
+
+        let sub = result.submatrix();
+        assert_eq!(sub.rows(), 10);
+        assert_eq!(sub.get(0, 3), matrix[expected[0] * cols + 3]);
+    }
+
+    #[test]
+    fn test_pearson_correlation_pairwise() {
+        let x = vec![1.0, 2.0, f64::NAN, 4.0, 5.0];
+        let y = vec![2.0, 4.0, 6.0, f64::NAN, 10.0];
+        assert!((pearson_correlation_pairwise(&x, &y) - 1.0).abs() < 1e-10);
+
+        // Only two complete pairs
+        let y = vec![f64::NAN, 4.0, 6.0, f64::NAN, 10.0];
+        assert!(pearson_correlation_pairwise(&x, &y).is_nan());
+    }
+
+    #[test]
+    fn test_correlation_matrix_pairwise() {
+        // 4 x 10 with 10% missing; expected values from pandas DataFrame.T.corr()
+        let nan = f64::NAN;
+        let matrix = vec![
+            1.2, 2.3, nan, 4.1, 5.0, 6.2, 7.1, 8.3, 9.0, 10.4,
+            2.0, 1.8, 3.5, 3.9, nan, 6.8, 6.9, 9.1, 8.7, 11.0,
+            9.5, 8.1, 7.7, nan, 5.2, 4.4, 3.9, 2.2, 1.5, 0.3,
+            3.3, 1.1, 4.4, 1.5, 5.9, 2.6, 5.3, nan, 3.5, 8.9,
+        ];
+        let result = correlation_matrix_pairwise(&matrix, 4, 10);
+        let corr = result.values();
+        let counts = result.counts();
+
+        assert!(corr.data().iter().all(|r| r.is_finite()));
+        assert!((corr.get(0, 1) - 0.987173934160270).abs() < 1e-12);
+        assert!((corr.get(0, 2) - -0.997308192289822).abs() < 1e-12);
+        assert!((corr.get(2, 3) - -0.610473341898457).abs() < 1e-12);
+        assert!((corr.get(3, 1) - 0.729174748283824).abs() < 1e-12);
+        assert!((corr.get(3, 3) - 1.0).abs() < 1e-12);
+        assert_eq!(counts.get(0, 1), 8.0);
+        assert_eq!(counts.get(2, 2), 9.0);
+        assert_eq!(corr.warnings(), 0);
+
+        // The fast path propagates NaN; the pairwise path does not
+        assert!(covariance_matrix(&matrix, 4, 10).get(0, 1).is_nan());
+        assert!(covariance_matrix_pairwise(&matrix, 4, 10).values().get(0, 1).is_finite());
+    }
+
+    #[test]
+    fn test_kendall_tau() {
+        // Reference values from scipy.stats.kendalltau(x, y, method="asymptotic")
+        let result = kendall_tau_test(&[12.0, 2.0, 1.0, 12.0, 2.0], &[1.0, 4.0, 7.0, 1.0, 0.0]);
+        assert!((result.statistic() - -0.4714045207910316).abs() < 1e-12);
+        assert!((result.p_value() - 0.28274545993277467).abs() < 1e-12);
+
+        let result = kendall_tau_test(
+            &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0],
+            &[2.0, 1.0, 4.0, 3.0, 6.0, 5.0, 8.0, 7.0],
+        );
+        assert!((result.statistic() - 0.7142857142857142).abs() < 1e-12);
+        assert!((result.p_value() - 0.013347575926843172).abs() < 1e-12);
+
+        assert!(kendall_tau(&[1.0, 2.0], &[1.0]).is_nan());
+        assert!(kendall_tau(&[1.0], &[1.0]).is_nan());
+    }
+
+    #[test]
+    fn test_kendall_tau_heavy_ties() {
+        let x = [1.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 3.0, 4.0];
+        let y = [1.0, 2.0, 1.0, 2.0, 2.0, 3.0, 3.0, 1.0, 3.0, 3.0];
+
+        let result = kendall_tau_test(&x, &y);
+        assert!((result.statistic() - 0.6473388749701496).abs() < 1e-12);
+        assert!((result.p_value() - 0.026644420998149874).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_correlation_matrix_methods() {
+        let matrix = vec![
+            1.0, 2.0, 3.0, 4.0, 5.0,
+            1.0, 4.0, 9.0, 16.0, 25.0,
+            5.0, 3.0, 4.0, 1.0, 2.0,
+        ];
+
+        let pearson = correlation_matrix(&matrix, 3, 5, "pearson");
+        let spearman = correlation_matrix(&matrix, 3, 5, "spearman");
+        let kendall = correlation_matrix(&matrix, 3, 5, "kendall");
+
+        assert!(pearson.get(0, 1) < 1.0 - 1e-6);
+        assert!((spearman.get(0, 1) - 1.0).abs() < 1e-10);
+        assert!((kendall.get(0, 1) - 1.0).abs() < 1e-10);
+        assert!((kendall.get(0, 2) - kendall_tau(&matrix[0..5], &matrix[10..15])).abs() < 1e-12);
+        assert_eq!(correlation_matrix(&matrix, 3, 5, "bogus").rows(), 0);
+    }
+
+    #[test]
+    fn test_partial_correlation() {
+        let x = [2.1, 3.4, 1.9, 5.6, 4.4, 6.1, 3.3, 7.2, 5.5, 6.8];
+        let y = [1.0, 2.2, 1.5, 3.9, 2.8, 4.5, 2.0, 5.1, 4.2, 4.6];
+        let z = [0.5, 1.1, 0.7, 2.0, 1.4, 2.2, 1.3, 2.9, 2.1, 2.5];
+        let w = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0, 3.0];
+
+        // Single covariate: recursive formula
+        let (rxy, rxz, ryz) = (
+            pearson_correlation(&x, &y),
+            pearson_correlation(&x, &z),
+            pearson_correlation(&y, &z),
+        );
+        let expected = (rxy - rxz * ryz) / ((1.0 - rxz * rxz) * (1.0 - ryz * ryz)).sqrt();
+        assert!((partial_correlation(&x, &y, &z, 1).unwrap() - expected).abs() < 1e-10);
+
+        // Two covariates: pingouin.partial_corr(x="x", y="y", covar=["z", "w"])
+        let zw: Vec<f64> = z.iter().zip(w.iter()).flat_map(|(&a, &b)| [a, b]).collect();
+        let r = partial_correlation(&x, &y, &zw, 2).unwrap();
+        assert!((r - 0.5722305520827992).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_partial_correlation_rank_deficient() {
+        let x = [1.0, 2.0, 4.0, 3.0, 5.0];
+        let y = [2.0, 1.0, 3.0, 5.0, 4.0];
+        // Second covariate is a multiple of the first
+        let z = [1.0, 2.0, 2.0, 4.0, 3.0, 6.0, 4.0, 8.0, 5.0, 10.0];
+
+        let err = partial_correlation(&x, &y, &z, 2).unwrap_err();
+        assert!(err.contains("rank-deficient"));
+
+        let matrix: Vec<f64> = x.iter().chain(y.iter()).copied().collect();
+        assert!(partial_correlation_matrix(&matrix, 2, 5, &z, 2).is_err());
+    }
+
+    #[test]
+    fn test_partial_correlation_matrix() {
+        let x = [2.1, 3.4, 1.9, 5.6, 4.4, 6.1, 3.3, 7.2, 5.5, 6.8];
+        let y = [1.0, 2.2, 1.5, 3.9, 2.8, 4.5, 2.0, 5.1, 4.2, 4.6];
+        let z = [0.5, 1.1, 0.7, 2.0, 1.4, 2.2, 1.3, 2.9, 2.1, 2.5];
+
+        let matrix: Vec<f64> = x.iter().chain(y.iter()).copied().collect();
+        let result = partial_correlation_matrix(&matrix, 2, 10, &z, 1).unwrap();
+
+        assert_eq!(result.rows(), 2);
+        assert!((result.get(0, 0) - 1.0).abs() < 1e-10);
+        assert!((result.get(0, 1) - partial_correlation(&x, &y, &z, 1).unwrap()).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_row_and_col_slices() {
+        let result = matmul(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0], &[1.0, 0.0, 0.0, 1.0], 3, 2, 2);
+
+        assert_eq!(result.row(1), vec![3.0, 4.0]);
+        assert_eq!(result.col(0), vec![1.0, 3.0, 5.0]);
+        assert!(result.row(3).is_empty());
+        assert!(result.col(2).is_empty());
+        assert_eq!(result.data_f32(), vec![1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        assert_eq!(result.take_data(), vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+    }
+
+    #[test]
+    fn test_submatrix() {
+        let data: Vec<f64> = (0..12).map(|x| x as f64).collect();
+        let result = transpose(&transpose(&data, 3, 4).data, 4, 3);
+
+        let sub = result.submatrix(1, 3, 1, 3);
+        assert_eq!(sub.rows(), 2);
+        assert_eq!(sub.cols(), 2);
+        assert_eq!(sub.data(), vec![5.0, 6.0, 9.0, 10.0]);
+
+        let full = result.submatrix(0, 3, 0, 4);
+        assert_eq!(full.data(), data);
+
+        assert_eq!(result.submatrix(0, 4, 0, 2).rows(), 0);
+        assert_eq!(result.submatrix(2, 1, 0, 2).rows(), 0);
+        assert_eq!(result.submatrix(0, 1, 0, 5).cols(), 0);
+        assert_eq!(result.submatrix(1, 1, 0, 4).data().len(), 0);
+    }
+
+    fn lcg_matrix(len: usize, seed: u64) -> Vec<f64> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matmul_blocked_matches_naive() {
+        let (m, k, n) = (37, 53, 29);
+        let a = lcg_matrix(m * k, 1);
+        let b = lcg_matrix(k * n, 2);
+
+        let mut naive = vec![0.0; m * n];
+        matmul_naive_into(&a, &b, &mut naive, m, k, n);
+
+        for bs in [1, 8, 16, 64] {
+            assert_eq!(matmul_blocked(&a, &b, m, k, n, bs, false).data, naive);
+            assert_eq!(matmul_blocked(&a, &b, m, k, n, bs, true).data, naive);
+        }
+    }
+
+    #[test]
+    fn test_matmul_large_uses_blocked_path() {
+        let (m, k, n) = (70, 65, 66);
+        let a = lcg_matrix(m * k, 3);
+        let b = lcg_matrix(k * n, 4);
+
+        let mut naive = vec![0.0; m * n];
+        matmul_naive_into(&a, &b, &mut naive, m, k, n);
+
+        let result = matmul(&a, &b, m, k, n);
+        for (x, y) in result.data.iter().zip(naive.iter()) {
+            assert!((x - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_matmul_f32() {
+        let a = vec![1.0f32, 2.0, 3.0, 4.0];
+        let b = vec![5.0f32, 6.0, 7.0, 8.0];
+
+        assert_eq!(matmul_f32(&a, &b, 2, 2, 2), vec![19.0, 22.0, 43.0, 50.0]);
+        assert!(matmul_f32(&a, &b, 2, 3, 2).is_empty());
+    }
+
+    #[test]
+    fn test_axis_summaries() {
+        let matrix = vec![1.0, 2.0, 3.0, 4.0, 6.0, 8.0];
+
+        assert_eq!(means(&matrix, 2, 3, 0), row_means(&matrix, 2, 3));
+        assert_eq!(means(&matrix, 2, 3, 1), vec![2.5, 4.0, 5.5]);
+        assert_eq!(col_means(&matrix, 2, 3), vec![2.5, 4.0, 5.5]);
+        assert_eq!(row_stds(&matrix, 2, 3), vec![1.0, 2.0]);
+
+        // Two values per column: sample std is |a - b| / sqrt(2)
+        let expected = [3.0, 4.0, 5.0].map(|d: f64| d / 2f64.sqrt());
+        for (s, e) in col_stds(&matrix, 2, 3).iter().zip(expected.iter()) {
+            assert!((s - e).abs() < 1e-12);
+        }
+
+        assert!(means(&matrix, 2, 3, 2).is_empty());
+        assert!(stds(&matrix, 1, 6, 1).is_empty());
+    }
+
+    #[test]
+    fn test_zscore_axis_matches_transpose() {
+        let (rows, cols) = (13, 7);
+        let matrix = lcg_matrix(rows * cols, 3);
+
+        let by_col = zscore_normalize(&matrix, rows, cols, 1);
+        let t = transpose(&matrix, rows, cols);
+        let by_row = zscore_normalize(&t.data(), cols, rows, 0);
+        let back = transpose(&by_row.data(), cols, rows);
+
+        assert_eq!(by_col.rows(), rows);
+        for (a, b) in by_col.data().iter().zip(back.data().iter()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+
+        assert_eq!(zscore_normalize(&matrix, rows, cols, 2).rows(), 0);
+    }
+
+    #[test]
+    fn test_robust_and_quantile_axis_match_transpose() {
+        let (rows, cols) = (9, 5);
+        let mut matrix = lcg_matrix(rows * cols, 4);
+        matrix[7] = f64::NAN;
+        let t = transpose(&matrix, rows, cols);
+
+        let robust = robust_zscore(&matrix, rows, cols, 1);
+        let robust_t = transpose(&robust_zscore(&t.data(), cols, rows, 0).data(), cols, rows);
+
+        let quantile = quantile_normalize(&matrix, rows, cols, 0);
+        let quantile_t = transpose(&quantile_normalize(&t.data(), cols, rows, 1).data(), cols, rows);
+
+        for (a, b) in robust.data().iter().zip(robust_t.data().iter())
+            .chain(quantile.data().iter().zip(quantile_t.data().iter()))
+        {
+            assert!((a.is_nan() && b.is_nan()) || (a - b).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_reorder_matrix() {
+        let matrix = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+
+        let result = reorder_matrix(&matrix, 2, 3, &[1, 0], &[2, 0, 1]);
+        assert_eq!(result.data(), vec![6.0, 4.0, 5.0, 3.0, 1.0, 2.0]);
+
+        let rows_only = reorder_matrix(&matrix, 2, 3, &[1, 0], &[]);
+        assert_eq!(rows_only.data(), vec![4.0, 5.0, 6.0, 1.0, 2.0, 3.0]);
+
+        assert_eq!(reorder_matrix(&matrix, 2, 3, &[0, 0], &[]).rows(), 0);
+        assert_eq!(reorder_matrix(&matrix, 2, 3, &[], &[0, 1, 3]).rows(), 0);
+    }
 }
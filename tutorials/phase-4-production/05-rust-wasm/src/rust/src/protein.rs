@@ -0,0 +1,290 @@
+//! Protein Properties
+//!
+//! Molecular weight, isoelectric point and hydropathy of
+//! translated sequences, in the style of Expasy ProtParam.
+
+/// Standard residues: (code, average mass, monoisotopic mass, Kyte-Doolittle
+/// hydropathy). Masses are of the residue, i.e. the amino acid minus water.
+const AMINO_ACIDS: [(u8, f64, f64, f64); 20] = [
+    (b'A', 71.0788, 71.03711, 1.8),
+    (b'C', 103.1388, 103.00919, 2.5),
+    (b'D', 115.0886, 115.02694, -3.5),
+    (b'E', 129.1155, 129.04259, -3.5),
+    (b'F', 147.1766, 147.06841, 2.8),
+    (b'G', 57.0519, 57.02146, -0.4),
+    (b'H', 137.1411, 137.05891, -3.2),
+    (b'I', 113.1594, 113.08406, 4.5),
+    (b'K', 128.1741, 128.09496, -3.9),
+    (b'L', 113.1594, 113.08406, 3.8),
+    (b'M', 131.1926, 131.04049, 1.9),
+    (b'N', 114.1038, 114.04293, -3.5),
+    (b'P', 97.1167, 97.05276, -1.6),
+    (b'Q', 128.1307, 128.05858, -3.5),
+    (b'R', 156.1875, 156.10111, -4.5),
+    (b'S', 87.0782, 87.03203, -0.8),
+    (b'T', 101.1051, 101.04768, -0.7),
+    (b'V', 99.1326, 99.06841, 4.2),
+    (b'W', 186.2132, 186.07931, -0.9),
+    (b'Y', 163.1760, 163.06333, -1.3),
+];
+
+/// Water added once per chain (average, monoisotopic)
+const WATER_MASS: (f64, f64) = (18.01524, 18.01056);
+
+// Bjellqvist pKa scale as used by Expasy: side chains, then termini,
+// with residue-specific overrides for the terminal residue
+const PKA_POSITIVE: [(u8, f64); 3] = [(b'K', 10.0), (b'R', 12.0), (b'H', 5.98)];
+const PKA_NEGATIVE: [(u8, f64); 4] = [(b'D', 4.05), (b'E', 4.45), (b'C', 9.0), (b'Y', 10.0)];
+const PKA_N_TERM: f64 = 7.5;
+const PKA_C_TERM: f64 = 3.55;
+const PKA_N_TERM_RESIDUE: [(u8, f64); 7] = [
+    (b'A', 7.59),
+    (b'M', 7.0),
+    (b'S', 6.93),
+    (b'P', 8.36),
+    (b'T', 6.82),
+    (b'V', 7.44),
+    (b'E', 7.7),
+];
+const PKA_C_TERM_RESIDUE: [(u8, f64); 2] = [(b'D', 4.55), (b'E', 4.75)];
+
+/// Index into `AMINO_ACIDS` of a one-letter code, case-insensitive
+fn residue_index(code: u8) -> Option<usize> {
+    let code = code.to_ascii_uppercase();
+    AMINO_ACIDS.iter().position(|aa| aa.0 == code)
+}
+
+fn lookup(table: &[(u8, f64)], code: u8) -> Option<f64> {
+    table.iter().find(|entry| entry.0 == code).map(|entry| entry.1)
+}
+
+/// Net charge of a chain at the given pH (Henderson-Hasselbalch)
+fn net_charge(residues: &[usize], ph: f64) -> f64 {
+    let positive = |pka: f64| 1.0 / (1.0 + 10f64.powf(ph - pka));
+    let negative = |pka: f64| -1.0 / (1.0 + 10f64.powf(pka - ph));
+
+    let first = AMINO_ACIDS[residues[0]].0;
+    let last = AMINO_ACIDS[residues[residues.len() - 1]].0;
+    let mut charge = positive(lookup(&PKA_N_TERM_RESIDUE, first).unwrap_or(PKA_N_TERM))
+        + negative(lookup(&PKA_C_TERM_RESIDUE, last).unwrap_or(PKA_C_TERM));
+
+    for &r in residues {
+        let code = AMINO_ACIDS[r].0;
+        if let Some(pka) = lookup(&PKA_POSITIVE, code) {
+            charge += positive(pka);
+        }
+        if let Some(pka) = lookup(&PKA_NEGATIVE, code) {
+            charge += negative(pka);
+        }
+    }
+
+    charge
+}
+
+/// pH of zero net charge, by bisection over 0-14
+fn isoelectric_point(residues: &[usize]) -> f64 {
+    let (mut low, mut high) = (0.0, 14.0);
+    while high - low > 1e-4 {
+        let mid = (low + high) / 2.0;
+        if net_charge(residues, mid) > 0.0 {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Summary of a protein sequence
+///
+/// Stops (`*`) and non-standard residues (`X`, `B`, `U`, ...) are skipped
+/// and counted; whitespace is ignored. Properties are computed from the
+/// standard residues as one chain. Without any, all values are NaN.
+pub struct ProteinResult {
+    length: usize,
+    molecular_weight: f64,
+    isoelectric_point: f64,
+    gravy: f64,
+    composition: [u32; 20],
+    stop_count: u32,
+    unknown_count: u32,
+}
+
+impl ProteinResult {
+    /// Number of standard residues
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Molecular weight in Da
+    pub fn molecular_weight(&self) -> f64 {
+        self.molecular_weight
+    }
+
+    pub fn isoelectric_point(&self) -> f64 {
+        self.isoelectric_point
+    }
+
+    /// Grand average of hydropathy (mean Kyte-Doolittle score)
+    pub fn gravy(&self) -> f64 {
+        self.gravy
+    }
+
+    /// The 20 standard residues, alphabetically by one-letter code
+    pub fn residues(&self) -> Vec<String> {
+        AMINO_ACIDS.iter().map(|aa| (aa.0 as char).to_string()).collect()
+    }
+
+    /// Fraction of the standard residues for each entry of `residues()`
+    pub fn fractions(&self) -> Vec<f64> {
+        self.composition
+            .iter()
+            .map(|&count| count as f64 / self.length as f64)
+            .collect()
+    }
+
+    /// Residue counts aligned with `residues()`
+    pub fn counts(&self) -> Vec<u32> {
+        self.composition.to_vec()
+    }
+
+    pub fn stop_count(&self) -> u32 {
+        self.stop_count
+    }
+
+    pub fn unknown_count(&self) -> u32 {
+        self.unknown_count
+    }
+}
+
+/// Molecular weight, pI, GRAVY and composition of a protein
+///
+/// `monoisotopic` selects monoisotopic instead of average masses. The pI
+/// uses the Bjellqvist pKa scale, matching Expasy ProtParam.
+pub fn protein_properties(protein: &str, monoisotopic: bool) -> ProteinResult {
+    let mut residues = Vec::with_capacity(protein.len());
+    let mut composition = [0u32; 20];
+    let mut stop_count = 0;
+    let mut unknown_count = 0;
+
+    for c in protein.bytes().filter(|c| !c.is_ascii_whitespace()) {
+        match residue_index(c) {
+            Some(r) => {
+                residues.push(r);
+                composition[r] += 1;
+            }
+            None if c == b'*' => stop_count += 1,
+            None => unknown_count += 1,
+        }
+    }
+
+    let mut result = ProteinResult {
+        length: residues.len(),
+        molecular_weight: f64::NAN,
+        isoelectric_point: f64::NAN,
+        gravy: f64::NAN,
+        composition,
+        stop_count,
+        unknown_count,
+    };
+    if residues.is_empty() {
+        return result;
+    }
+
+    let residue_mass = |r: usize| if monoisotopic { AMINO_ACIDS[r].2 } else { AMINO_ACIDS[r].1 };
+    let water = if monoisotopic { WATER_MASS.1 } else { WATER_MASS.0 };
+    result.molecular_weight = residues.iter().map(|&r| residue_mass(r)).sum::<f64>() + water;
+    result.isoelectric_point = isoelectric_point(&residues);
+    result.gravy = residues.iter().map(|&r| AMINO_ACIDS[r].3).sum::<f64>() / residues.len() as f64;
+
+    result
+}
+
+/// Kyte-Doolittle hydropathy in sliding windows (step 1)
+///
+/// Windows run over the sequence as given (whitespace excluded), so index
+/// i covers residues i..i + window. Stops and unknown residues are left out
+/// of the window mean; a window with no standard residue is NaN.
+pub fn hydrophobicity_profile(protein: &str, window: usize) -> Vec<f64> {
+    let scores: Vec<Option<f64>> = protein
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| residue_index(c).map(|r| AMINO_ACIDS[r].3))
+        .collect();
+
+    if window == 0 || scores.len() < window {
+        return vec![];
+    }
+
+    scores
+        .windows(window)
+        .map(|w| {
+            let (sum, n) = w.iter().flatten().fold((0.0, 0), |(sum, n), s| (sum + s, n + 1));
+            if n == 0 { f64::NAN } else { sum / n as f64 }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UBIQUITIN: &str = "MQIFVKTLTGKTITLEVEPSDTIENVKAKIQDKEGIPPDQQRLIFAGKQLEDGRTLSDYNIQKESTLHLVLRLRGG";
+    const LYSOZYME: &str = "KVFGRCELAAAMKRHGLDNYRGYSLGNWVCAAKFESNFNTQATNRNTDGSTDYGILQINSRWWCNDGRTPGSRNLCNIPCSALLSSDITASVNCAKKIVSDGNGMNAWVAWRNRCKGTDVQAWIRGCRL";
+
+    #[test]
+    fn test_protein_properties_expasy() {
+        // Expasy ProtParam: human ubiquitin MW 8564.84, pI 6.56, GRAVY -0.489
+        let ubiquitin = protein_properties(UBIQUITIN, false);
+        assert_eq!(ubiquitin.length(), 76);
+        assert!((ubiquitin.molecular_weight() - 8564.84).abs() < 0.01);
+        assert!((ubiquitin.isoelectric_point() - 6.56).abs() < 0.005);
+        assert!((ubiquitin.gravy() - -0.489).abs() < 0.0005);
+
+        // Monoisotopic mass of ubiquitin
+        let mono = protein_properties(UBIQUITIN, true);
+        assert!((mono.molecular_weight() - 8559.62).abs() < 0.01);
+
+        // Expasy ProtParam: mature hen egg-white lysozyme MW 14313.14, pI 9.32
+        let lysozyme = protein_properties(LYSOZYME, false);
+        assert!((lysozyme.molecular_weight() - 14313.14).abs() < 0.01);
+        assert!((lysozyme.isoelectric_point() - 9.32).abs() < 0.005);
+    }
+
+    #[test]
+    fn test_protein_composition_and_skips() {
+        let result = protein_properties("MKX*AA K*", false);
+        assert_eq!(result.length(), 5);
+        assert_eq!((result.stop_count(), result.unknown_count()), (2, 1));
+        assert_eq!(result.molecular_weight(), protein_properties("MKAAK", false).molecular_weight());
+
+        let residues = result.residues();
+        let fractions = result.fractions();
+        assert_eq!(residues.len(), 20);
+        let fraction = |code: &str| fractions[residues.iter().position(|r| r == code).unwrap()];
+        assert!((fraction("A") - 0.4).abs() < 1e-12);
+        assert!((fraction("K") - 0.4).abs() < 1e-12);
+        assert_eq!(fraction("W"), 0.0);
+
+        let empty = protein_properties("**X", false);
+        assert_eq!(empty.length(), 0);
+        assert!(empty.molecular_weight().is_nan() && empty.isoelectric_point().is_nan());
+    }
+
+    #[test]
+    fn test_hydrophobicity_profile() {
+        let profile = hydrophobicity_profile("IIIKKK", 3);
+        assert_eq!(profile.len(), 4);
+        assert!((profile[0] - 4.5).abs() < 1e-12);
+        assert!((profile[3] - -3.9).abs() < 1e-12);
+
+        // Unknowns are left out of the mean
+        let profile = hydrophobicity_profile("IX*K", 2);
+        assert!((profile[0] - 4.5).abs() < 1e-12);
+        assert!(profile[1].is_nan());
+        assert!((profile[2] - -3.9).abs() < 1e-12);
+
+        assert!(hydrophobicity_profile("IK", 3).is_empty());
+        assert!(hydrophobicity_profile("IK", 0).is_empty());
+    }
+}
@@ -0,0 +1,335 @@
+//! Position Weight Matrices
+//!
+//! Log-odds matrices built from aligned binding sites, and
+//! scanning of sequences for motif hits on either strand.
+
+use crate::sequence::base_code;
+
+pub(crate) const UNIFORM_BACKGROUND: [f64; 4] = [0.25; 4];
+
+/// Position weight matrix over A, C, G, T
+///
+/// Counts exclude pseudocounts; probabilities are
+/// `(count + pseudocount) / (sites + 4 * pseudocount)` and scores are
+/// log2(probability / background).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pwm {
+    counts: Vec<[f64; 4]>,
+    pseudocount: f64,
+    background: [f64; 4],
+    scores: Vec<[f64; 4]>,
+}
+
+/// A PWM match; `position` is the 0-based start on the forward strand
+#[derive(Debug, Clone, PartialEq)]
+pub struct MotifHit {
+    pub position: u32,
+    pub strand: i8,
+    pub score: f64,
+}
+
+impl Pwm {
+    /// Build from per-column counts (A, C, G, T)
+    pub fn from_counts(counts: Vec<[f64; 4]>, pseudocount: f64, background: [f64; 4]) -> Result<Pwm, String> {
+        if counts.is_empty() {
+            return Err("PWM needs at least one column".to_string());
+        }
+        if !(pseudocount.is_finite() && pseudocount > 0.0) {
+            return Err("Pseudocount must be positive".to_string());
+        }
+        if counts.iter().flatten().any(|c| !(c.is_finite() && *c >= 0.0)) {
+            return Err("Counts must be non-negative".to_string());
+        }
+        if background.iter().any(|b| !(b.is_finite() && *b > 0.0)) {
+            return Err("Background frequencies must be positive".to_string());
+        }
+
+        // Normalise so callers may pass percentages or raw composition counts
+        let total: f64 = background.iter().sum();
+        let background = background.map(|b| b / total);
+
+        let scores = counts
+            .iter()
+            .map(|column| {
+                let sites: f64 = column.iter().sum();
+                let mut scores = [0.0; 4];
+                for (b, score) in scores.iter_mut().enumerate() {
+                    let p = (column[b] + pseudocount) / (sites + 4.0 * pseudocount);
+                    *score = (p / background[b]).log2();
+                }
+                scores
+            })
+            .collect();
+
+        Ok(Pwm {
+            counts,
+            pseudocount,
+            background,
+            scores,
+        })
+    }
+
+    pub fn counts(&self) -> &[[f64; 4]] {
+        &self.counts
+    }
+
+    pub fn pseudocount(&self) -> f64 {
+        self.pseudocount
+    }
+
+    pub fn background(&self) -> [f64; 4] {
+        self.background
+    }
+
+    /// Per-column log2 odds scores
+    pub fn scores(&self) -> &[[f64; 4]] {
+        &self.scores
+    }
+
+    /// Per-column base probabilities, including pseudocounts
+    pub fn probabilities(&self) -> Vec<[f64; 4]> {
+        self.counts
+            .iter()
+            .map(|column| {
+                let sites: f64 = column.iter().sum();
+                column.map(|c| (c + self.pseudocount) / (sites + 4.0 * self.pseudocount))
+            })
+            .collect()
+    }
+
+    /// Score of one window of 2-bit codes read on the given strand
+    fn window_score(&self, codes: &[usize], reverse: bool) -> f64 {
+        let n = self.scores.len();
+        (0..n)
+            .map(|i| {
+                if reverse {
+                    self.scores[n - 1 - i][3 - codes[i]]
+                } else {
+                    self.scores[i][codes[i]]
+                }
+            })
+            .sum()
+    }
+
+    /// Hits scoring at least `threshold`, in position order
+    ///
+    /// Reverse-strand hits score the reverse complement of the window.
+    /// Windows with non-ACGT bases are skipped.
+    pub fn scan(&self, sequence: &str, threshold: f64, both_strands: bool) -> Vec<MotifHit> {
+        let width = self.scores.len();
+        let codes: Vec<Option<usize>> = sequence.bytes().map(|b| base_code(b).map(|c| c as usize)).collect();
+        let mut hits = Vec::new();
+        if codes.len() < width {
+            return hits;
+        }
+
+        let mut window = Vec::with_capacity(width);
+        for start in 0..=codes.len() - width {
+            window.clear();
+            window.extend(codes[start..start + width].iter().map_while(|&c| c));
+            if window.len() < width {
+                continue;
+            }
+
+            let strands: &[(i8, bool)] = if both_strands { &[(1, false), (-1, true)] } else { &[(1, false)] };
+            for &(strand, reverse) in strands {
+                let score = self.window_score(&window, reverse);
+                if score >= threshold {
+                    hits.push(MotifHit {
+                        position: start as u32,
+                        strand,
+                        score,
+                    });
+                }
+            }
+        }
+
+        hits
+    }
+}
+
+impl Pwm {
+    /// Motif width in columns
+    pub fn length(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Best achievable score (consensus sequence)
+    pub fn max_score(&self) -> f64 {
+        self.scores.iter().map(|s| s.iter().cloned().fold(f64::NEG_INFINITY, f64::max)).sum()
+    }
+
+    /// Worst achievable score
+    pub fn min_score(&self) -> f64 {
+        self.scores.iter().map(|s| s.iter().cloned().fold(f64::INFINITY, f64::min)).sum()
+    }
+
+    /// Absolute threshold for a fraction of the score range (0 = min, 1 = max)
+    pub fn threshold_for_fraction(&self, fraction: f64) -> f64 {
+        let min = self.min_score();
+        min + fraction * (self.max_score() - min)
+    }
+
+    /// Most likely base per column
+    pub fn consensus(&self) -> String {
+        self.scores
+            .iter()
+            .map(|s| {
+                let best = (0..4).fold(0, |best, b| if s[b] > s[best] { b } else { best });
+                b"ACGT"[best] as char
+            })
+            .collect()
+    }
+
+    /// Scan a sequence (see `Pwm::scan`)
+    pub fn find_hits(&self, sequence: &str, threshold: f64, both_strands: bool) -> PwmHits {
+        PwmHits {
+            hits: self.scan(sequence, threshold, both_strands),
+        }
+    }
+}
+
+/// PWM hits as parallel arrays
+pub struct PwmHits {
+    hits: Vec<MotifHit>,
+}
+
+impl PwmHits {
+    pub fn count(&self) -> usize {
+        self.hits.len()
+    }
+
+    /// 0-based start positions on the forward strand
+    pub fn positions(&self) -> Vec<u32> {
+        self.hits.iter().map(|h| h.position).collect()
+    }
+
+    /// 1 for the forward strand, -1 for the reverse strand
+    pub fn strands(&self) -> Vec<i8> {
+        self.hits.iter().map(|h| h.strand).collect()
+    }
+
+    pub fn scores(&self) -> Vec<f64> {
+        self.hits.iter().map(|h| h.score).collect()
+    }
+}
+
+/// Build a PWM from equal-length aligned sites against a uniform background
+///
+/// Non-ACGT bases (e.g. N) contribute no count to their column.
+pub fn build_pwm(sequences: Vec<String>, pseudocount: f64) -> Result<Pwm, String> {
+    build_pwm_with_background(sequences, pseudocount, UNIFORM_BACKGROUND.to_vec())
+}
+
+/// `build_pwm` with background frequencies for A, C, G, T
+pub fn build_pwm_with_background(sequences: Vec<String>, pseudocount: f64, background: Vec<f64>) -> Result<Pwm, String> {
+    let background: [f64; 4] = background
+        .try_into()
+        .map_err(|_| "Background needs 4 frequencies (A, C, G, T)".to_string())?;
+
+    let width = match sequences.first() {
+        Some(first) => first.len(),
+        None => return Err("No sequences given".to_string()),
+    };
+    if let Some(i) = sequences.iter().position(|s| s.len() != width) {
+        return Err(format!(
+            "Sequence {} has length {}, expected {}",
+            i + 1,
+            sequences[i].len(),
+            width
+        ));
+    }
+
+    let mut counts = vec![[0.0; 4]; width];
+    for sequence in &sequences {
+        for (column, base) in counts.iter_mut().zip(sequence.bytes()) {
+            if let Some(code) = base_code(base) {
+                column[code as usize] += 1.0;
+            }
+        }
+    }
+
+    Pwm::from_counts(counts, pseudocount, background)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::reverse_complement;
+
+    fn random_dna(len: usize, seed: u64) -> String {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                b"ACGT"[(state >> 62) as usize] as char
+            })
+            .collect()
+    }
+
+    fn sites() -> Vec<String> {
+        ["GGATTACA", "GGATTACA", "GGATTAGA", "GCATTACA", "GGATTACT", "GGTTTACA"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_build_pwm() {
+        let pwm = build_pwm(sites(), 0.5).unwrap();
+        assert_eq!(pwm.length(), 8);
+        assert_eq!(pwm.consensus(), "GGATTACA");
+        assert_eq!(pwm.counts()[0], [0.0, 0.0, 6.0, 0.0]);
+
+        // Column 1: G seen 5 of 6 times, probability (5 + 0.5) / (6 + 2)
+        let probabilities = pwm.probabilities();
+        assert!((probabilities[1][2] - 5.5 / 8.0).abs() < 1e-12);
+        assert!((pwm.scores[1][2] - (5.5 / 8.0 / 0.25f64).log2()).abs() < 1e-12);
+
+        assert!((pwm.max_score() - pwm.window_score(&[2, 2, 0, 3, 3, 0, 1, 0], false)).abs() < 1e-12);
+        assert!(pwm.min_score() < 0.0);
+        assert_eq!(pwm.threshold_for_fraction(1.0), pwm.max_score());
+        assert_eq!(pwm.threshold_for_fraction(0.0), pwm.min_score());
+
+        // A GC-rich background lowers the scores of G and C
+        let gc_rich = build_pwm_with_background(sites(), 0.5, vec![0.2, 0.3, 0.3, 0.2]).unwrap();
+        assert!(gc_rich.scores[0][2] < pwm.scores[0][2]);
+        assert!(gc_rich.scores[2][0] > pwm.scores[2][0]);
+
+        let rebuilt = Pwm::from_counts(pwm.counts().to_vec(), 0.5, UNIFORM_BACKGROUND).unwrap();
+        assert_eq!(rebuilt, pwm);
+    }
+
+    #[test]
+    fn test_build_pwm_errors() {
+        assert!(build_pwm(vec![], 1.0).is_err());
+        assert!(build_pwm(vec!["ACGT".into(), "ACG".into()], 1.0).is_err());
+        assert!(build_pwm(vec!["ACGT".into()], 0.0).is_err());
+        assert!(build_pwm_with_background(vec!["ACGT".into()], 1.0, vec![0.5, 0.5]).is_err());
+    }
+
+    #[test]
+    fn test_scan_planted_site() {
+        let pwm = build_pwm(sites(), 0.5).unwrap();
+
+        // Plant the consensus on the reverse strand
+        let mut sequence = random_dna(300, 7);
+        sequence.replace_range(137..145, &reverse_complement("GGATTACA"));
+
+        let hits = pwm.scan(&sequence, pwm.threshold_for_fraction(0.6), true);
+        let top = hits.iter().max_by(|a, b| a.score.total_cmp(&b.score)).unwrap();
+        assert_eq!((top.position, top.strand), (137, -1));
+        assert!((top.score - pwm.max_score()).abs() < 1e-12);
+
+        // Forward-only scanning cannot see it
+        let forward = pwm.scan(&sequence, pwm.max_score() - 1e-9, false);
+        assert!(forward.is_empty());
+
+        // Windows with N are skipped
+        sequence.replace_range(140..141, "N");
+        assert!(pwm.scan(&sequence, pwm.max_score() - 1e-9, true).is_empty());
+
+        let result = pwm.find_hits(&sequence, pwm.threshold_for_fraction(0.6), true);
+        assert_eq!(result.count(), result.positions().len());
+    }
+}
@@ -0,0 +1,348 @@
+//! Linear Regression
+//!
+//! Ordinary least squares fits for relating gene expression to a
+//! continuous covariate such as pseudotime or dosage.
+
+use crate::distributions::{benjamini_hochberg, student_t_two_tailed};
+
+/// Result of a simple linear regression (y = intercept + slope * x)
+pub struct RegressionResult {
+    slope: f64,
+    intercept: f64,
+    r_squared: f64,
+    p_value: f64,
+    std_err: f64,
+}
+
+impl RegressionResult {
+    /// Fitted slope
+    pub fn slope(&self) -> f64 { self.slope }
+
+    /// Fitted intercept
+    pub fn intercept(&self) -> f64 { self.intercept }
+
+    /// Coefficient of determination
+    pub fn r_squared(&self) -> f64 { self.r_squared }
+
+    /// Two-tailed p-value for slope != 0 (t-distribution, n - 2 df)
+    pub fn p_value(&self) -> f64 { self.p_value }
+
+    /// Standard error of the slope
+    pub fn std_err(&self) -> f64 { self.std_err }
+}
+
+impl RegressionResult {
+    fn nan() -> Self {
+        RegressionResult {
+            slope: f64::NAN,
+            intercept: f64::NAN,
+            r_squared: f64::NAN,
+            p_value: f64::NAN,
+            std_err: f64::NAN,
+        }
+    }
+}
+
+/// Ordinary least squares fit of y on x
+///
+/// Returns NaN for every field when the inputs differ in length,
+/// contain NaN, or x has no variance. The p-value and standard error
+/// need at least three points.
+pub fn linear_regression(x: &[f64], y: &[f64]) -> RegressionResult {
+    if x.len() != y.len() || x.len() < 2 {
+        return RegressionResult::nan();
+    }
+    if x.iter().chain(y.iter()).any(|v| v.is_nan()) {
+        return RegressionResult::nan();
+    }
+
+    let n = x.len() as f64;
+    let mean_x: f64 = x.iter().sum::<f64>() / n;
+    let mean_y: f64 = y.iter().sum::<f64>() / n;
+
+    let mut sxx = 0.0;
+    let mut sxy = 0.0;
+    let mut syy = 0.0;
+
+    for (xi, yi) in x.iter().zip(y.iter()) {
+        let dx = xi - mean_x;
+        let dy = yi - mean_y;
+        sxx += dx * dx;
+        sxy += dx * dy;
+        syy += dy * dy;
+    }
+
+    if sxx == 0.0 {
+        return RegressionResult::nan();
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let r_squared = if syy > 0.0 { sxy * sxy / (sxx * syy) } else { f64::NAN };
+
+    let df = n - 2.0;
+    let (std_err, p_value) = if df > 0.0 {
+        let sse = (syy - slope * sxy).max(0.0);
+        let std_err = (sse / df / sxx).sqrt();
+        let t = slope / std_err;
+        (std_err, student_t_two_tailed(t, df))
+    } else {
+        (f64::NAN, f64::NAN)
+    };
+
+    RegressionResult {
+        slope,
+        intercept,
+        r_squared,
+        p_value,
+        std_err,
+    }
+}
+
+/// Least-squares design of an intercept plus covariate columns
+///
+/// The normal equations are factored once with a pivoted Cholesky
+/// decomposition so many responses can be residualized cheaply.
+pub(crate) struct CovariateDesign {
+    /// Design matrix (n x p, row-major) including the intercept column
+    design: Vec<f64>,
+    n: usize,
+    p: usize,
+    /// Lower-triangular factor of the pivoted normal equations (p x p)
+    chol: Vec<f64>,
+    /// Column permutation applied before factoring
+    pivots: Vec<usize>,
+}
+
+impl CovariateDesign {
+    /// Relative pivot size below which a covariate is considered redundant
+    const RANK_TOLERANCE: f64 = 1e-10;
+
+    /// Build a design from `covariates` (n x n_cov, row-major)
+    ///
+    /// Fails when the covariates (with the intercept) are rank-deficient.
+    pub(crate) fn new(covariates: &[f64], n: usize, n_cov: usize) -> Result<Self, String> {
+        if covariates.len() != n * n_cov {
+            return Err(format!(
+                "Expected {} covariate values ({} x {}), found {}",
+                n * n_cov, n, n_cov, covariates.len()
+            ));
+        }
+
+        let p = n_cov + 1;
+        if n <= p {
+            return Err(format!("Need more than {} observations for {} covariates", p, n_cov));
+        }
+
+        let mut design = Vec::with_capacity(n * p);
+        for i in 0..n {
+            design.push(1.0);
+            design.extend_from_slice(&covariates[i * n_cov..(i + 1) * n_cov]);
+        }
+
+        // Normal equations X'X
+        let mut xtx = vec![0.0; p * p];
+        for row in design.chunks(p) {
+            for a in 0..p {
+                for b in a..p {
+                    xtx[a * p + b] += row[a] * row[b];
+                }
+            }
+        }
+        for a in 0..p {
+            for b in 0..a {
+                xtx[a * p + b] = xtx[b * p + a];
+            }
+        }
+
+        // Pivoted Cholesky: pick the largest remaining diagonal at each step
+        let mut pivots: Vec<usize> = (0..p).collect();
+        let mut chol = vec![0.0; p * p];
+        let max_diag = (0..p).map(|a| xtx[a * p + a]).fold(0.0, f64::max);
+
+        for k in 0..p {
+            let residual_diag = |j: usize, chol: &[f64], pivots: &[usize]| {
+                let col = pivots[j];
+                xtx[col * p + col] - (0..k).map(|m| chol[j * p + m].powi(2)).sum::<f64>()
+            };
+
+            let best = (k..p)
+                .max_by(|&a, &b| {
+                    residual_diag(a, &chol, &pivots).total_cmp(&residual_diag(b, &chol, &pivots))
+                })
+                .unwrap_or(k);
+
+            let diag = residual_diag(best, &chol, &pivots);
+            if diag <= Self::RANK_TOLERANCE * max_diag.max(1.0) {
+                return Err(format!(
+                    "Covariate matrix is rank-deficient (rank {} of {} including intercept)",
+                    k, p
+                ));
+            }
+
+            pivots.swap(k, best);
+            for m in 0..k {
+                chol.swap(k * p + m, best * p + m);
+            }
+
+            let l_kk = diag.sqrt();
+            chol[k * p + k] = l_kk;
+
+            for j in k + 1..p {
+                let (col_j, col_k) = (pivots[j], pivots[k]);
+                let dot: f64 = (0..k).map(|m| chol[j * p + m] * chol[k * p + m]).sum();
+                chol[j * p + k] = (xtx[col_j * p + col_k] - dot) / l_kk;
+            }
+        }
+
+        Ok(CovariateDesign {
+            design,
+            n,
+            p,
+            chol,
+            pivots,
+        })
+    }
+
+    /// Residuals of `y` (length n) after regressing out the design
+    pub(crate) fn residuals(&self, y: &[f64]) -> Vec<f64> {
+        let (n, p) = (self.n, self.p);
+
+        // X'y in pivoted order
+        let mut rhs = vec![0.0; p];
+        for (k, value) in rhs.iter_mut().enumerate() {
+            let col = self.pivots[k];
+            *value = (0..n).map(|i| self.design[i * p + col] * y[i]).sum();
+        }
+
+        // Solve L z = rhs, then L' w = z
+        for k in 0..p {
+            let dot: f64 = (0..k).map(|m| self.chol[k * p + m] * rhs[m]).sum();
+            rhs[k] = (rhs[k] - dot) / self.chol[k * p + k];
+        }
+        for k in (0..p).rev() {
+            let dot: f64 = (k + 1..p).map(|m| self.chol[m * p + k] * rhs[m]).sum();
+            rhs[k] = (rhs[k] - dot) / self.chol[k * p + k];
+        }
+
+        let mut beta = vec![0.0; p];
+        for (k, &value) in rhs.iter().enumerate() {
+            beta[self.pivots[k]] = value;
+        }
+
+        (0..n)
+            .map(|i| {
+                let fitted: f64 = (0..p).map(|c| self.design[i * p + c] * beta[c]).sum();
+                y[i] - fitted
+            })
+            .collect()
+    }
+}
+
+/// Per-row regression results, one entry per matrix row
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchRegression {
+    pub slopes: Vec<f64>,
+    pub intercepts: Vec<f64>,
+    pub r_squared: Vec<f64>,
+    /// BH-adjusted p-values
+    pub p_values: Vec<f64>,
+}
+
+/// Fit one regression per row (genes x samples) against a shared covariate
+///
+/// # Arguments
+/// * `matrix` - Flattened row-major matrix (rows x cols)
+/// * `x` - Covariate of length `cols`
+///
+/// Returns empty vectors if the dimensions do not match.
+pub fn linear_regression_batch(matrix: &[f64], rows: usize, cols: usize, x: &[f64]) -> BatchRegression {
+    if matrix.len() != rows * cols || x.len() != cols {
+        return BatchRegression {
+            slopes: vec![],
+            intercepts: vec![],
+            r_squared: vec![],
+            p_values: vec![],
+        };
+    }
+
+    let mut slopes = Vec::with_capacity(rows);
+    let mut intercepts = Vec::with_capacity(rows);
+    let mut r_squared = Vec::with_capacity(rows);
+    let mut raw_p = Vec::with_capacity(rows);
+
+    for row in matrix.chunks(cols.max(1)).take(rows) {
+        // NaN rows come back as all-NaN fits and are left out of the BH correction
+        let fit = linear_regression(x, row);
+        slopes.push(fit.slope);
+        intercepts.push(fit.intercept);
+        r_squared.push(fit.r_squared);
+        raw_p.push(fit.p_value);
+    }
+
+    BatchRegression {
+        slopes,
+        intercepts,
+        r_squared,
+        p_values: benjamini_hochberg(&raw_p),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // R's built-in `cars` dataset: lm(dist ~ speed, data = cars)
+    const SPEED: [f64; 50] = [
+        4.0, 4.0, 7.0, 7.0, 8.0, 9.0, 10.0, 10.0, 10.0, 11.0,
+        11.0, 12.0, 12.0, 12.0, 12.0, 13.0, 13.0, 13.0, 13.0, 14.0,
+        14.0, 14.0, 14.0, 15.0, 15.0, 15.0, 16.0, 16.0, 17.0, 17.0,
+        17.0, 18.0, 18.0, 18.0, 18.0, 19.0, 19.0, 19.0, 20.0, 20.0,
+        20.0, 20.0, 20.0, 22.0, 23.0, 24.0, 24.0, 24.0, 24.0, 25.0,
+    ];
+    const DIST: [f64; 50] = [
+        2.0, 10.0, 4.0, 22.0, 16.0, 10.0, 18.0, 26.0, 34.0, 17.0,
+        28.0, 14.0, 20.0, 24.0, 28.0, 26.0, 34.0, 34.0, 46.0, 26.0,
+        36.0, 60.0, 80.0, 20.0, 26.0, 54.0, 32.0, 40.0, 32.0, 40.0,
+        50.0, 42.0, 56.0, 76.0, 84.0, 36.0, 46.0, 68.0, 32.0, 48.0,
+        52.0, 56.0, 64.0, 66.0, 54.0, 70.0, 92.0, 93.0, 120.0, 85.0,
+    ];
+
+    #[test]
+    fn test_linear_regression_matches_r() {
+        let fit = linear_regression(&SPEED, &DIST);
+
+        assert!((fit.slope() - 3.932409).abs() < 1e-6);
+        assert!((fit.intercept() - -17.579095).abs() < 1e-6);
+        assert!((fit.r_squared() - 0.6510794).abs() < 1e-6);
+        assert!((fit.std_err() - 0.4155128).abs() < 1e-6);
+        assert!((fit.p_value() - 1.489836e-12).abs() < 1e-17);
+    }
+
+    #[test]
+    fn test_linear_regression_degenerate() {
+        assert!(linear_regression(&[1.0, 1.0, 1.0], &[1.0, 2.0, 3.0]).slope().is_nan());
+        assert!(linear_regression(&[1.0, 2.0], &[1.0]).slope().is_nan());
+        assert!(linear_regression(&[1.0, 2.0, f64::NAN], &[1.0, 2.0, 3.0]).slope().is_nan());
+    }
+
+    #[test]
+    fn test_linear_regression_batch_skips_nan_rows() {
+        let x = vec![1.0, 2.0, 3.0, 4.0];
+        let matrix = vec![
+            2.0, 4.1, 5.9, 8.0,
+            1.0, f64::NAN, 3.0, 4.0,
+            4.0, 3.0, 2.2, 0.9,
+        ];
+
+        let batch = linear_regression_batch(&matrix, 3, 4, &x);
+
+        assert_eq!(batch.slopes.len(), 3);
+        assert!(batch.slopes[0] > 1.9);
+        assert!(batch.slopes[1].is_nan());
+        assert!(batch.p_values[1].is_nan());
+        assert!(batch.slopes[2] < 0.0);
+        assert!(!batch.p_values[0].is_nan());
+        assert!(!batch.p_values[2].is_nan());
+    }
+}
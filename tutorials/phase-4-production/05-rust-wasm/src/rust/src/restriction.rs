@@ -0,0 +1,235 @@
+//! Restriction Enzyme Digests
+//!
+//! Recognition site search and fragment sizes for linear
+//! and circular sequences, built on the IUPAC motif scanner.
+
+use crate::sequence::{compile_iupac, reverse_complement_masks, scan_masks};
+
+/// Common enzymes: (name, recognition site, top-strand cut offset)
+///
+/// All sites are palindromic, so the bottom strand is cut at
+/// `site length - cut` from the start of the site.
+const BUILTIN_ENZYMES: [(&str, &str, i64); 20] = [
+    ("AluI", "AGCT", 2),
+    ("BamHI", "GGATCC", 1),
+    ("BglII", "AGATCT", 1),
+    ("EcoRI", "GAATTC", 1),
+    ("EcoRV", "GATATC", 3),
+    ("HaeIII", "GGCC", 2),
+    ("HindIII", "AAGCTT", 1),
+    ("HinfI", "GANTC", 1),
+    ("KpnI", "GGTACC", 5),
+    ("NcoI", "CCATGG", 1),
+    ("NdeI", "CATATG", 2),
+    ("NotI", "GCGGCCGC", 2),
+    ("PstI", "CTGCAG", 5),
+    ("SacI", "GAGCTC", 5),
+    ("SalI", "GTCGAC", 1),
+    ("SmaI", "CCCGGG", 3),
+    ("SpeI", "ACTAGT", 1),
+    ("XbaI", "TCTAGA", 1),
+    ("XhoI", "CTCGAG", 1),
+    ("XmnI", "GAANNNNTTC", 5),
+];
+
+/// Recognition pattern and cut offsets, relative to the start of the
+/// site on the strand it is written for
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enzyme {
+    pub name: String,
+    pub pattern: String,
+    pub cut: i64,
+    pub cut_complement: i64,
+}
+
+impl Enzyme {
+    /// `cut_complement` defaults to the palindromic position
+    pub fn new(name: &str, pattern: &str, cut: i64, cut_complement: Option<i64>) -> Enzyme {
+        Enzyme {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            cut,
+            cut_complement: cut_complement.unwrap_or(pattern.len() as i64 - cut),
+        }
+    }
+
+    /// Look up a built-in enzyme (case-insensitive)
+    pub fn builtin(name: &str) -> Result<Enzyme, String> {
+        BUILTIN_ENZYMES
+            .iter()
+            .find(|(n, _, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(n, pattern, cut)| Enzyme::new(n, pattern, cut, None))
+            .ok_or_else(|| format!("Unknown enzyme: {}", name))
+    }
+}
+
+/// Digest of one sequence by one enzyme
+///
+/// Cut positions count the bases to the left of the cut (0..=len).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Digest {
+    pub sites: Vec<u32>,
+    pub cuts_top: Vec<u32>,
+    pub cuts_bottom: Vec<u32>,
+    pub fragments: Vec<u32>,
+}
+
+/// Fragment lengths from sorted, distinct top-strand cuts
+fn fragment_lengths(cuts: &[u32], len: u32, circular: bool) -> Vec<u32> {
+    if cuts.is_empty() {
+        return if len > 0 { vec![len] } else { vec![] };
+    }
+
+    let mut fragments: Vec<u32> = cuts.windows(2).map(|w| w[1] - w[0]).collect();
+
+    if circular {
+        fragments.push(len - cuts[cuts.len() - 1] + cuts[0]);
+    } else {
+        fragments.insert(0, cuts[0]);
+        fragments.push(len - cuts[cuts.len() - 1]);
+    }
+
+    fragments
+}
+
+/// Find sites and cut positions of `enzyme` in `sequence`
+///
+/// Circular sequences also match sites spanning the origin; cuts are then
+/// reported modulo the sequence length. On linear sequences cuts that
+/// fall outside the sequence are dropped.
+pub fn digest(sequence: &str, enzyme: &Enzyme, circular: bool) -> Result<Digest, String> {
+    let masks = compile_iupac(&enzyme.pattern)
+        .ok_or_else(|| format!("Invalid recognition site for {}: {}", enzyme.name, enzyme.pattern))?;
+    let reverse = reverse_complement_masks(&masks);
+
+    let bytes = sequence.as_bytes();
+    let len = bytes.len();
+    let site_len = masks.len() as i64;
+
+    let mut text = bytes.to_vec();
+    if circular && len > 0 {
+        text.extend(bytes.iter().cycle().take(masks.len() - 1));
+    }
+
+    // (site start, top cut, bottom cut) before wrapping/clipping
+    let mut hits: Vec<(usize, i64, i64)> = scan_masks(&text, &masks, false)
+        .into_iter()
+        .map(|p| (p, p as i64 + enzyme.cut, p as i64 + enzyme.cut_complement))
+        .collect();
+
+    // Non-palindromic sites also occur in reverse orientation
+    if reverse != masks {
+        hits.extend(scan_masks(&text, &reverse, false).into_iter().map(|p| {
+            let p = p as i64;
+            (p as usize, p + site_len - enzyme.cut_complement, p + site_len - enzyme.cut)
+        }));
+    }
+
+    hits.retain(|&(p, _, _)| p < len);
+    hits.sort_unstable();
+
+    let place = |cut: i64| -> Option<u32> {
+        if circular {
+            Some(cut.rem_euclid(len as i64) as u32)
+        } else {
+            (0..=len as i64).contains(&cut).then_some(cut as u32)
+        }
+    };
+
+    let mut cuts_top: Vec<u32> = hits.iter().filter_map(|&(_, top, _)| place(top)).collect();
+    let mut cuts_bottom: Vec<u32> = hits.iter().filter_map(|&(_, _, bottom)| place(bottom)).collect();
+    cuts_top.sort_unstable();
+    cuts_top.dedup();
+    cuts_bottom.sort_unstable();
+    cuts_bottom.dedup();
+
+    // Cuts at the very ends of a linear sequence do not create fragments
+    let interior: Vec<u32> = cuts_top
+        .iter()
+        .copied()
+        .filter(|&c| circular || (c > 0 && (c as usize) < len))
+        .collect();
+
+    Ok(Digest {
+        sites: hits.iter().map(|&(p, _, _)| p as u32).collect(),
+        fragments: fragment_lengths(&interior, len as u32, circular),
+        cuts_top,
+        cuts_bottom,
+    })
+}
+
+/// Digest `sequence` with each enzyme, in order
+pub fn restriction_sites(sequence: &str, enzymes: &[Enzyme], circular: bool) -> Result<Vec<Digest>, String> {
+    enzymes
+        .iter()
+        .map(|enzyme| digest(sequence, enzyme, circular))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // EcoRI (G^AATTC) at 5 and 31, BamHI (G^GATCC) at 20; 41 bp
+    const PLASMID: &str = "AAAAAGAATTCAAAAAAAAAGGATCCAAAAAGAATTCAAAA";
+
+    #[test]
+    fn test_linear_digest() {
+        let eco = digest(PLASMID, &Enzyme::builtin("ecori").unwrap(), false).unwrap();
+        assert_eq!(eco.sites, vec![5, 31]);
+        assert_eq!(eco.cuts_top, vec![6, 32]);
+        assert_eq!(eco.cuts_bottom, vec![10, 36]);
+        assert_eq!(eco.fragments, vec![6, 26, 9]);
+
+        let bam = digest(PLASMID, &Enzyme::builtin("BamHI").unwrap(), false).unwrap();
+        assert_eq!(bam.cuts_top, vec![21]);
+        assert_eq!(bam.fragments, vec![21, 20]);
+
+        let none = digest(PLASMID, &Enzyme::builtin("NotI").unwrap(), false).unwrap();
+        assert!(none.sites.is_empty());
+        assert_eq!(none.fragments, vec![41]);
+
+        assert!(Enzyme::builtin("NoSuchI").is_err());
+    }
+
+    #[test]
+    fn test_circular_digest() {
+        let eco = digest(PLASMID, &Enzyme::builtin("EcoRI").unwrap(), true).unwrap();
+        assert_eq!(eco.fragments, vec![26, 15]);
+
+        let bam = digest(PLASMID, &Enzyme::builtin("BamHI").unwrap(), true).unwrap();
+        assert_eq!(bam.fragments, vec![41]);
+
+        // Second EcoRI site spans the origin (GAA...TTC)
+        let origin = "TTCAAGAATTCAAAAAAGAA";
+        let linear = digest(origin, &Enzyme::builtin("EcoRI").unwrap(), false).unwrap();
+        assert_eq!(linear.sites, vec![5]);
+        assert_eq!(linear.fragments, vec![6, 14]);
+
+        let circular = digest(origin, &Enzyme::builtin("EcoRI").unwrap(), true).unwrap();
+        assert_eq!(circular.sites, vec![5, 17]);
+        assert_eq!(circular.cuts_top, vec![6, 18]);
+        assert_eq!(circular.cuts_bottom, vec![2, 10]);
+        assert_eq!(circular.fragments, vec![12, 8]);
+    }
+
+    #[test]
+    fn test_custom_enzymes() {
+        // BsaI GGTCTC(1/5) is not palindromic: check both orientations
+        let bsai = Enzyme::new("BsaI", "GGTCTC", 7, Some(11));
+        let forward = digest("GGTCTCAAAAAAAAAAA", &bsai, false).unwrap();
+        assert_eq!(forward.cuts_top, vec![7]);
+        assert_eq!(forward.cuts_bottom, vec![11]);
+
+        let reverse = digest("AAAAAAAAGAGACCAAAA", &bsai, false).unwrap();
+        assert_eq!(reverse.sites, vec![8]);
+        assert_eq!(reverse.cuts_top, vec![3]);
+        assert_eq!(reverse.cuts_bottom, vec![7]);
+
+        let hinf = digest("CCGATTCGGAGTCC", &Enzyme::builtin("HinfI").unwrap(), false).unwrap();
+        assert_eq!(hinf.sites, vec![2, 8]);
+        assert_eq!(hinf.fragments, vec![3, 6, 5]);
+
+        assert!(digest(PLASMID, &Enzyme::new("Bad", "GAXTC", 1, None), false).is_err());
+    }
+}
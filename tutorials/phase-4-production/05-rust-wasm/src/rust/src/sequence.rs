@@ -1,11 +1,13 @@
 //! Sequence Operations
 //!
 //! Fast algorithms for DNA/RNA sequence analysis including
-//! k-mer counting, GC content, and basic alignment.
+//! k-mer counting, GC content, FASTA/FASTQ parsing, and basic alignment.
 
-use wasm_bindgen::prelude::*;
 use std::collections::HashMap;
 
+use crate::matrix::MatrixResult;
+use vcf_parser::{fasta, Region};
+
 /// Calculate GC content of a DNA sequence
 /// 
 /// # Arguments
@@ -13,7 +15,6 @@ use std::collections::HashMap;
 /// 
 /// # Returns
 /// GC content as fraction (0.0 - 1.0)
-#[wasm_bindgen]
 pub fn gc_content(sequence: &str) -> f64 {
     if sequence.is_empty() {
         return 0.0;
@@ -37,7 +38,6 @@ pub fn gc_content(sequence: &str) -> f64 {
 }
 
 /// Calculate GC content in sliding windows
-#[wasm_bindgen]
 pub fn gc_content_windows(sequence: &str, window_size: usize, step: usize) -> Vec<f64> {
     if sequence.len() < window_size || window_size == 0 || step == 0 {
         return vec![];
@@ -54,11 +54,377 @@ pub fn gc_content_windows(sequence: &str, window_size: usize, step: usize) -> Ve
     results
 }
 
+/// Per-window values with their 0-based, half-open coordinates
+pub struct WindowedValues {
+    starts: Vec<u32>,
+    ends: Vec<u32>,
+    values: Vec<f64>,
+}
+
+impl WindowedValues {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    
+    pub fn starts(&self) -> Vec<u32> {
+        self.starts.clone()
+    }
+    
+    /// Exclusive window ends; the last may be short of `start + window_size`
+    pub fn ends(&self) -> Vec<u32> {
+        self.ends.clone()
+    }
+    
+    pub fn values(&self) -> Vec<f64> {
+        self.values.clone()
+    }
+}
+
+/// Calculate GC content in sliding windows, with window coordinates
+///
+/// Unlike `gc_content_windows`, bases past the last full window are not
+/// dropped: they are covered by one trailing partial window starting at
+/// the next step. A sequence shorter than the window yields a single
+/// window over the whole sequence.
+pub fn gc_content_windows_positions(sequence: &str, window_size: usize, step: usize) -> WindowedValues {
+    let mut result = WindowedValues {
+        starts: vec![],
+        ends: vec![],
+        values: vec![],
+    };
+    if sequence.is_empty() || window_size == 0 || step == 0 {
+        return result;
+    }
+    
+    let len = sequence.len();
+    let mut start = 0;
+    loop {
+        let end = (start + window_size).min(len);
+        result.starts.push(start as u32);
+        result.ends.push(end as u32);
+        result.values.push(gc_content(&sequence[start..end]));
+        
+        start += step;
+        if end == len || start >= len {
+            break;
+        }
+    }
+    
+    result
+}
+
+/// GC skew (G - C) / (G + C) of a window; 0 when it has no G or C
+fn gc_skew(window: &[u8]) -> f64 {
+    let (g, c) = window.iter().fold((0u32, 0u32), |(g, c), &b| match b {
+        b'G' | b'g' => (g + 1, c),
+        b'C' | b'c' => (g, c + 1),
+        _ => (g, c),
+    });
+    
+    if g + c == 0 {
+        0.0
+    } else {
+        (g as f64 - c as f64) / (g + c) as f64
+    }
+}
+
+/// Calculate GC skew (G - C) / (G + C) in sliding windows
+///
+/// Windows without any G or C have a skew of 0. Like `gc_content_windows`,
+/// only full windows are reported.
+pub fn gc_skew_windows(sequence: &str, window: usize, step: usize) -> Vec<f64> {
+    if sequence.len() < window || window == 0 || step == 0 {
+        return vec![];
+    }
+    
+    let bytes = sequence.as_bytes();
+    (0..=bytes.len() - window)
+        .step_by(step)
+        .map(|start| gc_skew(&bytes[start..start + window]))
+        .collect()
+}
+
+/// Cumulative GC skew over consecutive non-overlapping windows
+///
+/// The last window may be partial so the whole sequence is covered. On a
+/// bacterial chromosome the minimum marks the replication origin and the
+/// maximum the terminus.
+pub fn cumulative_gc_skew(sequence: &str, window: usize) -> Vec<f64> {
+    if window == 0 {
+        return vec![];
+    }
+    
+    sequence
+        .as_bytes()
+        .chunks(window)
+        .scan(0.0, |total, chunk| {
+            *total += gc_skew(chunk);
+            Some(*total)
+        })
+        .collect()
+}
+
+/// Cumulative GC skew with the positions of its extremes
+pub struct SkewResult {
+    cumulative: Vec<f64>,
+    window: usize,
+    len: usize,
+    min_index: usize,
+    max_index: usize,
+}
+
+impl SkewResult {
+    pub fn cumulative(&self) -> Vec<f64> {
+        self.cumulative.clone()
+    }
+    
+    /// Index of the window where cumulative skew is lowest
+    pub fn min_index(&self) -> usize {
+        self.min_index
+    }
+    
+    /// Index of the window where cumulative skew is highest
+    pub fn max_index(&self) -> usize {
+        self.max_index
+    }
+    
+    /// Predicted origin: 0-based position just past the minimum window
+    pub fn origin(&self) -> usize {
+        self.boundary(self.min_index)
+    }
+    
+    /// Predicted terminus: 0-based position just past the maximum window
+    pub fn terminus(&self) -> usize {
+        self.boundary(self.max_index)
+    }
+}
+
+impl SkewResult {
+    fn boundary(&self, index: usize) -> usize {
+        if self.cumulative.is_empty() {
+            0
+        } else {
+            ((index + 1) * self.window).min(self.len)
+        }
+    }
+}
+
+/// Cumulative GC skew and its argmin / argmax (see `cumulative_gc_skew`)
+///
+/// Ties resolve to the first window reaching the extreme.
+pub fn gc_skew_analysis(sequence: &str, window: usize) -> SkewResult {
+    let cumulative = cumulative_gc_skew(sequence, window);
+    
+    let mut min_index = 0;
+    let mut max_index = 0;
+    for (i, &value) in cumulative.iter().enumerate() {
+        if value < cumulative[min_index] {
+            min_index = i;
+        }
+        if value > cumulative[max_index] {
+            max_index = i;
+        }
+    }
+    
+    SkewResult {
+        cumulative,
+        window,
+        len: sequence.len(),
+        min_index,
+        max_index,
+    }
+}
+
+/// Window size of the Gardiner-Garden & Frommer CpG island scan
+const CPG_WINDOW: usize = 200;
+
+/// A CpG island with 1-based inclusive coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpgIsland {
+    pub start: u32,
+    pub end: u32,
+    /// G+C fraction of the non-N bases
+    pub gc_content: f64,
+    /// Observed / expected CpG ratio
+    pub oe_ratio: f64,
+}
+
+impl CpgIsland {
+    pub fn length(&self) -> u32 {
+        self.end - self.start + 1
+    }
+}
+
+/// Prefix counts for O(1) composition queries over any interval
+struct CpgCounts {
+    bytes: Vec<u8>,
+    valid: Vec<u32>,
+    c: Vec<u32>,
+    g: Vec<u32>,
+    cpg: Vec<u32>,
+}
+
+impl CpgCounts {
+    fn new(sequence: &str) -> CpgCounts {
+        let bytes = sequence.as_bytes().to_ascii_uppercase();
+        let n = bytes.len();
+        let mut counts = CpgCounts {
+            valid: vec![0; n + 1],
+            c: vec![0; n + 1],
+            g: vec![0; n + 1],
+            cpg: vec![0; n + 1],
+            bytes,
+        };
+        
+        for (i, &base) in counts.bytes.iter().enumerate() {
+            counts.valid[i + 1] = counts.valid[i] + matches!(base, b'A' | b'C' | b'G' | b'T') as u32;
+            counts.c[i + 1] = counts.c[i] + (base == b'C') as u32;
+            counts.g[i + 1] = counts.g[i] + (base == b'G') as u32;
+            counts.cpg[i + 1] = counts.cpg[i] + counts.is_cpg(i) as u32;
+        }
+        
+        counts
+    }
+    
+    /// Whether a CpG dinucleotide starts at `i`
+    fn is_cpg(&self, i: usize) -> bool {
+        self.bytes[i] == b'C' && self.bytes.get(i + 1) == Some(&b'G')
+    }
+    
+    /// (non-N bases, GC fraction, observed/expected CpG) of [start, end)
+    fn stats(&self, start: usize, end: usize) -> (u32, f64, f64) {
+        let valid = self.valid[end] - self.valid[start];
+        let c = (self.c[end] - self.c[start]) as f64;
+        let g = (self.g[end] - self.g[start]) as f64;
+        let cpg = if end > start { self.cpg[end - 1] - self.cpg[start] } else { 0 } as f64;
+        
+        let gc = if valid > 0 { (c + g) / valid as f64 } else { 0.0 };
+        let oe = if c > 0.0 && g > 0.0 { cpg * valid as f64 / (c * g) } else { 0.0 };
+        (valid, gc, oe)
+    }
+    
+    /// Island criteria; intervals that are mostly N never qualify
+    fn qualifies(&self, start: usize, end: usize, min_gc: f64, min_oe_ratio: f64) -> bool {
+        let (valid, gc, oe) = self.stats(start, end);
+        2 * valid as usize >= end - start && gc >= min_gc && oe >= min_oe_ratio
+    }
+    
+    /// Shrink [start, end) to begin and end on a CpG; None if it has none
+    fn trim_to_cpg(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        let first = (start..end.saturating_sub(1)).find(|&i| self.is_cpg(i))?;
+        let last = (first..end - 1).rev().find(|&i| self.is_cpg(i))?;
+        Some((first, last + 2))
+    }
+}
+
+/// Detect CpG islands (Gardiner-Garden & Frommer)
+///
+/// Slides a 200 bp window one base at a time, merges overlapping windows
+/// with GC >= `min_gc` and observed/expected CpG >= `min_oe_ratio`, then
+/// trims each region to its outermost CpGs and shrinks it until the
+/// whole island meets the criteria. Islands shorter than `min_length` are
+/// dropped. N bases are excluded from all counts. Typical values are
+/// 200, 0.5 and 0.6.
+pub fn find_cpg_islands(sequence: &str, min_length: usize, min_gc: f64, min_oe_ratio: f64) -> Vec<CpgIsland> {
+    let counts = CpgCounts::new(sequence);
+    let n = counts.bytes.len();
+    if n < CPG_WINDOW {
+        return vec![];
+    }
+    
+    // Merge qualifying windows that overlap or touch
+    let mut regions: Vec<(usize, usize)> = Vec::new();
+    for start in 0..=n - CPG_WINDOW {
+        let end = start + CPG_WINDOW;
+        if !counts.qualifies(start, end, min_gc, min_oe_ratio) {
+            continue;
+        }
+        match regions.last_mut() {
+            Some(region) if start <= region.1 => region.1 = end,
+            _ => regions.push((start, end)),
+        }
+    }
+    
+    let mut islands = Vec::new();
+    
+    for (start, end) in regions {
+        let mut bounds = counts.trim_to_cpg(start, end);
+        let mut trim_start = true;
+        
+        // Alternately drop a base from each end until the island qualifies
+        while let Some((s, e)) = bounds {
+            if e - s < min_length.max(2) || counts.qualifies(s, e, min_gc, min_oe_ratio) {
+                break;
+            }
+            bounds = if trim_start { counts.trim_to_cpg(s + 1, e) } else { counts.trim_to_cpg(s, e - 1) };
+            trim_start = !trim_start;
+        }
+        
+        if let Some((s, e)) = bounds {
+            if e - s >= min_length && counts.qualifies(s, e, min_gc, min_oe_ratio) {
+                let (_, gc_content, oe_ratio) = counts.stats(s, e);
+                islands.push(CpgIsland {
+                    start: s as u32 + 1,
+                    end: e as u32,
+                    gc_content,
+                    oe_ratio,
+                });
+            }
+        }
+    }
+    
+    islands
+}
+
+/// CpG islands as parallel arrays
+pub struct CpgIslandResult {
+    islands: Vec<CpgIsland>,
+}
+
+impl CpgIslandResult {
+    pub fn count(&self) -> usize {
+        self.islands.len()
+    }
+    
+    /// 1-based start positions
+    pub fn starts(&self) -> Vec<u32> {
+        self.islands.iter().map(|i| i.start).collect()
+    }
+    
+    /// 1-based inclusive end positions
+    pub fn ends(&self) -> Vec<u32> {
+        self.islands.iter().map(|i| i.end).collect()
+    }
+    
+    pub fn lengths(&self) -> Vec<u32> {
+        self.islands.iter().map(|i| i.length()).collect()
+    }
+    
+    /// GC fraction of each island
+    pub fn gc_content(&self) -> Vec<f64> {
+        self.islands.iter().map(|i| i.gc_content).collect()
+    }
+    
+    pub fn oe_ratios(&self) -> Vec<f64> {
+        self.islands.iter().map(|i| i.oe_ratio).collect()
+    }
+}
+
+/// Detect CpG islands (see `find_cpg_islands`)
+pub fn cpg_islands(sequence: &str, min_length: usize, min_gc: f64, min_oe_ratio: f64) -> CpgIslandResult {
+    CpgIslandResult {
+        islands: find_cpg_islands(sequence, min_length, min_gc, min_oe_ratio),
+    }
+}
+
 /// Count k-mers in a sequence
 /// 
 /// Returns sorted array of [kmer, count] pairs as a flat string
 /// Format: "ACGT:5,CGTA:3,..."
-#[wasm_bindgen]
 pub fn count_kmers(sequence: &str, k: usize) -> String {
     if sequence.len() < k || k == 0 {
         return String::new();
@@ -78,7 +444,7 @@ pub fn count_kmers(sequence: &str, k: usize) -> String {
     
     // Convert to sorted vec and format as string
     let mut sorted: Vec<_> = counts.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by count descending
+    sorted.sort_by_key(|&(_, count)| std::cmp::Reverse(count)); // Sort by count descending
     
     sorted
         .into_iter()
@@ -91,14 +457,12 @@ pub fn count_kmers(sequence: &str, k: usize) -> String {
 }
 
 /// K-mer result structure for more complex queries
-#[wasm_bindgen]
 pub struct KmerResult {
     kmers: Vec<String>,
     counts: Vec<u32>,
     total_kmers: u32,
 }
 
-#[wasm_bindgen]
 impl KmerResult {
     pub fn kmers(&self) -> Vec<String> {
         self.kmers.clone()
@@ -117,7 +481,6 @@ impl KmerResult {
     }
 }
 
-#[wasm_bindgen]
 pub fn count_kmers_detailed(sequence: &str, k: usize) -> KmerResult {
     if sequence.len() < k || k == 0 {
         return KmerResult {
@@ -142,7 +505,7 @@ pub fn count_kmers_detailed(sequence: &str, k: usize) -> KmerResult {
     }
     
     let mut sorted: Vec<_> = counts.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+    sorted.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
     
     let (kmers, counts): (Vec<_>, Vec<_>) = sorted.into_iter().unzip();
     
@@ -153,8 +516,181 @@ pub fn count_kmers_detailed(sequence: &str, k: usize) -> KmerResult {
     }
 }
 
+/// Longest k-mer that fits the 2-bit packed encoding
+const MAX_PACKED_K: usize = 32;
+
+/// 2-bit code of a nucleotide (A=0, C=1, G=2, T=3), case-insensitive
+#[inline]
+pub(crate) fn base_code(base: u8) -> Option<u64> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Count 2-bit packed k-mers (k <= 32), skipping windows with non-ACGT bases
+///
+/// With `canonical`, each k-mer is keyed by the smaller of its encoding and
+/// that of its reverse complement (the lexicographic minimum). Returns the
+/// counts and the number of k-mers seen.
+pub(crate) fn packed_kmer_counts(bytes: &[u8], k: usize, canonical: bool) -> (HashMap<u64, u32>, u32) {
+    let mut counts: HashMap<u64, u32> = HashMap::new();
+    let mut total = 0u32;
+    
+    if k == 0 || k > MAX_PACKED_K || bytes.len() < k {
+        return (counts, total);
+    }
+    
+    let mask = if k == MAX_PACKED_K { u64::MAX } else { (1u64 << (2 * k)) - 1 };
+    let top_shift = 2 * (k as u64 - 1);
+    let (mut forward, mut reverse) = (0u64, 0u64);
+    let mut valid = 0usize;
+    
+    for &base in bytes {
+        let code = match base_code(base) {
+            Some(code) => code,
+            None => {
+                valid = 0;
+                continue;
+            }
+        };
+        
+        forward = ((forward << 2) | code) & mask;
+        reverse = (reverse >> 2) | ((3 - code) << top_shift);
+        valid += 1;
+        
+        if valid >= k {
+            let key = if canonical { forward.min(reverse) } else { forward };
+            *counts.entry(key).or_insert(0) += 1;
+            total += 1;
+        }
+    }
+    
+    (counts, total)
+}
+
+/// Decode a 2-bit packed k-mer back to an uppercase string
+pub(crate) fn decode_kmer(code: u64, k: usize) -> String {
+    (0..k)
+        .rev()
+        .map(|i| b"ACGT"[((code >> (2 * i)) & 3) as usize] as char)
+        .collect()
+}
+
+/// Count canonical k-mers (k <= 32) using a 2-bit packed encoding
+///
+/// A k-mer and its reverse complement are counted together under the
+/// lexicographically smaller of the two.
+pub fn count_kmers_canonical(sequence: &str, k: usize) -> KmerResult {
+    count_kmers_packed(sequence, k, true)
+}
+
+/// Packed k-mer counter (k <= 32) with optional canonical counting
+///
+/// Results are sorted by descending count, then by k-mer. An empty result
+/// is returned for k = 0 or k > 32.
+pub fn count_kmers_packed(sequence: &str, k: usize, canonical: bool) -> KmerResult {
+    let (counts, total) = packed_kmer_counts(sequence.as_bytes(), k, canonical);
+    
+    let mut sorted: Vec<(u64, u32)> = counts.into_iter().collect();
+    sorted.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    
+    KmerResult {
+        kmers: sorted.iter().map(|&(code, _)| decode_kmer(code, k)).collect(),
+        counts: sorted.iter().map(|&(_, count)| count).collect(),
+        total_kmers: total,
+    }
+}
+
+/// Normalized canonical k-mer spectrum of a sequence
+///
+/// Holds the packed frequency map so one profile can be compared
+/// against many others without recounting.
+pub struct KmerProfile {
+    k: usize,
+    total: u32,
+    frequencies: HashMap<u64, f64>,
+}
+
+impl KmerProfile {
+    pub fn k(&self) -> usize {
+        self.k
+    }
+    
+    /// Number of k-mers counted
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+    
+    /// Number of distinct canonical k-mers
+    pub fn distinct(&self) -> usize {
+        self.frequencies.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.frequencies.is_empty()
+    }
+}
+
+/// Canonical k-mer frequency profile (k <= 32)
+///
+/// Sequences shorter than k give an empty profile.
+pub fn kmer_profile(sequence: &str, k: usize) -> KmerProfile {
+    let (counts, total) = packed_kmer_counts(sequence.as_bytes(), k, true);
+    
+    KmerProfile {
+        k,
+        total,
+        frequencies: counts
+            .into_iter()
+            .map(|(code, count)| (code, count as f64 / total as f64))
+            .collect(),
+    }
+}
+
+/// Distance between two k-mer profiles
+///
+/// `metric` is `"cosine"` (1 - cosine similarity), `"jensen-shannon"`
+/// (divergence in bits, 0..1) or `"jaccard"` (over k-mer presence).
+/// Returns NaN for empty profiles, mismatched k or an unknown metric.
+pub fn kmer_distance(a: &KmerProfile, b: &KmerProfile, metric: &str) -> f64 {
+    if a.is_empty() || b.is_empty() || a.k != b.k {
+        return f64::NAN;
+    }
+    
+    let freq = |profile: &KmerProfile, code: &u64| profile.frequencies.get(code).copied().unwrap_or(0.0);
+    
+    match metric {
+        "cosine" => {
+            let dot: f64 = a.frequencies.iter().map(|(code, &p)| p * freq(b, code)).sum();
+            let norm = |p: &KmerProfile| p.frequencies.values().map(|x| x * x).sum::<f64>().sqrt();
+            (1.0 - dot / (norm(a) * norm(b))).max(0.0)
+        }
+        "jensen-shannon" => {
+            // Sum over the union of k-mers of p log2(p / m) terms
+            let half_kl = |p: f64, q: f64| if p > 0.0 { 0.5 * p * (2.0 * p / (p + q)).log2() } else { 0.0 };
+            let from_a: f64 = a.frequencies.iter().map(|(code, &p)| half_kl(p, freq(b, code))).sum();
+            let from_b: f64 = b.frequencies.iter().map(|(code, &q)| half_kl(q, freq(a, code))).sum();
+            (from_a + from_b).max(0.0)
+        }
+        "jaccard" => {
+            let shared = a.frequencies.keys().filter(|code| b.frequencies.contains_key(code)).count();
+            let union = a.frequencies.len() + b.frequencies.len() - shared;
+            1.0 - shared as f64 / union as f64
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// One-shot k-mer distance between two sequences (see `kmer_distance`)
+pub fn compare_sequences_kmer(seq1: &str, seq2: &str, k: usize, metric: &str) -> f64 {
+    kmer_distance(&kmer_profile(seq1, k), &kmer_profile(seq2, k), metric)
+}
+
 /// Reverse complement of DNA sequence
-#[wasm_bindgen]
 pub fn reverse_complement(sequence: &str) -> String {
     sequence
         .chars()
@@ -170,7 +706,6 @@ pub fn reverse_complement(sequence: &str) -> String {
 }
 
 /// Transcribe DNA to RNA
-#[wasm_bindgen]
 pub fn transcribe(sequence: &str) -> String {
     sequence
         .chars()
@@ -182,140 +717,1850 @@ pub fn transcribe(sequence: &str) -> String {
         .collect()
 }
 
-/// Translate RNA to protein (single frame)
-#[wasm_bindgen]
-pub fn translate(sequence: &str) -> String {
-    let codon_table = get_codon_table();
-    let seq_upper = sequence.to_uppercase();
-    let bytes = seq_upper.as_bytes();
+/// NCBI translation tables: amino acids for all 64 codons in TCAG order
+/// (codon index = 16 * first + 4 * second + third, with T/U=0, C=1, A=2, G=3)
+const GENETIC_CODES: [(u8, &[u8; 64]); 6] = [
+    // Standard
+    (1, b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG"),
+    // Vertebrate mitochondrial
+    (2, b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG"),
+    // Yeast mitochondrial
+    (3, b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG"),
+    // Mold, protozoan and coelenterate mitochondrial; Mycoplasma
+    (4, b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG"),
+    // Invertebrate mitochondrial
+    (5, b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG"),
+    // Bacterial, archaeal and plant plastid (differs from 1 only in start codons)
+    (11, b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG"),
+];
+
+const STANDARD_CODE: &[u8; 64] = GENETIC_CODES[0].1;
+
+fn genetic_code_table(genetic_code: u8) -> Result<&'static [u8; 64], String> {
+    GENETIC_CODES
+        .iter()
+        .find(|(id, _)| *id == genetic_code)
+        .map(|(_, table)| *table)
+        .ok_or_else(|| format!("Unsupported genetic code: {}", genetic_code))
+}
+
+/// Translate a single DNA or RNA codon (case-insensitive); unknown codons give 'X'
+fn translate_codon(table: &[u8; 64], codon: &[u8]) -> char {
+    let base = |b: u8| match b.to_ascii_uppercase() {
+        b'T' | b'U' => Some(0),
+        b'C' => Some(1),
+        b'A' => Some(2),
+        b'G' => Some(3),
+        _ => None,
+    };
     
+    match (base(codon[0]), base(codon[1]), base(codon[2])) {
+        (Some(a), Some(b), Some(c)) => table[16 * a + 4 * b + c] as char,
+        _ => 'X',
+    }
+}
+
+/// Translate complete codons; a trailing partial codon is dropped
+fn translate_with_table(bytes: &[u8], table: &[u8; 64]) -> String {
     bytes
-        .chunks(3)
-        .filter(|chunk| chunk.len() == 3)
-        .map(|codon| {
-            let codon_str = String::from_utf8_lossy(codon).to_string();
-            *codon_table.get(&codon_str).unwrap_or(&'X')
-        })
+        .chunks_exact(3)
+        .map(|codon| translate_codon(table, codon))
         .collect()
 }
 
-fn get_codon_table() -> HashMap<String, char> {
-    let mut table = HashMap::new();
-    
-    // Standard genetic code
-    let codons = [
-        ("UUU", 'F'), ("UUC", 'F'), ("UUA", 'L'), ("UUG", 'L'),
-        ("UCU", 'S'), ("UCC", 'S'), ("UCA", 'S'), ("UCG", 'S'),
-        ("UAU", 'Y'), ("UAC", 'Y'), ("UAA", '*'), ("UAG", '*'),
-        ("UGU", 'C'), ("UGC", 'C'), ("UGA", '*'), ("UGG", 'W'),
-        ("CUU", 'L'), ("CUC", 'L'), ("CUA", 'L'), ("CUG", 'L'),
-        ("CCU", 'P'), ("CCC", 'P'), ("CCA", 'P'), ("CCG", 'P'),
-        ("CAU", 'H'), ("CAC", 'H'), ("CAA", 'Q'), ("CAG", 'Q'),
-        ("CGU", 'R'), ("CGC", 'R'), ("CGA", 'R'), ("CGG", 'R'),
-        ("AUU", 'I'), ("AUC", 'I'), ("AUA", 'I'), ("AUG", 'M'),
-        ("ACU", 'T'), ("ACC", 'T'), ("ACA", 'T'), ("ACG", 'T'),
-        ("AAU", 'N'), ("AAC", 'N'), ("AAA", 'K'), ("AAG", 'K'),
-        ("AGU", 'S'), ("AGC", 'S'), ("AGA", 'R'), ("AGG", 'R'),
-        ("GUU", 'V'), ("GUC", 'V'), ("GUA", 'V'), ("GUG", 'V'),
-        ("GCU", 'A'), ("GCC", 'A'), ("GCA", 'A'), ("GCG", 'A'),
-        ("GAU", 'D'), ("GAC", 'D'), ("GAA", 'E'), ("GAG", 'E'),
-        ("GGU", 'G'), ("GGC", 'G'), ("GGA", 'G'), ("GGG", 'G'),
-    ];
-    
-    for (codon, aa) in codons {
-        table.insert(codon.to_string(), aa);
+/// Translate RNA (or DNA) to protein (single frame, standard code)
+pub fn translate(sequence: &str) -> String {
+    translate_with_table(sequence.as_bytes(), STANDARD_CODE)
+}
+
+/// Translate one reading frame with an NCBI genetic code
+///
+/// Frames +1..+3 start at the first, second or third base; -1..-3 do the
+/// same on the reverse complement. DNA and RNA input are both accepted.
+pub fn translate_frame(sequence: &str, frame: i8, genetic_code: u8) -> Result<String, String> {
+    let table = genetic_code_table(genetic_code)?;
+    if !(1..=3).contains(&frame.unsigned_abs()) {
+        return Err(format!("Invalid frame: {} (expected +1..+3 or -1..-3)", frame));
     }
     
-    table
+    let strand = if frame > 0 {
+        sequence.to_string()
+    } else {
+        let dna: String = sequence
+            .chars()
+            .map(|c| match c {
+                'U' => 'T',
+                'u' => 't',
+                _ => c,
+            })
+            .collect();
+        reverse_complement(&dna)
+    };
+    
+    let offset = (frame.unsigned_abs() - 1) as usize;
+    Ok(translate_with_table(strand.as_bytes().get(offset..).unwrap_or(&[]), table))
 }
 
-/// Simple Needleman-Wunsch alignment score
-/// Returns alignment score (not full traceback)
-#[wasm_bindgen]
-pub fn alignment_score(seq1: &str, seq2: &str, match_score: i32, mismatch: i32, gap: i32) -> i32 {
-    let m = seq1.len();
-    let n = seq2.len();
+/// Translations of all six frames, in the order +1, +2, +3, -1, -2, -3
+pub fn translate_six_frames(sequence: &str, genetic_code: u8) -> Result<Vec<String>, String> {
+    [1, 2, 3, -1, -2, -3]
+        .into_iter()
+        .map(|frame| translate_frame(sequence, frame, genetic_code))
+        .collect()
+}
+
+/// Open reading frames found by `find_orfs`
+///
+/// Frames are +1..+3 and -1..-3. Coordinates are 1-based on the input
+/// strand with `start` at the first base of the start codon, so reverse
+/// frame ORFs have `start > end`. Lengths are in amino acids, excluding
+/// the stop codon.
+pub struct OrfResult {
+    frames: Vec<i8>,
+    starts: Vec<u32>,
+    ends: Vec<u32>,
+    lengths: Vec<u32>,
+    proteins: Vec<String>,
+}
+
+impl OrfResult {
+    pub fn count(&self) -> usize {
+        self.frames.len()
+    }
     
-    if m == 0 || n == 0 {
-        return -(m.max(n) as i32) * gap.abs();
+    pub fn frames(&self) -> Vec<i8> {
+        self.frames.clone()
     }
     
-    let seq1_bytes = seq1.as_bytes();
-    let seq2_bytes = seq2.as_bytes();
+    pub fn starts(&self) -> Vec<u32> {
+        self.starts.clone()
+    }
     
-    // Use only two rows for space efficiency
-    let mut prev = vec![0i32; n + 1];
-    let mut curr = vec![0i32; n + 1];
+    pub fn ends(&self) -> Vec<u32> {
+        self.ends.clone()
+    }
     
-    // Initialize first row
-    for j in 0..=n {
-        prev[j] = j as i32 * gap;
+    pub fn lengths(&self) -> Vec<u32> {
+        self.lengths.clone()
     }
     
-    // Fill matrix
-    for i in 1..=m {
-        curr[0] = i as i32 * gap;
-        
-        for j in 1..=n {
-            let match_score = if seq1_bytes[i - 1] == seq2_bytes[j - 1] {
-                match_score
-            } else {
-                mismatch
-            };
+    /// Translated proteins (without the terminal '*')
+    pub fn proteins(&self) -> Vec<String> {
+        self.proteins.clone()
+    }
+}
+
+/// Find ATG-initiated open reading frames in all six frames
+///
+/// ORFs sharing a stop codon in the same frame are nested; only the
+/// longest is reported. Without `require_stop`, ORFs running off the end
+/// of the sequence are reported up to the last complete codon.
+pub fn find_orfs(sequence: &str, min_length_aa: usize, require_stop: bool) -> OrfResult {
+    find_orfs_with_options(sequence, min_length_aa, require_stop, false)
+}
+
+/// `find_orfs` with control over reporting nested ORFs
+pub fn find_orfs_with_options(
+    sequence: &str,
+    min_length_aa: usize,
+    require_stop: bool,
+    include_nested: bool,
+) -> OrfResult {
+    let forward = sequence.as_bytes();
+    let reverse = reverse_complement(sequence);
+    let n = forward.len();
+    
+    let mut result = OrfResult {
+        frames: vec![],
+        starts: vec![],
+        ends: vec![],
+        lengths: vec![],
+        proteins: vec![],
+    };
+    
+    for (strand, bytes) in [(1i8, forward), (-1i8, reverse.as_bytes())] {
+        for offset in 0..3 {
+            let codons: Vec<&[u8]> = bytes[offset.min(n)..]
+                .chunks(3)
+                .filter(|c| c.len() == 3)
+                .collect();
+            let protein: Vec<char> = codons.iter().map(|c| translate_codon(STANDARD_CODE, c)).collect();
+            
+            // Start codons (as codon indices) not yet closed by a stop
+            let mut open: Vec<usize> = Vec::new();
             
-            curr[j] = (prev[j - 1] + match_score)
-                .max(prev[j] + gap)
-                .max(curr[j - 1] + gap);
+            for k in 0..=codons.len() {
+                let at_stop = protein.get(k) == Some(&'*');
+                let at_end = k == codons.len();
+                
+                if !at_stop && !at_end {
+                    if codons[k].eq_ignore_ascii_case(b"ATG") {
+                        open.push(k);
+                    }
+                    continue;
+                }
+                
+                if at_stop || !require_stop {
+                    let reported = if include_nested { &open[..] } else { &open[..open.len().min(1)] };
+                    
+                    for &first in reported {
+                        let length = k - first;
+                        if length < min_length_aa {
+                            continue;
+                        }
+                        
+                        // Strand positions (0-based) of the first and last ORF bases
+                        let last_codon = if at_stop { k } else { k - 1 };
+                        let begin = offset + first * 3;
+                        let finish = offset + last_codon * 3 + 2;
+                        let (start, end) = if strand > 0 {
+                            (begin + 1, finish + 1)
+                        } else {
+                            (n - begin, n - finish)
+                        };
+                        
+                        result.frames.push(strand * (offset as i8 + 1));
+                        result.starts.push(start as u32);
+                        result.ends.push(end as u32);
+                        result.lengths.push(length as u32);
+                        result.proteins.push(protein[first..k].iter().collect());
+                    }
+                }
+                open.clear();
+            }
         }
-        
-        std::mem::swap(&mut prev, &mut curr);
     }
     
-    prev[n]
+    result
 }
 
-/// Find all occurrences of pattern in text
-#[wasm_bindgen]
-pub fn find_pattern(text: &str, pattern: &str) -> Vec<u32> {
-    if pattern.is_empty() || text.len() < pattern.len() {
-        return vec![];
-    }
-    
-    let text_upper = text.to_uppercase();
-    let pattern_upper = pattern.to_uppercase();
-    
-    text_upper
-        .match_indices(&pattern_upper)
-        .map(|(idx, _)| idx as u32)
-        .collect()
+/// Largest DP matrix (in cells) `local_align`/`global_align` will allocate by default
+const DEFAULT_MAX_ALIGNMENT_CELLS: usize = 50_000_000;
+
+// Traceback byte per cell: low two bits say where the best score came
+// from, the high bits whether the gap states extended an existing gap.
+const TRACE_STOP: u8 = 0;
+const TRACE_DIAG: u8 = 1;
+const TRACE_UP: u8 = 2;
+const TRACE_LEFT: u8 = 3;
+const TRACE_UP_EXTEND: u8 = 4;
+const TRACE_LEFT_EXTEND: u8 = 8;
+
+/// Affine gap scoring; a gap of length L scores `gap_open + (L - 1) * gap_extend`
+#[derive(Clone, Copy)]
+struct Scoring {
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
 }
 
-/// Hamming distance between two equal-length sequences
-#[wasm_bindgen]
-pub fn hamming_distance(seq1: &str, seq2: &str) -> u32 {
-    if seq1.len() != seq2.len() {
-        return u32::MAX;
+impl Scoring {
+    fn new(match_score: i32, mismatch: i32, gap_open: i32, gap_extend: i32) -> Scoring {
+        // Gap penalties are accepted with either sign
+        Scoring {
+            match_score,
+            mismatch,
+            gap_open: -gap_open.abs(),
+            gap_extend: -gap_extend.abs(),
+        }
     }
     
-    seq1.chars()
-        .zip(seq2.chars())
-        .filter(|(a, b)| a.to_ascii_uppercase() != b.to_ascii_uppercase())
-        .count() as u32
+    fn gap(&self, len: usize) -> i32 {
+        if len == 0 {
+            0
+        } else {
+            self.gap_open + (len as i32 - 1) * self.gap_extend
+        }
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Where an alignment may start and end
+#[derive(Clone, Copy, PartialEq)]
+enum AlignMode {
+    Local,
+    Global,
+    /// Global, but gaps before the first or after the last aligned base
+    /// of either sequence score zero (EMBOSS needle's default)
+    GlobalFreeEnds,
+}
+
+/// Gotoh dynamic programming over two rows, optionally recording traceback
+///
+/// Returns the best score and the cell it ends in: the maximum cell for
+/// local alignment, the bottom-right corner for global alignment, and the
+/// best cell of the last row or column with free end gaps (the corner on
+/// ties).
+fn affine_dp(
+    a: &[u8],
+    b: &[u8],
+    scoring: Scoring,
+    mode: AlignMode,
+    mut trace: Option<&mut [u8]>,
+) -> (i32, usize, usize) {
+    const NEG: i32 = i32::MIN / 4;
+    let (m, n) = (a.len(), b.len());
+    let width = n + 1;
+    let local = mode == AlignMode::Local;
+    let end_gap = |len: usize| if mode == AlignMode::Global { scoring.gap(len) } else { 0 };
     
-    #[test]
-    fn test_gc_content() {
-        assert!((gc_content("GCGC") - 1.0).abs() < 1e-10);
-        assert!((gc_content("ATAT") - 0.0).abs() < 1e-10);
-        assert!((gc_content("ACGT") - 0.5).abs() < 1e-10);
+    let mut h_prev = vec![0i32; width];
+    let mut h_cur = vec![0i32; width];
+    let mut f_prev = vec![NEG; width];
+    let mut f_cur = vec![NEG; width];
+    
+    if !local {
+        for (j, h) in h_prev.iter_mut().enumerate().skip(1) {
+            *h = end_gap(j);
+            if let Some(trace) = trace.as_deref_mut() {
+                trace[j] = TRACE_LEFT | if j > 1 { TRACE_LEFT_EXTEND } else { 0 };
+            }
+        }
     }
     
-    #[test]
-    fn test_reverse_complement() {
+    // Free end gaps may also end the alignment in the last column
+    let mut best = (0, 0, if local { 0 } else { n });
+    
+    for i in 1..=m {
+        h_cur[0] = if local { 0 } else { end_gap(i) };
+        if let Some(trace) = trace.as_deref_mut() {
+            trace[i * width] = if local {
+                TRACE_STOP
+            } else {
+                TRACE_UP | if i > 1 { TRACE_UP_EXTEND } else { 0 }
+            };
+        }
+        
+        let mut e = NEG;
+        for j in 1..=n {
+            let mut bits = 0;
+            
+            // Gap in seq1 (consumes seq2)
+            let e_open = h_cur[j - 1] + scoring.gap_open;
+            let e_extend = e + scoring.gap_extend;
+            e = if e_extend > e_open {
+                bits |= TRACE_LEFT_EXTEND;
+                e_extend
+            } else {
+                e_open
+            };
+            
+            // Gap in seq2 (consumes seq1)
+            let f_open = h_prev[j] + scoring.gap_open;
+            let f_extend = f_prev[j] + scoring.gap_extend;
+            let f = if f_extend > f_open {
+                bits |= TRACE_UP_EXTEND;
+                f_extend
+            } else {
+                f_open
+            };
+            f_cur[j] = f;
+            
+            let substitution = if a[i - 1].eq_ignore_ascii_case(&b[j - 1]) {
+                scoring.match_score
+            } else {
+                scoring.mismatch
+            };
+            
+            let (mut h, mut source) = (h_prev[j - 1] + substitution, TRACE_DIAG);
+            if f > h {
+                h = f;
+                source = TRACE_UP;
+            }
+            if e > h {
+                h = e;
+                source = TRACE_LEFT;
+            }
+            if local && h <= 0 {
+                h = 0;
+                source = TRACE_STOP;
+            }
+            
+            h_cur[j] = h;
+            if let Some(trace) = trace.as_deref_mut() {
+                trace[i * width + j] = bits | source;
+            }
+            if local && h > best.0 {
+                best = (h, i, j);
+            }
+        }
+        if mode == AlignMode::GlobalFreeEnds && i < m && h_cur[n] > best.0 {
+            best = (h_cur[n], i, n);
+        }
+        
+        std::mem::swap(&mut h_prev, &mut h_cur);
+        std::mem::swap(&mut f_prev, &mut f_cur);
+    }
+    
+    match mode {
+        AlignMode::Local => best,
+        AlignMode::Global => (h_prev[n], m, n),
+        AlignMode::GlobalFreeEnds => {
+            let mut end = (h_prev[n], m, n);
+            if best.0 > end.0 {
+                end = best;
+            }
+            for (j, &h) in h_prev.iter().enumerate().take(n) {
+                if h > end.0 {
+                    end = (h, m, j);
+                }
+            }
+            end
+        }
+    }
+}
+
+/// Pairwise alignment with traceback
+///
+/// Coordinates are 1-based and inclusive (all zero for an empty local
+/// alignment). The CIGAR string describes seq2 against seq1: `I` marks
+/// bases only in seq2, `D` bases only in seq1. Identity is the percentage
+/// of alignment columns with identical bases.
+pub struct AlignmentResult {
+    score: i32,
+    start1: u32,
+    end1: u32,
+    start2: u32,
+    end2: u32,
+    aligned1: String,
+    aligned2: String,
+    cigar: String,
+    identity: f64,
+    error: Option<String>,
+}
+
+impl AlignmentResult {
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+    
+    pub fn start1(&self) -> u32 {
+        self.start1
+    }
+    
+    pub fn end1(&self) -> u32 {
+        self.end1
+    }
+    
+    pub fn start2(&self) -> u32 {
+        self.start2
+    }
+    
+    pub fn end2(&self) -> u32 {
+        self.end2
+    }
+    
+    /// seq1 with `-` at gap positions
+    pub fn aligned1(&self) -> String {
+        self.aligned1.clone()
+    }
+    
+    /// seq2 with `-` at gap positions
+    pub fn aligned2(&self) -> String {
+        self.aligned2.clone()
+    }
+    
+    pub fn cigar(&self) -> String {
+        self.cigar.clone()
+    }
+    
+    /// Percent identity over the alignment length
+    pub fn identity(&self) -> f64 {
+        self.identity
+    }
+    
+    /// Why the alignment was not computed (e.g. too large), if it failed
+    pub fn error(&self) -> Option<String> {
+        self.error.clone()
+    }
+}
+
+impl AlignmentResult {
+    fn failed(error: String) -> AlignmentResult {
+        AlignmentResult {
+            score: 0,
+            start1: 0,
+            end1: 0,
+            start2: 0,
+            end2: 0,
+            aligned1: String::new(),
+            aligned2: String::new(),
+            cigar: String::new(),
+            identity: f64::NAN,
+            error: Some(error),
+        }
+    }
+}
+
+fn align(seq1: &str, seq2: &str, scoring: Scoring, mode: AlignMode, max_cells: usize) -> AlignmentResult {
+    let (a, b) = (seq1.as_bytes(), seq2.as_bytes());
+    let width = b.len() + 1;
+    let cells = (a.len() + 1).saturating_mul(width);
+    
+    if cells > max_cells {
+        return AlignmentResult::failed(format!(
+            "Alignment needs {} cells, above the limit of {}",
+            cells, max_cells
+        ));
+    }
+    
+    let mut trace = vec![TRACE_STOP; cells];
+    let (score, mut end_i, mut end_j) = affine_dp(a, b, scoring, mode, Some(&mut trace));
+    
+    // Walk back from the end cell, collecting columns in reverse
+    let (mut i, mut j) = (end_i, end_j);
+    let mut state = TRACE_DIAG;
+    let mut aligned1 = Vec::new();
+    let mut aligned2 = Vec::new();
+    let mut ops = Vec::new();
+    
+    loop {
+        let cell = trace[i * width + j];
+        match state {
+            TRACE_UP => {
+                aligned1.push(a[i - 1]);
+                aligned2.push(b'-');
+                ops.push(b'D');
+                i -= 1;
+                if cell & TRACE_UP_EXTEND == 0 {
+                    state = TRACE_DIAG;
+                }
+            }
+            TRACE_LEFT => {
+                aligned1.push(b'-');
+                aligned2.push(b[j - 1]);
+                ops.push(b'I');
+                j -= 1;
+                if cell & TRACE_LEFT_EXTEND == 0 {
+                    state = TRACE_DIAG;
+                }
+            }
+            _ => match cell & 3 {
+                TRACE_DIAG => {
+                    aligned1.push(a[i - 1]);
+                    aligned2.push(b[j - 1]);
+                    ops.push(b'M');
+                    i -= 1;
+                    j -= 1;
+                }
+                TRACE_STOP => break,
+                gap => state = gap,
+            },
+        }
+    }
+    
+    aligned1.reverse();
+    aligned2.reverse();
+    ops.reverse();
+    
+    // Unscored end gaps after the end cell (at most one sequence has any)
+    if mode == AlignMode::GlobalFreeEnds {
+        let (tail1, tail2) = (&a[end_i..], &b[end_j..]);
+        aligned1.extend_from_slice(tail1);
+        aligned2.extend(std::iter::repeat_n(b'-', tail1.len()));
+        ops.extend(std::iter::repeat_n(b'D', tail1.len()));
+        aligned1.extend(std::iter::repeat_n(b'-', tail2.len()));
+        aligned2.extend_from_slice(tail2);
+        ops.extend(std::iter::repeat_n(b'I', tail2.len()));
+        (end_i, end_j) = (a.len(), b.len());
+    }
+    
+    let mut cigar = String::new();
+    for run in ops.chunk_by(|x, y| x == y) {
+        cigar.push_str(&format!("{}{}", run.len(), run[0] as char));
+    }
+    
+    let identical = aligned1
+        .iter()
+        .zip(aligned2.iter())
+        .filter(|(x, y)| **x != b'-' && x.eq_ignore_ascii_case(y))
+        .count();
+    let identity = if ops.is_empty() { 0.0 } else { 100.0 * identical as f64 / ops.len() as f64 };
+    
+    // Empty local alignments report zero coordinates
+    let span = |start: usize, end: usize| if start < end { (start as u32 + 1, end as u32) } else { (0, 0) };
+    let (start1, end1) = span(i, end_i);
+    let (start2, end2) = span(j, end_j);
+    
+    AlignmentResult {
+        score,
+        start1,
+        end1,
+        start2,
+        end2,
+        aligned1: String::from_utf8_lossy(&aligned1).into_owned(),
+        aligned2: String::from_utf8_lossy(&aligned2).into_owned(),
+        cigar,
+        identity,
+        error: None,
+    }
+}
+
+/// Smith-Waterman local alignment with affine gaps and traceback
+pub fn local_align(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> AlignmentResult {
+    local_align_with_limit(seq1, seq2, match_score, mismatch, gap_open, gap_extend, DEFAULT_MAX_ALIGNMENT_CELLS)
+}
+
+/// `local_align` with a custom limit on the traceback matrix size (cells = bytes)
+pub fn local_align_with_limit(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    max_cells: usize,
+) -> AlignmentResult {
+    let scoring = Scoring::new(match_score, mismatch, gap_open, gap_extend);
+    align(seq1, seq2, scoring, AlignMode::Local, max_cells)
+}
+
+/// Needleman-Wunsch global alignment with affine gaps and traceback
+///
+/// Gaps at the ends of the sequences are scored like any other gap.
+/// EMBOSS needle leaves them unscored by default; use
+/// `global_align_with_options` with `penalize_end_gaps = false` to match.
+pub fn global_align(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+) -> AlignmentResult {
+    global_align_with_limit(seq1, seq2, match_score, mismatch, gap_open, gap_extend, DEFAULT_MAX_ALIGNMENT_CELLS)
+}
+
+/// `global_align` with a custom limit on the traceback matrix size (cells = bytes)
+pub fn global_align_with_limit(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    max_cells: usize,
+) -> AlignmentResult {
+    let scoring = Scoring::new(match_score, mismatch, gap_open, gap_extend);
+    align(seq1, seq2, scoring, AlignMode::Global, max_cells)
+}
+
+/// `global_align`, optionally leaving end gaps unscored
+///
+/// With `penalize_end_gaps = false` a gap before the first or after the
+/// last aligned base of either sequence scores zero, as in EMBOSS needle
+/// without `-endweight`. End gaps still appear in the aligned strings and
+/// CIGAR, and the coordinates still span both whole sequences.
+pub fn global_align_with_options(
+    seq1: &str,
+    seq2: &str,
+    match_score: i32,
+    mismatch: i32,
+    gap_open: i32,
+    gap_extend: i32,
+    penalize_end_gaps: bool,
+) -> AlignmentResult {
+    let scoring = Scoring::new(match_score, mismatch, gap_open, gap_extend);
+    let mode = if penalize_end_gaps { AlignMode::Global } else { AlignMode::GlobalFreeEnds };
+    align(seq1, seq2, scoring, mode, DEFAULT_MAX_ALIGNMENT_CELLS)
+}
+
+/// Simple Needleman-Wunsch alignment score
+/// Returns alignment score (not full traceback) with a linear gap penalty
+pub fn alignment_score(seq1: &str, seq2: &str, match_score: i32, mismatch: i32, gap: i32) -> i32 {
+    let scoring = Scoring::new(match_score, mismatch, gap, gap);
+    affine_dp(seq1.as_bytes(), seq2.as_bytes(), scoring, AlignMode::Global, None).0
+}
+
+/// Gas constant in cal / (K mol), rounded as Primer3's oligotm has it
+const GAS_CONSTANT: f64 = 1.987;
+
+/// SantaLucia (1998) unified nearest-neighbor parameters at 1 M Na+,
+/// (ΔH kcal/mol, ΔS cal/K/mol) indexed by the 2-bit codes of the 5'->3' pair
+const NN_PARAMS: [[(f64, f64); 4]; 4] = [
+    [(-7.9, -22.2), (-8.4, -22.4), (-7.8, -21.0), (-7.2, -20.4)],
+    [(-8.5, -22.7), (-8.0, -19.9), (-10.6, -27.2), (-7.8, -21.0)],
+    [(-8.2, -22.2), (-9.8, -24.4), (-8.0, -19.9), (-8.4, -22.4)],
+    [(-7.2, -21.3), (-8.2, -22.2), (-8.5, -22.7), (-7.9, -22.2)],
+];
+
+/// Initiation with a terminal G·C or A·T pair, and the symmetry correction
+const NN_INIT_GC: (f64, f64) = (0.1, -2.8);
+const NN_INIT_AT: (f64, f64) = (2.3, 4.1);
+const NN_SYMMETRY_ENTROPY: f64 = -1.4;
+
+/// Oligos shorter than this use the Wallace rule under method "auto"
+const WALLACE_MAX_LENGTH: usize = 14;
+
+/// Conditions used by `oligo_properties` (Primer3 defaults, no Mg2+)
+const DEFAULT_NA_MM: f64 = 50.0;
+const DEFAULT_OLIGO_NM: f64 = 50.0;
+
+/// Hairpin scan: shortest loop, stem score threshold (match 2 / mismatch -2)
+/// and longest sequence scanned, since the scan is cubic in length
+const HAIRPIN_MIN_LOOP: usize = 3;
+const HAIRPIN_MIN_SCORE: i32 = 10;
+const HAIRPIN_MAX_LENGTH: usize = 200;
+
+/// 2-bit codes of an unambiguous DNA sequence; None if any base is not ACGT
+fn oligo_codes(sequence: &str) -> Option<Vec<usize>> {
+    sequence.bytes().map(|b| base_code(b).map(|c| c as usize)).collect()
+}
+
+/// Wallace rule: 2 °C per A/T plus 4 °C per G/C
+fn wallace_tm(codes: &[usize]) -> f64 {
+    codes.iter().map(|&c| if c == 1 || c == 2 { 4.0 } else { 2.0 }).sum()
+}
+
+/// Nearest-neighbor Tm with the SantaLucia entropy salt correction
+fn nearest_neighbor_tm(codes: &[usize], na_conc_mm: f64, oligo_conc_nm: f64) -> f64 {
+    let n = codes.len();
+    if n < 2 || na_conc_mm <= 0.0 || oligo_conc_nm <= 0.0 {
+        return f64::NAN;
+    }
+    
+    let (mut dh, mut ds) = codes
+        .windows(2)
+        .map(|pair| NN_PARAMS[pair[0]][pair[1]])
+        .fold((0.0, 0.0), |(h, s), (dh, ds)| (h + dh, s + ds));
+    
+    for end in [codes[0], codes[n - 1]] {
+        let (h, s) = if end == 1 || end == 2 { NN_INIT_GC } else { NN_INIT_AT };
+        dh += h;
+        ds += s;
+    }
+    
+    // Self-complementary duplexes need no strand excess term
+    let self_complementary = (0..n).all(|i| codes[i] == 3 - codes[n - 1 - i]);
+    let strand_factor = if self_complementary {
+        ds += NN_SYMMETRY_ENTROPY;
+        1.0
+    } else {
+        4.0
+    };
+    
+    ds += 0.368 * (n - 1) as f64 * (na_conc_mm / 1000.0).ln();
+    
+    dh * 1000.0 / (ds + GAS_CONSTANT * (oligo_conc_nm * 1e-9 / strand_factor).ln()) - 273.15
+}
+
+/// Melting temperature of an oligo in °C
+///
+/// `method` is "wallace" (2·AT + 4·GC), "nn" (nearest-neighbor, SantaLucia
+/// 1998 with salt correction, for a duplex with its exact complement) or
+/// "auto" (Wallace below 14 nt, nearest-neighbor otherwise). Concentrations
+/// are Na+ in mM and oligo in nM; the Wallace rule ignores them.
+///
+/// Returns NaN for ambiguous or non-DNA bases, an empty sequence or an
+/// unknown method.
+pub fn melting_temperature(sequence: &str, method: &str, na_conc_mm: f64, oligo_conc_nm: f64) -> f64 {
+    let codes = match oligo_codes(sequence) {
+        Some(codes) if !codes.is_empty() => codes,
+        _ => return f64::NAN,
+    };
+    
+    match method {
+        "wallace" => wallace_tm(&codes),
+        "nn" | "nearest-neighbor" => nearest_neighbor_tm(&codes, na_conc_mm, oligo_conc_nm),
+        "auto" if codes.len() < WALLACE_MAX_LENGTH => wallace_tm(&codes),
+        "auto" => nearest_neighbor_tm(&codes, na_conc_mm, oligo_conc_nm),
+        _ => f64::NAN,
+    }
+}
+
+/// Single-stranded molecular weight in g/mol (no 5' phosphate)
+fn oligo_molecular_weight(codes: &[usize]) -> f64 {
+    const NUCLEOTIDE_WEIGHTS: [f64; 4] = [313.21, 289.18, 329.21, 304.2];
+    codes.iter().map(|&c| NUCLEOTIDE_WEIGHTS[c]).sum::<f64>() - 61.96
+}
+
+/// Whether the oligo can fold back on itself
+///
+/// For every split leaving a loop of at least three bases, the 5' arm is
+/// locally aligned against the reverse complement of the 3' arm; a stem
+/// scoring 10 or more (e.g. five consecutive pairs) counts as a risk.
+fn has_hairpin_risk(sequence: &str) -> bool {
+    let n = sequence.len();
+    if n > HAIRPIN_MAX_LENGTH {
+        return false;
+    }
+    
+    let scoring = Scoring::new(2, -2, 4, 4);
+    (1..n.saturating_sub(HAIRPIN_MIN_LOOP)).any(|split| {
+        let arm = reverse_complement(&sequence[split + HAIRPIN_MIN_LOOP..]);
+        affine_dp(&sequence.as_bytes()[..split], arm.as_bytes(), scoring, AlignMode::Local, None).0 >= HAIRPIN_MIN_SCORE
+    })
+}
+
+/// Primer / oligo summary for design panels
+///
+/// Temperatures use 50 mM Na+ and 50 nM oligo. Sequences with ambiguous
+/// bases set `has_ambiguous` and report NaN temperatures and weight.
+pub struct OligoResult {
+    length: usize,
+    gc: f64,
+    tm_wallace: f64,
+    tm_nn: f64,
+    molecular_weight: f64,
+    has_hairpin_risk: bool,
+    has_ambiguous: bool,
+}
+
+impl OligoResult {
+    pub fn length(&self) -> usize {
+        self.length
+    }
+    
+    /// GC fraction of the unambiguous bases
+    pub fn gc(&self) -> f64 {
+        self.gc
+    }
+    
+    pub fn tm_wallace(&self) -> f64 {
+        self.tm_wallace
+    }
+    
+    pub fn tm_nn(&self) -> f64 {
+        self.tm_nn
+    }
+    
+    pub fn molecular_weight(&self) -> f64 {
+        self.molecular_weight
+    }
+    
+    /// Self-complementary stem of about five or more pairs with a loop of
+    /// three or more bases; always false above 200 nt
+    pub fn has_hairpin_risk(&self) -> bool {
+        self.has_hairpin_risk
+    }
+    
+    pub fn has_ambiguous(&self) -> bool {
+        self.has_ambiguous
+    }
+}
+
+/// Length, GC, melting temperatures, weight and hairpin risk of an oligo
+pub fn oligo_properties(sequence: &str) -> OligoResult {
+    let gc = gc_content(sequence);
+    
+    match oligo_codes(sequence) {
+        Some(codes) if !codes.is_empty() => OligoResult {
+            length: codes.len(),
+            gc,
+            tm_wallace: wallace_tm(&codes),
+            tm_nn: nearest_neighbor_tm(&codes, DEFAULT_NA_MM, DEFAULT_OLIGO_NM),
+            molecular_weight: oligo_molecular_weight(&codes),
+            has_hairpin_risk: has_hairpin_risk(sequence),
+            has_ambiguous: false,
+        },
+        codes => OligoResult {
+            length: sequence.chars().count(),
+            gc,
+            tm_wallace: f64::NAN,
+            tm_nn: f64::NAN,
+            molecular_weight: f64::NAN,
+            has_hairpin_risk: false,
+            has_ambiguous: codes.is_none(),
+        },
+    }
+}
+
+/// Find all occurrences of pattern in text (case-insensitive)
+///
+/// Overlapping occurrences are all reported, as 0-based offsets.
+pub fn find_pattern(text: &str, pattern: &str) -> Vec<u32> {
+    if pattern.is_empty() || text.len() < pattern.len() {
+        return vec![];
+    }
+    
+    text.as_bytes()
+        .windows(pattern.len())
+        .enumerate()
+        .filter(|(_, window)| window.eq_ignore_ascii_case(pattern.as_bytes()))
+        .map(|(idx, _)| idx as u32)
+        .collect()
+}
+
+/// Bitmask of the bases (A=1, C=2, G=4, T/U=8) an IUPAC code stands for
+fn iupac_mask(code: u8) -> u8 {
+    match code.to_ascii_uppercase() {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' | b'U' => 0b1000,
+        b'R' => 0b0101,
+        b'Y' => 0b1010,
+        b'S' => 0b0110,
+        b'W' => 0b1001,
+        b'K' => 0b1100,
+        b'M' => 0b0011,
+        b'B' => 0b1110,
+        b'D' => 0b1101,
+        b'H' => 0b1011,
+        b'V' => 0b0111,
+        b'N' => 0b1111,
+        _ => 0,
+    }
+}
+
+/// Complement of an IUPAC bitmask (swaps A/T and C/G)
+fn complement_mask(mask: u8) -> u8 {
+    ((mask & 0b0001) << 3) | ((mask & 0b1000) >> 3) | ((mask & 0b0010) << 1) | ((mask & 0b0100) >> 1)
+}
+
+/// Per-position bitmasks for an IUPAC pattern; `None` if empty or invalid
+pub(crate) fn compile_iupac(pattern: &str) -> Option<Vec<u8>> {
+    let masks: Vec<u8> = pattern.bytes().map(iupac_mask).collect();
+    (!masks.is_empty() && masks.iter().all(|&m| m != 0)).then_some(masks)
+}
+
+/// Masks of the reverse complement of a compiled pattern
+pub(crate) fn reverse_complement_masks(masks: &[u8]) -> Vec<u8> {
+    masks.iter().rev().map(|&m| complement_mask(m)).collect()
+}
+
+/// 0-based offsets where compiled masks match the text (overlaps included)
+///
+/// A text base matches when it is one of the pattern's bases. Ambiguity
+/// codes in the text only match if `text_ambiguity` is set, in which case
+/// any overlap with the pattern's bases counts.
+pub(crate) fn scan_masks(text: &[u8], masks: &[u8], text_ambiguity: bool) -> Vec<usize> {
+    if masks.is_empty() || text.len() < masks.len() {
+        return vec![];
+    }
+    
+    let matches = |base: u8, mask: u8| {
+        let t = iupac_mask(base);
+        if text_ambiguity {
+            t & mask != 0
+        } else {
+            t.count_ones() == 1 && t & mask != 0
+        }
+    };
+    
+    text.windows(masks.len())
+        .enumerate()
+        .filter(|(_, window)| window.iter().zip(masks).all(|(&b, &m)| matches(b, m)))
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Motif hits with the strand they were found on
+///
+/// Positions are 0-based offsets of the leftmost base on the forward
+/// strand; strand is 1 or -1. Palindromic motifs are reported once per
+/// strand.
+pub struct MotifHits {
+    positions: Vec<u32>,
+    strands: Vec<i8>,
+}
+
+impl MotifHits {
+    pub fn count(&self) -> usize {
+        self.positions.len()
+    }
+    
+    pub fn positions(&self) -> Vec<u32> {
+        self.positions.clone()
+    }
+    
+    pub fn strands(&self) -> Vec<i8> {
+        self.strands.clone()
+    }
+}
+
+/// Find an IUPAC degenerate pattern (e.g. `GAANNNNTTC`) on the forward strand
+///
+/// Returns 0-based offsets of all (overlapping) matches; an invalid
+/// pattern matches nothing.
+pub fn find_pattern_iupac(text: &str, pattern: &str) -> Vec<u32> {
+    find_motif(text, pattern, false, false).positions
+}
+
+/// Find an IUPAC pattern, optionally on both strands and with ambiguous text
+pub fn find_motif(text: &str, pattern: &str, both_strands: bool, text_ambiguity: bool) -> MotifHits {
+    let mut hits: Vec<(usize, i8)> = Vec::new();
+    
+    if let Some(masks) = compile_iupac(pattern) {
+        let bytes = text.as_bytes();
+        hits.extend(scan_masks(bytes, &masks, text_ambiguity).into_iter().map(|p| (p, 1)));
+        
+        if both_strands {
+            let reverse = reverse_complement_masks(&masks);
+            hits.extend(scan_masks(bytes, &reverse, text_ambiguity).into_iter().map(|p| (p, -1)));
+            hits.sort_by_key(|&(pos, strand)| (pos, -strand));
+        }
+    }
+    
+    MotifHits {
+        positions: hits.iter().map(|&(p, _)| p as u32).collect(),
+        strands: hits.iter().map(|&(_, s)| s).collect(),
+    }
+}
+
+/// IUPAC code of each A/C/G/T bitmask (see `iupac_mask`)
+const IUPAC_CODES: &[u8; 16] = b"-ACMGRSVTWYHKDBN";
+
+/// Per-position counts of A, C, G, T, other (N etc.) and gaps over
+/// equal-length sequences; None if the lengths differ or there are none
+fn column_counts(sequences: &[String]) -> Option<Vec<[u32; 6]>> {
+    let width = sequences.first()?.len();
+    if sequences.iter().any(|s| s.len() != width) {
+        return None;
+    }
+    
+    let mut counts = vec![[0u32; 6]; width];
+    for sequence in sequences {
+        for (column, base) in counts.iter_mut().zip(sequence.bytes()) {
+            let slot = match base {
+                b'-' => 5,
+                _ => base_code(base).map_or(4, |c| c as usize),
+            };
+            column[slot] += 1;
+        }
+    }
+    
+    Some(counts)
+}
+
+/// Per-position base fractions of equal-length aligned sequences
+///
+/// Rows are positions, columns A, C, G, T, N/other. Gaps (`-`) are left
+/// out of the position's total; see `base_frequency_matrix_with_gaps` to
+/// count them. Returns an empty result if the lengths differ.
+pub fn base_frequency_matrix(sequences: Vec<String>) -> MatrixResult {
+    base_frequency_matrix_with_gaps(sequences, false)
+}
+
+/// `base_frequency_matrix`, optionally with a sixth column for gaps
+///
+/// With `count_gaps`, fractions are over all sequences and each row sums
+/// to 1. Otherwise an all-gap position is a row of zeros.
+pub fn base_frequency_matrix_with_gaps(sequences: Vec<String>, count_gaps: bool) -> MatrixResult {
+    let counts = match column_counts(&sequences) {
+        Some(counts) => counts,
+        None => return MatrixResult::new(vec![], 0, 0),
+    };
+    
+    let cols = if count_gaps { 6 } else { 5 };
+    let mut data = Vec::with_capacity(counts.len() * cols);
+    for column in &counts {
+        let total: u32 = column[..cols].iter().sum();
+        data.extend(column[..cols].iter().map(|&c| if total == 0 { 0.0 } else { c as f64 / total as f64 }));
+    }
+    
+    MatrixResult::new(data, counts.len(), cols)
+}
+
+/// Consensus of equal-length aligned sequences
+///
+/// Each position takes the most frequent base if its fraction of all
+/// sequences is at least `min_fraction`. Bases tied for most frequent
+/// above the threshold give their IUPAC ambiguity code (e.g. `R` for A/G).
+/// Positions where no base reaches the threshold, e.g. from gaps, N or too
+/// few sequences, are `N`. Returns an empty string if the lengths differ.
+pub fn consensus_sequence(sequences: Vec<String>, min_fraction: f64) -> String {
+    let counts = match column_counts(&sequences) {
+        Some(counts) => counts,
+        None => return String::new(),
+    };
+    let n = sequences.len() as f64;
+    
+    counts
+        .iter()
+        .map(|column| {
+            let top = column[..4].iter().copied().max().unwrap_or(0);
+            if top == 0 || (top as f64 / n) < min_fraction {
+                return 'N';
+            }
+            let mask = (0..4).filter(|&b| column[b] == top).fold(0, |mask, b| mask | (1 << b));
+            IUPAC_CODES[mask] as char
+        })
+        .collect()
+}
+
+/// Hamming distance between two equal-length sequences (case-insensitive)
+///
+/// Returns `None` when the lengths differ.
+pub fn hamming_distance(seq1: &str, seq2: &str) -> Option<u32> {
+    if seq1.len() != seq2.len() {
+        return None;
+    }
+    
+    Some(
+        seq1.chars()
+            .zip(seq2.chars())
+            .filter(|(a, b)| !a.eq_ignore_ascii_case(b))
+            .count() as u32,
+    )
+}
+
+/// Levenshtein (edit) distance, case-insensitive
+pub fn levenshtein(seq1: &str, seq2: &str) -> u32 {
+    let (a, b) = (seq1.as_bytes(), seq2.as_bytes());
+    
+    // Use only two rows for space efficiency
+    let mut prev: Vec<u32> = (0..=b.len() as u32).collect();
+    let mut curr = vec![0u32; b.len() + 1];
+    
+    for (i, &x) in a.iter().enumerate() {
+        curr[0] = i as u32 + 1;
+        
+        for (j, &y) in b.iter().enumerate() {
+            let cost = !x.eq_ignore_ascii_case(&y) as u32;
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    
+    prev[b.len()]
+}
+
+/// Levenshtein distance if it is at most `max_distance`, otherwise `None`
+///
+/// Only the diagonal band of width 2 * max_distance + 1 is computed and
+/// the scan stops as soon as every cell in a row exceeds the limit, so
+/// distant pairs (e.g. unrelated barcodes) are rejected quickly.
+pub fn levenshtein_banded(seq1: &str, seq2: &str, max_distance: u32) -> Option<u32> {
+    let (a, b) = (seq1.as_bytes(), seq2.as_bytes());
+    let (m, n) = (a.len(), b.len());
+    let k = max_distance as usize;
+    
+    if m.abs_diff(n) > k {
+        return None;
+    }
+    
+    // Anything above the band is clamped to k + 1
+    let over = k + 1;
+    let mut prev: Vec<usize> = (0..=n).map(|j| if j <= k { j } else { over }).collect();
+    let mut curr = vec![over; n + 1];
+    
+    for i in 1..=m {
+        let lo = i.saturating_sub(k).max(1);
+        let hi = (i + k).min(n);
+        
+        curr[0] = if i <= k { i } else { over };
+        curr[lo - 1] = if lo == 1 { curr[0] } else { over };
+        if hi < n {
+            curr[hi + 1] = over;
+        }
+        
+        let mut row_min = curr[lo - 1];
+        for j in lo..=hi {
+            let cost = !a[i - 1].eq_ignore_ascii_case(&b[j - 1]) as usize;
+            let value = (prev[j - 1] + cost).min(prev[j] + 1).min(curr[j - 1] + 1).min(over);
+            curr[j] = value;
+            row_min = row_min.min(value);
+        }
+        
+        if row_min > k {
+            return None;
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    
+    (prev[n] <= k).then_some(prev[n] as u32)
+}
+
+/// Similarity as 1 - levenshtein / max(len1, len2); 1.0 for two empty sequences
+pub fn sequence_identity(seq1: &str, seq2: &str) -> f64 {
+    let max_len = seq1.len().max(seq2.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+    
+    1.0 - levenshtein(seq1, seq2) as f64 / max_len as f64
+}
+
+/// A single FASTA record
+#[derive(Clone, Debug, PartialEq)]
+pub struct FastaRecord {
+    id: String,
+    description: String,
+    sequence: String,
+}
+
+impl FastaRecord {
+    /// Identifier (header text up to the first whitespace)
+    pub fn id(&self) -> String {
+        self.id.clone()
+    }
+    
+    /// Rest of the header line after the identifier
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+    
+    /// Sequence with line breaks removed (case preserved)
+    pub fn sequence(&self) -> String {
+        self.sequence.clone()
+    }
+    
+    pub fn length(&self) -> usize {
+        self.sequence.len()
+    }
+}
+
+/// Lazy iterator over the records of a FASTA file
+pub struct FastaRecords<'a> {
+    lines: std::iter::Peekable<std::str::Lines<'a>>,
+}
+
+impl Iterator for FastaRecords<'_> {
+    type Item = FastaRecord;
+    
+    fn next(&mut self) -> Option<FastaRecord> {
+        // Skip anything before the next header
+        let header = loop {
+            if let Some(header) = self.lines.next()?.strip_prefix('>') {
+                break header.trim();
+            }
+        };
+        
+        let (id, description) = match header.split_once(char::is_whitespace) {
+            Some((id, description)) => (id, description.trim()),
+            None => (header, ""),
+        };
+        
+        let mut sequence = String::new();
+        while let Some(line) = self.lines.next_if(|line| !line.starts_with('>')) {
+            sequence.push_str(line.trim_end());
+        }
+        
+        Some(FastaRecord {
+            id: id.to_string(),
+            description: description.to_string(),
+            sequence,
+        })
+    }
+}
+
+/// Iterate over FASTA records without materializing the whole file
+pub fn fasta_records(content: &str) -> FastaRecords<'_> {
+    FastaRecords {
+        lines: content.lines().peekable(),
+    }
+}
+
+/// Parsed FASTA file
+pub struct FastaResult {
+    records: Vec<FastaRecord>,
+}
+
+impl FastaResult {
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+    
+    pub fn ids(&self) -> Vec<String> {
+        self.records.iter().map(|r| r.id.clone()).collect()
+    }
+    
+    pub fn descriptions(&self) -> Vec<String> {
+        self.records.iter().map(|r| r.description.clone()).collect()
+    }
+    
+    pub fn lengths(&self) -> Vec<u32> {
+        self.records.iter().map(|r| r.sequence.len() as u32).collect()
+    }
+    
+    pub fn sequences(&self) -> Vec<String> {
+        self.records.iter().map(|r| r.sequence.clone()).collect()
+    }
+    
+    /// Record at `index`, if any
+    pub fn record(&self, index: usize) -> Option<FastaRecord> {
+        self.records.get(index).cloned()
+    }
+}
+
+impl FastaResult {
+    pub fn iter(&self) -> std::slice::Iter<'_, FastaRecord> {
+        self.records.iter()
+    }
+}
+
+/// Parse a (multi-record) FASTA file
+///
+/// Wrapped sequence lines of any width are concatenated and soft-masked
+/// (lowercase) bases are kept as-is. Lines before the first header are
+/// ignored.
+pub fn parse_fasta(content: &str) -> FastaResult {
+    FastaResult {
+        records: fasta_records(content).collect(),
+    }
+}
+
+/// Contig length summary of an assembly
+///
+/// All values are zero for an empty assembly; `gc` is only set when
+/// computed from sequences.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyStats {
+    n_contigs: u32,
+    total_length: f64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    n50: f64,
+    l50: u32,
+    n90: f64,
+    l90: u32,
+    gc: Option<f64>,
+}
+
+impl AssemblyStats {
+    pub fn n_contigs(&self) -> u32 {
+        self.n_contigs
+    }
+    
+    pub fn total_length(&self) -> f64 {
+        self.total_length
+    }
+    
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+    
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+    
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+    
+    pub fn n50(&self) -> f64 {
+        self.n50
+    }
+    
+    pub fn l50(&self) -> u32 {
+        self.l50
+    }
+    
+    pub fn n90(&self) -> f64 {
+        self.n90
+    }
+    
+    pub fn l90(&self) -> u32 {
+        self.l90
+    }
+    
+    /// GC fraction over all ACGT bases
+    pub fn gc(&self) -> Option<f64> {
+        self.gc
+    }
+}
+
+/// Nx / Lx of descending lengths: the length of the contig at which the
+/// running total first reaches `fraction` of `total`, and its 1-based rank
+fn nx(sorted_desc: &[f64], total: f64, fraction: f64) -> (f64, u32) {
+    let mut running = 0.0;
+    for (i, &len) in sorted_desc.iter().enumerate() {
+        running += len;
+        if running >= total * fraction {
+            return (len, i as u32 + 1);
+        }
+    }
+    (0.0, 0)
+}
+
+/// Assembly statistics from contig lengths
+///
+/// N50 is the length of the contig at which the cumulative sum of lengths,
+/// sorted longest first, reaches at least half the total; L50 is how many
+/// contigs that takes (likewise for N90 / L90). Non-finite and
+/// non-positive lengths are ignored.
+pub fn assembly_stats(lengths: &[f64]) -> AssemblyStats {
+    let mut sorted: Vec<f64> = lengths.iter().copied().filter(|l| l.is_finite() && *l > 0.0).collect();
+    sorted.sort_by(|a, b| b.total_cmp(a));
+    
+    let total: f64 = sorted.iter().sum();
+    let (n50, l50) = nx(&sorted, total, 0.5);
+    let (n90, l90) = nx(&sorted, total, 0.9);
+    
+    AssemblyStats {
+        n_contigs: sorted.len() as u32,
+        total_length: total,
+        min: sorted.last().copied().unwrap_or(0.0),
+        max: sorted.first().copied().unwrap_or(0.0),
+        mean: if sorted.is_empty() { 0.0 } else { total / sorted.len() as f64 },
+        n50,
+        l50,
+        n90,
+        l90,
+        gc: None,
+    }
+}
+
+/// Assembly statistics of the records in FASTA content, with overall GC
+pub fn assembly_stats_from_fasta(content: &str) -> AssemblyStats {
+    let mut lengths = Vec::new();
+    let (mut gc, mut acgt) = (0u64, 0u64);
+    
+    for record in fasta_records(content) {
+        lengths.push(record.sequence.len() as f64);
+        for base in record.sequence.bytes() {
+            match base {
+                b'G' | b'C' | b'g' | b'c' => {
+                    gc += 1;
+                    acgt += 1;
+                }
+                b'A' | b'T' | b'a' | b't' => acgt += 1,
+                _ => {}
+            }
+        }
+    }
+    
+    AssemblyStats {
+        gc: (acgt > 0).then(|| gc as f64 / acgt as f64),
+        ..assembly_stats(&lengths)
+    }
+}
+
+/// Random access into a FASTA file via a `.fai`-style index
+///
+/// Wraps [`vcf_parser::fasta::FastaIndex`] with 1-based, inclusive
+/// coordinates and string errors.
+pub struct FastaIndex {
+    inner: fasta::FastaIndex,
+}
+
+impl FastaIndex {
+    /// Index FASTA content on the fly
+    ///
+    /// Within a record every line except the last must have the same
+    /// width (as required by `samtools faidx`); records may differ.
+    pub fn new(content: &str) -> Result<FastaIndex, String> {
+        fasta::FastaIndex::from_bytes(content.as_bytes().to_vec())
+            .map(|inner| FastaIndex { inner })
+            .map_err(|e| e.to_string())
+    }
+    
+    /// Use a precomputed `.fai` index for `content`
+    pub fn from_fai(content: &str, fai: &str) -> Result<FastaIndex, String> {
+        fasta::FastaIndex::from_bytes_with_fai(content.as_bytes().to_vec(), fai)
+            .map(|inner| FastaIndex { inner })
+            .map_err(|e| e.to_string())
+    }
+    
+    pub fn ids(&self) -> Vec<String> {
+        self.inner.records().iter().map(|r| r.name.clone()).collect()
+    }
+    
+    pub fn lengths(&self) -> Vec<u32> {
+        self.inner.records().iter().map(|r| r.length as u32).collect()
+    }
+    
+    /// Serialize the index in `.fai` format
+    pub fn to_fai(&self) -> String {
+        self.inner.to_fai()
+    }
+    
+    /// Sequence of `id` from `start` to `end` (1-based, inclusive)
+    pub fn fetch(&self, id: &str, start: usize, end: usize) -> Result<String, String> {
+        let unknown = || format!("Unknown sequence: {}", id);
+        let length = self.inner.sequence_length(id).ok_or_else(unknown)?;
+        
+        if start == 0 || start > end || end as u64 > length {
+            return Err(format!(
+                "Region {}:{}-{} is outside 1-{}",
+                id, start, end, length
+            ));
+        }
+        
+        let bases = self
+            .inner
+            .fetch(id, start as u64 - 1, (end - start + 1) as u64)
+            .map_err(|e| format!("Index for sequence '{}' does not match the FASTA content: {}", id, e))?
+            .ok_or_else(unknown)?;
+        if bases.len() != end - start + 1 {
+            return Err(format!("Index for sequence '{}' does not match the FASTA content", id));
+        }
+        Ok(bases)
+    }
+    
+    /// Fetch a region string such as `chr1:100-200`
+    pub fn fetch_region(&self, region: &str) -> Result<String, String> {
+        let region = Region::parse(region).map_err(|e| e.to_string())?;
+        let length = self
+            .inner
+            .sequence_length(&region.chrom)
+            .ok_or_else(|| format!("Unknown sequence: {}", region.chrom))?;
+        
+        self.fetch(
+            &region.chrom,
+            region.start as usize,
+            region.end.unwrap_or(length) as usize,
+        )
+    }
+}
+
+/// Fetch a region (e.g. `chr1:100-200`) from FASTA content
+///
+/// Indexes the content on every call; build a `FastaIndex` once for
+/// repeated queries.
+pub fn fetch_region(content: &str, region: &str) -> Result<String, String> {
+    FastaIndex::new(content)?.fetch_region(region)
+}
+
+/// A single FASTQ read borrowed from the input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FastqRecord<'a> {
+    pub id: &'a str,
+    pub sequence: &'a str,
+    pub quality: &'a str,
+}
+
+/// Iterator over strict 4-line FASTQ records
+///
+/// Malformed records (bad header or separator, quality length mismatch,
+/// quality characters below the Phred offset, truncation) are skipped and
+/// counted; parsing resumes at the next line that looks like a header.
+pub struct FastqRecords<'a> {
+    lines: Vec<&'a str>,
+    pos: usize,
+    offset: u8,
+    malformed: u32,
+}
+
+impl<'a> FastqRecords<'a> {
+    /// Number of malformed records skipped so far
+    pub fn malformed(&self) -> u32 {
+        self.malformed
+    }
+    
+    fn looks_like_record(&self, i: usize) -> bool {
+        self.lines[i].starts_with('@')
+            && self.lines.get(i + 2).is_some_and(|line| line.starts_with('+'))
+    }
+    
+    fn record_at(&self, i: usize) -> Option<FastqRecord<'a>> {
+        let header = self.lines.get(i)?.strip_prefix('@')?;
+        let sequence = self.lines.get(i + 1)?.trim_end();
+        let separator = self.lines.get(i + 2)?;
+        let quality = self.lines.get(i + 3)?.trim_end();
+        
+        let valid = separator.starts_with('+')
+            && quality.len() == sequence.len()
+            && sequence.bytes().all(|b| b.is_ascii_alphabetic() || b == b'.')
+            && quality.bytes().all(|q| q >= self.offset && q <= b'~');
+        
+        valid.then(|| FastqRecord {
+            id: header.split_whitespace().next().unwrap_or(""),
+            sequence,
+            quality,
+        })
+    }
+}
+
+impl<'a> Iterator for FastqRecords<'a> {
+    type Item = FastqRecord<'a>;
+    
+    fn next(&mut self) -> Option<FastqRecord<'a>> {
+        loop {
+            while self.lines.get(self.pos)?.trim().is_empty() {
+                self.pos += 1;
+            }
+            
+            if let Some(record) = self.record_at(self.pos) {
+                self.pos += 4;
+                return Some(record);
+            }
+            
+            // Resynchronize on the next plausible header
+            self.malformed += 1;
+            self.pos += 1;
+            while self.pos < self.lines.len() && !self.looks_like_record(self.pos) {
+                self.pos += 1;
+            }
+        }
+    }
+}
+
+/// Iterate over Phred+33 FASTQ records
+pub fn fastq_records(content: &str) -> FastqRecords<'_> {
+    fastq_records_with_offset(content, 33)
+}
+
+/// Iterate over FASTQ records with an explicit Phred offset (33 or 64)
+pub fn fastq_records_with_offset(content: &str, offset: u8) -> FastqRecords<'_> {
+    FastqRecords {
+        lines: content.lines().collect(),
+        pos: 0,
+        offset,
+        malformed: 0,
+    }
+}
+
+/// Summary statistics for a FASTQ file
+pub struct FastqStats {
+    n_reads: u32,
+    malformed: u32,
+    min_len: u32,
+    max_len: u32,
+    mean_len: f64,
+    mean_quality_per_position: Vec<f64>,
+    gc_fraction: f64,
+    q20_fraction: f64,
+    q30_fraction: f64,
+}
+
+impl FastqStats {
+    pub fn n_reads(&self) -> u32 {
+        self.n_reads
+    }
+    
+    /// Number of malformed records that were skipped
+    pub fn malformed(&self) -> u32 {
+        self.malformed
+    }
+    
+    pub fn min_len(&self) -> u32 {
+        self.min_len
+    }
+    
+    pub fn max_len(&self) -> u32 {
+        self.max_len
+    }
+    
+    pub fn mean_len(&self) -> f64 {
+        self.mean_len
+    }
+    
+    /// Mean Phred quality at each cycle, over reads long enough to cover it
+    pub fn mean_quality_per_position(&self) -> Vec<f64> {
+        self.mean_quality_per_position.clone()
+    }
+    
+    /// GC content over A/C/G/T bases
+    pub fn gc_fraction(&self) -> f64 {
+        self.gc_fraction
+    }
+    
+    /// Fraction of bases with quality >= 20
+    pub fn q20_fraction(&self) -> f64 {
+        self.q20_fraction
+    }
+    
+    /// Fraction of bases with quality >= 30
+    pub fn q30_fraction(&self) -> f64 {
+        self.q30_fraction
+    }
+}
+
+/// QC statistics for Phred+33 FASTQ content
+pub fn parse_fastq(content: &str) -> FastqStats {
+    parse_fastq_with_offset(content, 33)
+}
+
+/// QC statistics for FASTQ content with an explicit Phred offset (33 or 64)
+pub fn parse_fastq_with_offset(content: &str, offset: u8) -> FastqStats {
+    let mut records = fastq_records_with_offset(content, offset);
+    
+    let mut n_reads = 0u32;
+    let mut min_len = u32::MAX;
+    let mut max_len = 0u32;
+    let mut total_bases = 0u64;
+    let mut quality_sums: Vec<u64> = Vec::new();
+    let mut quality_counts: Vec<u32> = Vec::new();
+    let (mut gc, mut acgt) = (0u64, 0u64);
+    let (mut q20, mut q30) = (0u64, 0u64);
+    
+    for record in records.by_ref() {
+        let len = record.sequence.len();
+        n_reads += 1;
+        min_len = min_len.min(len as u32);
+        max_len = max_len.max(len as u32);
+        total_bases += len as u64;
+        
+        if quality_sums.len() < len {
+            quality_sums.resize(len, 0);
+            quality_counts.resize(len, 0);
+        }
+        
+        for (i, q) in record.quality.bytes().enumerate() {
+            let q = (q - offset) as u64;
+            quality_sums[i] += q;
+            quality_counts[i] += 1;
+            q20 += (q >= 20) as u64;
+            q30 += (q >= 30) as u64;
+        }
+        
+        for base in record.sequence.bytes() {
+            match base.to_ascii_uppercase() {
+                b'G' | b'C' => {
+                    gc += 1;
+                    acgt += 1;
+                }
+                b'A' | b'T' => acgt += 1,
+                _ => {}
+            }
+        }
+    }
+    
+    let fraction = |count: u64, total: u64| if total > 0 { count as f64 / total as f64 } else { 0.0 };
+    
+    FastqStats {
+        n_reads,
+        malformed: records.malformed(),
+        min_len: if n_reads > 0 { min_len } else { 0 },
+        max_len,
+        mean_len: fraction(total_bases, n_reads as u64),
+        mean_quality_per_position: quality_sums
+            .iter()
+            .zip(quality_counts.iter())
+            .map(|(&sum, &count)| sum as f64 / count as f64)
+            .collect(),
+        gc_fraction: fraction(gc, acgt),
+        q20_fraction: fraction(q20, total_bases),
+        q30_fraction: fraction(q30, total_bases),
+    }
+}
+
+/// Phred scores of a quality string; empty if any character is below the
+/// offset or above `~`
+pub fn phred_to_scores(qual: &str, offset: u8) -> Vec<u8> {
+    if qual.bytes().any(|q| q < offset || q > b'~') {
+        return vec![];
+    }
+    qual.bytes().map(|q| q - offset).collect()
+}
+
+/// Arithmetic mean Phred score; NaN if empty or invalid
+pub fn mean_quality(qual: &str, offset: u8) -> f64 {
+    let scores = phred_to_scores(qual, offset);
+    if scores.is_empty() {
+        return f64::NAN;
+    }
+    scores.iter().map(|&q| q as f64).sum::<f64>() / scores.len() as f64
+}
+
+/// Per-base error probabilities 10^(-Q/10); empty if invalid
+pub fn error_probability(qual: &str, offset: u8) -> Vec<f64> {
+    phred_to_scores(qual, offset)
+        .into_iter()
+        .map(|q| 10f64.powf(-(q as f64) / 10.0))
+        .collect()
+}
+
+/// Guess the Phred offset of a quality string: 33 or 64
+///
+/// Characters below `;` only occur in Phred+33; strings confined to
+/// `@`..`J` are valid in both encodings and default to 33, as do
+/// strings with any character outside the Phred+64 range.
+pub fn guess_offset(qual: &str) -> u8 {
+    let (min, max) = qual.bytes().fold((u8::MAX, 0), |(lo, hi), q| (lo.min(q), hi.max(q)));
+    if min >= b'@' && max > b'J' && max <= b'h' {
+        64
+    } else {
+        33
+    }
+}
+
+/// Quality-trimmed read: bases `start..end` (0-based, end exclusive)
+pub struct TrimResult {
+    start: u32,
+    end: u32,
+    trimmed_seq: String,
+    trimmed_qual: String,
+}
+
+impl TrimResult {
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+    
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+    
+    pub fn trimmed_seq(&self) -> String {
+        self.trimmed_seq.clone()
+    }
+    
+    pub fn trimmed_qual(&self) -> String {
+        self.trimmed_qual.clone()
+    }
+}
+
+/// BWA-style quality trimming of both read ends
+///
+/// From each end, `threshold - Q` is summed base by base until the sum
+/// turns negative, and the read is cut where the sum peaked. Isolated good
+/// bases inside a poor tail are trimmed with it. A read that is poor
+/// throughout trims to empty (start = end = 0).
+pub fn quality_trim(sequence: &str, qual: &str, threshold: u8, offset: u8) -> Result<TrimResult, String> {
+    if sequence.len() != qual.len() {
+        return Err(format!(
+            "Sequence length {} does not match quality length {}",
+            sequence.len(),
+            qual.len()
+        ));
+    }
+    let scores = phred_to_scores(qual, offset);
+    if scores.len() != qual.len() {
+        return Err(format!("Quality string has characters outside Phred+{}", offset));
+    }
+    
+    // Cut point maximising the running sum of threshold - Q along `indices`
+    let cut = |indices: &mut dyn Iterator<Item = usize>| {
+        let (mut sum, mut best, mut cut) = (0i64, 0i64, None);
+        for i in indices {
+            sum += threshold as i64 - scores[i] as i64;
+            if sum < 0 {
+                break;
+            }
+            if sum > best {
+                best = sum;
+                cut = Some(i);
+            }
+        }
+        cut
+    };
+    
+    let n = scores.len();
+    let start = cut(&mut (0..n)).map_or(0, |i| i + 1);
+    let end = cut(&mut (0..n).rev()).unwrap_or(n);
+    let (start, end) = if start < end && sequence.is_char_boundary(start) && sequence.is_char_boundary(end) {
+        (start, end)
+    } else {
+        (0, 0)
+    };
+    
+    Ok(TrimResult {
+        start: start as u32,
+        end: end as u32,
+        trimmed_seq: sequence[start..end].to_string(),
+        trimmed_qual: qual[start..end].to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_gc_content() {
+        assert!((gc_content("GCGC") - 1.0).abs() < 1e-10);
+        assert!((gc_content("ATAT") - 0.0).abs() < 1e-10);
+        assert!((gc_content("ACGT") - 0.5).abs() < 1e-10);
+    }
+    
+    #[test]
+    fn test_reverse_complement() {
         assert_eq!(reverse_complement("ACGT"), "ACGT");
         assert_eq!(reverse_complement("AAAA"), "TTTT");
         assert_eq!(reverse_complement("GCTA"), "TAGC");
@@ -334,8 +2579,689 @@ mod tests {
     
     #[test]
     fn test_hamming() {
-        assert_eq!(hamming_distance("ACGT", "ACGT"), 0);
-        assert_eq!(hamming_distance("ACGT", "TGCA"), 4);
-        assert_eq!(hamming_distance("ACGT", "ACGA"), 1);
+        assert_eq!(hamming_distance("ACGT", "ACGT"), Some(0));
+        assert_eq!(hamming_distance("ACGT", "TGCA"), Some(4));
+        assert_eq!(hamming_distance("ACGT", "ACGA"), Some(1));
+        assert_eq!(hamming_distance("ACGT", "acga"), Some(1));
+        assert_eq!(hamming_distance("ACGT", "ACG"), None);
+    }
+    
+    const TWO_RECORDS: &str = ">chr1 first test contig\nACGTACGTac\ngtACGTACGT\nAC\n>chr2\nTTTGGGC\nCCAAAnn\nNA\n";
+    
+    #[test]
+    fn test_parse_fasta() {
+        let result = parse_fasta(TWO_RECORDS);
+        
+        assert_eq!(result.len(), 2);
+        assert_eq!(result.ids(), vec!["chr1", "chr2"]);
+        assert_eq!(result.descriptions(), vec!["first test contig", ""]);
+        assert_eq!(result.lengths(), vec![22, 16]);
+        assert_eq!(result.record(0).unwrap().sequence(), "ACGTACGTacgtACGTACGTAC");
+        assert_eq!(result.record(1).unwrap().sequence(), "TTTGGGCCCAAAnnNA");
+        assert!(result.record(2).is_none());
+        
+        let windows: Vec<FastaRecord> = fasta_records("ignored\r\n>a x\r\nAC\r\nG\r\n").collect();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].sequence(), "ACG");
+    }
+    
+    #[test]
+    fn test_fasta_index_fetch() {
+        let index = FastaIndex::new(TWO_RECORDS).unwrap();
+        let parsed = parse_fasta(TWO_RECORDS);
+        
+        assert_eq!(index.lengths(), parsed.lengths());
+        assert_eq!(index.fetch("chr1", 9, 14).unwrap(), "acgtAC");
+        assert_eq!(index.fetch("chr2", 7, 16).unwrap(), "CCCAAAnnNA");
+        
+        // Every range matches the parsed sequence
+        for record in parsed.iter() {
+            let n = record.length();
+            for start in 1..=n {
+                for end in start..=n {
+                    assert_eq!(
+                        index.fetch(&record.id(), start, end).unwrap(),
+                        record.sequence()[start - 1..end]
+                    );
+                }
+            }
+        }
+        
+        assert!(index.fetch("chr1", 20, 23).is_err());
+        assert!(index.fetch("chr1", 0, 3).is_err());
+        assert!(index.fetch("chr3", 1, 1).is_err());
+    }
+    
+    #[test]
+    fn test_fasta_index_fai() {
+        let index = FastaIndex::new(TWO_RECORDS).unwrap();
+        let fai = index.to_fai();
+        assert_eq!(fai, "chr1\t22\t24\t10\t11\nchr2\t16\t55\t7\t8\n");
+        
+        let from_fai = FastaIndex::from_fai(TWO_RECORDS, &fai).unwrap();
+        assert_eq!(from_fai.fetch("chr2", 1, 16).unwrap(), "TTTGGGCCCAAAnnNA");
+        
+        assert!(FastaIndex::from_fai(TWO_RECORDS, "chr1\t22\tx\t10\t11\n").is_err());
+        assert!(FastaIndex::new(">a\nACG\nACGT\n").is_err());
+        assert!(FastaIndex::new(">a\nACGT\nAC\nAC\n").is_err());
+        assert!(FastaIndex::new(">a\nAC\n>a\nAC\n").is_err());
+    }
+    
+    #[test]
+    fn test_fetch_region() {
+        assert_eq!(fetch_region(TWO_RECORDS, "chr1:11-13").unwrap(), "gtA");
+        assert_eq!(fetch_region(TWO_RECORDS, "chr2:4-7").unwrap(), "GGGC");
+        assert_eq!(fetch_region(TWO_RECORDS, "chr2:15").unwrap(), "NA");
+        assert_eq!(fetch_region(TWO_RECORDS, "chr2").unwrap(), "TTTGGGCCCAAAnnNA");
+        assert!(fetch_region(TWO_RECORDS, "chr2:10-20").is_err());
+        // Parsed by vcf_parser::Region
+        assert_eq!(fetch_region(TWO_RECORDS, "chr1:1,0-1,2").unwrap(), "cgt");
+        assert!(fetch_region(TWO_RECORDS, "chr1:0-5").is_err());
+        assert!(fetch_region(TWO_RECORDS, "chr1:9-5").is_err());
+    }
+    
+    #[test]
+    fn test_parse_fastq() {
+        // r3 has a truncated quality line, r5 is cut off at end of file
+        let content = "@r1 desc\nACGT\n+\nIIII\n@r2\nGGCCA\n+r2\n##5?I\n@r3\nACG\n+\nII\n@r4\nTT\n+\n55\n@r5\nAC\n+\n";
+        let stats = parse_fastq(content);
+        
+        assert_eq!(stats.n_reads(), 3);
+        assert_eq!(stats.malformed(), 2);
+        assert_eq!(stats.min_len(), 2);
+        assert_eq!(stats.max_len(), 5);
+        assert!((stats.mean_len() - 11.0 / 3.0).abs() < 1e-12);
+        
+        let expected = [62.0 / 3.0, 62.0 / 3.0, 30.0, 35.0, 40.0];
+        let per_position = stats.mean_quality_per_position();
+        assert_eq!(per_position.len(), expected.len());
+        for (q, e) in per_position.iter().zip(expected.iter()) {
+            assert!((q - e).abs() < 1e-12);
+        }
+        
+        assert!((stats.gc_fraction() - 6.0 / 11.0).abs() < 1e-12);
+        assert!((stats.q20_fraction() - 9.0 / 11.0).abs() < 1e-12);
+        assert!((stats.q30_fraction() - 6.0 / 11.0).abs() < 1e-12);
+        
+        let ids: Vec<&str> = fastq_records(content).map(|r| r.id).collect();
+        assert_eq!(ids, vec!["r1", "r2", "r4"]);
+    }
+    
+    #[test]
+    fn test_parse_fastq_offsets() {
+        // 'h' is Q40 in Phred+64 but '#' is below the offset
+        let content = "@a\nAC\n+\nhh\n@b\nAC\n+\n#h\n";
+        let stats = parse_fastq_with_offset(content, 64);
+        
+        assert_eq!(stats.n_reads(), 1);
+        assert_eq!(stats.malformed(), 1);
+        assert_eq!(stats.mean_quality_per_position(), vec![40.0, 40.0]);
+        
+        let empty = parse_fastq("");
+        assert_eq!(empty.n_reads(), 0);
+        assert_eq!(empty.min_len(), 0);
+    }
+    
+    #[test]
+    fn test_find_orfs_both_strands() {
+        // +3: ATGAAATAG at 3-11; -2: ATGCCCTGA on the reverse strand at 22-14
+        let sequence = "GGATGAAATAGTTTCAGGGCATA";
+        let result = find_orfs(sequence, 1, true);
+        
+        assert_eq!(result.count(), 2);
+        assert_eq!(result.frames(), vec![3, -2]);
+        assert_eq!(result.starts(), vec![3, 22]);
+        assert_eq!(result.ends(), vec![11, 14]);
+        assert_eq!(result.lengths(), vec![2, 2]);
+        assert_eq!(result.proteins(), vec!["MK", "MP"]);
+        
+        assert_eq!(find_orfs(sequence, 3, true).count(), 0);
+    }
+    
+    #[test]
+    fn test_find_orfs_nested_and_open() {
+        let nested = "ATGATGAAATAA";
+        let longest = find_orfs(nested, 1, true);
+        assert_eq!(longest.starts(), vec![1]);
+        assert_eq!(longest.proteins(), vec!["MMK"]);
+        
+        let all = find_orfs_with_options(nested, 1, true, true);
+        assert_eq!(all.starts(), vec![1, 4]);
+        assert_eq!(all.ends(), vec![12, 12]);
+        
+        // No stop codon: only reported when a stop is not required
+        assert_eq!(find_orfs("ATGAAACCCG", 1, true).count(), 0);
+        let open = find_orfs("ATGAAACCCG", 1, false);
+        assert_eq!(open.ends(), vec![9]);
+        assert_eq!(open.proteins(), vec!["MKP"]);
+    }
+    
+    #[test]
+    fn test_translate_frames() {
+        // DNA and RNA input translate identically
+        assert_eq!(translate("ATGGCCTAA"), "MA*");
+        assert_eq!(translate_frame("AUGGCCUAA", 1, 1).unwrap(), "MA*");
+        assert_eq!(translate_frame("CATGGCCTAAG", 2, 1).unwrap(), "MA*");
+        assert_eq!(translate_frame("TTAGGCCAT", -1, 1).unwrap(), "MA*");
+        assert_eq!(translate_frame("UUAGGCCAUG", -2, 1).unwrap(), "MA*");
+        
+        let frames = translate_six_frames("ATGGCCTAA", 1).unwrap();
+        assert_eq!(frames, vec!["MA*", "WP", "GL", "LGH", "*A", "RP"]);
+        
+        assert!(translate_frame("ATG", 0, 1).is_err());
+        assert!(translate_frame("ATG", 4, 1).is_err());
+        assert!(translate_frame("ATG", 1, 99).is_err());
+    }
+    
+    #[test]
+    fn test_mitochondrial_code() {
+        // AGA/AGG are stops and UGA is Trp in vertebrate mitochondria
+        assert_eq!(translate_frame("AGAAGGUGAAUA", 1, 1).unwrap(), "RR*I");
+        assert_eq!(translate_frame("AGAAGGUGAAUA", 1, 2).unwrap(), "**WM");
+        assert_eq!(translate_frame("AGAAGGTGAATA", 1, 11).unwrap(), "RR*I");
+        assert_eq!(translate_frame("CTGTGA", 1, 3).unwrap(), "TW");
+    }
+    
+    #[test]
+    fn test_local_align() {
+        // Scoring: +5 / -4, gap of length L costs 10 + (L - 1). That is
+        // EDNAFULL on ACGT with EMBOSS's gap model (-gapopen 10 -gapextend 1);
+        // the expected scores are worked out by hand, not copied from water.
+        let ungapped = local_align("GGGACGTACGTCCC", "TTACGTACGTAA", 5, -4, 10, 1);
+        assert!(ungapped.error().is_none());
+        assert_eq!(ungapped.score(), 40);
+        assert_eq!((ungapped.start1(), ungapped.end1()), (4, 11));
+        assert_eq!((ungapped.start2(), ungapped.end2()), (3, 10));
+        assert_eq!(ungapped.cigar(), "8M");
+        assert!((ungapped.identity() - 100.0).abs() < 1e-12);
+        
+        let gapped = local_align("ACGTACGTAAGCTAGCTA", "ACGTACGTGCTAGCTA", 5, -4, 10, 1);
+        assert_eq!(gapped.score(), 69);
+        assert_eq!(gapped.aligned1(), "ACGTACGTAAGCTAGCTA");
+        assert_eq!(gapped.aligned2(), "ACGTACGT--GCTAGCTA");
+        assert_eq!(gapped.cigar(), "8M2D8M");
+        assert!((gapped.identity() - 1600.0 / 18.0).abs() < 1e-12);
+        
+        let unrelated = local_align("AAAA", "CCCC", 5, -4, 10, 1);
+        assert_eq!(unrelated.score(), 0);
+        assert_eq!(unrelated.cigar(), "");
+        assert_eq!(unrelated.start1(), 0);
+    }
+    
+    #[test]
+    fn test_global_align() {
+        let result = global_align("GGACGTACGT", "acgtacgt", 5, -4, 10, 1);
+        assert_eq!(result.score(), 29);
+        assert_eq!(result.aligned2(), "--acgtacgt");
+        assert_eq!(result.cigar(), "2D8M");
+        assert_eq!((result.start1(), result.end1()), (1, 10));
+        assert_eq!((result.start2(), result.end2()), (1, 8));
+        
+        let insertion = global_align("ACGTGCTA", "ACGTCCGCTA", 5, -4, 10, 1);
+        assert_eq!(insertion.cigar(), "4M2I4M");
+        assert_eq!(insertion.score(), 40 - 11);
+        
+        // Linear gaps match the score-only path
+        assert_eq!(alignment_score("GATTACA", "GCATGCU", 1, -1, -1), 0);
+        assert_eq!(global_align("GATTACA", "GCATGCU", 1, -1, 1, 1).score(), 0);
+        assert_eq!(alignment_score("", "ACG", 1, -1, -2), -6);
+        
+        let too_big = global_align_with_limit("ACGT", "ACGT", 1, -1, 1, 1, 10);
+        assert!(too_big.error().is_some());
+        assert!(too_big.identity().is_nan());
+    }
+    
+    #[test]
+    fn test_global_align_free_end_gaps() {
+        // Same scoring as above; the leading 2 bp gap is no longer charged
+        let leading = global_align_with_options("GGACGTACGT", "acgtacgt", 5, -4, 10, 1, false);
+        assert_eq!(leading.score(), 40);
+        assert_eq!(leading.aligned2(), "--acgtacgt");
+        assert_eq!(leading.cigar(), "2D8M");
+        assert_eq!((leading.start1(), leading.end1()), (1, 10));
+        assert_eq!((leading.start2(), leading.end2()), (1, 8));
+        assert!((leading.identity() - 80.0).abs() < 1e-12);
+        
+        let trailing = global_align_with_options("ACGTACGTGG", "ACGTACGT", 5, -4, 10, 1, false);
+        assert_eq!(trailing.score(), 40);
+        assert_eq!(trailing.aligned2(), "ACGTACGT--");
+        assert_eq!(trailing.cigar(), "8M2D");
+        
+        let longer2 = global_align_with_options("ACGTACGT", "TTACGTACGTA", 5, -4, 10, 1, false);
+        assert_eq!(longer2.score(), 40);
+        assert_eq!(longer2.aligned1(), "--ACGTACGT-");
+        assert_eq!(longer2.cigar(), "2I8M1I");
+        
+        // Internal gaps are still charged
+        let insertion = global_align_with_options("ACGTGCTA", "ACGTCCGCTA", 5, -4, 10, 1, false);
+        assert_eq!(insertion.cigar(), "4M2I4M");
+        assert_eq!(insertion.score(), 40 - 11);
+        
+        let penalized = global_align_with_options("GGACGTACGT", "acgtacgt", 5, -4, 10, 1, true);
+        assert_eq!(penalized.score(), 29);
+        assert_eq!(global_align_with_options("", "ACG", 1, -1, 2, 2, false).cigar(), "3I");
+    }
+    
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("ACGT", "acgt"), 0);
+        assert_eq!(levenshtein("", "ACG"), 3);
+        assert_eq!(levenshtein_banded("kitten", "sitting", 3), Some(3));
+        assert_eq!(levenshtein_banded("kitten", "sitting", 2), None);
+        assert_eq!(levenshtein_banded("ACGTACGT", "ACG", 4), None);
+        assert!((sequence_identity("ACGTACGTAC", "ACGTTCGTAC") - 0.9).abs() < 1e-12);
+        assert!((sequence_identity("", "") - 1.0).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_levenshtein_banded_matches_full() {
+        let mut state = 7u64;
+        let mut random_sequence = |max_len: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let len = (state >> 33) % max_len;
+            (0..len)
+                .map(|_| {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    b"ACGT"[(state >> 62) as usize] as char
+                })
+                .collect::<String>()
+        };
+        
+        for _ in 0..300 {
+            let a = random_sequence(16);
+            let b = random_sequence(16);
+            let full = levenshtein(&a, &b);
+            
+            for k in 0..12 {
+                let expected = (full <= k).then_some(full);
+                assert_eq!(levenshtein_banded(&a, &b, k), expected, "{} vs {} (k = {})", a, b, k);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_find_pattern_overlaps() {
+        assert_eq!(find_pattern("AAAA", "AAA"), vec![0, 1]);
+        assert_eq!(find_pattern("acgACG", "ACG"), vec![0, 3]);
+        assert_eq!(find_pattern_iupac("AAAA", "AAA"), vec![0, 1]);
+        assert_eq!(find_pattern_iupac("ATATAT", "WTA"), vec![0, 2]);
+    }
+    
+    #[test]
+    fn test_find_pattern_iupac() {
+        // XmnI-like site GAANNNNTTC
+        let text = "CCGAACGTATTCGGAAttttTTCA";
+        assert_eq!(find_pattern_iupac(text, "GAANNNNTTC"), vec![2, 13]);
+        assert_eq!(find_pattern_iupac("ACGTGT", "RY"), vec![0, 2, 4]);
+        assert!(find_pattern_iupac(text, "GAAX").is_empty());
+        
+        // Ambiguous text only matches when asked to
+        assert!(find_pattern_iupac("GANTC", "GAATC").is_empty());
+        assert_eq!(find_motif("GANTC", "GAATC", false, true).positions(), vec![0]);
+        assert!(find_motif("GARTC", "GAYTC", false, true).positions().is_empty());
+    }
+    
+    #[test]
+    fn test_find_motif_both_strands() {
+        // ACCT forward at 0, its reverse complement AGGT at 6; GAATTC is palindromic
+        let hits = find_motif("ACCTGAAGGTGAATTC", "ACCT", true, false);
+        assert_eq!(hits.positions(), vec![0, 6]);
+        assert_eq!(hits.strands(), vec![1, -1]);
+        
+        let palindrome = find_motif("ACCTGAAGGTGAATTC", "GAATTC", true, false);
+        assert_eq!(palindrome.positions(), vec![10, 10]);
+        assert_eq!(palindrome.strands(), vec![1, -1]);
+    }
+    
+    fn random_dna(len: usize, seed: u64) -> String {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                b"ACGT"[(state >> 62) as usize] as char
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_count_kmers_canonical() {
+        let result = count_kmers_canonical("ACGT", 2);
+        assert_eq!(result.kmers(), vec!["AC", "CG"]);
+        assert_eq!(result.counts(), vec![2, 1]);
+        assert_eq!(result.total(), 3);
+        
+        // Reverse complements share canonical k-mers; N breaks windows
+        let a = count_kmers_canonical("GGATCCAANTTG", 3);
+        let b = count_kmers_canonical(&reverse_complement("GGATCCAANTTG"), 3);
+        assert_eq!(a.kmers(), b.kmers());
+        assert_eq!(a.counts(), b.counts());
+        assert_eq!(a.total(), 7);
+        
+        assert_eq!(count_kmers_packed("ACGT", 33, false).total(), 0);
+        assert_eq!(count_kmers_packed(&"ACGT".repeat(10), 32, true).total(), 9);
+    }
+    
+    #[test]
+    fn test_count_kmers_packed_matches_strings() {
+        // Speed is measured by the `count_kmers` criterion benchmark
+        let sequence = random_dna(1_000_000, 11);
+        let packed = count_kmers_packed(&sequence, 8, true);
+        
+        let short = &sequence[..20_000];
+        let strings = count_kmers_detailed(short, 6);
+        let forward = count_kmers_packed(short, 6, false);
+        assert_eq!(forward.total(), strings.total());
+        assert_eq!(forward.unique_count(), strings.unique_count());
+        
+        let mut by_string: Vec<(String, u32)> = strings.kmers().into_iter().zip(strings.counts()).collect();
+        let mut by_code: Vec<(String, u32)> = forward.kmers().into_iter().zip(forward.counts()).collect();
+        by_string.sort();
+        by_code.sort();
+        assert_eq!(by_string, by_code);
+        
+        assert_eq!(packed.total() as usize, sequence.len() - 7);
+        assert_eq!(packed.counts().iter().sum::<u32>(), packed.total());
+    }
+    
+    #[test]
+    fn test_kmer_distance() {
+        let metrics = ["cosine", "jensen-shannon", "jaccard"];
+        let sequence = "ACGTTGCAAGGCTTACGATCGATCGGATCCATGCA";
+        let profile = kmer_profile(sequence, 4);
+        let reverse = kmer_profile(&reverse_complement(sequence), 4);
+        
+        for metric in metrics {
+            assert!(kmer_distance(&profile, &profile, metric).abs() < 1e-12);
+            assert!(kmer_distance(&profile, &reverse, metric).abs() < 1e-12);
+            
+            // Disjoint spectra are maximally distant
+            assert!((compare_sequences_kmer("AAAAAA", "CCCCCC", 2, metric) - 1.0).abs() < 1e-12);
+            
+            assert!(compare_sequences_kmer("ACG", sequence, 4, metric).is_nan());
+            assert!(kmer_distance(&profile, &kmer_profile(sequence, 3), metric).is_nan());
+        }
+        assert!(kmer_distance(&profile, &profile, "euclidean").is_nan());
+        
+        // AA/TT share a canonical k-mer, AC does not: {AA: 1/2, AC: 1/2} vs {AA: 1}
+        let mixed = compare_sequences_kmer("AAC", "TTT", 2, "jaccard");
+        assert!((mixed - 0.5).abs() < 1e-12);
+        let cosine = compare_sequences_kmer("AAC", "TTT", 2, "cosine");
+        assert!((cosine - (1.0 - 0.5f64.sqrt())).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_kmer_distance_unrelated() {
+        let original = random_dna(500, 5);
+        let unrelated = random_dna(500, 6);
+        
+        // Point mutation every 50 bases
+        let mutated: String = original
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i % 50 == 25 { if c == 'A' { 'C' } else { 'A' } } else { c })
+            .collect();
+        
+        let a = kmer_profile(&original, 6);
+        for metric in ["cosine", "jensen-shannon", "jaccard"] {
+            let near = kmer_distance(&a, &kmer_profile(&mutated, 6), metric);
+            let far = kmer_distance(&a, &kmer_profile(&unrelated, 6), metric);
+            assert!(near > 0.0 && near < far);
+            assert!(far > 0.5 && far <= 1.0);
+        }
+    }
+    
+    #[test]
+    fn test_cpg_islands() {
+        // 300 bp AT-only flanks around a 400 bp CpG-rich core at 301-700
+        let flank = "AATTTATAAT".repeat(30);
+        let core = "CGAGCGTC".repeat(50);
+        let sequence = format!("{}{}{}", flank, core, flank);
+        assert_eq!(sequence.len(), 1000);
+        
+        let islands = find_cpg_islands(&sequence, 200, 0.5, 0.6);
+        assert_eq!(islands.len(), 1);
+        
+        // Trimmed to the first and last CpG of the core, found here from
+        // the layout rather than by the scan
+        let first_cpg = flank.len() + core.find("CG").unwrap() + 1;
+        let last_cpg_end = flank.len() + core.rfind("CG").unwrap() + 2;
+        assert_eq!((first_cpg, last_cpg_end), (301, 698));
+        let island = &islands[0];
+        assert_eq!((island.start as usize, island.end as usize), (first_cpg, last_cpg_end));
+        assert_eq!(island.length(), 398);
+        assert!(island.gc_content > 0.74 && island.oe_ratio > 1.7);
+        
+        // Too short for a stricter minimum length
+        assert!(find_cpg_islands(&sequence, 500, 0.5, 0.6).is_empty());
+        
+        let result = cpg_islands(&sequence.to_lowercase(), 200, 0.5, 0.6);
+        assert_eq!(result.starts(), vec![301]);
+        assert_eq!(result.ends(), vec![698]);
+    }
+    
+    #[test]
+    fn test_cpg_islands_ignore_n() {
+        // Masked bases neither dilute nor create islands
+        let masked = "N".repeat(1000);
+        assert!(find_cpg_islands(&masked, 200, 0.5, 0.6).is_empty());
+        
+        let core = "CGAGCGTC".repeat(50);
+        let sequence = format!("{}{}{}", "N".repeat(150), core, "N".repeat(150));
+        let islands = find_cpg_islands(&sequence, 200, 0.5, 0.6);
+        assert_eq!(islands.len(), 1);
+        assert_eq!((islands[0].start, islands[0].end), (151, 548));
+    }
+    
+    #[test]
+    fn test_gc_content_windows_positions() {
+        // 11 bases, window 4, step 3: full windows at 0, 3, 6 and a partial one at 9..11
+        let result = gc_content_windows_positions("GGGGAAAACCA", 4, 3);
+        assert_eq!(result.starts(), vec![0, 3, 6, 9]);
+        assert_eq!(result.ends(), vec![4, 7, 10, 11]);
+        assert_eq!(result.values(), vec![1.0, 0.25, 0.5, 0.5]);
+        
+        // Values of full windows agree with gc_content_windows
+        let full = gc_content_windows("GGGGAAAACCA", 4, 3);
+        assert_eq!(result.values()[..3], full[..]);
+        
+        // Exact fit adds no trailing window
+        let exact = gc_content_windows_positions("GGGGAAAA", 4, 4);
+        assert_eq!(exact.starts(), vec![0, 4]);
+        
+        let short = gc_content_windows_positions("GCA", 10, 5);
+        assert_eq!((short.starts(), short.ends()), (vec![0], vec![3]));
+        assert!(gc_content_windows_positions("", 10, 5).is_empty());
+    }
+    
+    #[test]
+    fn test_gc_skew_windows() {
+        let skew = gc_skew_windows("GGGCAAAAGCCC", 4, 4);
+        assert_eq!(skew, vec![0.5, 0.0, -0.5]);
+        assert!(gc_skew_windows("GG", 4, 1).is_empty());
+    }
+    
+    #[test]
+    fn test_cumulative_gc_skew_origin() {
+        // Synthetic replichore layout: C-rich, G-rich, C-rich. Cumulative
+        // skew falls until the origin at 3000 and peaks at the terminus at 9000.
+        let c_rich = "ACCGT".repeat(600);
+        let g_rich = "ACGGT".repeat(1200);
+        let sequence = format!("{}{}{}", c_rich, g_rich, c_rich);
+        
+        let result = gc_skew_analysis(&sequence, 500);
+        assert_eq!(result.cumulative().len(), 24);
+        assert_eq!(result.min_index(), 5);
+        assert_eq!(result.origin(), 3000);
+        assert_eq!(result.terminus(), 9000);
+        
+        assert_eq!(cumulative_gc_skew(&sequence, 500), result.cumulative());
+        assert!((result.cumulative()[5] + 2.0).abs() < 1e-9);
+        
+        let empty = gc_skew_analysis("", 500);
+        assert_eq!((empty.origin(), empty.terminus()), (0, 0));
+    }
+    
+    #[test]
+    fn test_melting_temperature_nn() {
+        // primer3-py's documented example, primer3.calc_tm('GTAAAACGACGGCCAGT')
+        // with its defaults: SantaLucia Tm and salt correction, 50 mM
+        // monovalent, no divalent cations, 50 nM oligo
+        let tm = melting_temperature("GTAAAACGACGGCCAGT", "nn", 50.0, 50.0);
+        assert!((tm - 49.168_082_289_117_65).abs() < 1e-9, "{}", tm);
+        assert_eq!(melting_temperature("gtaaaacgacggccagt", "nn", 50.0, 50.0), tm);
+        
+        // Higher salt and oligo concentration stabilise the duplex
+        assert!(melting_temperature("GTAAAACGACGGCCAGT", "nn", 200.0, 50.0) > tm);
+        assert!(melting_temperature("GTAAAACGACGGCCAGT", "nn", 50.0, 250.0) > tm);
+        assert!(melting_temperature("GTAAAACGACGGCCAGT", "nn", 0.0, 50.0).is_nan());
+    }
+    
+    #[test]
+    fn test_melting_temperature_methods() {
+        assert_eq!(melting_temperature("ACGTACGT", "wallace", 50.0, 50.0), 24.0);
+        assert_eq!(melting_temperature("ACGTACGT", "auto", 50.0, 50.0), 24.0);
+        assert_eq!(
+            melting_temperature("GTAAAACGACGGCCAGT", "auto", 50.0, 50.0),
+            melting_temperature("GTAAAACGACGGCCAGT", "nn", 50.0, 50.0)
+        );
+        
+        assert!(melting_temperature("ACGTNACGT", "nn", 50.0, 50.0).is_nan());
+        assert!(melting_temperature("ACGTRACGT", "wallace", 50.0, 50.0).is_nan());
+        assert!(melting_temperature("", "wallace", 50.0, 50.0).is_nan());
+        assert!(melting_temperature("ACGT", "marmur", 50.0, 50.0).is_nan());
+    }
+    
+    #[test]
+    fn test_oligo_properties() {
+        let result = oligo_properties("GTAAAACGACGGCCAGT");
+        assert_eq!(result.length(), 17);
+        assert!((result.gc() - 9.0 / 17.0).abs() < 1e-12);
+        assert_eq!(result.tm_wallace(), 52.0);
+        assert!((result.tm_nn() - 49.168_082_289_117_65).abs() < 1e-9);
+        assert!((result.molecular_weight() - 5_228.47).abs() < 1e-6);
+        assert!(!result.has_ambiguous());
+        
+        // Stem GCGCAG / CTGCGC around a TTTT loop
+        assert!(oligo_properties("GCGCAGTTTTCTGCGC").has_hairpin_risk());
+        assert!(!oligo_properties("AAAAAAAAAACCCCCCCCCC").has_hairpin_risk());
+        
+        let ambiguous = oligo_properties("ACGTNNACGT");
+        assert!(ambiguous.has_ambiguous());
+        assert!(ambiguous.tm_nn().is_nan() && ambiguous.molecular_weight().is_nan());
+        assert_eq!(ambiguous.length(), 10);
+    }
+    
+    #[test]
+    fn test_assembly_stats() {
+        // Worked example: total 32, 8 + 8 reaches half, 8 + 8 + 4 + 3 + 3 + 2 + 2 reaches 90%
+        let stats = assembly_stats(&[2.0, 3.0, 4.0, 8.0, 2.0, 3.0, 8.0, 2.0]);
+        assert_eq!(stats.n_contigs(), 8);
+        assert_eq!(stats.total_length(), 32.0);
+        assert_eq!((stats.min(), stats.max(), stats.mean()), (2.0, 8.0, 4.0));
+        assert_eq!((stats.n50(), stats.l50()), (8.0, 2));
+        assert_eq!((stats.n90(), stats.l90()), (2.0, 7));
+        assert_eq!(stats.gc(), None);
+        
+        // Single contig
+        let single = assembly_stats(&[5000.0]);
+        assert_eq!((single.n50(), single.max(), single.total_length()), (5000.0, 5000.0, 5000.0));
+        assert_eq!(single.l50(), 1);
+        
+        let empty = assembly_stats(&[]);
+        assert_eq!((empty.n50(), empty.mean(), empty.min(), empty.l50()), (0.0, 0.0, 0.0, 0));
+    }
+    
+    #[test]
+    fn test_assembly_stats_from_fasta() {
+        let fasta = ">ctg1\nGGGGCC\nAA\n>ctg2\nATNN\n>ctg3\nCG\n";
+        let stats = assembly_stats_from_fasta(fasta);
+        assert_eq!(stats.n_contigs(), 3);
+        assert_eq!(stats.total_length(), 14.0);
+        assert_eq!((stats.n50(), stats.l50()), (8.0, 1));
+        
+        // 8 G/C among 12 ACGT bases; N is excluded
+        assert!((stats.gc().unwrap() - 8.0 / 12.0).abs() < 1e-12);
+        
+        assert_eq!(assembly_stats_from_fasta("").gc(), None);
+    }
+    
+    #[test]
+    fn test_base_frequency_matrix() {
+        // Column 2 is a variant column: 2 A, 2 G, 1 C
+        let reads: Vec<String> = ["ACATG", "ACGTG", "ACATG", "ACGT-", "ACCTN"].iter().map(|s| s.to_string()).collect();
+        
+        let matrix = base_frequency_matrix(reads.clone());
+        assert_eq!((matrix.rows(), matrix.cols()), (5, 5));
+        assert_eq!(matrix.row(0), vec![1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(matrix.row(2), vec![0.4, 0.2, 0.4, 0.0, 0.0]);
+        
+        // Gap excluded from the total, N counted as other
+        assert_eq!(matrix.row(4), vec![0.0, 0.0, 0.75, 0.0, 0.25]);
+        
+        let with_gaps = base_frequency_matrix_with_gaps(reads, true);
+        assert_eq!(with_gaps.cols(), 6);
+        assert_eq!(with_gaps.row(4), vec![0.0, 0.0, 0.6, 0.0, 0.2, 0.2]);
+        
+        let mismatched = base_frequency_matrix(vec!["ACGT".into(), "ACG".into()]);
+        assert_eq!((mismatched.rows(), mismatched.cols()), (0, 0));
+        assert_eq!(base_frequency_matrix(vec![]).rows(), 0);
+    }
+    
+    #[test]
+    fn test_consensus_sequence() {
+        let reads: Vec<String> = ["ACATG", "ACGTG", "ACATG", "ACGT-", "ACCTN"].iter().map(|s| s.to_string()).collect();
+        
+        // A/G tie at 0.4 each gives R; the last column reaches 0.6
+        assert_eq!(consensus_sequence(reads.clone(), 0.4), "ACRTG");
+        assert_eq!(consensus_sequence(reads.clone(), 0.5), "ACNTG");
+        assert_eq!(consensus_sequence(reads.clone(), 0.7), "ACNTN");
+        
+        assert_eq!(consensus_sequence(vec!["ACGT".into(), "ACG".into()], 0.5), "");
+        assert_eq!(consensus_sequence(vec!["--".into()], 0.0), "NN");
+    }
+    
+    #[test]
+    fn test_phred_scores() {
+        assert_eq!(phred_to_scores("!+5I", 33), vec![0, 10, 20, 40]);
+        assert_eq!(phred_to_scores("@Jh", 64), vec![0, 10, 40]);
+        assert!(phred_to_scores("#h", 64).is_empty());
+        
+        assert!((mean_quality("+5", 33) - 15.0).abs() < 1e-12);
+        assert!(mean_quality("", 33).is_nan());
+        
+        let p = error_probability("+5?", 33);
+        assert!((p[0] - 0.1).abs() < 1e-12 && (p[1] - 0.01).abs() < 1e-12 && (p[2] - 0.001).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_guess_offset() {
+        assert_eq!(guess_offset("IIIIHHGG#!"), 33);
+        assert_eq!(guess_offset("hhhhggfP@B"), 64);
+        // Valid in both; modern data is Phred+33
+        assert_eq!(guess_offset("@ABCDEFGHIJ"), 33);
+        assert_eq!(guess_offset(""), 33);
+    }
+    
+    #[test]
+    fn test_quality_trim() {
+        // Q2 tails around a Q40 core, with a lone Q30 base inside the 3' tail
+        let sequence = "NNACGTACGTACGTNAN";
+        let qual = "##IIIIIIIIIIII#?#";
+        let result = quality_trim(sequence, qual, 20, 33).unwrap();
+        assert_eq!((result.start(), result.end()), (2, 14));
+        assert_eq!(result.trimmed_seq(), "ACGTACGTACGT");
+        assert_eq!(result.trimmed_qual(), "IIIIIIIIIIII");
+        
+        // Nothing to trim
+        let clean = quality_trim("ACGT", "IIII", 20, 33).unwrap();
+        assert_eq!((clean.start(), clean.end()), (0, 4));
+        
+        // Poor throughout
+        let poor = quality_trim("ACGT", "####", 20, 33).unwrap();
+        assert_eq!((poor.start(), poor.end(), poor.trimmed_seq()), (0, 0, String::new()));
+        
+        assert!(quality_trim("ACGT", "III", 20, 33).is_err());
+        assert!(quality_trim("ACGT", "II#!", 20, 64).is_err());
     }
 }